@@ -0,0 +1,38 @@
+//! Laying seeded boards from 10,000 to 4,000,000 cells at 15% mines, to
+//! find where spreading the work over threads starts to pay: run it with
+//! and without the `parallel` feature and compare the rows.
+//!
+//! `cargo bench --bench generation [--features parallel]`
+
+use minesweeper_rs::Board;
+use std::time::{Duration, Instant};
+
+/// Square boards measured, by side.
+const SIDES: [usize; 7] = [100, 200, 300, 400, 700, 1000, 2000];
+
+/// Boards laid at each size, each from its own seed.
+const ROUNDS: u64 = 10;
+
+fn main() {
+    let parallel = if cfg!(feature = "parallel") {
+        "on"
+    } else {
+        "off"
+    };
+    println!("parallel feature {parallel}");
+    for side in SIDES {
+        let mines = side * side * 15 / 100;
+        let mut total = Duration::ZERO;
+        for seed in 0..ROUNDS {
+            let started = Instant::now();
+            let board = Board::with_seed(side, side, mines, seed, Some((side / 2, side / 2)));
+            total += started.elapsed();
+            std::hint::black_box(board);
+        }
+        println!(
+            "{side:>4}x{side:<4} {:>9} cells: {:>10.2?} per board",
+            side * side,
+            total / ROUNDS as u32
+        );
+    }
+}
@@ -0,0 +1,115 @@
+//! Counted analyses of Expert midgames after a one-cell change: counting
+//! every group from scratch against `Solver::analyze_incremental`, which
+//! keeps the tallies of the groups the change didn't touch.
+//!
+//! `cargo bench --bench incremental`
+
+use minesweeper_rs::solver::{self, DirtyInfo, Solver};
+use minesweeper_rs::{Board, Difficulty};
+use std::time::{Duration, Instant};
+
+/// Positions measured, and one-cell changes tried on each.
+const POSITIONS: u64 = 12;
+const CHANGES: usize = 8;
+
+/// Seeded Expert boards played by deduction, guessing the lowest flat
+/// chance when stuck, until a third of the safe cells are open and
+/// nothing more can be deduced: where a player looks at the chances.
+fn midgames() -> impl Iterator<Item = Board> {
+    let (width, height, mines) = Difficulty::Expert.spec().params();
+    let safe = width * height - mines;
+    (0..).filter_map(move |seed| {
+        let start = (width / 2, height / 2);
+        let mut board = Board::with_seed(width, height, mines, seed, Some(start));
+        board.reveal_cell(start.0, start.1);
+        loop {
+            if board.is_over() {
+                return None;
+            }
+            let analysis = solver::analyze(&board);
+            match analysis.safe().first() {
+                Some(&(x, y)) => board.reveal_cell(x, y),
+                None if board.revealed_safe() >= safe / 3 => break,
+                None => {
+                    let (x, y) = hidden(&board)
+                        .into_iter()
+                        .filter(|&(x, y)| analysis.probability(x, y) < 1.0)
+                        .min_by(|&(ax, ay), &(bx, by)| {
+                            let chance = analysis.probability(ax, ay);
+                            chance.total_cmp(&analysis.probability(bx, by))
+                        })?;
+                    board.reveal_cell(x, y)
+                }
+            };
+        }
+        Some(board)
+    })
+}
+
+fn hidden(board: &Board) -> Vec<(usize, usize)> {
+    (0..board.height())
+        .flat_map(|y| (0..board.width()).map(move |x| (x, y)))
+        .filter(|&(x, y)| !board.cell(x, y).is_revealed())
+        .collect()
+}
+
+/// Safe hidden cells whose reveal opens just themselves, next to the
+/// numbers so they change a group.
+fn one_cell_reveals(board: &Board) -> Vec<(usize, usize)> {
+    let mines: Vec<bool> = board
+        .encode_layout()
+        .bytes()
+        .filter(|&b| b != b'\n')
+        .map(|b| b == b'*')
+        .collect();
+    hidden(board)
+        .into_iter()
+        .filter(|&(x, y)| !mines[y * board.width() + x])
+        .filter(|&(x, y)| {
+            board
+                .neighbors(x, y)
+                .any(|(nx, ny)| board.cell(nx, ny).is_revealed())
+        })
+        .filter(|&(x, y)| {
+            let mut next = board.clone();
+            next.reveal_cell(x, y);
+            next.revealed_safe() == board.revealed_safe() + 1
+        })
+        .take(CHANGES)
+        .collect()
+}
+
+fn main() {
+    let (mut full, mut incremental) = (Duration::ZERO, Duration::ZERO);
+    let (mut counted, mut reused, mut changes) = (0, 0, 0);
+    for mut board in midgames().take(POSITIONS as usize) {
+        for (x, y) in one_cell_reveals(&board) {
+            let mut solver = Solver::new();
+            solver.analyze(&board);
+            board.take_dirty();
+            let mut next = board.clone();
+            next.reveal_cell(x, y);
+            let dirty = DirtyInfo::cells(next.take_dirty());
+
+            let started = Instant::now();
+            let fresh = Solver::new().analyze(&next);
+            full += started.elapsed();
+            let started = Instant::now();
+            let kept = solver.analyze_incremental(&next, &dirty);
+            incremental += started.elapsed();
+
+            assert_eq!(fresh, kept);
+            counted += solver.stats().counted;
+            reused += solver.stats().reused;
+            changes += 1;
+        }
+    }
+    let per = |total: Duration| total / changes.max(1) as u32;
+    println!("{changes} one-cell reveals on {POSITIONS} Expert midgames");
+    println!("from scratch: {:>10.2?} per analysis", per(full));
+    println!("incremental:  {:>10.2?} per analysis", per(incremental));
+    println!(
+        "speedup:      {:>10.1}x, {counted} groups counted and {reused} kept",
+        full.as_secs_f64() / incremental.as_secs_f64()
+    );
+}
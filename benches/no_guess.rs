@@ -0,0 +1,41 @@
+//! No-guess generation of Expert boards (30x16, 99 mines) from a centre
+//! start, on one to eight worker threads, to show what checking candidates
+//! in parallel saves. Every row finds the same boards; rows past the
+//! machine's core count only show the cost of idle threads.
+//!
+//! `cargo bench --bench no_guess`
+
+use minesweeper_rs::{generate, BoardOptions};
+use std::sync::atomic::AtomicBool;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Boards generated for each thread count, each from its own base seed.
+const ROUNDS: u64 = 10;
+
+fn main() {
+    let cores = thread::available_parallelism().map_or(1, |n| n.get());
+    println!("{cores} core(s)");
+    let options = BoardOptions::new(30, 16, 99);
+    let abort = AtomicBool::new(false);
+    let mut serial = Duration::ZERO;
+    for workers in [1, 2, 4, 8] {
+        let mut total = Duration::ZERO;
+        for seed in 0..ROUNDS {
+            let started = Instant::now();
+            let found =
+                generate::no_guess_on(workers, &options, (15, 8), seed, &abort, &|_| {}).unwrap();
+            total += started.elapsed();
+            assert!(found.is_some(), "seed {seed} found no board");
+            std::hint::black_box(found);
+        }
+        if workers == 1 {
+            serial = total;
+        }
+        println!(
+            "{workers} thread(s): {:>10.2?} per board, {:.1}x",
+            total / ROUNDS as u32,
+            serial.as_secs_f64() / total.as_secs_f64()
+        );
+    }
+}
@@ -0,0 +1,108 @@
+//! A 2000x2000 board with 15% mines played to the end on the bare core, to
+//! check large boards stay usable: how long laying it and the first
+//! opening take, what reading one screenful of cells costs a frame (all
+//! the board widget visits once it culls to the viewport) against a 60 fps
+//! budget, that every reveal after that stays cheap, and the peak memory
+//! (Linux only) against 100 MB. `cargo run --release -- --stress` opens a
+//! board that size in the GUI.
+//!
+//! cargo run --release --example stress --no-default-features
+
+use minesweeper_rs::Board;
+use std::time::{Duration, Instant};
+
+const SIDE: usize = 2000;
+
+/// Cells across and down a full-HD window at the board widget's smallest
+/// zoom (7 px cells), the most it paints in one frame.
+const VIEW: (usize, usize) = (280, 160);
+
+/// Frames read while scrolling diagonally across the board.
+const FRAMES: usize = 600;
+
+/// One frame at 60 fps.
+const FRAME: Duration = Duration::from_micros(16_667);
+
+fn main() {
+    let mines = SIDE * SIDE * 15 / 100;
+    let start = (SIDE / 2, SIDE / 2);
+
+    let started = Instant::now();
+    let mut board = Board::with_seed(SIDE, SIDE, mines, 1, Some(start));
+    println!(
+        "laid {SIDE}x{SIDE} with {mines} mines in {:.2?}",
+        started.elapsed()
+    );
+
+    let started = Instant::now();
+    board.reveal_cell(start.0, start.1);
+    println!(
+        "first click opened {} cells in {:.2?}",
+        board.revealed_safe(),
+        started.elapsed()
+    );
+
+    let started = Instant::now();
+    let mut slowest = Duration::ZERO;
+    for frame in 0..FRAMES {
+        let left = frame * (SIDE - VIEW.0) / FRAMES;
+        let top = frame * (SIDE - VIEW.1) / FRAMES;
+        let shown = Instant::now();
+        let mut open = 0;
+        for y in top..top + VIEW.1 {
+            for x in left..left + VIEW.0 {
+                open += usize::from(board.cell(x, y).is_revealed());
+            }
+        }
+        std::hint::black_box(open);
+        slowest = slowest.max(shown.elapsed());
+    }
+    println!(
+        "one screen of cells: {:.2?} a frame, {slowest:.2?} at worst ({})",
+        started.elapsed() / FRAMES as u32,
+        verdict(slowest <= FRAME)
+    );
+
+    // every safe cell in turn; most are already open and come back ignored
+    let layout = board.encode_layout();
+    let started = Instant::now();
+    let mut moves = 0u32;
+    for (y, row) in layout.lines().enumerate() {
+        for (x, c) in row.bytes().enumerate() {
+            if c != b'*' {
+                board.reveal_cell(x, y);
+                moves += 1;
+            }
+        }
+    }
+    let took = started.elapsed();
+    assert!(board.is_won(), "opening every safe cell should win");
+    println!(
+        "won after {moves} more reveals in {took:.2?}, {:.2?} each",
+        took / moves
+    );
+
+    match peak_memory() {
+        Some(kb) => println!(
+            "peak memory {} MB ({})",
+            kb / 1024,
+            verdict(kb <= 100 * 1024)
+        ),
+        None => println!("peak memory isn't available on this platform"),
+    }
+}
+
+fn verdict(ok: bool) -> &'static str {
+    if ok {
+        "within budget"
+    } else {
+        "over budget"
+    }
+}
+
+/// The process's peak resident set, in kB.
+fn peak_memory() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|l| l.starts_with("VmHWM:"))?;
+    line.split_whitespace().nth(1)?.parse().ok()
+}
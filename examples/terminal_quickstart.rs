@@ -0,0 +1,26 @@
+//! A whole game in the terminal. Type `x y` or `B7` to reveal, `f x y` to
+//! flag. The full game, with presets and sockets, is `minesweeper-term`.
+//!
+//! cargo run --example terminal_quickstart --no-default-features
+
+use minesweeper_rs::render::{BoardRenderer, Style};
+use minesweeper_rs::session::{Command, Session};
+use minesweeper_rs::Difficulty;
+use std::io::{self, BufRead};
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut session = Session::new(Difficulty::Beginner.options().build()?);
+    let mut lines = io::stdin().lock().lines();
+    while !session.board().is_over() {
+        print!("{}", Style::default().render(session.board()));
+        let Some(line) = lines.next() else { break };
+        match line?.parse::<Command>() {
+            Ok(command) => println!("{}", session.apply(command)),
+            Err(err) => println!("{err}"),
+        }
+    }
+    if session.board().is_won() {
+        println!("You win!");
+    }
+    Ok(())
+}
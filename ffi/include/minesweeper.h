@@ -0,0 +1,141 @@
+#ifndef MINESWEEPER_H
+#define MINESWEEPER_H
+
+#include <stdbool.h>
+#include <stdint.h>
+
+/**
+ * Largest board `ms_board_new` will allocate, in cells.
+ */
+#define MS_MAX_CELLS 16000000
+
+/**
+ * Whether a game is still running.
+ */
+typedef enum MsGameState {
+  MS_GAME_STATE_PLAYING = 0,
+  MS_GAME_STATE_WON = 1,
+  MS_GAME_STATE_LOST = 2,
+  MS_GAME_STATE_INVALID = -1,
+} MsGameState;
+
+/**
+ * Outcome of a call that acts on a board.
+ */
+typedef enum MsResult {
+  MS_RESULT_OK = 0,
+  MS_RESULT_REVEALED = 1,
+  MS_RESULT_EXPLODED = 2,
+  MS_RESULT_WON = 3,
+  MS_RESULT_FLAGGED = 4,
+  MS_RESULT_UNFLAGGED = 5,
+  MS_RESULT_IGNORED_OUT_OF_BOUNDS = -1,
+  MS_RESULT_IGNORED_ALREADY_REVEALED = -2,
+  MS_RESULT_IGNORED_FLAGGED = -3,
+  MS_RESULT_IGNORED_GAME_OVER = -4,
+  MS_RESULT_NULL_POINTER = -5,
+  MS_RESULT_PANIC = -6,
+  MS_RESULT_IGNORED_NO_FLAGS_LEFT = -7,
+  /**
+   * Ignored for a reason newer than this header.
+   */
+  MS_RESULT_IGNORED_OTHER = -8,
+} MsResult;
+
+/**
+ * Opaque game board.
+ */
+typedef struct MsBoard MsBoard;
+
+/**
+ * What the player can see of one cell. `is_mine` and `neighbor_mines` are
+ * only filled in once the cell is revealed, so frontends can't leak mines.
+ */
+typedef struct MsCellInfo {
+  bool is_revealed;
+  bool is_flagged;
+  bool is_mine;
+  uint8_t neighbor_mines;
+} MsCellInfo;
+
+/**
+ * One cell the last reveal opened, with the ring it opened in: 0 for the
+ * revealed cell, then one more for each step the opening spread. Rings
+ * are for animating an opening and mean nothing for play.
+ */
+typedef struct MsRevealedCell {
+  uint32_t x;
+  uint32_t y;
+  uint32_t ring;
+} MsRevealedCell;
+
+/**
+ * Create a board whose mines are placed from `seed`. Returns null if a
+ * side is zero, the board exceeds `MS_MAX_CELLS` or `mines` doesn't fit.
+ */
+MsBoard *ms_board_new(uint32_t width, uint32_t height, uint32_t mines, uint64_t seed);
+
+/**
+ * Free a board from `ms_board_new`. Null is a no-op.
+ *
+ * # Safety
+ * `board` must be null or a live pointer from `ms_board_new`; it is
+ * invalid afterwards.
+ */
+void ms_board_free(MsBoard *board);
+
+/**
+ * Reveal (x, y). Returns `MS_RESULT_REVEALED`, `MS_RESULT_EXPLODED`,
+ * `MS_RESULT_WON` or the reason the reveal was ignored.
+ *
+ * # Safety
+ * `board` must be null or a live pointer from `ms_board_new`.
+ */
+MsResult ms_reveal(MsBoard *board, uint32_t x, uint32_t y);
+
+/**
+ * Flag or unflag (x, y). Returns `MS_RESULT_FLAGGED`,
+ * `MS_RESULT_UNFLAGGED` or the reason the toggle was ignored.
+ *
+ * # Safety
+ * `board` must be null or a live pointer from `ms_board_new`.
+ */
+MsResult ms_toggle_flag(MsBoard *board, uint32_t x, uint32_t y);
+
+/**
+ * Write what the player can see of (x, y) into `out`. Returns `MS_RESULT_OK`
+ * or `MS_RESULT_IGNORED_OUT_OF_BOUNDS`; `out` is untouched on failure.
+ *
+ * # Safety
+ * `board` must be null or a live pointer from `ms_board_new`, and `out`
+ * null or valid for writing one `MsCellInfo`.
+ */
+MsResult ms_cell_info(MsBoard *board, uint32_t x, uint32_t y, MsCellInfo *out);
+
+/**
+ * Whether the game on `board` is running, won or lost. Null gives
+ * `MS_GAME_STATE_INVALID`.
+ *
+ * # Safety
+ * `board` must be null or a live pointer from `ms_board_new`.
+ */
+MsGameState ms_board_state(MsBoard *board);
+
+/**
+ * Copy the cells the last `ms_reveal` opened into `out`, ring by ring and
+ * each ring row by row, and store how many there are in `count`. At most
+ * `capacity` are written, so a call with a capacity of 0 and a null `out`
+ * finds the size to allocate. Returns `MS_RESULT_OK`; a reveal that was
+ * ignored leaves a count of 0.
+ *
+ * # Safety
+ * `board` must be null or a live pointer from `ms_board_new`, `out` null
+ * or valid for writing `capacity` `MsRevealedCell`s, and `count` null or
+ * valid for writing one `uint32_t`.
+ */
+MsResult ms_last_reveal(MsBoard *board,
+                        MsRevealedCell *out,
+                        uint32_t capacity,
+                        uint32_t *count);
+
+#endif  /* MINESWEEPER_H */
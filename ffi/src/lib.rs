@@ -0,0 +1,268 @@
+//! C API for the minesweeper core, built as a cdylib.
+//!
+//! Boards are opaque: `ms_board_new` returns an owned `MsBoard*` that stays
+//! valid until it is passed to `ms_board_free`, and must not be used after
+//! that or from two threads at once. Every function accepts a null board and
+//! reports it instead of crashing, and panics never cross the boundary; they
+//! come back as `MS_RESULT_PANIC` (or a null/invalid value where there is no
+//! result code).
+
+use minesweeper_rs::{Board, FlagResult, Ignored, RevealResult};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+/// Largest board `ms_board_new` will allocate, in cells.
+pub const MS_MAX_CELLS: u64 = 16_000_000;
+
+/// Opaque game board.
+pub struct MsBoard(Board);
+
+/// Outcome of a call that acts on a board.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MsResult {
+    Ok = 0,
+    Revealed = 1,
+    Exploded = 2,
+    Won = 3,
+    Flagged = 4,
+    Unflagged = 5,
+    IgnoredOutOfBounds = -1,
+    IgnoredAlreadyRevealed = -2,
+    IgnoredFlagged = -3,
+    IgnoredGameOver = -4,
+    NullPointer = -5,
+    Panic = -6,
+    IgnoredNoFlagsLeft = -7,
+    /// Ignored for a reason newer than this header.
+    IgnoredOther = -8,
+}
+
+impl From<Ignored> for MsResult {
+    fn from(reason: Ignored) -> Self {
+        match reason {
+            Ignored::OutOfBounds => MsResult::IgnoredOutOfBounds,
+            Ignored::AlreadyRevealed => MsResult::IgnoredAlreadyRevealed,
+            Ignored::Flagged => MsResult::IgnoredFlagged,
+            Ignored::GameOver => MsResult::IgnoredGameOver,
+            Ignored::NoFlagsLeft => MsResult::IgnoredNoFlagsLeft,
+            _ => MsResult::IgnoredOther,
+        }
+    }
+}
+
+/// Whether a game is still running.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MsGameState {
+    Playing = 0,
+    Won = 1,
+    Lost = 2,
+    Invalid = -1,
+}
+
+/// What the player can see of one cell. `is_mine` and `neighbor_mines` are
+/// only filled in once the cell is revealed, so frontends can't leak mines.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MsCellInfo {
+    pub is_revealed: bool,
+    pub is_flagged: bool,
+    pub is_mine: bool,
+    pub neighbor_mines: u8,
+}
+
+/// One cell the last reveal opened, with the ring it opened in: 0 for the
+/// revealed cell, then one more for each step the opening spread. Rings
+/// are for animating an opening and mean nothing for play.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MsRevealedCell {
+    pub x: u32,
+    pub y: u32,
+    pub ring: u32,
+}
+
+/// Run `f` on the board behind `board`, turning null and panics into codes.
+///
+/// # Safety
+/// `board` must be null or a live pointer from `ms_board_new`.
+unsafe fn with_board(board: *mut MsBoard, f: impl FnOnce(&mut Board) -> MsResult) -> MsResult {
+    // SAFETY: guaranteed by the caller.
+    let Some(board) = (unsafe { board.as_mut() }) else {
+        return MsResult::NullPointer;
+    };
+    catch_unwind(AssertUnwindSafe(|| f(&mut board.0))).unwrap_or(MsResult::Panic)
+}
+
+/// Create a board whose mines are placed from `seed`. Returns null if a
+/// side is zero, the board exceeds `MS_MAX_CELLS` or `mines` doesn't fit.
+#[no_mangle]
+pub extern "C" fn ms_board_new(width: u32, height: u32, mines: u32, seed: u64) -> *mut MsBoard {
+    let cells = width as u64 * height as u64;
+    if width == 0 || height == 0 || cells > MS_MAX_CELLS || mines as u64 > cells {
+        return std::ptr::null_mut();
+    }
+    catch_unwind(|| {
+        let board = Board::with_seed(width as usize, height as usize, mines as usize, seed, None);
+        Box::into_raw(Box::new(MsBoard(board)))
+    })
+    .unwrap_or(std::ptr::null_mut())
+}
+
+/// Free a board from `ms_board_new`. Null is a no-op.
+///
+/// # Safety
+/// `board` must be null or a live pointer from `ms_board_new`; it is
+/// invalid afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn ms_board_free(board: *mut MsBoard) {
+    if !board.is_null() {
+        // SAFETY: see above; ownership goes back to the Box and is dropped.
+        let _ = catch_unwind(|| drop(unsafe { Box::from_raw(board) }));
+    }
+}
+
+/// Reveal (x, y). Returns `MS_RESULT_REVEALED`, `MS_RESULT_EXPLODED`,
+/// `MS_RESULT_WON` or the reason the reveal was ignored.
+///
+/// # Safety
+/// `board` must be null or a live pointer from `ms_board_new`.
+#[no_mangle]
+pub unsafe extern "C" fn ms_reveal(board: *mut MsBoard, x: u32, y: u32) -> MsResult {
+    let reveal = |board: &mut Board| match board.reveal_cell(x as usize, y as usize) {
+        RevealResult::Revealed => MsResult::Revealed,
+        RevealResult::Exploded => MsResult::Exploded,
+        RevealResult::Won => MsResult::Won,
+        RevealResult::Ignored(reason) => reason.into(),
+    };
+    // SAFETY: forwarded from the caller.
+    unsafe { with_board(board, reveal) }
+}
+
+/// Flag or unflag (x, y). Returns `MS_RESULT_FLAGGED`,
+/// `MS_RESULT_UNFLAGGED` or the reason the toggle was ignored.
+///
+/// # Safety
+/// `board` must be null or a live pointer from `ms_board_new`.
+#[no_mangle]
+pub unsafe extern "C" fn ms_toggle_flag(board: *mut MsBoard, x: u32, y: u32) -> MsResult {
+    let toggle = |board: &mut Board| match board.toggle_flag(x as usize, y as usize) {
+        FlagResult::Flagged => MsResult::Flagged,
+        FlagResult::Unflagged => MsResult::Unflagged,
+        FlagResult::Ignored(reason) => reason.into(),
+    };
+    // SAFETY: forwarded from the caller.
+    unsafe { with_board(board, toggle) }
+}
+
+/// Write what the player can see of (x, y) into `out`. Returns `MS_RESULT_OK`
+/// or `MS_RESULT_IGNORED_OUT_OF_BOUNDS`; `out` is untouched on failure.
+///
+/// # Safety
+/// `board` must be null or a live pointer from `ms_board_new`, and `out`
+/// null or valid for writing one `MsCellInfo`.
+#[no_mangle]
+pub unsafe extern "C" fn ms_cell_info(
+    board: *mut MsBoard,
+    x: u32,
+    y: u32,
+    out: *mut MsCellInfo,
+) -> MsResult {
+    if out.is_null() {
+        return MsResult::NullPointer;
+    }
+    let read = |board: &mut Board| {
+        let (x, y) = (x as usize, y as usize);
+        if x >= board.width() || y >= board.height() {
+            return MsResult::IgnoredOutOfBounds;
+        }
+        let cell = board.cell(x, y);
+        let revealed = cell.is_revealed();
+        let info = MsCellInfo {
+            is_revealed: revealed,
+            is_flagged: cell.is_flagged(),
+            is_mine: revealed && cell.is_mine() == Some(true),
+            neighbor_mines: if revealed {
+                cell.neighbor_mines().unwrap_or(0)
+            } else {
+                0
+            },
+        };
+        // SAFETY: checked non-null above; the caller owns the storage.
+        unsafe { out.write(info) };
+        MsResult::Ok
+    };
+    // SAFETY: forwarded from the caller.
+    unsafe { with_board(board, read) }
+}
+
+/// Whether the game on `board` is running, won or lost. Null gives
+/// `MS_GAME_STATE_INVALID`.
+///
+/// # Safety
+/// `board` must be null or a live pointer from `ms_board_new`.
+#[no_mangle]
+pub unsafe extern "C" fn ms_board_state(board: *mut MsBoard) -> MsGameState {
+    let mut state = MsGameState::Invalid;
+    let read = |board: &mut Board| {
+        state = if board.is_won() {
+            MsGameState::Won
+        } else if board.is_lost() {
+            MsGameState::Lost
+        } else {
+            MsGameState::Playing
+        };
+        MsResult::Ok
+    };
+    // SAFETY: forwarded from the caller.
+    unsafe { with_board(board, read) };
+    state
+}
+
+/// Copy the cells the last `ms_reveal` opened into `out`, ring by ring and
+/// each ring row by row, and store how many there are in `count`. At most
+/// `capacity` are written, so a call with a capacity of 0 and a null `out`
+/// finds the size to allocate. Returns `MS_RESULT_OK`; a reveal that was
+/// ignored leaves a count of 0.
+///
+/// # Safety
+/// `board` must be null or a live pointer from `ms_board_new`, `out` null
+/// or valid for writing `capacity` `MsRevealedCell`s, and `count` null or
+/// valid for writing one `uint32_t`.
+#[no_mangle]
+pub unsafe extern "C" fn ms_last_reveal(
+    board: *mut MsBoard,
+    out: *mut MsRevealedCell,
+    capacity: u32,
+    count: *mut u32,
+) -> MsResult {
+    if count.is_null() || (out.is_null() && capacity > 0) {
+        return MsResult::NullPointer;
+    }
+    let read = |board: &mut Board| {
+        let cells = board
+            .last_reveal_wavefronts()
+            .into_iter()
+            .enumerate()
+            .flat_map(|(ring, cells)| {
+                cells.into_iter().map(move |(x, y)| MsRevealedCell {
+                    x: x as u32,
+                    y: y as u32,
+                    ring: ring as u32,
+                })
+            });
+        let mut total = 0;
+        for cell in cells {
+            if total < capacity {
+                // SAFETY: within the `capacity` the caller vouched for.
+                unsafe { out.add(total as usize).write(cell) };
+            }
+            total += 1;
+        }
+        // SAFETY: checked non-null above; the caller owns the storage.
+        unsafe { count.write(total) };
+        MsResult::Ok
+    };
+    // SAFETY: forwarded from the caller.
+    unsafe { with_board(board, read) }
+}
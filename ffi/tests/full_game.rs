@@ -0,0 +1,99 @@
+//! Plays games through the exported C functions, the way a C caller would.
+
+use minesweeper_ffi::*;
+use std::ptr;
+
+#[test]
+fn mine_free_board_is_won_by_one_reveal() {
+    let board = ms_board_new(8, 6, 0, 1);
+    assert!(!board.is_null());
+    unsafe {
+        assert_eq!(ms_board_state(board), MsGameState::Playing);
+        assert_eq!(ms_toggle_flag(board, 7, 5), MsResult::Flagged);
+        assert_eq!(ms_toggle_flag(board, 7, 5), MsResult::Unflagged);
+        assert_eq!(ms_reveal(board, 0, 0), MsResult::Won);
+        assert_eq!(ms_board_state(board), MsGameState::Won);
+
+        let mut info = MsCellInfo::default();
+        assert_eq!(ms_cell_info(board, 7, 5, &mut info), MsResult::Ok);
+        assert!(info.is_revealed && !info.is_mine);
+        assert_eq!(ms_reveal(board, 1, 1), MsResult::IgnoredGameOver);
+        ms_board_free(board);
+    }
+}
+
+#[test]
+fn game_ends_on_the_first_mine_or_the_only_safe_cell() {
+    let board = ms_board_new(3, 3, 8, 42);
+    assert!(!board.is_null());
+    unsafe {
+        let mut info = MsCellInfo::default();
+        assert_eq!(ms_cell_info(board, 0, 0, &mut info), MsResult::Ok);
+        assert!(!info.is_mine, "hidden cells must not report mines");
+
+        match ms_reveal(board, 0, 0) {
+            MsResult::Exploded => {
+                assert_eq!(ms_board_state(board), MsGameState::Lost);
+                assert_eq!(ms_cell_info(board, 0, 0, &mut info), MsResult::Ok);
+                assert!(info.is_mine);
+            }
+            MsResult::Won => assert_eq!(ms_board_state(board), MsGameState::Won),
+            other => panic!("unexpected {other:?}"),
+        }
+        ms_board_free(board);
+    }
+}
+
+#[test]
+fn bad_arguments_are_reported() {
+    assert!(ms_board_new(0, 5, 0, 0).is_null());
+    assert!(ms_board_new(4, 4, 17, 0).is_null());
+    assert!(ms_board_new(u32::MAX, u32::MAX, 1, 0).is_null());
+
+    let board = ms_board_new(4, 4, 3, 7);
+    unsafe {
+        assert_eq!(ms_reveal(ptr::null_mut(), 0, 0), MsResult::NullPointer);
+        assert_eq!(ms_board_state(ptr::null_mut()), MsGameState::Invalid);
+        assert_eq!(ms_reveal(board, 4, 0), MsResult::IgnoredOutOfBounds);
+        assert_eq!(
+            ms_cell_info(board, 0, 0, ptr::null_mut()),
+            MsResult::NullPointer
+        );
+        ms_board_free(ptr::null_mut());
+        ms_board_free(board);
+    }
+}
+
+#[test]
+fn the_last_reveal_comes_back_in_rings() {
+    let board = ms_board_new(5, 1, 0, 3);
+    unsafe {
+        let mut count = 9;
+        assert_eq!(
+            ms_last_reveal(board, ptr::null_mut(), 0, &mut count),
+            MsResult::Ok
+        );
+        assert_eq!(count, 0);
+
+        assert_eq!(ms_reveal(board, 2, 0), MsResult::Won);
+        assert_eq!(
+            ms_last_reveal(board, ptr::null_mut(), 0, &mut count),
+            MsResult::Ok
+        );
+        assert_eq!(count, 5);
+        let mut cells = vec![MsRevealedCell::default(); 3];
+        assert_eq!(
+            ms_last_reveal(board, cells.as_mut_ptr(), 3, &mut count),
+            MsResult::Ok
+        );
+        assert_eq!(count, 5);
+        let rings: Vec<_> = cells.iter().map(|c| (c.x, c.ring)).collect();
+        assert_eq!(rings, [(2, 0), (1, 1), (3, 1)]);
+
+        assert_eq!(
+            ms_last_reveal(board, ptr::null_mut(), 1, &mut count),
+            MsResult::NullPointer
+        );
+        ms_board_free(board);
+    }
+}
@@ -0,0 +1,10 @@
+//! Any line as a terminal or socket command.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use minesweeper_rs::session::Command;
+
+fuzz_target!(|line: &str| {
+    let _ = line.parse::<Command>();
+});
@@ -0,0 +1,14 @@
+//! Any text as a board layout, `*` for a mine and `.` for a safe cell.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use minesweeper_rs::Board;
+
+fuzz_target!(|text: &str| {
+    if let Ok(board) = Board::decode_layout(text) {
+        let layout = board.encode_layout();
+        let again = Board::decode_layout(&layout).expect("a written layout reads back");
+        assert_eq!(again.encode_layout(), layout);
+    }
+});
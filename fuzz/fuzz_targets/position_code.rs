@@ -0,0 +1,21 @@
+//! Any text as a bug report's position code, and every board one reads
+//! back as through the commands that take `--code`: its layout, the text
+//! renders and the analysis.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use minesweeper_rs::analysis::AnalysisReport;
+use minesweeper_rs::render::{BoardRenderer, Style};
+use minesweeper_rs::report::{decode_position_code, DebugSnapshot};
+use minesweeper_rs::solver;
+
+fuzz_target!(|code: &str| {
+    if let Ok(board) = decode_position_code(code) {
+        board.encode_layout();
+        Style::default().render(&board);
+        DebugSnapshot::new(&board).show_mines(true).to_string();
+        AnalysisReport::new(&board);
+        solver::analyze(&board);
+    }
+});
@@ -0,0 +1,10 @@
+//! Any text as a pasted or opened board: a puzzle file or a game ID.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use minesweeper_rs::formats;
+
+fuzz_target!(|text: &str| {
+    let _ = formats::read(text);
+});
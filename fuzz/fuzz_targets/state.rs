@@ -0,0 +1,26 @@
+//! Any bytes as an autosaved board; the header's sizes are checked before
+//! anything is allocated. A board that reads back must also write back
+//! the same and survive everything the commands do with one: its layout,
+//! the text renders and the analysis.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use minesweeper_rs::analysis::AnalysisReport;
+use minesweeper_rs::render::{BoardRenderer, Style};
+use minesweeper_rs::report::DebugSnapshot;
+use minesweeper_rs::{solver, Board};
+
+fuzz_target!(|bytes: &[u8]| {
+    if let Ok(board) = Board::decode_state(bytes) {
+        let saved = board.encode_state();
+        let again = Board::decode_state(&saved).expect("a saved board reads back");
+        assert_eq!(again.encode_state(), saved);
+
+        board.encode_layout();
+        Style::default().render(&board);
+        DebugSnapshot::new(&board).show_mines(true).to_string();
+        AnalysisReport::new(&board);
+        solver::analyze(&board);
+    }
+});
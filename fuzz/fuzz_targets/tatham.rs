@@ -0,0 +1,13 @@
+//! Any text as a Simon Tatham "Mines" game ID.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use minesweeper_rs::formats::tatham;
+
+fuzz_target!(|id: &str| {
+    if let Ok(puzzle) = tatham::decode(id) {
+        let again = tatham::decode(&tatham::encode(&puzzle)).expect("an encoded ID reads back");
+        assert_eq!(again.board.encode_layout(), puzzle.board.encode_layout());
+    }
+});
@@ -0,0 +1,218 @@
+//! Python bindings for the minesweeper core, built with maturin.
+//!
+//! Observations are plain integers per cell so they drop straight into
+//! numpy or a training loop: `0..=8` for a revealed number, `MINE` for a
+//! revealed mine, `HIDDEN` and `FLAGGED` for covered cells, and (only on
+//! boards built with `debug=True`) `HIDDEN_MINE` for covered mines.
+
+use minesweeper_rs::{solver, Board, BoardOptions, FlagResult, Ignored, RevealResult};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+const MINE: i8 = 9;
+const HIDDEN: i8 = -1;
+const FLAGGED: i8 = -2;
+const HIDDEN_MINE: i8 = -3;
+
+fn ignored_name(reason: Ignored) -> &'static str {
+    match reason {
+        Ignored::OutOfBounds => "out_of_bounds",
+        Ignored::AlreadyRevealed => "already_revealed",
+        Ignored::Flagged => "flagged",
+        Ignored::GameOver => "game_over",
+        Ignored::NoFlagsLeft => "no_flags_left",
+        _ => "ignored",
+    }
+}
+
+/// A minesweeper board.
+///
+/// Board(width, height, mines, seed=None, debug=False)
+///
+/// The same seed always gives the same mine layout. Without `debug=True`,
+/// `cells()` never shows where covered mines are.
+#[pyclass(name = "Board", module = "minesweeper")]
+struct PyBoard {
+    board: Board,
+    debug: bool,
+}
+
+#[pymethods]
+impl PyBoard {
+    #[new]
+    #[pyo3(signature = (width, height, mines, seed=None, debug=false))]
+    fn new(
+        width: usize,
+        height: usize,
+        mines: usize,
+        seed: Option<u64>,
+        debug: bool,
+    ) -> PyResult<Self> {
+        let mut options = BoardOptions::new(width, height, mines);
+        if let Some(seed) = seed {
+            options = options.seed(seed);
+        }
+        let board = options
+            .build()
+            .map_err(|err| PyValueError::new_err(err.to_string()))?;
+        Ok(PyBoard { board, debug })
+    }
+
+    /// Board width in cells.
+    #[getter]
+    fn width(&self) -> usize {
+        self.board.width()
+    }
+
+    /// Board height in cells.
+    #[getter]
+    fn height(&self) -> usize {
+        self.board.height()
+    }
+
+    /// Number of mines on the board.
+    #[getter]
+    fn mines(&self) -> usize {
+        self.board.mine_count()
+    }
+
+    /// Seed the mines were placed from, or None for an unseeded board.
+    #[getter]
+    fn seed(&self) -> Option<u64> {
+        self.board.seed()
+    }
+
+    /// Flag cap for flag-limit mode, or None for unlimited flags. Set it to
+    /// `mines` to play with exactly as many flags as mines.
+    #[getter]
+    fn max_flags(&self) -> Option<usize> {
+        self.board.max_flags()
+    }
+
+    #[setter]
+    fn set_max_flags(&mut self, max_flags: Option<usize>) {
+        self.board.set_max_flags(max_flags);
+    }
+
+    /// True once every safe cell is revealed.
+    #[getter]
+    fn is_won(&self) -> bool {
+        self.board.is_won()
+    }
+
+    /// True once a mine has been revealed.
+    #[getter]
+    fn is_lost(&self) -> bool {
+        self.board.is_lost()
+    }
+
+    /// reveal(x, y) -> str
+    ///
+    /// Reveal a cell. Returns "revealed", "exploded" or "won", or why the
+    /// move was ignored, from the same reasons `flag()` gives:
+    /// "out_of_bounds", "already_revealed", "flagged", "game_over" or
+    /// "no_flags_left" (which only a flag ever runs into).
+    fn reveal(&mut self, x: usize, y: usize) -> &'static str {
+        match self.board.reveal_cell(x, y) {
+            RevealResult::Revealed => "revealed",
+            RevealResult::Exploded => "exploded",
+            RevealResult::Won => "won",
+            RevealResult::Ignored(reason) => ignored_name(reason),
+        }
+    }
+
+    /// flag(x, y) -> str
+    ///
+    /// Toggle a flag. Returns "flagged" or "unflagged", or why the move was
+    /// ignored: "out_of_bounds", "already_revealed", "game_over" or (with a
+    /// flag limit) "no_flags_left".
+    fn flag(&mut self, x: usize, y: usize) -> &'static str {
+        match self.board.toggle_flag(x, y) {
+            FlagResult::Flagged => "flagged",
+            FlagResult::Unflagged => "unflagged",
+            FlagResult::Ignored(reason) => ignored_name(reason),
+        }
+    }
+
+    /// wavefronts() -> list[list[tuple[int, int]]]
+    ///
+    /// The cells the last reveal opened as (x, y), in rings: the revealed
+    /// cell alone, then the cells each ring's blanks opened, each ring row
+    /// by row. For animating an opening; the rings mean nothing for play.
+    /// Empty after a reveal that was ignored.
+    fn wavefronts(&self) -> Vec<Vec<(usize, usize)>> {
+        self.board.last_reveal_wavefronts()
+    }
+
+    /// cells() -> list[list[int]]
+    ///
+    /// Per-cell observations as rows, indexed `[y][x]`. See the module
+    /// constants HIDDEN, FLAGGED, MINE and HIDDEN_MINE.
+    fn cells(&self) -> Vec<Vec<i8>> {
+        let board = &self.board;
+        // the layout is the answer key, so it is only read in debug mode
+        let layout = self.debug.then(|| board.encode_layout().into_bytes());
+        let hidden_mine = |x: usize, y: usize| {
+            layout
+                .as_ref()
+                .is_some_and(|layout| layout[y * (board.width() + 1) + x] == b'*')
+        };
+        (0..board.height())
+            .map(|y| {
+                (0..board.width())
+                    .map(|x| {
+                        let cell = board.cell(x, y);
+                        if cell.is_revealed() {
+                            match cell.neighbor_mines() {
+                                Some(count) => count as i8,
+                                None => MINE,
+                            }
+                        } else if hidden_mine(x, y) {
+                            HIDDEN_MINE
+                        } else if cell.is_flagged() {
+                            FLAGGED
+                        } else {
+                            HIDDEN
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// solve() -> list[list[float]]
+    ///
+    /// Estimated chance that each cell is a mine, indexed `[y][x]`, using
+    /// only what the player can see. Cells settled by logic are 0.0 or 1.0;
+    /// the rest share the remaining mines evenly.
+    fn solve(&self) -> Vec<Vec<f64>> {
+        solver::mine_probabilities(&self.board)
+            .chunks(self.board.width())
+            .map(<[f64]>::to_vec)
+            .collect()
+    }
+
+    fn __repr__(&self) -> String {
+        let seed = self
+            .board
+            .seed()
+            .map_or("None".to_string(), |seed| seed.to_string());
+        format!(
+            "Board(width={}, height={}, mines={}, seed={seed})",
+            self.board.width(),
+            self.board.height(),
+            self.board.mine_count()
+        )
+    }
+}
+
+/// Minesweeper game core. Start with `help(minesweeper.Board)`.
+#[pymodule]
+fn minesweeper(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyBoard>()?;
+    m.add("MINE", MINE)?;
+    m.add("HIDDEN", HIDDEN)?;
+    m.add("FLAGGED", FLAGGED)?;
+    m.add("HIDDEN_MINE", HIDDEN_MINE)?;
+    Ok(())
+}
@@ -0,0 +1,60 @@
+import minesweeper
+import pytest
+
+
+def test_same_seed_same_board():
+    a = minesweeper.Board(9, 9, 10, seed=7, debug=True)
+    b = minesweeper.Board(9, 9, 10, seed=7, debug=True)
+    assert a.cells() == b.cells()
+
+
+def test_observation_hides_mines_without_debug():
+    board = minesweeper.Board(9, 9, 10, seed=7)
+    cells = board.cells()
+    assert len(cells) == 9 and all(len(row) == 9 for row in cells)
+    assert all(v == minesweeper.HIDDEN for row in cells for v in row)
+
+    debug = minesweeper.Board(9, 9, 10, seed=7, debug=True)
+    hidden_mines = sum(v == minesweeper.HIDDEN_MINE for row in debug.cells() for v in row)
+    assert hidden_mines == 10
+
+
+def test_empty_board_is_won_by_one_reveal():
+    board = minesweeper.Board(5, 4, 0, seed=1)
+    assert board.flag(4, 3) == "flagged"
+    assert board.flag(4, 3) == "unflagged"
+    assert board.reveal(0, 0) == "won"
+    assert board.is_won and not board.is_lost
+    assert board.reveal(1, 1) == "game_over"
+    assert all(v == 0 for row in board.cells() for v in row)
+
+
+def test_wavefronts_ring_out_from_the_reveal():
+    board = minesweeper.Board(5, 1, 0, seed=1)
+    assert board.wavefronts() == []
+    assert board.reveal(2, 0) == "won"
+    assert board.wavefronts() == [[(2, 0)], [(1, 0), (3, 0)], [(0, 0), (4, 0)]]
+    assert board.reveal(0, 0) == "game_over"
+    assert board.wavefronts() == []
+
+
+def test_full_board_explodes():
+    board = minesweeper.Board(3, 3, 9, seed=1)
+    assert board.reveal(1, 1) == "exploded"
+    assert board.is_lost
+    assert board.cells()[1][1] == minesweeper.MINE
+
+
+def test_solve_probabilities():
+    board = minesweeper.Board(6, 6, 6, seed=3)
+    probs = board.solve()
+    assert len(probs) == 6 and len(probs[0]) == 6
+    assert sum(map(sum, probs)) == pytest.approx(6)
+    assert board.reveal(10, 0) == "out_of_bounds"
+
+
+def test_bad_arguments():
+    with pytest.raises(ValueError):
+        minesweeper.Board(0, 5, 1)
+    with pytest.raises(ValueError):
+        minesweeper.Board(2, 2, 5)
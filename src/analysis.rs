@@ -0,0 +1,225 @@
+//! How hard a board is, for puzzle authors and pack curators grading their
+//! boards: its 3BV, density and openings, how far deduction gets and a
+//! grade drawn from all of them.
+//!
+//! The report reads the whole layout, mines included, so it is a tool for
+//! whoever made the board rather than a hint for whoever plays it.
+//!
+//! ```
+//! use minesweeper_rs::analysis::{AnalysisReport, Grade};
+//! use minesweeper_rs::Board;
+//!
+//! let board = Board::decode_layout("*........\n.........\n........*\n")?;
+//! let report = AnalysisReport::new(&board);
+//! assert_eq!((report.three_bv, report.openings), (1, 1));
+//! assert!(report.no_guess);
+//! assert_eq!(report.grade, Grade::Easy);
+//! assert!(report.to_string().contains("Grade: easy"));
+//! # Ok::<(), minesweeper_rs::LayoutError>(())
+//! ```
+
+use crate::{render, solver, stats, Board};
+use std::fmt;
+
+/// A difficulty grade, easiest first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Grade {
+    /// Little to clear and little to think about.
+    Easy,
+    /// A fair amount of clearing or some tangled numbers.
+    Medium,
+    /// Dense, long or tangled, or needing a guess.
+    Hard,
+    /// Most of the above at once.
+    Fiendish,
+}
+
+impl Grade {
+    /// The grade from a board's measurements. Each of density, 3BV and the
+    /// largest constraint group scores 0 to 2 points, needing a guess
+    /// scores 2, and the total picks the grade: up to 1 is easy, up to 3
+    /// medium, up to 5 hard and anything more fiendish.
+    ///
+    /// ```
+    /// use minesweeper_rs::analysis::Grade;
+    ///
+    /// assert_eq!(Grade::score(0.12, 20, 3, true), Grade::Easy);
+    /// assert_eq!(Grade::score(0.16, 60, 3, true), Grade::Medium);
+    /// assert_eq!(Grade::score(0.21, 160, 20, false), Grade::Fiendish);
+    /// ```
+    pub fn score(density: f64, three_bv: usize, largest_group: usize, no_guess: bool) -> Grade {
+        let points = |value: f64, medium: f64, hard: f64| {
+            if value < medium {
+                0
+            } else if value < hard {
+                1
+            } else {
+                2
+            }
+        };
+        let total = points(density, 0.14, 0.19)
+            + points(three_bv as f64, 50.0, 150.0)
+            + points(largest_group as f64, 12.0, 30.0)
+            + if no_guess { 0 } else { 2 };
+        match total {
+            0..=1 => Grade::Easy,
+            2..=3 => Grade::Medium,
+            4..=5 => Grade::Hard,
+            _ => Grade::Fiendish,
+        }
+    }
+
+    /// The lowercase name the report uses.
+    pub fn name(self) -> &'static str {
+        match self {
+            Grade::Easy => "easy",
+            Grade::Medium => "medium",
+            Grade::Hard => "hard",
+            Grade::Fiendish => "fiendish",
+        }
+    }
+}
+
+impl fmt::Display for Grade {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Everything the analysis measured about one board. `Display` writes it
+/// for people and [`AnalysisReport::to_json`] for tools.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnalysisReport {
+    /// Width in cells.
+    pub width: usize,
+    /// Height in cells.
+    pub height: usize,
+    /// Mines on the board.
+    pub mines: usize,
+    /// Share of cells that are mines.
+    pub density: f64,
+    /// Clicks needed to clear the board without flagging.
+    pub three_bv: usize,
+    /// Distinct openings, the areas a single click clears.
+    pub openings: usize,
+    /// Cells in the largest group of hidden cells the solver had to
+    /// reason about together.
+    pub largest_group: usize,
+    /// Whether deduction clears the board from the current position.
+    pub no_guess: bool,
+    /// On an untouched board, the cell deduction started from: the first
+    /// opening it clears the board from, or failing that the first opening
+    /// (or safe cell, without one).
+    pub start: Option<(usize, usize)>,
+    /// The grade [`Grade::score`] gives the numbers above.
+    pub grade: Grade,
+}
+
+impl AnalysisReport {
+    /// Measure `board` as it stands. A game in progress is judged from its
+    /// revealed cells; an untouched one from its best opening.
+    pub fn new(board: &Board) -> AnalysisReport {
+        let (width, height, mines) = (board.width(), board.height(), board.mine_count());
+        let openings = stats::openings(board);
+        let untouched = board.revealed_safe() == 0 && !board.is_over();
+
+        let (start, deduction) = if untouched {
+            let opened = |&(x, y): &(usize, usize)| {
+                let mut board = board.clone();
+                board.clear_flags();
+                board.reveal_cell(x, y);
+                ((x, y), solver::deduce(&board))
+            };
+            let mut tries = openings.iter().map(opened);
+            let first = tries.next();
+            let solved = first
+                .filter(|(_, deduction)| deduction.solved)
+                .or_else(|| tries.find(|(_, deduction)| deduction.solved));
+            let fallback = first.or_else(|| {
+                let safe = (0..height)
+                    .flat_map(|y| (0..width).map(move |x| (x, y)))
+                    .find(|&(x, y)| !board.bits(x, y).is_mine())?;
+                Some(opened(&safe))
+            });
+            match solved.or(fallback) {
+                Some((start, deduction)) => (Some(start), deduction),
+                None => (None, solver::Deduction::default()),
+            }
+        } else {
+            (None, solver::deduce(board))
+        };
+
+        let density = mines as f64 / (width * height) as f64;
+        let three_bv = stats::three_bv(board);
+        AnalysisReport {
+            width,
+            height,
+            mines,
+            density,
+            three_bv,
+            openings: openings.len(),
+            largest_group: deduction.largest_group,
+            no_guess: deduction.solved,
+            start,
+            grade: Grade::score(density, three_bv, deduction.largest_group, deduction.solved),
+        }
+    }
+
+    /// The report as one JSON object, keys as the field names, with the
+    /// grade by name and `start` as `[x, y]` or `null`.
+    ///
+    /// ```
+    /// use minesweeper_rs::analysis::AnalysisReport;
+    /// use minesweeper_rs::Board;
+    ///
+    /// let report = AnalysisReport::new(&Board::decode_layout("*.\n")?);
+    /// assert_eq!(
+    ///     report.to_json(),
+    ///     r#"{"width":2,"height":1,"mines":1,"density":0.5,"three_bv":1,"openings":0,"#
+    ///         .to_owned()
+    ///         + r#""largest_group":0,"no_guess":true,"start":[1,0],"grade":"medium"}"#
+    /// );
+    /// # Ok::<(), minesweeper_rs::LayoutError>(())
+    /// ```
+    pub fn to_json(&self) -> String {
+        let start = self
+            .start
+            .map_or("null".to_string(), |(x, y)| format!("[{x},{y}]"));
+        format!(
+            "{{\"width\":{},\"height\":{},\"mines\":{},\"density\":{},\"three_bv\":{},\
+             \"openings\":{},\"largest_group\":{},\"no_guess\":{},\"start\":{start},\
+             \"grade\":\"{}\"}}",
+            self.width,
+            self.height,
+            self.mines,
+            self.density,
+            self.three_bv,
+            self.openings,
+            self.largest_group,
+            self.no_guess,
+            self.grade
+        )
+    }
+}
+
+impl fmt::Display for AnalysisReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Board: {}x{}, {} mines ({:.1}% density)",
+            self.width,
+            self.height,
+            self.mines,
+            self.density * 100.0
+        )?;
+        writeln!(f, "3BV: {}", self.three_bv)?;
+        writeln!(f, "Openings: {}", self.openings)?;
+        writeln!(f, "Largest constraint group: {} cells", self.largest_group)?;
+        let from = self.start.map_or(String::new(), |(x, y)| {
+            format!(", opening {}{}", render::column_name(x), y + 1)
+        });
+        let answer = if self.no_guess { "yes" } else { "no" };
+        writeln!(f, "Solvable without guessing: {answer}{from}")?;
+        writeln!(f, "Grade: {}", self.grade)
+    }
+}
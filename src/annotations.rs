@@ -0,0 +1,202 @@
+//! Notes scribbled on cells while studying a position: letters for the
+//! cases of a 50/50, coloured dots and corners to group the cells of a
+//! region. They are cosmetic, so nothing in the game reads them; they are
+//! kept with the autosave and never go into race results.
+//!
+//! ```
+//! use minesweeper_rs::annotations::{Annotations, Hue, Note};
+//!
+//! let mut notes = Annotations::default();
+//! notes.toggle(0, 0, Note::Letter('A'));
+//! notes.toggle(1, 0, Note::Dot(Hue::Cyan));
+//! notes.clear();
+//! assert!(notes.is_empty());
+//! assert!(notes.undo_clear());
+//! assert_eq!(notes.get(0, 0), Some(Note::Letter('A')));
+//! ```
+
+use std::collections::BTreeMap;
+
+/// The colour of a dot or corner.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Hue {
+    /// Pink.
+    Pink,
+    /// Cyan.
+    Cyan,
+    /// Lime green.
+    Lime,
+}
+
+impl Hue {
+    const ALL: [Hue; 3] = [Hue::Pink, Hue::Cyan, Hue::Lime];
+}
+
+/// One note on one cell.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Note {
+    /// A single ASCII character, such as the A or B of a case analysis.
+    Letter(char),
+    /// A coloured dot.
+    Dot(Hue),
+    /// A coloured corner, for marking out a region.
+    Corner(Hue),
+}
+
+/// The notes a frontend offers, in the order of the number keys 1 to 9.
+pub const PALETTE: [Note; 9] = [
+    Note::Letter('A'),
+    Note::Letter('B'),
+    Note::Letter('C'),
+    Note::Dot(Hue::Pink),
+    Note::Dot(Hue::Cyan),
+    Note::Dot(Hue::Lime),
+    Note::Corner(Hue::Pink),
+    Note::Corner(Hue::Cyan),
+    Note::Corner(Hue::Lime),
+];
+
+/// A layer of notes over a board, at most one per cell.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Annotations {
+    notes: BTreeMap<(usize, usize), Note>,
+    cleared: Option<BTreeMap<(usize, usize), Note>>, // until the next change
+    revision: u64,
+}
+
+impl Annotations {
+    /// The note on (x, y), if any.
+    pub fn get(&self, x: usize, y: usize) -> Option<Note> {
+        self.notes.get(&(x, y)).copied()
+    }
+
+    /// Every note, in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), Note)> + '_ {
+        let mut notes: Vec<_> = self
+            .notes
+            .iter()
+            .map(|(&cell, &note)| (cell, note))
+            .collect();
+        notes.sort_unstable_by_key(|&((x, y), _)| (y, x));
+        notes.into_iter()
+    }
+
+    /// How many cells have a note.
+    pub fn len(&self) -> usize {
+        self.notes.len()
+    }
+
+    /// Whether no cell has a note.
+    pub fn is_empty(&self) -> bool {
+        self.notes.is_empty()
+    }
+
+    /// Put `note` on (x, y), replacing what was there, or take it off if
+    /// it is already there. Returns whether the cell now has `note`.
+    /// Characters outside ASCII can't be saved, so they are ignored.
+    pub fn toggle(&mut self, x: usize, y: usize, note: Note) -> bool {
+        if matches!(note, Note::Letter(c) if !c.is_ascii_graphic()) {
+            return false;
+        }
+        self.changed();
+        if self.notes.get(&(x, y)) == Some(&note) {
+            self.notes.remove(&(x, y));
+            false
+        } else {
+            self.notes.insert((x, y), note);
+            true
+        }
+    }
+
+    /// Take any note off (x, y).
+    pub fn erase(&mut self, x: usize, y: usize) {
+        if self.notes.contains_key(&(x, y)) {
+            self.changed();
+            self.notes.remove(&(x, y));
+        }
+    }
+
+    /// Take every note off at once. `undo_clear` puts them back until the
+    /// next change.
+    pub fn clear(&mut self) {
+        if self.notes.is_empty() {
+            return;
+        }
+        self.revision += 1;
+        self.cleared = Some(std::mem::take(&mut self.notes));
+    }
+
+    /// Whether `undo_clear` has anything to put back.
+    pub fn can_undo_clear(&self) -> bool {
+        self.cleared.is_some()
+    }
+
+    /// Put back the notes the last `clear` took off; false if there is
+    /// nothing to undo.
+    pub fn undo_clear(&mut self) -> bool {
+        let Some(notes) = self.cleared.take() else {
+            return false;
+        };
+        self.revision += 1;
+        self.notes = notes;
+        true
+    }
+
+    /// Counts every change, so a frontend can tell when to save.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    fn changed(&mut self) {
+        self.revision += 1;
+        self.cleared = None;
+    }
+
+    /// Little endian: the note count as a `u32`, then per note x and y as
+    /// `u32`s, a kind byte (0 letter, 1 dot, 2 corner) and a value byte
+    /// (the character, or the hue from 0).
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = (self.notes.len() as u32).to_le_bytes().to_vec();
+        for (&(x, y), &note) in &self.notes {
+            out.extend_from_slice(&(x as u32).to_le_bytes());
+            out.extend_from_slice(&(y as u32).to_le_bytes());
+            let hue = |hue| Hue::ALL.iter().position(|&h| h == hue).unwrap_or(0) as u8;
+            out.extend_from_slice(&match note {
+                Note::Letter(c) => [0, c as u8],
+                Note::Dot(h) => [1, hue(h)],
+                Note::Corner(h) => [2, hue(h)],
+            });
+        }
+        out
+    }
+
+    /// Read `encode` output, dropping notes that fall outside a board of
+    /// `width` by `height`; `None` if the bytes are cut short or corrupt.
+    pub fn decode(bytes: &[u8], width: usize, height: usize) -> Option<Annotations> {
+        let (count, mut rest) = bytes.split_first_chunk::<4>()?;
+        let count = u32::from_le_bytes(*count) as usize;
+        if rest.len() != count.checked_mul(10)? {
+            return None;
+        }
+        let mut notes = BTreeMap::new();
+        while let Some((entry, tail)) = rest.split_first_chunk::<10>() {
+            rest = tail;
+            let x = u32::from_le_bytes(entry[..4].try_into().ok()?) as usize;
+            let y = u32::from_le_bytes(entry[4..8].try_into().ok()?) as usize;
+            let hue = || Hue::ALL.get(usize::from(entry[9])).copied();
+            let note = match entry[8] {
+                0 if entry[9].is_ascii_graphic() => Note::Letter(char::from(entry[9])),
+                1 => Note::Dot(hue()?),
+                2 => Note::Corner(hue()?),
+                _ => return None,
+            };
+            if x < width && y < height {
+                notes.insert((x, y), note);
+            }
+        }
+        Some(Annotations {
+            notes,
+            ..Annotations::default()
+        })
+    }
+}
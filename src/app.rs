@@ -0,0 +1,3234 @@
+//! Application state and the top-level eframe app, which draws the current
+//! screen and switches to whichever screen it asks for.
+
+use crate::bundle::{self, Import};
+use crate::changelog::{self, Changelog, Release, Version};
+use crate::clock::{self, Calendar, Clock, SystemCalendar};
+use crate::coach::{Advisor, Coach};
+use crate::crash;
+use crate::earcons::{self, Earcon, Earcons};
+use crate::history::{Dealt, Game, History, Outcome};
+use crate::journal::{Action, Journal};
+use crate::logging;
+use crate::net::leaderboard::{self, Submission};
+use crate::notify::{self, Payload};
+use crate::particles::ParticleSystem;
+use crate::perf::profile_scope;
+use crate::played::PlayedBoards;
+use crate::profiles::{self, Profiles};
+use crate::quick_entry::QuickEntry;
+use crate::race;
+use crate::replays::ReplayStore;
+use crate::repro;
+use crate::screens::compact::CompactMode;
+use crate::screens::editor::EditorScreen;
+use crate::screens::game::CellMenu;
+use crate::screens::history::HistoryView;
+use crate::screens::menu::ProfileForm;
+use crate::screens::race::RaceScreen;
+use crate::screens::settings::DataTransfer;
+use crate::screens::spectate::SpectateScreen;
+use crate::screens::themes::ThemeEditor;
+use crate::screens::trainer::TrainerScreen;
+use crate::screens::versus::VersusScreen;
+use crate::screens::{self, Screen};
+use crate::settings::{AutoOpen, Settings, MAX_ENDGAME_CELLS};
+use crate::toasts::Toasts;
+use crate::tutorial::Tutorial;
+use crate::visuals::CellVisuals;
+use crate::widgets::{BoardView, CellClick, Heatmap, KillScreen};
+use eframe::egui;
+use minesweeper_rs::annotations::{Annotations, Note, PALETTE};
+use minesweeper_rs::assist;
+use minesweeper_rs::autosave::{Autosave, Session};
+use minesweeper_rs::endgame::{self, Outlook};
+use minesweeper_rs::hints::{Hint, HintLadder, PENALTY_SECS};
+use minesweeper_rs::objective::{Tracker, Verdict};
+use minesweeper_rs::odds::{self, Chance};
+use minesweeper_rs::puzzle::Puzzle;
+use minesweeper_rs::rating;
+use minesweeper_rs::session::AbandonReason;
+use minesweeper_rs::stats::{self, InputStats};
+use minesweeper_rs::timeline::{FlagLog, RevealTimes};
+use minesweeper_rs::{
+    generate, solver, Board, BoardError, BoardOptions, BoardSnapshot, CycleMode, Difficulty,
+    Ignored, Marking, MarkingResult, RevealResult, Symmetry,
+};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{mpsc, Arc};
+use tracing::{info, warn};
+
+/// Cells a single reveal must open to count as a large opening.
+const LARGE_OPENING: usize = 30;
+
+/// Seconds of play between autosaves; a finished game is saved at once.
+const AUTOSAVE_SECS: f64 = 10.0;
+
+/// How long an ignored-action message stays on screen, in seconds.
+const FEEDBACK_SECS: f64 = 1.5;
+
+/// A short-lived status message. The cell it is about, if any, flashes
+/// through the cell visuals for as long.
+pub struct Feedback {
+    pub reason: Ignored,
+    until: f64,
+}
+
+/// Something that happened in play that the screen may want to show.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GameEvent {
+    Opened { x: usize, y: usize, cells: usize }, // a large opening cascaded
+    FinalFlag(usize, usize),                     // the flag that completed a correct set
+    Exploded(usize, usize),
+    Won(usize, usize), // the last cell revealed
+}
+
+/// The first move of a game when the settings make it for the player. It
+/// is kept apart from the player's own: it isn't counted as a click, can't
+/// be undone, and a win from the best opening is marked in the history.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AutoMove {
+    pub choice: AutoOpen,
+    pub cell: (usize, usize), // where it was made, or will be once generation ends
+}
+
+/// A no-guess board being generated in the background after the first click.
+pub struct Generation {
+    start: (usize, usize),
+    pub attempts: Arc<AtomicU32>,
+    abort: Arc<AtomicBool>,
+    result: mpsc::Receiver<Option<Board>>,
+}
+
+impl Drop for Generation {
+    fn drop(&mut self) {
+        // stop the workers if the game is reset before they finish
+        self.abort.store(true, Ordering::Relaxed);
+    }
+}
+
+/// How the current game is played and what it counts for. Everything that
+/// differs between modes asks this rather than checking settings itself.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GameMode {
+    #[default]
+    Standard,
+    Zen,      // untimed and forgiving, and never recorded
+    TestPlay, // a board from the editor, tried out; never recorded or autosaved
+    Rematch,  // a recent board dealt again; recorded, but never a best
+}
+
+impl GameMode {
+    /// Whether finished games go into the history, records and leaderboard.
+    pub fn keeps_records(self) -> bool {
+        matches!(self, GameMode::Standard | GameMode::Rematch)
+    }
+
+    /// Whether a win can be a best time or go to the leaderboard. A
+    /// rematch can't: its mines were seen the first time round.
+    pub fn counts_for_bests(self) -> bool {
+        self == GameMode::Standard
+    }
+
+    /// Whether a mine gets flagged instead of ending the game, and any move
+    /// can be taken back.
+    pub fn forgiving(self) -> bool {
+        self == GameMode::Zen
+    }
+
+    /// Whether the game is kept in the autosave slot to resume later. A
+    /// test play or rematch isn't, as it would come back as a standard game.
+    pub fn resumable(self) -> bool {
+        matches!(self, GameMode::Standard | GameMode::Zen)
+    }
+
+    /// Whether a win gets confetti rather than a quiet message.
+    pub fn celebrates(self) -> bool {
+        matches!(self, GameMode::Standard | GameMode::Rematch)
+    }
+}
+
+/// Something that throws away the game on the board, held while the
+/// player is asked whether to abandon it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Discard {
+    NewGame,                // another board on the same settings
+    Difficulty(Difficulty), // a switch made mid-game
+    Menu(Difficulty),       // a game picked from the menu
+    TestPlay,               // the editor's board, from the editor
+    Rematch(u64),           // a recent board, by `Dealt::hash`
+}
+
+impl Discard {
+    fn reason(self) -> AbandonReason {
+        match self {
+            Discard::NewGame => AbandonReason::NewGame,
+            Discard::Difficulty(_) => AbandonReason::Difficulty,
+            Discard::Menu(_) | Discard::TestPlay | Discard::Rematch(_) => AbandonReason::Menu,
+        }
+    }
+}
+
+/// The post-game analysis, worked out in the background once asked for and
+/// kept until the next game.
+pub enum PostGame {
+    Pending(mpsc::Receiver<KillScreen>),
+    Ready(Box<KillScreen>),
+}
+
+/// The chance the fatal cell was a mine, for the loss banner: worked out
+/// in the background from the position before the click.
+pub enum FatalOdds {
+    Pending(mpsc::Receiver<Option<Chance>>),
+    Ready(Option<Chance>),
+}
+
+/// The endgame outlook for one board revision, worked out in the
+/// background; `None` inside when the position is too open to search.
+pub enum Endgame {
+    Pending(u64, mpsc::Receiver<Option<Outlook>>),
+    Ready(u64, Option<Outlook>),
+}
+
+/// Everything the screens read and change. Which screen is showing lives
+/// in `MinesweeperApp`; this is the game itself plus its settings.
+pub struct AppState {
+    pub board: Board,
+    pub visuals: CellVisuals, // how each cell looks, kept beside the board
+    pub difficulty: Difficulty,
+    pub particles: ParticleSystem,
+    pub visual_rng: StdRng, // all the effects' randomness, seeded for captures
+    pub board_seeds: Option<StdRng>, // deals boards in a fixed order, for recordings
+    pub earcons: Earcons,   // accessibility tones, when the settings turn them on
+    pub events: Vec<GameEvent>, // since the screen last drained them
+    pub feedback: Option<Feedback>,
+    pub toasts: Toasts,            // errors such as a failed save, until they fade
+    pub confirm_clear_flags: bool, // "Remove all flags?" is showing
+    pub pending_discard: Option<Discard>, // "Abandon this game?" is showing
+    pub covered: bool,             // a dialog is over the board: no clock, no input
+    pub editing_custom: bool,      // custom size row shown under the difficulty row
+    pub custom_width: usize,
+    pub custom_height: usize,
+    pub custom_mines: usize,
+    pub custom_symmetry: Option<Symmetry>, // "Advanced generation" for custom boards
+    pub custom_spaced: bool,               // no two mines side by side
+    pub custom_error: Option<BoardError>,  // why the custom row's Start refused
+    pub surprise: Option<Difficulty>,      // "Surprise me" pick shown before starting
+    pub seen_revision: u64,                // board revision whose dirty cells were drained
+    pub awaiting_first_click: bool,        // mines not placed until the first reveal
+    pub generation: Option<Generation>,
+    pub auto_open_due: bool, // the settings' first move is still to be made
+    pub auto_move: Option<AutoMove>, // the first move, if it was made for the player
+    pub used_reveal_remaining: bool, // game ended through "open all remaining"
+    pub reveal_times: RevealTimes,
+    pub flag_log: FlagLog,             // reviewed once the game is over
+    pub heatmap: Option<Heatmap>,      // end-of-game overlay, when switched on
+    pub final_position: Option<Board>, // board before the move that ended the game
+    pub post_game: Option<PostGame>,
+    pub fatal_odds: Option<FatalOdds>, // set as a click loses the game
+    pub endgame: Option<Endgame>,      // the latest outlook, when the settings ask for it
+    pub endgame_shown: bool,           // an outlook was on screen this game; wins are marked
+    pub objective: Tracker,            // a puzzle's clicks, flags and target; classic otherwise
+    pub show_analysis: bool,           // post-game analysis drawn instead of the board
+    pub input_stats: InputStats,
+    pub three_bv: usize,          // filled in when the game ends
+    pub coach: Option<Coach>,     // "Watch & learn" bot, while it has the board
+    pub coached: bool,            // the bot moved this game; kept out of the history
+    pub mode: GameMode,           // picked from the settings as each game starts
+    pub undo: Vec<BoardSnapshot>, // positions before each move, in forgiving modes
+    pub undo_from_deal: bool,     // the first of them is from before the first reveal
+    pub journal: Journal,         // this game's actions and events, for bug reports
+    pub history: History,
+    pub advisor: Advisor, // difficulty suggestion after the last game
+    pub log: logging::LogBuffer,
+    pub settings: Settings, // the active profile's, while it is active
+    pub profiles: Profiles,
+    pub profile_form: ProfileForm,
+    pub history_view: HistoryView,
+    pub data_transfer: DataTransfer,
+    pub leaderboard: leaderboard::Client,
+    pub race: RaceScreen,
+    pub spectate: SpectateScreen,
+    pub editor: EditorScreen,
+    pub theme_editor: ThemeEditor,
+    pub tutorial: Tutorial,
+    pub trainer: TrainerScreen,
+    pub versus: VersusScreen,
+    pub whats_new: Vec<Release>,   // notes the menu shows until dismissed
+    pub data_dir: Option<PathBuf>, // per-user game data; profiles live under it
+    pub replays: Option<ReplayStore>, // saved race results, under the data dir
+    pub played: PlayedBoards,      // race boards played before, per profile
+    pub autosave: Option<Autosave>,
+    pub crash_report: Option<PathBuf>,
+    pub recovered: Option<Session>, // unfinished game offered on the menu
+    pub clock: Clock,               // time spent on the current board, and undos
+    pub last_tick: f64,             // egui time of the previous frame
+    pub slept: bool,                // "Game paused while the system slept" is showing
+    pub calendar: Box<dyn Calendar>, // where today's date comes from
+    pub saved_revision: u64,        // board revision in the autosave slot
+    pub saved_at_secs: f64,         // clock time when it was written
+    pub minimized: Arc<AtomicBool>, // workers leave a minimized window asleep
+    pub cell_menu: Option<CellMenu>, // opened by a long press on a cell
+    pub quick_entry: QuickEntry,    // cell name typed so far
+    pub constraints: Option<Vec<(usize, usize)>>, // highlighted from the menu until a click
+    pub hints: HintLadder,
+    pub hint_note: Option<(u64, String)>, // the last hint, shown while the board revision holds
+    pub board_view: BoardView,            // zoom, scroll and keyboard cursor; kept across games
+    pub compact: CompactMode,             // the small always-on-top window, while it is up
+    pub notes: Annotations,               // scribbled while studying; kept with the autosave
+    pub annotating: bool,                 // clicks put notes down and the clock stops
+    pub note_pick: Option<Note>,          // what a click puts down; None rubs out
+    pub saved_notes: u64,                 // notes revision in the autosave slot
+}
+
+impl AppState {
+    pub fn new() -> Self {
+        let difficulty = Difficulty::Beginner;
+        let (width, height, mines) = difficulty.spec().params();
+        let board = Board::blank(width, height, mines);
+        Self {
+            visuals: CellVisuals::new(&board),
+            board,
+            difficulty,
+            particles: ParticleSystem::new(),
+            visual_rng: StdRng::from_entropy(),
+            board_seeds: None,
+            earcons: Earcons::default(),
+            events: Vec::new(),
+            feedback: None,
+            toasts: Toasts::default(),
+            confirm_clear_flags: false,
+            pending_discard: None,
+            covered: false,
+            editing_custom: false,
+            custom_width: 30,
+            custom_height: 16,
+            custom_mines: 99,
+            custom_symmetry: None,
+            custom_spaced: false,
+            custom_error: None,
+            surprise: None,
+            seen_revision: 0,
+            awaiting_first_click: true,
+            generation: None,
+            auto_open_due: false,
+            auto_move: None,
+            used_reveal_remaining: false,
+            reveal_times: RevealTimes::new(width),
+            flag_log: FlagLog::default(),
+            heatmap: None,
+            final_position: None,
+            post_game: None,
+            fatal_odds: None,
+            endgame: None,
+            endgame_shown: false,
+            objective: Tracker::default(),
+            show_analysis: false,
+            input_stats: InputStats::default(),
+            three_bv: 0,
+            coach: None,
+            coached: false,
+            mode: GameMode::default(),
+            undo: Vec::new(),
+            undo_from_deal: false,
+            journal: Journal::default(),
+            history: History::default(),
+            advisor: Advisor::default(),
+            log: logging::LogBuffer::default(),
+            settings: Settings::default(),
+            profiles: Profiles::default(),
+            profile_form: ProfileForm::default(),
+            history_view: HistoryView::default(),
+            data_transfer: DataTransfer::default(),
+            leaderboard: leaderboard::Client::default(),
+            race: RaceScreen::default(),
+            spectate: SpectateScreen::default(),
+            editor: EditorScreen::default(),
+            theme_editor: ThemeEditor::default(),
+            tutorial: Tutorial::new(),
+            trainer: TrainerScreen::default(),
+            versus: VersusScreen::default(),
+            whats_new: Vec::new(),
+            data_dir: None,
+            replays: None,
+            played: PlayedBoards::default(),
+            autosave: None,
+            crash_report: None,
+            recovered: None,
+            clock: Clock::default(),
+            last_tick: 0.0,
+            slept: false,
+            calendar: Box::new(SystemCalendar),
+            saved_revision: 0,
+            saved_at_secs: 0.0,
+            minimized: Arc::default(),
+            cell_menu: None,
+            quick_entry: QuickEntry::default(),
+            constraints: None,
+            hints: HintLadder::default(),
+            hint_note: None,
+            board_view: BoardView::default(),
+            compact: CompactMode::default(),
+            notes: Annotations::default(),
+            annotating: false,
+            note_pick: Some(PALETTE[0]),
+            saved_notes: 0,
+        }
+    }
+
+    /// Catch up with the board once the frame's moves are made: the cell
+    /// visuals follow the cells no move has taken yet, and if there were
+    /// any, they changed after the board was drawn, so another frame is
+    /// asked for to show them. A crash report gets the board as it is now.
+    pub fn sync_revision(&mut self, ctx: &egui::Context) {
+        if self.board.revision() == self.seen_revision {
+            return;
+        }
+        if !self.take_dirty().is_empty() {
+            ctx.request_repaint();
+        }
+        crash::note_board(&self.board);
+        self.seen_revision = self.board.revision();
+    }
+
+    /// The cells changed since the last call, once the cell visuals have
+    /// caught up with them.
+    fn take_dirty(&mut self) -> Vec<(usize, usize)> {
+        let dirty = self.board.take_dirty();
+        self.visuals.sync_from_events(&self.board, &[], &dirty);
+        dirty
+    }
+
+    /// Note `event` for the screen, and show it on the cells it is about.
+    fn event(&mut self, event: GameEvent) {
+        self.visuals.sync_from_events(&self.board, &[event], &[]);
+        self.events.push(event);
+        self.journal.event(event);
+    }
+
+    /// No-guess generation re-solves the board many times, so it is only
+    /// offered below this size.
+    pub fn no_guess_available(&self) -> bool {
+        let (w, h, _) = self.difficulty.spec().params();
+        w * h <= generate::MAX_CELLS
+    }
+
+    /// Whether the current board is generated no-guess on the first click.
+    fn no_guess(&self) -> bool {
+        self.settings.gameplay.no_guess && self.no_guess_available()
+    }
+
+    /// A fresh board at the current difficulty. The mines are placed on the
+    /// first reveal, around it, unless an opening is revealed right away.
+    pub fn reset(&mut self) {
+        let (w, h, m) = self.difficulty.spec().params();
+        self.generation = None;
+        self.awaiting_first_click = true;
+        self.board = Board::blank(w, h, m);
+        self.board
+            .set_max_flags(self.settings.gameplay.flag_limit.then_some(m));
+        self.visuals.reset(&self.board);
+        info!(
+            width = w,
+            height = h,
+            mines = m,
+            no_guess = self.no_guess(),
+            "new game"
+        );
+        self.particles.clear();
+        self.events.clear();
+        self.feedback = None;
+        self.confirm_clear_flags = false;
+        self.cell_menu = None;
+        self.quick_entry.clear();
+        self.constraints = None;
+        self.used_reveal_remaining = false;
+        self.reveal_times = RevealTimes::new(w);
+        self.flag_log = FlagLog::default();
+        self.hints = HintLadder::default();
+        self.hint_note = None;
+        self.heatmap = None;
+        self.clear_post_game();
+        self.input_stats = InputStats::default();
+        self.coach = None;
+        self.coached = false;
+        self.endgame = None;
+        self.endgame_shown = false;
+        self.objective = Tracker::default();
+        self.mode = self.chosen_mode();
+        self.undo.clear();
+        self.undo_from_deal = false;
+        self.journal.clear();
+        self.recovered = None;
+        self.clock = Clock::default();
+        self.saved_revision = 0;
+        self.saved_at_secs = 0.0;
+        self.notes = Annotations::default();
+        self.annotating = false;
+        self.saved_notes = 0;
+        self.auto_move = None;
+        self.auto_open_due = self.settings.gameplay.auto_open != AutoOpen::Off;
+    }
+
+    /// Make the first move for the player if the settings ask for it and
+    /// the game hasn't begun. It is kept as safe as a first click, and the
+    /// clock starts with it, or once a no-guess board is ready.
+    pub fn auto_open(&mut self, ctx: &egui::Context) {
+        if !std::mem::take(&mut self.auto_open_due) || !self.awaiting_first_click {
+            return;
+        }
+        let choice = self.settings.gameplay.auto_open;
+        let (w, h) = (self.board.width(), self.board.height());
+        let mut rng = rand::thread_rng();
+        let (x, y) = match choice {
+            AutoOpen::Off => return,
+            AutoOpen::Centre => (w / 2, h / 2),
+            AutoOpen::Random | AutoOpen::BestOpening => (rng.gen_range(0..w), rng.gen_range(0..h)),
+        };
+        self.auto_move = Some(AutoMove {
+            choice,
+            cell: (x, y),
+        });
+        info!(?choice, x, y, "auto-open");
+        if self.no_guess() {
+            // the move is made as the board is adopted
+            self.start_generation(ctx, x, y);
+        } else if let Some(board) = self.lay_mines(x, y) {
+            let (x, y) = self.first_cell(&board, (x, y));
+            self.awaiting_first_click = false;
+            self.adopt(board, x, y);
+        }
+    }
+
+    /// Where the first move goes on `board`, laid out around `start`: at
+    /// `start`, unless it is an automatic move to the best opening. On a
+    /// no-guess board the best opening is only taken if the solver can
+    /// still clear the board from it.
+    fn first_cell(&mut self, board: &Board, start: (usize, usize)) -> (usize, usize) {
+        let (radius, no_guess) = (self.settings.gameplay.safe_radius, self.no_guess());
+        let Some(auto) = self
+            .auto_move
+            .as_mut()
+            .filter(|auto| auto.choice == AutoOpen::BestOpening)
+        else {
+            return start;
+        };
+        let solvable = |cell| solver::solvable_from(board, cell, &|| false) == Some(true);
+        auto.cell = stats::best_opening(board, radius)
+            .filter(|&cell| !no_guess || solvable(cell))
+            .unwrap_or(start);
+        auto.cell
+    }
+
+    /// The mode the settings ask for in the next game.
+    fn chosen_mode(&self) -> GameMode {
+        if self.settings.gameplay.zen {
+            GameMode::Zen
+        } else {
+            GameMode::Standard
+        }
+    }
+
+    /// Options for a board of `difficulty`, with the custom row's symmetry
+    /// and spacing if it is a custom board.
+    pub fn board_options(&self, difficulty: Difficulty) -> BoardOptions {
+        let options = difficulty.options();
+        if !matches!(difficulty, Difficulty::Custom { .. }) {
+            return options;
+        }
+        let options = options.avoid_adjacent_mines(self.custom_spaced);
+        match self.custom_symmetry {
+            Some(symmetry) => options.symmetry(symmetry),
+            None => options,
+        }
+    }
+
+    /// The seed for the next board: the next of `board_seeds` when they
+    /// are fixed, else a fresh one.
+    fn board_seed(&mut self) -> u64 {
+        match &mut self.board_seeds {
+            Some(seeds) => seeds.gen(),
+            None => rand::thread_rng().gen(),
+        }
+    }
+
+    /// Place the mines with the safe radius around (x, y) kept clear, as
+    /// far as the density allows, and reveal it.
+    fn open_first(&mut self, x: usize, y: usize) {
+        let Some(board) = self.lay_mines(x, y) else {
+            return;
+        };
+        self.awaiting_first_click = false;
+        self.adopt(board, x, y);
+    }
+
+    /// A board for the current game with the safe radius around (x, y)
+    /// kept clear, as far as the density allows.
+    fn lay_mines(&mut self, x: usize, y: usize) -> Option<Board> {
+        // seeded, so a win can be rebuilt for the leaderboard and webhook
+        let seed = self.board_seed();
+        let radius = self.settings.gameplay.safe_radius;
+        // a shaped layout that won't fit around the click gives way to a free one
+        let board = [
+            self.board_options(self.difficulty),
+            self.difficulty.options(),
+        ]
+        .into_iter()
+        .find_map(|options| {
+            let options = options.safe_start(x, y).seed(seed);
+            (0..=radius)
+                .rev()
+                .find_map(|r| options.clone().safe_radius(r).build().ok())
+        });
+        if board.is_none() {
+            warn!(x, y, "no room for a safe first click");
+        }
+        board
+    }
+
+    /// Resume the session found in the autosave slot at startup.
+    pub fn recover(&mut self) {
+        let Some(session) = self.recovered.take() else {
+            return;
+        };
+        let board = session.board;
+        self.difficulty =
+            Difficulty::from_params(board.width(), board.height(), board.mine_count());
+        self.generation = None;
+        self.awaiting_first_click = false;
+        self.auto_open_due = false;
+        self.auto_move = None;
+        self.used_reveal_remaining = false;
+        // reveal times aren't autosaved, so the heatmap starts from here
+        self.reveal_times = RevealTimes::new(board.width());
+        self.flag_log = FlagLog::default();
+        self.hints = HintLadder::default();
+        self.hint_note = None;
+        self.heatmap = None;
+        self.clear_post_game();
+        self.input_stats = InputStats::default();
+        self.particles.clear();
+        self.events.clear();
+        self.feedback = None;
+        // the slot doesn't keep the mode, so the current choice applies
+        self.mode = self.chosen_mode();
+        self.undo.clear();
+        self.undo_from_deal = false;
+        self.clock.secs = session.elapsed_secs;
+        self.saved_revision = board.revision();
+        self.saved_at_secs = session.elapsed_secs;
+        self.board = board;
+        self.visuals.reset(&self.board);
+        self.journal.begin(&self.board); // the slot doesn't keep the moves
+        self.notes = session.annotations;
+        self.annotating = false;
+        self.saved_notes = self.notes.revision();
+        info!("recovered autosaved session");
+    }
+
+    /// Play the editor's board, opened from its start if it has one, as a
+    /// game that goes into no records.
+    fn test_play(&mut self) {
+        let puzzle = self.editor.puzzle();
+        info!(start = ?puzzle.start, "test play");
+        self.deal(puzzle, GameMode::TestPlay);
+    }
+
+    /// Deal the recent board `hash` again, opened from the cell it was
+    /// opened from before. It is recorded as a rematch, or not at all in
+    /// zen mode.
+    fn rematch(&mut self, hash: u64) {
+        let Some(dealt) = self.history.boards.iter().find(|dealt| dealt.hash == hash) else {
+            warn!(hash, "rematch of a board no longer kept");
+            return;
+        };
+        let board = match Board::decode_layout(&dealt.layout) {
+            Ok(board) => board,
+            Err(err) => {
+                self.toasts.error("couldn't deal that board again", &err);
+                return;
+            }
+        };
+        let start = Some(dealt.start);
+        let mode = match self.chosen_mode() {
+            GameMode::Standard => GameMode::Rematch,
+            mode => mode,
+        };
+        info!(hash, ?start, "rematch");
+        self.deal(
+            Puzzle {
+                start,
+                ..Puzzle::new(board)
+            },
+            mode,
+        );
+    }
+
+    /// Start `puzzle` as a game in `mode`, opened from its start if it has
+    /// one.
+    fn deal(&mut self, puzzle: Puzzle, mode: GameMode) {
+        let (board, objective) = (puzzle.board, puzzle.objective);
+        self.difficulty =
+            Difficulty::from_params(board.width(), board.height(), board.mine_count());
+        self.reset();
+        self.mode = mode;
+        self.auto_open_due = false;
+        self.awaiting_first_click = false;
+        self.objective = Tracker::new(objective);
+        match puzzle.start {
+            // opened for the player, but their first click all the same
+            Some((x, y)) => {
+                self.objective.click();
+                self.adopt(board, x, y);
+                self.judge_objective();
+            }
+            None => {
+                self.board = board;
+                self.visuals.reset(&self.board);
+                self.journal.begin(&self.board);
+            }
+        }
+    }
+
+    /// Keep the board just finished or given up for a rematch from the
+    /// menu's recent boards.
+    fn keep_board(&mut self) {
+        let Some(start) = self.opening() else {
+            return;
+        };
+        self.history.keep_board(Dealt {
+            hash: self.board.layout_hash(),
+            seed: self.board.seed(),
+            layout: self.board.encode_layout(),
+            start,
+        });
+    }
+
+    /// The cell the game was opened from: the first reveal, which `adopt`
+    /// logs first.
+    fn opening(&self) -> Option<(usize, usize)> {
+        match self.journal.actions().first() {
+            Some(&Action::Reveal(x, y)) => Some((x, y)),
+            _ => None,
+        }
+    }
+
+    /// The finished board's difficulty for the log and the rating.
+    fn board_difficulty(&self) -> Option<f64> {
+        let metrics = rating::Metrics::measure(&self.board, self.opening()?)?;
+        Some(metrics.difficulty())
+    }
+
+    /// Take over `profiles`, loading the active profile's data and slot.
+    pub fn use_profiles(&mut self, profiles: Profiles) {
+        self.profiles = profiles;
+        let data = self.profiles.data();
+        self.settings = data.settings.clone();
+        self.history = data.history.clone();
+        self.autosave = self.profile_autosave();
+        self.played = self.profile_played();
+    }
+
+    /// The active profile's autosave slot, when there is a data dir.
+    fn profile_autosave(&self) -> Option<Autosave> {
+        let id = self.profiles.data().id;
+        let dir = self.data_dir.as_deref()?;
+        Some(Autosave::new(
+            profiles::profile_dir(dir, id).join("autosave.bin"),
+        ))
+    }
+
+    /// The active profile's boards played before; one that is never
+    /// written when there is no data dir.
+    fn profile_played(&self) -> PlayedBoards {
+        let id = self.profiles.data().id;
+        match self.data_dir.as_deref() {
+            Some(dir) => PlayedBoards::open(profiles::profile_dir(dir, id).join("boards.json")),
+            None => PlayedBoards::default(),
+        }
+    }
+
+    /// Switch to another profile. A game in progress is kept in the old
+    /// profile's slot, and the new profile's saved game is offered for
+    /// recovery.
+    pub fn switch_profile(&mut self, ctx: &egui::Context, name: &str) {
+        if name == self.profiles.current() || !self.profiles.names().any(|n| n == name) {
+            return;
+        }
+        // an untouched board leaves the old slot as it was
+        let stored = match &self.autosave {
+            _ if !self.mode.resumable() => Ok(()),
+            Some(autosave) if self.board.is_over() => autosave.clear(),
+            Some(autosave) if self.board.revision() > 0 && !self.awaiting_first_click => {
+                autosave.save_annotated(&self.board, self.clock.secs, &self.notes)
+            }
+            _ => Ok(()),
+        };
+        if let Err(err) = stored {
+            let what = "couldn't keep the game for the old profile";
+            self.toasts.error(what, &err);
+        }
+
+        let live = (
+            std::mem::take(&mut self.settings),
+            std::mem::take(&mut self.history),
+        );
+        if let Some(data) = self.profiles.switch(name, live) {
+            self.settings = data.settings.clone();
+            self.history = data.history.clone();
+        }
+        self.advisor = Advisor::default();
+        self.settings.apply(ctx);
+        self.whats_new.clear();
+        self.check_whats_new();
+        self.autosave = self.profile_autosave();
+        self.played = self.profile_played();
+        self.reset();
+        self.recovered = self.read_recovered();
+        info!(profile = name, "switched profile");
+    }
+
+    /// Queue the release notes this profile hasn't seen for the menu, and
+    /// count them as seen, so each version's notes show once.
+    pub fn check_whats_new(&mut self) {
+        match changelog::bundled() {
+            Ok(changelog) => self.note_whats_new(changelog, &Version::current()),
+            Err(err) => warn!(%err, "the bundled changelog doesn't parse"),
+        }
+    }
+
+    fn note_whats_new(&mut self, changelog: &Changelog, current: &Version) {
+        let seen = self.settings.seen_version.parse::<Version>().ok();
+        if seen.as_ref().is_some_and(|seen| seen >= current) {
+            return; // seen already, or notes from a newer build
+        }
+        let unseen = changelog.unseen(seen.as_ref(), current);
+        if !unseen.is_empty() {
+            self.whats_new = unseen.into_iter().cloned().collect();
+        }
+        self.settings.seen_version = current.to_string();
+    }
+
+    /// The report the last run left when it crashed, until dismissed: open
+    /// its folder, or copy it to paste into a bug report.
+    fn crash_notice(&mut self, ctx: &egui::Context) {
+        let Some(path) = &self.crash_report else {
+            return;
+        };
+        let mut dismissed = false;
+        egui::Window::new("The last run crashed")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label("A crash report was saved on this computer. Nothing was sent.");
+                ui.monospace(path.display().to_string());
+                ui.horizontal(|ui| {
+                    if ui.button("Open folder").clicked() {
+                        if let Some(dir) = path.parent() {
+                            let url = format!("file://{}", dir.display());
+                            ctx.open_url(egui::OpenUrl::new_tab(url));
+                        }
+                    }
+                    if ui.button("Copy report").clicked() {
+                        match std::fs::read_to_string(path) {
+                            Ok(report) => ctx.copy_text(report),
+                            Err(err) => {
+                                self.toasts.error_at("couldn't read the report", path, &err)
+                            }
+                        }
+                    }
+                    dismissed = ui.button("Dismiss").clicked();
+                });
+            });
+        if dismissed {
+            self.crash_report = None;
+        }
+    }
+
+    /// The game paused while the machine slept, until the player carries
+    /// on; none of the time asleep counted.
+    fn sleep_notice(&mut self, ctx: &egui::Context) {
+        if !self.slept {
+            return;
+        }
+        let mut resume = false;
+        let modal = egui::Modal::new(egui::Id::new("sleep_notice")).show(ctx, |ui| {
+            ui.heading("Game paused while the system slept");
+            ui.label(format!(
+                "The clock stopped at {:.0}s and picks up from there.",
+                self.clock.secs
+            ));
+            resume = ui.button("Resume").clicked();
+        });
+        if resume || modal.should_close() {
+            self.slept = false;
+        }
+    }
+
+    /// Delete a profile other than the active one, with its files.
+    pub fn delete_profile(&mut self, name: &str) {
+        let Some(data) = self.profiles.delete(name) else {
+            return;
+        };
+        if let Some(dir) = &self.data_dir {
+            match std::fs::remove_dir_all(profiles::profile_dir(dir, data.id)) {
+                Err(err) if err.kind() != std::io::ErrorKind::NotFound => {
+                    self.toasts.error("couldn't remove profile files", &err);
+                }
+                _ => {}
+            }
+        }
+        info!(profile = name, "deleted profile");
+    }
+
+    /// Write a data bundle of every profile, the active one as it is now,
+    /// to `path`. An empty path or a folder is refused before anything is
+    /// written.
+    pub fn export_data(&self, path: &Path) -> std::io::Result<()> {
+        if path.as_os_str().is_empty() || path.is_dir() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "choose a file to export to, not a folder",
+            ));
+        }
+        let mut profiles = self.profiles.clone();
+        profiles.fold_live((&self.settings, &self.history));
+        std::fs::write(path, bundle::export(&profiles, self.data_dir.as_deref()))
+    }
+
+    /// Bring in a data bundle under `mode`, returning what changed. The
+    /// active profile picks up its new settings and history at once.
+    pub fn import_data(
+        &mut self,
+        ctx: &egui::Context,
+        import: Import,
+        mode: bundle::Mode,
+    ) -> Vec<String> {
+        self.profiles.fold_live((&self.settings, &self.history));
+        let report = import.apply(mode, &mut self.profiles, self.data_dir.as_deref());
+        // imported results land in the folder behind the index's back
+        if let Some(replays) = &mut self.replays {
+            *replays = ReplayStore::open(replays.dir().to_path_buf());
+        }
+        let data = self.profiles.data();
+        self.settings = data.settings.clone();
+        self.history = data.history.clone();
+        self.settings.apply(ctx);
+        if !self.in_progress() {
+            self.recovered = self.read_recovered();
+        }
+        info!(?mode, changes = report.len(), "imported data");
+        report
+    }
+
+    /// The game left in the active profile's slot, if any. A slot that
+    /// is there but can't be read is reported, not quietly passed over.
+    pub fn read_recovered(&mut self) -> Option<Session> {
+        match self.autosave.as_ref()?.read() {
+            Ok(session) => session,
+            Err(err) => {
+                self.toasts.error("couldn't recover the last game", &err);
+                None
+            }
+        }
+    }
+
+    pub fn discard_recovered(&mut self) {
+        self.recovered = None;
+        if let Some(Err(err)) = self.autosave.as_ref().map(Autosave::clear) {
+            self.toasts.error("couldn't remove the saved game", &err);
+        }
+    }
+
+    /// Count play time and write the autosave slot every `AUTOSAVE_SECS` of
+    /// play and whenever a game ends. The clock runs on while minimized.
+    pub fn tick_autosave(&mut self, ctx: &egui::Context, in_game: bool) {
+        let now = ctx.input(|i| i.time);
+        let playing = in_game
+            && !self.covered
+            && !self.annotating
+            && !self.is_settled()
+            && !self.awaiting_first_click
+            && self.generation.is_none();
+        if playing {
+            match clock::frame_gap(self.last_tick, now, self.is_minimized()) {
+                Some(gap) => self.clock.secs += gap,
+                None => {
+                    info!(gap = now - self.last_tick, "the system slept; game paused");
+                    self.slept = true;
+                }
+            }
+            // frames keep coming while the player sits still, so a long gap
+            // can only be the machine asleep
+            ctx.request_repaint_after(std::time::Duration::from_secs_f64(clock::TICK_SECS));
+        }
+        self.last_tick = now;
+
+        let Some(autosave) = self.autosave.as_ref().filter(|_| self.mode.resumable()) else {
+            return;
+        };
+        // notes are saved as they change, since the clock stops for them
+        let notes_changed = self.notes.revision() != self.saved_notes;
+        let due = self.board.is_over()
+            || notes_changed
+            || self.clock.secs - self.saved_at_secs >= AUTOSAVE_SECS;
+        let changed = notes_changed || self.board.revision() != self.saved_revision;
+        // a blank board has no mines yet, so there is nothing to resume
+        if !due || !changed || self.awaiting_first_click {
+            return;
+        }
+        if let Err(err) = autosave.save_annotated(&self.board, self.clock.secs, &self.notes) {
+            self.toasts
+                .error_at("couldn't save game", autosave.path(), &err);
+        }
+        self.saved_revision = self.board.revision();
+        self.saved_notes = self.notes.revision();
+        self.saved_at_secs = self.clock.secs;
+    }
+
+    /// Whether one of the game's own dialogs is up: a confirmation, the
+    /// custom size row, the crash notice or the notice that the game paused
+    /// while the machine slept. While one is, or the quit
+    /// prompt, the board is `covered`: its clock stops and it takes no
+    /// input, and both carry on as they were once it closes.
+    pub fn dialog_open(&self) -> bool {
+        self.confirm_clear_flags
+            || self.pending_discard.is_some()
+            || self.editing_custom
+            || self.crash_report.is_some()
+            || self.slept
+    }
+
+    pub fn is_minimized(&self) -> bool {
+        self.minimized.load(Ordering::Relaxed)
+    }
+
+    /// Note whether the window is minimized. Effects still running when it
+    /// goes are dropped, since nobody would see them finish.
+    pub fn set_minimized(&mut self, minimized: bool) {
+        let was = self.minimized.swap(minimized, Ordering::Relaxed);
+        if minimized && !was {
+            self.particles.clear();
+            info!("window minimized");
+        } else if was && !minimized {
+            info!("window restored");
+        }
+    }
+
+    /// A game has been started and not finished, so quitting would lose it.
+    pub fn in_progress(&self) -> bool {
+        self.board.revision() > 0 && !self.awaiting_first_click && !self.board.is_over()
+    }
+
+    /// Write the autosave slot now rather than waiting for the next tick.
+    pub fn save_now(&mut self) -> std::io::Result<()> {
+        let Some(autosave) = self.autosave.as_ref().filter(|_| self.mode.resumable()) else {
+            return Ok(());
+        };
+        autosave.save_annotated(&self.board, self.clock.secs, &self.notes)?;
+        self.saved_revision = self.board.revision();
+        self.saved_notes = self.notes.revision();
+        self.saved_at_secs = self.clock.secs;
+        Ok(())
+    }
+
+    pub fn set_difficulty(&mut self, difficulty: Difficulty) {
+        self.difficulty = difficulty;
+        self.reset();
+    }
+
+    /// Whether the game on the board goes into the history when it ends.
+    fn keeps_records(&self) -> bool {
+        !self.coached && self.mode.keeps_records()
+    }
+
+    /// Throw away the game on the board for `discard`, first asking the
+    /// player when it is a recorded game in progress and the settings say
+    /// to ask.
+    pub fn discard(&mut self, discard: Discard) {
+        let ask = self.settings.gameplay.confirm_abandon;
+        if ask && self.in_progress() && self.keeps_records() {
+            self.pending_discard = Some(discard);
+        } else {
+            self.carry_out(discard);
+        }
+    }
+
+    /// Do what `discard` asked for, abandoning the game in progress.
+    pub fn carry_out(&mut self, discard: Discard) {
+        self.pending_discard = None;
+        self.abandon(discard.reason());
+        match discard {
+            Discard::NewGame => self.reset(),
+            Discard::Difficulty(difficulty) | Discard::Menu(difficulty) => {
+                self.set_difficulty(difficulty)
+            }
+            Discard::TestPlay => self.test_play(),
+            Discard::Rematch(hash) => self.rematch(hash),
+        }
+    }
+
+    /// Give up the game in progress for `reason`, if there is one. A
+    /// recorded game is counted as abandoned, and logged as a loss unless
+    /// the settings say abandoned games don't count.
+    pub fn abandon(&mut self, reason: AbandonReason) {
+        if !self.in_progress() {
+            return;
+        }
+        self.input_stats.finish(&self.board);
+        info!(
+            reason = reason.label(),
+            secs = self.clock.secs,
+            "game abandoned"
+        );
+        if !self.keeps_records() {
+            return;
+        }
+        self.history.abandoned += 1;
+        if !self.settings.gameplay.abandon_is_loss {
+            return;
+        }
+        self.three_bv = stats::three_bv(&self.board);
+        let efficiency = self.input_stats.efficiency(self.three_bv).unwrap_or(0.0);
+        let size = self.difficulty.spec().params();
+        self.history.add(&self.input_stats, efficiency);
+        self.history.remember(Outcome { size, won: false });
+        self.history.log_game(Game {
+            day: self.calendar.today(),
+            size,
+            won: false,
+            secs: self.clock.secs,
+            three_bv: self.three_bv,
+            assisted: false,
+            abandoned: true,
+            undone: self.clock.used_undo,
+            board: Some(self.board.layout_hash()),
+            rematch: self.mode == GameMode::Rematch,
+            difficulty: self.board_difficulty(),
+        });
+        self.keep_board();
+    }
+
+    pub fn back_to_menu(&mut self) {
+        self.particles.clear();
+        self.events.clear();
+        // keep last selected difficulty
+    }
+
+    /// A repaint request for worker threads that does nothing while the
+    /// window is minimized; results are picked up on the first frame after
+    /// it is restored.
+    pub fn waker(&self, ctx: &egui::Context) -> impl Fn() + Send + 'static {
+        let (minimized, ctx) = (self.minimized.clone(), ctx.clone());
+        move || {
+            if !minimized.load(Ordering::Relaxed) {
+                ctx.request_repaint();
+            }
+        }
+    }
+
+    /// Kick off background no-guess generation around the first click.
+    pub fn start_generation(&mut self, ctx: &egui::Context, x: usize, y: usize) {
+        let options = self.board_options(self.difficulty);
+        let attempts = Arc::new(AtomicU32::new(0));
+        let abort = Arc::new(AtomicBool::new(false));
+        let (tx, rx) = mpsc::channel();
+        let seed = self.board_seed();
+
+        let (worker_attempts, worker_abort) = (attempts.clone(), abort.clone());
+        let wake = self.waker(ctx);
+        std::thread::spawn(move || {
+            let progress = |attempt: u32| {
+                worker_attempts.fetch_max(attempt, Ordering::Relaxed);
+                wake();
+            };
+            let result = generate::no_guess_with(&options, (x, y), seed, &worker_abort, &progress)
+                .ok()
+                .flatten();
+            let _ = tx.send(result);
+            wake();
+        });
+
+        self.awaiting_first_click = false;
+        self.generation = Some(Generation {
+            start: (x, y),
+            attempts,
+            abort,
+            result: rx,
+        });
+    }
+
+    /// Swap in the generated board once it is ready and open the first cell.
+    pub fn poll_generation(&mut self) {
+        let Some(generation) = &self.generation else {
+            return;
+        };
+        let Ok(result) = generation.result.try_recv() else {
+            return;
+        };
+        let (x, y) = generation.start;
+        self.generation = None;
+        match result {
+            Some(board) => {
+                let (x, y) = self.first_cell(&board, (x, y));
+                self.adopt(board, x, y);
+            }
+            // no luck within the attempt budget: at least keep the start safe
+            None => self.open_first(x, y),
+        }
+    }
+
+    /// Swap the blank board for `board`, keeping any flags and question
+    /// marks placed while waiting, and open the first cell.
+    fn adopt(&mut self, mut board: Board, x: usize, y: usize) {
+        board.set_max_flags(self.board.max_flags());
+        for fy in 0..board.height() {
+            for fx in 0..board.width() {
+                let marking = self.board.cell(fx, fy).marking();
+                if marking != Marking::None && (fx, fy) != (x, y) {
+                    board.set_marking(fx, fy, marking);
+                }
+            }
+        }
+        self.board = board;
+        self.visuals.reset(&self.board);
+        self.journal.begin(&self.board);
+        self.journal.record(Action::Reveal(x, y));
+        // so the first reveal can be undone too, back to a game not begun
+        if self.mode.forgiving() {
+            self.undo.push(self.board.snapshot());
+            self.undo_from_deal = true;
+        }
+        self.reveal(x, y);
+    }
+
+    /// Reveal a cell for the player, logging the action and its outcome.
+    fn reveal(&mut self, x: usize, y: usize) -> RevealResult {
+        let automatic = self.board.revealed_safe() == 0
+            && self.auto_move.is_some_and(|auto| auto.cell == (x, y));
+        let before = self.board.snapshot();
+        let result = self.board.reveal_cell(x, y);
+        if result == RevealResult::Exploded && self.mode.forgiving() {
+            self.defuse(&before, x, y);
+        }
+        let rings = self.board.last_reveal_wavefronts();
+        let cells = self.record_reveals(Some((x, y)), &rings);
+        if !automatic {
+            self.input_stats.record_reveal(result);
+        }
+        info!(x, y, ?result, automatic, "reveal");
+        match result {
+            RevealResult::Exploded if self.board.is_over() => {
+                self.event(GameEvent::Exploded(x, y));
+            }
+            RevealResult::Revealed | RevealResult::Won if cells >= LARGE_OPENING => {
+                self.event(GameEvent::Opened { x, y, cells });
+            }
+            _ => {}
+        }
+        if self.board.is_over() && matches!(result, RevealResult::Exploded | RevealResult::Won) {
+            self.final_position = self.position_before(&before);
+            if result == RevealResult::Exploded {
+                self.start_fatal_odds(x, y);
+            }
+            self.finish_game((x, y));
+        }
+        result
+    }
+
+    /// A forgiving mode's answer to a mine at (x, y): take the move back
+    /// and flag the mine instead.
+    fn defuse(&mut self, before: &BoardSnapshot, x: usize, y: usize) {
+        if self.board.restore(before).is_ok() {
+            self.board.set_marking(x, y, Marking::Flag);
+            info!(x, y, "defused");
+        }
+    }
+
+    /// In a forgiving mode, the position to go back to if the move about
+    /// to be made changes anything.
+    fn undo_point(&self) -> Option<(u64, BoardSnapshot)> {
+        let undoable = self.mode.forgiving() && !self.awaiting_first_click;
+        undoable.then(|| (self.board.revision(), self.board.snapshot()))
+    }
+
+    /// Keep `point` for `undo` if the board moved on from it.
+    fn keep_undo_point(&mut self, point: Option<(u64, BoardSnapshot)>) {
+        if let Some((revision, snapshot)) = point {
+            if self.board.revision() != revision {
+                self.undo.push(snapshot);
+            }
+        }
+    }
+
+    /// Take back the last move, in a forgiving mode, even one that won.
+    /// The clock keeps running as `Clock` says; taking back the first
+    /// reveal leaves the game waiting for a first click again, which deals
+    /// new mines.
+    pub fn undo(&mut self) {
+        let Some(snapshot) = self.undo.pop() else {
+            return;
+        };
+        self.journal.record(Action::Undo);
+        let flagged = self.flagged_cells();
+        if self.board.restore(&snapshot).is_ok() {
+            self.log_flags_since(&flagged);
+            self.take_dirty();
+            self.heatmap = None;
+            self.clear_post_game();
+            let to_start = self.undo.is_empty() && self.undo_from_deal;
+            self.clock.undo(to_start);
+            if to_start {
+                self.undo_from_deal = false;
+                self.awaiting_first_click = true;
+                self.auto_move = None;
+                self.reveal_times = RevealTimes::new(self.board.width());
+            }
+            info!(left = self.undo.len(), to_start, "undo");
+        }
+    }
+
+    /// The board as it stood at `before`, for the post-game analysis.
+    fn position_before(&self, before: &BoardSnapshot) -> Option<Board> {
+        let mut position = self.board.clone();
+        position.restore(before).ok()?;
+        Some(position)
+    }
+
+    /// Timestamp the cells the last action revealed, `clicked` first, play
+    /// their `rings` as a cascade, and return how many there were.
+    fn record_reveals(
+        &mut self,
+        clicked: Option<(usize, usize)>,
+        rings: &[Vec<(usize, usize)>],
+    ) -> usize {
+        let mut cells = self.take_dirty();
+        if let Some(i) = cells.iter().position(|&cell| Some(cell) == clicked) {
+            cells[..=i].rotate_right(1);
+        }
+        let millis = (self.clock.secs * 1000.0) as u32;
+        let before = self.reveal_times.len();
+        self.reveal_times.record(&self.board, &cells, millis);
+        let opened = self.reveal_times.len() - before;
+        if self.settings.accessibility.animations {
+            self.visuals.cascade(rings);
+        }
+        self.cue_reveals(&cells, opened);
+        opened
+    }
+
+    /// The earcon for `opened` cells among `cells`: a lone number's pitch,
+    /// or a sweep for a cascade. A mine that went off has none.
+    fn cue_reveals(&mut self, cells: &[(usize, usize)], opened: usize) {
+        if opened == 0 {
+            return;
+        }
+        let Some(&(x, y)) = cells
+            .iter()
+            .find(|&&(x, y)| self.board.cell(x, y).is_revealed())
+        else {
+            return;
+        };
+        let earcon = match self.board.cell(x, y).neighbor_mines() {
+            Some(n) if n > 0 && opened == 1 => Earcon::Number(n),
+            Some(_) => Earcon::Cascade(opened),
+            None => return,
+        };
+        self.cue(earcon, x);
+    }
+
+    /// Play `earcon` for a cell in column `x`, if the settings want them.
+    fn cue(&mut self, earcon: Earcon, x: usize) {
+        let access = &self.settings.accessibility;
+        if !access.earcons {
+            return;
+        }
+        let pan = if access.earcon_pan {
+            earcons::pan(x, self.board.width())
+        } else {
+            0.0
+        };
+        self.earcons.play(earcon, pan);
+    }
+
+    /// Switch the end-of-game heatmap on or off.
+    pub fn set_heatmap(&mut self, on: bool) {
+        self.show_analysis &= !on;
+        self.heatmap =
+            on.then(|| Heatmap::new(&self.reveal_times, self.board.width() * self.board.height()));
+    }
+
+    /// "Open all remaining" is offered once there is a flag per mine.
+    pub fn can_reveal_remaining(&self) -> bool {
+        // nothing is staked in a forgiving game
+        !self.mode.forgiving()
+            && !self.is_settled()
+            && !self.awaiting_first_click
+            && self.generation.is_none()
+            && self.board.flags() == self.board.mine_count()
+    }
+
+    fn clear_post_game(&mut self) {
+        self.final_position = None;
+        self.post_game = None;
+        self.fatal_odds = None;
+        self.show_analysis = false;
+    }
+
+    /// Analyse the position before the final move on a worker thread, once
+    /// per game.
+    pub fn start_analysis(&mut self, ctx: &egui::Context) {
+        let Some(position) = self.final_position.clone() else {
+            return;
+        };
+        if self.post_game.is_some() {
+            return;
+        }
+        let (tx, rx) = mpsc::channel();
+        let wake = self.waker(ctx);
+        std::thread::spawn(move || {
+            let _ = tx.send(KillScreen::new(position));
+            wake();
+        });
+        self.post_game = Some(PostGame::Pending(rx));
+    }
+
+    /// The finished analysis, picking it up from the worker if it just
+    /// arrived.
+    pub fn kill_screen(&mut self) -> Option<&KillScreen> {
+        if let Some(PostGame::Pending(rx)) = &self.post_game {
+            if let Ok(done) = rx.try_recv() {
+                self.post_game = Some(PostGame::Ready(Box::new(done)));
+            }
+        }
+        match &self.post_game {
+            Some(PostGame::Ready(kill_screen)) => Some(kill_screen),
+            _ => None,
+        }
+    }
+
+    /// Work out on a worker thread how likely (x, y) was to be a mine in
+    /// the final position. The banner polls for it, so no waker is needed.
+    fn start_fatal_odds(&mut self, x: usize, y: usize) {
+        let Some(position) = self.final_position.clone() else {
+            return;
+        };
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = tx.send(odds::chance(&position, x, y));
+        });
+        self.fatal_odds = Some(FatalOdds::Pending(rx));
+    }
+
+    /// The fatal cell's odds, picking them up from the worker if they just
+    /// arrived.
+    pub fn fatal_odds(&mut self) -> Option<&FatalOdds> {
+        if let Some(FatalOdds::Pending(rx)) = &self.fatal_odds {
+            match rx.try_recv() {
+                Ok(chance) => self.fatal_odds = Some(FatalOdds::Ready(chance)),
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    self.fatal_odds = Some(FatalOdds::Ready(None));
+                }
+                Err(mpsc::TryRecvError::Empty) => {}
+            }
+        }
+        self.fatal_odds.as_ref()
+    }
+
+    /// The outlook for the position as it stands, if the settings ask for
+    /// one and few enough cells are in question, starting it on a worker
+    /// the first time each board revision asks. Once one is returned, the
+    /// game counts as assisted.
+    pub fn endgame_outlook(&mut self, ctx: &egui::Context) -> Option<Outlook> {
+        let gameplay = &self.settings.gameplay;
+        let limit = gameplay.endgame_cells.min(MAX_ENDGAME_CELLS);
+        let board = &self.board;
+        // every mine could be among the hidden cells, so at least this many
+        // are in question; past the limit there is no need to look closer
+        let hidden = board.width() * board.height() - board.revealed_safe();
+        if !gameplay.endgame_outlook
+            || self.awaiting_first_click
+            || board.is_over()
+            || hidden.saturating_sub(board.mine_count()) > limit
+        {
+            return None;
+        }
+        let revision = board.revision();
+        match &self.endgame {
+            Some(Endgame::Pending(at, rx)) if *at == revision => match rx.try_recv() {
+                Ok(outlook) => self.endgame = Some(Endgame::Ready(revision, outlook)),
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    self.endgame = Some(Endgame::Ready(revision, None));
+                }
+                Err(mpsc::TryRecvError::Empty) => {}
+            },
+            Some(Endgame::Ready(at, _)) if *at == revision => {}
+            _ => {
+                let (tx, rx) = mpsc::channel();
+                let (position, wake) = (board.clone(), self.waker(ctx));
+                std::thread::spawn(move || {
+                    let _ = tx.send(endgame::outlook(&position, limit));
+                    wake();
+                });
+                self.endgame = Some(Endgame::Pending(revision, rx));
+            }
+        }
+        let Some(Endgame::Ready(_, Some(outlook))) = self.endgame else {
+            return None;
+        };
+        self.endgame_shown = true;
+        Some(outlook)
+    }
+
+    /// Reveal every unflagged cell at once, staking the game on the flags.
+    pub fn reveal_remaining(&mut self) {
+        self.journal.record(Action::RevealRemaining);
+        let before = self.board.snapshot();
+        let result = self.board.reveal_remaining();
+        let rings = self.board.last_reveal_wavefronts();
+        self.record_reveals(None, &rings);
+        self.input_stats.record_reveal(result);
+        info!(?result, "reveal remaining");
+        if matches!(result, RevealResult::Exploded | RevealResult::Won) {
+            self.used_reveal_remaining = true;
+            self.final_position = self.position_before(&before);
+            let centre = (self.board.width() / 2, self.board.height() / 2);
+            self.finish_game(centre);
+        }
+    }
+
+    /// Take every flag off the board, to start over on a tangle of them.
+    pub fn clear_flags(&mut self) {
+        let flagged = self.flagged_cells();
+        let removed = self.board.clear_flags();
+        self.log_flags_since(&flagged);
+        self.journal.record(Action::ClearFlags);
+        info!(removed, "cleared flags");
+    }
+
+    /// The player's moves so far, for numbering flag changes.
+    fn move_number(&self) -> u32 {
+        self.input_stats.left_clicks + self.input_stats.right_clicks
+    }
+
+    fn log_flag(&mut self, cell: (usize, usize), placed: bool) {
+        let millis = (self.clock.secs * 1000.0) as u32;
+        self.flag_log
+            .record(cell, placed, self.move_number(), Some(millis));
+    }
+
+    /// Every flagged cell, in row-major order.
+    fn flagged_cells(&self) -> Vec<(usize, usize)> {
+        let board = &self.board;
+        (0..board.height())
+            .flat_map(|y| (0..board.width()).map(move |x| (x, y)))
+            .filter(|&(x, y)| board.cell(x, y).is_flagged())
+            .collect()
+    }
+
+    /// Log the flags that came and went since `before` was flagged, for
+    /// changes made to many cells at once.
+    fn log_flags_since(&mut self, before: &[(usize, usize)]) {
+        let after = self.flagged_cells();
+        for &cell in before {
+            if after
+                .binary_search_by_key(&(cell.1, cell.0), |&(x, y)| (y, x))
+                .is_err()
+            {
+                self.log_flag(cell, false);
+            }
+        }
+        for &cell in &after {
+            if before
+                .binary_search_by_key(&(cell.1, cell.0), |&(x, y)| (y, x))
+                .is_err()
+            {
+                self.log_flag(cell, true);
+            }
+        }
+    }
+
+    /// Settle this game's statistics and add them to the lifetime totals.
+    /// `last` is the cell that ended it, which a win's flags spread from.
+    fn finish_game(&mut self, last: (usize, usize)) {
+        self.input_stats.finish(&self.board);
+        self.three_bv = stats::three_bv(&self.board);
+        let efficiency = self.input_stats.efficiency(self.three_bv).unwrap_or(0.0);
+        if self.keeps_records() {
+            let (size, won) = (self.difficulty.spec().params(), self.board.is_won());
+            let assisted = self.endgame_shown
+                || self
+                    .auto_move
+                    .is_some_and(|auto| auto.choice == AutoOpen::BestOpening);
+            self.history.add(&self.input_stats, efficiency);
+            self.history.remember(Outcome { size, won });
+            self.history.log_game(Game {
+                day: self.calendar.today(),
+                size,
+                won,
+                secs: self.clock.secs,
+                three_bv: self.three_bv,
+                assisted,
+                abandoned: false,
+                undone: self.clock.used_undo,
+                board: Some(self.board.layout_hash()),
+                rematch: self.mode == GameMode::Rematch,
+                difficulty: self.board_difficulty(),
+            });
+            self.keep_board();
+            let counts = self.clock.counts_for_bests() && self.mode.counts_for_bests();
+            let best = won && counts && self.history.record_win(size, self.clock.secs, assisted);
+            self.notify(won, best);
+            // the leaderboard has no way to mark a time, so it isn't sent one
+            if won && !assisted && counts {
+                self.submit_score();
+            }
+            let no_guess_offer = self.no_guess_available() && !self.settings.gameplay.no_guess;
+            self.advisor
+                .update(&self.history.recent, self.difficulty, no_guess_offer);
+        }
+        if self.board.is_won() && self.mode.celebrates() {
+            self.event(GameEvent::Won(last.0, last.1));
+        }
+        info!(win = self.board.is_won(), stats = ?self.input_stats, efficiency, "game over");
+    }
+
+    /// Post the finished game to the webhook, if it is set up for this
+    /// kind of result.
+    fn notify(&self, won: bool, best: bool) {
+        let webhook = &self.settings.advanced.webhook;
+        let Some(event) = webhook.event(won, best) else {
+            return;
+        };
+        let (width, height, mines) = self.difficulty.spec().params();
+        let secs = self.clock.secs;
+        let payload = Payload {
+            event,
+            difficulty: self.difficulty.label().to_string(),
+            width,
+            height,
+            mines,
+            secs,
+            three_bv_per_sec: if secs > 0.0 {
+                self.three_bv as f64 / secs
+            } else {
+                0.0
+            },
+            seed: self.board.seed(),
+            profile: self.profiles.current().to_string(),
+        };
+        notify::send(webhook.url.clone(), payload);
+    }
+
+    /// Queue a win for the online leaderboard, if the player takes part
+    /// and the board has a table: a preset, with a seed to check it by.
+    fn submit_score(&mut self) {
+        let settings = &self.settings.advanced.leaderboard;
+        let (Some(spec), Some(seed)) = (leaderboard::spec(self.difficulty), self.board.seed())
+        else {
+            return;
+        };
+        if !settings.ready() {
+            return;
+        }
+        self.leaderboard.submit(Submission {
+            spec,
+            name: settings.name.trim().to_string(),
+            time_ms: (self.clock.secs * 1000.0).round() as u64,
+            bbbv: self.three_bv,
+            seed,
+            layout_hash: race::layout_hash(&self.board),
+            move_log_hash: format!("{:016x}", self.reveal_times.hash()),
+        });
+    }
+
+    /// Send queued leaderboard scores, waking again when a retry is due.
+    pub fn tick_leaderboard(&mut self, ctx: &egui::Context) {
+        let now = ctx.input(|i| i.time);
+        let wake = self.waker(ctx);
+        let settings = &self.settings.advanced.leaderboard;
+        if let Some(wait) = self.leaderboard.tick(settings, now, wake) {
+            ctx.request_repaint_after_secs(wait as f32);
+        }
+    }
+
+    /// Apply a click from the board widget. Clicking while the bot plays
+    /// takes the game over from it.
+    pub fn handle_click(&mut self, ctx: &egui::Context, click: CellClick) {
+        let click = if self.settings.controls.swap_buttons {
+            click.swapped()
+        } else {
+            click
+        };
+        self.handle_key(ctx, click);
+    }
+
+    /// A move from the keyboard cursor, which swapped buttons leave alone.
+    pub fn handle_key(&mut self, ctx: &egui::Context, click: CellClick) {
+        if self.generation.is_some() || self.objective.is_decided() {
+            return;
+        }
+        self.coach = None;
+        self.play(ctx, click);
+    }
+
+    /// Let the "Watch & learn" bot move if its rationale has been up long
+    /// enough, or at once for `step`.
+    pub fn tick_coach(&mut self, ctx: &egui::Context, step: bool) {
+        let Some(coach) = &mut self.coach else {
+            return;
+        };
+        if self.generation.is_some() {
+            return;
+        }
+        let now = ctx.input(|i| i.time);
+        let click = coach.next_move(&self.board, self.awaiting_first_click, now, step);
+        if let Some(wait) = coach.wait(now) {
+            ctx.request_repaint_after(std::time::Duration::from_secs_f64(wait));
+        }
+        if let Some(click) = click {
+            self.coached = true;
+            self.play(ctx, click);
+        }
+        if self.board.is_over() {
+            self.coach = None;
+        }
+    }
+
+    /// Make a move, whoever it comes from.
+    fn play(&mut self, ctx: &egui::Context, click: CellClick) {
+        let undo_point = self.undo_point();
+        match click {
+            // Left click = reveal; the first one places the mines
+            CellClick::Reveal(x, y)
+                if self.awaiting_first_click && !self.board.cell(x, y).is_flagged() =>
+            {
+                self.objective.click();
+                if self.no_guess() {
+                    self.start_generation(ctx, x, y);
+                } else {
+                    self.open_first(x, y);
+                }
+            }
+            CellClick::Reveal(x, y) => {
+                self.journal.record(Action::Reveal(x, y));
+                match self.reveal(x, y) {
+                    RevealResult::Ignored(reason) => self.show_feedback(ctx, reason, x, y),
+                    _ => self.objective.click(),
+                }
+            }
+            // Right click = flag, or question mark if those are on
+            CellClick::Flag(x, y) => {
+                self.journal.record(Action::Flag(x, y));
+                let mode = if self.settings.gameplay.question_marks {
+                    CycleMode::FlagsAndQuestions
+                } else {
+                    CycleMode::Flags
+                };
+                let result = self.board.cycle_marking(x, y, mode);
+                if let MarkingResult::Changed {
+                    to: Marking::Flag, ..
+                } = result
+                {
+                    self.objective.flag();
+                }
+                self.marked(ctx, x, y, result);
+            }
+        }
+        self.judge_objective();
+        self.keep_undo_point(undo_point);
+    }
+
+    /// Whether play on this board is done: it is won or lost, or its
+    /// puzzle objective was met or failed.
+    pub fn is_settled(&self) -> bool {
+        self.board.is_over() || self.objective.is_decided()
+    }
+
+    /// Hold the game to its puzzle objective after a move. One met or
+    /// failed ends play where the board stands.
+    fn judge_objective(&mut self) {
+        if self.objective.objective().is_classic() || self.objective.is_decided() {
+            return;
+        }
+        let verdict = self.objective.check(&self.board);
+        if verdict != Verdict::Open {
+            info!(
+                ?verdict,
+                clicks = self.objective.clicks(),
+                "objective decided"
+            );
+        }
+    }
+
+    /// Carry out a journaled action again, as a bug report replays it.
+    pub fn replay(&mut self, ctx: &egui::Context, action: Action) {
+        match action {
+            Action::Reveal(x, y) => self.play(ctx, CellClick::Reveal(x, y)),
+            Action::Flag(x, y) => self.play(ctx, CellClick::Flag(x, y)),
+            Action::Mark(x, y, mark) => self.mark(ctx, x, y, mark.into()),
+            Action::Chord(x, y) => self.chord(x, y),
+            Action::ClearFlags => self.clear_flags(),
+            Action::RevealRemaining => self.reveal_remaining(),
+            Action::Undo => self.undo(),
+            Action::Hint => self.journal.record(action), // its move follows
+        }
+    }
+
+    /// Save a bug report bundle of the game in progress, saying where on
+    /// stdout and in a toast.
+    pub fn save_bug_report(&mut self) {
+        match repro::save(self) {
+            Ok(path) => {
+                println!("saved bug report to {}", path.display());
+                self.toasts
+                    .notice("Saved a bug report bundle", path.display().to_string());
+            }
+            Err(err) => self.toasts.error("couldn't save the bug report", &err),
+        }
+    }
+
+    /// Take the next rung of the hint ladder, adding its penalty to the
+    /// clock: outline the region, then the deciding numbers, then play the
+    /// answer.
+    pub fn hint(&mut self, ctx: &egui::Context) {
+        if self.awaiting_first_click || self.generation.is_some() || self.is_settled() {
+            return;
+        }
+        self.coach = None;
+        let Some(hint) = self.hints.next(&self.board) else {
+            let note = "Nothing is certain from here, so it's a guess.".to_string();
+            self.hint_note = Some((self.board.revision(), note));
+            return;
+        };
+        let (level, note) = (hint.level(), hint.to_string());
+        self.journal.record(Action::Hint);
+        self.clock.secs += f64::from(PENALTY_SECS[level - 1]);
+        info!(level, "hint");
+        match hint {
+            Hint::Answer {
+                cell: (x, y),
+                mine: true,
+            } => self.mark(ctx, x, y, Marking::Flag),
+            Hint::Answer {
+                cell: (x, y),
+                mine: false,
+            } => {
+                if self.board.cell(x, y).is_flagged() {
+                    self.mark(ctx, x, y, Marking::None);
+                }
+                self.play(ctx, CellClick::Reveal(x, y));
+            }
+            Hint::Region(cells) | Hint::Numbers(cells) => self.constraints = Some(cells),
+        }
+        self.hint_note = Some((self.board.revision(), note));
+    }
+
+    /// Put the picked note on (x, y), or take it off if it is already
+    /// there; with no note picked, rub out whatever is there.
+    pub fn annotate(&mut self, x: usize, y: usize) {
+        match self.note_pick {
+            Some(note) => {
+                self.notes.toggle(x, y, note);
+            }
+            None => self.notes.erase(x, y),
+        }
+    }
+
+    /// Put `marking` on (x, y), as picked from the cell's menu.
+    pub fn mark(&mut self, ctx: &egui::Context, x: usize, y: usize, marking: Marking) {
+        self.coach = None;
+        let undo_point = self.undo_point();
+        self.journal.record(Action::Mark(x, y, marking.into()));
+        let result = self.board.set_marking(x, y, marking);
+        self.marked(ctx, x, y, result);
+        self.keep_undo_point(undo_point);
+    }
+
+    /// Bookkeeping after a marking action, however it was made.
+    fn marked(&mut self, ctx: &egui::Context, x: usize, y: usize, result: MarkingResult) {
+        self.input_stats.record_marking(result);
+        if let MarkingResult::Changed { from, to } = result {
+            if from == Marking::Flag || to == Marking::Flag {
+                let flagged = to == Marking::Flag;
+                self.log_flag((x, y), flagged);
+                let earcon = if !flagged {
+                    Earcon::Unflagged
+                } else if self.board.flags_misplaced() {
+                    // every hidden cell flagged yet unwon, so a flag is wrong
+                    Earcon::Warning
+                } else {
+                    Earcon::Flagged
+                };
+                self.cue(earcon, x);
+            }
+        }
+        info!(x, y, ?result, "mark");
+        if result.flagged() && self.board.all_mines_flagged() {
+            self.event(GameEvent::FinalFlag(x, y));
+        }
+        if let MarkingResult::Ignored(reason) = result {
+            self.show_feedback(ctx, reason, x, y);
+        }
+        if result.flagged() && self.settings.gameplay.open_on_number {
+            self.open_on_number(x, y);
+        }
+    }
+
+    /// Open the hidden neighbours of the number at (x, y) once it has all
+    /// its flags. One chord counts as one reveal click.
+    pub fn chord(&mut self, x: usize, y: usize) {
+        self.coach = None;
+        let cells = self.board.chord_targets(x, y);
+        if cells.is_empty() || self.objective.is_decided() {
+            return;
+        }
+        let undo_point = self.undo_point();
+        self.journal.record(Action::Chord(x, y));
+        let result = self.open_cells(cells);
+        self.input_stats.record_reveal(result);
+        info!(x, y, ?result, "chord");
+        self.objective.click();
+        self.judge_objective();
+        self.keep_undo_point(undo_point);
+    }
+
+    /// The "open on number" assist after a flag at (x, y): open the other
+    /// neighbours of the one number it completes. These opens aren't the
+    /// player's clicks, so they stay out of the click counts.
+    fn open_on_number(&mut self, x: usize, y: usize) {
+        let cells = assist::open_on_number(&self.board, x, y);
+        if cells.is_empty() {
+            return;
+        }
+        let result = self.open_cells(cells);
+        info!(x, y, ?result, "open on number");
+    }
+
+    /// Reveal `cells` in turn until the game ends, finishing it if it did,
+    /// and return the last result.
+    fn open_cells(&mut self, cells: Vec<(usize, usize)>) -> RevealResult {
+        let before = self.board.snapshot();
+        let mut result = RevealResult::Revealed;
+        let mut fatal = None;
+        let mut last = (0, 0);
+        let mut rings: Vec<Vec<(usize, usize)>> = Vec::new();
+        for (cx, cy) in cells {
+            if self.board.cell(cx, cy).is_revealed() {
+                continue; // opened by an earlier cell's flood
+            }
+            let step = self.mode.forgiving().then(|| self.board.snapshot());
+            result = self.board.reveal_cell(cx, cy);
+            last = (cx, cy);
+            match (result, step) {
+                (RevealResult::Exploded, Some(step)) => self.defuse(&step, cx, cy),
+                (RevealResult::Exploded, None) => {
+                    self.event(GameEvent::Exploded(cx, cy));
+                    fatal = Some((cx, cy));
+                }
+                _ => {}
+            }
+            // the openings spread side by side, ring for ring
+            for (n, ring) in self.board.last_reveal_wavefronts().into_iter().enumerate() {
+                match rings.get_mut(n) {
+                    Some(merged) => merged.extend(ring),
+                    None => rings.push(ring),
+                }
+            }
+            if self.board.is_over() {
+                break;
+            }
+        }
+        self.record_reveals(None, &rings);
+        if self.board.is_over() && matches!(result, RevealResult::Exploded | RevealResult::Won) {
+            self.final_position = self.position_before(&before);
+            if let Some((x, y)) = fatal {
+                self.start_fatal_odds(x, y);
+            }
+            self.finish_game(last);
+        }
+        result
+    }
+
+    /// Drop the feedback message once its time is up.
+    pub fn expire_feedback(&mut self, ctx: &egui::Context) {
+        let now = ctx.input(|i| i.time);
+        if self.feedback.as_ref().is_some_and(|f| now >= f.until) {
+            self.feedback = None;
+            self.visuals.set_flash(None);
+        }
+    }
+
+    fn show_feedback(&mut self, ctx: &egui::Context, reason: Ignored, x: usize, y: usize) {
+        let now = ctx.input(|i| i.time);
+        // only flash cells whose own state blocked the action
+        let cell = match reason {
+            Ignored::Flagged | Ignored::AlreadyRevealed => Some((x, y)),
+            _ => None,
+        };
+        self.visuals.set_flash(cell);
+        self.cue(Earcon::Warning, x);
+        self.feedback = Some(Feedback {
+            reason,
+            until: now + FEEDBACK_SECS,
+        });
+        ctx.request_repaint_after(std::time::Duration::from_secs_f64(FEEDBACK_SECS));
+    }
+
+    /// The debug log window, when it is open.
+    pub fn log_window(&mut self, ctx: &egui::Context) {
+        if !self.settings.advanced.show_log {
+            return;
+        }
+        egui::Window::new("Log")
+            .open(&mut self.settings.advanced.show_log)
+            .default_size([520.0, 300.0])
+            .show(ctx, |ui| {
+                egui::ScrollArea::vertical()
+                    .stick_to_bottom(true)
+                    .show(ui, |ui| {
+                        for line in self.log.lines() {
+                            ui.monospace(line);
+                        }
+                    });
+            });
+    }
+}
+
+impl Default for AppState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// How far along quitting is.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Exit {
+    Running,
+    Asking { save_error: Option<String> }, // "quit with a game in progress?" modal open
+    Quitting { keep_save: bool },          // close sent; on_exit leaves the slot if kept
+}
+
+/// The eframe app: which screen is showing, plus the state they share.
+pub struct MinesweeperApp {
+    pub screen: Screen,
+    pub state: AppState,
+    pub interrupt: Arc<AtomicBool>, // set by the Ctrl+C handler
+    settings_back: Screen,          // where the settings screen's Back button goes
+    exit: Exit,
+    pub frames: u64, // frames drawn; a minimized window draws none
+    #[cfg(feature = "dev-tools")]
+    pub recorder: Option<crate::recording::Recorder>, // `--record`, see `recording`
+    #[cfg(feature = "dev-tools")]
+    pub profiler: crate::perf::Profiler, // frame timings and the F12 HUD
+}
+
+impl MinesweeperApp {
+    pub fn new(state: AppState) -> Self {
+        Self {
+            screen: Screen::Menu, // start on menu screen
+            state,
+            interrupt: Arc::default(),
+            settings_back: Screen::Menu,
+            exit: Exit::Running,
+            frames: 0,
+            #[cfg(feature = "dev-tools")]
+            recorder: None,
+            #[cfg(feature = "dev-tools")]
+            profiler: crate::perf::Profiler::default(),
+        }
+    }
+
+    /// Go straight to a fresh game at `difficulty`.
+    pub fn start_game(&mut self, difficulty: Difficulty) {
+        self.state.set_difficulty(difficulty);
+        self.screen = Screen::Game;
+    }
+
+    fn navigate(&mut self, to: Screen) {
+        match to {
+            Screen::Menu => self.state.back_to_menu(),
+            // the theme screen is a page of the settings, not a way in
+            Screen::Settings if self.screen == Screen::Themes => {}
+            Screen::Settings => self.settings_back = self.screen,
+            Screen::Game
+            | Screen::Compact
+            | Screen::Race
+            | Screen::Spectate
+            | Screen::Tutorial
+            | Screen::Trainer
+            | Screen::Versus
+            | Screen::History
+            | Screen::Leaderboards
+            | Screen::About
+            | Screen::Editor
+            | Screen::Themes => {}
+        }
+        self.screen = to;
+    }
+
+    /// Draw one frame. Separate from `update` so it can run without an
+    /// `eframe::Frame`.
+    ///
+    /// A minimized window draws nothing and so asks for no further frames:
+    /// particles, the coach and the race clock all stop with it, while
+    /// play time still counts. Restoring picks everything up again.
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        #[cfg(feature = "dev-tools")]
+        if let Some(recorder) = &mut self.recorder {
+            recorder.capture(ctx);
+        }
+        self.handle_close(ctx);
+        crash::set_enabled(self.state.settings.advanced.crash_reports);
+        let in_game = matches!(self.screen, Screen::Game | Screen::Compact);
+        self.state.covered = self.state.dialog_open() || matches!(self.exit, Exit::Asking { .. });
+        self.state.tick_autosave(ctx, in_game);
+        self.state.tick_leaderboard(ctx);
+        let minimized = ctx.input(|i| i.viewport().minimized == Some(true));
+        self.state.set_minimized(minimized);
+        if minimized && matches!(self.exit, Exit::Running) {
+            return;
+        }
+        self.frames += 1;
+        self.state.log_window(ctx);
+
+        let next = match self.screen {
+            Screen::Menu => screens::menu::show(ctx, &mut self.state),
+            Screen::Game => screens::game::show(ctx, &mut self.state),
+            Screen::Compact => screens::compact::show(ctx, &mut self.state),
+            Screen::Settings => screens::settings::show(ctx, &mut self.state, self.settings_back),
+            Screen::Race => screens::race::show(ctx, &mut self.state),
+            Screen::Spectate => screens::spectate::show(ctx, &mut self.state),
+            Screen::Tutorial => screens::tutorial::show(ctx, &mut self.state),
+            Screen::Trainer => screens::trainer::show(ctx, &mut self.state),
+            Screen::Versus => screens::versus::show(ctx, &mut self.state),
+            Screen::History => screens::history::show(ctx, &mut self.state),
+            Screen::Leaderboards => screens::leaderboards::show(ctx, &mut self.state),
+            Screen::About => screens::about::show(ctx, &mut self.state),
+            Screen::Editor => screens::editor::show(ctx, &mut self.state),
+            Screen::Themes => screens::themes::show(ctx, &mut self.state),
+        };
+        if let Some(next) = next {
+            self.navigate(next);
+        }
+        if matches!(self.exit, Exit::Asking { .. }) {
+            self.exit_prompt(ctx);
+        } else if self.state.pending_discard.is_some() {
+            self.abandon_prompt(ctx);
+        }
+        self.state.crash_notice(ctx);
+        self.state.sleep_notice(ctx);
+        self.state.toasts.show(ctx);
+    }
+
+    /// Hold a window close or Ctrl+C while a game is in progress and ask
+    /// what to do with it. A second Ctrl+C while asking saves and quits.
+    fn handle_close(&mut self, ctx: &egui::Context) {
+        let interrupted = self.interrupt.swap(false, Ordering::Relaxed);
+        let requested = ctx.input(|i| i.viewport().close_requested());
+        if matches!(self.exit, Exit::Quitting { .. }) || !(interrupted || requested) {
+            return;
+        }
+        let asking = matches!(self.exit, Exit::Asking { .. });
+        if interrupted && asking {
+            self.quit(ctx, self.state.autosave.is_some());
+        } else if self.state.in_progress() {
+            if requested {
+                // eframe closes after this frame unless told otherwise
+                ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+            }
+            if interrupted {
+                eprintln!(
+                    "game in progress: answer in the window, or Ctrl+C again to save and quit"
+                );
+                ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+            }
+            if !asking {
+                self.exit = Exit::Asking { save_error: None };
+            }
+        } else if interrupted {
+            self.quit(ctx, false);
+        }
+    }
+
+    /// Close the window, first saving the game if `save`. A failed save
+    /// keeps the window open with the error shown.
+    fn quit(&mut self, ctx: &egui::Context, save: bool) {
+        if save {
+            if let Err(err) = self.state.save_now() {
+                warn!(%err, "couldn't save before quitting");
+                self.exit = Exit::Asking {
+                    save_error: Some(err.to_string()),
+                };
+                return;
+            }
+        }
+        info!(saved = save, "quitting");
+        self.exit = Exit::Quitting { keep_save: save };
+        ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+    }
+
+    /// "Save and quit", "Quit without saving" or "Cancel".
+    fn exit_prompt(&mut self, ctx: &egui::Context) {
+        let mut quit = None; // Some(save)
+        let mut cancel = false;
+        let modal = egui::Modal::new(egui::Id::new("exit_prompt")).show(ctx, |ui| {
+            ui.heading("Quit with a game in progress?");
+            if let Exit::Asking {
+                save_error: Some(err),
+            } = &self.exit
+            {
+                ui.colored_label(egui::Color32::LIGHT_RED, format!("Couldn't save: {err}"));
+            }
+            ui.horizontal(|ui| {
+                let can_save = self.state.autosave.is_some();
+                if ui
+                    .add_enabled(can_save, egui::Button::new("Save and quit"))
+                    .on_hover_text("Offer to resume it next time")
+                    .on_disabled_hover_text("No data folder to save to")
+                    .clicked()
+                {
+                    quit = Some(true);
+                }
+                if ui.button("Quit without saving").clicked() {
+                    quit = Some(false);
+                }
+                cancel = ui.button("Cancel").clicked();
+            });
+        });
+        if let Some(save) = quit {
+            if !save {
+                self.state.abandon(AbandonReason::Quit);
+            }
+            self.quit(ctx, save);
+        } else if cancel || modal.should_close() {
+            self.exit = Exit::Running;
+        }
+    }
+
+    /// "Abandon this game?" before a new game, difficulty or menu pick
+    /// throws away the one in progress.
+    fn abandon_prompt(&mut self, ctx: &egui::Context) {
+        let Some(discard) = self.state.pending_discard else {
+            return;
+        };
+        let mut abandon = None;
+        let modal = egui::Modal::new(egui::Id::new("abandon_prompt")).show(ctx, |ui| {
+            ui.heading("Abandon this game?");
+            let gameplay = &mut self.state.settings.gameplay;
+            ui.label(if gameplay.abandon_is_loss {
+                "It will count as a loss."
+            } else {
+                "It will be counted as abandoned, not lost."
+            });
+            let mut dont_ask = !gameplay.confirm_abandon;
+            if ui.checkbox(&mut dont_ask, "Don't ask again").changed() {
+                gameplay.confirm_abandon = !dont_ask;
+            }
+            ui.horizontal(|ui| {
+                if ui.button("Abandon game").clicked() {
+                    abandon = Some(true);
+                }
+                if ui.button("Keep playing").clicked() {
+                    abandon = Some(false);
+                }
+            });
+        });
+        match abandon {
+            Some(true) => self.state.carry_out(discard),
+            Some(false) => self.state.pending_discard = None,
+            None if modal.should_close() => self.state.pending_discard = None,
+            None => {}
+        }
+    }
+}
+
+impl eframe::App for MinesweeperApp {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        #[cfg(feature = "dev-tools")]
+        self.profiler.begin_frame();
+        {
+            profile_scope!(crate::perf::Scope::Frame);
+            self.ui(ctx);
+        }
+        #[cfg(feature = "dev-tools")]
+        {
+            self.profiler.end_frame();
+            if ctx.input(|i| i.key_pressed(egui::Key::F12)) {
+                self.profiler.shown = !self.profiler.shown;
+            }
+            self.profiler.hud(ctx);
+        }
+    }
+
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        // a recording runs on defaults, which mustn't replace the profiles
+        #[cfg(feature = "dev-tools")]
+        if self.recorder.is_some() {
+            return;
+        }
+        let state = &mut self.state;
+        state
+            .profiles
+            .save(storage, (&state.settings, &state.history));
+    }
+
+    /// A clean exit: the slot only needs to outlive a crash, unless the
+    /// game was saved to be resumed.
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        #[cfg(feature = "dev-tools")]
+        if let Some(recorder) = &self.recorder {
+            match recorder.save(self) {
+                Ok(()) => info!(path = %recorder.path.display(), "recording saved"),
+                Err(err) => warn!(%err, "couldn't save the recording"),
+            }
+        }
+        if self.exit == (Exit::Quitting { keep_save: true }) {
+            return;
+        }
+        if let Some(Err(err)) = self.state.autosave.as_ref().map(Autosave::clear) {
+            warn!(%err, "couldn't remove autosave");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::journal::Mark;
+    use crate::particles::Emitter;
+    use crate::visuals::CellVisual;
+    use minesweeper_rs::objective::Failure;
+    use minesweeper_rs::render::Glyph;
+
+    /// Run one headless frame.
+    fn frame(ctx: &egui::Context, app: &mut MinesweeperApp) {
+        let _ = ctx.run(egui::RawInput::default(), |ctx| app.ui(ctx));
+    }
+
+    /// Run one headless frame with the window minimized or not, returning
+    /// how long egui was asked to wait before the next.
+    fn frame_minimized(
+        ctx: &egui::Context,
+        app: &mut MinesweeperApp,
+        minimized: bool,
+    ) -> std::time::Duration {
+        let mut input = egui::RawInput::default();
+        let root = input.viewports.entry(egui::ViewportId::ROOT).or_default();
+        root.minimized = Some(minimized);
+        let output = ctx.run(input, |ctx| app.ui(ctx));
+        output.viewport_output[&egui::ViewportId::ROOT].repaint_delay
+    }
+
+    #[test]
+    fn minimized_windows_draw_nothing_until_restored() {
+        let ctx = egui::Context::default();
+        let mut app = MinesweeperApp::new(AppState::default());
+        app.start_game(Difficulty::Beginner);
+        frame_minimized(&ctx, &mut app, false);
+        let area = egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(20.0, 20.0));
+        let rng = &mut app.state.visual_rng;
+        app.state.particles.emit(Emitter::debris(), area, rng);
+
+        let drawn = app.frames;
+        let mut delay = std::time::Duration::ZERO;
+        for _ in 0..3 {
+            delay = frame_minimized(&ctx, &mut app, true);
+        }
+        assert_eq!(app.frames, drawn);
+        assert_eq!(delay, std::time::Duration::MAX);
+        assert!(!app.state.particles.is_active());
+
+        frame_minimized(&ctx, &mut app, false);
+        assert_eq!(app.frames, drawn + 1);
+        assert!(!app.state.is_minimized());
+    }
+
+    #[test]
+    fn leaderboards_screen_copes_when_off_or_unreachable() {
+        let ctx = egui::Context::default();
+        let mut app = MinesweeperApp::new(AppState::default());
+        app.navigate(Screen::Leaderboards);
+        frame(&ctx, &mut app);
+        assert!(!app.state.settings.advanced.leaderboard.enabled);
+
+        let settings = &mut app.state.settings.advanced.leaderboard;
+        settings.enabled = true;
+        settings.server = "http://127.0.0.1:1".to_string(); // nothing listens there
+        let settings = settings.clone();
+        for _ in 0..500 {
+            frame(&ctx, &mut app);
+            let view = app.state.leaderboard.table(&settings, "9x9x10", 0.0, || {});
+            if view.error.is_some() {
+                assert!(view.standings.is_none());
+                frame(&ctx, &mut app);
+                return;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        panic!("the fetch never failed");
+    }
+
+    #[test]
+    fn starting_a_game_switches_screen_and_board() {
+        let mut app = MinesweeperApp::new(AppState::default());
+        assert_eq!(app.screen, Screen::Menu);
+
+        app.start_game(Difficulty::Expert);
+        assert_eq!(app.screen, Screen::Game);
+        let board = &app.state.board;
+        assert_eq!(
+            (board.width(), board.height(), board.mine_count()),
+            (25, 25, 99)
+        );
+    }
+
+    #[test]
+    fn back_to_menu_keeps_difficulty_and_board() {
+        let mut app = MinesweeperApp::new(AppState::default());
+        app.start_game(Difficulty::Intermediate);
+        app.state.board.toggle_flag(2, 3);
+
+        app.navigate(Screen::Menu);
+        assert_eq!(app.screen, Screen::Menu);
+        assert_eq!(app.state.difficulty, Difficulty::Intermediate);
+        assert!(app.state.board.cell(2, 3).is_flagged());
+    }
+
+    #[test]
+    fn clicks_flag_and_refuse_flagged_reveals() {
+        let ctx = egui::Context::default();
+        let mut state = AppState::default();
+
+        state.handle_click(&ctx, CellClick::Flag(4, 4));
+        assert!(state.board.cell(4, 4).is_flagged());
+
+        state.handle_click(&ctx, CellClick::Reveal(4, 4));
+        assert!(!state.board.cell(4, 4).is_revealed());
+        let reason = state.feedback.as_ref().map(|f| f.reason);
+        assert_eq!(reason, Some(Ignored::Flagged));
+
+        state.handle_click(&ctx, CellClick::Flag(4, 4));
+        assert!(!state.board.cell(4, 4).is_flagged());
+    }
+
+    #[test]
+    fn abandoned_games_are_counted_as_the_settings_say() {
+        let ctx = egui::Context::default();
+        let mut state = AppState::default();
+        let play = |state: &mut AppState| {
+            state.board = Board::decode_layout("*..\n...\n..*\n").unwrap();
+            state.awaiting_first_click = false;
+            state.handle_click(&ctx, CellClick::Reveal(1, 0));
+            assert!(state.in_progress());
+        };
+
+        play(&mut state);
+        state.discard(Discard::NewGame);
+        assert_eq!(state.pending_discard, Some(Discard::NewGame), "asked first");
+        state.carry_out(Discard::NewGame);
+        assert_eq!((state.history.games, state.history.abandoned), (1, 1));
+        let logged = state.history.log.back().unwrap();
+        assert!(logged.abandoned && !logged.won);
+        assert_eq!(state.history.recent.back().map(|o| o.won), Some(false));
+
+        state.settings.gameplay.abandon_is_loss = false;
+        state.settings.gameplay.confirm_abandon = false;
+        play(&mut state);
+        state.discard(Discard::Difficulty(Difficulty::Expert));
+        assert_eq!(state.difficulty, Difficulty::Expert, "not asked");
+        assert_eq!((state.history.games, state.history.abandoned), (1, 2));
+
+        // a fresh board has nothing to abandon
+        state.discard(Discard::NewGame);
+        assert_eq!(state.history.abandoned, 2);
+
+        state.settings.gameplay.zen = true;
+        state.reset();
+        play(&mut state);
+        state.abandon(AbandonReason::Quit);
+        assert_eq!(state.history.abandoned, 2, "zen games aren't recorded");
+    }
+
+    #[test]
+    fn coached_games_stay_out_of_the_history() {
+        let ctx = egui::Context::default();
+        let mut state = AppState {
+            coach: Some(Coach::default()),
+            ..Default::default()
+        };
+        state.tick_coach(&ctx, true);
+        assert!(!state.awaiting_first_click);
+        while !state.board.is_over() {
+            state.tick_coach(&ctx, true);
+        }
+        assert!(state.coach.is_none());
+        assert_eq!(state.history.games, 0);
+
+        // a click hands the board back
+        state.reset();
+        state.coach = Some(Coach::default());
+        state.handle_click(&ctx, CellClick::Reveal(4, 4));
+        assert!(state.coach.is_none());
+        assert!(!state.coached);
+    }
+
+    #[test]
+    fn no_guess_waits_for_the_first_click() {
+        let ctx = egui::Context::default();
+        let mut state = AppState::default();
+        state.settings.gameplay.no_guess = true;
+        state.reset();
+        assert!(state.awaiting_first_click);
+
+        state.handle_click(&ctx, CellClick::Reveal(0, 0));
+        assert!(!state.awaiting_first_click);
+        assert!(state.generation.is_some());
+        // further clicks are ignored until the board arrives
+        state.handle_click(&ctx, CellClick::Flag(8, 8));
+        assert!(!state.board.cell(8, 8).is_flagged());
+    }
+
+    #[test]
+    fn first_click_keeps_the_safe_radius_clear() {
+        let ctx = egui::Context::default();
+        let mut state = AppState::default();
+        state.settings.gameplay.safe_radius = 2;
+        state.set_difficulty(Difficulty::Beginner.with_percent(60));
+        assert!(state.awaiting_first_click);
+
+        state.handle_click(&ctx, CellClick::Reveal(0, 8));
+        assert!(!state.awaiting_first_click);
+        let layout = state.board.encode_layout();
+        let rows: Vec<&str> = layout.lines().collect();
+        for row in &rows[6..] {
+            assert_eq!(&row[..3], "...");
+        }
+    }
+
+    #[test]
+    fn custom_boards_keep_their_shape_and_others_ignore_it() {
+        let ctx = egui::Context::default();
+        let mut state = AppState {
+            custom_symmetry: Some(Symmetry::Horizontal),
+            custom_spaced: true,
+            ..Default::default()
+        };
+        state.set_difficulty(Difficulty::custom(10, 8, 12, 50));
+        state.handle_click(&ctx, CellClick::Reveal(0, 0));
+        let layout = state.board.encode_layout();
+        assert!(layout.lines().all(|row| row.chars().rev().eq(row.chars())));
+        assert!(!layout.contains("**"));
+
+        let expert = state.board_options(Difficulty::Expert);
+        assert_eq!(expert, Difficulty::Expert.options());
+    }
+
+    /// Whether a mine lies within `radius` of (x, y), read off the layout.
+    fn mine_near(board: &Board, (x, y): (usize, usize), radius: usize) -> bool {
+        let rows: Vec<Vec<char>> = board
+            .encode_layout()
+            .lines()
+            .map(|row| row.chars().collect())
+            .collect();
+        let (w, h) = (board.width(), board.height());
+        (y.saturating_sub(radius)..=(y + radius).min(h - 1)).any(|ny| {
+            (x.saturating_sub(radius)..=(x + radius).min(w - 1)).any(|nx| rows[ny][nx] == '*')
+        })
+    }
+
+    /// A game on `layout`, as if its first click had happened.
+    fn laid(layout: &str) -> AppState {
+        AppState {
+            board: Board::decode_layout(layout).unwrap(),
+            awaiting_first_click: false,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn auto_open_keeps_the_safe_radius_and_is_not_a_click() {
+        let ctx = egui::Context::default();
+        let mut state = AppState::default();
+        state.reset();
+        state.auto_open(&ctx);
+        assert!(state.awaiting_first_click, "off by default");
+        assert_eq!(state.auto_move, None);
+
+        state.settings.gameplay.safe_radius = 2;
+        for choice in [AutoOpen::Centre, AutoOpen::Random, AutoOpen::BestOpening] {
+            state.settings.gameplay.auto_open = choice;
+            for _ in 0..10 {
+                state.reset();
+                assert!(state.awaiting_first_click, "made once the screen is up");
+                state.auto_open(&ctx);
+                assert!(!state.awaiting_first_click);
+                let auto = state.auto_move.unwrap();
+                assert_eq!(auto.choice, choice);
+                assert!(state.board.cell(auto.cell.0, auto.cell.1).is_revealed());
+                assert!(!mine_near(&state.board, auto.cell, 2), "{choice:?}");
+                assert_eq!(state.input_stats.left_clicks, 0);
+                assert_eq!(state.clock.secs, 0.0);
+                // the heatmap's timeline has it, at the very start
+                assert_eq!(state.reveal_times.last_millis(), 0);
+                assert!(!state.reveal_times.is_empty());
+            }
+        }
+        let best = stats::best_opening(&state.board, 2);
+        assert_eq!(state.auto_move.map(|auto| auto.cell), best);
+        state.settings.gameplay.auto_open = AutoOpen::Centre;
+        state.reset();
+        state.auto_open(&ctx);
+        assert_eq!(state.auto_move.unwrap().cell, (4, 4));
+        // only once per game
+        state.awaiting_first_click = true;
+        state.auto_open(&ctx);
+        assert!(state.awaiting_first_click);
+    }
+
+    #[test]
+    fn the_endgame_outlook_is_worked_out_per_move_and_marks_the_game() {
+        let ctx = egui::Context::default();
+        let mut state = laid("*.\n..\n..\n");
+        state.handle_click(&ctx, CellClick::Reveal(0, 2));
+        assert_eq!(state.endgame_outlook(&ctx), None, "off in the settings");
+        assert!(state.endgame.is_none());
+
+        state.settings.gameplay.endgame_outlook = true;
+        let mut outlook = None;
+        for _ in 0..200 {
+            outlook = state.endgame_outlook(&ctx);
+            if outlook.is_some() {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        let outlook = outlook.expect("the worker should finish");
+        assert_eq!((outlook.guesses, outlook.win_chance), (1, 0.5));
+        assert!(state.endgame_shown);
+
+        state.handle_click(&ctx, CellClick::Reveal(1, 0));
+        assert!(state.board.is_won());
+        assert!(state.history.log.back().unwrap().assisted);
+        assert_eq!(state.endgame_outlook(&ctx), None, "the game is over");
+    }
+
+    #[test]
+    fn a_win_from_the_best_opening_is_marked() {
+        let ctx = egui::Context::default();
+        for (choice, marked) in [(AutoOpen::Random, false), (AutoOpen::BestOpening, true)] {
+            let mut state = AppState::default();
+            state.settings.gameplay.auto_open = choice;
+            state.reset();
+            state.auto_open(&ctx);
+            let layout = state.board.encode_layout();
+            let mut clicks = 0;
+            for (y, row) in layout.lines().enumerate() {
+                for (x, c) in row.chars().enumerate() {
+                    if c == '.' && !state.board.cell(x, y).is_revealed() {
+                        state.handle_click(&ctx, CellClick::Reveal(x, y));
+                        clicks += 1;
+                    }
+                }
+            }
+            assert!(state.board.is_won());
+            assert_eq!(state.input_stats.left_clicks, clicks);
+            assert_eq!(state.history.log.back().unwrap().assisted, marked);
+            assert_eq!(state.history.bests[0].assisted, marked);
+        }
+    }
+
+    #[test]
+    fn no_guess_auto_open_waits_for_the_board() {
+        let ctx = egui::Context::default();
+        let mut state = AppState::default();
+        state.settings.gameplay.no_guess = true;
+        state.settings.gameplay.auto_open = AutoOpen::BestOpening;
+        state.reset();
+        state.auto_open(&ctx);
+        assert!(state.generation.is_some());
+        while state.generation.is_some() {
+            std::thread::sleep(std::time::Duration::from_millis(1));
+            state.poll_generation();
+        }
+        let auto = state.auto_move.unwrap();
+        assert!(state.board.cell(auto.cell.0, auto.cell.1).is_revealed());
+        assert!(!mine_near(&state.board, auto.cell, 1));
+        assert_eq!(state.input_stats.left_clicks, 0);
+    }
+
+    #[test]
+    fn analysis_studies_the_position_before_the_last_move() {
+        use crate::widgets::Overlay;
+
+        let ctx = egui::Context::default();
+        let mut state = laid("*..\n...\n...\n");
+        state.handle_click(&ctx, CellClick::Reveal(0, 0));
+        assert!(state.board.is_lost());
+
+        state.start_analysis(&ctx);
+        while state.kill_screen().is_none() {
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+        let kill_screen = state.kill_screen().unwrap();
+        assert!(!kill_screen.position.cell(0, 0).is_revealed());
+        // the mine shows in the ground-truth channel, the safe cell doesn't
+        assert!(kill_screen.tint(0).is_some_and(|c| c.b() > 0));
+        assert!(kill_screen.tint(1).is_some_and(|c| c.b() == 0));
+
+        state.reset();
+        assert!(state.post_game.is_none() && state.final_position.is_none());
+    }
+
+    #[test]
+    fn open_on_number_opens_for_the_player() {
+        let ctx = egui::Context::default();
+        let mut state = AppState::default();
+        state.settings.gameplay.open_on_number = true;
+        state.board = Board::decode_layout("*....\n.....\n").unwrap();
+        state.awaiting_first_click = false;
+        state.handle_click(&ctx, CellClick::Reveal(1, 1));
+
+        state.handle_click(&ctx, CellClick::Flag(0, 0));
+        assert!(state.board.is_won());
+        // the assist's opens aren't the player's clicks
+        assert_eq!(state.input_stats.left_clicks, 1);
+    }
+
+    #[test]
+    fn cell_visuals_start_over_and_follow_undo() {
+        let ctx = egui::Context::default();
+        let mut state = laid("*....\n.....\n");
+        state.handle_click(&ctx, CellClick::Reveal(0, 0));
+        assert!(state.visuals.get(0, 0).exploded);
+        assert!(!state.visuals.get(1, 0).exploded);
+
+        state.reset();
+        assert_eq!(state.visuals.get(0, 0), CellVisual::default());
+
+        // an undone flag takes its look with it
+        state.mode = GameMode::Zen;
+        state.board = Board::decode_layout("*....\n.....\n").unwrap();
+        state.awaiting_first_click = false;
+        state.handle_click(&ctx, CellClick::Flag(0, 0));
+        assert!(state.visuals.get(0, 0).final_flag);
+        state.handle_click(&ctx, CellClick::Reveal(0, 0));
+        assert!(state.visuals.get(0, 0).flash);
+        state.undo();
+        assert!(!state.visuals.get(0, 0).final_flag);
+        assert!(state.visuals.get(0, 0).flash);
+
+        // a board loaded from the slot starts plain, even at the same size
+        state.recovered = Some(Session {
+            board: Board::decode_layout("*....\n.....\n").unwrap(),
+            elapsed_secs: 3.0,
+            annotations: Annotations::default(),
+        });
+        state.recover();
+        assert_eq!(state.visuals.get(0, 0), CellVisual::default());
+    }
+
+    #[test]
+    fn moves_during_a_cascade_land_at_once_and_end_the_same() {
+        let ctx = egui::Context::default();
+        // a wall of mines down the middle, with a gap the flood can't reach
+        let layout = "..*..\n...*.\n..*..\n";
+        let play = |animations: bool| {
+            let mut state = AppState::default();
+            state.settings.accessibility.animations = animations;
+            state.board = Board::decode_layout(layout).unwrap();
+            state.awaiting_first_click = false;
+            state.visuals.reset(&state.board);
+            state.handle_click(&ctx, CellClick::Reveal(0, 0));
+            assert_eq!(state.visuals.get(1, 1).veiled, animations);
+            state.handle_click(&ctx, CellClick::Flag(2, 0));
+            state.handle_click(&ctx, CellClick::Flag(2, 2));
+            // a chord on the number the cascade hasn't shown yet
+            state.chord(1, 1);
+            assert!(state.board.cell(2, 1).is_revealed());
+            for (x, y) in [(4, 0), (3, 0), (4, 1), (3, 2), (4, 2)] {
+                state.handle_click(&ctx, CellClick::Reveal(x, y));
+            }
+            assert!(state.board.is_won());
+            let veiled = (0..5).any(|x| (0..3).any(|y| state.visuals.get(x, y).veiled));
+            assert_eq!(veiled, animations);
+            state.visuals.tick_cascade(f64::MAX, animations);
+            assert!((0..5).all(|x| (0..3).all(|y| !state.visuals.get(x, y).veiled)));
+            let cells = (0..3).flat_map(|y| (0..5).map(move |x| (x, y)));
+            cells
+                .map(|(x, y)| Glyph::of(state.board.cell(x, y)))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(play(true), play(false));
+    }
+
+    #[test]
+    fn zen_forgives_mines_undoes_and_records_nothing() {
+        let ctx = egui::Context::default();
+        let mut state = laid("*....\n.....\n");
+        state.mode = GameMode::Zen;
+
+        state.handle_click(&ctx, CellClick::Reveal(0, 0));
+        assert!(!state.board.is_over());
+        assert!(state.board.cell(0, 0).is_flagged());
+        state.undo();
+        assert!(!state.board.cell(0, 0).is_flagged());
+        assert!(state.undo.is_empty());
+
+        state.handle_click(&ctx, CellClick::Reveal(4, 1));
+        state.handle_click(&ctx, CellClick::Reveal(0, 1));
+        assert!(state.board.is_won());
+        assert_eq!(state.history.games, 0);
+        assert!(!state.events.iter().any(|e| matches!(e, GameEvent::Won(..))));
+        assert_eq!(state.undo.len(), 2);
+    }
+
+    #[test]
+    fn a_test_play_is_the_editors_board_and_records_nothing() {
+        let ctx = egui::Context::default();
+        let mut state = AppState::default();
+        state.editor.new_grid((3, 2));
+        state.editor.toggle(0, 0);
+        state.editor.pick_start(2, 1);
+
+        for _ in 0..2 {
+            state.carry_out(Discard::TestPlay);
+            assert_eq!(state.mode, GameMode::TestPlay);
+            assert_eq!(state.board.encode_layout(), "*..\n...\n");
+            assert!(
+                state.board.cell(2, 1).is_revealed(),
+                "opened from the start"
+            );
+            assert!(!state.board.cell(0, 1).is_revealed());
+            state.handle_click(&ctx, CellClick::Reveal(0, 1));
+            assert!(state.board.is_won());
+        }
+        assert_eq!(state.history.games, 0);
+        assert!(!state.events.iter().any(|e| matches!(e, GameEvent::Won(..))));
+
+        state.reset();
+        assert_eq!(state.mode, GameMode::Standard, "only until the next game");
+    }
+
+    #[test]
+    fn a_puzzle_objective_is_judged_move_by_move_and_ends_play() {
+        let ctx = egui::Context::default();
+        let mut state = AppState::default();
+        let puzzle: Puzzle = "+++\nstart = [0, 0]\nclicks = 2\nregion = [[4, 0]]\n+++\n\
+                              ...*.\n...*.\n"
+            .parse()
+            .unwrap();
+        state.editor.load(puzzle);
+        state.carry_out(Discard::TestPlay);
+        assert_eq!(
+            state.objective.clicks_left(),
+            Some(1),
+            "the start was a click"
+        );
+
+        // a flag is no click, and a click that misses the target spends it
+        state.handle_click(&ctx, CellClick::Flag(3, 0));
+        assert_eq!(state.objective.verdict(), Verdict::Open);
+        state.handle_click(&ctx, CellClick::Reveal(4, 1));
+        assert_eq!(
+            state.objective.verdict(),
+            Verdict::Failed(Failure::OutOfClicks)
+        );
+        assert!(state.is_settled() && !state.board.is_over());
+        state.handle_click(&ctx, CellClick::Reveal(4, 0));
+        assert!(!state.board.cell(4, 0).is_revealed(), "play has ended");
+
+        // met on the budget's last click
+        state.carry_out(Discard::TestPlay);
+        state.handle_click(&ctx, CellClick::Reveal(4, 0));
+        assert_eq!(state.objective.verdict(), Verdict::Met);
+    }
+
+    #[test]
+    fn a_rematch_deals_the_same_mines_and_is_never_a_best() {
+        let ctx = egui::Context::default();
+        let mut state = AppState::default();
+        state.set_difficulty(Difficulty::Beginner);
+        state.handle_click(&ctx, CellClick::Reveal(4, 4));
+        let layout = state.board.encode_layout();
+        let row = state.board.width() + 1;
+        let mine = layout.find('*').unwrap();
+        state.handle_click(&ctx, CellClick::Reveal(mine % row, mine / row));
+        assert!(state.board.is_over());
+
+        let hash = state.history.recent_boards()[0].dealt.hash;
+        for attempts in 2..4 {
+            state.carry_out(Discard::Rematch(hash));
+            assert_eq!(state.mode, GameMode::Rematch);
+            assert_eq!(state.board.encode_layout(), layout);
+            assert!(state.board.cell(4, 4).is_revealed(), "opened where it was");
+            for (i, _) in layout.match_indices('.') {
+                state.handle_click(&ctx, CellClick::Reveal(i % row, i / row));
+            }
+            assert!(state.board.is_won());
+
+            let recent = state.history.recent_boards();
+            assert_eq!((recent.len(), recent[0].attempts), (1, attempts));
+        }
+        let rematches: Vec<_> = state.history.log.iter().map(|g| g.rematch).collect();
+        assert_eq!(rematches, [false, true, true]);
+        assert!(state.history.bests.is_empty());
+    }
+
+    #[test]
+    fn undo_keeps_the_clock_unless_it_goes_back_to_the_start() {
+        let ctx = egui::Context::default();
+        // (back past the first reveal, taking notes, undoing a win)
+        for (to_start, paused, revive) in [
+            (false, false, false),
+            (false, false, true),
+            (false, true, false),
+            (false, true, true),
+            (true, false, false),
+            (true, false, true),
+            (true, true, false),
+            (true, true, true),
+        ] {
+            let mut state = AppState::default();
+            state.settings.gameplay.zen = true;
+            state.reset();
+            state.handle_click(&ctx, CellClick::Reveal(4, 4));
+            let layout = state.board.encode_layout();
+            let cells = || {
+                layout
+                    .lines()
+                    .enumerate()
+                    .flat_map(|(y, row)| row.char_indices().map(move |(x, c)| ((x, y), c == '*')))
+            };
+            let (mine, _) = cells().find(|&(_, mine)| mine).unwrap();
+            state.handle_click(&ctx, CellClick::Flag(mine.0, mine.1));
+            if revive {
+                for ((x, y), _) in cells().filter(|&(_, mine)| !mine) {
+                    state.handle_click(&ctx, CellClick::Reveal(x, y));
+                }
+                assert!(state.board.is_won());
+            }
+            state.clock.secs = 30.0;
+            state.annotating = paused;
+
+            let undos = if to_start { state.undo.len() } else { 1 };
+            for _ in 0..undos {
+                state.undo();
+            }
+            let case = format!("to_start {to_start}, paused {paused}, revive {revive}");
+            assert!(!state.board.is_over(), "{case}");
+            assert_eq!(state.annotating, paused, "{case}");
+            assert_eq!(state.awaiting_first_click, to_start, "{case}");
+            let clock = if to_start {
+                Clock::default()
+            } else {
+                Clock {
+                    secs: 30.0,
+                    used_undo: true,
+                }
+            };
+            assert_eq!(state.clock, clock, "{case}");
+        }
+    }
+
+    #[test]
+    fn menu_flag_and_chord_finish_the_board() {
+        let ctx = egui::Context::default();
+        let mut state = laid("*....\n.....\n");
+        state.handle_click(&ctx, CellClick::Reveal(1, 1));
+
+        state.chord(1, 1); // no flag yet, so nothing to open
+        assert_eq!(state.input_stats.left_clicks, 1);
+        state.mark(&ctx, 0, 0, Marking::Flag);
+        state.chord(1, 1);
+        assert!(state.board.is_won());
+        assert_eq!(state.input_stats.left_clicks, 2);
+    }
+
+    #[test]
+    fn flags_are_reviewed_only_after_the_game() {
+        let ctx = egui::Context::default();
+        let mut state = laid("*....\n.....\n");
+        state.handle_click(&ctx, CellClick::Flag(1, 0));
+        state.handle_click(&ctx, CellClick::Flag(1, 0));
+        state.handle_click(&ctx, CellClick::Flag(4, 1));
+        state.clear_flags();
+        assert_eq!(state.flag_log.len(), 4);
+        assert!(state.flag_log.review(&state.board).is_none());
+
+        state.handle_click(&ctx, CellClick::Reveal(0, 0));
+        let review = state.flag_log.review(&state.board).unwrap();
+        let moves: Vec<_> = review
+            .flags
+            .iter()
+            .map(|flag| (flag.placed.move_number, flag.removed.map(|r| r.move_number)))
+            .collect();
+        assert_eq!(moves, [(1, Some(2)), (3, Some(3))]);
+        assert_eq!(review.on_safe_cells(), 2);
+    }
+
+    #[test]
+    fn a_loss_reports_the_odds_of_the_fatal_cell() {
+        let ctx = egui::Context::default();
+        let mut state = laid("*..\n");
+        state.handle_click(&ctx, CellClick::Reveal(1, 0));
+        assert!(state.fatal_odds.is_none());
+
+        state.handle_click(&ctx, CellClick::Reveal(0, 0));
+        let Some(FatalOdds::Pending(rx)) = &state.fatal_odds else {
+            panic!("the odds should be under way as the game is lost");
+        };
+        let chance = rx.recv().unwrap();
+        assert_eq!(chance, Some(Chance::Counted(0.5)));
+
+        state.reset();
+        assert!(state.fatal_odds.is_none());
+    }
+
+    #[test]
+    fn notes_stop_the_clock_and_are_saved_with_the_game() {
+        let ctx = egui::Context::default();
+        let mut state = AppState::default();
+        let dir = std::env::temp_dir().join(format!("minesweeper-notes-{}", std::process::id()));
+        state.autosave = Some(Autosave::new(dir.join("autosave.bin")));
+        state.board = Board::decode_layout("*..\n...\n..*\n").unwrap();
+        state.awaiting_first_click = false;
+        state.handle_click(&ctx, CellClick::Reveal(2, 0));
+        state.save_now().unwrap();
+
+        state.annotating = true;
+        state.annotate(0, 0);
+        let revision = state.board.revision();
+        let input = egui::RawInput {
+            time: Some(30.0),
+            ..Default::default()
+        };
+        let _ = ctx.run(input, |ctx| state.tick_autosave(ctx, true));
+        assert_eq!(state.clock.secs, 0.0);
+        assert_eq!(state.board.revision(), revision);
+
+        let session = state.autosave.as_ref().and_then(Autosave::load).unwrap();
+        assert_eq!(session.annotations.get(0, 0), Some(PALETTE[0]));
+        state.reset();
+        assert!(state.notes.is_empty());
+        state.recovered = Some(session);
+        state.recover();
+        assert_eq!(state.notes.get(0, 0), Some(PALETTE[0]));
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn cells_changed_after_the_board_is_drawn_ask_for_another_frame() {
+        let ctx = egui::Context::default();
+        let mut state = laid("*..\n");
+        let frame = |state: &mut AppState| {
+            let mut asked = false;
+            let _ = ctx.run(egui::RawInput::default(), |ctx| {
+                state.sync_revision(ctx);
+                asked = ctx.has_requested_repaint();
+            });
+            asked
+        };
+        let settle = |state: &mut AppState| (0..10).any(|_| !frame(state));
+        assert!(settle(&mut state), "egui's own first frames");
+        state.board.toggle_flag(1, 0);
+        assert!(frame(&mut state));
+        assert!(settle(&mut state));
+        assert!(!frame(&mut state), "nothing changed since");
+    }
+
+    #[test]
+    fn moves_are_journaled_for_the_debug_snapshot() {
+        let ctx = egui::Context::default();
+        let mut state = laid("*..\n...\n");
+        state.handle_click(&ctx, CellClick::Reveal(2, 0));
+        state.mark(&ctx, 0, 0, Marking::Flag);
+        state.clear_flags();
+        state.handle_click(&ctx, CellClick::Flag(0, 0));
+        assert_eq!(
+            state.journal.actions(),
+            [
+                Action::Reveal(2, 0),
+                Action::Mark(0, 0, Mark::Flag),
+                Action::ClearFlags,
+                Action::Flag(0, 0),
+            ]
+        );
+        let moves = state.journal.commands();
+        let text = minesweeper_rs::report::DebugSnapshot::new(&state.board)
+            .moves(&moves)
+            .to_string();
+        assert!(text.ends_with("moves: 4\n  1. C1\n  2. f A1\n  3. c\n  4. f A1\n"));
+
+        state.reset();
+        assert!(state.journal.actions().is_empty());
+    }
+
+    #[test]
+    fn the_third_hint_plays_the_move_and_every_hint_costs_time() {
+        let ctx = egui::Context::default();
+        let mut state = laid("*..\n...\n.*.\n");
+        state.handle_click(&ctx, CellClick::Reveal(2, 0));
+        state.handle_click(&ctx, CellClick::Reveal(0, 1));
+
+        state.hint(&ctx);
+        state.hint(&ctx);
+        assert_eq!(state.constraints, Some(vec![(1, 0)]));
+        assert!(!state.board.cell(0, 0).is_flagged());
+        state.hint(&ctx);
+        assert!(state.board.cell(0, 0).is_flagged());
+        assert_eq!(state.clock.secs, 35.0);
+        assert_eq!(state.hint_note.as_ref().unwrap().1, "A1 is a mine.");
+
+        // the flag changed the board, so the ladder starts again
+        state.hint(&ctx);
+        assert_eq!(state.hints.used(), [2, 1, 1]);
+    }
+
+    #[test]
+    fn settings_go_back_where_they_came_from() {
+        let ctx = egui::Context::default();
+        let mut app = MinesweeperApp::new(AppState::default());
+        app.start_game(Difficulty::Beginner);
+
+        app.navigate(Screen::Settings);
+        frame(&ctx, &mut app);
+        assert_eq!(app.screen, Screen::Settings);
+        assert_eq!(app.settings_back, Screen::Game);
+
+        // a trip to the themes and back still returns to the game
+        app.navigate(Screen::Themes);
+        frame(&ctx, &mut app);
+        app.navigate(Screen::Settings);
+        assert_eq!(app.settings_back, Screen::Game);
+    }
+
+    /// Put `app` in a game with a cell open and a flag down; the mines
+    /// are only placed on the first reveal, so a flag alone isn't a game.
+    fn mid_game(app: &mut MinesweeperApp) {
+        app.start_game(Difficulty::Beginner);
+        app.state.board = Board::decode_layout("*..\n...\n..*\n").unwrap();
+        app.state.awaiting_first_click = false;
+        app.state.board.reveal_cell(1, 0);
+        app.state.board.toggle_flag(0, 0);
+    }
+
+    /// Run one headless frame in which the window is asked to close.
+    fn close_frame(ctx: &egui::Context, app: &mut MinesweeperApp) -> Vec<egui::ViewportCommand> {
+        let mut input = egui::RawInput::default();
+        input
+            .viewports
+            .entry(egui::ViewportId::ROOT)
+            .or_default()
+            .events
+            .push(egui::ViewportEvent::Close);
+        let mut output = ctx.run(input, |ctx| app.ui(ctx));
+        output
+            .viewport_output
+            .remove(&egui::ViewportId::ROOT)
+            .map(|viewport| viewport.commands)
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn closing_mid_game_asks_and_cancel_keeps_running() {
+        let ctx = egui::Context::default();
+        let mut app = MinesweeperApp::new(AppState::default());
+        mid_game(&mut app);
+
+        let commands = close_frame(&ctx, &mut app);
+        assert!(commands.contains(&egui::ViewportCommand::CancelClose));
+        assert!(matches!(app.exit, Exit::Asking { .. }));
+
+        // asking again doesn't let the close through either
+        let commands = close_frame(&ctx, &mut app);
+        assert!(commands.contains(&egui::ViewportCommand::CancelClose));
+
+        app.exit = Exit::Running; // what Cancel does
+        frame(&ctx, &mut app);
+        assert_eq!(app.exit, Exit::Running);
+        assert!(app.state.board.cell(0, 0).is_flagged());
+    }
+
+    #[test]
+    fn closing_without_a_game_just_closes() {
+        let ctx = egui::Context::default();
+        let mut app = MinesweeperApp::new(AppState::default());
+        let commands = close_frame(&ctx, &mut app);
+        assert!(!commands.contains(&egui::ViewportCommand::CancelClose));
+        assert_eq!(app.exit, Exit::Running);
+    }
+
+    #[test]
+    fn ctrl_c_mid_game_asks_then_quits() {
+        let ctx = egui::Context::default();
+        let mut app = MinesweeperApp::new(AppState::default());
+        mid_game(&mut app);
+
+        app.interrupt.store(true, Ordering::Relaxed);
+        frame(&ctx, &mut app);
+        assert!(matches!(app.exit, Exit::Asking { .. }));
+
+        // no autosave slot in tests, so the second Ctrl+C quits unsaved
+        app.interrupt.store(true, Ordering::Relaxed);
+        frame(&ctx, &mut app);
+        assert_eq!(app.exit, Exit::Quitting { keep_save: false });
+    }
+
+    #[test]
+    fn both_screens_draw_headless() {
+        let ctx = egui::Context::default();
+        let mut app = MinesweeperApp::new(AppState::default());
+        frame(&ctx, &mut app);
+        app.start_game(Difficulty::Beginner);
+        frame(&ctx, &mut app);
+        app.state.board.reveal_all();
+        frame(&ctx, &mut app);
+        assert_eq!(app.screen, Screen::Game);
+    }
+
+    #[test]
+    fn whats_new_shows_once_per_version() {
+        let changelog = Changelog::parse(
+            r#"[{"version": "1.0.0", "entries": [{"category": "added", "text": "a"}]},
+                {"version": "1.1.0", "entries": [{"category": "fixed", "text": "b"}]},
+                {"version": "1.2.0", "entries": [{"category": "fixed", "text": "c"}]}]"#,
+        )
+        .unwrap();
+        let v = |text: &str| text.parse::<Version>().unwrap();
+        let mut state = AppState::default();
+        let shown = |state: &AppState| -> Vec<String> {
+            state
+                .whats_new
+                .iter()
+                .map(|r| r.version.to_string())
+                .collect()
+        };
+
+        // a first run shows only this version's notes
+        state.note_whats_new(&changelog, &v("1.0.0"));
+        assert_eq!(shown(&state), ["1.0.0"]);
+        state.whats_new.clear(); // dismissed
+        state.note_whats_new(&changelog, &v("1.0.0"));
+        assert!(state.whats_new.is_empty());
+
+        // skipping a version shows both, then nothing on the next start
+        state.note_whats_new(&changelog, &v("1.2.0"));
+        assert_eq!(shown(&state), ["1.2.0", "1.1.0"]);
+        state.whats_new.clear();
+        state.note_whats_new(&changelog, &v("1.2.0"));
+        assert!(state.whats_new.is_empty());
+
+        // going back to an older build doesn't forget what was seen
+        state.note_whats_new(&changelog, &v("1.1.0"));
+        state.note_whats_new(&changelog, &v("1.2.0"));
+        assert!(state.whats_new.is_empty());
+        assert_eq!(state.settings.seen_version, "1.2.0");
+    }
+}
@@ -0,0 +1,47 @@
+//! Help for beginners that stops short of playing for them.
+//!
+//! "Open on number": when a flag completes exactly one revealed number, so
+//! that it touches as many flags as it shows, its other hidden neighbours
+//! can be opened for the player. Before offering them the flags around
+//! that number are checked against [`solver::contradicted_flags`], so a
+//! flag the numbers prove wrong never opens anything. A flag that is wrong
+//! without being provably so still does, and sets off a mine as a manual
+//! open would have.
+
+use crate::{solver, Board};
+
+/// The cells to open after the player flags (x, y), in row-major order,
+/// or none if the flag completes no number, more than one, or one whose
+/// flags the visible numbers contradict.
+///
+/// ```
+/// use minesweeper_rs::{assist, Board};
+///
+/// let mut board = Board::decode_layout("*....\n.....\n")?;
+/// board.reveal_cell(1, 1);
+/// board.toggle_flag(0, 0);
+/// assert_eq!(
+///     assist::open_on_number(&board, 0, 0),
+///     [(1, 0), (2, 0), (0, 1), (2, 1)]
+/// );
+/// # Ok::<(), minesweeper_rs::LayoutError>(())
+/// ```
+pub fn open_on_number(board: &Board, x: usize, y: usize) -> Vec<(usize, usize)> {
+    if !board.cell(x, y).is_flagged() {
+        return Vec::new();
+    }
+    let completed: Vec<_> = board
+        .neighbors(x, y)
+        .map(|(nx, ny)| ((nx, ny), board.chord_targets(nx, ny)))
+        .filter(|(_, targets)| !targets.is_empty())
+        .collect();
+    let Ok([((nx, ny), targets)]) = <[_; 1]>::try_from(completed) else {
+        return Vec::new();
+    };
+
+    let wrong = solver::contradicted_flags(board);
+    if board.neighbors(nx, ny).any(|cell| wrong.contains(&cell)) {
+        return Vec::new();
+    }
+    targets
+}
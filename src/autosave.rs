@@ -0,0 +1,193 @@
+//! Crash-safe autosave slot. Frontends save the running session every so
+//! often and clear the slot on a clean exit, so a slot that is still there
+//! at startup means the last run ended abruptly and can be recovered.
+
+use crate::annotations::Annotations;
+use crate::Board;
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+const MAGIC: &[u8; 4] = b"MSS2";
+const MAGIC_V1: &[u8; 4] = b"MSS1"; // no notes, and the board runs to the end
+
+/// A game in progress: the board plus how long it has been played.
+pub struct Session {
+    /// The board as it was saved.
+    pub board: Board,
+    /// Seconds of play before the save.
+    pub elapsed_secs: f64,
+    /// The player's notes on the board; empty in slots from before notes.
+    pub annotations: Annotations,
+}
+
+impl Session {
+    /// Magic `MSS2`, elapsed seconds as an `f64`, the board snapshot's
+    /// length as a `u32` and the snapshot, then the notes.
+    fn encode(board: &Board, elapsed_secs: f64, annotations: &Annotations) -> Vec<u8> {
+        let state = board.encode_state();
+        let mut out = MAGIC.to_vec();
+        out.extend_from_slice(&elapsed_secs.to_le_bytes());
+        out.extend_from_slice(&(state.len() as u32).to_le_bytes());
+        out.extend_from_slice(&state);
+        out.extend_from_slice(&annotations.encode());
+        out
+    }
+
+    /// Read `encode` output, or an `MSS1` slot from before notes; `None` if
+    /// it is cut short or corrupt.
+    pub fn decode(bytes: &[u8]) -> Option<Session> {
+        let (rest, v1) = match bytes.strip_prefix(MAGIC) {
+            Some(rest) => (rest, false),
+            None => (bytes.strip_prefix(MAGIC_V1)?, true),
+        };
+        let (elapsed, rest) = rest.split_at_checked(8)?;
+        let elapsed_secs = f64::from_le_bytes(elapsed.try_into().ok()?);
+        let (board, notes) = if v1 {
+            (rest, None)
+        } else {
+            let (len, rest) = rest.split_first_chunk::<4>()?;
+            let (board, notes) = rest.split_at_checked(u32::from_le_bytes(*len) as usize)?;
+            (board, Some(notes))
+        };
+        match Board::decode_state(board) {
+            Ok(board) if elapsed_secs.is_finite() => {
+                let annotations = match notes {
+                    Some(notes) => Annotations::decode(notes, board.width(), board.height())?,
+                    None => Annotations::default(),
+                };
+                Some(Session {
+                    board,
+                    elapsed_secs: elapsed_secs.max(0.0),
+                    annotations,
+                })
+            }
+            Ok(_) => None,
+            Err(err) => {
+                warn!(%err, "ignoring unreadable autosave");
+                None
+            }
+        }
+    }
+}
+
+/// Why a slot that is there couldn't be recovered.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum AutosaveError {
+    /// The file exists but couldn't be read.
+    Unreadable(io::Error),
+    /// The file was read but isn't a save this version understands.
+    Corrupt,
+}
+
+impl fmt::Display for AutosaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AutosaveError::Unreadable(err) => write!(f, "can't read the save: {err}"),
+            AutosaveError::Corrupt => f.write_str("the save is corrupt"),
+        }
+    }
+}
+
+impl std::error::Error for AutosaveError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AutosaveError::Unreadable(err) => Some(err),
+            AutosaveError::Corrupt => None,
+        }
+    }
+}
+
+/// One autosave file on disk.
+///
+/// ```no_run
+/// use minesweeper_rs::autosave::Autosave;
+/// use minesweeper_rs::Board;
+///
+/// let slot = Autosave::new("autosave.bin");
+/// slot.save(&Board::new(9, 9, 10), 12.5)?;
+/// let session = slot.load().expect("just saved");
+/// assert_eq!(session.elapsed_secs, 12.5);
+/// slot.clear()?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct Autosave {
+    path: PathBuf,
+}
+
+impl Autosave {
+    /// A slot stored at `path`; nothing is read or written yet.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Autosave { path: path.into() }
+    }
+
+    /// Where the slot lives.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Write the session to a temporary file and rename it over the slot,
+    /// so a crash mid-write leaves the previous save intact.
+    pub fn save(&self, board: &Board, elapsed_secs: f64) -> io::Result<()> {
+        self.save_annotated(board, elapsed_secs, &Annotations::default())
+    }
+
+    /// `save`, keeping the player's notes on the board too.
+    pub fn save_annotated(
+        &self,
+        board: &Board,
+        elapsed_secs: f64,
+        annotations: &Annotations,
+    ) -> io::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let tmp = self.path.with_extension("tmp");
+        let mut file = fs::File::create(&tmp)?;
+        file.write_all(&Session::encode(board, elapsed_secs, annotations))?;
+        file.sync_all()?;
+        fs::rename(&tmp, &self.path)
+    }
+
+    /// The saved session, if there is a readable one.
+    pub fn load(&self) -> Option<Session> {
+        self.read().ok().flatten()
+    }
+
+    /// The saved session, `None` if the slot is empty, or why the slot
+    /// that is there can't be recovered.
+    ///
+    /// ```
+    /// use minesweeper_rs::autosave::{Autosave, AutosaveError};
+    ///
+    /// let dir = std::env::temp_dir().join("autosave-read-doc");
+    /// let slot = Autosave::new(dir.join("autosave.bin"));
+    /// assert!(slot.read()?.is_none());
+    /// std::fs::create_dir_all(&dir)?;
+    /// std::fs::write(slot.path(), b"MSS2 garbage")?;
+    /// assert!(matches!(slot.read(), Err(AutosaveError::Corrupt)));
+    /// # std::fs::remove_dir_all(&dir)?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn read(&self) -> Result<Option<Session>, AutosaveError> {
+        let bytes = match fs::read(&self.path) {
+            Ok(bytes) => bytes,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(AutosaveError::Unreadable(err)),
+        };
+        Session::decode(&bytes)
+            .map(Some)
+            .ok_or(AutosaveError::Corrupt)
+    }
+
+    /// Remove the slot after a clean exit. A missing slot is fine.
+    pub fn clear(&self) -> io::Result<()> {
+        match fs::remove_file(&self.path) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        }
+    }
+}
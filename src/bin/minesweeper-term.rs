@@ -0,0 +1,623 @@
+//! A whole game in the terminal. Pick a difficulty, or `custom` to size
+//! your own board and save it as a preset. Then type `x y` or a cell
+//! name such as `B7` to reveal, `f x y` or `f B7` to flag, `c` to clear
+//! every flag, `ra` to open every unflagged cell once there is a flag per
+//! mine (after a yes, as it stakes the game on the flags), `h` for a hint (again for a stronger one, up to the move
+//! itself) and `analyze` for a report on how hard the board is from here
+//! (it reads the mines, so it spoils). A loss says how likely the cell
+//! that lost it was to be a mine. `n` gives up the game for a new one and
+//! `q` gives up and quits; either way a game that was under way is
+//! abandoned, not left unfinished.
+//!
+//! cargo run --bin minesweeper-term --no-default-features -- --style compact
+//!
+//! `--style` is `classic` (the default), `compact` or `grid`, and
+//! `--markers` is `classic`, `minimal`, `letters` or your own flag, question
+//! mark and mine like `F,?,M`. `--preset NAME` skips the prompt for a
+//! saved board, and `--config PATH` says where presets are kept
+//! (`minesweeper.toml` by default). `--symmetry` is `horizontal`,
+//! `vertical` or `rotational`, for a mine layout that mirrors itself or
+//! looks the same upside down, and `--spaced` keeps any two mines from
+//! sharing a side.
+//!
+//! `--board-file PATH` plays a layout file (`*` for a mine, `.` for a safe
+//! cell) instead. `--analyze` prints the report for the board and stops,
+//! for grading puzzles, and `--json` prints reports as JSON.
+//!
+//! `--serve PATH` plays the board over a Unix socket at PATH instead of
+//! here: every client sends the same commands and gets the board as JSON
+//! after each move. With `--seats N` it is a race instead, each of the
+//! first N clients playing their own copy of the board. `--attach PATH`
+//! joins such a game from this terminal, and `--spectate PATH` watches one
+//! without being able to move.
+//!
+//! Errors go to stderr as one line, and the exit code says what kind: 2
+//! for a bad command line, 3 for a board or preset that can't be used and
+//! 4 for a terminal or socket that failed, as in the game's own commands.
+
+use minesweeper_rs::analysis::AnalysisReport;
+use minesweeper_rs::difficulty::{Presets, Reply, Wizard};
+use minesweeper_rs::render::{BoardRenderer, Markers, Style};
+use minesweeper_rs::session::{AbandonReason, Command, Session};
+use minesweeper_rs::{Board, Difficulty, Symmetry};
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::str::FromStr;
+
+/// Why the game stopped early; see the exit codes above.
+#[derive(Debug)]
+enum Failure {
+    Usage(String),
+    Input(String),
+    Io(io::Error),
+}
+
+impl Failure {
+    fn code(&self) -> u8 {
+        match self {
+            Failure::Usage(_) => 2,
+            Failure::Input(_) => 3,
+            Failure::Io(_) => 4,
+        }
+    }
+
+    fn usage(err: impl fmt::Display) -> Failure {
+        Failure::Usage(err.to_string())
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Failure::Usage(message) | Failure::Input(message) => f.write_str(message),
+            Failure::Io(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl From<io::Error> for Failure {
+    fn from(err: io::Error) -> Self {
+        Failure::Io(err)
+    }
+}
+
+fn main() -> ExitCode {
+    match run(std::env::args().skip(1), io::stdin().lock(), io::stdout()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(failure) => {
+            eprintln!("error: {failure}");
+            ExitCode::from(failure.code())
+        }
+    }
+}
+
+/// The command line, read but not yet acted on.
+struct Options {
+    style: Style,
+    markers: Markers,
+    preset: Option<String>,
+    config: PathBuf,
+    board_file: Option<PathBuf>,
+    analyze: bool,
+    json: bool,
+    serve: Option<PathBuf>,
+    attach: Option<PathBuf>,
+    spectate: Option<PathBuf>,
+    seats: usize,
+    symmetry: Option<Symmetry>,
+    spaced: bool,
+}
+
+impl Options {
+    fn parse(args: impl IntoIterator<Item = String>) -> Result<Options, Failure> {
+        let mut options = Options {
+            style: Style::default(),
+            markers: Markers::default(),
+            preset: None,
+            config: PathBuf::from("minesweeper.toml"),
+            board_file: None,
+            analyze: false,
+            json: false,
+            serve: None,
+            attach: None,
+            spectate: None,
+            seats: 1,
+            symmetry: None,
+            spaced: false,
+        };
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--style" => options.style = parsed(args.next())?,
+                "--markers" => options.markers = parsed(args.next())?,
+                "--preset" => options.preset = args.next(),
+                "--config" => options.config = args.next().unwrap_or_default().into(),
+                "--board-file" => options.board_file = args.next().map(PathBuf::from),
+                "--analyze" => options.analyze = true,
+                "--json" => options.json = true,
+                "--symmetry" => options.symmetry = Some(parsed(args.next())?),
+                "--spaced" => options.spaced = true,
+                "--serve" => options.serve = args.next().map(PathBuf::from),
+                "--attach" => options.attach = args.next().map(PathBuf::from),
+                "--spectate" => options.spectate = args.next().map(PathBuf::from),
+                "--seats" => options.seats = parsed(args.next())?,
+                other => return Err(Failure::usage(format!("unknown argument {other}"))),
+            }
+        }
+        if options.seats == 0 {
+            return Err(Failure::usage("--seats needs at least one seat"));
+        }
+        Ok(options)
+    }
+}
+
+/// Play as `args` say, reading the player's lines from `input` and
+/// writing the board and every answer to `out`.
+fn run(
+    args: impl IntoIterator<Item = String>,
+    input: impl BufRead,
+    mut out: impl Write + Send + 'static,
+) -> Result<(), Failure> {
+    let options = Options::parse(args)?;
+    if let Some(path) = options.attach {
+        return Ok(join(&path, input, out)?);
+    }
+    if let Some(path) = options.spectate {
+        return Ok(watch(&path, out)?);
+    }
+
+    // an unreadable file is left alone rather than overwritten by a save
+    let config = &options.config;
+    let (presets, writable) = match Presets::load(config) {
+        Ok(presets) => (presets, Some(config.as_path())),
+        Err(err) => {
+            writeln!(out, "Can't read presets from {}: {err}", config.display())?;
+            (Presets::default(), None)
+        }
+    };
+    let mut lines = input.lines();
+    // a new game replays the file's layout, or deals another board
+    let (board, deal) = match &options.board_file {
+        Some(path) => {
+            let board = std::fs::read_to_string(path)
+                .map_err(|err| err.to_string())
+                .and_then(|text| Board::decode_layout(&text).map_err(|err| err.to_string()))
+                .map_err(|err| Failure::Input(format!("{}: {err}", path.display())))?;
+            (board, None)
+        }
+        None => {
+            let difficulty = match &options.preset {
+                Some(name) => presets.get(name).ok_or_else(|| {
+                    Failure::usage(format!("no preset called {name} in {}", config.display()))
+                })?,
+                None => match choose(&mut lines, &mut out, presets, writable)? {
+                    Some(difficulty) => difficulty,
+                    None => return Ok(()),
+                },
+            };
+            let mut deal = difficulty.options().avoid_adjacent_mines(options.spaced);
+            if let Some(symmetry) = options.symmetry {
+                deal = deal.symmetry(symmetry);
+            }
+            (deal.clone().build().map_err(Failure::usage)?, Some(deal))
+        }
+    };
+    if options.analyze {
+        print_report(&mut out, &board, options.json)?;
+        return Ok(());
+    }
+
+    let mut session = Session::new(board.clone());
+    if let Some(path) = &options.serve {
+        if options.seats > 1 {
+            return Ok(race(path, vec![session; options.seats], out)?);
+        }
+        host(path, &mut session, &mut out)?;
+    }
+    while !session.board().is_over() {
+        let shown = options.style.render_with(session.board(), &options.markers);
+        write!(out, "{shown}")?;
+        out.flush()?;
+        let Some(line) = lines.next() else { break };
+        let line = line?;
+        match line.trim() {
+            "q" => {
+                if session.abandon(AbandonReason::Quit) {
+                    writeln!(out, "Game abandoned.")?;
+                }
+                break;
+            }
+            "n" => {
+                if session.abandon(AbandonReason::NewGame) {
+                    writeln!(out, "Game abandoned.")?;
+                }
+                let next = match &deal {
+                    Some(deal) => deal.clone().build().map_err(Failure::usage)?,
+                    None => board.clone(),
+                };
+                session = Session::new(next);
+                continue;
+            }
+            _ => {}
+        }
+        let command = match line.parse::<Command>() {
+            Ok(command) => command,
+            Err(err) => {
+                writeln!(out, "{err}")?;
+                continue;
+            }
+        };
+        if command == Command::ClearFlags {
+            writeln!(out, "Remove all {} flags? [y/N]", session.board().flags())?;
+            let answer = lines.next().transpose()?.unwrap_or_default();
+            if !answer.trim().eq_ignore_ascii_case("y") {
+                continue;
+            }
+        }
+        let staked = session.board().flags() == session.board().mine_count();
+        if command == Command::RevealRemaining && staked {
+            writeln!(out, "Open every unflagged cell? A wrong flag loses. [y/N]")?;
+            let answer = lines.next().transpose()?.unwrap_or_default();
+            if !answer.trim().eq_ignore_ascii_case("y") {
+                continue;
+            }
+        }
+        if command == Command::Analyze {
+            print_report(&mut out, session.board(), options.json)?;
+            continue;
+        }
+        let reply = session.apply(command);
+        if !reply.is_empty() {
+            writeln!(out, "{reply}")?;
+        }
+    }
+    let (board, hints) = (session.board(), session.hints());
+    if board.is_won() {
+        writeln!(out, "You win!")?;
+    } else if board.is_lost() {
+        writeln!(out, "Boom.")?;
+        if let Some(chance) = session.fatal() {
+            writeln!(out, "{chance}")?;
+        }
+    }
+    let [regions, numbers, answers] = hints.used();
+    if hints.penalty_secs() > 0 {
+        writeln!(
+            out,
+            "Hints: {regions} where to look, {numbers} which number, {answers} moves played \
+             (+{}s)",
+            hints.penalty_secs()
+        )?;
+    }
+    Ok(())
+}
+
+/// A flag's value, read as a `T`; a missing one reads as empty.
+fn parsed<T: FromStr>(value: Option<String>) -> Result<T, Failure>
+where
+    T::Err: fmt::Display,
+{
+    value.unwrap_or_default().parse().map_err(Failure::usage)
+}
+
+/// Print how hard `board` is from where it stands.
+fn print_report(out: &mut impl Write, board: &Board, json: bool) -> io::Result<()> {
+    let report = AnalysisReport::new(board);
+    if json {
+        writeln!(out, "{}", report.to_json())
+    } else {
+        write!(out, "{report}")
+    }
+}
+
+/// Play `session` over a socket at `path` until the game is over.
+#[cfg(unix)]
+fn host(path: &Path, session: &mut Session, out: &mut impl Write) -> io::Result<()> {
+    let server = minesweeper_rs::serve::Server::bind(path)?;
+    writeln!(out, "Serving on {}; connect with --attach.", path.display())?;
+    out.flush()?;
+    server.run(session)
+}
+
+/// Race on copies of one board over a socket at `path`, then say how each
+/// seat did.
+#[cfg(unix)]
+fn race(path: &Path, mut seats: Vec<Session>, mut out: impl Write) -> io::Result<()> {
+    let server = minesweeper_rs::serve::Server::bind(path)?;
+    writeln!(
+        out,
+        "Racing {} seats on {}; join with --attach or watch with --spectate.",
+        seats.len(),
+        path.display()
+    )?;
+    out.flush()?;
+    server.run_race(&mut seats)?;
+    for (seat, session) in seats.iter().enumerate() {
+        let result = if session.board().is_won() {
+            "won"
+        } else {
+            "lost"
+        };
+        writeln!(out, "Seat {seat}: {result}")?;
+    }
+    Ok(())
+}
+
+/// Send `input`'s lines to the game served at `path`, copying what comes
+/// back to `out`.
+#[cfg(unix)]
+fn join(path: &Path, input: impl BufRead, out: impl Write + Send + 'static) -> io::Result<()> {
+    minesweeper_rs::serve::attach(path, input, out)
+}
+
+/// Copy everything the game served at `path` sends to `out`, as a
+/// spectator.
+#[cfg(unix)]
+fn watch(path: &Path, out: impl Write) -> io::Result<()> {
+    minesweeper_rs::serve::spectate(path, out)
+}
+
+#[cfg(not(unix))]
+fn host(_: &Path, _: &mut Session, _: &mut impl Write) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "--serve needs Unix sockets",
+    ))
+}
+
+#[cfg(not(unix))]
+fn race(_: &Path, _: Vec<Session>, _: impl Write) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "--serve needs Unix sockets",
+    ))
+}
+
+#[cfg(not(unix))]
+fn join(_: &Path, _: impl BufRead, _: impl Write) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "--attach needs Unix sockets",
+    ))
+}
+
+#[cfg(not(unix))]
+fn watch(_: &Path, _: impl Write) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "--spectate needs Unix sockets",
+    ))
+}
+
+/// Run the difficulty prompt, saving a new preset to `config` if asked.
+/// `None` if the player quits.
+fn choose(
+    lines: &mut impl Iterator<Item = io::Result<String>>,
+    out: &mut impl Write,
+    mut presets: Presets,
+    config: Option<&Path>,
+) -> io::Result<Option<Difficulty>> {
+    let mut wizard = Wizard::new(presets.clone());
+    writeln!(out, "{}", wizard.prompt())?;
+    for line in lines.by_ref() {
+        match wizard.feed(&line?) {
+            Reply::Ask(text) => writeln!(out, "{text}")?,
+            Reply::Quit => break,
+            Reply::Start {
+                difficulty,
+                save_as,
+            } => {
+                if let Some(name) = save_as {
+                    let Some(config) = config else {
+                        writeln!(out, "Not saving {name}: the presets file couldn't be read.")?;
+                        return Ok(Some(difficulty));
+                    };
+                    let saved = presets
+                        .insert(&name, difficulty)
+                        .and_then(|_| presets.save(config));
+                    match saved {
+                        Ok(()) => {
+                            writeln!(out, "Saved. Next time, start it with --preset {name}.")?
+                        }
+                        Err(err) => writeln!(
+                            out,
+                            "Couldn't save {name} to {}: {err}. Playing it anyway.",
+                            config.display()
+                        )?,
+                    }
+                }
+                return Ok(Some(difficulty));
+            }
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    /// Output shared with the test, as `run` may hand it to another thread.
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl Captured {
+        fn text(&self) -> String {
+            String::from_utf8_lossy(&self.0.lock().unwrap()).into_owned()
+        }
+    }
+
+    impl Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// A fresh directory of this test run's own.
+    fn scratch(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir()
+            .join(format!("minesweeper-term-{}", std::process::id()))
+            .join(name);
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// `layout` written to a board file in `dir`.
+    fn board_file(dir: &Path, layout: &str) -> String {
+        let path = dir.join("board.txt");
+        std::fs::write(&path, layout).unwrap();
+        path.display().to_string()
+    }
+
+    /// Run with `args`, typing `input`; what it printed, and how it ended.
+    fn term(args: &[&str], input: &str) -> (Result<(), Failure>, String) {
+        let out = Captured::default();
+        let args = args.iter().map(|arg| arg.to_string());
+        let ended = run(args, input.as_bytes(), out.clone());
+        (ended, out.text())
+    }
+
+    #[test]
+    fn a_board_file_is_played_to_a_win() {
+        let dir = scratch("win");
+        let file = board_file(&dir, "*..\n...\n");
+        let (ended, out) = term(&["--board-file", &file], "2 1\nA2\n");
+        assert!(ended.is_ok());
+        assert!(out.ends_with("You win!\n"), "{out}");
+    }
+
+    #[test]
+    fn a_loss_says_how_likely_the_fatal_cell_was() {
+        let dir = scratch("loss");
+        let file = board_file(&dir, "*..\n");
+        let (ended, out) = term(&["--board-file", &file], "1 0\n0 0\n");
+        assert!(ended.is_ok());
+        assert!(
+            out.ends_with("Boom.\nIt was a coin flip at 50%.\n"),
+            "{out}"
+        );
+    }
+
+    #[test]
+    fn reveal_remaining_asks_first_and_stakes_the_game_on_the_flags() {
+        let dir = scratch("reveal-remaining");
+        let file = board_file(&dir, "*..\n...\n");
+        let (_, out) = term(&["--board-file", &file], "ra\nq\n");
+        assert!(out.contains("`ra` needs a flag on every mine first: 0 of 1 flagged"));
+
+        let (_, out) = term(&["--board-file", &file], "f 0 0\nra\nn\nq\n");
+        assert!(out.contains("A wrong flag loses. [y/N]"), "{out}");
+        assert!(!out.contains("Finished"), "{out}");
+
+        let (_, out) = term(&["--board-file", &file], "f 0 0\nra\ny\n");
+        assert!(
+            out.ends_with("Finished with reveal-remaining.\nYou win!\n"),
+            "{out}"
+        );
+        let (_, out) = term(&["--board-file", &file], "f 1 0\nra\ny\n");
+        assert!(
+            out.ends_with("a flag was on a safe cell.\nBoom.\n"),
+            "{out}"
+        );
+    }
+
+    #[test]
+    fn quitting_a_game_under_way_abandons_it() {
+        let dir = scratch("quit");
+        let file = board_file(&dir, "*..\n...\n");
+        let (_, out) = term(&["--board-file", &file], "q\n");
+        assert!(!out.contains("Game abandoned."), "{out}");
+        let (_, out) = term(&["--board-file", &file], "2 1\nq\n");
+        assert!(out.ends_with("Game abandoned.\n"), "{out}");
+    }
+
+    #[test]
+    fn clearing_flags_waits_for_a_yes() {
+        let dir = scratch("clear");
+        let file = board_file(&dir, "*..\n...\n");
+        let (_, out) = term(&["--board-file", &file], "f 0 0\nc\nn\nc\ny\n");
+        assert_eq!(out.matches("Remove all 1 flags? [y/N]").count(), 2, "{out}");
+        let (_, out) = term(&["--board-file", &file], "f 0 0\nc\ny\nc\n");
+        assert!(out.contains("Remove all 0 flags?"), "{out}");
+    }
+
+    #[test]
+    fn analyze_reports_and_stops() {
+        let dir = scratch("analyze");
+        let file = board_file(&dir, "*..\n...\n");
+        let (ended, out) = term(&["--board-file", &file, "--analyze", "--json"], "");
+        assert!(ended.is_ok());
+        assert!(out.starts_with('{') && out.ends_with("}\n"), "{out}");
+    }
+
+    #[test]
+    fn a_custom_board_is_saved_as_a_preset_and_started_by_name() {
+        let dir = scratch("presets");
+        let config = dir.join("minesweeper.toml").display().to_string();
+        let input = "custom\n12\n10\n20\ny\nwide\nq\n";
+        let (ended, out) = term(&["--config", &config], input);
+        assert!(ended.is_ok());
+        assert!(
+            out.contains("Next time, start it with --preset wide."),
+            "{out}"
+        );
+        let presets = Presets::load(&config).unwrap();
+        assert_eq!(presets.get("wide").unwrap().spec().params(), (12, 10, 20));
+
+        let (ended, _) = term(&["--config", &config, "--preset", "wide"], "q\n");
+        assert!(ended.is_ok());
+        let (ended, _) = term(&["--config", &config], "quit\n");
+        assert!(ended.is_ok());
+    }
+
+    #[test]
+    fn failures_carry_their_exit_codes() {
+        let dir = scratch("failures");
+        let code = |args: &[&str]| term(args, "").0.unwrap_err().code();
+        assert_eq!(code(&["--fast"]), 2);
+        assert_eq!(code(&["--seats", "0"]), 2);
+        assert_eq!(code(&["--style", "fancy"]), 2);
+        let config = dir.join("minesweeper.toml").display().to_string();
+        assert_eq!(code(&["--config", &config, "--preset", "nope"]), 2);
+        let file = board_file(&dir, "*x\n");
+        assert_eq!(code(&["--board-file", &file]), 3);
+        let missing = dir.join("missing.txt").display().to_string();
+        assert_eq!(code(&["--board-file", &missing]), 3);
+        let socket = dir.join("nobody.sock").display().to_string();
+        assert_eq!(code(&["--attach", &socket]), 4);
+        assert_eq!(code(&["--spectate", &socket]), 4);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn a_served_game_is_played_by_an_attached_client() {
+        let dir = scratch("serve");
+        let file = board_file(&dir, "*..\n...\n");
+        let socket = dir.join("game.sock");
+        let args = ["--board-file", &file, "--serve"].map(String::from);
+        let args = args.into_iter().chain([socket.display().to_string()]);
+        let out = Captured::default();
+        let served = {
+            let out = out.clone();
+            std::thread::spawn(move || run(args, io::empty(), out))
+        };
+        while !socket.exists() {
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+
+        let joined = term(&["--attach", &socket.display().to_string()], "2 1\n0 1\n");
+        assert!(joined.0.is_ok());
+        assert!(joined.1.contains("\"state\":\"won\""), "{}", joined.1);
+        served.join().unwrap().unwrap();
+        assert!(out.text().ends_with("You win!\n"), "{}", out.text());
+    }
+}
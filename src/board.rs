@@ -0,0 +1,1109 @@
+//! The board and the rules of play.
+
+use crate::render::Glyph;
+use crate::rng::BoardRng;
+
+mod counts;
+mod diff;
+mod layout;
+mod marking;
+mod options;
+mod snapshot;
+mod state;
+mod symmetry;
+
+pub use diff::{BoardDiff, CellDiff, SizeMismatch};
+pub use layout::LayoutError;
+pub use marking::{CycleMode, Marking, MarkingResult};
+pub use options::{BoardError, BoardOptions};
+pub use snapshot::{BoardSnapshot, SnapshotMismatch};
+pub use state::{DecodeError, MAX_CELLS};
+pub use symmetry::{Symmetry, UnknownSymmetry};
+
+/// One cell packed into a byte: three state bits plus the neighbour count in
+/// the high nibble, so even multi-million cell boards stay a few MB.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct Bits(u8);
+
+impl Bits {
+    const MINE: u8 = 0b0001;
+    const REVEALED: u8 = 0b0010;
+    const FLAGGED: u8 = 0b0100;
+    const QUESTION: u8 = 0b1000;
+
+    pub(crate) fn is_mine(self) -> bool {
+        self.0 & Self::MINE != 0
+    }
+
+    pub(crate) fn is_revealed(self) -> bool {
+        self.0 & Self::REVEALED != 0
+    }
+
+    pub(crate) fn is_flagged(self) -> bool {
+        self.0 & Self::FLAGGED != 0
+    }
+
+    pub(crate) fn neighbor_mines(self) -> u8 {
+        self.0 >> 4
+    }
+
+    fn set(&mut self, bit: u8, on: bool) {
+        if on {
+            self.0 |= bit;
+        } else {
+            self.0 &= !bit;
+        }
+    }
+
+    fn set_neighbor_mines(&mut self, count: u8) {
+        self.0 = (self.0 & 0x0f) | (count << 4);
+    }
+}
+
+/// What a player can see of one cell. Whether a covered cell hides a mine,
+/// and how many mines surround it, stay unknown until it is revealed or the
+/// game is over.
+///
+/// ```
+/// use minesweeper_rs::Board;
+///
+/// let mut board = Board::decode_layout("..*\n...\n")?;
+/// assert_eq!(board.cell(2, 0).is_mine(), None);
+///
+/// board.reveal_cell(1, 1);
+/// assert_eq!(board.cell(1, 1).is_mine(), Some(false));
+/// assert_eq!(board.cell(1, 1).neighbor_mines(), Some(1));
+/// # Ok::<(), minesweeper_rs::LayoutError>(())
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cell {
+    bits: Bits,
+    visible: bool, // revealed, or the game is over
+}
+
+impl Cell {
+    /// Whether the cell has been opened.
+    ///
+    /// ```
+    /// # let mut board = minesweeper_rs::Board::decode_layout("*..\n")?;
+    /// board.reveal_cell(2, 0);
+    /// assert!(board.cell(2, 0).is_revealed());
+    /// # Ok::<(), minesweeper_rs::LayoutError>(())
+    /// ```
+    pub fn is_revealed(self) -> bool {
+        self.bits.is_revealed()
+    }
+
+    /// Whether the player has flagged the cell.
+    ///
+    /// ```
+    /// # let mut board = minesweeper_rs::Board::decode_layout("*..\n")?;
+    /// board.toggle_flag(0, 0);
+    /// assert!(board.cell(0, 0).is_flagged());
+    /// # Ok::<(), minesweeper_rs::LayoutError>(())
+    /// ```
+    pub fn is_flagged(self) -> bool {
+        self.bits.is_flagged()
+    }
+
+    /// The player's marking on the cell, cleared once it is revealed.
+    ///
+    /// ```
+    /// use minesweeper_rs::{Board, CycleMode, Marking};
+    ///
+    /// let mut board = Board::decode_layout("*..\n")?;
+    /// board.cycle_marking(2, 0, CycleMode::Flags);
+    /// assert_eq!(board.cell(2, 0).marking(), Marking::Flag);
+    /// # Ok::<(), minesweeper_rs::LayoutError>(())
+    /// ```
+    pub fn marking(self) -> Marking {
+        self.bits.marking()
+    }
+
+    /// Whether the cell is a mine, or `None` while that is still hidden.
+    /// Every cell shows once the game is over.
+    ///
+    /// ```
+    /// # let mut board = minesweeper_rs::Board::decode_layout("*.*\n")?;
+    /// assert_eq!(board.cell(2, 0).is_mine(), None);
+    /// board.reveal_cell(0, 0); // boom
+    /// assert_eq!(board.cell(2, 0).is_mine(), Some(true));
+    /// # Ok::<(), minesweeper_rs::LayoutError>(())
+    /// ```
+    pub fn is_mine(self) -> Option<bool> {
+        self.visible.then(|| self.bits.is_mine())
+    }
+
+    /// Mines among the cell's neighbours, for a safe cell that can be seen.
+    /// `None` for covered cells and for mines.
+    ///
+    /// ```
+    /// # let mut board = minesweeper_rs::Board::decode_layout("*.*\n")?;
+    /// assert_eq!(board.cell(1, 0).neighbor_mines(), None);
+    /// board.reveal_cell(1, 0);
+    /// assert_eq!(board.cell(1, 0).neighbor_mines(), Some(2));
+    /// # Ok::<(), minesweeper_rs::LayoutError>(())
+    /// ```
+    pub fn neighbor_mines(self) -> Option<u8> {
+        (self.visible && !self.bits.is_mine()).then(|| self.bits.neighbor_mines())
+    }
+}
+
+const NEIGHBOR_OFFSETS: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+// ---------------- ACTION RESULTS ----------------
+
+/// Why a board action had no effect.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Ignored {
+    /// The coordinates are off the board.
+    OutOfBounds,
+    /// The cell is already open.
+    AlreadyRevealed,
+    /// Flagged cells can't be revealed until unflagged.
+    Flagged,
+    /// The game has already been won or lost.
+    GameOver,
+    /// Flag-limit mode and every flag is in use.
+    NoFlagsLeft,
+}
+
+impl Ignored {
+    /// Short explanation shown to the player.
+    ///
+    /// ```
+    /// use minesweeper_rs::Ignored;
+    ///
+    /// assert_eq!(Ignored::AlreadyRevealed.message(), "cell is already revealed");
+    /// ```
+    pub fn message(self) -> &'static str {
+        match self {
+            Ignored::OutOfBounds => "that cell is outside the board",
+            Ignored::AlreadyRevealed => "cell is already revealed",
+            Ignored::Flagged => "cell is flagged — right-click to unflag first",
+            Ignored::GameOver => "game is over — start a new game",
+            Ignored::NoFlagsLeft => "no flags left — remove one first",
+        }
+    }
+}
+
+/// What a reveal did.
+///
+/// ```
+/// use minesweeper_rs::{Board, Ignored, RevealResult};
+///
+/// let mut board = Board::decode_layout("*..\n")?;
+/// assert_eq!(board.reveal_cell(1, 0), RevealResult::Revealed);
+/// assert_eq!(
+///     board.reveal_cell(1, 0),
+///     RevealResult::Ignored(Ignored::AlreadyRevealed)
+/// );
+/// assert_eq!(board.reveal_cell(2, 0), RevealResult::Won);
+/// # Ok::<(), minesweeper_rs::LayoutError>(())
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RevealResult {
+    /// Cells opened and the game goes on.
+    Revealed,
+    /// A mine went off; the game is lost.
+    Exploded,
+    /// The last safe cell opened; the game is won.
+    Won,
+    /// Nothing changed.
+    Ignored(Ignored),
+}
+
+/// What a flag toggle did.
+///
+/// ```
+/// use minesweeper_rs::{Board, FlagResult};
+///
+/// let mut board = Board::decode_layout("*..\n")?;
+/// assert_eq!(board.toggle_flag(0, 0), FlagResult::Flagged);
+/// assert_eq!(board.toggle_flag(0, 0), FlagResult::Unflagged);
+/// # Ok::<(), minesweeper_rs::LayoutError>(())
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FlagResult {
+    /// A flag went down.
+    Flagged,
+    /// A flag came off.
+    Unflagged,
+    /// Nothing changed.
+    Ignored(Ignored),
+}
+
+/// A game of minesweeper: the mine layout plus what has been revealed and
+/// flagged so far.
+///
+/// ```
+/// use minesweeper_rs::{Board, RevealResult};
+///
+/// // the first click and its neighbours are kept clear, so this opens up
+/// let mut board = Board::with_seed(9, 9, 10, 42, Some((4, 4)));
+/// assert_ne!(board.reveal_cell(4, 4), RevealResult::Exploded);
+/// assert!(board.revealed_safe() >= 9);
+/// ```
+#[derive(Clone)]
+pub struct Board {
+    width: usize,
+    height: usize,
+    mine_count: usize,
+    pub(crate) cells: Vec<Bits>, // row-major, index y * width + x
+    game_over: bool,
+    win: bool,
+    seed: Option<u64>,        // seed the mines were placed from, if any
+    max_flags: Option<usize>, // flag cap for flag-limit mode
+    flags: usize,             // flagged cells, for the mine counter and the cap
+    revealed_safe: usize,     // non-mine cells revealed so far, for O(1) win checks
+    revision: u64,            // bumped once per mutating action
+    dirty: Vec<u32>,          // cell indices changed since the last take_dirty()
+    wavefront: Vec<u32>,      // cells the last reveal opened, ring by ring
+    ring_ends: Vec<u32>,      // where each ring of `wavefront` ends
+    spare: Vec<u32>,          // mine candidates, kept empty so resets reuse the buffer
+}
+
+impl Board {
+    /// A board with randomly placed mines.
+    ///
+    /// # Panics
+    /// If there are more mines than cells. [`BoardOptions::build`] checks
+    /// its arguments instead.
+    ///
+    /// ```
+    /// let board = minesweeper_rs::Board::new(16, 16, 40);
+    /// assert_eq!(board.seed(), None);
+    /// ```
+    pub fn new(width: usize, height: usize, mine_count: usize) -> Self {
+        let mut board = Board::blank(width, height, mine_count);
+        board.lay_mines(rand::random(), None, 0);
+        board
+    }
+
+    /// A board with no mines placed yet, shown while waiting for the first
+    /// click of a no-guess game.
+    ///
+    /// ```
+    /// let board = minesweeper_rs::Board::blank(9, 9, 10);
+    /// assert_eq!(board.mine_count(), 10);
+    /// ```
+    pub fn blank(width: usize, height: usize, mine_count: usize) -> Self {
+        Board {
+            width,
+            height,
+            mine_count,
+            cells: vec![Bits::default(); width * height],
+            game_over: false,
+            win: false,
+            seed: None,
+            max_flags: None,
+            flags: 0,
+            revealed_safe: 0,
+            revision: 0,
+            dirty: Vec::new(),
+            wavefront: Vec::new(),
+            ring_ends: Vec::new(),
+            spare: Vec::new(),
+        }
+    }
+
+    /// Build a board whose mines come from `seed`, keeping the area around
+    /// `safe` clear.
+    ///
+    /// The layout is a stable format: the same arguments give the same board
+    /// on every platform and in every release, so seeds can be shared
+    /// between builds. See `place_mines` for the exact algorithm; the golden
+    /// tests in `tests/generation.rs` fail if it ever drifts.
+    ///
+    /// # Panics
+    /// If the mines don't fit, as for [`Board::new`].
+    ///
+    /// ```
+    /// use minesweeper_rs::Board;
+    ///
+    /// let a = Board::with_seed(16, 16, 40, 7, None);
+    /// let b = Board::with_seed(16, 16, 40, 7, None);
+    /// assert_eq!(a.encode_layout(), b.encode_layout());
+    /// ```
+    pub fn with_seed(
+        width: usize,
+        height: usize,
+        mine_count: usize,
+        seed: u64,
+        safe: Option<(usize, usize)>,
+    ) -> Self {
+        // room permitting, the neighbours of the safe cell stay clear too
+        let radius = usize::from(width * height - mine_count >= 9);
+        Board::with_safe_area(width, height, mine_count, seed, safe, radius)
+    }
+
+    /// As `with_seed`, keeping every cell within `radius` of `safe` clear.
+    /// [`BoardOptions::safe_radius`] checks that the mines still fit.
+    pub(crate) fn with_safe_area(
+        width: usize,
+        height: usize,
+        mine_count: usize,
+        seed: u64,
+        safe: Option<(usize, usize)>,
+        radius: usize,
+    ) -> Self {
+        let mut board = Board::blank(width, height, mine_count);
+        board.seed = Some(seed);
+        board.lay_mines(seed, safe, radius);
+        board
+    }
+
+    /// Width in cells.
+    ///
+    /// ```
+    /// assert_eq!(minesweeper_rs::Board::blank(30, 16, 99).width(), 30);
+    /// ```
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Height in cells.
+    ///
+    /// ```
+    /// assert_eq!(minesweeper_rs::Board::blank(30, 16, 99).height(), 16);
+    /// ```
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Mines on the board.
+    ///
+    /// ```
+    /// assert_eq!(minesweeper_rs::Board::blank(30, 16, 99).mine_count(), 99);
+    /// ```
+    pub fn mine_count(&self) -> usize {
+        self.mine_count
+    }
+
+    /// Seed the mines were placed from, if the board was seeded.
+    ///
+    /// ```
+    /// let board = minesweeper_rs::Board::with_seed(9, 9, 10, 42, None);
+    /// assert_eq!(board.seed(), Some(42));
+    /// ```
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    /// True once the game has been won or lost.
+    ///
+    /// ```
+    /// # let mut board = minesweeper_rs::Board::decode_layout("*.\n")?;
+    /// board.reveal_cell(0, 0);
+    /// assert!(board.is_over());
+    /// # Ok::<(), minesweeper_rs::LayoutError>(())
+    /// ```
+    pub fn is_over(&self) -> bool {
+        self.game_over
+    }
+
+    /// True once every safe cell is revealed.
+    ///
+    /// ```
+    /// # let mut board = minesweeper_rs::Board::decode_layout("*.\n")?;
+    /// board.reveal_cell(1, 0);
+    /// assert!(board.is_won());
+    /// # Ok::<(), minesweeper_rs::LayoutError>(())
+    /// ```
+    pub fn is_won(&self) -> bool {
+        self.game_over && self.win
+    }
+
+    /// True once a mine has gone off.
+    ///
+    /// ```
+    /// # let mut board = minesweeper_rs::Board::decode_layout("*.\n")?;
+    /// board.reveal_cell(0, 0);
+    /// assert!(board.is_lost());
+    /// # Ok::<(), minesweeper_rs::LayoutError>(())
+    /// ```
+    pub fn is_lost(&self) -> bool {
+        self.game_over && !self.win
+    }
+
+    /// What the player can see of (x, y).
+    ///
+    /// # Panics
+    /// If the cell is off the board.
+    ///
+    /// ```
+    /// let board = minesweeper_rs::Board::with_seed(9, 9, 10, 42, None);
+    /// assert!(!board.cell(8, 8).is_revealed());
+    /// ```
+    pub fn cell(&self, x: usize, y: usize) -> Cell {
+        let bits = self.bits(x, y);
+        Cell {
+            bits,
+            visible: self.game_over || bits.is_revealed(),
+        }
+    }
+
+    /// The packed cell, mine bit included whatever the game state.
+    pub(crate) fn bits(&self, x: usize, y: usize) -> Bits {
+        self.cells[y * self.width + x]
+    }
+
+    /// Neighbouring coordinates of a cell that lie on the board.
+    ///
+    /// ```
+    /// let board = minesweeper_rs::Board::blank(9, 9, 10);
+    /// assert_eq!(board.neighbors(0, 0).count(), 3);
+    /// assert_eq!(board.neighbors(4, 4).count(), 8);
+    /// ```
+    pub fn neighbors(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        NEIGHBOR_OFFSETS.iter().filter_map(move |(dx, dy)| {
+            let nx = x as isize + dx;
+            let ny = y as isize + dy;
+            self.in_bounds(nx, ny).then_some((nx as usize, ny as usize))
+        })
+    }
+
+    fn in_bounds(&self, x: isize, y: isize) -> bool {
+        x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height
+    }
+
+    /// Place mines from `seed` around the safe area, then count neighbours.
+    fn lay_mines(&mut self, seed: u64, safe: Option<(usize, usize)>, radius: usize) {
+        let excluded = Board::safe_area(self.width, self.height, safe, radius);
+        self.place_mines(seed, excluded);
+        self.compute_neighbor_counts();
+    }
+
+    /// Whether a cell index lies in the square within `radius` of `safe`,
+    /// clipped to a `width` x `height` board.
+    fn safe_area(
+        width: usize,
+        height: usize,
+        safe: Option<(usize, usize)>,
+        radius: usize,
+    ) -> impl Fn(usize) -> bool {
+        let square = safe.filter(|_| width > 0 && height > 0).map(|(sx, sy)| {
+            let xs = sx.saturating_sub(radius)..=sx.saturating_add(radius).min(width - 1);
+            let ys = sy.saturating_sub(radius)..=sy.saturating_add(radius).min(height - 1);
+            (xs, ys)
+        });
+        move |idx| {
+            square
+                .as_ref()
+                .is_some_and(|(xs, ys)| xs.contains(&(idx % width)) && ys.contains(&(idx / width)))
+        }
+    }
+
+    /// Place mines from `seed`, never on a cell `excluded` picks out.
+    ///
+    /// The candidates are every cell index not excluded, ascending. A
+    /// partial Fisher–Yates shuffle then picks the mines: for `i` in
+    /// `0..mine_count`, swap candidate `i` with candidate
+    /// `i + rng.below(len - i)` and mine the cell now at `i`. That is exactly
+    /// one `BoardRng` draw per mine.
+    fn place_mines(&mut self, seed: u64, excluded: impl Fn(usize) -> bool) {
+        let mut candidates = std::mem::take(&mut self.spare);
+        candidates.extend((0..self.cells.len() as u32).filter(|&idx| !excluded(idx as usize)));
+        let mut rng = BoardRng::new(seed);
+
+        for i in 0..self.mine_count {
+            let j = i + rng.below(candidates.len() - i);
+            candidates.swap(i, j);
+        }
+        self.set_mines(&mut candidates[..self.mine_count]);
+        candidates.clear();
+        self.spare = candidates;
+    }
+
+    /// Reveal a cell. Only a flag protects a cell, which must be taken off
+    /// before it can be opened; a question mark doesn't, and is cleared as
+    /// the cell opens.
+    ///
+    /// ```
+    /// use minesweeper_rs::{Board, Ignored, Marking, RevealResult};
+    ///
+    /// let mut board = Board::decode_layout("*...\n")?;
+    /// board.toggle_flag(3, 0);
+    /// assert_eq!(board.reveal_cell(3, 0), RevealResult::Ignored(Ignored::Flagged));
+    /// board.set_marking(2, 0, Marking::Question);
+    /// assert_eq!(board.reveal_cell(2, 0), RevealResult::Revealed);
+    /// assert_eq!(board.cell(2, 0).marking(), Marking::None);
+    /// assert_eq!(board.reveal_cell(0, 0), RevealResult::Exploded);
+    /// # Ok::<(), minesweeper_rs::LayoutError>(())
+    /// ```
+    pub fn reveal_cell(&mut self, x: usize, y: usize) -> RevealResult {
+        self.clear_wavefronts();
+        if x >= self.width || y >= self.height {
+            return RevealResult::Ignored(Ignored::OutOfBounds);
+        }
+        if self.game_over {
+            return RevealResult::Ignored(Ignored::GameOver);
+        }
+
+        let cell = self.bits(x, y);
+
+        if cell.is_revealed() {
+            return RevealResult::Ignored(Ignored::AlreadyRevealed);
+        }
+        if cell.is_flagged() {
+            return RevealResult::Ignored(Ignored::Flagged);
+        }
+
+        self.revision += 1;
+        let idx = y * self.width + x;
+        self.open(idx);
+        self.wavefront.push(idx as u32);
+        self.ring_ends.push(1);
+
+        if cell.is_mine() {
+            self.game_over = true;
+            self.win = false;
+            return RevealResult::Exploded;
+        }
+
+        if cell.neighbor_mines() == 0 {
+            self.flood_reveal();
+        }
+
+        if self.check_win() {
+            self.game_over = true;
+            self.win = true;
+            return RevealResult::Won;
+        }
+
+        RevealResult::Revealed
+    }
+
+    /// Mark one hidden cell revealed, keeping the counters in step.
+    fn open(&mut self, idx: usize) {
+        let cell = &mut self.cells[idx];
+        cell.set(Bits::REVEALED, true);
+        cell.set(Bits::QUESTION, false);
+        if !cell.is_mine() {
+            self.revealed_safe += 1;
+        }
+        self.dirty.push(idx as u32);
+    }
+
+    /// Open everything connected to the blank cell just opened, the only
+    /// cell in `wavefront`, a ring at a time: each ring is what the blanks
+    /// of the ring before it open. Works through `wavefront` in place, so
+    /// huge openings need no stack and allocate nothing once it has grown.
+    fn flood_reveal(&mut self) {
+        let mut head = 0;
+        while head < self.wavefront.len() {
+            let ring_end = self.wavefront.len();
+            for i in head..ring_end {
+                let idx = self.wavefront[i] as usize;
+                if self.cells[idx].neighbor_mines() != 0 {
+                    continue;
+                }
+                let (x, y) = (idx % self.width, idx / self.width);
+                for (dx, dy) in NEIGHBOR_OFFSETS {
+                    let nx = x as isize + dx;
+                    let ny = y as isize + dy;
+
+                    if !self.in_bounds(nx, ny) {
+                        continue;
+                    }
+
+                    let nidx = ny as usize * self.width + nx as usize;
+                    let cell = self.cells[nidx];
+
+                    if !cell.is_revealed() && !cell.is_flagged() {
+                        self.open(nidx);
+                        self.wavefront.push(nidx as u32);
+                    }
+                }
+            }
+            head = ring_end;
+            if self.wavefront.len() > ring_end {
+                self.ring_ends.push(self.wavefront.len() as u32);
+            }
+        }
+    }
+
+    /// Put a flag on a covered cell, or take it off again: `cycle_marking`
+    /// with [`CycleMode::Flags`]. A question mark becomes a flag.
+    ///
+    /// ```
+    /// use minesweeper_rs::{Board, FlagResult, Ignored};
+    ///
+    /// let mut board = Board::decode_layout("*..\n")?;
+    /// board.reveal_cell(1, 0);
+    /// assert_eq!(
+    ///     board.toggle_flag(1, 0),
+    ///     FlagResult::Ignored(Ignored::AlreadyRevealed)
+    /// );
+    /// # Ok::<(), minesweeper_rs::LayoutError>(())
+    /// ```
+    pub fn toggle_flag(&mut self, x: usize, y: usize) -> FlagResult {
+        match self.cycle_marking(x, y, CycleMode::Flags) {
+            MarkingResult::Changed {
+                to: Marking::Flag, ..
+            } => FlagResult::Flagged,
+            MarkingResult::Changed { .. } | MarkingResult::Unchanged => FlagResult::Unflagged,
+            MarkingResult::Ignored(reason) => FlagResult::Ignored(reason),
+        }
+    }
+
+    /// Cells currently flagged.
+    ///
+    /// ```
+    /// # let mut board = minesweeper_rs::Board::decode_layout("*..\n")?;
+    /// board.toggle_flag(1, 0);
+    /// assert_eq!(board.flags(), 1);
+    /// # Ok::<(), minesweeper_rs::LayoutError>(())
+    /// ```
+    pub fn flags(&self) -> usize {
+        self.flags
+    }
+
+    /// Flag cap for flag-limit mode, or `None` for unlimited flags.
+    ///
+    /// ```
+    /// let board = minesweeper_rs::Board::blank(9, 9, 10);
+    /// assert_eq!(board.max_flags(), None);
+    /// ```
+    pub fn max_flags(&self) -> Option<usize> {
+        self.max_flags
+    }
+
+    /// Cap the number of flags, or lift the cap with `None`. Flags already
+    /// placed stay even if there are more than the new cap.
+    ///
+    /// ```
+    /// use minesweeper_rs::{Board, FlagResult, Ignored};
+    ///
+    /// let mut board = Board::decode_layout("*..\n")?;
+    /// board.set_max_flags(Some(1));
+    /// board.toggle_flag(0, 0);
+    /// assert_eq!(board.toggle_flag(1, 0), FlagResult::Ignored(Ignored::NoFlagsLeft));
+    /// # Ok::<(), minesweeper_rs::LayoutError>(())
+    /// ```
+    pub fn set_max_flags(&mut self, max_flags: Option<usize>) {
+        self.max_flags = max_flags;
+    }
+
+    /// Flags that can still be placed under `max_flags`, if there is a cap.
+    ///
+    /// ```
+    /// # let mut board = minesweeper_rs::Board::decode_layout("*..\n")?;
+    /// board.set_max_flags(Some(1));
+    /// assert_eq!(board.flags_left(), Some(1));
+    /// # Ok::<(), minesweeper_rs::LayoutError>(())
+    /// ```
+    pub fn flags_left(&self) -> Option<usize> {
+        self.max_flags.map(|max| max.saturating_sub(self.flags))
+    }
+
+    /// Safe cells revealed so far, out of `width * height - mine_count`.
+    ///
+    /// ```
+    /// # let mut board = minesweeper_rs::Board::decode_layout("*..\n")?;
+    /// board.reveal_cell(1, 0);
+    /// assert_eq!(board.revealed_safe(), 1);
+    /// # Ok::<(), minesweeper_rs::LayoutError>(())
+    /// ```
+    pub fn revealed_safe(&self) -> usize {
+        self.revealed_safe
+    }
+
+    /// True when every hidden cell is flagged yet the game goes on. Play
+    /// would have been won if the hidden cells were exactly the mines, so
+    /// at least one flag is on a safe cell. This is pure counting, so
+    /// saying so gives nothing away, and it can't fire while any hidden
+    /// cell is unflagged.
+    ///
+    /// ```
+    /// # let mut board = minesweeper_rs::Board::decode_layout("*..\n")?;
+    /// board.reveal_cell(1, 0);
+    /// board.toggle_flag(0, 0);
+    /// assert!(!board.flags_misplaced());
+    /// board.toggle_flag(2, 0);
+    /// assert!(board.flags_misplaced());
+    /// # Ok::<(), minesweeper_rs::LayoutError>(())
+    /// ```
+    pub fn flags_misplaced(&self) -> bool {
+        !self.game_over && self.flags == self.cells.len() - self.revealed_safe
+    }
+
+    /// Remove every flag, returning how many there were. Does nothing once
+    /// the game is over.
+    ///
+    /// ```
+    /// # let mut board = minesweeper_rs::Board::decode_layout("*..\n")?;
+    /// board.toggle_flag(0, 0);
+    /// board.toggle_flag(1, 0);
+    /// assert_eq!(board.clear_flags(), 2);
+    /// assert_eq!(board.flags(), 0);
+    /// # Ok::<(), minesweeper_rs::LayoutError>(())
+    /// ```
+    pub fn clear_flags(&mut self) -> usize {
+        if self.game_over || self.flags == 0 {
+            return 0;
+        }
+        for (idx, cell) in self.cells.iter_mut().enumerate() {
+            if cell.is_flagged() {
+                cell.set(Bits::FLAGGED, false);
+                self.dirty.push(idx as u32);
+            }
+        }
+        self.revision += 1;
+        std::mem::take(&mut self.flags)
+    }
+
+    fn check_win(&self) -> bool {
+        self.revealed_safe == self.width * self.height - self.mine_count
+    }
+
+    /// True when every mine carries a flag and no flag is on a safe cell.
+    /// This reads the hidden layout, so frontends should only act on it in
+    /// ways that don't tell the player which flags are right.
+    ///
+    /// ```
+    /// # let mut board = minesweeper_rs::Board::decode_layout("*..\n")?;
+    /// board.toggle_flag(0, 0);
+    /// assert!(board.all_mines_flagged());
+    /// # Ok::<(), minesweeper_rs::LayoutError>(())
+    /// ```
+    pub fn all_mines_flagged(&self) -> bool {
+        self.flags == self.mine_count
+            && self
+                .cells
+                .iter()
+                .all(|cell| cell.is_flagged() == cell.is_mine())
+    }
+
+    /// The cells a chord on (x, y) opens, in row-major order: the hidden,
+    /// unflagged neighbours of a revealed number that touches as many flags
+    /// as it shows. Empty anywhere else, off the board included, or with
+    /// nothing left to open.
+    ///
+    /// ```
+    /// # let mut board = minesweeper_rs::Board::decode_layout("*..\n...\n")?;
+    /// board.reveal_cell(1, 0);
+    /// assert!(board.chord_targets(1, 0).is_empty());
+    /// board.toggle_flag(0, 0);
+    /// assert_eq!(board.chord_targets(1, 0), [(2, 0), (0, 1), (1, 1), (2, 1)]);
+    /// # Ok::<(), minesweeper_rs::LayoutError>(())
+    /// ```
+    pub fn chord_targets(&self, x: usize, y: usize) -> Vec<(usize, usize)> {
+        if x >= self.width || y >= self.height {
+            return Vec::new();
+        }
+        let cell = self.bits(x, y);
+        let count = usize::from(cell.neighbor_mines());
+        if self.game_over || !cell.is_revealed() || cell.is_mine() || count == 0 {
+            return Vec::new();
+        }
+        let flags = self
+            .neighbors(x, y)
+            .filter(|&(nx, ny)| self.bits(nx, ny).is_flagged())
+            .count();
+        if flags != count {
+            return Vec::new();
+        }
+        let mut targets: Vec<_> = self
+            .neighbors(x, y)
+            .filter(|&(nx, ny)| {
+                let cell = self.bits(nx, ny);
+                !cell.is_revealed() && !cell.is_flagged()
+            })
+            .collect();
+        targets.sort_unstable_by_key(|&(x, y)| (y, x));
+        targets
+    }
+
+    /// Reveal every cell that is neither revealed nor flagged, in one move.
+    /// Meant for when the flags account for every mine: it wins if they are
+    /// all correct and explodes if any flag is on a safe cell, since a mine
+    /// is then left unflagged.
+    ///
+    /// ```
+    /// use minesweeper_rs::{Board, RevealResult};
+    ///
+    /// let mut board = Board::decode_layout("*...\n....\n")?;
+    /// board.toggle_flag(0, 0);
+    /// assert_eq!(board.reveal_remaining(), RevealResult::Won);
+    /// # Ok::<(), minesweeper_rs::LayoutError>(())
+    /// ```
+    pub fn reveal_remaining(&mut self) -> RevealResult {
+        self.clear_wavefronts();
+        if self.game_over {
+            return RevealResult::Ignored(Ignored::GameOver);
+        }
+
+        let mut exploded = false;
+        for idx in 0..self.cells.len() {
+            let cell = self.cells[idx];
+            if !cell.is_revealed() && !cell.is_flagged() {
+                self.open(idx);
+                self.wavefront.push(idx as u32);
+                exploded |= cell.is_mine();
+            }
+        }
+        if !self.wavefront.is_empty() {
+            self.ring_ends.push(self.wavefront.len() as u32);
+        }
+        self.revision += 1;
+
+        if exploded {
+            self.game_over = true;
+            self.win = false;
+            RevealResult::Exploded
+        } else if self.check_win() {
+            self.game_over = true;
+            self.win = true;
+            RevealResult::Won
+        } else {
+            RevealResult::Revealed
+        }
+    }
+
+    /// Open every cell, as shown after a lost game. Doesn't change who won.
+    ///
+    /// ```
+    /// # let mut board = minesweeper_rs::Board::decode_layout("*..\n")?;
+    /// board.reveal_all();
+    /// assert!(board.cell(0, 0).is_revealed());
+    /// # Ok::<(), minesweeper_rs::LayoutError>(())
+    /// ```
+    pub fn reveal_all(&mut self) {
+        for idx in 0..self.cells.len() {
+            if !self.cells[idx].is_revealed() {
+                self.open(idx);
+            }
+        }
+        self.revision += 1;
+    }
+
+    /// Monotonic counter bumped once per mutating action, so caches can
+    /// tell whether anything changed without diffing the grid.
+    ///
+    /// ```
+    /// # let mut board = minesweeper_rs::Board::decode_layout("*..\n")?;
+    /// let before = board.revision();
+    /// board.toggle_flag(1, 0);
+    /// assert!(board.revision() > before);
+    /// # Ok::<(), minesweeper_rs::LayoutError>(())
+    /// ```
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Cells whose state changed since the last call, each listed once.
+    ///
+    /// ```
+    /// # let mut board = minesweeper_rs::Board::decode_layout("*..\n")?;
+    /// board.toggle_flag(1, 0);
+    /// assert_eq!(board.take_dirty(), [(1, 0)]);
+    /// assert!(board.take_dirty().is_empty());
+    /// # Ok::<(), minesweeper_rs::LayoutError>(())
+    /// ```
+    pub fn take_dirty(&mut self) -> Vec<(usize, usize)> {
+        let mut dirty = std::mem::take(&mut self.dirty);
+        dirty.sort_unstable();
+        dirty.dedup();
+        dirty
+            .into_iter()
+            .map(|i| (i as usize % self.width, i as usize / self.width))
+            .collect()
+    }
+
+    /// The cells the last [`reveal_cell`](Self::reveal_cell) opened, in
+    /// rings by how many steps the opening took to reach them: the clicked
+    /// cell alone, then the cells the blanks of each ring opened in turn,
+    /// each ring row by row. Every opened cell is in exactly one ring and touches a cell of the
+    /// ring before. As flags stop the opening, a cell past one can come a
+    /// ring or more later than its distance from the click.
+    ///
+    /// This is presentation metadata, for drawing an opening as a wave or
+    /// staggering its sounds, and means nothing for play. After
+    /// [`reveal_remaining`](Self::reveal_remaining) it is one ring; it is
+    /// empty after a reveal that changed nothing, and undoing
+    /// with [`restore`](Self::restore) or resetting clears it.
+    ///
+    /// ```
+    /// use minesweeper_rs::Board;
+    ///
+    /// let mut board = Board::decode_layout("....\n....\n...*\n")?;
+    /// board.reveal_cell(0, 0);
+    /// assert_eq!(
+    ///     board.last_reveal_wavefronts(),
+    ///     [
+    ///         vec![(0, 0)],
+    ///         vec![(1, 0), (0, 1), (1, 1)],
+    ///         vec![(2, 0), (2, 1), (0, 2), (1, 2), (2, 2)],
+    ///         vec![(3, 0), (3, 1)],
+    ///     ]
+    /// );
+    /// # Ok::<(), minesweeper_rs::LayoutError>(())
+    /// ```
+    pub fn last_reveal_wavefronts(&self) -> Vec<Vec<(usize, usize)>> {
+        let mut start = 0;
+        self.ring_ends
+            .iter()
+            .map(|&end| {
+                let mut ring = self.wavefront[start..end as usize].to_vec();
+                ring.sort_unstable();
+                start = end as usize;
+                ring.into_iter()
+                    .map(|i| (i as usize % self.width, i as usize / self.width))
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn clear_wavefronts(&mut self) {
+        self.wavefront.clear();
+        self.ring_ends.clear();
+    }
+
+    /// Return the string to show for a cell: "■", "🚩", "💣", "1", "2", ...
+    /// This is [`Glyph::label`]; the terminal styles in `render` share it.
+    ///
+    /// ```
+    /// # let board = minesweeper_rs::Board::decode_layout("*..\n")?;
+    /// assert_eq!(board.cell_label(1, 0), "■");
+    /// # Ok::<(), minesweeper_rs::LayoutError>(())
+    /// ```
+    pub fn cell_label(&self, x: usize, y: usize) -> &'static str {
+        Glyph::of(self.cell(x, y)).label()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// What the cell under test shows before the action.
+    #[derive(Clone, Copy, Debug)]
+    enum Shown {
+        Hidden,
+        Flagged,
+        Questioned,
+        Revealed,
+    }
+
+    /// The cell every test acts on: safe, and next to one mine.
+    const AT: (usize, usize) = (0, 1);
+
+    /// Two mines and no empty cell, so a reveal opens one cell and never
+    /// wins, with the cell at `AT` showing `shown`.
+    fn board(shown: Shown) -> Board {
+        let mut board = Board::decode_layout("*..\n..*\n").unwrap();
+        let (x, y) = AT;
+        match shown {
+            Shown::Hidden => {}
+            Shown::Flagged => assert_eq!(board.toggle_flag(x, y), FlagResult::Flagged),
+            Shown::Questioned => {
+                board.set_marking(x, y, Marking::Question);
+            }
+            Shown::Revealed => assert_eq!(board.reveal_cell(x, y), RevealResult::Revealed),
+        }
+        board
+    }
+
+    #[test]
+    fn reveal_in_each_state() {
+        let (x, y) = AT;
+        for (shown, expected) in [
+            (Shown::Hidden, RevealResult::Revealed),
+            (Shown::Flagged, RevealResult::Ignored(Ignored::Flagged)),
+            (Shown::Questioned, RevealResult::Revealed),
+            (
+                Shown::Revealed,
+                RevealResult::Ignored(Ignored::AlreadyRevealed),
+            ),
+        ] {
+            let mut board = board(shown);
+            let revision = board.revision();
+            assert_eq!(board.reveal_cell(x, y), expected, "{shown:?}");
+            let open = !matches!(shown, Shown::Flagged);
+            assert_eq!(board.cell(x, y).is_revealed(), open, "{shown:?}");
+            assert_eq!(board.revealed_safe(), usize::from(open), "{shown:?}");
+            let changed = expected == RevealResult::Revealed;
+            assert_eq!(board.revision() > revision, changed, "{shown:?}");
+        }
+    }
+
+    #[test]
+    fn flag_in_each_state() {
+        let (x, y) = AT;
+        for (shown, expected, flags) in [
+            (Shown::Hidden, FlagResult::Flagged, 1),
+            (Shown::Flagged, FlagResult::Unflagged, 0),
+            (Shown::Questioned, FlagResult::Flagged, 1),
+            (
+                Shown::Revealed,
+                FlagResult::Ignored(Ignored::AlreadyRevealed),
+                0,
+            ),
+        ] {
+            let mut board = board(shown);
+            assert_eq!(board.toggle_flag(x, y), expected, "{shown:?}");
+            assert_eq!(board.flags(), flags, "{shown:?}");
+            assert_eq!(board.cell(x, y).is_flagged(), flags == 1, "{shown:?}");
+        }
+    }
+
+    #[test]
+    fn chord_in_each_state() {
+        let (x, y) = AT;
+        for shown in [Shown::Hidden, Shown::Flagged, Shown::Questioned] {
+            let mut board = board(shown);
+            board.toggle_flag(0, 0);
+            assert!(board.chord_targets(x, y).is_empty(), "{shown:?}");
+        }
+
+        // a revealed number chords once its mines are flagged, and not before
+        let mut board = board(Shown::Revealed);
+        assert!(board.chord_targets(x, y).is_empty());
+        board.toggle_flag(0, 0);
+        assert_eq!(board.chord_targets(x, y), [(1, 0), (1, 1)]);
+    }
+
+    #[test]
+    fn a_finished_game_or_a_cell_off_the_board_ignores_every_action() {
+        for shown in [
+            Shown::Hidden,
+            Shown::Flagged,
+            Shown::Questioned,
+            Shown::Revealed,
+        ] {
+            let mut board = board(shown);
+            board.reveal_cell(0, 0);
+            let (x, y) = AT;
+            let over = Ignored::GameOver;
+            assert_eq!(
+                board.reveal_cell(x, y),
+                RevealResult::Ignored(over),
+                "{shown:?}"
+            );
+            assert_eq!(
+                board.toggle_flag(x, y),
+                FlagResult::Ignored(over),
+                "{shown:?}"
+            );
+            assert!(board.chord_targets(x, y).is_empty(), "{shown:?}");
+        }
+
+        let mut board = board(Shown::Hidden);
+        let off = Ignored::OutOfBounds;
+        assert_eq!(board.reveal_cell(3, 0), RevealResult::Ignored(off));
+        assert_eq!(board.toggle_flag(0, 2), FlagResult::Ignored(off));
+        assert!(board.chord_targets(3, 0).is_empty());
+        assert!(board.chord_targets(usize::MAX, usize::MAX).is_empty());
+    }
+}
@@ -0,0 +1,115 @@
+//! Setting the mines a shuffle picked and counting every cell's
+//! neighbours: the two passes over every cell that laying a board takes,
+//! and what a mega board spends its generation time on.
+//!
+//! With the `parallel` feature, boards of `PARALLEL_CELLS` or more run
+//! both passes on rayon's threads, a band of rows per task. A band only
+//! writes its own cells and only reads the mines, which are settled before
+//! counting starts, so the board comes out the same bit for bit as it does
+//! one cell at a time. Smaller boards always go one cell at a time.
+
+#[cfg(feature = "parallel")]
+use super::NEIGHBOR_OFFSETS;
+use super::{Bits, Board};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// Fewest cells a board needs before it is laid on several threads; below
+/// this the threads cost more than they save (`cargo bench --bench
+/// generation` with and without `--features parallel`).
+#[cfg(feature = "parallel")]
+const PARALLEL_CELLS: usize = 100_000;
+
+/// Cells in a band, give or take a row.
+#[cfg(feature = "parallel")]
+const BAND_CELLS: usize = 16 * 1024;
+
+impl Board {
+    /// Set the mine bit of every cell index in `mines`, which may be sorted
+    /// on the way.
+    pub(super) fn set_mines(&mut self, mines: &mut [u32]) {
+        #[cfg(feature = "parallel")]
+        if self.cells.len() >= PARALLEL_CELLS {
+            mines.par_sort_unstable();
+            let mines = &*mines;
+            let band = self.band_cells();
+            self.cells
+                .par_chunks_mut(band)
+                .enumerate()
+                .for_each(|(i, cells)| {
+                    let start = i * band;
+                    let first = mines.partition_point(|&idx| (idx as usize) < start);
+                    for &idx in &mines[first..] {
+                        let Some(bits) = cells.get_mut(idx as usize - start) else {
+                            break; // the next band's
+                        };
+                        bits.set(Bits::MINE, true);
+                    }
+                });
+            return;
+        }
+        for &idx in mines.iter() {
+            self.cells[idx as usize].set(Bits::MINE, true);
+        }
+    }
+
+    pub(super) fn compute_neighbor_counts(&mut self) {
+        #[cfg(feature = "parallel")]
+        if self.cells.len() >= PARALLEL_CELLS {
+            self.count_in_bands();
+            return;
+        }
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let count = if self.bits(x, y).is_mine() {
+                    0
+                } else {
+                    self.neighbors(x, y)
+                        .filter(|&(nx, ny)| self.bits(nx, ny).is_mine())
+                        .count() as u8
+                };
+                self.cells[y * self.width + x].set_neighbor_mines(count);
+            }
+        }
+    }
+
+    /// `compute_neighbor_counts` a band at a time, reading the mines from
+    /// a copy so the bands can be written while they are read.
+    #[cfg(feature = "parallel")]
+    fn count_in_bands(&mut self) {
+        let (width, height) = (self.width, self.height);
+        let mines: Vec<bool> = self.cells.par_iter().map(|bits| bits.is_mine()).collect();
+        let mine_at = |x: isize, y: isize| {
+            x >= 0
+                && y >= 0
+                && (x as usize) < width
+                && (y as usize) < height
+                && mines[y as usize * width + x as usize]
+        };
+        let band = self.band_cells();
+        self.cells
+            .par_chunks_mut(band)
+            .enumerate()
+            .for_each(|(i, cells)| {
+                for (offset, bits) in cells.iter_mut().enumerate() {
+                    let idx = i * band + offset;
+                    let (x, y) = ((idx % width) as isize, (idx / width) as isize);
+                    let count = if mines[idx] {
+                        0
+                    } else {
+                        NEIGHBOR_OFFSETS
+                            .iter()
+                            .filter(|&&(dx, dy)| mine_at(x + dx, y + dy))
+                            .count() as u8
+                    };
+                    bits.set_neighbor_mines(count);
+                }
+            });
+    }
+
+    /// Cells in a band: whole rows, about `BAND_CELLS` of them.
+    #[cfg(feature = "parallel")]
+    fn band_cells(&self) -> usize {
+        (BAND_CELLS / self.width).max(1) * self.width
+    }
+}
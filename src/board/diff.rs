@@ -0,0 +1,179 @@
+//! Two boards compared cell by cell: which cells were opened or covered,
+//! marked differently, or have a mine on one board and not the other.
+//!
+//! A diff between a board before and after a move lists the same cells as
+//! [`Board::take_dirty`]; it also works between boards that never shared a
+//! history, such as a client's copy and the server's.
+
+use super::{Board, Marking};
+use crate::render::Glyph;
+use std::fmt;
+
+/// How one cell differs between two boards, from [`Board::diff`]. Each
+/// field is `None` where the boards agree.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CellDiff {
+    /// Column of the cell.
+    pub x: usize,
+    /// Row of the cell.
+    pub y: usize,
+    /// Whether the cell is revealed on the other board, when that differs.
+    pub revealed: Option<bool>,
+    /// The marking on this board and on the other, when they differ.
+    pub marking: Option<(Marking, Marking)>,
+    /// Whether the cell is a mine on the other board, when the layouts
+    /// differ here.
+    pub mine: Option<bool>,
+}
+
+/// Every cell that differs between two boards of one size, row by row.
+///
+/// Shown as a grid with `.` for each cell the boards agree on and, for the
+/// rest, how the cell looks on the other board: its glyph if it was opened
+/// or marked differently (`0` for an opened blank), else `+` or `-` for a
+/// mine only the other board has or lacks.
+///
+/// ```
+/// use minesweeper_rs::Board;
+///
+/// let before = Board::decode_layout("*..\n...\n")?;
+/// let mut after = before.clone();
+/// after.toggle_flag(0, 0);
+/// after.reveal_cell(2, 1);
+///
+/// let diff = before.diff(&after)?;
+/// assert_eq!(diff.positions(), [(0, 0), (1, 0), (2, 0), (1, 1), (2, 1)]);
+/// assert_eq!(diff.to_string(), "⚑10\n.10\n");
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BoardDiff {
+    width: usize,
+    height: usize,
+    cells: Vec<CellDiff>,
+    glyphs: Vec<Glyph>, // how each differing cell looks on the other board
+}
+
+impl BoardDiff {
+    /// The cells that differ, in row-major order.
+    pub fn cells(&self) -> &[CellDiff] {
+        &self.cells
+    }
+
+    /// Where the differing cells are, in the order [`Board::take_dirty`]
+    /// lists cells.
+    pub fn positions(&self) -> Vec<(usize, usize)> {
+        self.cells.iter().map(|cell| (cell.x, cell.y)).collect()
+    }
+
+    /// Whether the boards agree everywhere.
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// Whether any cell is a mine on one board and not the other.
+    pub fn layouts_differ(&self) -> bool {
+        self.cells.iter().any(|cell| cell.mine.is_some())
+    }
+}
+
+impl fmt::Display for BoardDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut changed = self.cells.iter().zip(&self.glyphs).peekable();
+        for y in 0..self.height {
+            let mut line = String::with_capacity(self.width);
+            for x in 0..self.width {
+                let here = changed.next_if(|(cell, _)| (cell.x, cell.y) == (x, y));
+                line.push(match here {
+                    None => '.',
+                    Some((cell, glyph)) if cell.revealed.is_some() || cell.marking.is_some() => {
+                        match glyph {
+                            Glyph::Count(0) => '0',
+                            glyph => glyph.narrow(),
+                        }
+                    }
+                    Some((cell, _)) if cell.mine == Some(true) => '+',
+                    Some(_) => '-',
+                });
+            }
+            writeln!(f, "{line}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Why two boards couldn't be compared: they aren't the same size.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SizeMismatch {
+    /// Width and height of the board diffed from.
+    pub ours: (usize, usize),
+    /// Width and height of the board it was compared with.
+    pub theirs: (usize, usize),
+}
+
+impl fmt::Display for SizeMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ((w, h), (ow, oh)) = (self.ours, self.theirs);
+        write!(f, "can't compare a {w}x{h} board with a {ow}x{oh} one")
+    }
+}
+
+impl std::error::Error for SizeMismatch {}
+
+impl Board {
+    /// How `other` differs from this board, cell by cell: what was opened
+    /// or covered, marked differently, and where the mines differ. The game
+    /// state and counters aren't compared; they follow from the cells.
+    ///
+    /// ```
+    /// use minesweeper_rs::Board;
+    ///
+    /// let ours = Board::decode_layout("*..\n")?;
+    /// let theirs = Board::decode_layout("..*\n")?;
+    /// let diff = ours.diff(&theirs)?;
+    /// assert!(diff.layouts_differ());
+    /// assert_eq!(diff.to_string(), "-.+\n");
+    ///
+    /// let wide = Board::decode_layout("*...\n")?;
+    /// assert!(ours.diff(&wide).is_err());
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn diff(&self, other: &Board) -> Result<BoardDiff, SizeMismatch> {
+        if (self.width, self.height) != (other.width, other.height) {
+            return Err(SizeMismatch {
+                ours: (self.width, self.height),
+                theirs: (other.width, other.height),
+            });
+        }
+        let mut cells = Vec::new();
+        let mut glyphs = Vec::new();
+        for (i, (&ours, &theirs)) in self.cells.iter().zip(&other.cells).enumerate() {
+            if ours == theirs {
+                continue;
+            }
+            let differs = |a, b| (a != b).then_some(b);
+            let (x, y) = (i % self.width, i / self.width);
+            let cell = CellDiff {
+                x,
+                y,
+                revealed: differs(ours.is_revealed(), theirs.is_revealed()),
+                marking: (ours.marking() != theirs.marking())
+                    .then_some((ours.marking(), theirs.marking())),
+                mine: differs(ours.is_mine(), theirs.is_mine()),
+            };
+            // the same cell with different neighbour counts is a layout
+            // difference next door, already listed there
+            if cell.revealed.is_none() && cell.marking.is_none() && cell.mine.is_none() {
+                continue;
+            }
+            cells.push(cell);
+            glyphs.push(Glyph::of(other.cell(x, y)));
+        }
+        Ok(BoardDiff {
+            width: self.width,
+            height: self.height,
+            cells,
+            glyphs,
+        })
+    }
+}
@@ -0,0 +1,130 @@
+//! Plain-text mine layouts: one line per row, `*` for a mine and `.` for a
+//! safe cell. Handy for tests, puzzles and bug reports. A layout is the
+//! answer key, so encoding one gives away every mine whatever the game
+//! state; only play state round-trips through `encode_state`.
+
+use super::{Bits, Board, MAX_CELLS};
+use std::fmt;
+
+/// Why a layout couldn't be read.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum LayoutError {
+    /// There are no rows, or the rows are empty.
+    Empty,
+    /// Rows have different lengths.
+    Ragged,
+    /// A character other than `*` or `.`.
+    BadChar,
+    /// The board would have more than `MAX_CELLS` cells.
+    TooLarge,
+}
+
+impl fmt::Display for LayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LayoutError::Empty => "layout has no cells",
+            LayoutError::Ragged => "layout rows differ in length",
+            LayoutError::BadChar => "layout may only contain '*' and '.'",
+            LayoutError::TooLarge => "layout describes an oversized board",
+        })
+    }
+}
+
+impl std::error::Error for LayoutError {}
+
+impl Board {
+    /// The mine layout as text, each row ending in a newline.
+    ///
+    /// ```
+    /// use minesweeper_rs::Board;
+    ///
+    /// let board = Board::decode_layout("*..\n.*.")?;
+    /// assert_eq!(board.encode_layout(), "*..\n.*.\n");
+    /// # Ok::<(), minesweeper_rs::LayoutError>(())
+    /// ```
+    pub fn encode_layout(&self) -> String {
+        let mut out = String::with_capacity((self.width + 1) * self.height);
+        for row in self.cells.chunks(self.width) {
+            out.extend(row.iter().map(|c| if c.is_mine() { '*' } else { '.' }));
+            out.push('\n');
+        }
+        out
+    }
+
+    /// A fingerprint of the mine layout, to check that a board is the one
+    /// its seed gives without sharing the layout itself.
+    ///
+    /// The hash is stable across releases and platforms: 64-bit FNV-1a over
+    /// the width and height, then each mine's x and y in row-major order,
+    /// every number as a little-endian `u64`. Play state doesn't affect it.
+    /// The golden values in `tests/generation.rs` pin it.
+    ///
+    /// ```
+    /// use minesweeper_rs::Board;
+    ///
+    /// let mut board = Board::decode_layout("*..\n")?;
+    /// assert_eq!(format!("{:016x}", board.layout_hash()), "38826c9aadeb2087");
+    /// board.reveal_cell(2, 0);
+    /// assert_eq!(format!("{:016x}", board.layout_hash()), "38826c9aadeb2087");
+    /// # Ok::<(), minesweeper_rs::LayoutError>(())
+    /// ```
+    pub fn layout_hash(&self) -> u64 {
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        let mut eat = |n: usize| {
+            for b in (n as u64).to_le_bytes() {
+                hash ^= u64::from(b);
+                hash = hash.wrapping_mul(0x0100_0000_01b3);
+            }
+        };
+        eat(self.width);
+        eat(self.height);
+        for (i, cell) in self.cells.iter().enumerate() {
+            if cell.is_mine() {
+                eat(i % self.width);
+                eat(i / self.width);
+            }
+        }
+        hash
+    }
+
+    /// A fresh, unseeded board with exactly the mines in `text`. The final
+    /// newline is optional.
+    ///
+    /// ```
+    /// use minesweeper_rs::{Board, LayoutError};
+    ///
+    /// let board = Board::decode_layout("*..\n...\n")?;
+    /// assert_eq!((board.width(), board.height(), board.mine_count()), (3, 2, 1));
+    /// assert_eq!(Board::decode_layout("*.\n.").err(), Some(LayoutError::Ragged));
+    /// # Ok::<(), LayoutError>(())
+    /// ```
+    pub fn decode_layout(text: &str) -> Result<Board, LayoutError> {
+        let rows: Vec<&str> = text.lines().collect();
+        let width = rows.first().map_or(0, |row| row.len());
+        if width == 0 {
+            return Err(LayoutError::Empty);
+        }
+        if rows.iter().any(|row| row.len() != width) {
+            return Err(LayoutError::Ragged);
+        }
+        if width * rows.len() > MAX_CELLS {
+            return Err(LayoutError::TooLarge);
+        }
+        let cells = rows
+            .iter()
+            .flat_map(|row| row.bytes())
+            .map(|b| match b {
+                b'*' => Ok(Bits(Bits::MINE)),
+                b'.' => Ok(Bits::default()),
+                _ => Err(LayoutError::BadChar),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mines = cells.iter().filter(|c| c.is_mine()).count();
+        let mut board = Board::blank(width, rows.len(), mines);
+        board.cells = cells;
+        board.compute_neighbor_counts();
+        Ok(board)
+    }
+}
@@ -0,0 +1,177 @@
+//! What the player puts on covered cells: flags and question marks.
+//!
+//! `set_marking` puts down one marking, `cycle_marking` steps to the next
+//! the way a right click does, and both say what changed or why nothing
+//! did. `toggle_flag` is `cycle_marking` with [`CycleMode::Flags`].
+
+use super::{Bits, Board, Ignored};
+
+/// A player's mark on a covered cell.
+///
+/// ```
+/// use minesweeper_rs::{Board, Marking};
+///
+/// let mut board = Board::decode_layout("*..\n")?;
+/// board.set_marking(1, 0, Marking::Question);
+/// assert_eq!(board.cell(1, 0).marking(), Marking::Question);
+/// assert!(!board.cell(1, 0).is_flagged());
+/// # Ok::<(), minesweeper_rs::LayoutError>(())
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Marking {
+    /// Nothing on the cell.
+    #[default]
+    None,
+    /// A flag: the player says it's a mine, and it can't be revealed.
+    Flag,
+    /// A question mark: a note to come back to. It doesn't count as a flag
+    /// and the cell stays revealable.
+    Question,
+}
+
+/// The markings `cycle_marking` steps through, in order.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CycleMode {
+    /// Flag, then clear again. A question mark put down some other way
+    /// becomes a flag.
+    #[default]
+    Flags,
+    /// Flag, then question mark, then clear.
+    FlagsAndQuestions,
+}
+
+impl CycleMode {
+    /// The marking after `marking` in this mode.
+    ///
+    /// ```
+    /// use minesweeper_rs::{CycleMode, Marking};
+    ///
+    /// assert_eq!(CycleMode::Flags.after(Marking::Flag), Marking::None);
+    /// assert_eq!(CycleMode::FlagsAndQuestions.after(Marking::Flag), Marking::Question);
+    /// ```
+    pub fn after(self, marking: Marking) -> Marking {
+        match (self, marking) {
+            (_, Marking::None) | (CycleMode::Flags, Marking::Question) => Marking::Flag,
+            (CycleMode::FlagsAndQuestions, Marking::Flag) => Marking::Question,
+            (CycleMode::Flags, Marking::Flag)
+            | (CycleMode::FlagsAndQuestions, Marking::Question) => Marking::None,
+        }
+    }
+}
+
+/// What a marking action did.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MarkingResult {
+    /// The cell's marking changed.
+    Changed {
+        /// The marking before.
+        from: Marking,
+        /// The marking now.
+        to: Marking,
+    },
+    /// The cell already had that marking.
+    Unchanged,
+    /// Nothing changed, and this is why.
+    Ignored(Ignored),
+}
+
+impl MarkingResult {
+    /// Whether a flag went down.
+    pub fn flagged(self) -> bool {
+        matches!(
+            self,
+            MarkingResult::Changed {
+                to: Marking::Flag,
+                ..
+            }
+        )
+    }
+}
+
+impl Bits {
+    pub(super) fn marking(self) -> Marking {
+        if self.is_flagged() {
+            Marking::Flag
+        } else if self.0 & Bits::QUESTION != 0 {
+            Marking::Question
+        } else {
+            Marking::None
+        }
+    }
+}
+
+impl Board {
+    /// Put `marking` on the covered cell at (x, y), replacing whatever was
+    /// there. In flag-limit mode a new flag needs one to spare.
+    ///
+    /// ```
+    /// use minesweeper_rs::{Board, Ignored, Marking, MarkingResult};
+    ///
+    /// let mut board = Board::decode_layout("*..\n")?;
+    /// board.set_max_flags(Some(1));
+    /// assert!(board.set_marking(0, 0, Marking::Flag).flagged());
+    /// assert_eq!(board.set_marking(0, 0, Marking::Flag), MarkingResult::Unchanged);
+    /// assert_eq!(
+    ///     board.set_marking(1, 0, Marking::Flag),
+    ///     MarkingResult::Ignored(Ignored::NoFlagsLeft)
+    /// );
+    /// # Ok::<(), minesweeper_rs::LayoutError>(())
+    /// ```
+    pub fn set_marking(&mut self, x: usize, y: usize, marking: Marking) -> MarkingResult {
+        if x >= self.width || y >= self.height {
+            return MarkingResult::Ignored(Ignored::OutOfBounds);
+        }
+        if self.game_over {
+            return MarkingResult::Ignored(Ignored::GameOver);
+        }
+        let idx = y * self.width + x;
+        let cell = self.cells[idx];
+        if cell.is_revealed() {
+            return MarkingResult::Ignored(Ignored::AlreadyRevealed);
+        }
+        let from = cell.marking();
+        if from == marking {
+            return MarkingResult::Unchanged;
+        }
+        let flagging = marking == Marking::Flag;
+        if flagging && self.max_flags.is_some_and(|max| self.flags >= max) {
+            return MarkingResult::Ignored(Ignored::NoFlagsLeft);
+        }
+
+        let cell = &mut self.cells[idx];
+        cell.set(Bits::FLAGGED, flagging);
+        cell.set(Bits::QUESTION, marking == Marking::Question);
+        if flagging {
+            self.flags += 1;
+        } else if from == Marking::Flag {
+            self.flags -= 1;
+        }
+        self.revision += 1;
+        self.dirty.push(idx as u32);
+        MarkingResult::Changed { from, to: marking }
+    }
+
+    /// Step the cell at (x, y) to its next marking in `mode`, as
+    /// `set_marking` would put it down.
+    ///
+    /// ```
+    /// use minesweeper_rs::{Board, CycleMode, Marking};
+    ///
+    /// let mut board = Board::decode_layout("*..\n")?;
+    /// let mode = CycleMode::FlagsAndQuestions;
+    /// for marking in [Marking::Flag, Marking::Question, Marking::None] {
+    ///     board.cycle_marking(2, 0, mode);
+    ///     assert_eq!(board.cell(2, 0).marking(), marking);
+    /// }
+    /// # Ok::<(), minesweeper_rs::LayoutError>(())
+    /// ```
+    pub fn cycle_marking(&mut self, x: usize, y: usize, mode: CycleMode) -> MarkingResult {
+        if x >= self.width || y >= self.height {
+            return MarkingResult::Ignored(Ignored::OutOfBounds);
+        }
+        let next = mode.after(self.cells[y * self.width + x].marking());
+        self.set_marking(x, y, next)
+    }
+}
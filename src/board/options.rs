@@ -0,0 +1,340 @@
+//! Checked board construction.
+
+use super::symmetry::Shape;
+use super::{Bits, Board, Symmetry, MAX_CELLS};
+use std::fmt;
+
+/// Everything needed to start a board, checked by `build` instead of
+/// panicking like `Board::new`.
+///
+/// ```
+/// use minesweeper_rs::BoardOptions;
+///
+/// let board = BoardOptions::new(30, 16, 99)
+///     .seed(7)
+///     .safe_start(4, 7)
+///     .max_flags(99)
+///     .build()?;
+/// assert_eq!(board.seed(), Some(7));
+/// assert_eq!(board.flags_left(), Some(99));
+/// # Ok::<(), minesweeper_rs::BoardError>(())
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BoardOptions {
+    width: usize,
+    height: usize,
+    mines: usize,
+    seed: Option<u64>,
+    safe: Option<(usize, usize)>,
+    safe_radius: Option<usize>, // None: 1, shrinking to 0 if the board is crowded
+    max_flags: Option<usize>,
+    shape: Shape,
+}
+
+/// Why `BoardOptions::build` refused.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BoardError {
+    /// Width or height is zero.
+    Empty,
+    /// More than `MAX_CELLS` cells.
+    TooLarge,
+    /// The mines don't fit, leaving the safe area clear.
+    TooManyMines,
+    /// The safe start is off the board.
+    SafeStartOutOfBounds,
+    /// No layout keeps the symmetry or spacing asked for with this many
+    /// mines around the safe area.
+    Unplaceable,
+}
+
+impl fmt::Display for BoardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            BoardError::Empty => "board must be at least 1x1",
+            BoardError::TooLarge => "board has too many cells",
+            BoardError::TooManyMines => "more mines than the board can hold",
+            BoardError::SafeStartOutOfBounds => "safe start is outside the board",
+            BoardError::Unplaceable => "no mine layout has the symmetry or spacing asked for",
+        })
+    }
+}
+
+impl std::error::Error for BoardError {}
+
+impl BoardOptions {
+    /// A `width` x `height` board with `mines` randomly placed mines.
+    ///
+    /// ```
+    /// let board = minesweeper_rs::BoardOptions::new(9, 9, 10).build()?;
+    /// assert_eq!(board.mine_count(), 10);
+    /// # Ok::<(), minesweeper_rs::BoardError>(())
+    /// ```
+    pub fn new(width: usize, height: usize, mines: usize) -> Self {
+        BoardOptions {
+            width,
+            height,
+            mines,
+            seed: None,
+            safe: None,
+            safe_radius: None,
+            max_flags: None,
+            shape: Shape::default(),
+        }
+    }
+
+    /// Place the mines from `seed`, as `Board::with_seed` does.
+    ///
+    /// ```
+    /// use minesweeper_rs::BoardOptions;
+    ///
+    /// let a = BoardOptions::new(9, 9, 10).seed(3).build()?;
+    /// let b = BoardOptions::new(9, 9, 10).seed(3).build()?;
+    /// assert_eq!(a.encode_layout(), b.encode_layout());
+    /// # Ok::<(), minesweeper_rs::BoardError>(())
+    /// ```
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Keep (x, y) and, room permitting, its neighbours free of mines.
+    ///
+    /// ```
+    /// use minesweeper_rs::{BoardOptions, RevealResult};
+    ///
+    /// let mut board = BoardOptions::new(9, 9, 70).safe_start(0, 0).build()?;
+    /// assert_ne!(board.reveal_cell(0, 0), RevealResult::Exploded);
+    /// # Ok::<(), minesweeper_rs::BoardError>(())
+    /// ```
+    pub fn safe_start(mut self, x: usize, y: usize) -> Self {
+        self.safe = Some((x, y));
+        self
+    }
+
+    /// Keep every cell within `radius` of the safe start clear: 0 for just
+    /// the cell, 1 for a 3x3 opening, 2 for 5x5. The area is clipped at the
+    /// board's edges, and `build` refuses if the mines don't fit around it.
+    /// Without this the neighbours are kept clear only when there is room.
+    ///
+    /// ```
+    /// use minesweeper_rs::{BoardError, BoardOptions};
+    ///
+    /// let board = BoardOptions::new(9, 9, 56).safe_start(4, 4).safe_radius(2).build()?;
+    /// assert!(board.encode_layout().lines().skip(2).take(5).all(|row| &row[2..7] == "....."));
+    ///
+    /// let crowded = BoardOptions::new(9, 9, 57).safe_start(4, 4).safe_radius(2).build();
+    /// assert_eq!(crowded.err(), Some(BoardError::TooManyMines));
+    /// # Ok::<(), BoardError>(())
+    /// ```
+    pub fn safe_radius(mut self, radius: usize) -> Self {
+        self.safe_radius = Some(radius);
+        self
+    }
+
+    /// Allow at most `max` flags at once.
+    ///
+    /// ```
+    /// let board = minesweeper_rs::BoardOptions::new(9, 9, 10).max_flags(10).build()?;
+    /// assert_eq!(board.max_flags(), Some(10));
+    /// # Ok::<(), minesweeper_rs::BoardError>(())
+    /// ```
+    pub fn max_flags(mut self, max: usize) -> Self {
+        self.max_flags = Some(max);
+        self
+    }
+
+    /// Lay the mines out with `symmetry`, which holds for the whole board
+    /// whatever is kept clear around the safe start. A cell on the axis
+    /// is its own image, so an odd count needs an odd side to put the odd
+    /// mine on. Shaped layouts are placed differently from plain ones: the
+    /// seed rebuilds the board only together with the same options.
+    ///
+    /// ```
+    /// use minesweeper_rs::{BoardError, BoardOptions, Symmetry};
+    ///
+    /// let turned = BoardOptions::new(8, 8, 12).symmetry(Symmetry::Rotational180);
+    /// let layout = turned.clone().seed(5).build()?.encode_layout();
+    /// let rows: Vec<&str> = layout.lines().collect();
+    /// assert!(rows.iter().zip(rows.iter().rev()).all(|(a, b)| a.chars().eq(b.chars().rev())));
+    ///
+    /// // an 8x8 board has no centre cell for an odd mine
+    /// let odd = BoardOptions::new(8, 8, 11).symmetry(Symmetry::Rotational180).build();
+    /// assert_eq!(odd.err(), Some(BoardError::Unplaceable));
+    /// # Ok::<(), BoardError>(())
+    /// ```
+    pub fn symmetry(mut self, symmetry: Symmetry) -> Self {
+        self.shape.symmetry = Some(symmetry);
+        self
+    }
+
+    /// Keep any two mines from sharing a side, for gentler boards. Mines
+    /// may still touch at the corners. As with [`symmetry`](Self::symmetry)
+    /// the seed alone no longer rebuilds the board.
+    ///
+    /// ```
+    /// use minesweeper_rs::{BoardError, BoardOptions};
+    ///
+    /// let board = BoardOptions::new(9, 9, 20).avoid_adjacent_mines(true).seed(3).build()?;
+    /// let layout = board.encode_layout();
+    /// let rows: Vec<Vec<char>> = layout.lines().map(|row| row.chars().collect()).collect();
+    /// for y in 0..9 {
+    ///     for x in 0..8 {
+    ///         assert!(!(rows[y][x] == '*' && rows[y][x + 1] == '*'));
+    ///         assert!(!(rows[x][y] == '*' && rows[x + 1][y] == '*'));
+    ///     }
+    /// }
+    ///
+    /// // more than a checkerboard holds
+    /// let packed = BoardOptions::new(4, 4, 9).avoid_adjacent_mines(true).build();
+    /// assert_eq!(packed.err(), Some(BoardError::Unplaceable));
+    /// # Ok::<(), BoardError>(())
+    /// ```
+    pub fn avoid_adjacent_mines(mut self, avoid: bool) -> Self {
+        self.shape.spaced = avoid;
+        self
+    }
+
+    /// Check the options and place the mines.
+    ///
+    /// ```
+    /// use minesweeper_rs::{BoardError, BoardOptions};
+    ///
+    /// let full = BoardOptions::new(3, 3, 9).safe_start(1, 1).build();
+    /// assert_eq!(full.err(), Some(BoardError::TooManyMines));
+    /// ```
+    pub fn build(&self) -> Result<Board, BoardError> {
+        self.check()?;
+        let seed = self.seed.unwrap_or_else(rand::random);
+        let (width, height, mines) = (self.width, self.height, self.mines);
+        let mut board = if !self.shape.is_free() {
+            let mut board = Board::blank(width, height, mines);
+            if !board.lay_shaped(seed, self.safe, self.radius(), self.shape) {
+                return Err(BoardError::Unplaceable);
+            }
+            board
+        } else {
+            match self.safe_radius {
+                Some(radius) => {
+                    Board::with_safe_area(width, height, mines, seed, self.safe, radius)
+                }
+                None => Board::with_seed(width, height, mines, seed, self.safe),
+            }
+        };
+        board.seed = self.seed;
+        board.max_flags = self.max_flags;
+        Ok(board)
+    }
+
+    /// Refuse options `build` can't honour, without placing any mines: a
+    /// frontend can ask before starting a game. A symmetry or spacing
+    /// that passes can still, rarely, find no layout in `build`.
+    ///
+    /// ```
+    /// use minesweeper_rs::{BoardError, BoardOptions, Symmetry};
+    ///
+    /// let options = BoardOptions::new(6, 6, 35).symmetry(Symmetry::Vertical);
+    /// assert_eq!(options.check(), Err(BoardError::Unplaceable));
+    /// assert_eq!(BoardOptions::new(6, 6, 35).check(), Ok(()));
+    /// ```
+    pub fn check(&self) -> Result<(), BoardError> {
+        let cells = self.width.checked_mul(self.height);
+        if self.width == 0 || self.height == 0 {
+            return Err(BoardError::Empty);
+        }
+        let cells = cells
+            .filter(|&n| n <= MAX_CELLS)
+            .ok_or(BoardError::TooLarge)?;
+        if self
+            .safe
+            .is_some_and(|(x, y)| x >= self.width || y >= self.height)
+        {
+            return Err(BoardError::SafeStartOutOfBounds);
+        }
+        let kept_clear = match (self.safe, self.safe_radius) {
+            (Some((x, y)), Some(radius)) => {
+                // cells of the square on one axis, clipped to 0..len
+                let span = |c: usize, len: usize| {
+                    c.saturating_add(radius).min(len - 1) - c.saturating_sub(radius) + 1
+                };
+                span(x, self.width) * span(y, self.height)
+            }
+            (Some(_), None) => 1,
+            (None, _) => 0,
+        };
+        if self.mines > cells - kept_clear {
+            return Err(BoardError::TooManyMines);
+        }
+        let excluded = Board::safe_area(self.width, self.height, self.safe, self.radius());
+        if !self.shape.is_free()
+            && !self
+                .shape
+                .fits(self.width, self.height, self.mines, excluded)
+        {
+            return Err(BoardError::Unplaceable);
+        }
+        Ok(())
+    }
+
+    /// Width, height and mine count.
+    pub(crate) fn params(&self) -> (usize, usize, usize) {
+        (self.width, self.height, self.mines)
+    }
+
+    /// The radius kept clear around the safe start: as asked, or as
+    /// `Board::with_seed` keeps it, the neighbours too if there is room.
+    fn radius(&self) -> usize {
+        let cells = self.width * self.height;
+        self.safe_radius
+            .unwrap_or(usize::from(cells.saturating_sub(self.mines) >= 9))
+    }
+}
+
+impl Board {
+    /// Start this board over as `options.build()` would, reusing its memory:
+    /// once a board has held a layout this size, resetting allocates
+    /// nothing. The same options (seed included) give the same layout as
+    /// `build`. On error the board is left as it was.
+    ///
+    /// ```
+    /// use minesweeper_rs::{Board, BoardOptions};
+    ///
+    /// let options = BoardOptions::new(16, 16, 40).seed(7).safe_start(3, 3);
+    /// let mut board = Board::new(9, 9, 10);
+    /// board.reset_in_place(&options)?;
+    /// assert_eq!(board.encode_layout(), options.build()?.encode_layout());
+    /// assert_eq!(board.seed(), Some(7));
+    /// # Ok::<(), minesweeper_rs::BoardError>(())
+    /// ```
+    pub fn reset_in_place(&mut self, options: &BoardOptions) -> Result<(), BoardError> {
+        options.check()?;
+        if !options.shape.is_free() {
+            // a shaped layout can still come up empty, so it is laid aside
+            let mut board = options.build()?;
+            board.revision = self.revision + 1;
+            board.spare = std::mem::take(&mut self.spare);
+            *self = board;
+            return Ok(());
+        }
+        let (width, height, mines) = (options.width, options.height, options.mines);
+        let radius = options.radius();
+
+        self.width = width;
+        self.height = height;
+        self.mine_count = mines;
+        self.cells.clear();
+        self.cells.resize(width * height, Bits::default());
+        self.game_over = false;
+        self.win = false;
+        self.seed = options.seed;
+        self.max_flags = options.max_flags;
+        self.flags = 0;
+        self.revealed_safe = 0;
+        self.revision += 1;
+        self.dirty.clear();
+        self.clear_wavefronts();
+        let seed = options.seed.unwrap_or_else(rand::random);
+        self.lay_mines(seed, options.safe, radius);
+        Ok(())
+    }
+}
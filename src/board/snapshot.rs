@@ -0,0 +1,104 @@
+//! Cheap copies of where a game stands, for taking a move back.
+//!
+//! A snapshot keeps only what play changes: one bit per cell each for
+//! revealed, flagged and question-marked, plus the game-over, win and counter fields. The
+//! mines stay with the board, so an expert board's snapshot is a couple of
+//! hundred bytes where a clone copies every cell.
+
+use super::{Bits, Board};
+use std::fmt;
+
+/// The play state of a board at one moment, from [`Board::snapshot`].
+///
+/// ```
+/// use minesweeper_rs::{Board, RevealResult};
+///
+/// let mut board = Board::decode_layout("*..\n...\n")?;
+/// let before = board.snapshot();
+/// assert_eq!(board.reveal_cell(0, 0), RevealResult::Exploded);
+/// board.restore(&before)?;
+/// assert!(!board.is_over());
+/// assert!(!board.cell(0, 0).is_revealed());
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BoardSnapshot {
+    layout: u64,        // layout_hash of the board it came from
+    revealed: Vec<u64>, // one bit per cell, row-major
+    flagged: Vec<u64>,
+    questioned: Vec<u64>,
+    game_over: bool,
+    win: bool,
+    flags: usize,
+    revealed_safe: usize,
+}
+
+/// Why `Board::restore` refused: the snapshot came from a board with a
+/// different size or mines.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SnapshotMismatch;
+
+impl fmt::Display for SnapshotMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("snapshot is of a different board")
+    }
+}
+
+impl std::error::Error for SnapshotMismatch {}
+
+/// Whether bit `i` of a packed bitmap is set.
+fn bit(words: &[u64], i: usize) -> bool {
+    (words[i / 64] >> (i % 64)) & 1 != 0
+}
+
+impl Board {
+    /// Capture the revealed and marked cells and the game state, so
+    /// [`restore`](Board::restore) can return to them.
+    pub fn snapshot(&self) -> BoardSnapshot {
+        let words = self.cells.len().div_ceil(64);
+        let mut revealed = vec![0; words];
+        let mut flagged = vec![0; words];
+        let mut questioned = vec![0; words];
+        for (i, cell) in self.cells.iter().enumerate() {
+            revealed[i / 64] |= u64::from(cell.is_revealed()) << (i % 64);
+            flagged[i / 64] |= u64::from(cell.is_flagged()) << (i % 64);
+            questioned[i / 64] |= u64::from(cell.0 & Bits::QUESTION != 0) << (i % 64);
+        }
+        BoardSnapshot {
+            layout: self.layout_hash(),
+            revealed,
+            flagged,
+            questioned,
+            game_over: self.game_over,
+            win: self.win,
+            flags: self.flags,
+            revealed_safe: self.revealed_safe,
+        }
+    }
+
+    /// Put the board back as it was when `snapshot` was taken. Changed
+    /// cells are marked dirty and the revision moves on, as for any move.
+    pub fn restore(&mut self, snapshot: &BoardSnapshot) -> Result<(), SnapshotMismatch> {
+        if snapshot.revealed.len() != self.cells.len().div_ceil(64)
+            || snapshot.layout != self.layout_hash()
+        {
+            return Err(SnapshotMismatch);
+        }
+        for (i, cell) in self.cells.iter_mut().enumerate() {
+            let before = *cell;
+            cell.set(Bits::REVEALED, bit(&snapshot.revealed, i));
+            cell.set(Bits::FLAGGED, bit(&snapshot.flagged, i));
+            cell.set(Bits::QUESTION, bit(&snapshot.questioned, i));
+            if *cell != before {
+                self.dirty.push(i as u32);
+            }
+        }
+        self.game_over = snapshot.game_over;
+        self.win = snapshot.win;
+        self.flags = snapshot.flags;
+        self.revealed_safe = snapshot.revealed_safe;
+        self.revision += 1;
+        self.clear_wavefronts();
+        Ok(())
+    }
+}
@@ -0,0 +1,156 @@
+//! Compact binary snapshot of a board, used by autosave.
+//!
+//! Layout, little endian: magic `MSB1`, width `u32`, height `u32`, mines
+//! `u32`, a flags byte (bit 0 game over, bit 1 win, bit 2 seeded, bit 3
+//! flag limit), seed `u64`, the flag limit as a `u32` only if bit 3 is set,
+//! then one byte per cell in row-major order holding its mine, revealed,
+//! flagged and question-mark bits (1, 2, 4 and 8). Neighbour counts are
+//! recomputed on load. A board with no mines laid yet, and nothing
+//! revealed, keeps the mine count its first click will lay.
+
+use super::{Bits, Board};
+use std::fmt;
+
+const MAGIC: &[u8; 4] = b"MSB1";
+const HEADER_LEN: usize = 4 + 4 * 3 + 1 + 8;
+const STATE_BITS: u8 = Bits::MINE | Bits::REVEALED | Bits::FLAGGED | Bits::QUESTION;
+
+/// Largest board a snapshot may describe, checked before allocating.
+pub const MAX_CELLS: usize = 16_000_000;
+
+/// Why a snapshot couldn't be loaded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DecodeError {
+    /// The bytes don't start with the snapshot magic.
+    BadMagic,
+    /// The snapshot ends early or has the wrong number of cells.
+    Truncated,
+    /// The board would have more than `MAX_CELLS` cells.
+    TooLarge,
+    /// A cell byte has bits set that no cell uses.
+    BadCell,
+    /// The header's mine count disagrees with the cells.
+    MineCountMismatch,
+    /// The header claims more mines than the board has cells.
+    TooManyMines,
+    /// The board has no width or no height.
+    Empty,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            DecodeError::BadMagic => "not a board snapshot",
+            DecodeError::Truncated => "snapshot is truncated",
+            DecodeError::TooLarge => "snapshot describes an oversized board",
+            DecodeError::BadCell => "snapshot has an invalid cell",
+            DecodeError::MineCountMismatch => "snapshot mine count doesn't match its cells",
+            DecodeError::TooManyMines => "snapshot has more mines than cells",
+            DecodeError::Empty => "snapshot describes a board with no cells",
+        })
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+impl Board {
+    /// Serialize the board so `decode_state` can rebuild it exactly.
+    ///
+    /// ```
+    /// use minesweeper_rs::Board;
+    ///
+    /// let mut board = Board::with_seed(9, 9, 10, 42, None);
+    /// board.toggle_flag(3, 3);
+    /// let restored = Board::decode_state(&board.encode_state())?;
+    /// assert!(restored.cell(3, 3).is_flagged());
+    /// assert_eq!(restored.seed(), Some(42));
+    /// # Ok::<(), minesweeper_rs::DecodeError>(())
+    /// ```
+    pub fn encode_state(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(HEADER_LEN + self.cells.len());
+        out.extend_from_slice(MAGIC);
+        for n in [self.width, self.height, self.mine_count] {
+            out.extend_from_slice(&(n as u32).to_le_bytes());
+        }
+        let flags = (self.game_over as u8)
+            | ((self.win as u8) << 1)
+            | ((self.seed.is_some() as u8) << 2)
+            | ((self.max_flags.is_some() as u8) << 3);
+        out.push(flags);
+        out.extend_from_slice(&self.seed.unwrap_or(0).to_le_bytes());
+        if let Some(max) = self.max_flags {
+            out.extend_from_slice(&(max as u32).to_le_bytes());
+        }
+        out.extend(self.cells.iter().map(|cell| cell.0 & STATE_BITS));
+        out
+    }
+
+    /// Rebuild a board from `encode_state` output. Sizes are checked before
+    /// anything is allocated, and trailing bytes are rejected.
+    ///
+    /// ```
+    /// use minesweeper_rs::{Board, DecodeError};
+    ///
+    /// assert_eq!(Board::decode_state(b"nope").err(), Some(DecodeError::BadMagic));
+    /// ```
+    pub fn decode_state(bytes: &[u8]) -> Result<Board, DecodeError> {
+        if bytes.len() < MAGIC.len() || &bytes[..MAGIC.len()] != MAGIC {
+            return Err(DecodeError::BadMagic);
+        }
+        if bytes.len() < HEADER_LEN {
+            return Err(DecodeError::Truncated);
+        }
+        let u32_at = |at: usize| u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap()) as usize;
+        let (width, height, mine_count) = (u32_at(4), u32_at(8), u32_at(12));
+        let flags = bytes[16];
+        let seed = u64::from_le_bytes(bytes[17..25].try_into().unwrap());
+
+        if width == 0 || height == 0 {
+            return Err(DecodeError::Empty);
+        }
+        let len = width.checked_mul(height).ok_or(DecodeError::TooLarge)?;
+        if len > MAX_CELLS {
+            return Err(DecodeError::TooLarge);
+        }
+        let (max_flags, cells) = if flags & 8 != 0 {
+            let rest = &bytes[HEADER_LEN..];
+            let (max, cells) = rest.split_at_checked(4).ok_or(DecodeError::Truncated)?;
+            let max = u32::from_le_bytes(max.try_into().unwrap());
+            (Some(max as usize), cells)
+        } else {
+            (None, &bytes[HEADER_LEN..])
+        };
+        if cells.len() != len {
+            return Err(DecodeError::Truncated);
+        }
+        if cells.iter().any(|&b| b & !STATE_BITS != 0) {
+            return Err(DecodeError::BadCell);
+        }
+        // checked before the blank case, which takes the count on trust
+        if mine_count > len {
+            return Err(DecodeError::TooManyMines);
+        }
+        // a board waiting for its first click has no mines laid yet
+        let laid = cells.iter().filter(|&&b| b & Bits::MINE != 0).count();
+        let blank = laid == 0 && cells.iter().all(|&b| b & Bits::REVEALED == 0);
+        if laid != mine_count && !blank {
+            return Err(DecodeError::MineCountMismatch);
+        }
+
+        let mut board = Board::blank(width, height, mine_count);
+        board.cells = cells.iter().map(|&b| Bits(b)).collect();
+        board.compute_neighbor_counts();
+        board.revealed_safe = board
+            .cells
+            .iter()
+            .filter(|c| c.is_revealed() && !c.is_mine())
+            .count();
+        board.flags = board.cells.iter().filter(|c| c.is_flagged()).count();
+        board.max_flags = max_flags;
+        board.game_over = flags & 1 != 0;
+        board.win = flags & 2 != 0;
+        board.seed = (flags & 4 != 0).then_some(seed);
+        Ok(board)
+    }
+}
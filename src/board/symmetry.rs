@@ -0,0 +1,250 @@
+//! Mine layouts with a shape asked of them: mirrored or turned about the
+//! centre, and perhaps with no two mines side by side.
+//!
+//! Under a symmetry the cells fall into orbits, a cell and its image or a
+//! lone cell on the axis, and a layout is symmetric when each orbit is
+//! mined whole or not at all. Mines go down an orbit at a time in an order
+//! shuffled by `BoardRng`, skipping orbits that would overshoot the count
+//! or, with spacing, touch a mine already down. A fill that comes up short
+//! is shuffled again from the same generator, up to [`TRIES`] times, so the
+//! layout still depends only on the seed and the options.
+
+use super::Board;
+use crate::rng::BoardRng;
+use std::fmt;
+use std::str::FromStr;
+
+/// Fills tried before a shaped layout is given up on.
+const TRIES: usize = 64;
+
+/// A symmetry the mine layout keeps.
+///
+/// ```
+/// use minesweeper_rs::{BoardOptions, Symmetry};
+///
+/// let board = BoardOptions::new(9, 9, 20)
+///     .symmetry(Symmetry::Horizontal)
+///     .build()?;
+/// assert!(board
+///     .encode_layout()
+///     .lines()
+///     .all(|row| row.chars().rev().eq(row.chars())));
+/// # Ok::<(), minesweeper_rs::BoardError>(())
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Symmetry {
+    /// Each row reads the same from either end.
+    Horizontal,
+    /// Each column reads the same from either end.
+    Vertical,
+    /// The same after half a turn about the centre.
+    Rotational180,
+}
+
+impl Symmetry {
+    /// Every symmetry, in the order menus list them.
+    pub const ALL: [Symmetry; 3] = [
+        Symmetry::Horizontal,
+        Symmetry::Vertical,
+        Symmetry::Rotational180,
+    ];
+
+    /// The name `FromStr` accepts.
+    pub fn name(self) -> &'static str {
+        match self {
+            Symmetry::Horizontal => "horizontal",
+            Symmetry::Vertical => "vertical",
+            Symmetry::Rotational180 => "rotational",
+        }
+    }
+
+    /// Where (x, y) lands on a `width` x `height` board.
+    ///
+    /// ```
+    /// use minesweeper_rs::Symmetry;
+    ///
+    /// assert_eq!(Symmetry::Horizontal.image(0, 1, 5, 3), (4, 1));
+    /// assert_eq!(Symmetry::Rotational180.image(0, 0, 5, 3), (4, 2));
+    /// ```
+    pub fn image(self, x: usize, y: usize, width: usize, height: usize) -> (usize, usize) {
+        match self {
+            Symmetry::Horizontal => (width - 1 - x, y),
+            Symmetry::Vertical => (x, height - 1 - y),
+            Symmetry::Rotational180 => (width - 1 - x, height - 1 - y),
+        }
+    }
+}
+
+impl FromStr for Symmetry {
+    type Err = UnknownSymmetry;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Symmetry::ALL
+            .into_iter()
+            .find(|symmetry| symmetry.name() == s)
+            .ok_or_else(|| UnknownSymmetry(s.to_string()))
+    }
+}
+
+/// A `--symmetry` value that isn't one of [`Symmetry::ALL`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnknownSymmetry(pub String);
+
+impl fmt::Display for UnknownSymmetry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown symmetry {:?}; expected one of", self.0)?;
+        for symmetry in Symmetry::ALL {
+            write!(f, " {}", symmetry.name())?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for UnknownSymmetry {}
+
+/// What a layout is asked to look like, beyond its mine count.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(super) struct Shape {
+    pub symmetry: Option<Symmetry>,
+    pub spaced: bool, // no two mines orthogonally adjacent
+}
+
+impl Shape {
+    /// Whether anything is asked at all; a free layout is placed the
+    /// classic way, so its seeds keep their boards.
+    pub fn is_free(self) -> bool {
+        self.symmetry.is_none() && !self.spaced
+    }
+
+    /// The orbits that can take mines, as the two cell indices of each (the
+    /// same twice for a lone cell): none with an `excluded` cell, and with
+    /// spacing none whose two cells touch each other.
+    fn orbits(
+        self,
+        width: usize,
+        height: usize,
+        excluded: impl Fn(usize) -> bool,
+    ) -> Vec<(usize, usize)> {
+        let mut orbits = Vec::new();
+        for idx in 0..width * height {
+            let (x, y) = (idx % width, idx / width);
+            let image = self.symmetry.map_or(idx, |symmetry| {
+                let (ix, iy) = symmetry.image(x, y, width, height);
+                iy * width + ix
+            });
+            if image < idx || excluded(idx) || excluded(image) {
+                continue;
+            }
+            if self.spaced && touching(idx, image, width) {
+                continue;
+            }
+            orbits.push((idx, image));
+        }
+        orbits
+    }
+
+    /// Whether `mines` could be laid at all: the usable orbits hold enough
+    /// cells, an odd count has a lone cell for the odd mine, and with
+    /// spacing there are no more than a checkerboard holds. A request that
+    /// passes can still prove too tight for `place`.
+    pub fn fits(
+        self,
+        width: usize,
+        height: usize,
+        mines: usize,
+        excluded: impl Fn(usize) -> bool,
+    ) -> bool {
+        let orbits = self.orbits(width, height, excluded);
+        let room: usize = orbits.iter().map(|&(a, b)| 1 + usize::from(a != b)).sum();
+        let lone = orbits.iter().any(|&(a, b)| a == b);
+        let spaced_max = if self.spaced {
+            (width * height).div_ceil(2)
+        } else {
+            usize::MAX
+        };
+        mines <= room && (mines.is_multiple_of(2) || lone) && mines <= spaced_max
+    }
+
+    /// The cells to mine, from `seed`; `None` if no fill within [`TRIES`]
+    /// reached the count.
+    pub fn place(
+        self,
+        width: usize,
+        height: usize,
+        mines: usize,
+        seed: u64,
+        excluded: impl Fn(usize) -> bool,
+    ) -> Option<Vec<usize>> {
+        let mut orbits = self.orbits(width, height, excluded);
+        let mut rng = BoardRng::new(seed);
+        let mut mined = vec![false; width * height];
+        let mut placed = Vec::with_capacity(mines);
+        for _ in 0..TRIES {
+            for i in (1..orbits.len()).rev() {
+                orbits.swap(i, rng.below(i + 1));
+            }
+            mined.fill(false);
+            placed.clear();
+            for &(a, b) in &orbits {
+                let size = 1 + usize::from(a != b);
+                if placed.len() == mines {
+                    break;
+                }
+                if placed.len() + size > mines {
+                    continue;
+                }
+                if self.spaced && [a, b].iter().any(|&c| next_to(c, width, height, &mined)) {
+                    continue;
+                }
+                mined[a] = true;
+                mined[b] = true;
+                placed.push(a);
+                if a != b {
+                    placed.push(b);
+                }
+            }
+            if placed.len() == mines {
+                return Some(placed);
+            }
+        }
+        None
+    }
+}
+
+/// Whether cells `a` and `b` share a side.
+fn touching(a: usize, b: usize, width: usize) -> bool {
+    let beside = a / width == b / width && a.abs_diff(b) == 1;
+    beside || a.abs_diff(b) == width
+}
+
+/// Whether any cell sharing a side with `idx` is mined.
+fn next_to(idx: usize, width: usize, height: usize, mined: &[bool]) -> bool {
+    let (x, y) = (idx % width, idx / width);
+    (x > 0 && mined[idx - 1])
+        || (x + 1 < width && mined[idx + 1])
+        || (y > 0 && mined[idx - width])
+        || (y + 1 < height && mined[idx + width])
+}
+
+impl Board {
+    /// Lay `shape`d mines on this freshly blank board; false, leaving it
+    /// blank, if none were found.
+    pub(super) fn lay_shaped(
+        &mut self,
+        seed: u64,
+        safe: Option<(usize, usize)>,
+        radius: usize,
+        shape: Shape,
+    ) -> bool {
+        let (width, height) = (self.width, self.height);
+        let excluded = Board::safe_area(width, height, safe, radius);
+        let Some(cells) = shape.place(width, height, self.mine_count, seed, excluded) else {
+            return false;
+        };
+        for idx in cells {
+            self.cells[idx].set(super::Bits::MINE, true);
+        }
+        self.compute_neighbor_counts();
+        true
+    }
+}
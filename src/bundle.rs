@@ -0,0 +1,464 @@
+//! Everything a player keeps, in one JSON file for moving to another
+//! computer: each profile's settings and history (statistics and best
+//! times), each profile's saved game, and saved race results.
+//!
+//! A bundle is a format tag, a version and named sections, and every
+//! section, and every entry in one, is checked on its own on import:
+//! whatever is valid comes in and the rest is listed with the reason.
+//! Sections this version doesn't know, say from a newer one, are skipped
+//! the same way instead of failing the import. Nothing is written until
+//! the player picks between merging into what is here and replacing it.
+
+use crate::history::History;
+use crate::profiles::{self, Profiles};
+use crate::race::RaceResult;
+use crate::settings::Settings;
+use minesweeper_rs::autosave::Autosave;
+use minesweeper_rs::Board;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+/// Tag every bundle carries, so other JSON is turned away.
+const FORMAT: &str = "bosnia-simulator-data";
+
+/// Bundle version written by this build.
+pub const VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct Envelope {
+    format: String,
+    version: u32,
+    sections: Map<String, Value>,
+}
+
+/// One profile's saved game, as bundled.
+#[derive(Serialize, Deserialize)]
+struct SavedGame {
+    elapsed_secs: f64,
+    state: String, // `Board::encode_state`, in hex
+}
+
+/// What a bundle holds that this version could read.
+#[derive(Default)]
+pub struct Import {
+    pub profiles: BTreeMap<String, Imported>,
+    pub races: BTreeMap<String, RaceResult>, // by file name
+    pub skipped: Vec<String>,                // what was left out, and why
+}
+
+/// One profile's worth of an import; any part may be missing.
+#[derive(Default)]
+pub struct Imported {
+    pub settings: Option<Settings>,
+    pub history: Option<History>,
+    pub saved_game: Option<(Board, f64)>, // board and seconds played
+}
+
+/// How an import meets the data already here.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    /// Add history to history, keep settings, saved games and race results
+    /// that are already here, and create missing profiles.
+    Merge,
+    /// Overwrite same-named profiles, saved games and race results with the
+    /// bundle's. Anything the bundle doesn't mention is kept.
+    Replace,
+}
+
+/// Why a file couldn't be read as a bundle at all.
+#[derive(Debug)]
+pub enum BundleError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    NotABundle,
+}
+
+impl fmt::Display for BundleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BundleError::Io(err) => write!(f, "{err}"),
+            BundleError::Json(err) => write!(f, "not a data bundle: {err}"),
+            BundleError::NotABundle => f.write_str("not a data bundle"),
+        }
+    }
+}
+
+impl std::error::Error for BundleError {}
+
+impl From<serde_json::Error> for BundleError {
+    fn from(err: serde_json::Error) -> Self {
+        BundleError::Json(err)
+    }
+}
+
+impl From<std::io::Error> for BundleError {
+    fn from(err: std::io::Error) -> Self {
+        BundleError::Io(err)
+    }
+}
+
+/// The bundle for `profiles`, whose active profile must already hold its
+/// live settings and history, plus the saved games and race results
+/// under `data_dir`.
+pub fn export(profiles: &Profiles, data_dir: Option<&Path>) -> String {
+    let mut settings = Map::new();
+    let mut history = Map::new();
+    let mut saved_games = Map::new();
+    for (name, data) in profiles.all() {
+        settings.insert(name.to_string(), json!(data.settings));
+        history.insert(name.to_string(), json!(data.history));
+        let slot = data_dir.map(|dir| slot(dir, data.id));
+        if let Some(session) = slot.and_then(|slot| slot.load()) {
+            let saved = SavedGame {
+                elapsed_secs: session.elapsed_secs,
+                state: hex(&session.board.encode_state()),
+            };
+            saved_games.insert(name.to_string(), json!(saved));
+        }
+    }
+
+    let mut races = Map::new();
+    let race_files = data_dir.and_then(|dir| std::fs::read_dir(dir.join("races")).ok());
+    for entry in race_files.into_iter().flatten().flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if let Ok(result) = RaceResult::load(&entry.path()) {
+            races.insert(name, json!(result));
+        }
+    }
+
+    let names: Vec<&str> = profiles.names().collect();
+    let sections = Map::from_iter([
+        (
+            "profiles".to_string(),
+            json!({ "current": profiles.current(), "names": names }),
+        ),
+        ("settings".to_string(), Value::Object(settings)),
+        ("history".to_string(), Value::Object(history)),
+        ("saved_games".to_string(), Value::Object(saved_games)),
+        ("races".to_string(), Value::Object(races)),
+    ]);
+    let envelope = Envelope {
+        format: FORMAT.to_string(),
+        version: VERSION,
+        sections,
+    };
+    serde_json::to_string_pretty(&envelope).expect("bundles serialize")
+}
+
+/// Read a bundle, keeping every section and entry that checks out.
+pub fn read(text: &str) -> Result<Import, BundleError> {
+    let envelope: Envelope = serde_json::from_str(text)?;
+    if envelope.format != FORMAT {
+        return Err(BundleError::NotABundle);
+    }
+    let mut import = Import::default();
+    if envelope.version > VERSION {
+        import.skipped.push(format!(
+            "bundle is version {}, newer than this app's {VERSION}: parts of it may be missing",
+            envelope.version
+        ));
+    }
+
+    for (section, value) in envelope.sections {
+        match section.as_str() {
+            // names come with the other sections; this one is for people
+            "profiles" => {}
+            "settings" => {
+                for (name, settings) in entries(&section, value, &mut import.skipped) {
+                    import.profile(name).settings = Some(settings);
+                }
+            }
+            "history" => {
+                for (name, history) in entries(&section, value, &mut import.skipped) {
+                    import.profile(name).history = Some(history);
+                }
+            }
+            "saved_games" => {
+                for (name, saved) in entries::<SavedGame>(&section, value, &mut import.skipped) {
+                    let board = unhex(&saved.state).and_then(|b| Board::decode_state(&b).ok());
+                    match board {
+                        Some(board) => {
+                            import.profile(name).saved_game = Some((board, saved.elapsed_secs));
+                        }
+                        None => import
+                            .skipped
+                            .push(format!("saved game for {name}: the board is corrupt")),
+                    }
+                }
+            }
+            "races" => {
+                for (file, result) in entries::<Value>(&section, value, &mut import.skipped) {
+                    if !is_race_file_name(&file) {
+                        import
+                            .skipped
+                            .push(format!("race result {file}: bad file name"));
+                        continue;
+                    }
+                    match RaceResult::from_json(&result.to_string()) {
+                        Ok(result) => {
+                            import.races.insert(file, result);
+                        }
+                        Err(err) => import.skipped.push(format!("race result {file}: {err}")),
+                    }
+                }
+            }
+            other => import
+                .skipped
+                .push(format!("section \"{other}\": not known to this version")),
+        }
+    }
+
+    let names: Vec<String> = import.profiles.keys().cloned().collect();
+    for name in names {
+        if let Err(err) = profiles::check_name(&name) {
+            import.profiles.remove(&name);
+            import.skipped.push(format!("profile {name:?}: {err}"));
+        }
+    }
+    Ok(import)
+}
+
+/// Read the bundle at `path`.
+pub fn load(path: &Path) -> Result<Import, BundleError> {
+    read(&std::fs::read_to_string(path)?)
+}
+
+impl Import {
+    fn profile(&mut self, name: String) -> &mut Imported {
+        self.profiles.entry(name).or_default()
+    }
+
+    /// One line on what would come in, for before the choice is made.
+    pub fn summary(&self) -> String {
+        let saved = self
+            .profiles
+            .values()
+            .filter(|profile| profile.saved_game.is_some())
+            .count();
+        format!(
+            "{} profile(s), {saved} saved game(s), {} race result(s)",
+            self.profiles.len(),
+            self.races.len()
+        )
+    }
+
+    /// Bring everything into `profiles` under `mode`, writing saved games
+    /// and race results under `data_dir`. Returns a line per change.
+    pub fn apply(
+        self,
+        mode: Mode,
+        profiles: &mut Profiles,
+        data_dir: Option<&Path>,
+    ) -> Vec<String> {
+        let mut report = Vec::new();
+        for (name, imported) in self.profiles {
+            let Ok((data, created)) = profiles.entry(&name) else {
+                continue; // names were checked on reading
+            };
+            let replace = mode == Mode::Replace || created;
+            if let Some(settings) = imported.settings.filter(|_| replace) {
+                data.settings = settings;
+            }
+            if let Some(history) = imported.history {
+                if replace {
+                    data.history = history;
+                } else {
+                    data.history.merge(history);
+                }
+            }
+            let id = data.id;
+            if let (Some((board, secs)), Some(dir)) = (imported.saved_game, data_dir) {
+                let slot = slot(dir, id);
+                if replace || slot.load().is_none() {
+                    if let Err(err) = slot.save(&board, secs) {
+                        report.push(format!("saved game for {name}: {err}"));
+                    }
+                }
+            }
+            report.push(match (created, mode) {
+                (true, _) => format!("added profile {name}"),
+                (false, Mode::Merge) => format!("merged into profile {name}"),
+                (false, Mode::Replace) => format!("replaced profile {name}"),
+            });
+        }
+
+        if let Some(dir) = data_dir.filter(|_| !self.races.is_empty()) {
+            let dir = dir.join("races");
+            let mut written = 0;
+            for (file, result) in self.races {
+                let path = dir.join(&file);
+                if mode == Mode::Merge && path.exists() {
+                    continue;
+                }
+                let stored = std::fs::create_dir_all(&dir)
+                    .and_then(|()| std::fs::write(&path, result.to_json()));
+                match stored {
+                    Ok(()) => written += 1,
+                    Err(err) => report.push(format!("race result {file}: {err}")),
+                }
+            }
+            report.push(format!("{written} race result(s) written"));
+        }
+        report
+    }
+}
+
+/// The entries of an object section, each parsed on its own; ones that
+/// don't parse are noted in `skipped`.
+fn entries<T: DeserializeOwned>(
+    section: &str,
+    value: Value,
+    skipped: &mut Vec<String>,
+) -> Vec<(String, T)> {
+    let Value::Object(map) = value else {
+        skipped.push(format!("section \"{section}\": not a set of entries"));
+        return Vec::new();
+    };
+    map.into_iter()
+        .filter_map(|(key, value)| match serde_json::from_value(value) {
+            Ok(entry) => Some((key, entry)),
+            Err(err) => {
+                skipped.push(format!("{section} for {key}: {err}"));
+                None
+            }
+        })
+        .collect()
+}
+
+fn slot(data_dir: &Path, id: u32) -> Autosave {
+    Autosave::new(profiles::profile_dir(data_dir, id).join("autosave.bin"))
+}
+
+/// A plain `NAME.json`, so an import can't write outside the races folder.
+fn is_race_file_name(name: &str) -> bool {
+    name.ends_with(".json") && Path::new(name).file_name().is_some_and(|file| file == name)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn unhex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn data_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir()
+            .join(format!("minesweeper-bundle-{}", std::process::id()))
+            .join(name);
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    /// A Default profile with some history and a saved game, plus Sam.
+    fn sample(dir: &Path) -> Profiles {
+        let mut profiles = Profiles::default();
+        profiles.create("Sam").unwrap();
+        let mut settings = Settings::default();
+        settings.gameplay.question_marks = true;
+        let history = History {
+            games: 7,
+            ..History::default()
+        };
+        profiles.fold_live((&settings, &history));
+
+        let mut board = Board::with_seed(9, 9, 10, 3, Some((4, 4)));
+        board.reveal_cell(4, 4);
+        slot(dir, profiles.data().id).save(&board, 12.5).unwrap();
+        profiles
+    }
+
+    #[test]
+    fn bundles_round_trip() {
+        let from = data_dir("round-trip-from");
+        let profiles = sample(&from);
+        let text = export(&profiles, Some(&from));
+
+        let import = read(&text).unwrap();
+        assert!(import.skipped.is_empty(), "{:?}", import.skipped);
+        assert_eq!(import.profiles.len(), 2);
+
+        let to = data_dir("round-trip-to");
+        let mut fresh = Profiles::default();
+        import.apply(Mode::Replace, &mut fresh, Some(&to));
+        assert_eq!(fresh.names().collect::<Vec<_>>(), ["Default", "Sam"]);
+        assert_eq!(fresh.data().settings, profiles.data().settings);
+        assert_eq!(fresh.data().history.games, 7);
+        let saved = slot(&to, fresh.data().id)
+            .load()
+            .expect("saved game came along");
+        assert_eq!(saved.elapsed_secs, 12.5);
+        assert_eq!(
+            saved.board.encode_state(),
+            slot(&from, profiles.data().id)
+                .load()
+                .unwrap()
+                .board
+                .encode_state()
+        );
+
+        // exporting what came in gives the same bundle back
+        assert_eq!(export(&fresh, Some(&to)), text);
+    }
+
+    #[test]
+    fn merging_adds_history_and_keeps_settings() {
+        let dir = data_dir("merge");
+        let import = read(&export(&sample(&dir), None)).unwrap();
+
+        let mut here = Profiles::default();
+        let history = History {
+            games: 3,
+            ..History::default()
+        };
+        here.fold_live((&Settings::default(), &history));
+        import.apply(Mode::Merge, &mut here, None);
+        assert_eq!(here.data().history.games, 10);
+        assert_eq!(here.data().settings, Settings::default());
+    }
+
+    #[test]
+    fn unknown_sections_and_bad_entries_are_skipped() {
+        let dir = data_dir("forward");
+        let mut bundle: Value = serde_json::from_str(&export(&sample(&dir), Some(&dir))).unwrap();
+        let sections = bundle["sections"].as_object_mut().unwrap();
+        sections.insert("replays".into(), json!({ "one": [1, 2, 3] }));
+        sections["history"]["Sam"] = json!("not a history");
+        sections["races"] = json!({ "../escape.json": {}, "made-up.json": { "player": "x" } });
+        bundle["version"] = json!(VERSION + 1);
+
+        let import = read(&bundle.to_string()).unwrap();
+        assert_eq!(import.profiles.len(), 2);
+        assert!(import.profiles["Sam"].history.is_none());
+        assert!(import.profiles["Sam"].settings.is_some());
+        assert!(import.profiles["Default"].saved_game.is_some());
+        assert!(import.races.is_empty());
+        // the version note, replays, Sam's history and both race results
+        assert_eq!(import.skipped.len(), 5, "{:?}", import.skipped);
+    }
+
+    #[test]
+    fn other_json_is_refused() {
+        assert!(matches!(read("{}"), Err(BundleError::Json(_))));
+        let other = json!({ "format": "something-else", "version": 1, "sections": {} });
+        assert!(matches!(
+            read(&other.to_string()),
+            Err(BundleError::NotABundle)
+        ));
+    }
+}
@@ -0,0 +1,461 @@
+//! Daily and weekly challenges: one board everyone gets on the same day or
+//! in the same week, played as a one-board seed race so the result is
+//! verified like any other. The daily is a plain Intermediate board; the
+//! weekly is a bigger one with a special rule that rotates with the ISO
+//! week number (see `Modifier::ROTATION`).
+//!
+//! Everything about a challenge follows from its date through
+//! `ChallengeSpec`, so players only need to agree on the calendar. The
+//! seed is a hash of the date, not a random draw, and the rule is picked
+//! by the week number, so the schedule is the same on every machine.
+//!
+//! Results go into a bucket of the history of their own, with streaks,
+//! rather than among the records for each board size.
+
+use crate::race::Race;
+use chrono::{Datelike, Duration, NaiveDate};
+use minesweeper_rs::difficulty::{BoardSpec, Mines};
+use minesweeper_rs::{BoardOptions, Difficulty, Symmetry};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Size of the weekly board; the daily is Intermediate.
+const WEEKLY_SIZE: (usize, usize) = (24, 20);
+
+/// Mines on the weekly board, unless the week's rule is `Dense`.
+const WEEKLY_MINES: usize = 90;
+
+/// Mine percentage of a `Dense` week.
+const DENSE_PERCENT: u8 = 22;
+
+/// Challenges remembered: a couple of years of dailies and weeklies.
+const KEPT_RESULTS: usize = 800;
+
+/// How often a challenge comes round.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Kind {
+    Daily,
+    Weekly,
+}
+
+impl Kind {
+    pub const ALL: [Kind; 2] = [Kind::Daily, Kind::Weekly];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Kind::Daily => "Daily challenge",
+            Kind::Weekly => "Weekly challenge",
+        }
+    }
+
+    /// The day a challenge taking in `day` is dated by: the day itself, or
+    /// the Monday of its week.
+    pub fn start(self, day: NaiveDate) -> NaiveDate {
+        match self {
+            Kind::Daily => day,
+            Kind::Weekly => day - Duration::days(day.weekday().num_days_from_monday().into()),
+        }
+    }
+
+    /// The challenge before the one dated `start`.
+    fn before(self, start: NaiveDate) -> NaiveDate {
+        match self {
+            Kind::Daily => start - Duration::days(1),
+            Kind::Weekly => start - Duration::weeks(1),
+        }
+    }
+
+    /// "3 days", "1 week".
+    pub fn periods(self, n: u32) -> String {
+        let unit = match self {
+            Kind::Daily => "day",
+            Kind::Weekly => "week",
+        };
+        match n {
+            1 => format!("1 {unit}"),
+            n => format!("{n} {unit}s"),
+        }
+    }
+}
+
+/// A weekly challenge's special rule. Each builds on a variant the game
+/// already has.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Modifier {
+    Symmetric,   // the layout turned half way round is the same
+    Spaced,      // no two mines share a side
+    FlagLimited, // no more flags than mines
+    Dense,       // DENSE_PERCENT of the cells are mines
+}
+
+impl Modifier {
+    /// The rule of ISO week `n` is `ROTATION[n % 4]`.
+    pub const ROTATION: [Modifier; 4] = [
+        Modifier::Symmetric,
+        Modifier::Spaced,
+        Modifier::FlagLimited,
+        Modifier::Dense,
+    ];
+
+    /// The rule of ISO week `week`.
+    pub fn of_week(week: u32) -> Modifier {
+        Modifier::ROTATION[week as usize % Modifier::ROTATION.len()]
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Modifier::Symmetric => "Symmetric",
+            Modifier::Spaced => "Spaced",
+            Modifier::FlagLimited => "Flag limit",
+            Modifier::Dense => "Dense",
+        }
+    }
+
+    /// The rule as the challenge card explains it.
+    pub fn rules(self) -> String {
+        match self {
+            Modifier::Symmetric => "Turned half way round, the mines are where they were.".into(),
+            Modifier::Spaced => "No two mines share a side.".into(),
+            Modifier::FlagLimited => "No more flags than there are mines.".into(),
+            Modifier::Dense => format!("{DENSE_PERCENT}% of the cells are mines."),
+        }
+    }
+
+    /// `options` for a board of `mines` mines under this rule. A dense
+    /// board's mine count is already in `mines`.
+    pub fn apply(self, options: BoardOptions, mines: usize) -> BoardOptions {
+        match self {
+            Modifier::Symmetric => options.symmetry(Symmetry::Rotational180),
+            Modifier::Spaced => options.avoid_adjacent_mines(true),
+            Modifier::FlagLimited => options.max_flags(mines),
+            Modifier::Dense => options,
+        }
+    }
+}
+
+/// Everything a challenge is, worked out from its date.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChallengeSpec {
+    pub kind: Kind,
+    pub day: NaiveDate, // see `Kind::start`
+    pub spec: BoardSpec,
+    pub modifier: Option<Modifier>,
+    pub seed: u64,
+}
+
+impl ChallengeSpec {
+    /// The `kind` of challenge that takes in `day`.
+    pub fn new(kind: Kind, day: NaiveDate) -> Self {
+        let day = kind.start(day);
+        let (spec, modifier) = match kind {
+            Kind::Daily => (Difficulty::Intermediate.spec(), None),
+            Kind::Weekly => {
+                let modifier = Modifier::of_week(day.iso_week().week());
+                let mines = match modifier {
+                    Modifier::Dense => Mines::Percent(DENSE_PERCENT),
+                    _ => Mines::Count(WEEKLY_MINES),
+                };
+                let (width, height) = WEEKLY_SIZE;
+                (BoardSpec::new(width, height, mines), Some(modifier))
+            }
+        };
+        let mut challenge = ChallengeSpec {
+            kind,
+            day,
+            spec,
+            modifier,
+            seed: 0,
+        };
+        // the name is part of the seed so a day and a week never share one
+        let name = match kind {
+            Kind::Daily => "daily",
+            Kind::Weekly => "weekly",
+        };
+        challenge.seed = seed(&format!("{name} {}", challenge.key()));
+        challenge
+    }
+
+    /// "2026-10-15" for a daily, "2026-W42" for a weekly.
+    pub fn key(&self) -> String {
+        match self.kind {
+            Kind::Daily => self.day.format("%Y-%m-%d").to_string(),
+            Kind::Weekly => {
+                let week = self.day.iso_week();
+                format!("{}-W{:02}", week.year(), week.week())
+            }
+        }
+    }
+
+    /// "Weekly challenge 2026-W42".
+    pub fn title(&self) -> String {
+        format!("{} {}", self.kind.label(), self.key())
+    }
+
+    /// The board and its rule, for the card shown before starting.
+    pub fn rules(&self) -> String {
+        match self.modifier {
+            Some(modifier) => format!("{} · {}: {}", self.spec, modifier.name(), modifier.rules()),
+            None => format!("{} · standard rules", self.spec),
+        }
+    }
+
+    /// The one-board race the challenge is played as.
+    pub fn race(&self) -> Race {
+        let (width, height, mines) = self.spec.params();
+        Race {
+            width,
+            height,
+            mines,
+            seeds: vec![self.seed],
+            handicaps: BTreeMap::new(),
+            modifier: self.modifier,
+        }
+    }
+
+    /// Options that build the challenge's board, before its centre is
+    /// opened.
+    #[cfg(test)]
+    pub fn options(&self) -> BoardOptions {
+        self.race().options(0)
+    }
+}
+
+/// The seed for `key`, the same on every machine: FNV-1a over its bytes.
+fn seed(key: &str) -> u64 {
+    key.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// How one challenge went, over all its tries.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ChallengeResult {
+    pub kind: Kind,
+    pub day: NaiveDate, // the challenge's, see `Kind::start`
+    pub modifier: Option<Modifier>,
+    pub tries: u32,
+    pub won: bool,
+    pub best: Option<f64>, // fastest win, in seconds
+}
+
+/// The challenges a profile has played, oldest first.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Challenges {
+    pub results: Vec<ChallengeResult>,
+}
+
+impl Challenges {
+    /// How `challenge` has gone so far, if it has been played.
+    pub fn get(&self, challenge: &ChallengeSpec) -> Option<&ChallengeResult> {
+        self.results
+            .iter()
+            .find(|result| result.kind == challenge.kind && result.day == challenge.day)
+    }
+
+    /// Note a try at `challenge`, won in `secs` or lost, forgetting the
+    /// oldest challenge past `KEPT_RESULTS`.
+    pub fn record(&mut self, challenge: &ChallengeSpec, won: bool, secs: f64) {
+        let played = self
+            .results
+            .iter()
+            .position(|result| result.kind == challenge.kind && result.day == challenge.day);
+        let index = played.unwrap_or_else(|| {
+            self.results.push(ChallengeResult {
+                kind: challenge.kind,
+                day: challenge.day,
+                modifier: challenge.modifier,
+                tries: 0,
+                won: false,
+                best: None,
+            });
+            self.results.len() - 1
+        });
+        let result = &mut self.results[index];
+        result.tries += 1;
+        if won {
+            result.won = true;
+            result.best = Some(result.best.map_or(secs, |best| best.min(secs)));
+        }
+        self.trim();
+    }
+
+    /// Challenges of `kind` won in a row, up to the one taking in `today`,
+    /// or the one before while today's is still to be won.
+    pub fn streak(&self, kind: Kind, today: NaiveDate) -> u32 {
+        let won = |day| {
+            self.results
+                .iter()
+                .any(|result| result.kind == kind && result.day == day && result.won)
+        };
+        let mut day = kind.start(today);
+        if !won(day) {
+            day = kind.before(day);
+        }
+        let mut streak = 0;
+        while won(day) {
+            streak += 1;
+            day = kind.before(day);
+        }
+        streak
+    }
+
+    /// The most challenges of `kind` ever won in a row.
+    pub fn longest_streak(&self, kind: Kind) -> u32 {
+        let mut days: Vec<NaiveDate> = self
+            .results
+            .iter()
+            .filter(|result| result.kind == kind && result.won)
+            .map(|result| result.day)
+            .collect();
+        days.sort();
+        let (mut longest, mut run) = (0, 0);
+        for (i, &day) in days.iter().enumerate() {
+            run = match i {
+                0 => 1,
+                _ if kind.before(day) == days[i - 1] => run + 1,
+                _ => 1,
+            };
+            longest = longest.max(run);
+        }
+        longest
+    }
+
+    /// Fold in challenges played elsewhere: tries add up, a win on either
+    /// side is a win, and the faster best is kept.
+    pub fn merge(&mut self, other: Challenges) {
+        for theirs in other.results {
+            let ours = self
+                .results
+                .iter_mut()
+                .find(|ours| ours.kind == theirs.kind && ours.day == theirs.day);
+            let Some(ours) = ours else {
+                self.results.push(theirs);
+                continue;
+            };
+            ours.tries += theirs.tries;
+            ours.won |= theirs.won;
+            ours.best = match (ours.best, theirs.best) {
+                (Some(a), Some(b)) => Some(a.min(b)),
+                (a, b) => a.or(b),
+            };
+        }
+        self.results.sort_by_key(|result| result.day);
+        self.trim();
+    }
+
+    fn trim(&mut self) {
+        let excess = self.results.len().saturating_sub(KEPT_RESULTS);
+        self.results.drain(..excess);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn dates_give_pinned_challenges() {
+        let daily = ChallengeSpec::new(Kind::Daily, day(2026, 10, 15));
+        assert_eq!(daily.key(), "2026-10-15");
+        assert_eq!((daily.spec.params(), daily.modifier), ((16, 16, 40), None));
+        assert_eq!(
+            daily.options(),
+            BoardOptions::new(16, 16, 40)
+                .seed(14136046681501103495)
+                .safe_start(8, 8)
+        );
+
+        // every day of a week gets that week's challenge
+        let weekly = ChallengeSpec::new(Kind::Weekly, day(2026, 10, 15));
+        assert_eq!(weekly, ChallengeSpec::new(Kind::Weekly, day(2026, 10, 18)));
+        assert_eq!(
+            (weekly.key(), weekly.day),
+            ("2026-W42".into(), day(2026, 10, 12))
+        );
+        assert_eq!(weekly.modifier, Some(Modifier::FlagLimited));
+        assert_eq!(
+            weekly.options(),
+            BoardOptions::new(24, 20, 90)
+                .seed(7474640880706513314)
+                .safe_start(12, 10)
+                .max_flags(90)
+        );
+
+        // the first days of 2027 are still in 2026's 53rd week
+        let new_year = ChallengeSpec::new(Kind::Weekly, day(2027, 1, 1));
+        assert_eq!(
+            (new_year.key(), new_year.day),
+            ("2026-W53".into(), day(2026, 12, 28))
+        );
+        assert_eq!(new_year.modifier, Some(Modifier::Spaced));
+        let dense = ChallengeSpec::new(Kind::Weekly, day(2026, 10, 19));
+        assert_eq!(dense.modifier, Some(Modifier::Dense));
+        assert_eq!(dense.spec.params(), (24, 20, 106));
+    }
+
+    #[test]
+    fn every_rule_lays_its_own_board() {
+        let monday = day(2026, 10, 5);
+        for week in 0..4 {
+            let challenge = ChallengeSpec::new(Kind::Weekly, monday + Duration::weeks(week));
+            let race = challenge.race();
+            let board = challenge.options().build().unwrap();
+            assert_eq!(race.board(0).encode_layout(), board.encode_layout());
+            assert!(race.board(0).cell(12, 10).is_revealed());
+            assert!(race.label().ends_with(challenge.modifier.unwrap().name()));
+        }
+        let next = ChallengeSpec::new(Kind::Daily, day(2026, 10, 16));
+        assert_ne!(
+            next.seed,
+            ChallengeSpec::new(Kind::Daily, day(2026, 10, 15)).seed
+        );
+    }
+
+    #[test]
+    fn streaks_run_back_from_today() {
+        let mut challenges = Challenges::default();
+        let won = |challenges: &mut Challenges, d| {
+            challenges.record(
+                &ChallengeSpec::new(Kind::Daily, day(2026, 10, d)),
+                true,
+                50.0,
+            )
+        };
+        for d in [1, 2, 3, 5, 6] {
+            won(&mut challenges, d);
+        }
+        let lost = ChallengeSpec::new(Kind::Daily, day(2026, 10, 7));
+        challenges.record(&lost, false, 20.0);
+
+        // today's isn't won yet, which leaves yesterday's streak standing
+        assert_eq!(challenges.streak(Kind::Daily, day(2026, 10, 7)), 2);
+        assert_eq!(challenges.streak(Kind::Daily, day(2026, 10, 8)), 0);
+        assert_eq!(challenges.streak(Kind::Weekly, day(2026, 10, 7)), 0);
+        assert_eq!(challenges.longest_streak(Kind::Daily), 3);
+
+        challenges.record(&lost, true, 80.0);
+        challenges.record(&lost, true, 70.0);
+        let result = challenges.get(&lost).unwrap();
+        assert_eq!(
+            (result.tries, result.won, result.best),
+            (3, true, Some(70.0))
+        );
+        assert_eq!(challenges.streak(Kind::Daily, day(2026, 10, 8)), 3);
+
+        let mut elsewhere = Challenges::default();
+        elsewhere.record(
+            &ChallengeSpec::new(Kind::Daily, day(2026, 10, 4)),
+            true,
+            60.0,
+        );
+        challenges.merge(elsewhere);
+        assert_eq!(challenges.longest_streak(Kind::Daily), 7);
+    }
+}
@@ -0,0 +1,403 @@
+//! Release notes, from `changelog.json` built into the binary: a list of
+//! releases, each with a version, a date and entries filed under a
+//! category. The About screen lists them all; the menu shows the ones a
+//! profile hasn't seen yet, once, after an upgrade.
+//!
+//! The file is read forgivingly. A release with a version that doesn't
+//! parse is skipped, releases may come in any order and are sorted newest
+//! first, and a file that isn't a list at all leaves the notes empty with
+//! the error to show instead.
+
+use serde::Deserialize;
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::OnceLock;
+use tracing::warn;
+
+/// The version this build calls itself.
+pub const CURRENT: &str = env!("CARGO_PKG_VERSION");
+
+/// The notes built into the binary.
+const BUNDLED: &str = include_str!("changelog.json");
+
+/// A semantic version, `major.minor.patch` with an optional pre-release
+/// such as `-beta.2`. Build metadata after a `+` is dropped, as it plays
+/// no part in ordering.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+    pub pre: Vec<Identifier>, // empty for a release
+}
+
+/// One dot-separated part of a pre-release. Numbers sort below words and
+/// by value, words by their ASCII text.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Identifier {
+    Numeric(u64),
+    Alphanumeric(String),
+}
+
+impl Version {
+    /// This build's version.
+    pub fn current() -> Version {
+        CURRENT.parse().expect("Cargo.toml has a semantic version")
+    }
+}
+
+impl Ord for Version {
+    /// Semver precedence: numbers first, then a pre-release sorts below
+    /// the release it leads up to and pre-releases compare part by part.
+    fn cmp(&self, other: &Self) -> Ordering {
+        let core = |v: &Version| (v.major, v.minor, v.patch);
+        core(self).cmp(&core(other)).then_with(|| {
+            match (self.pre.is_empty(), other.pre.is_empty()) {
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                (false, false) => self.pre.cmp(&other.pre),
+            }
+        })
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        for (i, part) in self.pre.iter().enumerate() {
+            f.write_str(if i == 0 { "-" } else { "." })?;
+            match part {
+                Identifier::Numeric(n) => write!(f, "{n}")?,
+                Identifier::Alphanumeric(s) => f.write_str(s)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Text that isn't a semantic version.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VersionError(pub String);
+
+impl fmt::Display for VersionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} is not a version like 1.2.3", self.0)
+    }
+}
+
+impl std::error::Error for VersionError {}
+
+impl FromStr for Version {
+    type Err = VersionError;
+
+    /// `1.2.3`, `v1.2.3`, `1.2.3-rc.1` or `1.2.3+build`.
+    fn from_str(s: &str) -> Result<Version, VersionError> {
+        let error = || VersionError(s.to_string());
+        let text = s.trim();
+        let text = text.strip_prefix('v').unwrap_or(text);
+        let text = text.split_once('+').map_or(text, |(version, _)| version);
+        let (core, pre) = match text.split_once('-') {
+            Some((core, pre)) => (core, Some(pre)),
+            None => (text, None),
+        };
+        let numbers: Vec<u64> = core
+            .split('.')
+            .map(|part| number(part).ok_or_else(error))
+            .collect::<Result<_, _>>()?;
+        let &[major, minor, patch] = numbers.as_slice() else {
+            return Err(error());
+        };
+        let pre = match pre {
+            None => Vec::new(),
+            Some(pre) => pre
+                .split('.')
+                .map(|part| identifier(part).ok_or_else(error))
+                .collect::<Result<_, _>>()?,
+        };
+        Ok(Version {
+            major,
+            minor,
+            patch,
+            pre,
+        })
+    }
+}
+
+/// A version number: digits, with no leading zero unless it is 0.
+fn number(part: &str) -> Option<u64> {
+    let digits = !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+    if !digits || (part.len() > 1 && part.starts_with('0')) {
+        return None;
+    }
+    part.parse().ok()
+}
+
+fn identifier(part: &str) -> Option<Identifier> {
+    if part.is_empty() || !part.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-') {
+        return None;
+    }
+    if part.bytes().all(|b| b.is_ascii_digit()) {
+        number(part).map(Identifier::Numeric)
+    } else {
+        Some(Identifier::Alphanumeric(part.to_string()))
+    }
+}
+
+/// What kind of change an entry is. Categories the file names that this
+/// build doesn't know are shown as `Other`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Category {
+    Added,
+    Changed,
+    Fixed,
+    Removed,
+    #[serde(other)]
+    Other,
+}
+
+impl Category {
+    /// Every category, in the order a release lists them.
+    pub const ALL: [Category; 5] = [
+        Category::Added,
+        Category::Changed,
+        Category::Fixed,
+        Category::Removed,
+        Category::Other,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Category::Added => "Added",
+            Category::Changed => "Changed",
+            Category::Fixed => "Fixed",
+            Category::Removed => "Removed",
+            Category::Other => "Other",
+        }
+    }
+}
+
+/// One line of a release's notes.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct Entry {
+    pub category: Category,
+    pub text: String,
+}
+
+/// One release as the file has it, before its version is checked.
+#[derive(Deserialize)]
+struct RawRelease {
+    version: String,
+    #[serde(default)]
+    date: String,
+    #[serde(default)]
+    entries: Vec<Entry>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Release {
+    pub version: Version,
+    pub date: String, // as written, since it is only ever shown
+    pub entries: Vec<Entry>,
+}
+
+impl Release {
+    /// The entries under `category`, in file order.
+    pub fn in_category(&self, category: Category) -> impl Iterator<Item = &Entry> {
+        self.entries.iter().filter(move |e| e.category == category)
+    }
+}
+
+/// Every release that parsed, newest first, one per version.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Changelog {
+    pub releases: Vec<Release>,
+}
+
+impl Changelog {
+    /// Read a changelog, skipping releases that don't parse. Only text
+    /// that isn't a JSON list at all is an error.
+    pub fn parse(text: &str) -> Result<Changelog, serde_json::Error> {
+        let raw: Vec<serde_json::Value> = serde_json::from_str(text)?;
+        let mut releases: Vec<Release> = raw
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, value)| {
+                let raw = match serde_json::from_value::<RawRelease>(value) {
+                    Ok(raw) => raw,
+                    Err(err) => {
+                        warn!(%err, release = i, "skipping a malformed changelog release");
+                        return None;
+                    }
+                };
+                match raw.version.parse() {
+                    Ok(version) => Some(Release {
+                        version,
+                        date: raw.date,
+                        entries: raw.entries,
+                    }),
+                    Err(err) => {
+                        warn!(%err, release = i, "skipping a changelog release");
+                        None
+                    }
+                }
+            })
+            .collect();
+        // stable, so the first of two releases with one version is kept
+        releases.sort_by(|a, b| b.version.cmp(&a.version));
+        releases.dedup_by(|later, first| later.version == first.version);
+        Ok(Changelog { releases })
+    }
+
+    /// The releases to show someone running `current` who last saw the
+    /// notes for `seen`: those newer than `seen` up to `current`, newest
+    /// first. With nothing seen yet, just `current`'s own notes.
+    pub fn unseen(&self, seen: Option<&Version>, current: &Version) -> Vec<&Release> {
+        self.releases
+            .iter()
+            .filter(|r| &r.version <= current)
+            .filter(|r| match seen {
+                Some(seen) => &r.version > seen,
+                None => &r.version == current,
+            })
+            .collect()
+    }
+}
+
+/// The notes built into the binary, read once; the error says why there
+/// are none.
+pub fn bundled() -> Result<&'static Changelog, &'static str> {
+    static PARSED: OnceLock<Result<Changelog, String>> = OnceLock::new();
+    PARSED
+        .get_or_init(|| Changelog::parse(BUNDLED).map_err(|err| err.to_string()))
+        .as_ref()
+        .map_err(String::as_str)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn v(text: &str) -> Version {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn versions_order_by_semver_precedence() {
+        // the example from the semver spec, lowest first
+        let ordered = [
+            "1.0.0-alpha",
+            "1.0.0-alpha.1",
+            "1.0.0-alpha.beta",
+            "1.0.0-beta",
+            "1.0.0-beta.2",
+            "1.0.0-beta.11",
+            "1.0.0-rc.1",
+            "1.0.0",
+            "1.0.1",
+            "1.2.0",
+            "1.10.0",
+            "2.0.0",
+        ];
+        for pair in ordered.windows(2) {
+            assert!(v(pair[0]) < v(pair[1]), "{} < {}", pair[0], pair[1]);
+        }
+        assert_eq!(v("v1.2.3+build.7"), v("1.2.3"));
+        assert_eq!(v("1.0.0-rc.1").to_string(), "1.0.0-rc.1");
+    }
+
+    #[test]
+    fn bad_versions_are_refused() {
+        for bad in [
+            "",
+            "1",
+            "1.2",
+            "1.2.3.4",
+            "1.02.3",
+            "1.2.x",
+            "1.2.3-",
+            "1.2.3-a..b",
+            "-1.2.3",
+        ] {
+            assert_eq!(bad.parse::<Version>(), Err(VersionError(bad.to_string())));
+        }
+    }
+
+    #[test]
+    fn releases_are_sorted_and_bad_ones_skipped() {
+        let changelog = Changelog::parse(
+            r#"[
+                {"version": "0.9.0", "date": "2025-01-01",
+                 "entries": [{"category": "fixed", "text": "old"}]},
+                {"version": "not a version", "entries": []},
+                {"version": "1.1.0", "entries": [{"category": "security", "text": "?"}]},
+                {"entries": []},
+                {"version": "1.0.0", "entries": "should be a list"},
+                {"version": "1.0.0", "date": "2025-06-01"},
+                {"version": "1.0.0", "date": "duplicate"},
+                42
+            ]"#,
+        )
+        .unwrap();
+        let versions: Vec<String> = changelog
+            .releases
+            .iter()
+            .map(|r| r.version.to_string())
+            .collect();
+        assert_eq!(versions, ["1.1.0", "1.0.0", "0.9.0"]);
+        assert_eq!(changelog.releases[1].date, "2025-06-01");
+        assert_eq!(changelog.releases[0].entries[0].category, Category::Other);
+        assert_eq!(
+            changelog.releases[2].in_category(Category::Fixed).count(),
+            1
+        );
+    }
+
+    #[test]
+    fn a_file_that_is_not_a_list_is_an_error() {
+        for bad in ["", "{", "{\"version\": \"1.0.0\"}", "[1, 2"] {
+            assert!(Changelog::parse(bad).is_err(), "{bad:?}");
+        }
+        assert_eq!(Changelog::parse("[]").unwrap(), Changelog::default());
+    }
+
+    #[test]
+    fn unseen_covers_the_releases_since_the_last_one_seen() {
+        let changelog = Changelog::parse(
+            r#"[{"version": "1.0.0"}, {"version": "1.1.0"}, {"version": "1.2.0-beta.1"},
+                {"version": "1.2.0"}, {"version": "2.0.0"}]"#,
+        )
+        .unwrap();
+        let unseen = |seen: Option<&str>, current: &str| -> Vec<String> {
+            changelog
+                .unseen(seen.map(v).as_ref(), &v(current))
+                .iter()
+                .map(|r| r.version.to_string())
+                .collect()
+        };
+        assert_eq!(
+            unseen(Some("1.0.0"), "1.2.0"),
+            ["1.2.0", "1.2.0-beta.1", "1.1.0"]
+        );
+        assert_eq!(unseen(Some("1.2.0"), "1.2.0"), Vec::<String>::new());
+        assert_eq!(unseen(None, "1.1.0"), ["1.1.0"]);
+        // a build newer than any notes, or one older than the last seen
+        assert_eq!(unseen(None, "3.0.0"), Vec::<String>::new());
+        assert_eq!(unseen(Some("2.0.0"), "1.2.0"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn the_bundled_notes_parse_and_cover_this_build() {
+        let changelog = bundled().unwrap();
+        assert_eq!(changelog.releases[0].version, Version::current());
+        assert!(changelog.releases.iter().all(|r| !r.entries.is_empty()));
+    }
+}
@@ -0,0 +1,63 @@
+//! How the subcommands fail: one line on stderr and an exit code that says
+//! what went wrong, the same way for `race`, `audit`, `simulate`, `verify`
+//! and the rest, so scripts can tell a bad command line from a bad file.
+
+use std::fmt;
+
+/// Why a subcommand stopped, and so its exit code: 1 for a check that ran
+/// and failed (a result that doesn't verify), 2 for a command line that
+/// doesn't make sense, 3 for an input file that can't be read or isn't
+/// what it should be, and 4 for output that can't be written.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CliError {
+    Failed(String),
+    Usage(String),
+    Input(String),
+    Output(String),
+}
+
+impl CliError {
+    pub fn code(&self) -> i32 {
+        match self {
+            CliError::Failed(_) => 1,
+            CliError::Usage(_) => 2,
+            CliError::Input(_) => 3,
+            CliError::Output(_) => 4,
+        }
+    }
+
+    /// Report the error on stderr and end the process with its code.
+    pub fn exit(&self) -> ! {
+        eprintln!("error: {self}");
+        std::process::exit(self.code())
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (CliError::Failed(message)
+        | CliError::Usage(message)
+        | CliError::Input(message)
+        | CliError::Output(message)) = self;
+        f.write_str(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_kind_of_failure_has_its_own_code() {
+        let errors = [
+            CliError::Failed(String::new()),
+            CliError::Usage(String::new()),
+            CliError::Input(String::new()),
+            CliError::Output(String::new()),
+        ];
+        let mut codes: Vec<i32> = errors.iter().map(CliError::code).collect();
+        codes.dedup();
+        assert_eq!(codes.len(), errors.len());
+        assert!(!codes.contains(&0));
+    }
+}
@@ -0,0 +1,182 @@
+//! The game clock, and what taking a move back does to it.
+//!
+//! An undo never winds the clock back. The time spent on a move that was
+//! taken back was still spent, and giving it back would let a player buy
+//! time by undoing. A game with an undo in it still shows its time, but
+//! `used_undo` keeps it out of the best times and off the leaderboard.
+//!
+//! The one exception is an undo back past the first reveal, to before any
+//! cell was open. The next click deals the mines afresh, so the game
+//! starts over: the clock goes back to zero and waits for that click, and
+//! the latch is cleared along with it.
+//!
+//! The clock stops while the player takes notes, and an undo leaves that
+//! as it is: undoing while paused stays paused. Undoing the move that
+//! ended a game brings the game back, and the clock carries on from the
+//! time it ended at.
+//!
+//! The clock runs on frame times, which only ever go forward, never the
+//! wall clock, so setting the time or a change of time zone or summer
+//! time leaves it alone. A gap between frames longer than [`SLEEP_SECS`]
+//! while the window is up is the machine sleeping, not the player
+//! thinking (a game in progress asks for a frame every second), so none
+//! of it counts and the game pauses until the player is back. Dates, for
+//! the history and the daily challenges, come from a [`Calendar`], which
+//! tests set by hand.
+
+use chrono::{DateTime, FixedOffset, NaiveDate};
+
+/// The longest gap between frames, in seconds, counted as play while the
+/// window is up; anything longer and the machine slept.
+pub const SLEEP_SECS: f64 = 5.0;
+/// How often a game in progress asks for a frame, so gaps between them
+/// stay well under `SLEEP_SECS` with the player sat still.
+pub const TICK_SECS: f64 = 1.0;
+/// The longest step, in seconds, an animation takes in one frame.
+pub const MAX_FRAME_DT: f32 = 0.1;
+
+/// Time played on the current board.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Clock {
+    pub secs: f64,
+    pub used_undo: bool, // a move was taken back since the first reveal
+}
+
+impl Clock {
+    /// Note an undo, `to_start` if it went back past the first reveal.
+    pub fn undo(&mut self, to_start: bool) {
+        if to_start {
+            *self = Clock::default();
+        } else {
+            self.used_undo = true;
+        }
+    }
+
+    /// Whether a win in this time may be a best time.
+    pub fn counts_for_bests(&self) -> bool {
+        !self.used_undo
+    }
+}
+
+/// What the gap between frames at `last` and `now` adds to the clock:
+/// `None` if it is so long the machine must have slept, and nothing if
+/// the time went backwards. A minimized window skips frames on purpose, so
+/// all of its gaps count.
+pub fn frame_gap(last: f64, now: f64, minimized: bool) -> Option<f64> {
+    let gap = now - last;
+    if !gap.is_finite() || gap <= 0.0 {
+        return Some(0.0);
+    }
+    (minimized || gap <= SLEEP_SECS).then_some(gap)
+}
+
+/// A frame's `dt` as an animation should step by: never backwards and
+/// never more than [`MAX_FRAME_DT`], however long the frame took.
+pub fn animation_dt(dt: f32) -> f32 {
+    if dt.is_finite() {
+        dt.clamp(0.0, MAX_FRAME_DT)
+    } else {
+        0.0
+    }
+}
+
+/// Where today's date comes from.
+pub trait Calendar {
+    /// The local time now, at the offset in force.
+    fn now(&self) -> DateTime<FixedOffset>;
+
+    /// The local date now.
+    fn today(&self) -> NaiveDate {
+        self.now().date_naive()
+    }
+}
+
+/// The system's own clock and time zone.
+pub struct SystemCalendar;
+
+impl Calendar for SystemCalendar {
+    fn now(&self) -> DateTime<FixedOffset> {
+        chrono::Local::now().into()
+    }
+}
+
+/// A calendar that shows whatever it was last set to, for tests.
+#[cfg(test)]
+pub struct SetCalendar(pub std::cell::Cell<DateTime<FixedOffset>>);
+
+#[cfg(test)]
+impl SetCalendar {
+    pub fn at(time: &str) -> Self {
+        SetCalendar(std::cell::Cell::new(parse(time)))
+    }
+
+    pub fn set(&self, time: &str) {
+        self.0.set(parse(time));
+    }
+}
+
+#[cfg(test)]
+fn parse(time: &str) -> DateTime<FixedOffset> {
+    DateTime::parse_from_rfc3339(time).expect("an RFC 3339 time")
+}
+
+#[cfg(test)]
+impl Calendar for SetCalendar {
+    fn now(&self) -> DateTime<FixedOffset> {
+        self.0.get()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_close_together_count_in_full() {
+        assert_eq!(frame_gap(10.0, 10.5, false), Some(0.5));
+        assert_eq!(frame_gap(10.0, 10.0 + SLEEP_SECS, false), Some(SLEEP_SECS));
+    }
+
+    #[test]
+    fn a_long_gap_with_the_window_up_is_a_sleep() {
+        assert_eq!(frame_gap(10.0, 3610.0, false), None);
+        // minimized, nobody is looking, so it was all play
+        assert_eq!(frame_gap(10.0, 3610.0, true), Some(3600.0));
+    }
+
+    #[test]
+    fn time_going_backwards_counts_nothing() {
+        assert_eq!(frame_gap(100.0, 40.0, false), Some(0.0));
+        assert_eq!(frame_gap(100.0, 40.0, true), Some(0.0));
+        assert_eq!(frame_gap(100.0, f64::NAN, false), Some(0.0));
+    }
+
+    #[test]
+    fn animations_never_step_far_or_backwards() {
+        assert_eq!(animation_dt(0.016), 0.016);
+        assert_eq!(animation_dt(3600.0), MAX_FRAME_DT);
+        assert_eq!(animation_dt(-1.0), 0.0);
+        assert_eq!(animation_dt(f32::INFINITY), 0.0);
+    }
+
+    #[test]
+    fn the_date_is_the_local_one_across_summer_time() {
+        // the night the clocks go forward in central Europe
+        let calendar = SetCalendar::at("2026-03-29T01:59:00+01:00");
+        assert_eq!(
+            calendar.today(),
+            NaiveDate::from_ymd_opt(2026, 3, 29).unwrap()
+        );
+        calendar.set("2026-03-29T03:00:00+02:00");
+        assert_eq!(
+            calendar.today(),
+            NaiveDate::from_ymd_opt(2026, 3, 29).unwrap()
+        );
+        // and back: 00:30 in summer time is still the day before in UTC
+        calendar.set("2026-10-25T00:30:00+02:00");
+        assert_eq!(
+            calendar.today(),
+            NaiveDate::from_ymd_opt(2026, 10, 25).unwrap()
+        );
+    }
+}
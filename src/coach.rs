@@ -0,0 +1,254 @@
+//! Help for players who want to improve: "Watch & learn", a bot that plays
+//! the current game slowly, saying why before each move, and difficulty
+//! suggestions from recent results (see `advice`).
+//!
+//! The bot's moves come straight from the solver, so every rationale names
+//! the revealed numbers the deduction read; when nothing is certain it
+//! takes the guess with the lowest mine chance and says so.
+
+use crate::widgets::CellClick;
+use minesweeper_rs::render;
+use minesweeper_rs::solver::{self, Reason};
+use minesweeper_rs::Board;
+
+mod advice;
+
+pub use advice::{Advisor, Suggestion};
+
+/// Seconds a rationale shows before the bot plays it.
+const MOVE_SECS: f64 = 1.5;
+
+/// The bot's next move and the explanation shown before it is played.
+pub struct Plan {
+    pub click: CellClick,
+    pub target: (usize, usize),
+    pub numbers: Vec<(usize, usize)>, // revealed numbers the rationale reads
+    pub why: String,
+}
+
+/// A bot watching over the current game.
+#[derive(Default)]
+pub struct Coach {
+    pub paused: bool,
+    pub plan: Option<Plan>, // shown until it is played
+    due: f64,               // egui time the plan is played
+}
+
+impl Coach {
+    /// The move to play now, if any. A new plan is shown for `MOVE_SECS`
+    /// first, or until `step` plays it early, paused or not.
+    pub fn next_move(
+        &mut self,
+        board: &Board,
+        first_click: bool,
+        now: f64,
+        step: bool,
+    ) -> Option<CellClick> {
+        if self.plan.is_none() {
+            self.plan = plan(board, first_click);
+            self.due = now + MOVE_SECS;
+        }
+        let ready = step || (!self.paused && now >= self.due);
+        if ready {
+            self.plan.take().map(|plan| plan.click)
+        } else {
+            None
+        }
+    }
+
+    /// Seconds until the plan is played, to schedule a repaint.
+    pub fn wait(&self, now: f64) -> Option<f64> {
+        (!self.paused && self.plan.is_some()).then(|| (self.due - now).max(0.0))
+    }
+}
+
+/// What the bot would do next on `board`: flag a proven mine, clear a
+/// proven-safe cell (taking a wrong flag off it first), or failing both,
+/// reveal the cell least likely to be a mine.
+pub fn plan(board: &Board, first_click: bool) -> Option<Plan> {
+    if board.is_over() {
+        return None;
+    }
+    if first_click {
+        let target = (board.width() / 2, board.height() / 2);
+        return Some(Plan {
+            click: CellClick::Reveal(target.0, target.1),
+            target,
+            numbers: Vec::new(),
+            why: "Nothing is showing yet, and the first click is always safe, \
+                  so open the middle."
+                .to_string(),
+        });
+    }
+
+    let analysis = solver::analyze(board);
+    let deduced = |target: (usize, usize), click: CellClick, mine: bool| {
+        let reason = analysis.reason(target.0, target.1)?;
+        Some(Plan {
+            click,
+            target,
+            numbers: reason.numbers(),
+            why: explain(board, reason, target, mine),
+        })
+    };
+
+    if board.flags_left() != Some(0) {
+        let mine = analysis
+            .mines()
+            .iter()
+            .find(|&&(x, y)| !board.cell(x, y).is_flagged());
+        if let Some(&(x, y)) = mine {
+            return deduced((x, y), CellClick::Flag(x, y), true);
+        }
+    }
+    if let Some(&(x, y)) = analysis.safe().first() {
+        let mut plan = deduced((x, y), CellClick::Reveal(x, y), false)?;
+        if board.cell(x, y).is_flagged() {
+            plan.click = CellClick::Flag(x, y);
+            plan.why
+                .push_str(" Its flag is wrong, so take it off first.");
+        }
+        return Some(plan);
+    }
+
+    let hidden = (0..board.height())
+        .flat_map(|y| (0..board.width()).map(move |x| (x, y)))
+        .filter(|&(x, y)| {
+            let cell = board.cell(x, y);
+            !cell.is_revealed() && !cell.is_flagged()
+        });
+    let (x, y) = hidden.min_by(|&(ax, ay), &(bx, by)| {
+        analysis
+            .probability(ax, ay)
+            .total_cmp(&analysis.probability(bx, by))
+    })?;
+    Some(Plan {
+        click: CellClick::Reveal(x, y),
+        target: (x, y),
+        numbers: Vec::new(),
+        why: format!(
+            "Nothing is certain, so take the safest guess: {} is a mine {:.0}% of the time.",
+            cell_name((x, y)),
+            analysis.probability(x, y) * 100.0
+        ),
+    })
+}
+
+/// One sentence on why `reason` makes `target` a mine or safe.
+pub fn explain(board: &Board, reason: Reason, target: (usize, usize), mine: bool) -> String {
+    let number = |at: (usize, usize)| {
+        let n = board.cell(at.0, at.1).neighbor_mines().unwrap_or(0);
+        format!("the {n} at {}", cell_name(at))
+    };
+    let target = cell_name(target);
+    match (reason, mine) {
+        (Reason::Satisfied(at), _) => {
+            let n = board.cell(at.0, at.1).neighbor_mines().unwrap_or(0);
+            let mines = if n == 1 { "mine" } else { "mines" };
+            format!(
+                "{} already touches {n} known {mines}, so its other hidden neighbours, \
+                 {target} included, are safe.",
+                capitalize(&number(at))
+            )
+        }
+        (Reason::Filled(at), _) => format!(
+            "{} has only as many hidden neighbours as mines it still needs, \
+             so {target} is a mine.",
+            capitalize(&number(at))
+        ),
+        (Reason::Subset { inner, outer }, false) => format!(
+            "Every hidden neighbour of {} also touches {}, and both still need \
+             the same number of mines, so {target} is safe.",
+            number(inner),
+            number(outer)
+        ),
+        (Reason::Subset { inner, outer }, true) => format!(
+            "Every hidden neighbour of {} also touches {}, which needs one more \
+             mine for each of its other hidden neighbours, so {target} is a mine.",
+            number(inner),
+            number(outer)
+        ),
+        (Reason::MineCount, false) => {
+            format!("Every mine is accounted for, so {target} is safe.")
+        }
+        (Reason::MineCount, true) => {
+            format!("The hidden cells left are exactly the mines left, so {target} is a mine.")
+        }
+    }
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    chars
+        .next()
+        .map_or(String::new(), |c| c.to_uppercase().chain(chars).collect())
+}
+
+/// Spreadsheet-style name for a cell, as in the copied coordinates
+/// grid: columns A, B, … from the left, rows from 1 at the top.
+pub fn cell_name((x, y): (usize, usize)) -> String {
+    format!("{}{}", render::column_name(x), y + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cells_are_named_like_a_spreadsheet() {
+        assert_eq!(cell_name((0, 0)), "A1");
+        assert_eq!(cell_name((3, 3)), "D4");
+        assert_eq!(cell_name((25, 9)), "Z10");
+        assert_eq!(cell_name((26, 0)), "AA1");
+        assert_eq!(cell_name((27, 0)), "AB1");
+    }
+
+    /// Mines at A1 and C1, and everything but A1 to C1 opened from G3:
+    /// the 1 at D1 proves C1, which then clears B1.
+    fn position() -> Board {
+        let mut board = Board::decode_layout("*.*....\n.......\n.......\n").unwrap();
+        board.reveal_cell(6, 2);
+        board
+    }
+
+    #[test]
+    fn proven_mines_are_flagged_before_safe_cells_open() {
+        let plan = plan(&position(), false).unwrap();
+        assert_eq!(plan.click, CellClick::Flag(2, 0));
+        assert_eq!(plan.numbers, [(3, 0)]);
+        assert!(plan.why.starts_with("The 1 at D1 "), "{}", plan.why);
+    }
+
+    #[test]
+    fn wrong_flags_come_off_first() {
+        let mut board = position();
+        board.toggle_flag(2, 0);
+        board.toggle_flag(1, 0);
+        let plan = plan(&board, false).unwrap();
+        assert_eq!(plan.click, CellClick::Flag(1, 0));
+        assert_eq!(plan.numbers, [(2, 1)]);
+        assert!(plan.why.contains("B1 included"), "{}", plan.why);
+        assert!(plan.why.ends_with("take it off first."));
+    }
+
+    #[test]
+    fn moves_wait_for_their_rationale_unless_stepped() {
+        let board = Board::decode_layout("*..\n...\n..*\n").unwrap();
+        let mut coach = Coach::default();
+        assert_eq!(coach.next_move(&board, true, 0.0, false), None);
+        assert!(coach.plan.is_some());
+        assert_eq!(coach.wait(1.0), Some(MOVE_SECS - 1.0));
+        assert_eq!(
+            coach.next_move(&board, true, MOVE_SECS, false),
+            Some(CellClick::Reveal(1, 1))
+        );
+
+        coach.paused = true;
+        assert_eq!(coach.next_move(&board, true, 10.0, false), None);
+        assert_eq!(coach.wait(10.0), None);
+        assert_eq!(
+            coach.next_move(&board, true, 10.0, true),
+            Some(CellClick::Reveal(1, 1))
+        );
+    }
+}
@@ -0,0 +1,233 @@
+//! Difficulty suggestions from recent results: a step up after a run of
+//! wins, an easier option after a losing streak.
+//!
+//! Only games at the level being played count. A suggestion appears at a
+//! strict threshold and is withdrawn at a looser one, so a single game
+//! either way doesn't make it come and go; once dismissed it stays hidden
+//! until it is withdrawn and earned again.
+
+use crate::history::Outcome;
+use minesweeper_rs::Difficulty;
+use std::collections::VecDeque;
+
+/// Recent games a step up is judged on.
+const WINDOW: usize = 10;
+/// Wins in the window that earn a step up...
+const STEP_UP_WINS: usize = 8;
+/// ...and that keep it on offer once it is.
+const KEEP_UP_WINS: usize = 6;
+/// Losses in a row that earn an easier option. It stays until a win.
+const LOSING_STREAK: usize = 5;
+
+/// A change of difficulty worth offering.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Suggestion {
+    /// Winning comfortably: try this harder preset.
+    StepUp(Difficulty),
+    /// Losing a lot: this easier preset, no-guess boards, or both.
+    Easier {
+        down: Option<Difficulty>,
+        no_guess: bool,
+    },
+}
+
+/// Tracks the suggestion on offer between games.
+#[derive(Default)]
+pub struct Advisor {
+    current: Option<Suggestion>,
+    level: Option<Difficulty>, // the difficulty `current` was made for
+    dismissed: bool,
+}
+
+impl Advisor {
+    /// Reconsider after a game at `difficulty`. `recent` is newest last;
+    /// `no_guess_offer` says whether no-guess boards are available and
+    /// not already on.
+    pub fn update(
+        &mut self,
+        recent: &VecDeque<Outcome>,
+        difficulty: Difficulty,
+        no_guess_offer: bool,
+    ) {
+        let params = difficulty.spec().params();
+        let results: Vec<bool> = recent
+            .iter()
+            .rev()
+            .filter(|o| o.size == params)
+            .map(|o| o.won)
+            .collect();
+        let wins =
+            (results.len() >= WINDOW).then(|| results[..WINDOW].iter().filter(|&&w| w).count());
+        let streak = results.iter().take_while(|&&won| !won).count();
+
+        let level = Difficulty::PRESETS.iter().position(|&d| d == difficulty);
+        let harder = level.and_then(|i| Difficulty::PRESETS.get(i + 1).copied());
+        let easier = level
+            .and_then(|i| i.checked_sub(1))
+            .map(|i| Difficulty::PRESETS[i]);
+
+        let kept = match self.current {
+            _ if self.level != Some(difficulty) => None,
+            Some(up @ Suggestion::StepUp(_)) => {
+                wins.is_some_and(|w| w >= KEEP_UP_WINS).then_some(up)
+            }
+            Some(Suggestion::Easier { .. }) if streak > 0 => {
+                // refreshed, in case no-guess was switched on meanwhile
+                easier_options(easier, no_guess_offer)
+            }
+            _ => None,
+        };
+        self.current = kept.or_else(|| {
+            // anything from here on is new, so it shows even if the last was dismissed
+            self.dismissed = false;
+            if wins.is_some_and(|w| w >= STEP_UP_WINS) {
+                harder.map(Suggestion::StepUp)
+            } else if streak >= LOSING_STREAK && level.is_some() {
+                easier_options(easier, no_guess_offer)
+            } else {
+                None
+            }
+        });
+        self.level = Some(difficulty);
+    }
+
+    /// The suggestion to show, unless it was dismissed.
+    pub fn shown(&self) -> Option<Suggestion> {
+        self.current.filter(|_| !self.dismissed)
+    }
+
+    /// Hide the suggestion until it is withdrawn and earned again.
+    pub fn dismiss(&mut self) {
+        self.dismissed = true;
+    }
+}
+
+fn easier_options(down: Option<Difficulty>, no_guess: bool) -> Option<Suggestion> {
+    (down.is_some() || no_guess).then_some(Suggestion::Easier { down, no_guess })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Play `games` ("W" or "L" each) at `difficulty`.
+    fn play(
+        advisor: &mut Advisor,
+        recent: &mut VecDeque<Outcome>,
+        difficulty: Difficulty,
+        games: &str,
+    ) {
+        for game in games.chars() {
+            recent.push_back(Outcome {
+                size: difficulty.spec().params(),
+                won: game == 'W',
+            });
+            advisor.update(recent, difficulty, true);
+        }
+    }
+
+    #[test]
+    fn eight_wins_in_ten_suggest_a_step_up() {
+        let (mut advisor, mut recent) = (Advisor::default(), VecDeque::new());
+        play(&mut advisor, &mut recent, Difficulty::Beginner, "LLWWWWWWW");
+        assert_eq!(advisor.shown(), None); // 7 of 9: not enough games yet
+        play(&mut advisor, &mut recent, Difficulty::Beginner, "W");
+        assert_eq!(
+            advisor.shown(),
+            Some(Suggestion::StepUp(Difficulty::Intermediate))
+        );
+    }
+
+    #[test]
+    fn a_step_up_survives_a_loss_or_two() {
+        let (mut advisor, mut recent) = (Advisor::default(), VecDeque::new());
+        play(
+            &mut advisor,
+            &mut recent,
+            Difficulty::Beginner,
+            "WWWWWWWWWW",
+        );
+        play(&mut advisor, &mut recent, Difficulty::Beginner, "LLLLW");
+        // 6 of the last 10 still won
+        assert!(advisor.shown().is_some());
+        play(&mut advisor, &mut recent, Difficulty::Beginner, "L");
+        assert_eq!(advisor.shown(), None);
+        // and it takes 8 of 10 again to come back
+        play(&mut advisor, &mut recent, Difficulty::Beginner, "WW");
+        assert_eq!(advisor.shown(), None);
+    }
+
+    #[test]
+    fn other_levels_dont_count() {
+        let (mut advisor, mut recent) = (Advisor::default(), VecDeque::new());
+        play(&mut advisor, &mut recent, Difficulty::Beginner, "WWWWW");
+        play(&mut advisor, &mut recent, Difficulty::Expert, "LLLL");
+        play(&mut advisor, &mut recent, Difficulty::Beginner, "WWWW");
+        assert_eq!(advisor.shown(), None);
+        play(&mut advisor, &mut recent, Difficulty::Beginner, "W");
+        assert!(advisor.shown().is_some());
+        // switching level withdraws it
+        play(&mut advisor, &mut recent, Difficulty::Intermediate, "W");
+        assert_eq!(advisor.shown(), None);
+    }
+
+    #[test]
+    fn expert_has_nowhere_up() {
+        let (mut advisor, mut recent) = (Advisor::default(), VecDeque::new());
+        play(&mut advisor, &mut recent, Difficulty::Expert, "WWWWWWWWWW");
+        assert_eq!(advisor.shown(), None);
+    }
+
+    #[test]
+    fn losing_streaks_offer_something_easier_until_a_win() {
+        let (mut advisor, mut recent) = (Advisor::default(), VecDeque::new());
+        play(&mut advisor, &mut recent, Difficulty::Intermediate, "LLLL");
+        assert_eq!(advisor.shown(), None);
+        play(&mut advisor, &mut recent, Difficulty::Intermediate, "L");
+        let easier = Suggestion::Easier {
+            down: Some(Difficulty::Beginner),
+            no_guess: true,
+        };
+        assert_eq!(advisor.shown(), Some(easier));
+        play(&mut advisor, &mut recent, Difficulty::Intermediate, "LL");
+        assert_eq!(advisor.shown(), Some(easier));
+        play(&mut advisor, &mut recent, Difficulty::Intermediate, "W");
+        assert_eq!(advisor.shown(), None);
+        play(&mut advisor, &mut recent, Difficulty::Intermediate, "L");
+        assert_eq!(advisor.shown(), None);
+    }
+
+    #[test]
+    fn beginner_can_only_offer_no_guess() {
+        let (mut advisor, mut recent) = (Advisor::default(), VecDeque::new());
+        play(&mut advisor, &mut recent, Difficulty::Beginner, "LLLLL");
+        assert_eq!(
+            advisor.shown(),
+            Some(Suggestion::Easier {
+                down: None,
+                no_guess: true
+            })
+        );
+
+        // with no-guess already on there's nothing left to offer
+        recent.push_back(Outcome {
+            size: Difficulty::Beginner.spec().params(),
+            won: false,
+        });
+        advisor.update(&recent, Difficulty::Beginner, false);
+        assert_eq!(advisor.shown(), None);
+    }
+
+    #[test]
+    fn dismissed_suggestions_stay_hidden_until_earned_again() {
+        let (mut advisor, mut recent) = (Advisor::default(), VecDeque::new());
+        play(&mut advisor, &mut recent, Difficulty::Intermediate, "LLLLL");
+        advisor.dismiss();
+        play(&mut advisor, &mut recent, Difficulty::Intermediate, "LLL");
+        assert_eq!(advisor.shown(), None);
+        play(&mut advisor, &mut recent, Difficulty::Intermediate, "WLLLL");
+        assert_eq!(advisor.shown(), None);
+        play(&mut advisor, &mut recent, Difficulty::Intermediate, "L");
+        assert!(advisor.shown().is_some());
+    }
+}
@@ -0,0 +1,277 @@
+//! Crash reports, kept on this machine. With "Save crash reports" on in
+//! Settings → Advanced (it starts off), a panic writes a report to the
+//! `crashes` folder of the data folder: the panic and where it happened, a
+//! backtrace, the version and OS, and the debug snapshot of the board in
+//! play, which leaves the mines out while the game goes on. The next run
+//! offers to open the folder or copy the report, once per report. Nothing
+//! is sent anywhere.
+//!
+//! The hook runs with the program in an unknown state, so it takes only
+//! what it can get without waiting or failing: the board is the snapshot
+//! last noted, its lock is only tried, and every write error is passed
+//! over. A data folder that can't be written falls back to the temp dir.
+
+use minesweeper_rs::report::DebugSnapshot;
+use minesweeper_rs::Board;
+use std::backtrace::Backtrace;
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock, TryLockError};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Version of the report format, on its first line.
+const FORMAT_VERSION: u32 = 1;
+
+/// Boards bigger than this are only described, as writing one out after
+/// every move would slow the mega boards down.
+const MAX_CELLS: usize = 100_000;
+
+/// Names the newest report offered, in each crashes folder.
+const OFFERED: &str = "offered";
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static DIRS: OnceLock<Vec<PathBuf>> = OnceLock::new(); // crashes folders, best first
+static BOARD: Mutex<String> = Mutex::new(String::new()); // snapshot of the board in play
+
+/// The crashes folders for the data folder `data_dir`, in the order they
+/// are tried: its own, then one in the temp dir.
+pub fn dirs(data_dir: Option<&Path>) -> Vec<PathBuf> {
+    let temp = std::env::temp_dir().join("bosnia-simulator");
+    data_dir
+        .into_iter()
+        .chain([temp.as_path()])
+        .map(|dir| dir.join("crashes"))
+        .collect()
+}
+
+/// Set the hook up to write reports to the first of `dirs` that takes
+/// them, once reports are turned on. Panics still print as before.
+pub fn install(dirs: Vec<PathBuf>) {
+    let _ = DIRS.set(dirs);
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        if ENABLED.load(Ordering::Relaxed) {
+            let report = report(info);
+            if let Some(path) = write(DIRS.get().map_or(&[], Vec::as_slice), &report) {
+                let _ = writeln!(
+                    std::io::stderr(),
+                    "crash report saved to {}",
+                    path.display()
+                );
+            }
+        }
+        previous(info);
+    }));
+}
+
+/// Turn reports on or off, as the setting says.
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Keep `board` for a report, if reports are on.
+pub fn note_board(board: &Board) {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    let snapshot = if board.width() * board.height() > MAX_CELLS {
+        format!(
+            "a {}x{} board, too big to include\n",
+            board.width(),
+            board.height()
+        )
+    } else {
+        DebugSnapshot::new(board).to_string()
+    };
+    if let Ok(mut noted) = BOARD.lock() {
+        *noted = snapshot;
+    }
+}
+
+/// The report for the panic `info`.
+fn report(info: &std::panic::PanicHookInfo<'_>) -> String {
+    let payload = info.payload();
+    let message = payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("(not a string)");
+    let board = match BOARD.try_lock() {
+        Ok(board) => board.clone(),
+        Err(TryLockError::Poisoned(poisoned)) => poisoned.into_inner().clone(),
+        Err(TryLockError::WouldBlock) => String::new(),
+    };
+    let mut out = String::new();
+    let _ = writeln!(out, "minesweeper-rs crash report v{FORMAT_VERSION}");
+    let _ = writeln!(out, "version: {}", env!("CARGO_PKG_VERSION"));
+    let os = std::env::consts::OS;
+    let _ = writeln!(out, "os: {os} {}", std::env::consts::ARCH);
+    let _ = writeln!(out, "time: {}", unix_secs());
+    let thread = std::thread::current();
+    let _ = writeln!(out, "thread: {}", thread.name().unwrap_or("unnamed"));
+    let _ = writeln!(out, "panic: {message}");
+    match info.location() {
+        Some(at) => {
+            let _ = writeln!(out, "at: {}:{}:{}", at.file(), at.line(), at.column());
+        }
+        None => out.push_str("at: unknown\n"),
+    }
+    let _ = write!(out, "backtrace:\n{}\n", Backtrace::force_capture());
+    if board.is_empty() {
+        out.push_str("board: none\n");
+    } else {
+        let _ = write!(out, "{board}");
+    }
+    out
+}
+
+/// Seconds since the epoch, or 0 on a clock set before it.
+fn unix_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs())
+}
+
+/// Save `report` to the first of `dirs` that takes it, returning where.
+fn write(dirs: &[PathBuf], report: &str) -> Option<PathBuf> {
+    let name = format!("crash-{:010}-{}.txt", unix_secs(), std::process::id());
+    dirs.iter().find_map(|dir| {
+        let path = dir.join(&name);
+        std::fs::create_dir_all(dir).ok()?;
+        std::fs::write(&path, report).ok()?;
+        Some(path)
+    })
+}
+
+/// The newest report in `dirs` that hasn't been offered yet, marked as
+/// offered now so that it isn't again.
+pub fn take_unoffered(dirs: &[PathBuf]) -> Option<PathBuf> {
+    let mut newest: Option<(String, &Path)> = None;
+    for dir in dirs {
+        let offered = std::fs::read_to_string(dir.join(OFFERED)).unwrap_or_default();
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            continue;
+        };
+        for name in entries
+            .flatten()
+            .filter_map(|e| e.file_name().into_string().ok())
+        {
+            let report = name.starts_with("crash-") && name.ends_with(".txt");
+            let newer = newest.as_ref().is_none_or(|(best, _)| name > *best);
+            if report && name > offered && newer {
+                newest = Some((name, dir));
+            }
+        }
+    }
+    let (name, dir) = newest?;
+    if let Err(err) = std::fs::write(dir.join(OFFERED), &name) {
+        tracing::warn!(%err, "can't note the crash report as offered");
+    }
+    Some(dir.join(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    /// Set in the child process `a_panic_leaves_a_report_behind` starts.
+    const CHILD: &str = "MINESWEEPER_CRASH_CHILD";
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir()
+            .join(format!("minesweeper-crash-{}", std::process::id()))
+            .join(name);
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn a_panic_leaves_a_report_behind() {
+        if let Some(dir) = std::env::var_os(CHILD) {
+            install(vec![PathBuf::from(dir)]);
+            set_enabled(true);
+            let mut board = Board::decode_layout("*..\n...\n").unwrap();
+            board.reveal_cell(2, 1);
+            note_board(&board);
+            panic!("deliberate");
+        }
+        let dir = temp_dir("child");
+        let output = Command::new(std::env::current_exe().unwrap())
+            .args(["--exact", "crash::tests::a_panic_leaves_a_report_behind"])
+            .env(CHILD, &dir)
+            .output()
+            .unwrap();
+        assert!(!output.status.success());
+
+        let mut reports = std::fs::read_dir(&dir).unwrap().flatten();
+        let report = std::fs::read_to_string(reports.next().unwrap().path()).unwrap();
+        assert!(reports.next().is_none());
+        let keys: Vec<_> = report
+            .lines()
+            .take(7)
+            .map(|line| line.split(':').next().unwrap())
+            .collect();
+        assert_eq!(
+            keys,
+            [
+                "minesweeper-rs crash report v1",
+                "version",
+                "os",
+                "time",
+                "thread",
+                "panic",
+                "at"
+            ]
+        );
+        assert!(report.contains("\npanic: deliberate\n"), "{report}");
+        let at = report
+            .lines()
+            .find(|line| line.starts_with("at: "))
+            .unwrap();
+        assert!(at.contains("crash.rs:"), "{at}");
+        assert!(report.contains("\nbacktrace:\n"));
+        assert!(report.contains("\nminesweeper-rs snapshot v1\n"));
+        // the game is still on, so where the mines are stays out
+        assert!(report.contains("\nstate: playing\n"));
+        assert!(!report.contains("\nmines:"));
+    }
+
+    #[test]
+    fn an_unwritable_folder_falls_back_to_the_next() {
+        let dir = temp_dir("fallback");
+        std::fs::create_dir_all(&dir).unwrap();
+        let blocked = dir.join("file");
+        std::fs::write(&blocked, "not a folder").unwrap();
+        let fallback = dir.join("temp");
+        let path = write(&[blocked.join("crashes"), fallback.clone()], "report").unwrap();
+        assert!(path.starts_with(&fallback));
+        assert_eq!(std::fs::read_to_string(path).unwrap(), "report");
+    }
+
+    #[test]
+    fn each_report_is_offered_once() {
+        let dir = temp_dir("offered");
+        let empty = temp_dir("none"); // never made
+        let dirs = [empty, dir.clone()];
+        assert_eq!(take_unoffered(&dirs), None);
+
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("crash-0000000001-7.txt"), "").unwrap();
+        std::fs::write(dir.join("crash-0000000002-7.txt"), "").unwrap();
+        std::fs::write(dir.join("notes.txt"), "").unwrap();
+        assert_eq!(
+            take_unoffered(&dirs),
+            Some(dir.join("crash-0000000002-7.txt"))
+        );
+        assert_eq!(take_unoffered(&dirs), None);
+
+        std::fs::write(dir.join("crash-0000000003-7.txt"), "").unwrap();
+        assert_eq!(
+            take_unoffered(&dirs),
+            Some(dir.join("crash-0000000003-7.txt"))
+        );
+    }
+}
@@ -0,0 +1,348 @@
+//! The standard board sizes, mine densities for any size, and custom boards,
+//! with named presets for the latter and the terminal's difficulty prompt.
+
+use crate::BoardOptions;
+use rand::Rng;
+use std::fmt;
+use std::ops::RangeInclusive;
+
+mod presets;
+mod wizard;
+
+pub use presets::{PresetError, Presets};
+pub use wizard::{Reply, Wizard};
+
+/// Largest side allowed for custom boards unless large boards are enabled.
+pub const MAX_CUSTOM_SIDE: usize = 50;
+/// Largest side with "experimental large boards" on; keeps cell indices in u32.
+pub const MAX_LARGE_SIDE: usize = 4000;
+
+/// Side lengths "Surprise me" picks from.
+const SURPRISE_SIDES: RangeInclusive<usize> = 8..=30;
+/// Mine percentages "Surprise me" picks from.
+const SURPRISE_PERCENT: RangeInclusive<u8> = 10..=25;
+
+/// How many mines a board gets.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Mines {
+    /// Exactly this many.
+    Count(usize),
+    /// This percentage of the cells, rounded.
+    Percent(u8),
+}
+
+/// Board dimensions plus a mine count or density: everything that decides
+/// what kind of board a game is played on.
+///
+/// ```
+/// use minesweeper_rs::difficulty::{BoardSpec, Mines};
+///
+/// let spec = BoardSpec::new(9, 9, Mines::Percent(25));
+/// assert_eq!(spec.mine_count(), 20);
+/// assert_eq!(spec.to_string(), "9×9, 25% mines (20)");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct BoardSpec {
+    width: usize,
+    height: usize,
+    mines: Mines,
+}
+
+impl BoardSpec {
+    /// A `width` x `height` board with `mines`.
+    pub fn new(width: usize, height: usize, mines: Mines) -> Self {
+        BoardSpec {
+            width,
+            height,
+            mines,
+        }
+    }
+
+    /// Width in cells.
+    pub fn width(self) -> usize {
+        self.width
+    }
+
+    /// Height in cells.
+    pub fn height(self) -> usize {
+        self.height
+    }
+
+    /// The mines as given, count or density.
+    pub fn mines(self) -> Mines {
+        self.mines
+    }
+
+    /// The number of mines. A density always gives at least one mine and
+    /// leaves at least one safe cell.
+    ///
+    /// ```
+    /// use minesweeper_rs::difficulty::{BoardSpec, Mines};
+    ///
+    /// assert_eq!(BoardSpec::new(16, 16, Mines::Count(40)).mine_count(), 40);
+    /// assert_eq!(BoardSpec::new(2, 2, Mines::Percent(10)).mine_count(), 1);
+    /// ```
+    pub fn mine_count(self) -> usize {
+        let cells = self.width * self.height;
+        match self.mines {
+            Mines::Count(count) => count,
+            Mines::Percent(percent) => {
+                let count = (cells * percent as usize + 50) / 100;
+                count.clamp(1, cells.saturating_sub(1).max(1))
+            }
+        }
+    }
+
+    /// Width, height and mine count.
+    ///
+    /// ```
+    /// use minesweeper_rs::difficulty::{BoardSpec, Mines};
+    ///
+    /// assert_eq!(BoardSpec::new(30, 16, Mines::Percent(16)).params(), (30, 16, 77));
+    /// ```
+    pub fn params(self) -> (usize, usize, usize) {
+        (self.width, self.height, self.mine_count())
+    }
+
+    /// Options for a board of this spec, to add a seed or flag limit to.
+    pub fn options(self) -> BoardOptions {
+        let (width, height, mines) = self.params();
+        BoardOptions::new(width, height, mines)
+    }
+}
+
+impl fmt::Display for BoardSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}×{}, ", self.width, self.height)?;
+        match self.mines {
+            Mines::Count(count) => write!(f, "{count} mines"),
+            Mines::Percent(percent) => write!(f, "{percent}% mines ({})", self.mine_count()),
+        }
+    }
+}
+
+/// Named mine densities, applied to whatever size is being played.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Density {
+    /// 10% mines.
+    Relaxed,
+    /// 16% mines, close to the classic presets.
+    Standard,
+    /// 25% mines.
+    Brutal,
+}
+
+impl Density {
+    /// Every density, lightest first.
+    pub const ALL: [Density; 3] = [Density::Relaxed, Density::Standard, Density::Brutal];
+
+    /// Share of the cells that are mines.
+    ///
+    /// ```
+    /// assert_eq!(minesweeper_rs::difficulty::Density::Brutal.percent(), 25);
+    /// ```
+    pub fn percent(self) -> u8 {
+        match self {
+            Density::Relaxed => 10,
+            Density::Standard => 16,
+            Density::Brutal => 25,
+        }
+    }
+
+    /// The named density for `percent`, if there is one.
+    pub fn from_percent(percent: u8) -> Option<Density> {
+        Density::ALL.into_iter().find(|d| d.percent() == percent)
+    }
+
+    /// Name shown in menus.
+    pub fn label(self) -> &'static str {
+        match self {
+            Density::Relaxed => "Relaxed (10%)",
+            Density::Standard => "Standard (16%)",
+            Density::Brutal => "Brutal (25%)",
+        }
+    }
+}
+
+/// A board size and mine count.
+///
+/// ```
+/// use minesweeper_rs::Difficulty;
+///
+/// let board = Difficulty::Intermediate.options().seed(1).build()?;
+/// assert_eq!((board.width(), board.height(), board.mine_count()), (16, 16, 40));
+/// # Ok::<(), minesweeper_rs::BoardError>(())
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Difficulty {
+    /// 9x9 with 10 mines.
+    Beginner,
+    /// 16x16 with 40 mines.
+    Intermediate,
+    /// 25x25 with 99 mines.
+    Expert,
+    /// Any size with a share of its cells mined, from a [`Density`] or
+    /// "Surprise me".
+    Scaled {
+        /// Width in cells.
+        width: usize,
+        /// Height in cells.
+        height: usize,
+        /// Percentage of cells that are mines.
+        percent: u8,
+    },
+    /// Any other size.
+    Custom {
+        /// Width in cells.
+        width: usize,
+        /// Height in cells.
+        height: usize,
+        /// Mines on the board.
+        mines: usize,
+    },
+}
+
+impl Difficulty {
+    /// The fixed sizes, easiest first.
+    pub const PRESETS: [Difficulty; 3] = [
+        Difficulty::Beginner,
+        Difficulty::Intermediate,
+        Difficulty::Expert,
+    ];
+
+    /// Dimensions and mines.
+    ///
+    /// ```
+    /// use minesweeper_rs::difficulty::{Density, Difficulty};
+    ///
+    /// assert_eq!(Difficulty::Beginner.spec().params(), (9, 9, 10));
+    /// let brutal = Difficulty::Beginner.with_density(Density::Brutal);
+    /// assert_eq!(brutal.spec().params(), (9, 9, 20));
+    /// ```
+    pub fn spec(self) -> BoardSpec {
+        let (width, height, mines) = match self {
+            Difficulty::Beginner => (9, 9, Mines::Count(10)),
+            Difficulty::Intermediate => (16, 16, Mines::Count(40)),
+            Difficulty::Expert => (25, 25, Mines::Count(99)),
+            Difficulty::Scaled {
+                width,
+                height,
+                percent,
+            } => (width, height, Mines::Percent(percent)),
+            Difficulty::Custom {
+                width,
+                height,
+                mines,
+            } => (width, height, Mines::Count(mines)),
+        };
+        BoardSpec::new(width, height, mines)
+    }
+
+    /// The preset with these parameters, or a custom difficulty.
+    ///
+    /// ```
+    /// use minesweeper_rs::Difficulty;
+    ///
+    /// assert_eq!(Difficulty::from_params(16, 16, 40), Difficulty::Intermediate);
+    /// assert!(matches!(Difficulty::from_params(8, 8, 8), Difficulty::Custom { .. }));
+    /// ```
+    pub fn from_params(width: usize, height: usize, mines: usize) -> Self {
+        Difficulty::PRESETS
+            .into_iter()
+            .find(|d| d.spec().params() == (width, height, mines))
+            .unwrap_or(Difficulty::Custom {
+                width,
+                height,
+                mines,
+            })
+    }
+
+    /// Name shown in menus.
+    ///
+    /// ```
+    /// assert_eq!(minesweeper_rs::Difficulty::Expert.label(), "Expert (25x25)");
+    /// ```
+    pub fn label(self) -> &'static str {
+        match self {
+            Difficulty::Beginner => "Beginner (9x9)",
+            Difficulty::Intermediate => "Intermediate (16x16)",
+            Difficulty::Expert => "Expert (25x25)",
+            Difficulty::Scaled { percent, .. } => {
+                Density::from_percent(percent).map_or("Surprise", Density::label)
+            }
+            Difficulty::Custom { .. } => "Custom",
+        }
+    }
+
+    /// The same size with `density` mines.
+    pub fn with_density(self, density: Density) -> Self {
+        self.with_percent(density.percent())
+    }
+
+    /// The same size with `percent` of its cells mined.
+    pub fn with_percent(self, percent: u8) -> Self {
+        let spec = self.spec();
+        Difficulty::Scaled {
+            width: spec.width(),
+            height: spec.height(),
+            percent,
+        }
+    }
+
+    /// The density this is played at, if it is one of the named ones.
+    pub fn density(self) -> Option<Density> {
+        match self {
+            Difficulty::Scaled { percent, .. } => Density::from_percent(percent),
+            _ => None,
+        }
+    }
+
+    /// A random size and density within comfortable bounds, for "Surprise
+    /// me".
+    ///
+    /// ```
+    /// use minesweeper_rs::Difficulty;
+    ///
+    /// let pick = Difficulty::surprise(&mut rand::thread_rng());
+    /// assert!(matches!(pick, Difficulty::Scaled { .. }));
+    /// ```
+    pub fn surprise(rng: &mut impl Rng) -> Self {
+        Difficulty::Scaled {
+            width: rng.gen_range(SURPRISE_SIDES),
+            height: rng.gen_range(SURPRISE_SIDES),
+            percent: rng.gen_range(SURPRISE_PERCENT),
+        }
+    }
+
+    /// A custom board with dimensions clamped to `max_side` and at least one
+    /// safe cell left for the first click.
+    ///
+    /// ```
+    /// use minesweeper_rs::Difficulty;
+    ///
+    /// assert_eq!(Difficulty::custom(1, 80, 500, 50).spec().params(), (2, 50, 99));
+    /// ```
+    pub fn custom(width: usize, height: usize, mines: usize, max_side: usize) -> Self {
+        let width = width.clamp(2, max_side);
+        let height = height.clamp(2, max_side);
+        let mines = mines.clamp(1, width * height - 1);
+        Difficulty::Custom {
+            width,
+            height,
+            mines,
+        }
+    }
+
+    /// Options for a board of this size, to add a seed or flag limit to.
+    ///
+    /// ```
+    /// use minesweeper_rs::Difficulty;
+    ///
+    /// let board = Difficulty::Beginner.options().build()?;
+    /// assert_eq!(board.mine_count(), 10);
+    /// # Ok::<(), minesweeper_rs::BoardError>(())
+    /// ```
+    pub fn options(self) -> BoardOptions {
+        self.spec().options()
+    }
+}
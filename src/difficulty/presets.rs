@@ -0,0 +1,233 @@
+//! Named custom boards kept in a config file, one TOML-style section each:
+//!
+//! ```text
+//! [presets.mylarge]
+//! width = 40
+//! height = 20
+//! mines = 160
+//! ```
+//!
+//! Only this subset of TOML is read: section headers, `key = value` pairs
+//! with whole-number values (several may share a line), blank lines and
+//! `#` comments. Saving rewrites the whole file.
+
+use super::{Difficulty, MAX_CUSTOM_SIDE};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// Words the difficulty prompt already answers to.
+const RESERVED: [&str; 5] = ["beginner", "intermediate", "expert", "custom", "quit"];
+
+/// Saved board sizes by name.
+///
+/// ```
+/// use minesweeper_rs::difficulty::Presets;
+/// use minesweeper_rs::Difficulty;
+///
+/// let presets: Presets = "[presets.wide]\nwidth = 30\nheight = 9\nmines = 40\n".parse()?;
+/// assert_eq!(presets.get("wide").map(|d| d.spec().params()), Some((30, 9, 40)));
+/// assert_eq!(presets.to_string().parse::<Presets>()?, presets);
+/// # Ok::<(), minesweeper_rs::difficulty::PresetError>(())
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Presets {
+    boards: BTreeMap<String, Difficulty>,
+}
+
+impl Presets {
+    /// Read presets from `path`. A missing file is no presets yet.
+    pub fn load(path: impl AsRef<Path>) -> Result<Presets, PresetError> {
+        match fs::read_to_string(path) {
+            Ok(text) => text.parse(),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Presets::default()),
+            Err(err) => Err(PresetError::Io(err)),
+        }
+    }
+
+    /// Write every preset to `path` through a temporary file, so a failed
+    /// write leaves the old file as it was.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), PresetError> {
+        let path = path.as_ref();
+        let write = || -> io::Result<()> {
+            if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+                fs::create_dir_all(dir)?;
+            }
+            let tmp = path.with_extension("tmp");
+            let mut file = fs::File::create(&tmp)?;
+            file.write_all(self.to_string().as_bytes())?;
+            file.sync_all()?;
+            fs::rename(&tmp, path)
+        };
+        write().map_err(PresetError::Io)
+    }
+
+    /// The board saved as `name`.
+    pub fn get(&self, name: &str) -> Option<Difficulty> {
+        self.boards.get(name).copied()
+    }
+
+    /// Every preset name, in order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.boards.keys().map(String::as_str)
+    }
+
+    /// Save `difficulty` as `name`, returning the board it replaced.
+    ///
+    /// ```
+    /// use minesweeper_rs::difficulty::{PresetError, Presets};
+    /// use minesweeper_rs::Difficulty;
+    ///
+    /// let mut presets = Presets::default();
+    /// assert!(presets.insert("big", Difficulty::custom(40, 20, 160, 50))?.is_none());
+    /// assert!(matches!(
+    ///     presets.insert("expert", Difficulty::Expert),
+    ///     Err(PresetError::BadName(_))
+    /// ));
+    /// # Ok::<(), PresetError>(())
+    /// ```
+    pub fn insert(
+        &mut self,
+        name: &str,
+        difficulty: Difficulty,
+    ) -> Result<Option<Difficulty>, PresetError> {
+        check_name(name)?;
+        Ok(self.boards.insert(name.to_string(), difficulty))
+    }
+}
+
+/// Why `name` can't be a preset name, if it can't: it must be letters,
+/// digits, `-` and `_`, and not a word the prompt already knows.
+pub(crate) fn check_name(name: &str) -> Result<(), PresetError> {
+    let ok = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        && !RESERVED.contains(&name.to_ascii_lowercase().as_str());
+    if ok {
+        Ok(())
+    } else {
+        Err(PresetError::BadName(name.to_string()))
+    }
+}
+
+impl fmt::Display for Presets {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (name, difficulty)) in self.boards.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            let (width, height, mines) = difficulty.spec().params();
+            writeln!(f, "[presets.{name}]")?;
+            writeln!(f, "width = {width}\nheight = {height}\nmines = {mines}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for Presets {
+    type Err = PresetError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut presets = Presets::default();
+        let mut section: Option<(String, [Option<usize>; 3])> = None;
+        let mut finish = |section: Option<(String, [Option<usize>; 3])>| match section {
+            Some((name, [Some(w), Some(h), Some(m)]))
+                if (2..=MAX_CUSTOM_SIDE).contains(&w)
+                    && (2..=MAX_CUSTOM_SIDE).contains(&h)
+                    && (1..w * h).contains(&m) =>
+            {
+                presets.insert(&name, Difficulty::from_params(w, h, m))?;
+                Ok(())
+            }
+            Some((name, _)) => Err(PresetError::Invalid(name)),
+            None => Ok(()),
+        };
+
+        for (number, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(header) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                finish(section.take())?;
+                // other tables are left alone; only presets are read
+                section = header
+                    .trim()
+                    .strip_prefix("presets.")
+                    .map(|name| (name.to_string(), [None; 3]));
+                continue;
+            }
+            // `width = 40 height = 20` as well as one pair per line
+            let words = line.replace('=', " = ");
+            let words: Vec<&str> = words.split_whitespace().collect();
+            if !words.len().is_multiple_of(3) {
+                return Err(PresetError::Syntax(number + 1));
+            }
+            for pair in words.chunks(3) {
+                let [key, "=", value] = pair else {
+                    return Err(PresetError::Syntax(number + 1));
+                };
+                let Some((_, fields)) = &mut section else {
+                    continue;
+                };
+                let slot = match *key {
+                    "width" => &mut fields[0],
+                    "height" => &mut fields[1],
+                    "mines" => &mut fields[2],
+                    _ => continue,
+                };
+                *slot = Some(value.parse().map_err(|_| PresetError::Syntax(number + 1))?);
+            }
+        }
+        finish(section)?;
+        Ok(presets)
+    }
+}
+
+/// A preset that couldn't be read, written or named.
+#[derive(Debug)]
+pub enum PresetError {
+    /// Names are letters, digits, `-` and `_`, other than the prompt's own
+    /// words such as `expert`.
+    BadName(String),
+    /// This line (from 1) isn't a section header or `key = number`.
+    Syntax(usize),
+    /// This preset lacks a width, height or mine count, or has one out of
+    /// range.
+    Invalid(String),
+    /// The file couldn't be read or written.
+    Io(io::Error),
+}
+
+impl fmt::Display for PresetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PresetError::BadName(name) => write!(
+                f,
+                "{name:?} can't be a preset name: use letters, digits, - and _, \
+                 and not beginner, intermediate, expert, custom or quit"
+            ),
+            PresetError::Syntax(line) => {
+                write!(f, "line {line}: expected [presets.NAME] or key = number")
+            }
+            PresetError::Invalid(name) => write!(
+                f,
+                "preset {name} needs a width and height from 2 to {MAX_CUSTOM_SIDE} \
+                 and at least one mine and one safe cell"
+            ),
+            PresetError::Io(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for PresetError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PresetError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
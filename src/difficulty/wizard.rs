@@ -0,0 +1,212 @@
+//! The terminal's difficulty prompt as a state machine: feed it the lines
+//! the player types and print what it answers, until it says which board
+//! to start. Choosing `custom` walks through the width, height and mines,
+//! checking each, then confirms and offers to save the board as a preset.
+//!
+//! ```
+//! use minesweeper_rs::difficulty::{Presets, Reply, Wizard};
+//!
+//! let mut wizard = Wizard::new(Presets::default());
+//! for line in ["custom", "40", "20", "160", "y"] {
+//!     assert!(matches!(wizard.feed(line), Reply::Ask(_)));
+//! }
+//! let Reply::Start { difficulty, save_as } = wizard.feed("mylarge") else {
+//!     unreachable!()
+//! };
+//! assert_eq!(difficulty.spec().params(), (40, 20, 160));
+//! assert_eq!(save_as.as_deref(), Some("mylarge"));
+//! ```
+
+use super::presets::{check_name, Presets};
+use super::{Difficulty, MAX_CUSTOM_SIDE};
+use crate::generate;
+
+/// Where the wizard is.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum State {
+    Choose,
+    Width,
+    Height {
+        width: usize,
+    },
+    Mines {
+        width: usize,
+        height: usize,
+    },
+    Confirm(Difficulty),
+    SaveAs(Difficulty),
+    Overwrite {
+        difficulty: Difficulty,
+        name: String,
+    },
+}
+
+/// What the wizard says to a line.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Reply {
+    /// Print this, which ends with the next question, and read a line.
+    Ask(String),
+    /// Start a game at `difficulty`, first saving it as the preset
+    /// `save_as` if there is one.
+    Start {
+        /// The board to play.
+        difficulty: Difficulty,
+        /// The preset name to save it under.
+        save_as: Option<String>,
+    },
+    /// The player typed `quit`.
+    Quit,
+}
+
+/// The difficulty prompt, from choosing a board to starting it.
+pub struct Wizard {
+    state: State,
+    presets: Presets,
+}
+
+impl Wizard {
+    /// A prompt offering the built-in difficulties, `custom` and `presets`.
+    pub fn new(presets: Presets) -> Self {
+        Wizard {
+            state: State::Choose,
+            presets,
+        }
+    }
+
+    /// The question waiting for an answer.
+    pub fn prompt(&self) -> String {
+        match &self.state {
+            State::Choose => {
+                let mut names = vec!["beginner", "intermediate", "expert", "custom"];
+                names.extend(self.presets.names());
+                format!("Difficulty ({}, or quit):", names.join(", "))
+            }
+            State::Width => format!("Width (2 to {MAX_CUSTOM_SIDE}):"),
+            State::Height { .. } => format!("Height (2 to {MAX_CUSTOM_SIDE}):"),
+            State::Mines { width, height } => format!("Mines (1 to {}):", width * height - 1),
+            State::Confirm(_) => "Start this board? [Y/n]".to_string(),
+            State::SaveAs(_) => {
+                "Save it as a preset? Type a name, or press Enter to skip:".to_string()
+            }
+            State::Overwrite { name, .. } => format!("Replace preset {name}? [y/N]"),
+        }
+    }
+
+    /// Answer one line of input.
+    pub fn feed(&mut self, line: &str) -> Reply {
+        let line = line.trim();
+        if line.eq_ignore_ascii_case("quit") {
+            return Reply::Quit;
+        }
+        let yes = line.eq_ignore_ascii_case("y") || line.eq_ignore_ascii_case("yes");
+        let no = line.eq_ignore_ascii_case("n") || line.eq_ignore_ascii_case("no");
+
+        let (say, next) = match std::mem::replace(&mut self.state, State::Choose) {
+            State::Choose if line.eq_ignore_ascii_case("custom") => (String::new(), State::Width),
+            State::Choose => match builtin(line).or_else(|| self.presets.get(line)) {
+                Some(difficulty) => return start(difficulty, None),
+                None => (format!("No difficulty called {line:?}."), State::Choose),
+            },
+            State::Width => match side(line) {
+                Some(width) => (String::new(), State::Height { width }),
+                None => (side_error("width"), State::Width),
+            },
+            State::Height { width } => match side(line) {
+                Some(height) => (String::new(), State::Mines { width, height }),
+                None => (side_error("height"), State::Height { width }),
+            },
+            State::Mines { width, height } => {
+                let cells = width * height;
+                match line.parse::<usize>() {
+                    Ok(mines) if (1..cells).contains(&mines) => {
+                        let difficulty = Difficulty::Custom {
+                            width,
+                            height,
+                            mines,
+                        };
+                        (summary(difficulty), State::Confirm(difficulty))
+                    }
+                    _ => (
+                        format!(
+                            "Mines must be a whole number from 1 to {}, leaving a safe cell.",
+                            cells - 1
+                        ),
+                        State::Mines { width, height },
+                    ),
+                }
+            }
+            State::Confirm(difficulty) if yes || line.is_empty() => {
+                (String::new(), State::SaveAs(difficulty))
+            }
+            State::Confirm(_) if no => ("Let's try again.".to_string(), State::Width),
+            State::Confirm(difficulty) => (
+                "Please answer y or n.".to_string(),
+                State::Confirm(difficulty),
+            ),
+            State::SaveAs(difficulty) if line.is_empty() => return start(difficulty, None),
+            State::SaveAs(difficulty) => match check_name(line) {
+                Err(err) => (err.to_string(), State::SaveAs(difficulty)),
+                Ok(()) => match self.presets.get(line) {
+                    None => return start(difficulty, Some(line.to_string())),
+                    Some(old) => (
+                        format!("A preset called {line} already exists ({}).", old.spec()),
+                        State::Overwrite {
+                            difficulty,
+                            name: line.to_string(),
+                        },
+                    ),
+                },
+            },
+            State::Overwrite { difficulty, name } if yes => return start(difficulty, Some(name)),
+            State::Overwrite { difficulty, .. } => (String::new(), State::SaveAs(difficulty)),
+        };
+        self.state = next;
+        let prompt = self.prompt();
+        Reply::Ask(if say.is_empty() {
+            prompt
+        } else {
+            format!("{say}\n{prompt}")
+        })
+    }
+}
+
+fn start(difficulty: Difficulty, save_as: Option<String>) -> Reply {
+    Reply::Start {
+        difficulty,
+        save_as,
+    }
+}
+
+fn builtin(name: &str) -> Option<Difficulty> {
+    match name.to_ascii_lowercase().as_str() {
+        "beginner" => Some(Difficulty::Beginner),
+        "intermediate" => Some(Difficulty::Intermediate),
+        "expert" => Some(Difficulty::Expert),
+        _ => None,
+    }
+}
+
+fn side(line: &str) -> Option<usize> {
+    line.parse()
+        .ok()
+        .filter(|side| (2..=MAX_CUSTOM_SIDE).contains(side))
+}
+
+fn side_error(which: &str) -> String {
+    format!("The {which} must be a whole number from 2 to {MAX_CUSTOM_SIDE}.")
+}
+
+/// The chosen board, its density and whether no-guess boards can be made.
+fn summary(difficulty: Difficulty) -> String {
+    let (width, height, mines) = difficulty.spec().params();
+    let density = mines as f64 * 100.0 / (width * height) as f64;
+    let no_guess = if generate::feasible(width, height, mines) {
+        "available"
+    } else {
+        "not available, too few safe cells for an opening"
+    };
+    format!(
+        "{}: {density:.1}% of the cells are mines. No-guess generation: {no_guess}.",
+        difficulty.spec()
+    )
+}
@@ -0,0 +1,261 @@
+//! Earcons: short tones that say what a move did without looking. An
+//! opened number sounds a pitch that climbs a whole tone per mine, from E4
+//! for a 1 to F♯5 for an 8; a blank's cascade sweeps upwards for longer the
+//! more cells it opened; a flag going on rises and coming off falls; and a
+//! move that can't be right buzzes. Each can be panned to the cell's
+//! column, left edge to right edge.
+//!
+//! The tones are synthesized as they play, sine and square waves shaped
+//! by rodio's source combinators, so there are no sound files and every
+//! pitch is exact. The audio device is opened on the first earcon; without
+//! one they are skipped, with a warning logged once.
+
+use rodio::source::{ChannelVolume, Function, SignalGenerator, SineWave, Source};
+use rodio::{cpal, OutputStream, OutputStreamHandle, Sink};
+use std::f32::consts::FRAC_PI_4;
+use std::time::Duration;
+use tracing::warn;
+
+/// Pitch of a 1, in Hz: E4.
+const BASE_PITCH: f32 = 329.63;
+
+/// Most steps in a cascade's sweep, reached at 1024 cells.
+const MAX_SWEEP_STEPS: usize = 12;
+
+/// Ramp at the start of each tone, so it doesn't click.
+const ATTACK: Duration = Duration::from_millis(5);
+
+/// Samples a second of the square wave, as rodio's sine wave has.
+const SAMPLE_RATE: u32 = 48_000;
+
+/// What a move sounded like.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Earcon {
+    Number(u8),     // a lone number opened
+    Cascade(usize), // this many cells opened at once
+    Flagged,
+    Unflagged,
+    Warning, // a move that was refused or has to be wrong
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Wave {
+    Sine,
+    Square,
+}
+
+/// One tone of an earcon; a frequency of 0 is a rest.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Note {
+    freq: f32,
+    secs: f32,
+    wave: Wave,
+}
+
+impl Note {
+    fn sine(freq: f32, secs: f32) -> Note {
+        Note {
+            freq,
+            secs,
+            wave: Wave::Sine,
+        }
+    }
+}
+
+impl Earcon {
+    fn notes(self) -> Vec<Note> {
+        match self {
+            Earcon::Number(n) => vec![Note::sine(pitch(n), 0.09)],
+            Earcon::Cascade(cells) => (0..sweep_steps(cells))
+                .map(|step| Note::sine(BASE_PITCH * semitones(step as f32), 0.025))
+                .collect(),
+            Earcon::Flagged => vec![Note::sine(660.0, 0.05), Note::sine(880.0, 0.05)],
+            Earcon::Unflagged => vec![Note::sine(880.0, 0.05), Note::sine(660.0, 0.05)],
+            Earcon::Warning => {
+                let buzz = Note {
+                    freq: 220.0,
+                    secs: 0.09,
+                    wave: Wave::Square,
+                };
+                vec![buzz, Note::sine(0.0, 0.04), buzz]
+            }
+        }
+    }
+}
+
+/// The frequency `steps` semitones above the base, as a ratio.
+fn semitones(steps: f32) -> f32 {
+    2f32.powf(steps / 12.0)
+}
+
+/// The pitch of a number, in Hz: a whole tone higher for each mine.
+/// Anything outside 1 to 8 is held to that range.
+pub fn pitch(n: u8) -> f32 {
+    let steps = f32::from(n.clamp(1, 8) - 1) * 2.0;
+    BASE_PITCH * semitones(steps)
+}
+
+/// Steps in the sweep for a cascade of `cells`: one more each time the
+/// opening doubles, up to `MAX_SWEEP_STEPS`.
+pub fn sweep_steps(cells: usize) -> usize {
+    let doublings = usize::BITS - cells.max(1).leading_zeros();
+    (doublings as usize + 1).min(MAX_SWEEP_STEPS)
+}
+
+/// Where column `x` of a board `width` cells wide sits, from -1 (left
+/// edge) to 1 (right edge). A single column is in the middle.
+pub fn pan(x: usize, width: usize) -> f32 {
+    if width <= 1 {
+        return 0.0;
+    }
+    let x = x.min(width - 1) as f32;
+    2.0 * x / (width - 1) as f32 - 1.0
+}
+
+/// Left and right volumes for `pan`, keeping the loudness the same
+/// across the board.
+fn volumes(pan: f32) -> [f32; 2] {
+    let angle = (pan.clamp(-1.0, 1.0) + 1.0) * FRAC_PI_4;
+    [angle.cos(), angle.sin()]
+}
+
+/// The audio output, opened when the first earcon plays.
+#[derive(Default)]
+pub struct Earcons {
+    output: Option<(OutputStream, OutputStreamHandle)>,
+    unavailable: bool, // opening failed, so don't try every move
+}
+
+impl Earcons {
+    /// Play `earcon`, panned to `pan` (see [`pan`]).
+    pub fn play(&mut self, earcon: Earcon, pan: f32) {
+        if let Some(sink) = self.sink() {
+            queue(&sink, earcon, pan);
+            sink.detach();
+        }
+    }
+
+    /// Every earcon in turn, for the settings' test button: the numbers
+    /// from left to right when `panned`, then a cascade, a flag going on
+    /// and off, and the warning.
+    pub fn demo(&mut self, panned: bool) {
+        let Some(sink) = self.sink() else {
+            return;
+        };
+        for n in 1..=8 {
+            let at = if panned {
+                pan(usize::from(n - 1), 8)
+            } else {
+                0.0
+            };
+            queue(&sink, Earcon::Number(n), at);
+            sink.append(SineWave::new(0.0).take_duration(Duration::from_millis(60)));
+        }
+        for earcon in [
+            Earcon::Cascade(200),
+            Earcon::Flagged,
+            Earcon::Unflagged,
+            Earcon::Warning,
+        ] {
+            sink.append(SineWave::new(0.0).take_duration(Duration::from_millis(250)));
+            queue(&sink, earcon, 0.0);
+        }
+        sink.detach();
+    }
+
+    fn sink(&mut self) -> Option<Sink> {
+        if self.output.is_none() && !self.unavailable {
+            match OutputStream::try_default() {
+                Ok(output) => self.output = Some(output),
+                Err(err) => {
+                    warn!(%err, "no audio output; earcons are off");
+                    self.unavailable = true;
+                }
+            }
+        }
+        let (_, handle) = self.output.as_ref()?;
+        Sink::try_new(handle)
+            .map_err(|err| warn!(%err, "couldn't play an earcon"))
+            .ok()
+    }
+}
+
+/// Put `earcon`'s tones on `sink`, panned to `pan`.
+fn queue(sink: &Sink, earcon: Earcon, pan: f32) {
+    let volumes = volumes(pan).to_vec();
+    for note in earcon.notes() {
+        let length = Duration::from_secs_f32(note.secs);
+        match note.wave {
+            Wave::Sine => sink.append(shaped(SineWave::new(note.freq), length, &volumes, 0.25)),
+            Wave::Square => sink.append(shaped(square(note.freq), length, &volumes, 0.08)),
+        }
+    }
+}
+
+/// A square wave at `freq` Hz, rodio having no square counterpart to
+/// `SineWave`.
+fn square(freq: f32) -> SignalGenerator {
+    SignalGenerator::new(cpal::SampleRate(SAMPLE_RATE), freq, Function::Square)
+}
+
+/// `wave` cut to `length` with its ends softened, at `gain`, on two
+/// channels at `volumes`.
+fn shaped<S>(
+    wave: S,
+    length: Duration,
+    volumes: &[f32],
+    gain: f32,
+) -> impl Source<Item = f32> + Send + 'static
+where
+    S: Source<Item = f32> + Send + 'static,
+{
+    let mut tone = wave.take_duration(length);
+    tone.set_filter_fadeout();
+    ChannelVolume::new(tone.fade_in(ATTACK).amplify(gain), volumes.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers_climb_a_whole_tone_each() {
+        assert!((pitch(1) - 329.63).abs() < 0.01);
+        assert!((pitch(8) - 739.99).abs() < 0.1, "F♯5, {}", pitch(8));
+        for n in 1..8 {
+            let ratio = pitch(n + 1) / pitch(n);
+            assert!((ratio - semitones(2.0)).abs() < 1e-4);
+        }
+        assert_eq!(pitch(0), pitch(1));
+        assert_eq!(pitch(9), pitch(8));
+    }
+
+    #[test]
+    fn columns_pan_from_edge_to_edge() {
+        assert_eq!(pan(0, 30), -1.0);
+        assert_eq!(pan(29, 30), 1.0);
+        assert_eq!(pan(2, 5), 0.0);
+        assert_eq!(pan(0, 1), 0.0);
+        assert_eq!(pan(40, 30), 1.0);
+
+        let [left, right] = volumes(-1.0);
+        assert!((left - 1.0).abs() < 1e-6 && right.abs() < 1e-6);
+        for p in [-1.0, -0.3, 0.0, 0.7, 1.0] {
+            let [left, right] = volumes(p);
+            assert!(
+                (left * left + right * right - 1.0).abs() < 1e-5,
+                "even at {p}"
+            );
+        }
+    }
+
+    #[test]
+    fn bigger_cascades_sweep_longer() {
+        assert_eq!(sweep_steps(1), 2);
+        assert!(sweep_steps(20) < sweep_steps(100));
+        assert_eq!(sweep_steps(1 << 20), MAX_SWEEP_STEPS);
+        let notes = Earcon::Cascade(100).notes();
+        assert_eq!(notes.len(), sweep_steps(100));
+        assert!(notes.windows(2).all(|pair| pair[0].freq < pair[1].freq));
+    }
+}
@@ -0,0 +1,229 @@
+//! How the rest of a game goes under best play, once few enough cells are
+//! hidden to try every way the mines could lie among them: the best chance
+//! of winning from here and how many guesses that takes.
+//!
+//! Cells the revealed numbers already show to be mines are set aside, so
+//! only the ones still in question count towards the limit. Every
+//! placement of the remaining mines that agrees with the revealed
+//! numbers is equally likely. A move opens a cell; it loses in the
+//! placements with a mine there and splits the others by the number it
+//! shows. The chance of winning from a set of placements is then the best,
+//! over every cell, of the chance of each number times the chance of
+//! winning from the placements that show it; one placement left is a won
+//! game, since every safe cell is then known. Sets are remembered as they
+//! are worked out, and the search gives up past [`BUDGET`] of them.
+//!
+//! ```
+//! use minesweeper_rs::endgame;
+//! use minesweeper_rs::Board;
+//!
+//! // a 1 with two hidden cells beside it: a coin flip
+//! let mut board = Board::decode_layout("*..\n")?;
+//! board.reveal_cell(1, 0);
+//! let outlook = endgame::outlook(&board, 10).unwrap();
+//! assert_eq!((outlook.guesses, outlook.win_chance), (1, 0.5));
+//! assert_eq!(outlook.to_string(), "1 coin flip ahead, 50% best case");
+//! # Ok::<(), minesweeper_rs::LayoutError>(())
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::{solver, Board};
+
+/// The most hidden cells searched, whatever the caller's limit.
+pub const MAX_CELLS: usize = 64;
+/// How many placements, and sets of them, the search may look at before
+/// giving up.
+pub const BUDGET: usize = 200_000;
+
+/// The rest of a game under best play.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Outlook {
+    /// Hidden cells, flagged or not, other than those the numbers show to
+    /// be mines.
+    pub cells: usize,
+    /// The most guesses best play can meet on the way to a win: clicks on
+    /// a cell that might be a mine.
+    pub guesses: u32,
+    /// The chance of winning from here, playing as well as possible.
+    pub win_chance: f64,
+}
+
+impl fmt::Display for Outlook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let percent = (self.win_chance * 100.0).round();
+        match self.guesses {
+            0 => f.write_str("No guesses ahead"),
+            1 => write!(f, "1 coin flip ahead, {percent}% best case"),
+            n => write!(f, "{n} coin flips ahead, {percent}% best case"),
+        }
+    }
+}
+
+/// The outlook for `board` under best play, if no more than `limit` of its
+/// hidden cells are still in question and the search fits in [`BUDGET`].
+/// Player flags are ignored, as the solver ignores them. `None` for a game that
+/// is over or not yet dealt.
+pub fn outlook(board: &Board, limit: usize) -> Option<Outlook> {
+    if board.is_over() {
+        return None;
+    }
+    // cells the numbers already show to be mines are left out, so an
+    // expert board's ninety-odd mines don't crowd the search
+    let analysis = solver::analyze(board);
+    let known = |x: usize, y: usize| analysis.mines().contains(&(x, y));
+    let width = board.width();
+    let hidden: Vec<(usize, usize)> = (0..board.height())
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .filter(|&(x, y)| !board.cell(x, y).is_revealed() && !known(x, y))
+        .collect();
+    if hidden.len() > limit.min(MAX_CELLS) || board.revealed_safe() == 0 {
+        return None;
+    }
+    let local = |at: (usize, usize)| hidden.iter().position(|&cell| cell == at);
+    let near: Vec<u64> = hidden
+        .iter()
+        .map(|&(x, y)| mask(board.neighbors(x, y).filter_map(local)))
+        .collect();
+    // each revealed number, less the known mines it touches, and the
+    // hidden cells around it
+    let numbers: Vec<(u64, u32)> = (0..board.height())
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .filter_map(|(x, y)| {
+            let shown = board.cell(x, y).neighbor_mines()?;
+            let around = mask(board.neighbors(x, y).filter_map(local));
+            let mines = board
+                .neighbors(x, y)
+                .filter(|&(nx, ny)| known(nx, ny))
+                .count();
+            (around != 0).then_some((around, u32::from(shown) - mines as u32))
+        })
+        .collect();
+
+    let mut search = Search {
+        near,
+        placements: Vec::new(),
+        known: HashMap::new(),
+        budget: BUDGET,
+    };
+    let mut place = Place {
+        numbers: &numbers,
+        cells: hidden.len(),
+        mines: board.mine_count() - analysis.mines().len(),
+        found: &mut search.placements,
+        budget: &mut search.budget,
+    };
+    place.extend(0, 0, 0)?;
+    if search.placements.is_empty() {
+        return None;
+    }
+    let all: Vec<u32> = (0..search.placements.len() as u32).collect();
+    let (win_chance, guesses) = search.best(&all)?;
+    Some(Outlook {
+        cells: hidden.len(),
+        guesses,
+        win_chance,
+    })
+}
+
+fn mask(cells: impl Iterator<Item = usize>) -> u64 {
+    cells.fold(0, |mask, i| mask | 1 << i)
+}
+
+/// Lays mines on the hidden cells one at a time, keeping the placements
+/// every number agrees with.
+struct Place<'a> {
+    numbers: &'a [(u64, u32)],
+    cells: usize,
+    mines: usize,
+    found: &'a mut Vec<u64>,
+    budget: &'a mut usize,
+}
+
+impl Place<'_> {
+    /// Carry on from cell `at` with `placed` mines laid as `mines_at`.
+    /// `None` once the budget runs out.
+    fn extend(&mut self, at: usize, placed: usize, mines_at: u64) -> Option<()> {
+        *self.budget = self.budget.checked_sub(1)?;
+        // the cells decided so far; a number they cover must be exact, and
+        // no number may be over
+        let decided = if at == 64 { u64::MAX } else { (1 << at) - 1 };
+        let fits = self.numbers.iter().all(|&(around, shown)| {
+            let laid = (around & mines_at).count_ones();
+            laid <= shown && (around & !decided != 0 || laid == shown)
+        });
+        if !fits || placed > self.mines || self.mines - placed > self.cells - at {
+            return Some(());
+        }
+        if at == self.cells {
+            self.found.push(mines_at);
+            return Some(());
+        }
+        self.extend(at + 1, placed, mines_at)?;
+        self.extend(at + 1, placed + 1, mines_at | 1 << at)
+    }
+}
+
+/// The best play over sets of placements, each a sorted list of indices
+/// into `placements`.
+struct Search {
+    near: Vec<u64>,
+    placements: Vec<u64>,
+    known: HashMap<Vec<u32>, (f64, u32)>,
+    budget: usize,
+}
+
+impl Search {
+    /// The chance of winning from `set`, and the guesses on the way.
+    fn best(&mut self, set: &[u32]) -> Option<(f64, u32)> {
+        if set.len() == 1 {
+            return Some((1.0, 0));
+        }
+        if let Some(&known) = self.known.get(set) {
+            return Some(known);
+        }
+        self.budget = self.budget.checked_sub(1)?;
+        let total = set.len() as f64;
+        let mut best: Option<(f64, u32)> = None;
+        for cell in 0..self.near.len() {
+            let bit = 1 << cell;
+            // the safe placements, split by the number the cell would show
+            let mut shows: Vec<(u32, Vec<u32>)> = Vec::new();
+            for &i in set {
+                let placement = self.placements[i as usize];
+                if placement & bit != 0 {
+                    continue;
+                }
+                let number = (placement & self.near[cell]).count_ones();
+                match shows.iter_mut().find(|(shown, _)| *shown == number) {
+                    Some((_, same)) => same.push(i),
+                    None => shows.push((number, vec![i])),
+                }
+            }
+            let safe: usize = shows.iter().map(|(_, same)| same.len()).sum();
+            let risky = safe < set.len();
+            // a certain mine, or a safe cell that tells nothing new
+            if safe == 0 || (!risky && shows.len() == 1) {
+                continue;
+            }
+            let mut chance = 0.0;
+            let mut guesses = 0;
+            for (_, same) in &shows {
+                let (win, ahead) = self.best(same)?;
+                chance += same.len() as f64 / total * win;
+                guesses = guesses.max(ahead);
+            }
+            let guesses = guesses + u32::from(risky);
+            let better =
+                best.is_none_or(|(c, g)| chance > c + 1e-12 || (chance > c - 1e-12 && guesses < g));
+            if better {
+                best = Some((chance, guesses));
+            }
+        }
+        // placements that differ always differ at a cell one of them mines
+        let best = best.expect("a move that tells the placements apart");
+        self.known.insert(set.to_vec(), best);
+        Some(best)
+    }
+}
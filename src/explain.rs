@@ -0,0 +1,235 @@
+//! `hint` and `solve`: the solver's deductions on the command line, for a
+//! position read the way `render-text` reads one. `hint` names the move
+//! the "Watch & learn" bot would make next, and `--why` adds its reason;
+//! `solve` plays every deduction out and prints where logic alone gets,
+//! with `--explain` giving a line for each deduction in the order it was
+//! applied. The reasons are the bot's own (see `coach::explain`), with
+//! cells named as on the coordinates grid.
+
+use crate::cli::CliError;
+use crate::coach::{self, cell_name};
+use crate::render_text::{self, Source};
+use crate::widgets::CellClick;
+use minesweeper_rs::report::DebugSnapshot;
+use minesweeper_rs::solver;
+use minesweeper_rs::Board;
+
+const HINT_USAGE: &str = "usage: hint (--autosave FILE | --replay RESULT.json [--board N] \
+                          [--move K] | --code HEX) [--why]";
+const SOLVE_USAGE: &str = "usage: solve (--autosave FILE | --replay RESULT.json [--board N] \
+                           [--move K] | --code HEX) [--explain]";
+
+/// Reasons one round of deductions prints before the rest are counted.
+const ROUND_LINES: usize = 20;
+
+/// `hint` or `solve` read from the command line.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Request {
+    pub source: Source,
+    pub explain: bool, // --why or --explain
+}
+
+/// `words` for a subcommand whose flag for explaining is `flag`.
+fn parse(words: &[String], usage: &str, flag: &str) -> Result<Request, String> {
+    let mut explain = false;
+    let source = render_text::parse_source(words, usage, |word| {
+        explain |= word == flag;
+        word == flag
+    })?;
+    Ok(Request { source, explain })
+}
+
+/// The next move on the position `words` name, with its reason on the
+/// line after for `--why`.
+pub fn hint(words: &[String]) -> Result<String, CliError> {
+    let request = parse(words, HINT_USAGE, "--why").map_err(CliError::Usage)?;
+    let (board, _, _) = render_text::load(&request.source)?;
+    let plan = coach::plan(&board, board.revealed_safe() == 0)
+        .ok_or_else(|| CliError::Failed("the game is over, so there is no move".into()))?;
+    let target = cell_name(plan.target);
+    let mut out = match plan.click {
+        CellClick::Reveal(..) => format!("open {target}\n"),
+        CellClick::Flag(x, y) if board.cell(x, y).is_flagged() => format!("unflag {target}\n"),
+        CellClick::Flag(..) => format!("flag {target}\n"),
+    };
+    if request.explain {
+        out.push_str(&plan.why);
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// Play every deduction out on the position `words` name and print where
+/// that leaves it, after the reasons in the order applied for `--explain`.
+pub fn solve(words: &[String]) -> Result<String, CliError> {
+    let request = parse(words, SOLVE_USAGE, "--explain").map_err(CliError::Usage)?;
+    let (mut board, _, _) = render_text::load(&request.source)?;
+    let rounds = play_out(&mut board);
+    let mut out = String::new();
+    if request.explain {
+        for reasons in &rounds {
+            out.push_str(&capped(reasons, ROUND_LINES));
+        }
+    }
+    out.push_str(&DebugSnapshot::new(&board).to_string());
+    Ok(out)
+}
+
+/// Apply every deduction to `board` until none is left, a round at a
+/// time: the mines a round proves are flagged, then its safe cells are
+/// opened, a wrong flag taken off first. Returns each round's reasons,
+/// worded against the board as the round found it.
+pub fn play_out(board: &mut Board) -> Vec<Vec<String>> {
+    let width = board.width();
+    let mut known_mines = vec![false; width * board.height()];
+    let mut rounds = Vec::new();
+    while !board.is_over() {
+        let step = solver::step(board, &known_mines);
+        let mines: Vec<_> = step
+            .mines
+            .iter()
+            .copied()
+            .filter(|&(x, y)| !known_mines[y * width + x])
+            .collect();
+        if mines.is_empty() && step.safe.is_empty() {
+            break;
+        }
+        let mut reasons = Vec::new();
+        let deduced = mines.iter().map(|&cell| (cell, true));
+        for (cell, mine) in deduced.chain(step.safe.iter().map(|&cell| (cell, false))) {
+            if let Some(reason) = step.reason(cell.0, cell.1) {
+                reasons.push(coach::explain(board, reason, cell, mine));
+            }
+        }
+        for &(x, y) in &mines {
+            known_mines[y * width + x] = true;
+            if !board.cell(x, y).is_flagged() {
+                board.toggle_flag(x, y);
+            }
+        }
+        for &(x, y) in &step.safe {
+            if board.cell(x, y).is_flagged() {
+                board.toggle_flag(x, y);
+            }
+            board.reveal_cell(x, y);
+        }
+        rounds.push(reasons);
+    }
+    rounds
+}
+
+/// `lines` one to a line, the first `max` of them, then how many more.
+fn capped(lines: &[String], max: usize) -> String {
+    let mut out = String::new();
+    for line in lines.iter().take(max) {
+        out.push_str(line);
+        out.push('\n');
+    }
+    match lines.len().saturating_sub(max) {
+        0 => {}
+        1 => out.push_str("…and 1 more deduction\n"),
+        more => out.push_str(&format!("…and {more} more deductions\n")),
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(s: &str) -> Vec<String> {
+        s.split(' ').map(String::from).collect()
+    }
+
+    #[test]
+    fn the_flag_for_reasons_is_the_subcommands_own() {
+        assert_eq!(
+            parse(&words("--why --code 0a"), HINT_USAGE, "--why"),
+            Ok(Request {
+                source: Source::Code("0a".into()),
+                explain: true,
+            })
+        );
+        assert!(parse(&words("--code 0a --explain"), HINT_USAGE, "--why").is_err());
+        assert!(parse(&words("--explain"), SOLVE_USAGE, "--explain").is_err());
+    }
+
+    #[test]
+    fn a_hint_names_the_move_and_says_why() {
+        // the 1 at D1 proves C1
+        let mut board = Board::decode_layout("*.*....\n.......\n.......\n").unwrap();
+        board.reveal_cell(6, 2);
+        let code = minesweeper_rs::report::position_code(&board);
+        assert_eq!(
+            hint(&words(&format!("--code {code}"))).unwrap(),
+            "flag C1\n"
+        );
+        assert_eq!(
+            hint(&words(&format!("--code {code} --why"))).unwrap(),
+            "flag C1\nThe 1 at D1 has only as many hidden neighbours as mines it still \
+             needs, so C1 is a mine.\n"
+        );
+    }
+
+    /// The reasons `play_out` gives on `layout` opened at `start`.
+    fn reasons(layout: &str, start: (usize, usize)) -> (Vec<Vec<String>>, Board) {
+        let mut board = Board::decode_layout(layout).unwrap();
+        board.reveal_cell(start.0, start.1);
+        let rounds = play_out(&mut board);
+        (rounds, board)
+    }
+
+    #[test]
+    fn single_numbers_explain_in_the_order_applied() {
+        let (rounds, board) = reasons("*.*....\n.......\n.......\n", (6, 2));
+        assert_eq!(
+            rounds,
+            [
+                vec![
+                    "The 1 at D1 has only as many hidden neighbours as mines it still \
+                      needs, so C1 is a mine."
+                ],
+                vec![
+                    "The 1 at C2 already touches 1 known mine, so its other hidden \
+                      neighbours, B1 included, are safe."
+                ],
+            ]
+        );
+        // B1 was the last safe cell, so A1 is left to the win
+        assert!(board.is_won());
+    }
+
+    #[test]
+    fn subsets_explain_the_difference() {
+        let (rounds, board) = reasons("*.*.\n....\n....\n", (3, 2));
+        assert_eq!(
+            rounds,
+            [
+                vec![
+                    "Every hidden neighbour of the 1 at A2 also touches the 2 at B2, which \
+                     needs one more mine for each of its other hidden neighbours, so C1 is \
+                     a mine.",
+                    "Every hidden neighbour of the 1 at D2 also touches the 1 at C2, and \
+                     both still need the same number of mines, so B1 is safe.",
+                ],
+                vec![
+                    "The 2 at B1 has only as many hidden neighbours as mines it still \
+                     needs, so A1 is a mine.",
+                    "The 1 at C2 already touches 1 known mine, so its other hidden \
+                     neighbours, D1 included, are safe.",
+                ],
+            ]
+        );
+        assert!(board.is_won());
+    }
+
+    #[test]
+    fn long_rounds_are_cut_short() {
+        let lines: Vec<String> = (0..25).map(|i| i.to_string()).collect();
+        let text = capped(&lines, 20);
+        assert_eq!(text.lines().count(), 21);
+        assert!(text.ends_with("19\n…and 5 more deductions\n"));
+        assert!(capped(&lines[..21], 20).ends_with("…and 1 more deduction\n"));
+        assert_eq!(capped(&lines[..2], 20), "0\n1\n");
+    }
+}
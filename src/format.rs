@@ -0,0 +1,128 @@
+//! Times, dates and counts as the player reads them, in one place so the
+//! game, the CLI and the summaries agree. (File formats are in
+//! [`formats`](crate::formats).)
+//!
+//! These are the English forms. Machine output, CSV and JSON, doesn't come
+//! through here: it writes plain numbers, `1234` and `61.5`, whatever the
+//! player reads.
+//!
+//! ```
+//! use minesweeper_rs::format;
+//!
+//! assert_eq!(format::clock(65.9), "1:05");
+//! assert_eq!(format::clock_tenths(65.94), "1:05.9");
+//! assert_eq!(format::clock_millis(8_250), "0:08.250");
+//! assert_eq!(format::date(2025, 2, 3), "Mon 3 Feb 2025");
+//! assert_eq!(format::count(12_345), "12,345");
+//! ```
+
+/// Whole seconds on a clock: `M:SS` under an hour, `H:MM:SS` from one.
+/// Fractions are dropped, so the clock never runs ahead, and anything
+/// below zero, or not a number, reads `0:00`.
+pub fn clock(secs: f64) -> String {
+    hms(whole(secs))
+}
+
+/// A time to the nearest tenth of a second, as races and results give it:
+/// `0:12.3`, `1:00:05.0`.
+pub fn clock_tenths(secs: f64) -> String {
+    let tenths = whole(secs * 10.0 + 0.5);
+    format!("{}.{}", hms(tenths / 10), tenths % 10)
+}
+
+/// `millis` milliseconds to the millisecond, as the leaderboard keeps
+/// them: `0:08.250`.
+pub fn clock_millis(millis: u64) -> String {
+    format!("{}.{:03}", hms(millis / 1000), millis % 1000)
+}
+
+/// `secs` as a whole number, 0 when it is negative or not a number.
+fn whole(secs: f64) -> u64 {
+    if secs.is_finite() && secs > 0.0 {
+        secs as u64
+    } else {
+        0
+    }
+}
+
+fn hms(secs: u64) -> String {
+    let (hours, minutes, secs) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if hours > 0 {
+        format!("{hours}:{minutes:02}:{secs:02}")
+    } else {
+        format!("{minutes}:{secs:02}")
+    }
+}
+
+const WEEKDAYS: [&str; 7] = [
+    "Sunday",
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+];
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+/// A day of the Gregorian calendar, short: `Mon 3 Feb 2025`. `month` and
+/// `day` count from 1.
+pub fn date(year: i32, month: u32, day: u32) -> String {
+    let weekday = &WEEKDAYS[weekday(year, month, day)][..3];
+    format!("{weekday} {day} {} {year}", month_short(month))
+}
+
+/// A day in full: `Monday 3 February 2025`.
+pub fn long_date(year: i32, month: u32, day: u32) -> String {
+    let weekday = WEEKDAYS[weekday(year, month, day)];
+    format!("{weekday} {day} {} {year}", month_name(month))
+}
+
+/// A month's name, `February`, counting from 1; out of range, the nearest.
+pub fn month_name(month: u32) -> &'static str {
+    MONTHS[month.clamp(1, 12) as usize - 1]
+}
+
+/// A month's name in three letters: `Feb`.
+pub fn month_short(month: u32) -> &'static str {
+    &month_name(month)[..3]
+}
+
+/// The day of the week, Sunday 0, by Sakamoto's method.
+fn weekday(year: i32, month: u32, day: u32) -> usize {
+    const OFFSETS: [i32; 12] = [0, 3, 2, 5, 0, 3, 5, 1, 4, 6, 2, 4];
+    let month = month.clamp(1, 12);
+    let year = if month < 3 { year - 1 } else { year };
+    let days = year + year.div_euclid(4) - year.div_euclid(100)
+        + year.div_euclid(400)
+        + OFFSETS[month as usize - 1]
+        + day as i32;
+    days.rem_euclid(7) as usize
+}
+
+/// `n` with its thousands grouped: `1,234,567`.
+pub fn count(n: u64) -> String {
+    let digits = n.to_string();
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
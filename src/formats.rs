@@ -0,0 +1,77 @@
+//! Boards written down in other ways than our own puzzle files, and
+//! [`read`] for text pasted in without saying which.
+
+use crate::puzzle::{Puzzle, PuzzleError};
+use crate::report::{self, CodeError};
+use crate::Board;
+use std::fmt;
+
+pub mod tatham;
+
+pub use tatham::TathamError;
+
+/// The board in `text`, whichever of these it is: a game ID of Simon
+/// Tatham's "Mines" (see [`tatham`]), a position code (see
+/// [`report::position_code`]) or a puzzle file, a bare layout included
+/// (see [`puzzle`](crate::puzzle)). Only the mines and the start are
+/// kept; a position code's play state is dropped.
+///
+/// ```
+/// use minesweeper_rs::formats;
+///
+/// let tatham = formats::read("3x2:2,1,u80")?;
+/// let layout = formats::read("*..\n...\n")?;
+/// assert_eq!(tatham.board.encode_layout(), layout.board.encode_layout());
+/// assert_eq!((tatham.start, layout.start), (Some((2, 1)), None));
+/// # Ok::<(), formats::ReadError>(())
+/// ```
+pub fn read(text: &str) -> Result<Puzzle, ReadError> {
+    let text = text.trim();
+    // the size before the colon starts with a digit; a layout never does,
+    // nor has a colon, and a position code has no colon
+    if text.starts_with(|c: char| c.is_ascii_digit()) && text.contains(':') {
+        return tatham::decode(text).map_err(ReadError::Tatham);
+    }
+    if !text.is_empty()
+        && text
+            .chars()
+            .all(|c| c.is_ascii_hexdigit() || c.is_whitespace())
+    {
+        let board = report::decode_position_code(text).map_err(ReadError::Code)?;
+        let board = Board::decode_layout(&board.encode_layout())
+            .expect("a decoded board's layout reads back");
+        return Ok(Puzzle::new(board));
+    }
+    text.parse().map_err(ReadError::Puzzle)
+}
+
+/// Why pasted text couldn't be read, as the format it looked like.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReadError {
+    /// It looked like a Tatham game ID.
+    Tatham(TathamError),
+    /// It looked like a position code.
+    Code(CodeError),
+    /// It looked like a puzzle file or layout.
+    Puzzle(PuzzleError),
+}
+
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReadError::Tatham(err) => write!(f, "not a Tatham game ID: {err}"),
+            ReadError::Code(err) => write!(f, "{err}"),
+            ReadError::Puzzle(err) => write!(f, "not a board: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for ReadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ReadError::Tatham(err) => Some(err),
+            ReadError::Code(err) => Some(err),
+            ReadError::Puzzle(err) => Some(err),
+        }
+    }
+}
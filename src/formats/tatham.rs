@@ -0,0 +1,286 @@
+//! Game IDs of "Mines" from Simon Tatham's Portable Puzzle Collection, so
+//! a board can be pasted from the web version and played here, or sent
+//! the other way.
+//!
+//! An ID is the parameters, a colon and the description:
+//!
+//! ```text
+//! 9x9:4,4,md6e795bf28b5f9d4b9750
+//! ```
+//!
+//! The parameters are the width and height; the description is the cell
+//! the game opens from as `x,y,` (it may be left out), then `m` for a
+//! masked bitmap or `u` (or nothing) for a plain one, then the mines: one
+//! bit per cell, row by row, the first cell in each byte's top bit, as a
+//! hex digit for every four cells. Masking XORs each half of the bitmap
+//! with a SHA-1 stream keyed on the other half, exactly as the original's
+//! `obfuscate_bitmap` does, so that the mines can't be read off the ID.
+//!
+//! The parameters may also carry the mine count (`n10`) and `a` for a
+//! board made without the unique-solution guarantee. Both only steer the
+//! original's generator, and a layout is read the same with or without
+//! them, as there. That generator also explains what can't be read: a
+//! random seed (`9x9n10#1234`) and a description still waiting for its
+//! first click (`r10,u,…`) only become boards inside it.
+//!
+//! ```
+//! use minesweeper_rs::formats::tatham;
+//!
+//! let puzzle = tatham::decode("3x2:2,1,u80")?;
+//! assert_eq!(puzzle.board.encode_layout(), "*..\n...\n");
+//! assert_eq!(puzzle.start, Some((2, 1)));
+//! assert_eq!(tatham::decode(&tatham::encode(&puzzle))?.start, Some((2, 1)));
+//! # Ok::<(), tatham::TathamError>(())
+//! ```
+
+use crate::puzzle::Puzzle;
+use crate::{Board, MAX_CELLS};
+use std::fmt;
+
+/// A board from the game ID `id`, opened from the cell the ID names if it
+/// names one. Whitespace around the ID is ignored.
+pub fn decode(id: &str) -> Result<Puzzle, TathamError> {
+    let id = id.trim();
+    if id.contains('#') {
+        return Err(TathamError::Seed);
+    }
+    let (params, desc) = id.split_once(':').ok_or(TathamError::NoDescription)?;
+    let (width, height) = size(params).ok_or(TathamError::Size)?;
+    let cells = width
+        .checked_mul(height)
+        .filter(|&cells| cells <= MAX_CELLS)
+        .ok_or(TathamError::TooLarge)?;
+    if desc.starts_with('r') {
+        return Err(TathamError::Seed);
+    }
+    let (start, desc) = start(desc, width, height).ok_or(TathamError::Start)?;
+    let (masked, hex) = match desc.as_bytes().first() {
+        Some(b'm') => (true, &desc[1..]),
+        Some(b'u') => (false, &desc[1..]),
+        _ => (false, desc),
+    };
+    let digits = cells.div_ceil(4);
+    if hex.len() != digits {
+        return Err(TathamError::Length(digits, hex.len()));
+    }
+    let mut bitmap = vec![0u8; cells.div_ceil(8)];
+    for (i, c) in hex.chars().enumerate() {
+        let value = c.to_digit(16).ok_or(TathamError::NotHex)? as u8;
+        bitmap[i / 2] |= value << (4 * (1 - i % 2));
+    }
+    if masked {
+        obfuscate(&mut bitmap, cells, true);
+    }
+
+    let mut layout = String::with_capacity((width + 1) * height);
+    for i in 0..cells {
+        let mine = bitmap[i / 8] & (0x80 >> (i % 8)) != 0;
+        layout.push(if mine { '*' } else { '.' });
+        if i % width == width - 1 {
+            layout.push('\n');
+        }
+    }
+    let board = Board::decode_layout(&layout).map_err(|_| TathamError::TooLarge)?;
+    if let Some((x, y)) = start {
+        if layout.as_bytes()[y * (width + 1) + x] == b'*' {
+            return Err(TathamError::Start);
+        }
+    }
+    Ok(Puzzle {
+        start,
+        ..Puzzle::new(board)
+    })
+}
+
+/// The game ID for `puzzle`'s mines, opened from its start if it has one,
+/// masked as the original writes its own.
+pub fn encode(puzzle: &Puzzle) -> String {
+    let board = &puzzle.board;
+    let cells = board.width() * board.height();
+    let mut bitmap = vec![0u8; cells.div_ceil(8)];
+    let layout = board.encode_layout();
+    let mines = layout.bytes().filter(|&b| b != b'\n');
+    for (i, cell) in mines.enumerate() {
+        if cell == b'*' {
+            bitmap[i / 8] |= 0x80 >> (i % 8);
+        }
+    }
+    obfuscate(&mut bitmap, cells, false);
+
+    let mut id = format!("{}x{}:", board.width(), board.height());
+    if let Some((x, y)) = puzzle.start {
+        id.push_str(&format!("{x},{y},"));
+    }
+    id.push('m');
+    for i in 0..cells.div_ceil(4) {
+        let value = bitmap[i / 2] >> (4 * (1 - i % 2)) & 0xF;
+        id.push(char::from_digit(u32::from(value), 16).expect("a nibble is a hex digit"));
+    }
+    id
+}
+
+/// The width and height the parameters start with: `WxH`, or just `W`
+/// for a square.
+fn size(params: &str) -> Option<(usize, usize)> {
+    let (width, rest) = number(params)?;
+    let height = match rest.strip_prefix('x') {
+        Some(rest) => number(rest)?.0,
+        None => width,
+    };
+    (width > 0 && height > 0).then_some((width, height))
+}
+
+/// The cell `x,y,` at the front of `desc`, if there is one, and what
+/// follows. `None` if it's there but malformed or off the board.
+fn start(desc: &str, width: usize, height: usize) -> Option<(Option<(usize, usize)>, &str)> {
+    if !desc.starts_with(|c: char| c.is_ascii_digit()) {
+        return Some((None, desc));
+    }
+    let (x, rest) = number(desc)?;
+    let (y, rest) = number(rest.strip_prefix(',')?)?;
+    let rest = rest.strip_prefix(',')?;
+    (x < width && y < height).then_some((Some((x, y)), rest))
+}
+
+/// The decimal number at the front of `text`, and what follows it.
+fn number(text: &str) -> Option<(usize, &str)> {
+    let end = text
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(text.len());
+    Some((text[..end].parse().ok()?, &text[end..]))
+}
+
+/// Mask `bitmap`, which holds `bits` cells, or unmask it with `decode`.
+/// The first half of the bytes is XORed with SHA-1 of the second half
+/// followed by a decimal counter, then the second half with SHA-1 of the
+/// new first half; unmasking takes the steps the other way round. The
+/// pad bits at the end are cleared after each step.
+fn obfuscate(bitmap: &mut [u8], bits: usize, decode: bool) {
+    let first = bitmap.len() / 2;
+    // each step: whether the key is the first half, the target the other
+    let mut steps = [false, true];
+    if decode {
+        steps.reverse();
+    }
+    for key_first in steps {
+        let (front, back) = bitmap.split_at_mut(first);
+        let (key, target) = if key_first {
+            (&*front, back)
+        } else {
+            (&*back, front)
+        };
+        let mut stream = Vec::with_capacity(target.len() + 20);
+        let mut counter = 0;
+        while stream.len() < target.len() {
+            let mut input = key.to_vec();
+            input.extend_from_slice(counter.to_string().as_bytes());
+            stream.extend_from_slice(&sha1(&input));
+            counter += 1;
+        }
+        for (byte, mask) in target.iter_mut().zip(stream) {
+            *byte ^= mask;
+        }
+        if !bits.is_multiple_of(8) {
+            bitmap[bits / 8] &= (0xFF00u16 >> (bits % 8)) as u8;
+        }
+    }
+}
+
+/// SHA-1 of `data`, which the masking is defined in terms of.
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut h: [u32; 5] = [
+        0x6745_2301,
+        0xEFCD_AB89,
+        0x98BA_DCFE,
+        0x1032_5476,
+        0xC3D2_E1F0,
+    ];
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&(data.len() as u64 * 8).to_be_bytes());
+    for block in message.chunks_exact(64) {
+        let mut w = [0u32; 80];
+        for (i, word) in block.chunks_exact(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+        let [mut a, mut b, mut c, mut d, mut e] = h;
+        for (i, &word) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5A82_7999),
+                20..=39 => (b ^ c ^ d, 0x6ED9_EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1B_BCDC),
+                _ => (b ^ c ^ d, 0xCA62_C1D6),
+            };
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+        for (h, v) in h.iter_mut().zip([a, b, c, d, e]) {
+            *h = h.wrapping_add(v);
+        }
+    }
+    let mut digest = [0; 20];
+    for (out, h) in digest.chunks_exact_mut(4).zip(h) {
+        out.copy_from_slice(&h.to_be_bytes());
+    }
+    digest
+}
+
+/// Why a game ID couldn't be read.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TathamError {
+    /// There is no colon between the parameters and the description.
+    NoDescription,
+    /// The parameters don't start with a width and height.
+    Size,
+    /// The board would have more than [`MAX_CELLS`] cells.
+    TooLarge,
+    /// A random seed, or a board that is made on the first click.
+    Seed,
+    /// The cell opened first isn't `x,y,` on the board, or is a mine.
+    Start,
+    /// The mine bitmap should have this many hex digits, but has that many.
+    Length(usize, usize),
+    /// The mine bitmap isn't all hex digits.
+    NotHex,
+}
+
+impl fmt::Display for TathamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TathamError::NoDescription => {
+                f.write_str("a game ID is the size, a colon and the board")
+            }
+            TathamError::Size => f.write_str("a game ID starts with the size, as WIDTHxHEIGHT"),
+            TathamError::TooLarge => f.write_str("the game ID describes an oversized board"),
+            TathamError::Seed => f.write_str(
+                "that is a seed, which only becomes a board in the original; open it \
+                 there, make the first click and copy the game ID it shows then",
+            ),
+            TathamError::Start => {
+                f.write_str("the cell opened first must be a safe cell on the board")
+            }
+            TathamError::Length(expected, found) => write!(
+                f,
+                "the mines should be {expected} hex digits for this size, not {found}"
+            ),
+            TathamError::NotHex => f.write_str("the mines should be written as hex digits"),
+        }
+    }
+}
+
+impl std::error::Error for TathamError {}
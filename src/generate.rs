@@ -0,0 +1,270 @@
+//! No-guess board generation. Candidates are built from seeds derived from
+//! one base seed and checked by the solver on several threads at once; the
+//! lowest-numbered candidate that passes wins, so the result depends only on
+//! the base seed and not on thread timing.
+
+use crate::{solver, Board, BoardError, BoardOptions};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::thread;
+use tracing::{debug, field, info, info_span};
+
+/// Give up (and fall back to a plain safe-start board) after this many.
+pub const MAX_ATTEMPTS: u32 = 5000;
+
+/// Largest board, in cells, that no-guess generation is offered for.
+pub const MAX_CELLS: usize = 40_000;
+
+/// Whether no-guess generation is worth offering for a board: it is no
+/// larger than [`MAX_CELLS`], and the first click's neighbours can be kept
+/// clear so it opens an area to deduce from.
+///
+/// ```
+/// use minesweeper_rs::generate::feasible;
+///
+/// assert!(feasible(9, 9, 72));
+/// assert!(!feasible(9, 9, 73));
+/// assert!(!feasible(300, 300, 10));
+/// ```
+pub fn feasible(width: usize, height: usize, mines: usize) -> bool {
+    let cells = width * height;
+    cells <= MAX_CELLS && cells.saturating_sub(mines) >= 9
+}
+
+/// Seed for candidate number `attempt`, mixed so neighbouring attempts
+/// don't get correlated RNG streams.
+///
+/// ```
+/// use minesweeper_rs::generate::derive_seed;
+///
+/// assert_ne!(derive_seed(1, 0), derive_seed(1, 1));
+/// ```
+pub fn derive_seed(base: u64, attempt: u32) -> u64 {
+    let mut z = base ^ (attempt as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Find a board that can be cleared without guessing after opening `start`.
+/// `progress` receives the number of the attempt being started; setting
+/// `abort` stops all workers and returns `None`. The returned board's `seed`
+/// is the derived seed, which rebuilds it on its own via `Board::with_seed`.
+///
+/// ```
+/// use minesweeper_rs::{generate, solver};
+/// use std::sync::atomic::AtomicBool;
+///
+/// let abort = AtomicBool::new(false);
+/// let board = generate::no_guess(9, 9, 10, (4, 4), 1, &abort, &|_| {}).unwrap();
+/// assert_eq!(solver::solvable_from(&board, (4, 4), &|| false), Some(true));
+/// ```
+pub fn no_guess(
+    width: usize,
+    height: usize,
+    mines: usize,
+    start: (usize, usize),
+    base_seed: u64,
+    abort: &AtomicBool,
+    progress: &(dyn Fn(u32) + Sync),
+) -> Option<Board> {
+    let options = BoardOptions::new(width, height, mines);
+    no_guess_with(&options, start, base_seed, abort, progress).ok()?
+}
+
+/// As [`no_guess`] for a board of `options`, so a symmetry or spacing
+/// holds too; the safe start and seed are set here. Options that can't
+/// be honoured at all are refused before any attempt, and the board that
+/// comes back rebuilds from its seed with these options.
+///
+/// ```
+/// use minesweeper_rs::{generate, solver, BoardError, BoardOptions, Symmetry};
+/// use std::sync::atomic::AtomicBool;
+///
+/// let abort = AtomicBool::new(false);
+/// let options = BoardOptions::new(9, 9, 10).symmetry(Symmetry::Vertical);
+/// let board = generate::no_guess_with(&options, (4, 4), 1, &abort, &|_| {})?.unwrap();
+/// assert_eq!(solver::solvable_from(&board, (4, 4), &|| false), Some(true));
+///
+/// let odd = BoardOptions::new(8, 8, 9).symmetry(Symmetry::Rotational180);
+/// let refused = generate::no_guess_with(&odd, (4, 4), 1, &abort, &|_| {});
+/// assert_eq!(refused.err(), Some(BoardError::Unplaceable));
+/// # Ok::<(), BoardError>(())
+/// ```
+pub fn no_guess_with(
+    options: &BoardOptions,
+    start: (usize, usize),
+    base_seed: u64,
+    abort: &AtomicBool,
+    progress: &(dyn Fn(u32) + Sync),
+) -> Result<Option<Board>, BoardError> {
+    let workers = thread::available_parallelism().map_or(1, |n| n.get().min(8));
+    no_guess_on(workers, options, start, base_seed, abort, progress)
+}
+
+/// As [`no_guess_with`] on exactly `workers` threads (at least one), rather
+/// than one per core up to eight. The board found is the same for any
+/// count; only the time taken changes, which the `no_guess` bench shows.
+pub fn no_guess_on(
+    workers: usize,
+    options: &BoardOptions,
+    start: (usize, usize),
+    base_seed: u64,
+    abort: &AtomicBool,
+    progress: &(dyn Fn(u32) + Sync),
+) -> Result<Option<Board>, BoardError> {
+    search(
+        workers,
+        MAX_ATTEMPTS,
+        options,
+        start,
+        base_seed,
+        abort,
+        progress,
+    )
+}
+
+/// The search behind [`no_guess_on`], giving up after `max_attempts`.
+fn search(
+    workers: usize,
+    max_attempts: u32,
+    options: &BoardOptions,
+    start: (usize, usize),
+    base_seed: u64,
+    abort: &AtomicBool,
+    progress: &(dyn Fn(u32) + Sync),
+) -> Result<Option<Board>, BoardError> {
+    let options = options.clone().safe_start(start.0, start.1);
+    options.check()?;
+    let (width, height, mines) = options.params();
+    let span = info_span!("no_guess", width, height, mines, attempts = field::Empty);
+    let _entered = span.enter();
+    // the seed rebuilds the mine layout, so keep it out of info logs
+    debug!(base_seed, "generating");
+
+    let next = AtomicU32::new(0);
+    let best = AtomicU32::new(u32::MAX);
+
+    thread::scope(|scope| {
+        for _ in 0..workers.max(1) {
+            scope.spawn(|| {
+                // one board per worker, reset for each attempt
+                let mut board = Board::blank(width, height, mines);
+                loop {
+                    let attempt = next.fetch_add(1, Ordering::Relaxed);
+                    if attempt >= max_attempts
+                        || attempt > best.load(Ordering::Relaxed)
+                        || abort.load(Ordering::Relaxed)
+                    {
+                        break;
+                    }
+                    progress(attempt + 1);
+
+                    let options = options.clone().seed(derive_seed(base_seed, attempt));
+                    if board.reset_in_place(&options).is_err() {
+                        continue;
+                    }
+                    // a lower attempt already succeeded, so this one can't win
+                    let should_stop =
+                        || abort.load(Ordering::Relaxed) || best.load(Ordering::Relaxed) < attempt;
+                    if solver::solvable_from(&board, start, &should_stop) == Some(true) {
+                        best.fetch_min(attempt, Ordering::Relaxed);
+                    }
+                }
+            });
+        }
+    });
+
+    let attempt = best.into_inner();
+    span.record("attempts", next.into_inner().min(max_attempts));
+    if abort.load(Ordering::Relaxed) {
+        info!("aborted");
+        return Ok(None);
+    }
+    if attempt == u32::MAX {
+        info!("no solvable board found");
+        return Ok(None);
+    }
+    info!(attempt, "found solvable board");
+    Ok(options.seed(derive_seed(base_seed, attempt)).build().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    fn expert() -> BoardOptions {
+        BoardOptions::new(30, 16, 99)
+    }
+
+    /// Whether candidate `attempt` from `base_seed` passes on its own.
+    fn passes(options: &BoardOptions, start: (usize, usize), base_seed: u64, attempt: u32) -> bool {
+        let board = (options.clone().safe_start(start.0, start.1))
+            .seed(derive_seed(base_seed, attempt))
+            .build()
+            .unwrap();
+        solver::solvable_from(&board, start, &|| false) == Some(true)
+    }
+
+    #[test]
+    fn the_lowest_passing_attempt_wins_on_any_thread_count() {
+        let abort = AtomicBool::new(false);
+        for base_seed in 0..4 {
+            let boards: Vec<Board> = [1, 2, 3, 8]
+                .into_iter()
+                .map(|workers| {
+                    no_guess_on(workers, &expert(), (15, 8), base_seed, &abort, &|_| {})
+                        .unwrap()
+                        .unwrap()
+                })
+                .collect();
+            for board in &boards[1..] {
+                assert_eq!(board.seed(), boards[0].seed());
+                assert_eq!(board.encode_layout(), boards[0].encode_layout());
+            }
+            let attempt = (0..MAX_ATTEMPTS)
+                .find(|&n| Some(derive_seed(base_seed, n)) == boards[0].seed())
+                .unwrap();
+            assert!(passes(&expert(), (15, 8), base_seed, attempt));
+            assert!((0..attempt).all(|n| !passes(&expert(), (15, 8), base_seed, n)));
+        }
+    }
+
+    #[test]
+    fn an_abort_stops_every_worker() {
+        let set = AtomicBool::new(true);
+        let started = AtomicU32::new(0);
+        let count = |_| {
+            started.fetch_add(1, Ordering::Relaxed);
+        };
+        let found = no_guess_on(4, &expert(), (15, 8), 1, &set, &count);
+        assert_eq!(found.map(|b| b.is_some()), Ok(false));
+        assert_eq!(started.load(Ordering::Relaxed), 0);
+
+        // aborted partway, from the progress callback as a UI would
+        let abort = AtomicBool::new(false);
+        let stop = |attempt| {
+            if attempt == 3 {
+                abort.store(true, Ordering::Relaxed);
+            }
+        };
+        let dense = BoardOptions::new(30, 16, 200);
+        let found = no_guess_on(4, &dense, (15, 8), 1, &abort, &stop);
+        assert_eq!(found.map(|b| b.is_some()), Ok(false));
+    }
+
+    #[test]
+    fn it_gives_up_after_the_last_attempt() {
+        // far too dense for any of the few candidates to pass
+        let dense = BoardOptions::new(30, 16, 200);
+        assert!((0..20).all(|n| !passes(&dense, (15, 8), 1, n)));
+        let abort = AtomicBool::new(false);
+        let started = Mutex::new(Vec::new());
+        let log = |attempt| started.lock().unwrap().push(attempt);
+        let found = search(4, 20, &dense, (15, 8), 1, &abort, &log);
+        assert_eq!(found.map(|b| b.is_some()), Ok(false));
+        let mut started = started.into_inner().unwrap();
+        started.sort_unstable();
+        assert_eq!(started, (1..=20).collect::<Vec<_>>());
+    }
+}
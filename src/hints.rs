@@ -0,0 +1,208 @@
+//! A hint ladder: asking again on the same position gets a stronger hint,
+//! and a costlier one.
+//!
+//! The first hint outlines the region where something can be worked out,
+//! the second points at the number that settles it, and the third gives the
+//! answer for the frontend to play. All three are about the same deduction,
+//! the first the solver finds, and any change to the board starts the
+//! ladder over. Each rung adds [`PENALTY_SECS`] to the clock.
+//!
+//! ```
+//! use minesweeper_rs::hints::{Hint, HintLadder};
+//! use minesweeper_rs::Board;
+//!
+//! let mut board = Board::decode_layout("*...\n....\n...*\n")?;
+//! board.reveal_cell(3, 0);
+//! let mut ladder = HintLadder::default();
+//! assert!(matches!(ladder.next(&board), Some(Hint::Region(_))));
+//! assert!(matches!(ladder.next(&board), Some(Hint::Numbers(_))));
+//! assert!(matches!(ladder.next(&board), Some(Hint::Answer { .. })));
+//! assert_eq!(ladder.penalty_secs(), 35);
+//! # Ok::<(), minesweeper_rs::LayoutError>(())
+//! ```
+
+use crate::render::column_name;
+use crate::solver::{self, Reason};
+use crate::Board;
+use std::fmt;
+
+/// Seconds each rung of the ladder costs.
+pub const PENALTY_SECS: [u32; 3] = [5, 10, 20];
+
+/// One hint, weakest first.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Hint {
+    /// Somewhere among these cells, the numbers and the hidden cells they
+    /// touch, something can be worked out.
+    Region(Vec<(usize, usize)>),
+    /// These numbers settle it; none when it's the mine count that does.
+    Numbers(Vec<(usize, usize)>),
+    /// The cell itself, and whether it is a mine.
+    Answer {
+        /// The deduced cell.
+        cell: (usize, usize),
+        /// True for a mine to flag, false for a safe cell to open.
+        mine: bool,
+    },
+}
+
+impl Hint {
+    /// The rung this hint is on, from 1.
+    pub fn level(&self) -> usize {
+        match self {
+            Hint::Region(_) => 1,
+            Hint::Numbers(_) => 2,
+            Hint::Answer { .. } => 3,
+        }
+    }
+
+    /// The cells to point out on the board.
+    pub fn cells(&self) -> Vec<(usize, usize)> {
+        match self {
+            Hint::Region(cells) | Hint::Numbers(cells) => cells.clone(),
+            Hint::Answer { cell, .. } => vec![*cell],
+        }
+    }
+}
+
+impl fmt::Display for Hint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = |&(x, y): &(usize, usize)| format!("{}{}", column_name(x), y + 1);
+        match self {
+            Hint::Region(cells) => {
+                let (xs, ys) = (cells.iter().map(|c| c.0), cells.iter().map(|c| c.1));
+                let (Some(left), Some(right)) = (xs.clone().min(), xs.max()) else {
+                    return f.write_str("Something can be worked out.");
+                };
+                let (top, bottom) = (ys.clone().min().unwrap_or(0), ys.max().unwrap_or(0));
+                write!(
+                    f,
+                    "Something can be worked out around {}:{}.",
+                    name(&(left, top)),
+                    name(&(right, bottom))
+                )
+            }
+            Hint::Numbers(numbers) if numbers.is_empty() => {
+                f.write_str("Count the mines left against the hidden cells.")
+            }
+            Hint::Numbers(numbers) => {
+                let names: Vec<String> = numbers.iter().map(name).collect();
+                write!(
+                    f,
+                    "Look at the number at {}.",
+                    names.join(" and the one at ")
+                )
+            }
+            Hint::Answer { cell, mine: true } => write!(f, "{} is a mine.", name(cell)),
+            Hint::Answer { cell, mine: false } => write!(f, "{} is safe.", name(cell)),
+        }
+    }
+}
+
+/// Where the player is on the ladder, and what the hints have cost so far.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HintLadder {
+    climbing: Option<Climb>,
+    used: [u32; 3], // hints taken at each level
+}
+
+/// The deduction being hinted at, and for which position.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Climb {
+    revision: u64,
+    level: usize,
+    cell: (usize, usize),
+    mine: bool,
+    reason: Reason,
+}
+
+impl HintLadder {
+    /// The next hint for `board`: a rung up if nothing changed since the
+    /// last one, else the first rung for whatever the solver finds now.
+    /// `None` when nothing is certain, which costs nothing.
+    pub fn next(&mut self, board: &Board) -> Option<Hint> {
+        let same = self
+            .climbing
+            .filter(|climb| climb.revision == board.revision() && climb.level < 3);
+        let climb = match same {
+            Some(climb) => Climb {
+                level: climb.level + 1,
+                ..climb
+            },
+            None => {
+                let (cell, mine, reason) = deduction(board)?;
+                Climb {
+                    revision: board.revision(),
+                    level: 1,
+                    cell,
+                    mine,
+                    reason,
+                }
+            }
+        };
+        self.climbing = Some(climb);
+        self.used[climb.level - 1] += 1;
+        Some(match climb.level {
+            1 => Hint::Region(region(board, climb.reason, climb.cell)),
+            2 => Hint::Numbers(climb.reason.numbers()),
+            _ => Hint::Answer {
+                cell: climb.cell,
+                mine: climb.mine,
+            },
+        })
+    }
+
+    /// Hints taken at each level, weakest first.
+    pub fn used(&self) -> [u32; 3] {
+        self.used
+    }
+
+    /// Seconds the hints taken so far add to the clock.
+    pub fn penalty_secs(&self) -> u32 {
+        self.used
+            .iter()
+            .zip(PENALTY_SECS)
+            .map(|(n, secs)| n * secs)
+            .sum()
+    }
+}
+
+/// The first thing the solver proves that the player hasn't marked: an
+/// unflagged mine, or a safe cell.
+fn deduction(board: &Board) -> Option<((usize, usize), bool, Reason)> {
+    if board.is_over() {
+        return None;
+    }
+    let analysis = solver::analyze(board);
+    let mine = analysis
+        .mines()
+        .iter()
+        .find(|&&(x, y)| !board.cell(x, y).is_flagged())
+        .map(|&cell| (cell, true));
+    let (cell, mine) = mine.or_else(|| analysis.safe().first().map(|&cell| (cell, false)))?;
+    Some((cell, mine, analysis.reason(cell.0, cell.1)?))
+}
+
+/// The numbers behind `reason` and every hidden cell they touch, the
+/// deduced cell among them, in row-major order. The mine count has no
+/// numbers, so there it is every hidden cell.
+fn region(board: &Board, reason: Reason, cell: (usize, usize)) -> Vec<(usize, usize)> {
+    let numbers = reason.numbers();
+    let mut cells: Vec<(usize, usize)> = if numbers.is_empty() {
+        (0..board.height())
+            .flat_map(|y| (0..board.width()).map(move |x| (x, y)))
+            .filter(|&(x, y)| !board.cell(x, y).is_revealed())
+            .collect()
+    } else {
+        let hidden = numbers.iter().flat_map(|&(x, y)| {
+            board
+                .neighbors(x, y)
+                .filter(|&(nx, ny)| !board.cell(nx, ny).is_revealed())
+        });
+        numbers.iter().copied().chain(hidden).collect()
+    };
+    cells.push(cell);
+    cells.sort_unstable_by_key(|&(x, y)| (y, x));
+    cells.dedup();
+    cells
+}
@@ -0,0 +1,608 @@
+//! Lifetime totals across finished games, plus the last few results, a
+//! dated log for the history calendar, the last few boards kept whole for
+//! a rematch, the daily and weekly challenges played and how the
+//! probability trainer has gone, persisted per profile next to the
+//! settings.
+
+use crate::challenge::Challenges;
+use chrono::NaiveDate;
+use minesweeper_rs::rating::{self, Played};
+use minesweeper_rs::stats::InputStats;
+use minesweeper_rs::trainer::GAPS;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+
+/// Finished games kept in `History::recent`.
+const RECENT_GAMES: usize = 20;
+
+/// Finished games kept in `History::log`, a few months' worth of steady
+/// play.
+const LOGGED_GAMES: usize = 2000;
+
+/// Boards kept whole in `History::boards`, and so listed on the menu.
+pub const RECENT_BOARDS: usize = 15;
+
+/// Trainer answers kept in `Trainer::recent`.
+const RECENT_ANSWERS: usize = 50;
+
+/// Right answers in a row that move the trainer up a level, and misses at
+/// a level that move it back down.
+const LEVEL_UP: u32 = 4;
+const LEVEL_DOWN: u32 = 2;
+
+/// The player's local date, which games are logged and shown under.
+pub fn today() -> NaiveDate {
+    chrono::Local::now().date_naive()
+}
+
+/// How one finished game went, for difficulty suggestions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Outcome {
+    pub size: (usize, usize, usize), // width, height, mines
+    pub won: bool,
+}
+
+/// The fastest win at one board size.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Best {
+    pub size: (usize, usize, usize),
+    pub secs: f64,
+    #[serde(default)]
+    pub assisted: bool, // best-opening start or endgame outlook, see `Game::assisted`
+}
+
+/// One finished game in the log.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Game {
+    pub day: NaiveDate, // local date it finished on
+    pub size: (usize, usize, usize),
+    pub won: bool,
+    pub secs: f64,
+    pub three_bv: usize,
+    /// Started from the best opening, which is read off the layout the
+    /// player can't see, or played with the endgame outlook on screen, so
+    /// its time is shown marked.
+    #[serde(default)]
+    pub assisted: bool,
+    /// Given up for another game rather than played out; logged lost when
+    /// abandoned games count as losses.
+    #[serde(default)]
+    pub abandoned: bool,
+    /// A move was taken back (see `clock`), so its time is shown but is
+    /// never a best.
+    #[serde(default)]
+    pub undone: bool,
+    /// The board's `Board::layout_hash`, which retries of one board share;
+    /// none for games logged before it was kept.
+    #[serde(default)]
+    pub board: Option<u64>,
+    /// A board from the recent boards, dealt again with its mines already
+    /// seen once, so like `undone` its time is never a best.
+    #[serde(default)]
+    pub rematch: bool,
+    /// The board's `rating::Metrics::difficulty`; none for boards too big
+    /// to measure and games logged before it was kept.
+    #[serde(default)]
+    pub difficulty: Option<f64>,
+}
+
+/// A board played lately, kept whole so that it can be dealt again.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Dealt {
+    pub hash: u64,             // `Board::layout_hash`, as in `Game::board`
+    pub seed: Option<u64>,     // what it was dealt from, for sharing
+    pub layout: String,        // `Board::encode_layout`
+    pub start: (usize, usize), // the cell it was opened from
+}
+
+/// One line of the menu's recent boards: the board, its latest game and
+/// how many of the logged games were played on it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RecentBoard<'a> {
+    pub dealt: &'a Dealt,
+    pub last: Game,
+    pub attempts: u32,
+}
+
+/// Totals over some logged games: a day's, or a whole filter's.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Tally {
+    pub games: u32,
+    pub wins: u32,
+    pub abandoned: u32,        // of the games, those given up
+    pub fastest: Option<Game>, // quickest win without an undo
+    speed_sum: f64,            // summed 3BV/s over wins
+}
+
+impl Tally {
+    fn add(&mut self, game: &Game) {
+        self.games += 1;
+        self.abandoned += game.abandoned as u32;
+        if !game.won {
+            return;
+        }
+        self.wins += 1;
+        self.speed_sum += game.three_bv as f64 / game.secs.max(0.001);
+        if !game.undone
+            && !game.rematch
+            && self.fastest.is_none_or(|fastest| game.secs < fastest.secs)
+        {
+            self.fastest = Some(*game);
+        }
+    }
+
+    /// Share of games won, as a percentage.
+    pub fn win_rate(&self) -> Option<f64> {
+        (self.games > 0).then(|| self.wins as f64 * 100.0 / self.games as f64)
+    }
+
+    /// Mean 3BV per second over the wins.
+    pub fn speed(&self) -> Option<f64> {
+        (self.wins > 0).then(|| self.speed_sum / self.wins as f64)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct History {
+    pub games: u32,
+    pub abandoned: u32, // games given up, whether or not they count in `games`
+    pub left_clicks: u64,
+    pub right_clicks: u64,
+    pub wasted_clicks: u64,
+    pub flags_placed: u64,
+    pub flags_correct: u64,
+    pub efficiency_sum: f64,       // summed per-game efficiency percentages
+    pub recent: VecDeque<Outcome>, // newest last, at most RECENT_GAMES
+    pub bests: Vec<Best>,          // one per size won at
+    pub log: VecDeque<Game>,       // newest last, at most LOGGED_GAMES
+    pub boards: VecDeque<Dealt>,   // played most lately last, at most RECENT_BOARDS
+    pub challenges: Challenges,    // kept apart from the size records above
+    pub trainer: Trainer,
+}
+
+impl History {
+    /// Fold one finished game in.
+    pub fn add(&mut self, stats: &InputStats, efficiency: f64) {
+        self.games += 1;
+        self.left_clicks += stats.left_clicks as u64;
+        self.right_clicks += stats.right_clicks as u64;
+        self.wasted_clicks += stats.wasted_clicks as u64;
+        self.flags_placed += stats.flags_placed as u64;
+        self.flags_correct += stats.flags_correct as u64;
+        self.efficiency_sum += efficiency;
+    }
+
+    /// Fold in history kept elsewhere, such as on another computer: totals
+    /// add up, each size keeps the faster best time, and the logs are
+    /// interleaved by day.
+    pub fn merge(&mut self, other: History) {
+        self.games += other.games;
+        self.abandoned += other.abandoned;
+        self.left_clicks += other.left_clicks;
+        self.right_clicks += other.right_clicks;
+        self.wasted_clicks += other.wasted_clicks;
+        self.flags_placed += other.flags_placed;
+        self.flags_correct += other.flags_correct;
+        self.efficiency_sum += other.efficiency_sum;
+        for outcome in other.recent {
+            self.remember(outcome);
+        }
+        for theirs in other.bests {
+            match self.bests.iter_mut().find(|best| best.size == theirs.size) {
+                Some(best) if theirs.secs < best.secs => *best = theirs,
+                Some(_) => {}
+                None => self.bests.push(theirs),
+            }
+        }
+        for dealt in other.boards {
+            self.keep_board(dealt);
+        }
+        self.challenges.merge(other.challenges);
+        self.trainer.merge(other.trainer);
+        self.log.extend(other.log);
+        self.log.make_contiguous().sort_by_key(|game| game.day);
+        while self.log.len() > LOGGED_GAMES {
+            self.log.pop_front();
+        }
+    }
+
+    /// Remember a result, forgetting the oldest past `RECENT_GAMES`.
+    pub fn remember(&mut self, outcome: Outcome) {
+        self.recent.push_back(outcome);
+        while self.recent.len() > RECENT_GAMES {
+            self.recent.pop_front();
+        }
+    }
+
+    /// Log a finished game, forgetting the oldest past `LOGGED_GAMES`.
+    pub fn log_game(&mut self, game: Game) {
+        self.log.push_back(game);
+        while self.log.len() > LOGGED_GAMES {
+            self.log.pop_front();
+        }
+    }
+
+    /// Keep `dealt` for a rematch as the board played most lately,
+    /// forgetting the one played longest ago past `RECENT_BOARDS`.
+    pub fn keep_board(&mut self, dealt: Dealt) {
+        self.boards.retain(|kept| kept.hash != dealt.hash);
+        self.boards.push_back(dealt);
+        while self.boards.len() > RECENT_BOARDS {
+            self.boards.pop_front();
+        }
+    }
+
+    /// The kept boards, most lately played first, each with its latest
+    /// game in the log and one line however often it was retried.
+    pub fn recent_boards(&self) -> Vec<RecentBoard<'_>> {
+        let mut recent: Vec<RecentBoard> = Vec::new();
+        for game in self.log.iter().rev() {
+            let Some(hash) = game.board else {
+                continue;
+            };
+            if let Some(line) = recent.iter_mut().find(|line| line.dealt.hash == hash) {
+                line.attempts += 1;
+            } else if let Some(dealt) = self.boards.iter().find(|dealt| dealt.hash == hash) {
+                recent.push(RecentBoard {
+                    dealt,
+                    last: *game,
+                    attempts: 1,
+                });
+            }
+        }
+        recent
+    }
+
+    /// Forget the kept boards, clearing the menu's list. The log and
+    /// everything counted from it stay.
+    pub fn forget_boards(&mut self) {
+        self.boards.clear();
+    }
+
+    /// Logged games at `size` (any size for `None`), tallied by day.
+    pub fn days(&self, size: Option<(usize, usize, usize)>) -> BTreeMap<NaiveDate, Tally> {
+        let mut days = BTreeMap::<NaiveDate, Tally>::new();
+        for game in self.at_size(size) {
+            days.entry(game.day).or_default().add(game);
+        }
+        days
+    }
+
+    /// Logged games at `size`, on `day` if one is given, tallied together.
+    pub fn tally(&self, size: Option<(usize, usize, usize)>, day: Option<NaiveDate>) -> Tally {
+        let mut tally = Tally::default();
+        for game in self.at_size(size) {
+            if day.is_none_or(|day| game.day == day) {
+                tally.add(game);
+            }
+        }
+        tally
+    }
+
+    /// The personal rating after each logged game at `size` (any size for
+    /// `None`) whose board was measured, oldest first; see `rating`.
+    pub fn ratings(&self, size: Option<(usize, usize, usize)>) -> Vec<f64> {
+        rating::trend(self.at_size(size).filter_map(|game| {
+            Some(Played {
+                difficulty: game.difficulty?,
+                won: game.won,
+                three_bv: game.three_bv,
+                secs: game.secs,
+            })
+        }))
+    }
+
+    /// Every board size in the log, smallest first.
+    pub fn logged_sizes(&self) -> Vec<(usize, usize, usize)> {
+        let mut sizes: Vec<_> = self.log.iter().map(|game| game.size).collect();
+        sizes.sort_by_key(|&(w, h, mines)| (w * h, mines));
+        sizes.dedup();
+        sizes
+    }
+
+    fn at_size(&self, size: Option<(usize, usize, usize)>) -> impl Iterator<Item = &Game> {
+        self.log
+            .iter()
+            .filter(move |game| size.is_none_or(|size| game.size == size))
+    }
+
+    /// Note a win taking `secs`, `assisted` as for `Game`, returning whether
+    /// it beat the best time at its size. The first win at a size sets the
+    /// best without beating one.
+    pub fn record_win(&mut self, size: (usize, usize, usize), secs: f64, assisted: bool) -> bool {
+        match self.bests.iter_mut().find(|best| best.size == size) {
+            Some(best) if secs < best.secs => {
+                best.secs = secs;
+                best.assisted = assisted;
+                true
+            }
+            Some(_) => false,
+            None => {
+                self.bests.push(Best {
+                    size,
+                    secs,
+                    assisted,
+                });
+                false
+            }
+        }
+    }
+
+    /// Mean efficiency per game, once there is a game to average.
+    pub fn average_efficiency(&self) -> Option<f64> {
+        (self.games > 0).then(|| self.efficiency_sum / self.games as f64)
+    }
+
+    /// Mean clicks of each kind per game: left, right, wasted.
+    pub fn average_clicks(&self) -> Option<(f64, f64, f64)> {
+        let games = self.games as f64;
+        (self.games > 0).then(|| {
+            (
+                self.left_clicks as f64 / games,
+                self.right_clicks as f64 / games,
+                self.wasted_clicks as f64 / games,
+            )
+        })
+    }
+}
+
+/// How the probability trainer has gone, and the level its questions are
+/// asked at: an index into `minesweeper_rs::trainer::GAPS`, the chances
+/// closer together the higher it is.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Trainer {
+    pub answered: u32,
+    pub right: u32,
+    pub recent: VecDeque<bool>, // newest last, at most RECENT_ANSWERS
+    pub level: usize,
+    run: u32,    // right in a row at this level
+    misses: u32, // wrong at this level
+}
+
+impl Trainer {
+    /// Note an answer, moving up a level after `LEVEL_UP` right in a row
+    /// and down after `LEVEL_DOWN` misses at the level.
+    pub fn answer(&mut self, right: bool) {
+        self.answered += 1;
+        self.right += right as u32;
+        self.recent.push_back(right);
+        while self.recent.len() > RECENT_ANSWERS {
+            self.recent.pop_front();
+        }
+        if right {
+            self.run += 1;
+        } else {
+            self.run = 0;
+            self.misses += 1;
+        }
+        let level = if self.run >= LEVEL_UP {
+            (self.level + 1).min(GAPS.len() - 1)
+        } else if self.misses >= LEVEL_DOWN {
+            self.level.saturating_sub(1)
+        } else {
+            return;
+        };
+        self.level = level;
+        (self.run, self.misses) = (0, 0);
+    }
+
+    /// Percentage of every answer that was right, once there is one.
+    pub fn accuracy(&self) -> Option<f64> {
+        (self.answered > 0).then(|| self.right as f64 * 100.0 / self.answered as f64)
+    }
+
+    /// Percentage of the recent answers that were right.
+    pub fn recent_accuracy(&self) -> Option<f64> {
+        let right = self.recent.iter().filter(|&&right| right).count();
+        (!self.recent.is_empty()).then(|| right as f64 * 100.0 / self.recent.len() as f64)
+    }
+
+    /// Fold in another record: the answers add up, and the higher level is
+    /// kept along with its progress.
+    fn merge(&mut self, other: Trainer) {
+        self.answered += other.answered;
+        self.right += other.right;
+        let mut recent = other.recent;
+        recent.extend(self.recent.drain(..));
+        while recent.len() > RECENT_ANSWERS {
+            recent.pop_front();
+        }
+        self.recent = recent;
+        if other.level > self.level {
+            (self.level, self.run, self.misses) = (other.level, other.run, other.misses);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Datelike;
+
+    const BEGINNER: (usize, usize, usize) = (9, 9, 10);
+    const EXPERT: (usize, usize, usize) = (25, 25, 99);
+
+    fn game(day: u32, size: (usize, usize, usize), won: bool, secs: f64) -> Game {
+        Game {
+            day: NaiveDate::from_ymd_opt(2026, 3, day).unwrap(),
+            size,
+            won,
+            secs,
+            three_bv: 30,
+            assisted: false,
+            abandoned: false,
+            undone: false,
+            board: None,
+            rematch: false,
+            difficulty: None,
+        }
+    }
+
+    fn dealt(hash: u64) -> Dealt {
+        Dealt {
+            hash,
+            seed: Some(hash),
+            layout: "*.\n..\n".into(),
+            start: (1, 1),
+        }
+    }
+
+    #[test]
+    fn the_log_keeps_the_newest_games() {
+        let mut history = History::default();
+        for i in 0..LOGGED_GAMES + 5 {
+            history.log_game(game(1, BEGINNER, true, i as f64));
+        }
+        assert_eq!(history.log.len(), LOGGED_GAMES);
+        assert_eq!(history.log.front().unwrap().secs, 5.0);
+    }
+
+    #[test]
+    fn days_tally_games_wins_and_the_fastest_win() {
+        let mut history = History::default();
+        history.log_game(game(1, BEGINNER, true, 20.0));
+        history.log_game(game(1, BEGINNER, false, 5.0));
+        history.log_game(game(1, EXPERT, true, 90.0));
+        history.log_game(game(2, BEGINNER, true, 12.0));
+
+        let days = history.days(Some(BEGINNER));
+        let first = days[&NaiveDate::from_ymd_opt(2026, 3, 1).unwrap()];
+        assert_eq!((first.games, first.wins), (2, 1));
+        assert_eq!(first.fastest.unwrap().secs, 20.0);
+        assert_eq!(first.win_rate(), Some(50.0));
+        assert_eq!(history.days(None).values().map(|t| t.games).sum::<u32>(), 4);
+
+        let second = NaiveDate::from_ymd_opt(2026, 3, 2).unwrap();
+        let all = history.tally(Some(BEGINNER), None);
+        assert_eq!((all.games, all.fastest.unwrap().day), (3, second));
+        assert_eq!(history.tally(None, Some(second)).games, 1);
+        assert_eq!(history.tally(Some(EXPERT), Some(second)), Tally::default());
+
+        // a faster win with a move taken back counts, but not as the best
+        history.log_game(Game {
+            undone: true,
+            ..game(2, BEGINNER, true, 8.0)
+        });
+        let all = history.tally(Some(BEGINNER), None);
+        assert_eq!((all.wins, all.fastest.unwrap().secs), (3, 12.0));
+        assert_eq!(history.logged_sizes(), [BEGINNER, EXPERT]);
+    }
+
+    #[test]
+    fn merging_keeps_the_faster_best_and_orders_the_log() {
+        let mut here = History::default();
+        here.log_game(game(3, BEGINNER, true, 20.0));
+        here.record_win(BEGINNER, 20.0, false);
+        let mut there = History::default();
+        there.log_game(game(1, BEGINNER, true, 15.0));
+        there.record_win(BEGINNER, 15.0, true);
+        there.record_win(EXPERT, 90.0, false);
+
+        here.merge(there);
+        let days: Vec<u32> = here.log.iter().map(|game| game.day.day()).collect();
+        assert_eq!(days, [1, 3]);
+        let bests: Vec<_> = here
+            .bests
+            .iter()
+            .map(|best| (best.size, best.secs, best.assisted))
+            .collect();
+        assert_eq!(bests, [(BEGINNER, 15.0, true), (EXPERT, 90.0, false)]);
+    }
+
+    #[test]
+    fn retries_of_a_board_make_one_line_with_their_count() {
+        let mut history = History::default();
+        let on = |board, day, won| Game {
+            board: Some(board),
+            ..game(day, BEGINNER, won, day as f64)
+        };
+        history.keep_board(dealt(1));
+        history.log_game(on(1, 1, false));
+        history.keep_board(dealt(2));
+        history.log_game(on(2, 2, true));
+        history.keep_board(dealt(1));
+        history.log_game(on(1, 3, false));
+        history.log_game(on(1, 4, true));
+        // logged before boards were kept, or kept no longer
+        history.log_game(game(5, BEGINNER, true, 5.0));
+        history.log_game(on(3, 6, true));
+
+        let recent = history.recent_boards();
+        let lines: Vec<_> = recent
+            .iter()
+            .map(|line| (line.dealt.hash, line.attempts, line.last.day.day()))
+            .collect();
+        assert_eq!(lines, [(1, 3, 4), (2, 1, 2)]);
+        assert!(recent[0].last.won);
+
+        for hash in 0..RECENT_BOARDS as u64 + 3 {
+            history.keep_board(dealt(hash));
+        }
+        assert_eq!(history.boards.len(), RECENT_BOARDS);
+        assert_eq!(history.boards.front().unwrap().hash, 3);
+        history.forget_boards();
+        assert!(history.recent_boards().is_empty());
+        assert_eq!(history.log.len(), 6);
+    }
+
+    #[test]
+    fn the_rating_follows_measured_games_at_the_size() {
+        let mut history = History::default();
+        let rated = |won| Game {
+            difficulty: Some(8.0),
+            ..game(2, EXPERT, won, 100.0)
+        };
+        history.log_game(game(1, EXPERT, true, 90.0)); // logged before ratings
+        history.log_game(rated(true));
+        history.log_game(Game {
+            difficulty: Some(4.0),
+            ..game(3, BEGINNER, false, 5.0)
+        });
+        history.log_game(rated(false));
+
+        let expert = history.ratings(Some(EXPERT));
+        assert_eq!(expert.len(), 2);
+        assert!(expert[0] > rating::START && expert[1] < expert[0]);
+        assert_eq!(history.ratings(None).len(), 3);
+        assert!(history.ratings(Some((8, 8, 10))).is_empty());
+    }
+
+    #[test]
+    fn the_trainer_climbs_on_a_run_and_drops_on_misses() {
+        let mut trainer = Trainer::default();
+        for _ in 0..LEVEL_UP {
+            trainer.answer(true);
+        }
+        assert_eq!(trainer.level, 1);
+        // a miss breaks the run, so it takes four more from here
+        for right in [true, true, false, true, true, true] {
+            trainer.answer(right);
+        }
+        assert_eq!(trainer.level, 1);
+        trainer.answer(false);
+        assert_eq!(trainer.level, 0);
+        trainer.answer(false);
+        trainer.answer(false);
+        assert_eq!(trainer.level, 0);
+
+        assert_eq!((trainer.answered, trainer.right), (13, 9));
+        assert_eq!(trainer.recent.len(), 13);
+        assert!((trainer.accuracy().unwrap() - 900.0 / 13.0).abs() < 1e-9);
+
+        let mut top = Trainer::default();
+        for _ in 0..RECENT_ANSWERS + 10 {
+            top.answer(true);
+        }
+        assert_eq!(top.level, GAPS.len() - 1);
+        assert_eq!(top.recent.len(), RECENT_ANSWERS);
+        assert_eq!(top.recent_accuracy(), Some(100.0));
+
+        trainer.merge(top);
+        assert_eq!(trainer.level, GAPS.len() - 1);
+        assert_eq!(trainer.answered, 13 + 60);
+        assert_eq!(trainer.recent.len(), RECENT_ANSWERS);
+        assert_eq!(trainer.recent.back(), Some(&false)); // ours are the newest
+    }
+}
@@ -0,0 +1,153 @@
+//! Files and arguments a player can hand the app, each of which must come
+//! back as an error (or an ignored argument) rather than a panic: race
+//! files and results, data bundles and the command line, mangled at random
+//! from good ones. The core's own parsers are fuzzed in `fuzz/` and checked
+//! the same way in `tests/hostile_inputs.rs`.
+
+use crate::bundle;
+use crate::profiles::Profiles;
+use crate::race::{BoardRun, Move, Race, RaceResult};
+use crate::Args;
+use minesweeper_rs::Difficulty;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+/// xorshift, so every run mangles the same way.
+fn next(state: &mut u64) -> u64 {
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    *state
+}
+
+/// `seeds` as they are, then `rounds` more made from them: cut short, with
+/// bits flipped, bytes dropped or added, or the end of another seed stuck
+/// on.
+fn mangled(seeds: &[String], rounds: usize) -> Vec<String> {
+    let mut rng = 0x9e37_79b9_7f4a_7c15;
+    let mut inputs = seeds.to_vec();
+    for _ in 0..rounds {
+        let mut bytes = seeds[next(&mut rng) as usize % seeds.len()]
+            .clone()
+            .into_bytes();
+        for _ in 0..=next(&mut rng) % 4 {
+            let at = next(&mut rng) as usize % (bytes.len() + 1);
+            let byte = next(&mut rng) as u8;
+            match next(&mut rng) % 5 {
+                0 => bytes.truncate(at),
+                1 if at < bytes.len() => bytes[at] ^= 1 << (byte % 8),
+                2 if at < bytes.len() => {
+                    bytes.remove(at);
+                }
+                3 => bytes.insert(at, byte),
+                _ => {
+                    let other = seeds[usize::from(byte) % seeds.len()].as_bytes();
+                    bytes.extend_from_slice(&other[other.len() / 2..]);
+                }
+            }
+        }
+        inputs.push(String::from_utf8_lossy(&bytes).into_owned());
+    }
+    inputs
+}
+
+/// A finished result for `race`, every board won by opening its safe cells
+/// in turn.
+fn finished(race: &Race) -> RaceResult {
+    let boards = (0..race.seeds.len())
+        .map(|i| {
+            let mut board = race.board(i);
+            let layout = board.encode_layout();
+            let mut moves = Vec::new();
+            for (y, row) in layout.lines().enumerate() {
+                for (x, c) in row.chars().enumerate() {
+                    if c == '.' && Move::Reveal(x, y).apply(&mut board) {
+                        moves.push(Move::Reveal(x, y));
+                    }
+                }
+            }
+            BoardRun::new(race, "ada", i, 10.0, true, moves)
+        })
+        .collect();
+    RaceResult {
+        player: "ada".into(),
+        race: race.clone(),
+        boards,
+    }
+}
+
+#[test]
+fn mangled_race_files_and_results_are_errors_or_check_out() {
+    let race = Race::random(Difficulty::Beginner, &mut StdRng::seed_from_u64(9));
+    let result = finished(&race);
+    assert!(result.verify().is_ok());
+    for text in mangled(&[race.to_json(), result.to_json()], 3000) {
+        if let Ok(race) = Race::from_json(&text) {
+            // a race that reads back can lay every one of its boards
+            for i in 0..race.seeds.len() {
+                race.board(i);
+            }
+        }
+        if let Ok(result) = RaceResult::from_json(&text) {
+            let _ = result.verify();
+        }
+    }
+}
+
+#[test]
+fn mangled_bundles_are_errors_or_partial_imports() {
+    let mut profiles = Profiles::default();
+    profiles.create("ada").unwrap();
+    let seeds = [bundle::export(&profiles, None), "{}".to_string()];
+    for text in mangled(&seeds, 3000) {
+        let _ = bundle::read(&text);
+    }
+}
+
+#[test]
+fn any_command_line_parses() {
+    const WORDS: &[&str] = &[
+        "race",
+        "audit",
+        "simulate",
+        "render-text",
+        "hint",
+        "solve",
+        "verify",
+        "reproduce",
+        "export-data",
+        "import-data",
+        "--analyze",
+        "--spectate",
+        "--tatham-id",
+        "--stress",
+        "--verbose",
+        "--log-file",
+        "--profile",
+        "--notify-url",
+        "--notify-on",
+        "--density",
+        "--visual-seed",
+        "--safe-radius",
+        "-v",
+        "-vvv",
+        "-v-v",
+        "0.2",
+        "-1",
+        "1e999",
+        "NaN",
+        "18446744073709551616",
+        "",
+        "é",
+    ];
+    let mut rng = 7;
+    for _ in 0..3000 {
+        let len = next(&mut rng) % 6;
+        let args = (0..len).map(|_| match next(&mut rng) % 8 {
+            // a run of v's long enough to overflow a u8 count
+            0 => format!("-{}", "v".repeat(next(&mut rng) as usize % 600)),
+            _ => WORDS[next(&mut rng) as usize % WORDS.len()].to_string(),
+        });
+        Args::parse_from(args.collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,184 @@
+//! A record of the game in progress, kept so a bug seen mid-game can be
+//! replayed: the board as its mines were laid, every action taken on it
+//! since, in order, and the last few events those actions set off. It
+//! lives in memory only, and starts over with each game; `repro` writes
+//! it out and plays it back.
+//!
+//! Actions are logged where they reach the board, not where the player
+//! made them, so a move is logged once whether it came from a click, the
+//! keyboard, the cell menu or the coach. Moves made inside another logged
+//! action, such as the answer a hint plays, are logged as themselves and
+//! the outer action is only noted.
+
+use crate::app::GameEvent;
+use minesweeper_rs::report::position_code;
+use minesweeper_rs::session::Command;
+use minesweeper_rs::{Board, Marking};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Events kept; older ones are dropped.
+pub const EVENTS: usize = 50;
+
+/// One thing done to the board.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Reveal(usize, usize),
+    Flag(usize, usize), // a right click, cycling the marking
+    Mark(usize, usize, Mark),
+    Chord(usize, usize),
+    ClearFlags,
+    RevealRemaining,
+    Undo,
+    Hint, // noted only: what it played is logged on its own
+}
+
+/// A marking picked from the cell menu.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Mark {
+    None,
+    Flag,
+    Question,
+}
+
+impl From<Marking> for Mark {
+    fn from(marking: Marking) -> Self {
+        match marking {
+            Marking::None => Mark::None,
+            Marking::Flag => Mark::Flag,
+            Marking::Question => Mark::Question,
+            // `Marking` is non-exhaustive; one the journal doesn't know
+            // replays as no marking
+            _ => Mark::None,
+        }
+    }
+}
+
+impl From<Mark> for Marking {
+    fn from(mark: Mark) -> Self {
+        match mark {
+            Mark::None => Marking::None,
+            Mark::Flag => Marking::Flag,
+            Mark::Question => Marking::Question,
+        }
+    }
+}
+
+impl Action {
+    /// The action in the session command language, if it has a line
+    /// there; a chord is a click on its number.
+    pub fn command(self) -> Option<Command> {
+        match self {
+            Action::Reveal(x, y) | Action::Chord(x, y) => Some(Command::Reveal(x, y)),
+            Action::Flag(x, y) | Action::Mark(x, y, _) => Some(Command::Flag(x, y)),
+            Action::ClearFlags => Some(Command::ClearFlags),
+            Action::Hint => Some(Command::Hint),
+            Action::RevealRemaining => Some(Command::RevealRemaining),
+            Action::Undo => None,
+        }
+    }
+}
+
+/// The current game's record.
+#[derive(Clone, Debug, Default)]
+pub struct Journal {
+    start: Option<String>, // position code of the board as play began on it
+    actions: Vec<Action>,
+    events: VecDeque<GameEvent>,
+}
+
+impl Journal {
+    /// Start over from `board`, as its mines are laid or it is recovered.
+    /// Actions logged before, on a board without mines, are dropped: what
+    /// they did is part of `board`.
+    pub fn begin(&mut self, board: &Board) {
+        *self = Journal {
+            start: Some(position_code(board)),
+            ..Journal::default()
+        };
+    }
+
+    /// Forget everything, for a new game.
+    pub fn clear(&mut self) {
+        *self = Journal::default();
+    }
+
+    pub fn record(&mut self, action: Action) {
+        self.actions.push(action);
+    }
+
+    pub fn event(&mut self, event: GameEvent) {
+        if self.events.len() == EVENTS {
+            self.events.pop_front();
+        }
+        self.events.push_back(event);
+    }
+
+    /// Where play began, as a position code; `None` until the mines are
+    /// laid.
+    pub fn start(&self) -> Option<&str> {
+        self.start.as_deref()
+    }
+
+    pub fn actions(&self) -> &[Action] {
+        &self.actions
+    }
+
+    /// The last [`EVENTS`] events, oldest first.
+    pub fn events(&self) -> impl Iterator<Item = GameEvent> + '_ {
+        self.events.iter().copied()
+    }
+
+    /// The actions that have a line in the command language, for a debug
+    /// snapshot.
+    pub fn commands(&self) -> Vec<Command> {
+        self.actions.iter().filter_map(|a| a.command()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_the_last_events_are_kept() {
+        let mut journal = Journal::default();
+        for x in 0..EVENTS + 5 {
+            journal.event(GameEvent::Exploded(x, 0));
+        }
+        let events: Vec<GameEvent> = journal.events().collect();
+        assert_eq!(events.len(), EVENTS);
+        assert_eq!(events[0], GameEvent::Exploded(5, 0));
+
+        journal.record(Action::Reveal(1, 1));
+        journal.begin(&Board::with_seed(9, 9, 10, 1, None));
+        assert!(journal.actions().is_empty());
+        assert_eq!(journal.events().count(), 0);
+        assert!(journal.start().is_some());
+    }
+
+    #[test]
+    fn actions_read_as_commands_where_they_can() {
+        let mut journal = Journal::default();
+        for action in [
+            Action::Chord(2, 2),
+            Action::Undo,
+            Action::Mark(0, 1, Mark::Question),
+            Action::Hint,
+            Action::RevealRemaining,
+        ] {
+            journal.record(action);
+        }
+        assert_eq!(
+            journal.commands(),
+            [
+                Command::Reveal(2, 2),
+                Command::Flag(0, 1),
+                Command::Hint,
+                Command::RevealRemaining
+            ]
+        );
+    }
+}
@@ -0,0 +1,53 @@
+//! Core minesweeper logic shared by the GUI and the language bindings: the
+//! board itself, a deduction-only solver, no-guess generation and reveal
+//! timing.
+//!
+//! ```
+//! use minesweeper_rs::{Board, RevealResult};
+//!
+//! let mut board = Board::decode_layout("*..\n...\n..*\n")?;
+//! board.toggle_flag(0, 0);
+//! assert_eq!(board.reveal_cell(2, 0), RevealResult::Revealed);
+//! assert_eq!(board.cell(2, 0).neighbor_mines(), Some(0));
+//! # Ok::<(), minesweeper_rs::LayoutError>(())
+//! ```
+//!
+//! Everything a player can't see yet stays hidden behind [`Cell`]: whether
+//! a covered cell is a mine is only known once it is revealed or the game
+//! is over.
+
+#![warn(missing_docs)]
+
+pub mod analysis;
+pub mod annotations;
+pub mod assist;
+pub mod autosave;
+mod board;
+pub mod difficulty;
+pub mod endgame;
+pub mod format;
+pub mod formats;
+pub mod generate;
+pub mod hints;
+pub mod objective;
+pub mod odds;
+pub mod puzzle;
+pub mod rating;
+pub mod render;
+pub mod replay;
+pub mod report;
+mod rng;
+#[cfg(unix)]
+pub mod serve;
+pub mod session;
+pub mod solver;
+pub mod stats;
+pub mod timeline;
+pub mod trainer;
+
+pub use board::{
+    Board, BoardDiff, BoardError, BoardOptions, BoardSnapshot, Cell, CellDiff, CycleMode,
+    DecodeError, FlagResult, Ignored, LayoutError, Marking, MarkingResult, RevealResult,
+    SizeMismatch, SnapshotMismatch, Symmetry, UnknownSymmetry, MAX_CELLS,
+};
+pub use difficulty::{BoardSpec, Difficulty};
@@ -0,0 +1,98 @@
+//! Logging setup. Everything goes through `tracing`; this wires up stderr,
+//! an optional JSON-lines file and the in-app log window. Mine locations and
+//! seeds are only ever logged at debug level or below, so info-level logs
+//! are safe to share.
+
+use std::collections::VecDeque;
+use std::fmt::{self, Write as _};
+use std::fs::File;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+use tracing_subscriber::util::SubscriberInitExt;
+
+/// How many events the log window keeps.
+const BUFFER_LINES: usize = 500;
+
+/// The last few hundred formatted events, shared with the log window.
+#[derive(Clone, Default)]
+pub struct LogBuffer(Arc<Mutex<VecDeque<String>>>);
+
+impl LogBuffer {
+    /// Copy of the buffered lines, oldest first.
+    pub fn lines(&self) -> Vec<String> {
+        self.0
+            .lock()
+            .map_or_else(|_| Vec::new(), |lines| lines.iter().cloned().collect())
+    }
+
+    fn push(&self, line: String) {
+        if let Ok(mut lines) = self.0.lock() {
+            if lines.len() == BUFFER_LINES {
+                lines.pop_front();
+            }
+            lines.push_back(line);
+        }
+    }
+}
+
+impl<S: Subscriber> Layer<S> for LogBuffer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let meta = event.metadata();
+        let mut line = format!("{:>5} {}: ", meta.level(), meta.target());
+        event.record(&mut FieldWriter(&mut line));
+        self.push(line);
+    }
+}
+
+/// Formats an event as `message key=value ...`.
+struct FieldWriter<'a>(&'a mut String);
+
+impl Visit for FieldWriter<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        let _ = if field.name() == "message" {
+            write!(self.0, "{value:?}")
+        } else {
+            write!(self.0, " {}={value:?}", field.name())
+        };
+    }
+}
+
+/// Install the global subscriber. `verbosity` is the number of `-v` flags:
+/// none shows warnings on stderr, then info, debug and trace. The log file
+/// and window always capture at least info.
+pub fn init(verbosity: u8, log_file: Option<&Path>) -> std::io::Result<LogBuffer> {
+    let level = match verbosity {
+        0 => LevelFilter::WARN,
+        1 => LevelFilter::INFO,
+        2 => LevelFilter::DEBUG,
+        _ => LevelFilter::TRACE,
+    };
+    let capture = level.max(LevelFilter::INFO);
+
+    let file = match log_file {
+        Some(path) => Some(
+            tracing_subscriber::fmt::layer()
+                .json()
+                .with_writer(Mutex::new(File::create(path)?))
+                .with_filter(capture),
+        ),
+        None => None,
+    };
+    let buffer = LogBuffer::default();
+
+    tracing_subscriber::registry()
+        .with(
+            tracing_subscriber::fmt::layer()
+                .with_writer(std::io::stderr)
+                .with_filter(level),
+        )
+        .with(file)
+        .with(buffer.clone().with_filter(capture))
+        .init();
+
+    Ok(buffer)
+}
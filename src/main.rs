@@ -1,489 +1,401 @@
-use eframe::egui;
-use rand::Rng;
+use app::{AppState, Discard, MinesweeperApp};
+use cli::CliError;
+use minesweeper_rs::difficulty::{Difficulty, MAX_LARGE_SIDE};
+use minesweeper_rs::formats::tatham;
+use profiles::Profiles;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use replays::ReplayStore;
+use screens::Screen;
+use settings::MAX_SAFE_RADIUS;
+use std::path::PathBuf;
+use std::sync::atomic::Ordering;
+
+mod app;
+mod bundle;
+mod challenge;
+mod changelog;
+mod cli;
+mod clock;
+mod coach;
+mod crash;
+mod earcons;
+mod explain;
+mod history;
+#[cfg(test)]
+mod hostile_inputs;
+mod journal;
+mod logging;
+mod net;
+mod notify;
+mod particles;
+mod perf;
+mod played;
+mod profiles;
+mod quick_entry;
+mod race;
+#[cfg(feature = "dev-tools")]
+mod recording;
+mod render_text;
+mod replays;
+mod repro;
+mod screens;
+mod settings;
+mod simulate;
+mod themes;
+mod toasts;
+mod tutorial;
+#[cfg(test)]
+mod ui_tests;
+mod versus;
+mod visuals;
+mod widgets;
+mod win_effects;
 
-#[derive(Clone)]
-struct Cell {
-    is_mine: bool,
-    is_revealed: bool,
-    is_flagged: bool,
-    neighbor_mines: u8,
-}
-
-impl Cell {
-    fn new() -> Self {
-        Cell {
-            is_mine: false,
-            is_revealed: false,
-            is_flagged: false,
-            neighbor_mines: 0,
-        }
-    }
-}
+// ---------------- ENTRY POINT ----------------
 
-struct Board {
-    pub width: usize,
-    pub height: usize,
-    pub mine_count: usize,
-    cells: Vec<Vec<Cell>>,
-    pub game_over: bool,
-    pub win: bool,
+/// Stress board for `--stress`: 2000x2000 with 15% mines.
+const STRESS_SIDE: usize = 2000;
+
+/// Command line flags: `-v`/`-vv`/`--verbose` (repeatable) raise the log
+/// level, `--log-file PATH` writes JSON lines, `--profile NAME` picks (or
+/// creates) a profile, `--density 0.2` starts with that share of mines,
+/// `--safe-radius 0..=2` sets (and saves) the first-click opening,
+/// `--stress` opens a huge board, `--visual-seed N` seeds the effects'
+/// randomness so confetti and the like play out the same every run (the
+/// hidden `visual_seed` setting does too), `--notify-url URL` and `--notify-on
+/// win,best,loss` set (and save) the webhook for finished games (an empty
+/// URL turns it off). `race ...` runs a seed race subcommand
+/// (see `race::cli`) and takes the rest of the line, as does `audit ...`
+/// (see `race::audit`), `simulate ...` (see `simulate::parse`),
+/// `render-text ...` (see `render_text::parse`), and `hint ...` and
+/// `solve ...` (see `explain`).
+/// `--tatham-id ID` plays the board of a game ID from Simon Tatham's
+/// "Mines" (see `minesweeper_rs::formats::tatham`), unrecorded, as the
+/// editor's test play does. `--spectate PATH` opens the spectate screen on the game served at that
+/// socket. `--analyze RESULT.json` prints the flag review of each board of
+/// a race result, and `verify RESULT.json` replays one and exits 0 if it
+/// holds, 1 with the reason if not; `reproduce BUNDLE.zip` does the same
+/// for a bug report bundle (see `repro`). `export-data FILE` writes a data
+/// bundle (see `bundle`) and exits; `import-data FILE` checks one and opens it in
+/// Settings to be merged or replaced. Builds with the `dev-tools` feature
+/// take `--record FILE`, which records the session's input for playback
+/// tests (see `recording`). Subcommands that fail say why on
+/// stderr and exit with the code `cli::CliError` gives the failure.
+#[derive(Default)]
+struct Args {
+    race: Option<Vec<String>>,
+    audit: Option<Vec<String>>,
+    simulate: Option<Vec<String>>,
+    render_text: Option<Vec<String>>,
+    hint: Option<Vec<String>>,
+    solve: Option<Vec<String>>,
+    analyze: Option<PathBuf>,
+    spectate: Option<PathBuf>,
+    tatham_id: Option<String>,
+    verify: Option<PathBuf>,
+    reproduce: Option<PathBuf>,
+    verbosity: u8,
+    log_file: Option<PathBuf>,
+    profile: Option<String>,
+    density: Option<u8>, // percent
+    safe_radius: Option<usize>,
+    notify_url: Option<String>,
+    notify_on: Option<String>,
+    export_data: Option<PathBuf>,
+    import_data: Option<PathBuf>,
+    stress: bool,
+    visual_seed: Option<u64>,
+    #[cfg(feature = "dev-tools")]
+    record: Option<PathBuf>,
 }
 
-impl Board {
-    fn new(width: usize, height: usize, mine_count: usize) -> Self {
-        let mut board = Board {
-            width,
-            height,
-            mine_count,
-            cells: vec![vec![Cell::new(); width]; height],
-            game_over: false,
-            win: false,
-        };
-        board.place_mines();
-        board.compute_neighbor_counts();
-        board
-    }
-
-    fn in_bounds(&self, x: isize, y: isize) -> bool {
-        x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height
+impl Args {
+    fn parse() -> Self {
+        Args::parse_from(std::env::args().skip(1))
     }
 
-    fn place_mines(&mut self) {
-        let mut rng = rand::thread_rng();
-        let mut placed = 0;
-
-        while placed < self.mine_count {
-            let idx = rng.gen_range(0..self.width * self.height);
-            let x = idx % self.width;
-            let y = idx / self.width;
-
-            if !self.cells[y][x].is_mine {
-                self.cells[y][x].is_mine = true;
-                placed += 1;
-            }
-        }
-    }
-
-    fn compute_neighbor_counts(&mut self) {
-        let dirs = [
-            (-1, -1),
-            (0, -1),
-            (1, -1),
-            (-1, 0),
-            (1, 0),
-            (-1, 1),
-            (0, 1),
-            (1, 1),
-        ];
-
-        for y in 0..self.height {
-            for x in 0..self.width {
-                if self.cells[y][x].is_mine {
-                    self.cells[y][x].neighbor_mines = 0;
-                    continue;
+    /// Parse flags from any source. Never panics: unknown arguments are
+    /// reported and skipped, and verbosity saturates instead of overflowing.
+    fn parse_from(args: impl IntoIterator<Item = String>) -> Self {
+        let mut parsed = Args::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "race" => parsed.race = Some(args.by_ref().collect()),
+                "audit" => parsed.audit = Some(args.by_ref().collect()),
+                "simulate" => parsed.simulate = Some(args.by_ref().collect()),
+                "render-text" => parsed.render_text = Some(args.by_ref().collect()),
+                "hint" => parsed.hint = Some(args.by_ref().collect()),
+                "solve" => parsed.solve = Some(args.by_ref().collect()),
+                "verify" => parsed.verify = args.next().map(PathBuf::from),
+                "reproduce" => parsed.reproduce = args.next().map(PathBuf::from),
+                "export-data" => parsed.export_data = args.next().map(PathBuf::from),
+                "import-data" => parsed.import_data = args.next().map(PathBuf::from),
+                "--analyze" => parsed.analyze = args.next().map(PathBuf::from),
+                "--spectate" => parsed.spectate = args.next().map(PathBuf::from),
+                "--tatham-id" => parsed.tatham_id = args.next(),
+                "--stress" => parsed.stress = true,
+                #[cfg(feature = "dev-tools")]
+                "--record" => parsed.record = args.next().map(PathBuf::from),
+                "--verbose" => parsed.verbosity = parsed.verbosity.saturating_add(1),
+                "--log-file" => parsed.log_file = args.next().map(PathBuf::from),
+                "--profile" => parsed.profile = args.next(),
+                "--notify-url" => parsed.notify_url = args.next(),
+                "--notify-on" => parsed.notify_on = args.next(),
+                "--density" => {
+                    let value = args.next().unwrap_or_default();
+                    match value.parse::<f64>() {
+                        Ok(share) if share > 0.0 && share < 1.0 => {
+                            parsed.density = Some((share * 100.0).round().clamp(1.0, 99.0) as u8);
+                        }
+                        _ => eprintln!("ignoring --density {value}: expected a share like 0.2"),
+                    }
                 }
-
-                let mut count = 0;
-                for (dx, dy) in &dirs {
-                    let nx = x as isize + dx;
-                    let ny = y as isize + dy;
-                    if self.in_bounds(nx, ny)
-                        && self.cells[ny as usize][nx as usize].is_mine
-                    {
-                        count += 1;
+                "--visual-seed" => {
+                    let value = args.next().unwrap_or_default();
+                    match value.parse() {
+                        Ok(seed) => parsed.visual_seed = Some(seed),
+                        Err(_) => eprintln!("ignoring --visual-seed {value}: expected a number"),
+                    }
+                }
+                "--safe-radius" => {
+                    let value = args.next().unwrap_or_default();
+                    match value.parse() {
+                        Ok(radius) if radius <= MAX_SAFE_RADIUS => {
+                            parsed.safe_radius = Some(radius);
+                        }
+                        _ => eprintln!(
+                            "ignoring --safe-radius {value}: expected 0 to {MAX_SAFE_RADIUS}"
+                        ),
                     }
                 }
-                self.cells[y][x].neighbor_mines = count;
+                short if short.starts_with("-v") && short[1..].bytes().all(|b| b == b'v') => {
+                    let count = u8::try_from(short.len() - 1).unwrap_or(u8::MAX);
+                    parsed.verbosity = parsed.verbosity.saturating_add(count);
+                }
+                other => eprintln!("ignoring unknown argument {other}"),
             }
         }
+        parsed
     }
+}
 
-    fn reveal_cell(&mut self, x: usize, y: usize) {
-        if x >= self.width || y >= self.height {
-            return;
-        }
-
-        let cell = &mut self.cells[y][x];
-
-        if cell.is_revealed || cell.is_flagged {
-            return;
-        }
-
-        cell.is_revealed = true;
-
-        if cell.is_mine {
-            self.game_over = true;
-            self.win = false;
-            return;
-        }
-
-        if cell.neighbor_mines == 0 {
-            self.flood_reveal(x, y);
-        }
+/// Per-user directory for game data such as the autosave slot.
+fn data_dir() -> Option<PathBuf> {
+    let var = |name| std::env::var_os(name).map(PathBuf::from);
+    let base = if cfg!(windows) {
+        var("APPDATA")
+    } else if cfg!(target_os = "macos") {
+        var("HOME").map(|home| home.join("Library").join("Application Support"))
+    } else {
+        var("XDG_DATA_HOME").or_else(|| var("HOME").map(|home| home.join(".local").join("share")))
+    };
+    base.map(|dir| dir.join("bosnia-simulator"))
+}
 
-        if self.check_win() {
-            self.game_over = true;
-            self.win = true;
+fn main() -> eframe::Result<()> {
+    let args = Args::parse();
+    let log = logging::init(args.verbosity, args.log_file.as_deref()).unwrap_or_else(|err| {
+        eprintln!("can't open log file: {err}");
+        logging::init(args.verbosity, None).unwrap_or_default()
+    });
+    crash::install(crash::dirs(data_dir().as_deref()));
+    if let Some(words) = &args.audit {
+        match race::audit(words) {
+            Ok(hash) => println!("{hash}"),
+            Err(err) => err.exit(),
         }
+        return Ok(());
     }
-
-    fn flood_reveal(&mut self, x: usize, y: usize) {
-        let dirs = [
-            (-1, -1),
-            (0, -1),
-            (1, -1),
-            (-1, 0),
-            (1, 0),
-            (-1, 1),
-            (0, 1),
-            (1, 1),
-        ];
-
-        for (dx, dy) in dirs {
-            let nx = x as isize + dx;
-            let ny = y as isize + dy;
-
-            if !self.in_bounds(nx, ny) {
-                continue;
-            }
-
-            let (ux, uy) = (nx as usize, ny as usize);
-            let cell = &mut self.cells[uy][ux];
-
-            if !cell.is_revealed && !cell.is_flagged {
-                cell.is_revealed = true;
-
-                if cell.neighbor_mines == 0 && !cell.is_mine {
-                    self.flood_reveal(ux, uy);
-                }
-            }
+    if let Some(words) = &args.simulate {
+        if let Err(err) = simulate::cli(words) {
+            err.exit();
         }
+        return Ok(());
     }
-
-    fn toggle_flag(&mut self, x: usize, y: usize) {
-        if x < self.width && y < self.height {
-            let cell = &mut self.cells[y][x];
-            if !cell.is_revealed {
-                cell.is_flagged = !cell.is_flagged;
-            }
+    if let Some(words) = &args.render_text {
+        match render_text::render(words) {
+            Ok(snapshot) => print!("{snapshot}"),
+            Err(err) => err.exit(),
         }
+        return Ok(());
     }
-
-    fn check_win(&self) -> bool {
-        for row in &self.cells {
-            for cell in row {
-                if !cell.is_mine && !cell.is_revealed {
-                    return false;
-                }
-            }
+    if let Some(words) = &args.hint {
+        match explain::hint(words) {
+            Ok(hint) => print!("{hint}"),
+            Err(err) => err.exit(),
         }
-        true
+        return Ok(());
     }
-
-    fn reveal_all(&mut self) {
-        for row in &mut self.cells {
-            for cell in row {
-                cell.is_revealed = true;
-            }
+    if let Some(words) = &args.solve {
+        match explain::solve(words) {
+            Ok(solved) => print!("{solved}"),
+            Err(err) => err.exit(),
         }
+        return Ok(());
     }
-
-    /// Return the string to show for a cell: "■", "🚩", "💣", "1", "2", ...
-    fn cell_label(&self, x: usize, y: usize) -> String {
-        let cell = &self.cells[y][x];
-        if cell.is_revealed {
-            if cell.is_mine {
-                "💣".to_string()
-            } else if cell.neighbor_mines == 0 {
-                " ".to_string()
-            } else {
-                cell.neighbor_mines.to_string()
+    if let Some(path) = &args.verify {
+        match replays::verify(path) {
+            Ok(summary) => println!("{summary}"),
+            Err(err) => {
+                let message = format!("{}: {err}", path.display());
+                match err {
+                    race::RaceError::Io(_) | race::RaceError::Json(_) => {
+                        CliError::Input(message).exit()
+                    }
+                    _ => CliError::Failed(message).exit(),
+                }
             }
-        } else if cell.is_flagged {
-            "🚩".to_string()
-        } else {
-            "■".to_string()
         }
+        return Ok(());
     }
-}
-
-// ---------------- DIFFICULTY ----------------
-
-#[derive(Clone, Copy, PartialEq, Eq)]
-enum Difficulty {
-    Beginner,
-    Intermediate,
-    Expert,
-}
-
-impl Difficulty {
-    fn params(self) -> (usize, usize, usize) {
-        match self {
-            Difficulty::Beginner => (9, 9, 10),
-            Difficulty::Intermediate => (16, 16, 40),
-            Difficulty::Expert => (25, 25, 99),
-        }
-    }
-
-    fn label(self) -> &'static str {
-        match self {
-            Difficulty::Beginner => "Beginner (9x9)",
-            Difficulty::Intermediate => "Intermediate (16x16)",
-            Difficulty::Expert => "Expert (25x25)",
+    if let Some(path) = &args.reproduce {
+        match repro::cli(path) {
+            Ok(summary) => println!("{summary}"),
+            Err(err) => err.exit(),
         }
+        return Ok(());
     }
-}
-
-// ---------------- CONFETTI ----------------
-
-struct Particle {
-    pos: egui::Pos2,
-    vel: egui::Vec2,
-    color: egui::Color32,
-    lifetime: f32,
-}
-
-// ---------------- GUI APP ----------------
-
-struct MinesweeperApp {
-    board: Board,
-    difficulty: Difficulty,
-    celebrating: bool,
-    confetti: Vec<Particle>,
-    in_game: bool, // false = start menu, true = playing
-}
-
-impl MinesweeperApp {
-    fn new() -> Self {
-        let difficulty = Difficulty::Beginner;
-        let (width, height, mines) = difficulty.params();
-        Self {
-            board: Board::new(width, height, mines),
-            difficulty,
-            celebrating: false,
-            confetti: Vec::new(),
-            in_game: false, // start on menu screen
+    if let Some(path) = &args.analyze {
+        match race::RaceResult::load(path) {
+            Ok(result) => print!("{}", result.flag_report()),
+            Err(err) => CliError::Input(format!("{}: {err}", path.display())).exit(),
         }
+        return Ok(());
     }
-
-    fn reset(&mut self) {
-        let (w, h, m) = self.difficulty.params();
-        self.board = Board::new(w, h, m);
-        self.celebrating = false;
-        self.confetti.clear();
-    }
-
-    fn set_difficulty(&mut self, difficulty: Difficulty) {
-        self.difficulty = difficulty;
-        self.reset();
-    }
-
-    fn start_game_with(&mut self, difficulty: Difficulty) {
-        self.set_difficulty(difficulty);
-        self.in_game = true;
-    }
-
-    fn back_to_menu(&mut self) {
-        self.in_game = false;
-        self.celebrating = false;
-        self.confetti.clear();
-        // keep last selected difficulty
-    }
-
-    fn start_celebration(&mut self, ctx: &egui::Context) {
-        self.celebrating = true;
-        self.confetti.clear();
-
-        // spawn confetti from top of screen
-        let rect = ctx.screen_rect();
-        let mut rng = rand::thread_rng();
-
-        for _ in 0..200 {
-            let x = rng.gen_range(rect.left()..rect.right());
-            let y = rng.gen_range(rect.top()..(rect.top() + 40.0));
-            let vx = rng.gen_range(-40.0..40.0);
-            let vy = rng.gen_range(50.0..150.0);
-
-            let colors = [
-                egui::Color32::RED,
-                egui::Color32::GREEN,
-                egui::Color32::BLUE,
-                egui::Color32::YELLOW,
-                egui::Color32::from_rgb(255, 0, 255),
-                egui::Color32::from_rgb(0, 255, 255),
-            ];
-            let color = colors[rng.gen_range(0..colors.len())];
-
-            self.confetti.push(Particle {
-                pos: egui::pos2(x, y),
-                vel: egui::vec2(vx, vy),
-                color,
-                lifetime: rng.gen_range(1.0..3.0),
-            });
-        }
-    }
-
-    fn update_confetti(&mut self, ctx: &egui::Context) {
-        if !self.celebrating {
-            return;
-        }
-
-        // In your egui version, stable_dt is already f32
-        let dt = ctx.input(|i| i.stable_dt);
-        let gravity = 200.0;
-
-        for p in &mut self.confetti {
-            p.vel.y += gravity * dt;
-            p.pos += p.vel * dt;
-            p.lifetime -= dt;
-        }
-
-        self.confetti.retain(|p| p.lifetime > 0.0);
-
-        if self.confetti.is_empty() {
-            self.celebrating = false;
-        }
-
-        let painter = ctx.layer_painter(egui::LayerId::new(
-            egui::Order::Foreground,
-            egui::Id::new("confetti"),
-        ));
-
-        for p in &self.confetti {
-            let size = egui::vec2(4.0, 8.0);
-            let rect = egui::Rect::from_center_size(p.pos, size);
-            painter.rect_filled(rect, 1.0, p.color);
-        }
+    let race = match args.race.as_deref().map(race::cli) {
+        None => None,
+        Some(Ok(None)) => return Ok(()),
+        Some(Ok(Some(race))) => Some(race),
+        Some(Err(err)) => err.exit(),
+    };
+    let import = args
+        .import_data
+        .as_deref()
+        .map(|path| match bundle::load(path) {
+            Ok(import) => {
+                for skipped in &import.skipped {
+                    eprintln!("skipping {skipped}");
+                }
+                println!(
+                    "read {}; choose merge or replace in Settings",
+                    import.summary()
+                );
+                import
+            }
+            Err(err) => CliError::Input(format!("{}: {err}", path.display())).exit(),
+        });
+    let tatham = args
+        .tatham_id
+        .as_deref()
+        .map(|id| match tatham::decode(id) {
+            Ok(puzzle) => puzzle,
+            Err(err) => CliError::Input(format!("--tatham-id {id}: {err}")).exit(),
+        });
+    let options = eframe::NativeOptions::default();
+    #[cfg(feature = "dev-tools")]
+    if let Some(path) = &args.record {
+        return recording::run(path.clone(), log, options);
     }
-}
 
-impl Default for MinesweeperApp {
-    fn default() -> Self {
-        Self::new()
+    let data_dir = data_dir();
+    if let Some(dir) = &data_dir {
+        profiles::migrate_autosave(dir);
     }
-}
-
-impl eframe::App for MinesweeperApp {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        if !self.in_game {
-            // -------- START MENU --------
-            egui::CentralPanel::default().show(ctx, |ui| {
-                ui.vertical_centered(|ui| {
-                    ui.add_space(40.0);
-                    ui.heading("Bosnia Simulator");
-                    ui.label("Version 1.0");
-                    ui.label("By Rylan Hillman");
-                    ui.add_space(20.0);
-                    ui.separator();
-                    ui.add_space(10.0);
-                    ui.label("Select difficulty to start:");
-
-                    ui.add_space(10.0);
-                    if ui.button("Beginner (9x9)").clicked() {
-                        self.start_game_with(Difficulty::Beginner);
-                    }
-                    if ui.button("Intermediate (16x16)").clicked() {
-                        self.start_game_with(Difficulty::Intermediate);
-                    }
-                    if ui.button("Expert (25x25)").clicked() {
-                        self.start_game_with(Difficulty::Expert);
-                    }
-                });
-            });
-            return;
-        }
-
-        // -------- GAME SCREEN --------
-        egui::CentralPanel::default().show(ctx, |ui| {
-            ui.heading("Bosnia Simulator");
-
-            // Difficulty row (can change mid-game)
-            ui.horizontal(|ui| {
-                ui.label("Difficulty:");
-
-                for diff in [Difficulty::Beginner, Difficulty::Intermediate, Difficulty::Expert] {
-                    let selected = self.difficulty == diff;
-                    if ui
-                        .selectable_label(selected, diff.label())
-                        .clicked()
-                    {
-                        self.set_difficulty(diff);
-                    }
-                }
+    let replays = data_dir
+        .as_ref()
+        .map(|dir| ReplayStore::open(dir.join("races")));
+    let mut app = MinesweeperApp::new(AppState {
+        log,
+        replays,
+        data_dir,
+        ..AppState::default()
+    });
 
-                if ui.button("Back to Menu").clicked() {
-                    self.back_to_menu();
-                }
-            });
-
-            // Controls row
-            ui.horizontal(|ui| {
-                if ui.button("New Game").clicked() {
-                    self.reset();
+    eframe::run_native(
+        "Bosnia Simulator", // window title
+        options,
+        Box::new(move |cc| {
+            // profile first, so the games started below use its settings
+            app.state.use_profiles(Profiles::load(cc.storage));
+            if let Some(name) = &args.profile {
+                let _ = app.state.profiles.create(name); // fine if it exists
+                app.state.switch_profile(&cc.egui_ctx, name.trim());
+            }
+            if let Some(seed) = args.visual_seed.or(app.state.settings.advanced.visual_seed) {
+                app.state.visual_rng = StdRng::seed_from_u64(seed);
+            }
+            if let Some(radius) = args.safe_radius {
+                app.state.settings.gameplay.safe_radius = radius;
+            }
+            let webhook = &mut app.state.settings.advanced.webhook;
+            if let Some(url) = &args.notify_url {
+                webhook.url = url.trim().to_string();
+            }
+            if let Some(events) = &args.notify_on {
+                if let Err(err) = webhook.set_events(events) {
+                    eprintln!("ignoring --notify-on {events}: {err}");
                 }
-
-                ui.label(format!("Mines: {}", self.board.mine_count));
-
-                if self.board.game_over {
-                    if self.board.win {
-                        ui.colored_label(egui::Color32::GREEN, "You win! 🎉");
-                    } else {
-                        ui.colored_label(egui::Color32::RED, "You hit a mine! 💥");
-                    }
+            }
+            app.state.settings.apply(&cc.egui_ctx);
+            app.state.crash_report =
+                crash::take_unoffered(&crash::dirs(app.state.data_dir.as_deref()));
+
+            // the stored profiles are only reachable in here, so export and
+            // leave before the first frame shows the window
+            if let Some(path) = &args.export_data {
+                match app.state.export_data(path) {
+                    Ok(()) => println!("exported to {}", path.display()),
+                    Err(err) => CliError::Output(format!("{}: {err}", path.display())).exit(),
                 }
-            });
-
-            ui.separator();
-
-            // Board grid
-            for y in 0..self.board.height {
-                ui.horizontal(|ui| {
-                    for x in 0..self.board.width {
-                        let label = self.board.cell_label(x, y);
-                        let button = egui::Button::new(label)
-                            .min_size(egui::vec2(28.0, 28.0));
-                        let response = ui.add(button);
-
-                        if !self.board.game_over {
-                            // Left click = reveal
-                            if response.clicked() {
-                                self.board.reveal_cell(x, y);
-                            }
-                            // Right click = flag
-                            if response.secondary_clicked() {
-                                self.board.toggle_flag(x, y);
-                            }
-                        }
-                    }
-                });
+                std::process::exit(0);
             }
 
-            // Trigger confetti once on win
-            if self.board.game_over && self.board.win && !self.celebrating {
-                self.start_celebration(ctx);
+            // Ctrl+C in the terminal asks about the game in progress, like
+            // closing the window does
+            let interrupt = app.interrupt.clone();
+            let ctx = cc.egui_ctx.clone();
+            if let Err(err) = ctrlc::set_handler(move || {
+                interrupt.store(true, Ordering::Relaxed);
+                ctx.request_repaint();
+            }) {
+                tracing::warn!(%err, "can't catch Ctrl+C");
             }
 
-            if self.board.game_over && !self.board.win {
-                if ui.button("Reveal all").clicked() {
-                    self.board.reveal_all();
-                }
+            app.state.check_whats_new();
+            if let Some(race) = race {
+                app.state.race.load(race);
+                app.screen = Screen::Race;
+            } else if let Some(path) = &args.spectate {
+                app.state.spectate.path = path.display().to_string();
+                let wake = app.state.waker(&cc.egui_ctx);
+                app.state.spectate.connect(wake);
+                app.screen = Screen::Spectate;
+            } else if let Some(puzzle) = tatham {
+                app.state.editor.load(puzzle);
+                app.state.carry_out(Discard::TestPlay);
+                app.screen = Screen::Game;
+            } else if args.stress {
+                let mines = STRESS_SIDE * STRESS_SIDE * 15 / 100;
+                app.state.settings.advanced.large_boards = true;
+                app.start_game(Difficulty::custom(
+                    STRESS_SIDE,
+                    STRESS_SIDE,
+                    mines,
+                    MAX_LARGE_SIDE,
+                ));
+            } else if let Some(percent) = args.density {
+                app.start_game(Difficulty::Beginner.with_percent(percent));
+            } else {
+                // a slot left behind means the last run didn't exit cleanly
+                app.state.recovered = app.state.read_recovered();
             }
-        });
-
-        // Draw and animate confetti on top
-        self.update_confetti(ctx);
-    }
-}
-
-// ---------------- ENTRY POINT ----------------
-
-fn main() -> eframe::Result<()> {
-    let options = eframe::NativeOptions::default();
-
-    eframe::run_native(
-        "Bosnia Simulator", // window title
-        options,
-        Box::new(|_cc| Ok(Box::new(MinesweeperApp::default()))),
+            if let Some(import) = import {
+                app.state.data_transfer.open(import);
+                app.screen = Screen::Settings;
+            }
+            Ok(Box::new(app))
+        }),
     )
 }
-
@@ -0,0 +1,6 @@
+//! Talking to servers other than a webhook: the online leaderboard (see
+//! `leaderboard`) and games served over a socket, watched as a spectator
+//! (see `spectate`).
+
+pub mod leaderboard;
+pub mod spectate;
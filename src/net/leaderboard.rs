@@ -0,0 +1,436 @@
+//! The opt-in online leaderboard. Wins on the preset boards are submitted
+//! to a server, and the Leaderboards screen shows its top 50 for each.
+//!
+//! It is off by default, and nothing is sent until the player turns it on
+//! and picks a display name. A submission holds that name, the board size,
+//! the time, the 3BV, the seed and fingerprints of the layout and of the
+//! reveal order, and nothing else.
+//!
+//! Requests run on their own threads, like webhook posts. Fetched tables
+//! are kept for a minute. A submission that can't be sent waits in a queue
+//! and is retried, so a win played offline is sent once the server is back,
+//! as long as the game is still running.
+
+use minesweeper_rs::Difficulty;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Where scores go unless the player names another server.
+pub const DEFAULT_SERVER: &str = "https://leaderboard.minesweeper-rs.example";
+/// Entries a table holds.
+pub const TOP: usize = 50;
+/// Longest a request may take, connecting included.
+const TIMEOUT: Duration = Duration::from_secs(5);
+/// Seconds a fetched table is shown before it is fetched again.
+const CACHE_SECS: f64 = 60.0;
+/// Seconds to wait after a failed submission before trying again.
+const RETRY_SECS: f64 = 60.0;
+/// Submissions kept while the server can't be reached; older ones go first.
+const MAX_QUEUED: usize = 100;
+
+/// Whether to take part, where, and under which name.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Leaderboard {
+    pub enabled: bool,
+    pub server: String,
+    pub name: String, // shown to everyone on the tables
+}
+
+impl Default for Leaderboard {
+    fn default() -> Self {
+        Leaderboard {
+            enabled: false,
+            server: DEFAULT_SERVER.to_string(),
+            name: String::new(),
+        }
+    }
+}
+
+impl Leaderboard {
+    /// True once it is on, with a server and a display name to submit as.
+    pub fn ready(&self) -> bool {
+        self.enabled && !self.server.trim().is_empty() && !self.name.trim().is_empty()
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}/{path}", self.server.trim().trim_end_matches('/'))
+    }
+}
+
+/// The size a leaderboard is kept for, as `WIDTHxHEIGHTxMINES`. Only the
+/// presets have one.
+pub fn spec(difficulty: Difficulty) -> Option<String> {
+    Difficulty::PRESETS.contains(&difficulty).then(|| {
+        let (width, height, mines) = difficulty.spec().params();
+        format!("{width}x{height}x{mines}")
+    })
+}
+
+/// One win, as POSTed to `/scores`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Submission {
+    pub spec: String,
+    pub name: String,
+    pub time_ms: u64,
+    pub bbbv: usize,
+    pub seed: u64,             // rebuilds the board, to check the rest against
+    pub layout_hash: String,   // `Board::layout_hash` in hex
+    pub move_log_hash: String, // `RevealTimes::hash` in hex
+}
+
+/// A table as `GET /scores/SPEC` returns it, best first.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Standings {
+    pub spec: String,
+    pub entries: Vec<Entry>, // at most TOP
+}
+
+/// One place on a table.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entry {
+    pub rank: u32,
+    pub name: String,
+    pub time_ms: u64,
+    pub bbbv: usize,
+}
+
+/// Why a request failed.
+#[derive(Debug)]
+enum Failure {
+    /// The server answered and refused it; sending it again won't help.
+    Rejected(String),
+    /// No usable answer, as when offline; worth another try later.
+    Unreachable(String),
+}
+
+impl Failure {
+    fn message(&self) -> &str {
+        match self {
+            Failure::Rejected(message) | Failure::Unreachable(message) => message,
+        }
+    }
+}
+
+/// One difficulty's table as last fetched.
+#[derive(Default)]
+struct Table {
+    standings: Option<Standings>, // from the last good fetch
+    error: Option<String>,        // why the latest fetch failed
+    fetched: Option<f64>,         // when the latest fetch finished
+    pending: Option<Receiver<Result<Standings, String>>>,
+}
+
+/// What the screen has to show for one difficulty.
+pub struct TableView<'a> {
+    pub standings: Option<&'a Standings>, // possibly stale, if `error` is set
+    pub loading: bool,
+    pub error: Option<&'a str>,
+}
+
+/// Submissions waiting to go out and the tables fetched so far. Times are
+/// egui seconds, as elsewhere in the app.
+#[derive(Default)]
+pub struct Client {
+    tables: HashMap<String, Table>,
+    queue: VecDeque<Submission>,
+    sending: Option<Receiver<Result<(), Failure>>>,
+    retry_at: f64,
+    pub last_error: Option<String>, // why the latest submission failed
+}
+
+impl Client {
+    /// Queue a win to be sent on the next `tick`.
+    pub fn submit(&mut self, submission: Submission) {
+        if self.queue.len() == MAX_QUEUED {
+            // not the one in flight, if any
+            self.queue.remove(usize::from(self.sending.is_some()));
+        }
+        self.queue.push_back(submission);
+        self.retry_at = 0.0;
+    }
+
+    /// Submissions not yet accepted by the server.
+    pub fn queued(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Pick up finished requests and send the next queued submission if
+    /// none is in flight and any wait after a failure is over. Returns the
+    /// seconds until a retry is due, to schedule a repaint.
+    pub fn tick(
+        &mut self,
+        settings: &Leaderboard,
+        now: f64,
+        wake: impl Fn() + Send + 'static,
+    ) -> Option<f64> {
+        if let Some(rx) = &self.sending {
+            match rx.try_recv() {
+                Err(TryRecvError::Empty) => return None,
+                Ok(Ok(())) => {
+                    self.queue.pop_front();
+                    self.last_error = None;
+                }
+                Ok(Err(Failure::Rejected(message))) => {
+                    warn!(%message, "leaderboard refused a score; dropping it");
+                    self.queue.pop_front();
+                    self.last_error = Some(message);
+                }
+                Ok(Err(Failure::Unreachable(message))) => {
+                    self.retry_at = now + RETRY_SECS;
+                    self.last_error = Some(message);
+                }
+                Err(TryRecvError::Disconnected) => self.retry_at = now + RETRY_SECS,
+            }
+            self.sending = None;
+        }
+
+        if !settings.ready() || self.queue.is_empty() {
+            return None;
+        }
+        if now < self.retry_at {
+            return Some(self.retry_at - now);
+        }
+        let (url, submission) = (settings.url("scores"), self.queue[0].clone());
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let result = post(&url, &submission);
+            match &result {
+                Ok(()) => info!(spec = submission.spec, "submitted score"),
+                Err(failure) => warn!(error = failure.message(), "score submission failed"),
+            }
+            let _ = tx.send(result);
+            wake();
+        });
+        self.sending = Some(rx);
+        None
+    }
+
+    /// The table for `spec`, fetching it first if it is more than a
+    /// minute old.
+    pub fn table(
+        &mut self,
+        settings: &Leaderboard,
+        spec: &str,
+        now: f64,
+        wake: impl Fn() + Send + 'static,
+    ) -> TableView<'_> {
+        let table = self.tables.entry(spec.to_string()).or_default();
+        if let Some(rx) = &table.pending {
+            match rx.try_recv() {
+                Err(TryRecvError::Empty) => {}
+                result => {
+                    match result.unwrap_or_else(|_| Err("the request was lost".to_string())) {
+                        Ok(standings) => (table.standings, table.error) = (Some(standings), None),
+                        Err(error) => table.error = Some(error),
+                    }
+                    table.fetched = Some(now);
+                    table.pending = None;
+                }
+            }
+        }
+
+        let fresh = table.fetched.is_some_and(|at| now - at < CACHE_SECS);
+        if table.pending.is_none() && !fresh && settings.enabled {
+            let url = settings.url(&format!("scores/{spec}"));
+            let (tx, rx) = mpsc::channel();
+            std::thread::spawn(move || {
+                let _ = tx.send(fetch(&url));
+                wake();
+            });
+            table.pending = Some(rx);
+        }
+        TableView {
+            standings: table.standings.as_ref(),
+            loading: table.pending.is_some(),
+            error: table.error.as_deref(),
+        }
+    }
+
+    /// Drop the cached table for `spec`, so the next look fetches it anew.
+    pub fn refresh(&mut self, spec: &str) {
+        if let Some(table) = self.tables.get_mut(spec) {
+            table.fetched = None;
+        }
+    }
+}
+
+fn agent() -> ureq::Agent {
+    ureq::AgentBuilder::new().timeout(TIMEOUT).build()
+}
+
+/// POST `submission`. A 4xx is a refusal; anything else that isn't a 2xx
+/// is worth retrying.
+fn post(url: &str, submission: &Submission) -> Result<(), Failure> {
+    let body = serde_json::to_string(submission).map_err(|e| Failure::Rejected(e.to_string()))?;
+    match agent()
+        .post(url)
+        .set("Content-Type", "application/json")
+        .send_string(&body)
+    {
+        Ok(_) => Ok(()),
+        Err(ureq::Error::Status(code, _)) if (400..500).contains(&code) => Err(Failure::Rejected(
+            format!("the leaderboard refused the score ({code})"),
+        )),
+        Err(err) => Err(Failure::Unreachable(err.to_string())),
+    }
+}
+
+/// GET a table, keeping at most `TOP` entries whatever the server sends.
+fn fetch(url: &str) -> Result<Standings, String> {
+    let text = agent()
+        .get(url)
+        .call()
+        .map_err(|e| e.to_string())?
+        .into_string()
+        .map_err(|e| e.to_string())?;
+    let mut standings: Standings =
+        serde_json::from_str(&text).map_err(|e| format!("unexpected answer: {e}"))?;
+    standings.entries.truncate(TOP);
+    Ok(standings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+
+    fn submission() -> Submission {
+        Submission {
+            spec: "9x9x10".to_string(),
+            name: "ada".to_string(),
+            time_ms: 8_250,
+            bbbv: 17,
+            seed: 7,
+            layout_hash: "38826c9aadeb2087".to_string(),
+            move_log_hash: "c0a43e1705ecacbf".to_string(),
+        }
+    }
+
+    fn settings(server: &str) -> Leaderboard {
+        Leaderboard {
+            enabled: true,
+            server: server.to_string(),
+            name: "ada".to_string(),
+        }
+    }
+
+    /// Answer one request on a local port with `response`, handing back
+    /// the request line.
+    fn mock_server(response: &'static str) -> (String, std::thread::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut request = String::new();
+            reader.read_line(&mut request).unwrap();
+            let mut length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                let line = line.trim_end().to_ascii_lowercase();
+                if line.is_empty() {
+                    break;
+                }
+                if let Some(value) = line.strip_prefix("content-length:") {
+                    length = value.trim().parse().unwrap();
+                }
+            }
+            reader.read_exact(&mut vec![0; length]).unwrap();
+            reader.into_inner().write_all(response.as_bytes()).unwrap();
+            request.trim_end().to_string()
+        });
+        (url, server)
+    }
+
+    /// Tick until the submission in flight has been settled.
+    fn settle(client: &mut Client, settings: &Leaderboard, now: f64) {
+        client.tick(settings, now, || {});
+        while client.sending.is_some() {
+            std::thread::sleep(Duration::from_millis(5));
+            client.tick(settings, now, || {});
+        }
+    }
+
+    #[test]
+    fn protocol_types_round_trip() {
+        let submission = submission();
+        let json = serde_json::to_string(&submission).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Submission>(&json).unwrap(),
+            submission
+        );
+
+        let standings = Standings {
+            spec: "16x16x40".to_string(),
+            entries: vec![Entry {
+                rank: 1,
+                name: "ada".to_string(),
+                time_ms: 30_100,
+                bbbv: 61,
+            }],
+        };
+        let json = serde_json::to_string(&standings).unwrap();
+        assert_eq!(serde_json::from_str::<Standings>(&json).unwrap(), standings);
+        let empty: Standings = serde_json::from_str(r#"{"spec":"9x9x10","entries":[]}"#).unwrap();
+        assert!(empty.entries.is_empty());
+    }
+
+    #[test]
+    fn only_presets_have_tables() {
+        assert_eq!(spec(Difficulty::Expert).as_deref(), Some("25x25x99"));
+        assert_eq!(spec(Difficulty::custom(10, 10, 10, 50)), None);
+    }
+
+    #[test]
+    fn nothing_is_sent_until_turned_on_and_named() {
+        let mut client = Client::default();
+        client.submit(submission());
+        let mut off = settings("http://127.0.0.1:1");
+        off.enabled = false;
+        client.tick(&off, 0.0, || {});
+        let unnamed = Leaderboard {
+            name: " ".to_string(),
+            ..settings("http://127.0.0.1:1")
+        };
+        client.tick(&unnamed, 0.0, || {});
+        assert!(client.sending.is_none());
+        assert_eq!(client.queued(), 1);
+        assert!(!Leaderboard::default().enabled);
+    }
+
+    #[test]
+    fn offline_submissions_wait_and_retry() {
+        let mut client = Client::default();
+        client.submit(submission());
+        let (url, server) = mock_server("HTTP/1.1 503 Service Unavailable\r\n\r\n");
+        settle(&mut client, &settings(&url), 0.0);
+        assert_eq!(server.join().unwrap(), "POST /scores HTTP/1.1");
+        assert_eq!(client.queued(), 1);
+        assert!(client.last_error.is_some());
+        assert_eq!(
+            client.tick(&settings(&url), 1.0, || {}),
+            Some(RETRY_SECS - 1.0)
+        );
+
+        let (url, _server) = mock_server("HTTP/1.1 201 Created\r\nContent-Length: 0\r\n\r\n");
+        settle(&mut client, &settings(&url), RETRY_SECS);
+        assert_eq!(client.queued(), 0);
+        assert_eq!(client.last_error, None);
+    }
+
+    #[test]
+    fn refused_submissions_are_dropped() {
+        let mut client = Client::default();
+        client.submit(submission());
+        let (url, _server) = mock_server("HTTP/1.1 422 Unprocessable Entity\r\n\r\n");
+        settle(&mut client, &settings(&url), 0.0);
+        assert_eq!(client.queued(), 0);
+        assert!(client.last_error.unwrap().contains("422"));
+    }
+}
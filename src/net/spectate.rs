@@ -0,0 +1,196 @@
+//! Watching a game served by `minesweeper_rs::serve` as a spectator: a
+//! thread reads the server's lines and the screen picks up each seat's
+//! latest board on its next frame.
+
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::io;
+use std::path::Path;
+use std::sync::mpsc::{Receiver, TryRecvError};
+
+/// A seat's board as the server sends it.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct SeatBoard {
+    pub width: usize,
+    pub height: usize,
+    pub mines: usize,
+    pub flags: usize,
+    pub state: String, // "playing", "won" or "lost"
+    pub rows: Vec<String>,
+}
+
+impl SeatBoard {
+    /// Safe cells opened so far and safe cells in all.
+    pub fn progress(&self) -> (usize, usize) {
+        let closed = ['░', '⚑', '?', '*'];
+        let revealed = self
+            .rows
+            .iter()
+            .flat_map(|row| row.chars())
+            .filter(|c| !closed.contains(c))
+            .count();
+        let safe = (self.width * self.height).saturating_sub(self.mines);
+        (revealed, safe)
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.state != "playing"
+    }
+}
+
+/// One line from the server; the ones a spectator has no use for (the
+/// role, replies) parse as `Other`.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Message {
+    Board {
+        seat: usize,
+        secs: f64,
+        board: SeatBoard,
+    },
+    Other(serde::de::IgnoredAny),
+}
+
+/// A seat as last seen, with when its clock was read.
+pub struct Seat {
+    pub board: SeatBoard,
+    secs: f64,
+    at: f64, // egui time the board came in
+}
+
+impl Seat {
+    /// The seat's clock at `now`: still running while its game goes on,
+    /// once it has started.
+    pub fn secs(&self, now: f64) -> f64 {
+        if self.board.is_over() || self.secs == 0.0 {
+            self.secs
+        } else {
+            self.secs + (now - self.at)
+        }
+    }
+}
+
+/// A connection to a served game, read on its own thread.
+pub struct Watch {
+    lines: Receiver<String>,
+    pub seats: BTreeMap<usize, Seat>,
+    pub ended: Option<String>, // why the stream stopped
+}
+
+impl Watch {
+    /// Connect to the game at `path` as a spectator. `wake` is called
+    /// for each line, so the screen redraws.
+    #[cfg(unix)]
+    pub fn connect(path: &Path, wake: impl Fn() + Send + 'static) -> io::Result<Watch> {
+        use std::io::{BufRead, BufReader, Write};
+
+        let stream = std::os::unix::net::UnixStream::connect(path)
+            .map_err(|err| io::Error::new(err.kind(), format!("{}: {err}", path.display())))?;
+        (&stream).write_all(b"role spectator\n")?;
+        let (tx, lines) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            for line in BufReader::new(stream).lines() {
+                let Ok(line) = line else { break };
+                if tx.send(line).is_err() {
+                    return;
+                }
+                wake();
+            }
+            wake(); // to see the stream end
+        });
+        Ok(Watch {
+            lines,
+            seats: BTreeMap::new(),
+            ended: None,
+        })
+    }
+
+    #[cfg(not(unix))]
+    pub fn connect(_: &Path, _: impl Fn() + Send + 'static) -> io::Result<Watch> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "spectating needs Unix sockets",
+        ))
+    }
+
+    /// Take in the lines that came since the last frame, at egui time
+    /// `now`.
+    pub fn poll(&mut self, now: f64) {
+        loop {
+            match self.lines.try_recv() {
+                Ok(line) => self.read(&line, now),
+                Err(TryRecvError::Empty) => return,
+                Err(TryRecvError::Disconnected) => {
+                    self.ended
+                        .get_or_insert_with(|| "the server hung up".to_string());
+                    return;
+                }
+            }
+        }
+    }
+
+    fn read(&mut self, line: &str, now: f64) {
+        match serde_json::from_str(line) {
+            Ok(Message::Board { seat, secs, board }) => {
+                self.seats.insert(
+                    seat,
+                    Seat {
+                        board,
+                        secs,
+                        at: now,
+                    },
+                );
+            }
+            Ok(Message::Other(_)) => {}
+            Err(err) => self.ended = Some(format!("unreadable line from the server: {err}")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    fn watch() -> Watch {
+        let (_, lines) = mpsc::channel();
+        Watch {
+            lines,
+            seats: BTreeMap::new(),
+            ended: None,
+        }
+    }
+
+    /// A board line as the server sends it, for a board one row high.
+    fn board(seat: usize, secs: f64, state: &str, row: &str) -> String {
+        format!(
+            "{{\"seat\":{seat},\"secs\":{secs:.1},\"board\":{{\"width\":{},\"height\":1,\
+             \"mines\":1,\"flags\":0,\"state\":\"{state}\",\"revision\":0,\
+             \"rows\":[\"{row}\"]}}}}",
+            row.chars().count()
+        )
+    }
+
+    #[test]
+    fn boards_replace_their_seat_and_other_lines_are_skipped() {
+        let mut watch = watch();
+        watch.read(r#"{"role":"spectator"}"#, 0.0);
+        watch.read(&board(1, 0.0, "playing", "░░░"), 1.0);
+        watch.read(&board(1, 2.5, "playing", "░1 "), 3.0);
+        watch.read(r#"{"reply":"spectators can't play"}"#, 3.0);
+
+        assert_eq!(watch.seats.len(), 1);
+        let seat = &watch.seats[&1];
+        assert_eq!(seat.board.progress(), (2, 2));
+        assert_eq!(seat.secs(4.0), 3.5, "the clock runs between boards");
+        assert!(watch.ended.is_none());
+    }
+
+    #[test]
+    fn a_finished_seat_keeps_its_time() {
+        let mut watch = watch();
+        watch.read(&board(0, 7.0, "lost", "*"), 10.0);
+        assert_eq!(watch.seats[&0].secs(60.0), 7.0);
+        assert_eq!(watch.seats[&0].board.progress(), (0, 0));
+    }
+}
@@ -0,0 +1,207 @@
+//! An optional webhook for brag posts: when a game is won, beats a
+//! personal best or (if wanted) is lost, a small JSON payload is POSTed to
+//! a URL of the player's choosing, such as a chat bot's.
+//!
+//! The request runs on its own thread with a timeout, so the game never
+//! waits on the network, and a failure is only logged: the celebration
+//! isn't the place for an error about someone's server.
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Longest a post may take, connecting included.
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Where to post, and which finished games to post about.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Webhook {
+    pub url: String, // empty for off
+    pub wins: bool,
+    pub bests: bool, // wins that beat the best time at that size
+    pub losses: bool,
+}
+
+impl Default for Webhook {
+    fn default() -> Self {
+        Webhook {
+            url: String::new(),
+            wins: true,
+            bests: true,
+            losses: false,
+        }
+    }
+}
+
+impl Webhook {
+    /// What a finished game is worth posting as, if anything. A personal
+    /// best is reported as one whenever bests or wins are on.
+    pub fn event(&self, won: bool, best: bool) -> Option<Event> {
+        if self.url.trim().is_empty() {
+            return None;
+        }
+        match (won, best) {
+            (true, true) if self.bests || self.wins => Some(Event::PersonalBest),
+            (true, _) if self.wins => Some(Event::Win),
+            (false, _) if self.losses => Some(Event::Loss),
+            _ => None,
+        }
+    }
+
+    /// Turn events on from a comma-separated list such as `win,best`.
+    pub fn set_events(&mut self, list: &str) -> Result<(), String> {
+        let (mut wins, mut bests, mut losses) = (false, false, false);
+        for name in list.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+            match name {
+                "win" => wins = true,
+                "best" => bests = true,
+                "loss" => losses = true,
+                other => return Err(format!("unknown event {other}; expected win, best or loss")),
+            }
+        }
+        (self.wins, self.bests, self.losses) = (wins, bests, losses);
+        Ok(())
+    }
+}
+
+/// Why a game is being posted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Event {
+    Win,
+    PersonalBest,
+    Loss,
+}
+
+/// The JSON body of a post.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Payload {
+    pub event: Event,
+    pub difficulty: String, // as the menus name it
+    pub width: usize,
+    pub height: usize,
+    pub mines: usize,
+    pub secs: f64,
+    pub three_bv_per_sec: f64,
+    pub seed: Option<u64>, // rebuilds the board, for friends to try it
+    pub profile: String,
+}
+
+/// Post `payload` to `url` on a background thread, logging the outcome.
+pub fn send(url: String, payload: Payload) -> std::thread::JoinHandle<()> {
+    std::thread::spawn(move || match post(&url, &payload) {
+        Ok(()) => info!(event = ?payload.event, "posted to webhook"),
+        Err(err) => warn!(%err, "webhook post failed"),
+    })
+}
+
+/// Post `payload` and wait for the answer. Anything but a 2xx is an error.
+fn post(url: &str, payload: &Payload) -> Result<(), String> {
+    let body = serde_json::to_string(payload).map_err(|e| e.to_string())?;
+    let agent = ureq::AgentBuilder::new().timeout(TIMEOUT).build();
+    agent
+        .post(url)
+        .set("Content-Type", "application/json")
+        .send_string(&body)
+        .map(drop)
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+
+    fn hook(url: &str) -> Webhook {
+        Webhook {
+            url: url.to_string(),
+            ..Webhook::default()
+        }
+    }
+
+    /// Accept one request on a local port, answer 204 and hand back the body.
+    fn mock_server() -> (String, std::thread::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                let line = line.trim_end().to_ascii_lowercase();
+                if line.is_empty() {
+                    break;
+                }
+                if let Some(value) = line.strip_prefix("content-length:") {
+                    length = value.trim().parse().unwrap();
+                }
+            }
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+            let mut stream = reader.into_inner();
+            stream
+                .write_all(b"HTTP/1.1 204 No Content\r\nConnection: close\r\n\r\n")
+                .unwrap();
+            String::from_utf8(body).unwrap()
+        });
+        (url, server)
+    }
+
+    #[test]
+    fn posts_the_payload_as_json() {
+        let (url, server) = mock_server();
+        let payload = Payload {
+            event: Event::PersonalBest,
+            difficulty: "Expert (25x25)".to_string(),
+            width: 25,
+            height: 25,
+            mines: 99,
+            secs: 80.5,
+            three_bv_per_sec: 2.25,
+            seed: Some(7),
+            profile: "Default".to_string(),
+        };
+        send(url, payload).join().unwrap();
+        let body: serde_json::Value = serde_json::from_str(&server.join().unwrap()).unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({
+                "event": "personal_best",
+                "difficulty": "Expert (25x25)",
+                "width": 25,
+                "height": 25,
+                "mines": 99,
+                "secs": 80.5,
+                "three_bv_per_sec": 2.25,
+                "seed": 7,
+                "profile": "Default",
+            })
+        );
+    }
+
+    #[test]
+    fn losses_only_post_when_asked() {
+        let mut webhook = hook("http://example.invalid/");
+        assert_eq!(webhook.event(false, false), None);
+        assert_eq!(webhook.event(true, false), Some(Event::Win));
+        assert_eq!(webhook.event(true, true), Some(Event::PersonalBest));
+        webhook.set_events("loss").unwrap();
+        assert_eq!(webhook.event(false, false), Some(Event::Loss));
+        assert_eq!(webhook.event(true, true), None);
+        assert!(webhook.set_events("win,draw").is_err());
+    }
+
+    #[test]
+    fn no_url_means_no_posts() {
+        let webhook = Webhook {
+            losses: true,
+            ..hook(" ")
+        };
+        assert_eq!(webhook.event(true, true), None);
+        assert_eq!(webhook.event(false, false), None);
+    }
+}
@@ -0,0 +1,259 @@
+//! What a puzzle asks for beyond clearing the board: a budget of clicks,
+//! a target region to open instead of the whole board, and a ban on
+//! flags. Every part is optional, and a puzzle that sets none of them is a
+//! classic game. A [`Tracker`] is told about each click and flag and
+//! checked after every move, and says whether the objective was met,
+//! failed or is still open.
+//!
+//! A click is a reveal or a chord the player makes; a puzzle's start,
+//! opened for them, is the first. Flags never count as clicks. Once the
+//! target is open the objective is met, even on the last click of the
+//! budget; a budget spent without it is a failure.
+//!
+//! ```
+//! use minesweeper_rs::objective::{Objective, Tracker, Verdict};
+//! use minesweeper_rs::Board;
+//!
+//! // open the two cells left of the mine in one click
+//! let mut board = Board::decode_layout("..*\n")?;
+//! let objective = Objective {
+//!     clicks: Some(1),
+//!     region: Some(vec![(0, 0), (1, 0)]),
+//!     no_flags: false,
+//! };
+//! let mut tracker = Tracker::new(objective);
+//! board.reveal_cell(0, 0);
+//! tracker.click();
+//! assert_eq!(tracker.check(&board), Verdict::Met);
+//! # Ok::<(), minesweeper_rs::LayoutError>(())
+//! ```
+
+use crate::Board;
+use std::fmt;
+
+/// What a puzzle asks of the player. The default is a classic game: clear
+/// the whole board, in any number of clicks, flags allowed.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Objective {
+    /// The most clicks allowed.
+    pub clicks: Option<usize>,
+    /// The safe cells to open; the whole board when `None`.
+    pub region: Option<Vec<(usize, usize)>>,
+    /// Whether placing a flag fails the puzzle.
+    pub no_flags: bool,
+}
+
+impl Objective {
+    /// Whether this is a plain clear-the-board game.
+    pub fn is_classic(&self) -> bool {
+        *self == Objective::default()
+    }
+
+    /// Whether (x, y) is in the target region. Without one, no cell is
+    /// singled out.
+    pub fn targets(&self, x: usize, y: usize) -> bool {
+        self.region
+            .as_ref()
+            .is_some_and(|region| region.contains(&(x, y)))
+    }
+
+    /// Whether every target cell of `board` is open.
+    pub fn is_met(&self, board: &Board) -> bool {
+        match &self.region {
+            Some(region) => region.iter().all(|&(x, y)| board.cell(x, y).is_revealed()),
+            None => board.is_won(),
+        }
+    }
+}
+
+impl fmt::Display for Objective {
+    /// What to do, in a phrase: "open the 3 marked cells in at most 4
+    /// clicks, without flags".
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.region {
+            Some(region) if region.len() == 1 => f.write_str("open the marked cell")?,
+            Some(region) => write!(f, "open the {} marked cells", region.len())?,
+            None => f.write_str("clear the board")?,
+        }
+        match self.clicks {
+            Some(1) => f.write_str(" in one click")?,
+            Some(clicks) => write!(f, " in at most {clicks} clicks")?,
+            None => {}
+        }
+        if self.no_flags {
+            f.write_str(", without flags")?;
+        }
+        Ok(())
+    }
+}
+
+/// The 3BV of `region` on `board`, or of the whole board without one: the
+/// openings that reach it, and its cells no opening reaches, each a
+/// click. Like [`stats::three_bv`](crate::stats::three_bv), this reads
+/// the whole layout.
+///
+/// ```
+/// use minesweeper_rs::{objective, Board};
+///
+/// let board = Board::decode_layout("...*.\n...*.\n")?;
+/// assert_eq!(objective::three_bv(&board, Some(&[(0, 0), (2, 1)])), 1);
+/// assert_eq!(objective::three_bv(&board, Some(&[(0, 0), (4, 0)])), 2);
+/// assert_eq!(objective::three_bv(&board, None), 3);
+/// # Ok::<(), minesweeper_rs::LayoutError>(())
+/// ```
+pub fn three_bv(board: &Board, region: Option<&[(usize, usize)]>) -> usize {
+    let Some(region) = region else {
+        return crate::stats::three_bv(board);
+    };
+    let width = board.width();
+    let index = |x: usize, y: usize| y * width + x;
+    let blank = |x: usize, y: usize| {
+        let cell = board.bits(x, y);
+        !cell.is_mine() && cell.neighbor_mines() == 0
+    };
+    let mut target = vec![false; width * board.height()];
+    for &(x, y) in region {
+        target[index(x, y)] = true;
+    }
+    // each opening's flood, its blank cells and their numbered edge, is
+    // a click if it opens any of the region
+    let mut reached = vec![false; target.len()];
+    let mut clicks = 0;
+    for y in 0..board.height() {
+        for x in 0..width {
+            if reached[index(x, y)] || !blank(x, y) {
+                continue;
+            }
+            reached[index(x, y)] = true;
+            let mut hits = target[index(x, y)];
+            let mut stack = vec![(x, y)];
+            while let Some((x, y)) = stack.pop() {
+                for (nx, ny) in board.neighbors(x, y) {
+                    // an edge cell two openings share counts for both
+                    hits |= target[index(nx, ny)];
+                    if !reached[index(nx, ny)] && blank(nx, ny) {
+                        reached[index(nx, ny)] = true;
+                        stack.push((nx, ny));
+                    }
+                }
+            }
+            clicks += usize::from(hits);
+        }
+    }
+    let edge = |x: usize, y: usize| board.neighbors(x, y).any(|(nx, ny)| blank(nx, ny));
+    let lone = region
+        .iter()
+        .filter(|&&(x, y)| !blank(x, y) && !edge(x, y))
+        .count();
+    clicks + lone
+}
+
+/// Where a puzzle stands against its objective.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    /// Neither met nor failed yet.
+    Open,
+    /// The target is open within the rules.
+    Met,
+    /// The objective can no longer be met.
+    Failed(Failure),
+}
+
+/// Why an objective failed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Failure {
+    /// A mine went off.
+    Exploded,
+    /// The click budget ran out with the target still closed.
+    OutOfClicks,
+    /// A flag was placed on a no-flags puzzle.
+    Flagged,
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Failure::Exploded => "a mine went off",
+            Failure::OutOfClicks => "out of clicks",
+            Failure::Flagged => "flags aren't allowed",
+        })
+    }
+}
+
+/// Follows one game against an [`Objective`]. Once met or failed, the
+/// verdict stands.
+#[derive(Clone, Debug, Default)]
+pub struct Tracker {
+    objective: Objective,
+    clicks: usize,
+    flagged: bool,
+    verdict: Option<Verdict>,
+}
+
+impl Tracker {
+    /// A game against `objective`, no clicks made yet.
+    pub fn new(objective: Objective) -> Self {
+        Tracker {
+            objective,
+            ..Tracker::default()
+        }
+    }
+
+    /// What the game is against.
+    pub fn objective(&self) -> &Objective {
+        &self.objective
+    }
+
+    /// Count a click: a reveal or a chord.
+    pub fn click(&mut self) {
+        self.clicks += 1;
+    }
+
+    /// Note that a flag went down.
+    pub fn flag(&mut self) {
+        self.flagged = true;
+    }
+
+    /// The clicks made so far.
+    pub fn clicks(&self) -> usize {
+        self.clicks
+    }
+
+    /// The clicks left in the budget, if there is one.
+    pub fn clicks_left(&self) -> Option<usize> {
+        self.objective
+            .clicks
+            .map(|budget| budget.saturating_sub(self.clicks))
+    }
+
+    /// Judge `board` after a move. A target opened on the budget's last
+    /// click is met; the budget spent with it still closed fails.
+    pub fn check(&mut self, board: &Board) -> Verdict {
+        if let Some(verdict) = self.verdict {
+            return verdict;
+        }
+        let verdict = if board.is_lost() {
+            Verdict::Failed(Failure::Exploded)
+        } else if self.objective.no_flags && self.flagged {
+            Verdict::Failed(Failure::Flagged)
+        } else if self.objective.is_met(board) {
+            Verdict::Met
+        } else if self.clicks_left() == Some(0) {
+            Verdict::Failed(Failure::OutOfClicks)
+        } else {
+            return Verdict::Open;
+        };
+        self.verdict = Some(verdict);
+        verdict
+    }
+
+    /// The verdict so far, without judging a new move.
+    pub fn verdict(&self) -> Verdict {
+        self.verdict.unwrap_or(Verdict::Open)
+    }
+
+    /// Whether the objective has been met or failed.
+    pub fn is_decided(&self) -> bool {
+        self.verdict.is_some()
+    }
+}
@@ -0,0 +1,136 @@
+//! How likely a cell was to be a mine given what the player could see, so
+//! the loss banner can tell a reckless click from bad luck.
+//!
+//! A cell next to revealed numbers gets the counted chance of a
+//! [`Solver`]: every way to place mines on the hidden cells linked to it
+//! through those numbers is tried, and each is weighted by the ways the
+//! mines left over could lie among the other unresolved cells. Counting
+//! stops after [`BUDGET`] steps so a sprawling frontier can't stall the
+//! caller; past that the cell is weighed as if no number touched it. A
+//! cell no number touches gets the density of the unresolved cells
+//! instead.
+//!
+//! [`BUDGET`]: crate::solver::BUDGET
+//!
+//! ```
+//! use minesweeper_rs::odds::{self, Chance};
+//! use minesweeper_rs::Board;
+//!
+//! let mut board = Board::decode_layout("*..\n")?;
+//! board.reveal_cell(1, 0);
+//! assert_eq!(odds::chance(&board, 0, 0), Some(Chance::Counted(0.5)));
+//! assert_eq!(
+//!     odds::chance(&board, 0, 0).unwrap().to_string(),
+//!     "It was a coin flip at 50%."
+//! );
+//! # Ok::<(), minesweeper_rs::LayoutError>(())
+//! ```
+
+use crate::solver::{Analysis, Solver};
+use crate::Board;
+use std::fmt;
+
+/// A cell's chance of being a mine, and how it was arrived at.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Chance {
+    /// Counted over every placement the numbers around it allow.
+    Counted(f64),
+    /// Counting ran past [`solver::BUDGET`](crate::solver::BUDGET), so
+    /// the cell was weighed with the ones no number touches.
+    Estimated(f64),
+    /// No number touched the cell: the share of unresolved cells that
+    /// were mines.
+    Background(f64),
+}
+
+impl Chance {
+    /// The chance itself, from 0 to 1.
+    pub fn value(self) -> f64 {
+        match self {
+            Chance::Counted(p) | Chance::Estimated(p) | Chance::Background(p) => p,
+        }
+    }
+}
+
+impl fmt::Display for Chance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let percent = (self.value() * 100.0).round();
+        match self {
+            Chance::Background(_) => write!(
+                f,
+                "Nothing around it gave it away: {percent}% of the unknown cells were mines."
+            ),
+            _ if self.value() == 1.0 => f.write_str("The numbers showed that cell was a mine."),
+            _ if percent == 50.0 => f.write_str("It was a coin flip at 50%."),
+            Chance::Estimated(_) => {
+                write!(f, "That cell was about {percent}% likely to be a mine.")
+            }
+            Chance::Counted(_) => write!(f, "That cell was {percent}% likely to be a mine."),
+        }
+    }
+}
+
+/// The chance that hidden cell (x, y) is a mine, judged from the revealed
+/// numbers and the mine count; `None` for a revealed cell. Player flags
+/// are ignored, as the solver ignores them. Only (x, y) is weighed, so
+/// this is cheaper than [`chances`] for a single cell.
+pub fn chance(board: &Board, x: usize, y: usize) -> Option<Chance> {
+    if board.cell(x, y).is_revealed() {
+        return None;
+    }
+    let mut solver = Solver::new();
+    let analysis = solver.analyze_cell(board, x, y);
+    let background = background(board, &analysis);
+    Some(judge(board, &solver, &analysis, background, x, y))
+}
+
+/// [`chance`] for every cell at once, indexed `y * width + x`, from a
+/// single count of the board.
+pub fn chances(board: &Board) -> Vec<Option<Chance>> {
+    let mut solver = Solver::new();
+    let analysis = solver.analyze(board);
+    let background = background(board, &analysis);
+    (0..board.height())
+        .flat_map(|y| (0..board.width()).map(move |x| (x, y)))
+        .map(|(x, y)| {
+            let hidden = !board.cell(x, y).is_revealed();
+            hidden.then(|| judge(board, &solver, &analysis, background, x, y))
+        })
+        .collect()
+}
+
+/// The share of the unresolved cells that are mines.
+fn background(board: &Board, analysis: &Analysis) -> f64 {
+    let hidden = (0..board.height())
+        .flat_map(|y| (0..board.width()).map(move |x| (x, y)))
+        .filter(|&(x, y)| !board.cell(x, y).is_revealed())
+        .count();
+    let unresolved = hidden - analysis.mines().len() - analysis.safe().len();
+    let left = board.mine_count().saturating_sub(analysis.mines().len());
+    left as f64 / unresolved.max(1) as f64
+}
+
+/// Hidden cell (x, y)'s chance from the analysis `solver` last made.
+fn judge(
+    board: &Board,
+    solver: &Solver,
+    analysis: &Analysis,
+    background: f64,
+    x: usize,
+    y: usize,
+) -> Chance {
+    let p = analysis.probability(x, y);
+    let touched = board
+        .neighbors(x, y)
+        .any(|(nx, ny)| board.cell(nx, ny).neighbor_mines().is_some());
+    // a deduced cell has a reason, and its chance is 0 or 1
+    if analysis.reason(x, y).is_some() {
+        Chance::Counted(p)
+    } else if !touched {
+        Chance::Background(background)
+    } else if solver.counted(x, y) {
+        Chance::Counted(p)
+    } else {
+        Chance::Estimated(p)
+    }
+}
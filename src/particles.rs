@@ -0,0 +1,577 @@
+//! Particle effects: win confetti, dust from big openings, sparks on the
+//! final flag and debris from an explosion.
+//!
+//! An `Emitter` describes an effect; `ParticleSystem::emit` starts one over
+//! a screen rect and `update_and_paint` moves, expires and draws everything
+//! once per frame. Particles live in one preallocated pool capped at
+//! `MAX_PARTICLES`, so spawning never allocates and effects can't pile up.
+//!
+//! Motion is integrated in closed form per step (gravity, the wind's sway,
+//! spin), so an effect plays out the same at 30 Hz as at 144 Hz. Particles
+//! fade out over their last `FADE_SECS` rather than vanishing.
+//!
+//! The system keeps no randomness of its own: whatever spawns takes the
+//! caller's RNG, so an effect seeded the same way and stepped by the same
+//! `dt`s plays out exactly the same, as video captures need.
+
+use eframe::egui;
+use rand::Rng;
+use std::ops::RangeInclusive;
+
+/// Live particles across all effects; spawns beyond this are dropped.
+pub const MAX_PARTICLES: usize = 2000;
+
+/// Seconds over which a particle fades out before it expires.
+pub const FADE_SECS: f32 = 0.5;
+
+/// How fast the wind swings from side to side, in radians per second.
+const WIND_RATE: f32 = 1.3;
+
+/// Debug builds log frames whose particle work takes longer than this.
+#[cfg(debug_assertions)]
+const FRAME_BUDGET: std::time::Duration = std::time::Duration::from_micros(200);
+
+/// How a particle is drawn.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Shape {
+    Rect(egui::Vec2),
+    Circle(f32),              // radius
+    Glyph(&'static str, f32), // text and font size
+}
+
+/// Initial velocity, in points per second.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Velocity {
+    /// Independent ranges for each axis.
+    Box {
+        x: RangeInclusive<f32>,
+        y: RangeInclusive<f32>,
+    },
+    /// Any direction, with a speed from the range.
+    Radial(RangeInclusive<f32>),
+}
+
+/// One effect: what it spawns and how those particles move.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Emitter {
+    pub burst: usize,  // spawned at once
+    pub rate: f32,     // spawned per second after that
+    pub duration: f32, // seconds the rate keeps going
+    pub lifetime: RangeInclusive<f32>,
+    pub velocity: Velocity,
+    pub gravity: f32,              // downward acceleration
+    pub wind: f32,                 // peak sideways drift, in points per second
+    pub spin: RangeInclusive<f32>, // radians per second, either way
+    pub pulse: f32,                // seconds between repeat bursts while `duration` lasts
+    pub colors: &'static [egui::Color32],
+    pub shape: Shape,
+}
+
+const CONFETTI_COLORS: &[egui::Color32] = &[
+    egui::Color32::RED,
+    egui::Color32::GREEN,
+    egui::Color32::BLUE,
+    egui::Color32::YELLOW,
+    egui::Color32::from_rgb(255, 0, 255),
+    egui::Color32::from_rgb(0, 255, 255),
+];
+const DUST_COLORS: &[egui::Color32] =
+    &[egui::Color32::from_gray(150), egui::Color32::from_gray(180)];
+const SPARK_COLORS: &[egui::Color32] = &[
+    egui::Color32::from_rgb(255, 230, 120),
+    egui::Color32::from_rgb(255, 200, 60),
+];
+const DEBRIS_COLORS: &[egui::Color32] = &[
+    egui::Color32::from_rgb(70, 60, 50),
+    egui::Color32::from_rgb(230, 110, 30),
+    egui::Color32::from_gray(90),
+];
+
+impl Emitter {
+    /// Win confetti thrown up and to the right in a few bursts, to start
+    /// from a top-left corner; `mirrored` for the other corner.
+    pub fn confetti() -> Self {
+        Emitter {
+            burst: 60,
+            rate: 0.0,
+            duration: 1.0,
+            lifetime: 2.0..=3.5,
+            velocity: Velocity::Box {
+                x: 80.0..=320.0,
+                y: -320.0..=-80.0,
+            },
+            gravity: 200.0,
+            wind: 30.0,
+            spin: 2.0..=9.0,
+            pulse: 0.4,
+            colors: CONFETTI_COLORS,
+            shape: Shape::Rect(egui::vec2(4.0, 8.0)),
+        }
+    }
+
+    /// The same effect thrown the other way sideways.
+    pub fn mirrored(self) -> Self {
+        let velocity = match self.velocity {
+            Velocity::Box { x, y } => Velocity::Box {
+                x: -*x.end()..=-*x.start(),
+                y,
+            },
+            radial => radial,
+        };
+        Emitter { velocity, ..self }
+    }
+
+    /// A soft puff when a large opening cascades.
+    pub fn dust() -> Self {
+        Emitter {
+            burst: 30,
+            rate: 0.0,
+            duration: 0.0,
+            lifetime: 0.4..=0.9,
+            velocity: Velocity::Radial(10.0..=40.0),
+            gravity: -10.0,
+            wind: 0.0,
+            spin: 0.0..=0.0,
+            pulse: 0.0,
+            colors: DUST_COLORS,
+            shape: Shape::Circle(3.0),
+        }
+    }
+
+    /// A quick burst when the last correct flag goes down.
+    pub fn sparks() -> Self {
+        Emitter {
+            burst: 24,
+            rate: 0.0,
+            duration: 0.0,
+            lifetime: 0.3..=0.6,
+            velocity: Velocity::Radial(80.0..=180.0),
+            gravity: 0.0,
+            wind: 0.0,
+            spin: 0.0..=0.0,
+            pulse: 0.0,
+            colors: SPARK_COLORS,
+            shape: Shape::Glyph("✦", 10.0),
+        }
+    }
+
+    /// Debris thrown up by an exploding mine, trickling for a moment.
+    pub fn debris() -> Self {
+        Emitter {
+            burst: 60,
+            rate: 60.0,
+            duration: 0.25,
+            lifetime: 0.6..=1.4,
+            velocity: Velocity::Box {
+                x: -120.0..=120.0,
+                y: -220.0..=-60.0,
+            },
+            gravity: 400.0,
+            wind: 0.0,
+            spin: 3.0..=12.0,
+            pulse: 0.0,
+            colors: DEBRIS_COLORS,
+            shape: Shape::Rect(egui::vec2(3.0, 3.0)),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Particle {
+    pos: egui::Pos2,
+    vel: egui::Vec2,
+    color: egui::Color32,
+    shape: Shape,
+    gravity: f32,
+    wind: f32,
+    phase: f32, // offset into the wind's swing, so streams don't move as one
+    angle: f32,
+    spin: f32,
+    life: f32, // seconds left
+}
+
+impl Particle {
+    /// Sideways distance the wind has carried this particle by `clock`,
+    /// up to a constant: the integral of `wind * sin(WIND_RATE * t + phase)`.
+    fn sway(&self, clock: f32) -> f32 {
+        -self.wind / WIND_RATE * (WIND_RATE * clock + self.phase).cos()
+    }
+
+    /// The colour to draw, faded over the last `FADE_SECS` of life.
+    fn faded(&self) -> egui::Color32 {
+        if self.life >= FADE_SECS {
+            self.color
+        } else {
+            self.color.gamma_multiply(self.life.max(0.0) / FADE_SECS)
+        }
+    }
+}
+
+/// An emitter still spawning at its `rate`, or in repeat bursts.
+struct Running {
+    emitter: Emitter,
+    area: egui::Rect,
+    left: f32,       // seconds of spawning left
+    pending: f32,    // fractional particles carried to the next step
+    next_pulse: f32, // seconds until the next repeat burst
+}
+
+pub struct ParticleSystem {
+    particles: Vec<Particle>,
+    running: Vec<Running>,
+    clock: f32,        // seconds since the system was last idle, for the wind
+    pub enabled: bool, // off with reduced motion
+}
+
+impl ParticleSystem {
+    pub fn new() -> Self {
+        ParticleSystem {
+            particles: Vec::with_capacity(MAX_PARTICLES),
+            running: Vec::new(),
+            clock: 0.0,
+            enabled: true,
+        }
+    }
+
+    /// Start `emitter` over `area`, in screen coordinates, its first
+    /// burst drawn from `rng`.
+    pub fn emit(&mut self, emitter: Emitter, area: egui::Rect, rng: &mut impl Rng) {
+        if !self.enabled {
+            return;
+        }
+        self.spawn(&emitter, area, emitter.burst, rng);
+        let pulsing = emitter.pulse > 0.0 && emitter.burst > 0;
+        if (emitter.rate > 0.0 || pulsing) && emitter.duration > 0.0 {
+            self.running.push(Running {
+                left: emitter.duration,
+                next_pulse: emitter.pulse,
+                emitter,
+                area,
+                pending: 0.0,
+            });
+        }
+    }
+
+    fn spawn(&mut self, emitter: &Emitter, area: egui::Rect, count: usize, rng: &mut impl Rng) {
+        let room = MAX_PARTICLES - self.particles.len();
+        for _ in 0..count.min(room) {
+            let pos = egui::pos2(
+                rng.gen_range(area.left()..=area.right()),
+                rng.gen_range(area.top()..=area.bottom()),
+            );
+            let vel = match &emitter.velocity {
+                Velocity::Box { x, y } => {
+                    egui::vec2(rng.gen_range(x.clone()), rng.gen_range(y.clone()))
+                }
+                Velocity::Radial(speed) => {
+                    let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+                    egui::Vec2::angled(angle) * rng.gen_range(speed.clone())
+                }
+            };
+            let spin = rng.gen_range(emitter.spin.clone());
+            self.particles.push(Particle {
+                pos,
+                vel,
+                color: emitter.colors[rng.gen_range(0..emitter.colors.len())],
+                shape: emitter.shape,
+                gravity: emitter.gravity,
+                wind: emitter.wind,
+                phase: rng.gen_range(0.0..=1.0),
+                angle: rng.gen_range(0.0..std::f32::consts::TAU),
+                spin: if rng.gen() { spin } else { -spin },
+                life: rng.gen_range(emitter.lifetime.clone()),
+            });
+        }
+    }
+
+    /// Advance everything by `dt` seconds, spawning from `rng`.
+    pub fn step(&mut self, dt: f32, rng: &mut impl Rng) {
+        let mut running = std::mem::take(&mut self.running);
+        for run in &mut running {
+            let spawning = dt.min(run.left);
+            run.pending += run.emitter.rate * spawning;
+            let mut count = run.pending as usize;
+            run.pending -= count as f32;
+            if run.emitter.pulse > 0.0 {
+                run.next_pulse -= spawning;
+                while run.next_pulse <= 0.0 && run.left > 0.0 {
+                    count += run.emitter.burst;
+                    run.next_pulse += run.emitter.pulse;
+                }
+            }
+            run.left -= dt;
+            self.spawn(&run.emitter, run.area, count, rng);
+        }
+        running.retain(|run| run.left > 0.0);
+        self.running = running;
+
+        let (before, after) = (self.clock, self.clock + dt);
+        for p in &mut self.particles {
+            // exact for constant gravity, whatever the step
+            p.pos += p.vel * dt + egui::vec2(0.0, 0.5 * p.gravity * dt * dt);
+            p.vel.y += p.gravity * dt;
+            p.pos.x += p.sway(after) - p.sway(before);
+            p.angle += p.spin * dt;
+            p.life -= dt;
+        }
+        self.particles.retain(|p| p.life > 0.0);
+        // restart the clock when idle, before it grows too big to step
+        self.clock = if self.is_active() { after } else { 0.0 };
+    }
+
+    pub fn len(&self) -> usize {
+        self.particles.len()
+    }
+
+    /// True while anything is on screen or still to be spawned.
+    pub fn is_active(&self) -> bool {
+        !self.particles.is_empty() || !self.running.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.particles.clear();
+        self.running.clear();
+    }
+
+    /// Step by `dt`, spawning from `rng`, and draw on top of everything
+    /// else.
+    pub fn update_and_paint(&mut self, ctx: &egui::Context, dt: f32, rng: &mut impl Rng) {
+        if !self.is_active() {
+            return;
+        }
+        #[cfg(debug_assertions)]
+        let started = std::time::Instant::now();
+        self.step(dt, rng);
+
+        let painter = ctx.layer_painter(egui::LayerId::new(
+            egui::Order::Foreground,
+            egui::Id::new("particles"),
+        ));
+        // every rect goes into one mesh, turned to its particle's angle
+        let mut rects = egui::Mesh::default();
+        for p in &self.particles {
+            let color = p.faded();
+            match p.shape {
+                Shape::Rect(size) => add_quad(&mut rects, p.pos, size, p.angle, color),
+                Shape::Circle(radius) => {
+                    painter.circle_filled(p.pos, radius, color);
+                }
+                Shape::Glyph(text, size) => {
+                    painter.text(
+                        p.pos,
+                        egui::Align2::CENTER_CENTER,
+                        text,
+                        egui::FontId::proportional(size),
+                        color,
+                    );
+                }
+            }
+        }
+        if !rects.is_empty() {
+            painter.add(rects);
+        }
+        ctx.request_repaint();
+
+        #[cfg(debug_assertions)]
+        {
+            let elapsed = started.elapsed();
+            if elapsed > FRAME_BUDGET {
+                let particles = self.len();
+                tracing::debug!(particles, ?elapsed, "particles over budget");
+            }
+        }
+    }
+}
+
+/// Append a `size` rect centred on `center` and turned by `angle` radians.
+fn add_quad(
+    mesh: &mut egui::Mesh,
+    center: egui::Pos2,
+    size: egui::Vec2,
+    angle: f32,
+    color: egui::Color32,
+) {
+    let rot = egui::emath::Rot2::from_angle(angle);
+    let half = size / 2.0;
+    let first = mesh.vertices.len() as u32;
+    for corner in [
+        egui::vec2(-half.x, -half.y),
+        egui::vec2(half.x, -half.y),
+        egui::vec2(half.x, half.y),
+        egui::vec2(-half.x, half.y),
+    ] {
+        mesh.colored_vertex(center + rot * corner, color);
+    }
+    mesh.add_triangle(first, first + 1, first + 2);
+    mesh.add_triangle(first, first + 2, first + 3);
+}
+
+impl Default for ParticleSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    const DT: f32 = 1.0 / 60.0;
+
+    fn rng() -> StdRng {
+        StdRng::seed_from_u64(7)
+    }
+
+    fn area() -> egui::Rect {
+        egui::Rect::from_min_size(egui::pos2(0.0, 0.0), egui::vec2(100.0, 100.0))
+    }
+
+    fn fixed(burst: usize, rate: f32, duration: f32, lifetime: f32) -> Emitter {
+        Emitter {
+            burst,
+            rate,
+            duration,
+            lifetime: lifetime..=lifetime,
+            ..Emitter::dust()
+        }
+    }
+
+    #[test]
+    fn bursts_expire_after_their_lifetime() {
+        let mut system = ParticleSystem::new();
+        let mut rng = rng();
+        system.emit(fixed(10, 0.0, 0.0, 0.5), area(), &mut rng);
+        assert_eq!(system.len(), 10);
+
+        for _ in 0..29 {
+            system.step(DT, &mut rng);
+        }
+        assert_eq!(system.len(), 10);
+        for _ in 0..2 {
+            system.step(DT, &mut rng);
+        }
+        assert_eq!(system.len(), 0);
+        assert!(!system.is_active());
+    }
+
+    #[test]
+    fn rate_spawns_over_the_duration() {
+        let mut system = ParticleSystem::new();
+        let mut rng = rng();
+        system.emit(fixed(0, 60.0, 0.5, 10.0), area(), &mut rng);
+        for _ in 0..60 {
+            system.step(DT, &mut rng);
+        }
+        // 60 per second for half a second, give or take float rounding
+        assert!((29..=30).contains(&system.len()), "{}", system.len());
+    }
+
+    #[test]
+    fn live_particles_are_capped() {
+        let mut system = ParticleSystem::new();
+        let mut rng = rng();
+        for _ in 0..40 {
+            system.emit(Emitter::confetti(), area(), &mut rng);
+        }
+        assert_eq!(system.len(), MAX_PARTICLES);
+    }
+
+    #[test]
+    fn confetti_comes_in_bursts() {
+        let mut system = ParticleSystem::new();
+        let mut rng = rng();
+        let burst = Emitter::confetti().burst;
+        system.emit(Emitter::confetti(), area(), &mut rng);
+        let mut counts = Vec::new();
+        for _ in 0..90 {
+            system.step(DT, &mut rng);
+            counts.push(system.len());
+        }
+        // the opening burst, then one at 0.4s and one at 0.8s
+        assert_eq!(counts[0], burst);
+        assert_eq!(counts[25], 2 * burst);
+        assert_eq!(counts[89], 3 * burst);
+    }
+
+    #[test]
+    fn motion_is_the_same_at_any_frame_rate() {
+        let run = |hz: u32| {
+            let mut system = ParticleSystem::new();
+            let mut rng = rng();
+            // one burst, as repeat bursts land on whichever frame is next
+            let emitter = Emitter {
+                lifetime: 5.0..=5.0,
+                pulse: 0.0,
+                ..Emitter::confetti()
+            };
+            system.emit(emitter, area(), &mut rng);
+            for _ in 0..hz {
+                system.step(1.0 / hz as f32, &mut rng);
+            }
+            system.particles
+        };
+        let (slow, fast) = (run(30), run(144));
+        assert_eq!(slow.len(), fast.len());
+        for (a, b) in slow.iter().zip(&fast) {
+            assert!(a.pos.distance(b.pos) < 0.05, "{:?} vs {:?}", a.pos, b.pos);
+            assert!((a.angle - b.angle).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn the_same_seed_and_steps_replay_a_celebration_exactly() {
+        let run = |seed: u64| {
+            let mut system = ParticleSystem::new();
+            let mut rng = StdRng::seed_from_u64(seed);
+            system.emit(Emitter::confetti(), area(), &mut rng);
+            system.emit(Emitter::confetti().mirrored(), area(), &mut rng);
+            let mut frames = Vec::new();
+            // uneven frames, the same in every run
+            for dt in [DT, 0.02, DT, 0.05, 0.1].repeat(12) {
+                system.step(dt, &mut rng);
+                frames.push(
+                    system
+                        .particles
+                        .iter()
+                        .map(|p| (p.pos, p.angle, p.faded()))
+                        .collect::<Vec<_>>(),
+                );
+            }
+            frames
+        };
+        let first = run(42);
+        assert!(first.iter().any(|frame| frame.len() > 100));
+        assert_eq!(first, run(42));
+        assert_ne!(first, run(43));
+    }
+
+    #[test]
+    fn particles_fade_before_expiring() {
+        let mut system = ParticleSystem::new();
+        let mut rng = rng();
+        system.emit(fixed(1, 0.0, 0.0, 1.0), area(), &mut rng);
+        let alpha = |system: &ParticleSystem| system.particles[0].faded().a();
+        let full = alpha(&system);
+        for _ in 0..30 {
+            system.step(DT, &mut rng);
+        }
+        assert_eq!(alpha(&system), full);
+        // a third of the fade left: at half, rounding lands on 128
+        for _ in 0..20 {
+            system.step(DT, &mut rng);
+        }
+        assert!(alpha(&system) < full / 2, "{}", alpha(&system));
+    }
+
+    #[test]
+    fn disabled_system_spawns_nothing() {
+        let mut system = ParticleSystem::new();
+        let mut rng = rng();
+        system.enabled = false;
+        system.emit(Emitter::debris(), area(), &mut rng);
+        system.step(DT, &mut rng);
+        assert!(!system.is_active());
+    }
+}
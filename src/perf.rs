@@ -0,0 +1,279 @@
+//! Where frame time goes, for the `dev-tools` build: scoped timers around
+//! board painting, the particle update, the solver overlay lookup and the
+//! whole update, kept for the last [`FRAMES`] frames and shown in a HUD
+//! that F12 toggles. With the `count-allocs` feature as well, a counting
+//! allocator adds allocations per frame.
+//!
+//! `profile_scope!(Scope::Board)` times the rest of the enclosing block. A
+//! scope adds its time to a thread-local tally for the frame, so timing
+//! code deep in the UI doesn't have to reach the app; [`Profiler::end_frame`]
+//! moves the tally into the ring buffer. Without `dev-tools` the macro
+//! expands to nothing and nothing here is compiled.
+//!
+//! Baselines, and how to take them, are in `benches/frame-budget.md`.
+
+/// Time the rest of the enclosing block as `scope`.
+#[cfg(feature = "dev-tools")]
+macro_rules! profile_scope {
+    ($scope:expr) => {
+        let _timer = $crate::perf::Timer::start($scope);
+    };
+}
+
+#[cfg(not(feature = "dev-tools"))]
+macro_rules! profile_scope {
+    ($($scope:tt)*) => {};
+}
+
+pub(crate) use profile_scope;
+
+#[cfg(feature = "dev-tools")]
+pub use tools::*;
+
+#[cfg(feature = "dev-tools")]
+mod tools {
+    use eframe::egui;
+    use std::cell::Cell;
+    use std::collections::VecDeque;
+    use std::time::Instant;
+
+    /// Frames kept for the averages and the sparkline.
+    pub const FRAMES: usize = 120;
+
+    /// A part of the frame that is timed.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum Scope {
+        Board,     // painting the board widget, every one drawn
+        Particles, // stepping and painting the particles
+        Overlay,   // looking up the solver's analysis overlay
+        Frame,     // the whole update
+    }
+
+    impl Scope {
+        pub const ALL: [Scope; 4] = [Scope::Board, Scope::Particles, Scope::Overlay, Scope::Frame];
+
+        pub fn label(self) -> &'static str {
+            match self {
+                Scope::Board => "Board paint",
+                Scope::Particles => "Particles",
+                Scope::Overlay => "Solver overlay",
+                Scope::Frame => "Total update",
+            }
+        }
+    }
+
+    thread_local! {
+        /// This frame's seconds per scope, so far.
+        static TALLY: Cell<[f64; Scope::ALL.len()]> = const { Cell::new([0.0; Scope::ALL.len()]) };
+    }
+
+    /// Adds the time from its start to its drop to its scope's tally.
+    pub struct Timer {
+        scope: Scope,
+        started: Instant,
+    }
+
+    impl Timer {
+        pub fn start(scope: Scope) -> Self {
+            Timer {
+                scope,
+                started: Instant::now(),
+            }
+        }
+    }
+
+    impl Drop for Timer {
+        fn drop(&mut self) {
+            let secs = self.started.elapsed().as_secs_f64();
+            TALLY.with(|tally| {
+                let mut times = tally.get();
+                times[self.scope as usize] += secs;
+                tally.set(times);
+            });
+        }
+    }
+
+    /// One frame's times, in milliseconds, and its allocations if they are
+    /// counted.
+    #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    pub struct Sample {
+        pub millis: [f32; Scope::ALL.len()],
+        pub allocs: Option<usize>,
+    }
+
+    /// The last [`FRAMES`] frames, and whether the HUD is up.
+    #[derive(Default)]
+    pub struct Profiler {
+        frames: VecDeque<Sample>,
+        allocs_at: usize, // the allocation count when the frame began
+        pub shown: bool,
+    }
+
+    impl Profiler {
+        /// Start timing a frame, dropping anything timed outside one.
+        pub fn begin_frame(&mut self) {
+            TALLY.with(|tally| tally.set([0.0; Scope::ALL.len()]));
+            self.allocs_at = alloc_count().unwrap_or(0);
+        }
+
+        /// File the frame's times away, dropping the oldest past `FRAMES`.
+        pub fn end_frame(&mut self) {
+            let times = TALLY.with(|tally| tally.replace([0.0; Scope::ALL.len()]));
+            let sample = Sample {
+                millis: times.map(|secs| (secs * 1000.0) as f32),
+                allocs: alloc_count().map(|count| count - self.allocs_at),
+            };
+            if self.frames.len() == FRAMES {
+                self.frames.pop_front();
+            }
+            self.frames.push_back(sample);
+        }
+
+        /// The mean milliseconds `scope` took over the kept frames.
+        pub fn average(&self, scope: Scope) -> f32 {
+            let total: f32 = self.frames.iter().map(|s| s.millis[scope as usize]).sum();
+            total / self.frames.len().max(1) as f32
+        }
+
+        /// The mean allocations per frame, if they are counted.
+        pub fn average_allocs(&self) -> Option<f32> {
+            let counts: Vec<usize> = self.frames.iter().filter_map(|s| s.allocs).collect();
+            (!counts.is_empty()).then(|| counts.iter().sum::<usize>() as f32 / counts.len() as f32)
+        }
+
+        /// The HUD, while `shown`: each scope's rolling average and worst
+        /// frame, the allocations, and a sparkline of the total.
+        pub fn hud(&self, ctx: &egui::Context) {
+            if !self.shown {
+                return;
+            }
+            egui::Window::new("Performance")
+                .default_pos(egui::pos2(16.0, 64.0))
+                .resizable(false)
+                .show(ctx, |ui| {
+                    egui::Grid::new("perf_scopes")
+                        .num_columns(3)
+                        .show(ui, |ui| {
+                            ui.strong("");
+                            ui.strong("avg ms");
+                            ui.strong("max ms");
+                            ui.end_row();
+                            for scope in Scope::ALL {
+                                let worst = self
+                                    .frames
+                                    .iter()
+                                    .map(|s| s.millis[scope as usize])
+                                    .fold(0.0, f32::max);
+                                ui.label(scope.label());
+                                ui.monospace(format!("{:.2}", self.average(scope)));
+                                ui.monospace(format!("{worst:.2}"));
+                                ui.end_row();
+                            }
+                        });
+                    match self.average_allocs() {
+                        Some(allocs) => ui.label(format!("{allocs:.0} allocations a frame")),
+                        None => ui.weak("Allocations: build with --features count-allocs"),
+                    };
+                    self.sparkline(ui);
+                    ui.weak(format!("Last {} frames; F12 hides this", self.frames.len()));
+                });
+        }
+
+        /// The total update time of each kept frame, oldest on the left,
+        /// against a line at 16.7 ms, a 60 Hz frame.
+        fn sparkline(&self, ui: &mut egui::Ui) {
+            const BUDGET_MS: f32 = 1000.0 / 60.0;
+            let size = egui::vec2(FRAMES as f32 * 2.0, 48.0);
+            let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+            let painter = ui.painter_at(rect);
+            painter.rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+            let top = self
+                .frames
+                .iter()
+                .map(|s| s.millis[Scope::Frame as usize])
+                .fold(BUDGET_MS * 2.0, f32::max);
+            let y = |millis: f32| rect.bottom() - rect.height() * (millis / top).min(1.0);
+            let budget = egui::Stroke::new(1.0, egui::Color32::DARK_GREEN);
+            painter.hline(rect.x_range(), y(BUDGET_MS), budget);
+            let points: Vec<egui::Pos2> = self
+                .frames
+                .iter()
+                .enumerate()
+                .map(|(i, s)| {
+                    egui::pos2(
+                        rect.left() + i as f32 * 2.0,
+                        y(s.millis[Scope::Frame as usize]),
+                    )
+                })
+                .collect();
+            let line = egui::Stroke::new(1.0, ui.visuals().text_color());
+            painter.add(egui::Shape::line(points, line));
+        }
+    }
+
+    /// Allocations made since the program started, with `count-allocs`.
+    #[cfg(feature = "count-allocs")]
+    fn alloc_count() -> Option<usize> {
+        Some(counting::ALLOCATIONS.load(std::sync::atomic::Ordering::Relaxed))
+    }
+
+    #[cfg(not(feature = "count-allocs"))]
+    fn alloc_count() -> Option<usize> {
+        None
+    }
+
+    /// The system allocator, counting every allocation it makes.
+    #[cfg(feature = "count-allocs")]
+    mod counting {
+        use std::alloc::{GlobalAlloc, Layout, System};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        pub static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+        struct Counting;
+
+        unsafe impl GlobalAlloc for Counting {
+            unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+                ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+                System.alloc(layout)
+            }
+
+            unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+                System.dealloc(ptr, layout)
+            }
+
+            unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+                ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+                System.realloc(ptr, layout, new_size)
+            }
+        }
+
+        #[global_allocator]
+        static GLOBAL: Counting = Counting;
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn scopes_add_up_per_frame_and_old_frames_roll_off() {
+            let mut profiler = Profiler::default();
+            for _ in 0..FRAMES + 5 {
+                profiler.begin_frame();
+                {
+                    profile_scope!(Scope::Board);
+                    std::thread::sleep(std::time::Duration::from_millis(1));
+                }
+                {
+                    profile_scope!(Scope::Overlay);
+                }
+                profiler.end_frame();
+            }
+            assert_eq!(profiler.frames.len(), FRAMES);
+            assert!(profiler.average(Scope::Board) >= 1.0);
+            assert!(profiler.average(Scope::Board) > profiler.average(Scope::Overlay));
+            assert_eq!(profiler.average(Scope::Particles), 0.0);
+        }
+    }
+}
@@ -0,0 +1,202 @@
+//! Boards played before, so a race board that comes round again can say
+//! so: "You've played this board twice; best 94.0 s". Kept per profile as
+//! a small file mapping each board's layout (`Board::layout_hash`) to when
+//! it was last played, how often, how often won and the fastest win.
+//!
+//! The store holds at most `MAX_BOARDS`, forgetting whichever was played
+//! longest ago. Two layouts can share a hash, so the seed a board was
+//! dealt from is kept alongside and boards whose seeds differ are told
+//! apart. A file that doesn't parse is started over, with a warning,
+//! rather than kept from being written.
+
+use crate::history;
+use chrono::NaiveDate;
+use minesweeper_rs::Board;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::io;
+use std::path::PathBuf;
+use tracing::warn;
+
+/// Boards remembered; a few months of daily races.
+pub const MAX_BOARDS: usize = 4000;
+
+/// One board as played so far.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Played {
+    pub layout: u64,
+    pub seed: Option<u64>, // set for boards dealt from one
+    pub day: NaiveDate,    // last played on
+    pub attempts: u32,
+    pub wins: u32,
+    pub best: Option<f64>, // fastest win, in seconds
+}
+
+impl Played {
+    /// Whether this is `layout` dealt from `seed`. Boards that share a
+    /// hash but have different seeds are different boards; a missing seed
+    /// on either side leaves the hash to decide.
+    fn is(&self, layout: u64, seed: Option<u64>) -> bool {
+        self.layout == layout && (self.seed.is_none() || seed.is_none() || self.seed == seed)
+    }
+
+    /// "You've played this board twice; best 94.0 s".
+    pub fn summary(&self) -> String {
+        let times = match self.attempts {
+            1 => "once".to_string(),
+            2 => "twice".to_string(),
+            n => format!("{n} times"),
+        };
+        let result = match self.best {
+            Some(best) => format!("best {best:.1} s"),
+            None => "not won yet".to_string(),
+        };
+        format!("You've played this board {times}; {result}")
+    }
+}
+
+/// The boards of one profile, least recently played first.
+#[derive(Default)]
+pub struct PlayedBoards {
+    path: Option<PathBuf>, // none for a store that is never written
+    boards: VecDeque<Played>,
+}
+
+impl PlayedBoards {
+    /// The store at `path`. A missing file is an empty store, and so is
+    /// one that can't be read, which is logged.
+    pub fn open(path: PathBuf) -> Self {
+        let boards = match std::fs::read_to_string(&path) {
+            Ok(text) => serde_json::from_str(&text).unwrap_or_else(|err| {
+                warn!(%err, path = %path.display(), "played boards unreadable; starting over");
+                VecDeque::new()
+            }),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => VecDeque::new(),
+            Err(err) => {
+                warn!(%err, path = %path.display(), "couldn't read played boards");
+                VecDeque::new()
+            }
+        };
+        PlayedBoards {
+            path: Some(path),
+            boards,
+        }
+    }
+
+    /// What is known about `board`, if it was played before.
+    pub fn lookup(&self, board: &Board) -> Option<&Played> {
+        self.find(board.layout_hash(), board.seed())
+    }
+
+    fn find(&self, layout: u64, seed: Option<u64>) -> Option<&Played> {
+        self.boards.iter().find(|played| played.is(layout, seed))
+    }
+
+    /// Note that `board` was just played to the end, won in `secs` or
+    /// lost.
+    pub fn record(&mut self, board: &Board, won: bool, secs: f64) {
+        self.note(
+            board.layout_hash(),
+            board.seed(),
+            won,
+            secs,
+            history::today(),
+        );
+    }
+
+    fn note(&mut self, layout: u64, seed: Option<u64>, won: bool, secs: f64, day: NaiveDate) {
+        let at = self
+            .boards
+            .iter()
+            .position(|played| played.is(layout, seed));
+        let mut played = at.and_then(|at| self.boards.remove(at)).unwrap_or(Played {
+            layout,
+            seed,
+            day,
+            attempts: 0,
+            wins: 0,
+            best: None,
+        });
+        played.seed = played.seed.or(seed);
+        played.day = day;
+        played.attempts += 1;
+        if won {
+            played.wins += 1;
+            played.best = Some(played.best.map_or(secs, |best| best.min(secs)));
+        }
+        self.boards.push_back(played);
+        while self.boards.len() > MAX_BOARDS {
+            self.boards.pop_front();
+        }
+    }
+
+    /// Write the store out, if it has a file.
+    pub fn save(&self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let text = serde_json::to_string(&self.boards).map_err(io::Error::other)?;
+        std::fs::write(path, text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(n: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 5, n).unwrap()
+    }
+
+    #[test]
+    fn boards_played_longest_ago_are_forgotten_first() {
+        let mut store = PlayedBoards::default();
+        for layout in 0..MAX_BOARDS as u64 {
+            store.note(layout, None, false, 0.0, day(1));
+        }
+        // playing the first again keeps it; the second goes instead
+        store.note(0, None, true, 50.0, day(2));
+        store.note(u64::MAX, None, false, 0.0, day(2));
+        assert_eq!(store.boards.len(), MAX_BOARDS);
+        assert!(store.find(1, None).is_none());
+
+        let first = store.find(0, None).unwrap();
+        assert_eq!((first.attempts, first.wins, first.best), (2, 1, Some(50.0)));
+        assert_eq!(first.day, day(2));
+    }
+
+    #[test]
+    fn colliding_hashes_with_different_seeds_stay_apart() {
+        let mut store = PlayedBoards::default();
+        store.note(7, Some(1), true, 90.0, day(1));
+        store.note(7, Some(2), false, 0.0, day(1));
+        store.note(7, Some(1), true, 80.0, day(2));
+        assert_eq!(store.boards.len(), 2);
+        assert_eq!(store.find(7, Some(1)).unwrap().best, Some(80.0));
+        assert_eq!(store.find(7, Some(2)).unwrap().wins, 0);
+        assert_eq!(
+            store.find(7, Some(1)).unwrap().summary(),
+            "You've played this board twice; best 80.0 s"
+        );
+    }
+
+    #[test]
+    fn a_corrupt_file_starts_over_and_is_written_again() {
+        let dir = std::env::temp_dir().join(format!("minesweeper-played-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("boards.json");
+        std::fs::write(&path, b"[{\"layout\": 12, \"seed\":").unwrap();
+
+        let mut store = PlayedBoards::open(path.clone());
+        assert_eq!(store.boards.len(), 0);
+        let board = Board::with_seed(9, 9, 10, 3, None);
+        store.record(&board, true, 12.5);
+        store.save().unwrap();
+
+        let store = PlayedBoards::open(path);
+        assert_eq!(store.lookup(&board).map(|p| p.best), Some(Some(12.5)));
+    }
+}
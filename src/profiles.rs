@@ -0,0 +1,317 @@
+//! Named player profiles, so people sharing a machine keep their own
+//! settings, statistics and saved game.
+//!
+//! All profiles are persisted together under one eframe storage key. The
+//! active profile's settings and history live in `AppState` while it is in
+//! use and are folded back in here on save or when switching. Each profile
+//! also gets its own directory under the data dir for its autosave slot,
+//! named by a numeric id so renaming never moves files.
+
+use crate::history::History;
+use crate::settings::Settings;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// eframe storage key for every profile.
+const STORAGE_KEY: &str = "profiles";
+
+/// Keys used before profiles existed; migrated into `DEFAULT_PROFILE`.
+const LEGACY_KEYS: [&str; 2] = ["settings", "history"];
+
+/// The profile everyone starts in, and where pre-profile data goes.
+pub const DEFAULT_PROFILE: &str = "Default";
+
+/// Longest profile name accepted, in characters.
+const MAX_NAME_CHARS: usize = 32;
+
+/// One player's persisted data.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProfileData {
+    pub id: u32, // names the profile's directory
+    pub settings: Settings,
+    pub history: History,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profiles {
+    current: String,
+    next_id: u32,
+    profiles: BTreeMap<String, ProfileData>,
+}
+
+/// Why a profile couldn't be created or renamed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NameError {
+    Empty,
+    TooLong,
+    Taken,
+}
+
+impl fmt::Display for NameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            NameError::Empty => "enter a name",
+            NameError::TooLong => "name is too long",
+            NameError::Taken => "a profile with that name already exists",
+        })
+    }
+}
+
+impl Default for Profiles {
+    fn default() -> Self {
+        let mut profiles = BTreeMap::new();
+        profiles.insert(DEFAULT_PROFILE.to_string(), ProfileData::default());
+        Profiles {
+            current: DEFAULT_PROFILE.to_string(),
+            next_id: 1,
+            profiles,
+        }
+    }
+}
+
+impl Profiles {
+    /// The stored profiles. On the first run with profiles, settings and
+    /// history saved by older versions become the "Default" profile.
+    pub fn load(storage: Option<&dyn eframe::Storage>) -> Self {
+        let Some(storage) = storage else {
+            return Profiles::default();
+        };
+        if let Some(mut profiles) = eframe::get_value::<Profiles>(storage, STORAGE_KEY) {
+            if !profiles.profiles.contains_key(&profiles.current) {
+                let first = profiles.profiles.keys().next().cloned();
+                profiles.current = first.unwrap_or_default();
+            }
+            if !profiles.profiles.is_empty() {
+                return profiles;
+            }
+        }
+
+        let mut profiles = Profiles::default();
+        let default = profiles.data_mut();
+        default.settings = eframe::get_value(storage, LEGACY_KEYS[0]).unwrap_or_default();
+        default.history = eframe::get_value(storage, LEGACY_KEYS[1]).unwrap_or_default();
+        profiles
+    }
+
+    /// Store every profile, taking the active one's data from `live`.
+    pub fn save(&mut self, storage: &mut dyn eframe::Storage, live: (&Settings, &History)) {
+        self.fold_live(live);
+        eframe::set_value(storage, STORAGE_KEY, self);
+        // the legacy copies now live in the Default profile
+        for key in LEGACY_KEYS {
+            if storage
+                .get_string(key)
+                .is_some_and(|value| !value.is_empty())
+            {
+                storage.set_string(key, String::new());
+            }
+        }
+    }
+
+    /// Copy the active profile's live settings and history in.
+    pub fn fold_live(&mut self, live: (&Settings, &History)) {
+        let data = self.data_mut();
+        data.settings = live.0.clone();
+        data.history = live.1.clone();
+    }
+
+    pub fn current(&self) -> &str {
+        &self.current
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.profiles.keys().map(String::as_str)
+    }
+
+    pub fn all(&self) -> impl Iterator<Item = (&str, &ProfileData)> {
+        self.profiles
+            .iter()
+            .map(|(name, data)| (name.as_str(), data))
+    }
+
+    /// The profile called `name`, created if there isn't one yet; also
+    /// says whether it was.
+    pub fn entry(&mut self, name: &str) -> Result<(&mut ProfileData, bool), NameError> {
+        let created = match self.create(name) {
+            Ok(()) => true,
+            Err(NameError::Taken) => false,
+            Err(err) => return Err(err),
+        };
+        let data = self.profiles.get_mut(name.trim()).expect("profile exists");
+        Ok((data, created))
+    }
+
+    pub fn data(&self) -> &ProfileData {
+        &self.profiles[&self.current]
+    }
+
+    fn data_mut(&mut self) -> &mut ProfileData {
+        self.profiles
+            .get_mut(&self.current)
+            .expect("current profile exists")
+    }
+
+    /// Make `name` active, handing back its stored data. `live` is the
+    /// outgoing profile's current settings and history.
+    pub fn switch(&mut self, name: &str, live: (Settings, History)) -> Option<&ProfileData> {
+        if !self.profiles.contains_key(name) {
+            return None;
+        }
+        let data = self.data_mut();
+        (data.settings, data.history) = live;
+        self.current = name.to_string();
+        Some(self.data())
+    }
+
+    pub fn create(&mut self, name: &str) -> Result<(), NameError> {
+        let name = self.check_name(name)?;
+        let id = self.next_id;
+        self.next_id += 1;
+        self.profiles.insert(
+            name,
+            ProfileData {
+                id,
+                ..ProfileData::default()
+            },
+        );
+        Ok(())
+    }
+
+    pub fn rename_current(&mut self, to: &str) -> Result<(), NameError> {
+        let to = self.check_name(to)?;
+        if let Some(data) = self.profiles.remove(&self.current) {
+            self.profiles.insert(to.clone(), data);
+        }
+        self.current = to;
+        Ok(())
+    }
+
+    /// Forget a profile that isn't active, returning its data so the
+    /// caller can remove its files. The active profile can't be deleted.
+    pub fn delete(&mut self, name: &str) -> Option<ProfileData> {
+        if name == self.current {
+            return None;
+        }
+        self.profiles.remove(name)
+    }
+
+    fn check_name(&self, name: &str) -> Result<String, NameError> {
+        check_name(name)?;
+        let name = name.trim();
+        if self.profiles.contains_key(name) {
+            Err(NameError::Taken)
+        } else {
+            Ok(name.to_string())
+        }
+    }
+}
+
+/// Whether `name`, trimmed, could name a profile, taken or not.
+pub fn check_name(name: &str) -> Result<(), NameError> {
+    let name = name.trim();
+    if name.is_empty() {
+        Err(NameError::Empty)
+    } else if name.chars().count() > MAX_NAME_CHARS {
+        Err(NameError::TooLong)
+    } else {
+        Ok(())
+    }
+}
+
+/// Directory holding one profile's files.
+pub fn profile_dir(data_dir: &Path, id: u32) -> PathBuf {
+    data_dir.join("profiles").join(format!("profile-{id}"))
+}
+
+/// Move an autosave slot from before profiles existed into the Default
+/// profile (id 0), unless that profile already has one.
+pub fn migrate_autosave(data_dir: &Path) {
+    let legacy = data_dir.join("autosave.bin");
+    let slot = profile_dir(data_dir, 0).join("autosave.bin");
+    if legacy.exists() && !slot.exists() {
+        let moved = std::fs::create_dir_all(profile_dir(data_dir, 0))
+            .and_then(|()| std::fs::rename(&legacy, &slot));
+        if let Err(err) = moved {
+            tracing::warn!(%err, "couldn't move autosave into the Default profile");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eframe::Storage as _;
+    use std::collections::HashMap;
+
+    #[derive(Default)]
+    struct MemoryStorage(HashMap<String, String>);
+
+    impl eframe::Storage for MemoryStorage {
+        fn get_string(&self, key: &str) -> Option<String> {
+            self.0.get(key).cloned()
+        }
+
+        fn set_string(&mut self, key: &str, value: String) {
+            self.0.insert(key.to_string(), value);
+        }
+
+        fn flush(&mut self) {}
+    }
+
+    #[test]
+    fn pre_profile_data_becomes_the_default_profile() {
+        let mut storage = MemoryStorage::default();
+        let mut settings = Settings::default();
+        settings.gameplay.no_guess = true;
+        let history = History {
+            games: 12,
+            ..History::default()
+        };
+        eframe::set_value(&mut storage, "settings", &settings);
+        eframe::set_value(&mut storage, "history", &history);
+
+        let mut profiles = Profiles::load(Some(&storage));
+        assert_eq!(profiles.current(), DEFAULT_PROFILE);
+        assert_eq!(profiles.data().settings, settings);
+        assert_eq!(profiles.data().history.games, 12);
+
+        // after a save the namespaced copy wins and the legacy keys are gone
+        profiles.save(&mut storage, (&settings, &history));
+        assert_eq!(storage.get_string("settings").as_deref(), Some(""));
+        let reloaded = Profiles::load(Some(&storage));
+        assert_eq!(reloaded.data().history.games, 12);
+    }
+
+    #[test]
+    fn switching_keeps_each_profiles_data_apart() {
+        let mut profiles = Profiles::default();
+        profiles.create("Sam").unwrap();
+        assert_eq!(profiles.create(" Sam "), Err(NameError::Taken));
+
+        let mine = History {
+            games: 3,
+            ..History::default()
+        };
+        let theirs = profiles.switch("Sam", (Settings::default(), mine)).unwrap();
+        assert_eq!(theirs.history.games, 0);
+        assert_ne!(theirs.id, 0);
+
+        let back = profiles
+            .switch(DEFAULT_PROFILE, Default::default())
+            .unwrap();
+        assert_eq!(back.history.games, 3);
+    }
+
+    #[test]
+    fn only_inactive_profiles_can_be_deleted() {
+        let mut profiles = Profiles::default();
+        profiles.create("Sam").unwrap();
+        assert!(profiles.delete(DEFAULT_PROFILE).is_none());
+        assert!(profiles.delete("Sam").is_some());
+        assert_eq!(profiles.names().collect::<Vec<_>>(), [DEFAULT_PROFILE]);
+    }
+}
@@ -0,0 +1,275 @@
+//! Hand-made puzzle files, as the board editor saves them: a mine layout
+//! in the plain-text format of [`Board::decode_layout`], after an optional
+//! front matter of TOML between two `+++` lines:
+//!
+//! ```text
+//! +++
+//! title = "Corner trap"
+//! start = [4, 0]
+//! +++
+//! *....
+//! .....
+//! ```
+//!
+//! `start` is the cell a puzzle is opened from, its forced start. Three
+//! more keys set an [`Objective`] other than clearing the board:
+//!
+//! ```text
+//! clicks = 12                       # the most clicks allowed
+//! region = [[0, 1], [1, 1], [2, 1]] # open just these cells
+//! no_flags = true                   # no flags may be placed
+//! ```
+//!
+//! Target cells must be safe cells on the board, and a budget no smaller
+//! than the 3BV of the target (see [`objective::three_bv`]). Only this
+//! subset of TOML is read: `key = value` pairs, one to a line, with blank
+//! lines and `#` comments. Other keys are skipped, so puzzle packs can add
+//! their own.
+
+use crate::objective::{self, Objective};
+use crate::{Board, LayoutError};
+use std::fmt;
+use std::str::FromStr;
+
+/// What opens and closes the front matter.
+const FENCE: &str = "+++";
+
+/// A board laid out by hand, with what is known about it.
+///
+/// ```
+/// use minesweeper_rs::puzzle::Puzzle;
+///
+/// let puzzle: Puzzle = "+++\ntitle = \"Pair\"\nstart = [2, 0]\n+++\n*..\n".parse()?;
+/// assert_eq!(puzzle.title.as_deref(), Some("Pair"));
+/// assert_eq!(puzzle.start, Some((2, 0)));
+/// assert_eq!(puzzle.to_string().parse::<Puzzle>()?.board.encode_layout(), "*..\n");
+/// # Ok::<(), minesweeper_rs::puzzle::PuzzleError>(())
+/// ```
+#[derive(Clone)]
+pub struct Puzzle {
+    /// Shown when the puzzle is listed or played.
+    pub title: Option<String>,
+    /// The safe cell the puzzle is opened from.
+    pub start: Option<(usize, usize)>,
+    /// The mines; play state isn't saved.
+    pub board: Board,
+    /// What the player has to do, a classic clear-the-board by default.
+    pub objective: Objective,
+}
+
+impl Puzzle {
+    /// `board` as a puzzle with no title or start.
+    pub fn new(board: Board) -> Self {
+        Puzzle {
+            title: None,
+            start: None,
+            board,
+            objective: Objective::default(),
+        }
+    }
+
+    /// Check that the start is a safe cell on the board, and that the
+    /// objective's target is too and its budget is enough to open it.
+    pub fn check(&self) -> Result<(), PuzzleError> {
+        let board = &self.board;
+        let safe = |(x, y): (usize, usize)| {
+            x < board.width() && y < board.height() && !board.bits(x, y).is_mine()
+        };
+        if let Some((x, y)) = self.start.filter(|&start| !safe(start)) {
+            return Err(PuzzleError::BadStart(x, y));
+        }
+        let region = self.objective.region.as_deref();
+        if let Some(&(x, y)) = region.into_iter().flatten().find(|&&cell| !safe(cell)) {
+            return Err(PuzzleError::BadTarget(x, y));
+        }
+        if let Some(clicks) = self.objective.clicks {
+            let needed = objective::three_bv(board, region);
+            if clicks < needed {
+                return Err(PuzzleError::TightBudget { clicks, needed });
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Puzzle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let objective = &self.objective;
+        if self.title.is_some() || self.start.is_some() || !objective.is_classic() {
+            writeln!(f, "{FENCE}")?;
+            if let Some(title) = &self.title {
+                let escaped = title.replace('\\', "\\\\").replace('"', "\\\"");
+                writeln!(f, "title = \"{escaped}\"")?;
+            }
+            if let Some((x, y)) = self.start {
+                writeln!(f, "start = [{x}, {y}]")?;
+            }
+            if let Some(clicks) = objective.clicks {
+                writeln!(f, "clicks = {clicks}")?;
+            }
+            if let Some(region) = &objective.region {
+                let cells: Vec<String> =
+                    region.iter().map(|(x, y)| format!("[{x}, {y}]")).collect();
+                writeln!(f, "region = [{}]", cells.join(", "))?;
+            }
+            if objective.no_flags {
+                writeln!(f, "no_flags = true")?;
+            }
+            writeln!(f, "{FENCE}")?;
+        }
+        f.write_str(&self.board.encode_layout())
+    }
+}
+
+impl FromStr for Puzzle {
+    type Err = PuzzleError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let (mut title, mut start) = (None, None);
+        let mut objective = Objective::default();
+        let mut layout = text;
+        let mut lines = text.split_inclusive('\n').enumerate();
+        if let Some((_, fence)) = lines.next().filter(|(_, line)| line.trim() == FENCE) {
+            let mut read = fence.len();
+            let mut closed = false;
+            for (number, line) in lines {
+                read += line.len();
+                let line = line.trim();
+                if line == FENCE {
+                    closed = true;
+                    break;
+                }
+                if line.is_empty() || line.starts_with('#') {
+                    continue;
+                }
+                let syntax = PuzzleError::FrontMatter(number + 1);
+                let (key, value) = line.split_once('=').ok_or(syntax.clone())?;
+                match key.trim() {
+                    "title" => title = Some(string(value.trim()).ok_or(syntax)?),
+                    "start" => start = Some(cell(value.trim()).ok_or(syntax)?),
+                    "clicks" => objective.clicks = Some(value.trim().parse().or(Err(syntax))?),
+                    "region" => objective.region = Some(cells(value.trim()).ok_or(syntax)?),
+                    "no_flags" => objective.no_flags = value.trim().parse().or(Err(syntax))?,
+                    _ => {}
+                }
+            }
+            if !closed {
+                return Err(PuzzleError::Unclosed);
+            }
+            layout = &text[read..];
+        }
+        let board = Board::decode_layout(layout).map_err(PuzzleError::Layout)?;
+        let puzzle = Puzzle {
+            title,
+            start,
+            board,
+            objective,
+        };
+        puzzle.check()?;
+        Ok(puzzle)
+    }
+}
+
+/// A TOML basic string's contents: `"..."`, with `\"` and `\\` escapes.
+fn string(value: &str) -> Option<String> {
+    let inner = value.strip_prefix('"')?.strip_suffix('"')?;
+    let mut out = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next()? {
+                c @ ('"' | '\\') => out.push(c),
+                _ => return None,
+            },
+            '"' => return None,
+            c => out.push(c),
+        }
+    }
+    Some(out)
+}
+
+/// A cell written as `[x, y]`.
+fn cell(value: &str) -> Option<(usize, usize)> {
+    let inner = value.strip_prefix('[')?.strip_suffix(']')?;
+    let (x, y) = inner.split_once(',')?;
+    Some((x.trim().parse().ok()?, y.trim().parse().ok()?))
+}
+
+/// A list of cells written as `[[x, y], ...]`.
+fn cells(value: &str) -> Option<Vec<(usize, usize)>> {
+    let inner = value.strip_prefix('[')?.strip_suffix(']')?.trim();
+    if inner.is_empty() {
+        return Some(Vec::new());
+    }
+    inner
+        .split_inclusive(']')
+        .map(|item| {
+            let item = item.trim();
+            cell(item.strip_prefix(',').unwrap_or(item).trim())
+        })
+        .collect()
+}
+
+/// Why a puzzle file couldn't be read.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PuzzleError {
+    /// This line (from 1) of the front matter isn't `key = value`, or the
+    /// value isn't the kind its key takes.
+    FrontMatter(usize),
+    /// The front matter has no closing `+++`.
+    Unclosed,
+    /// The layout after the front matter isn't one.
+    Layout(LayoutError),
+    /// The start is off the board or on a mine.
+    BadStart(usize, usize),
+    /// A target cell is off the board or on a mine.
+    BadTarget(usize, usize),
+    /// The click budget is under the 3BV of the target.
+    TightBudget {
+        /// The budget.
+        clicks: usize,
+        /// The target's 3BV.
+        needed: usize,
+    },
+}
+
+impl fmt::Display for PuzzleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PuzzleError::FrontMatter(line) => {
+                write!(
+                    f,
+                    "line {line}: expected title = \"...\", start = [x, y], clicks = n, \
+                     region = [[x, y], ...] or no_flags = true"
+                )
+            }
+            PuzzleError::Unclosed => write!(f, "the front matter has no closing {FENCE}"),
+            PuzzleError::Layout(err) => write!(f, "{err}"),
+            PuzzleError::BadStart(x, y) => {
+                write!(f, "the start ({x}, {y}) must be a safe cell on the board")
+            }
+            PuzzleError::BadTarget(x, y) => {
+                write!(
+                    f,
+                    "the target cell ({x}, {y}) must be a safe cell on the board"
+                )
+            }
+            PuzzleError::TightBudget { clicks, needed } => {
+                write!(
+                    f,
+                    "{clicks} clicks can't open a target with a 3BV of {needed}"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for PuzzleError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PuzzleError::Layout(err) => Some(err),
+            _ => None,
+        }
+    }
+}
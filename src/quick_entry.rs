@@ -0,0 +1,148 @@
+//! Quick entry: typing a cell's name as the coordinate labels write it
+//! ("B7") and Enter opens it, "FB7" flags it, without the mouse or the
+//! board's cursor. The cells the typing so far can mean are highlighted
+//! as it goes: "B" lights up column B and "B7" narrows it to the cell.
+//!
+//! A leading F is the flag prefix only when a letter follows it, so "F7"
+//! is still the cell in column F.
+
+use crate::widgets::CellClick;
+use minesweeper_rs::render;
+use minesweeper_rs::Board;
+
+/// Longest entry taken; no board has cell names anywhere near as long.
+const MAX_TYPED: usize = 12;
+
+/// The cell name typed so far.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct QuickEntry {
+    typed: String, // upper case, letters then digits
+}
+
+/// What an entry points at so far.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Target {
+    Column(usize),
+    Cell(usize, usize),
+}
+
+impl QuickEntry {
+    pub fn is_empty(&self) -> bool {
+        self.typed.is_empty()
+    }
+
+    /// Take a typed character, returning whether it fits: letters first,
+    /// then the row's digits.
+    pub fn push(&mut self, c: char) -> bool {
+        let after_digit = self.typed.ends_with(|d: char| d.is_ascii_digit());
+        let fits = if c.is_ascii_alphabetic() {
+            !after_digit
+        } else {
+            c.is_ascii_digit() && !self.typed.is_empty()
+        };
+        if !fits || self.typed.len() == MAX_TYPED {
+            return false;
+        }
+        self.typed.push(c.to_ascii_uppercase());
+        true
+    }
+
+    /// Take back the last character.
+    pub fn pop(&mut self) {
+        self.typed.pop();
+    }
+
+    pub fn clear(&mut self) {
+        self.typed.clear();
+    }
+
+    /// Whether the entry flags rather than opens, and the name after the
+    /// prefix.
+    fn parts(&self) -> (bool, &str) {
+        match self.typed.strip_prefix('F') {
+            Some(rest) if rest.starts_with(|c: char| c.is_ascii_alphabetic()) => (true, rest),
+            _ => (false, &self.typed),
+        }
+    }
+
+    /// The entry as echoed back: the name, after a flag for the prefix.
+    pub fn echo(&self) -> String {
+        match self.parts() {
+            (true, name) => format!("⚑ {name}"),
+            (false, name) => name.to_string(),
+        }
+    }
+
+    /// What the entry points at on `board`, if anything there.
+    pub fn target(&self, board: &Board) -> Option<Target> {
+        let (_, name) = self.parts();
+        let target = match render::parse_cell_name(name) {
+            Some((x, y)) => Target::Cell(x, y),
+            None => Target::Column(render::column_index(name)?),
+        };
+        let on_board = match target {
+            Target::Column(x) => x < board.width(),
+            Target::Cell(x, y) => x < board.width() && y < board.height(),
+        };
+        on_board.then_some(target)
+    }
+
+    /// The cells to highlight on `board`.
+    pub fn cells(&self, board: &Board) -> Vec<(usize, usize)> {
+        match self.target(board) {
+            Some(Target::Column(x)) => (0..board.height()).map(|y| (x, y)).collect(),
+            Some(Target::Cell(x, y)) => vec![(x, y)],
+            None => Vec::new(),
+        }
+    }
+
+    /// The move Enter makes, once the entry names a cell on `board`.
+    pub fn commit(&self, board: &Board) -> Option<CellClick> {
+        let Some(Target::Cell(x, y)) = self.target(board) else {
+            return None;
+        };
+        Some(match self.parts() {
+            (true, _) => CellClick::Flag(x, y),
+            (false, _) => CellClick::Reveal(x, y),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn typed(text: &str) -> QuickEntry {
+        let mut entry = QuickEntry::default();
+        for c in text.chars() {
+            entry.push(c);
+        }
+        entry
+    }
+
+    #[test]
+    fn typing_narrows_from_a_column_to_a_cell() {
+        let board = Board::blank(9, 9, 10);
+        assert_eq!(typed("b").cells(&board).len(), 9);
+        assert_eq!(typed("b7").cells(&board), [(1, 6)]);
+        assert_eq!(typed("B7").commit(&board), Some(CellClick::Reveal(1, 6)));
+        assert_eq!(typed("B").commit(&board), None, "not a cell yet");
+        assert_eq!(typed("B10").target(&board), None, "off the board");
+        assert_eq!(typed("7B").echo(), "B", "names start with a letter");
+        assert_eq!(typed("B7C").echo(), "B7", "and letters don't follow digits");
+    }
+
+    #[test]
+    fn f_flags_only_before_a_letter() {
+        let board = Board::blank(9, 9, 10);
+        assert_eq!(typed("fb7").commit(&board), Some(CellClick::Flag(1, 6)));
+        assert_eq!(typed("fb7").echo(), "⚑ B7");
+        assert_eq!(typed("f7").commit(&board), Some(CellClick::Reveal(5, 6)));
+        assert_eq!(typed("ff2").commit(&board), Some(CellClick::Flag(5, 1)));
+        assert_eq!(
+            typed("f").cells(&board).len(),
+            9,
+            "column F until a letter follows"
+        );
+    }
+}
@@ -0,0 +1,1017 @@
+//! Seed races: a few shared seeds played back to back and compared
+//! afterwards by swapping files. There is no server: a race file goes out,
+//! everyone plays it on their own time, and the result files come back.
+//!
+//! Each race board is built from its seed with the centre kept clear, and
+//! the centre is opened before the clock starts, so everyone gets the same
+//! opening. A result carries every board's move log, which `verify` replays
+//! against the seeds (see `minesweeper_rs::replay`), and a hash of the log and time. That catches casual
+//! edits to a result file, not a determined cheat, which is as much as a
+//! file anyone can rewrite allows.
+//!
+//! Results saved since move times were kept also time every move, and
+//! `verify` checks those run forward and end within the board's time, so
+//! a time can't be cut without rewriting the whole log to match.
+//!
+//! Each run also records the layout hash (`Board::layout_hash`) of the board
+//! it was played on, so anyone can check with `audit` that a seed gives
+//! that board, and a result played on some other board is refused.
+//!
+//! The host can give players a handicap, seconds added to their total when
+//! results are compared (the clock itself starts the same for everyone).
+//! Handicaps are part of the race, so results for a race with different
+//! handicaps are for a different race, and a player's handicap goes into
+//! the hash of each of their boards, so renaming the player to shed one
+//! shows up as tampering.
+
+use crate::challenge::Modifier;
+use crate::cli::CliError;
+use minesweeper_rs::difficulty::MAX_CUSTOM_SIDE;
+use minesweeper_rs::replay;
+use minesweeper_rs::timeline::{FlagLog, FlagReview};
+use minesweeper_rs::{format, Board, BoardOptions, Difficulty, FlagResult};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::{self, Write as _};
+use std::path::Path;
+
+pub use minesweeper_rs::replay::{Flaw, Move};
+
+/// Boards in a newly created race.
+pub const RACE_BOARDS: usize = 5;
+
+/// Seconds a lost board counts for.
+pub const LOSS_PENALTY_SECS: f64 = 300.0;
+
+/// Most boards a race file may list.
+const MAX_RACE_BOARDS: usize = 50;
+
+/// The boards everyone plays: one size and mine count, several seeds.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Race {
+    pub width: usize,
+    pub height: usize,
+    pub mines: usize,
+    pub seeds: Vec<u64>,
+    /// Seconds added to a player's total, by player name; none for most
+    /// races.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub handicaps: BTreeMap<String, i64>,
+    /// The special rule every board is played under, for a weekly
+    /// challenge (see `challenge`); none for most races. It changes the
+    /// boards the seeds give, so a result played without it doesn't
+    /// verify.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modifier: Option<Modifier>,
+}
+
+/// How one board of a race went.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BoardRun {
+    pub secs: f64,
+    pub won: bool,
+    pub moves: Vec<Move>,
+    /// Seconds into the board each move was made, one per move; empty in
+    /// results from before moves were timed.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub times: Vec<f64>,
+    pub layout: String, // the board's layout hash, in hex
+    pub hash: String,   // of the moves and time, see `run_hash`
+}
+
+/// One player's go at a race, as swapped between players.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RaceResult {
+    pub player: String,
+    pub race: Race,
+    pub boards: Vec<BoardRun>, // in seed order; fewer if unfinished
+}
+
+/// Why a race or result file was refused.
+#[derive(Debug)]
+pub enum RaceError {
+    Json(serde_json::Error),
+    Io(std::io::Error),
+    Invalid,                  // no seeds, too many, or a board that can't be built
+    TooLarge,                 // a side over MAX_CUSTOM_SIDE, checked before building
+    Tampered(usize, Flaw),    // this board's log doesn't replay as claimed
+    WrongBoard(usize),        // this board's layout hash isn't its seed's
+    OtherRace,                // results for a different race
+    NegativeHandicap(String), // handicaps only ever add time
+}
+
+impl fmt::Display for RaceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RaceError::Json(err) => write!(f, "not a race file: {err}"),
+            RaceError::Io(err) => write!(f, "{err}"),
+            RaceError::Invalid => f.write_str("race has no playable boards"),
+            RaceError::TooLarge => write!(
+                f,
+                "race boards are at most {MAX_CUSTOM_SIDE}x{MAX_CUSTOM_SIDE}"
+            ),
+            RaceError::Tampered(index, flaw) => {
+                write!(f, "board {} doesn't match its move log: {flaw}", index + 1)
+            }
+            RaceError::WrongBoard(index) => {
+                write!(
+                    f,
+                    "board {} wasn't played on the board its seed gives",
+                    index + 1
+                )
+            }
+            RaceError::OtherRace => f.write_str("those results are for a different race"),
+            RaceError::NegativeHandicap(player) => {
+                write!(f, "{player}'s handicap is negative; handicaps add time")
+            }
+        }
+    }
+}
+
+impl std::error::Error for RaceError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RaceError::Json(err) => Some(err),
+            RaceError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<serde_json::Error> for RaceError {
+    fn from(err: serde_json::Error) -> Self {
+        RaceError::Json(err)
+    }
+}
+
+impl From<std::io::Error> for RaceError {
+    fn from(err: std::io::Error) -> Self {
+        RaceError::Io(err)
+    }
+}
+
+impl Race {
+    /// `RACE_BOARDS` random seeds at `difficulty`.
+    pub fn random(difficulty: Difficulty, rng: &mut impl Rng) -> Self {
+        let (width, height, mines) = difficulty.spec().params();
+        Race {
+            width,
+            height,
+            mines,
+            seeds: (0..RACE_BOARDS).map(|_| rng.gen()).collect(),
+            handicaps: BTreeMap::new(),
+            modifier: None,
+        }
+    }
+
+    pub fn from_json(text: &str) -> Result<Self, RaceError> {
+        let race: Race = serde_json::from_str(text)?;
+        race.check()?;
+        Ok(race)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("races serialize")
+    }
+
+    pub fn load(path: &Path) -> Result<Self, RaceError> {
+        Race::from_json(&std::fs::read_to_string(path)?)
+    }
+
+    fn check(&self) -> Result<(), RaceError> {
+        if self.seeds.is_empty() || self.seeds.len() > MAX_RACE_BOARDS {
+            return Err(RaceError::Invalid);
+        }
+        if let Some((player, _)) = self.handicaps.iter().find(|(_, &secs)| secs < 0) {
+            return Err(RaceError::NegativeHandicap(player.clone()));
+        }
+        // everyone plays every board, so one too big to build is refused
+        // before the first is laid
+        if self.width > MAX_CUSTOM_SIDE || self.height > MAX_CUSTOM_SIDE {
+            return Err(RaceError::TooLarge);
+        }
+        self.options(0).check().map_err(|_| RaceError::Invalid)
+    }
+
+    /// Name for menus, e.g. "5 × Intermediate (16x16)", with the special
+    /// rule after a dot if there is one.
+    pub fn label(&self) -> String {
+        let boards = self.seeds.len();
+        let label = match Difficulty::from_params(self.width, self.height, self.mines) {
+            Difficulty::Custom { .. } => format!(
+                "{boards} × {}x{}, {} mines",
+                self.width, self.height, self.mines
+            ),
+            preset => format!("{boards} × {}", preset.label()),
+        };
+        match self.modifier {
+            Some(modifier) => format!("{label} · {}", modifier.name()),
+            None => label,
+        }
+    }
+
+    /// Give `player` a handicap of `secs`, or take theirs away with 0.
+    pub fn set_handicap(&mut self, player: &str, secs: i64) -> Result<(), RaceError> {
+        match secs {
+            ..=-1 => return Err(RaceError::NegativeHandicap(player.into())),
+            0 => self.handicaps.remove(player),
+            _ => self.handicaps.insert(player.into(), secs),
+        };
+        Ok(())
+    }
+
+    /// Seconds added to `player`'s total.
+    pub fn handicap(&self, player: &str) -> i64 {
+        self.handicaps.get(player).copied().unwrap_or(0)
+    }
+
+    /// The cell opened for everyone before the clock starts.
+    pub fn start(&self) -> (usize, usize) {
+        (self.width / 2, self.height / 2)
+    }
+
+    /// Options that lay board `index`: its seed, the starting cell kept
+    /// clear and the modifier's rule.
+    pub fn options(&self, index: usize) -> BoardOptions {
+        let (x, y) = self.start();
+        let options = BoardOptions::new(self.width, self.height, self.mines)
+            .seed(self.seeds[index])
+            .safe_start(x, y);
+        match self.modifier {
+            Some(modifier) => modifier.apply(options, self.mines),
+            None => options,
+        }
+    }
+
+    /// Board `index`, with the starting cell already open. This is how a
+    /// seed becomes a board for races and for `audit`.
+    pub fn board(&self, index: usize) -> Board {
+        let (x, y) = self.start();
+        let seed = self.seeds[index];
+        // a modifier's layout that finds no room gives way to a plain one,
+        // for everyone alike
+        let mut board = self.options(index).build().unwrap_or_else(|_| {
+            Board::with_seed(self.width, self.height, self.mines, seed, Some((x, y)))
+        });
+        board.reveal_cell(x, y);
+        board
+    }
+}
+
+impl BoardRun {
+    /// A finished board of `race`, hashed along with `player`'s handicap.
+    #[cfg(test)]
+    pub fn new(
+        race: &Race,
+        player: &str,
+        index: usize,
+        secs: f64,
+        won: bool,
+        moves: Vec<Move>,
+    ) -> Self {
+        BoardRun::timed(race, player, index, secs, won, moves, Vec::new())
+    }
+
+    /// `new` with the seconds into the board of each move, as play
+    /// records them.
+    pub fn timed(
+        race: &Race,
+        player: &str,
+        index: usize,
+        secs: f64,
+        won: bool,
+        moves: Vec<Move>,
+        times: Vec<f64>,
+    ) -> Self {
+        let handicap = race.handicap(player);
+        let hash = run_hash(
+            race.seeds[index],
+            index,
+            handicap,
+            secs,
+            won,
+            &moves,
+            &times,
+        );
+        BoardRun {
+            secs,
+            won,
+            moves,
+            times,
+            layout: layout_hash(&race.board(index)),
+            hash,
+        }
+    }
+
+    /// Seconds this board counts for in the total.
+    pub fn score(&self) -> f64 {
+        if self.won {
+            self.secs
+        } else {
+            LOSS_PENALTY_SECS
+        }
+    }
+
+    /// Board `index` of `race` replayed from this log with its flags
+    /// checked against the mines. Race logs keep no times, so flags are
+    /// placed by move number alone.
+    pub fn flag_review(&self, race: &Race, index: usize) -> Option<FlagReview> {
+        let mut board = race.board(index);
+        let mut log = FlagLog::default();
+        for (i, &m) in self.moves.iter().enumerate() {
+            let Move::Flag(x, y) = m else {
+                m.apply(&mut board);
+                continue;
+            };
+            let placed = match board.toggle_flag(x, y) {
+                FlagResult::Flagged => true,
+                FlagResult::Unflagged => false,
+                FlagResult::Ignored(_) => continue,
+            };
+            log.record((x, y), placed, i as u32 + 1, None);
+        }
+        log.review(&board)
+    }
+
+    /// Time taken, or the penalty for a loss.
+    pub fn describe(&self) -> String {
+        if self.won {
+            format::clock_tenths(self.secs)
+        } else {
+            format!("lost (+{LOSS_PENALTY_SECS:.0}s)")
+        }
+    }
+}
+
+impl RaceResult {
+    pub fn from_json(text: &str) -> Result<Self, RaceError> {
+        let result: RaceResult = serde_json::from_str(text)?;
+        result.verify()?;
+        Ok(result)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("results serialize")
+    }
+
+    pub fn load(path: &Path) -> Result<Self, RaceError> {
+        RaceResult::from_json(&std::fs::read_to_string(path)?)
+    }
+
+    /// Check every board against its hashes and replay its moves from the
+    /// seed, so a loss can't be passed off as a win, nor a game on another
+    /// board as this race's, nor a handicapped player's as someone else's.
+    /// Each move has to do something, as play only logs those, and none may
+    /// follow the end of the board; the error says which check failed.
+    pub fn verify(&self) -> Result<(), RaceError> {
+        self.race.check()?;
+        if self.boards.len() > self.race.seeds.len() {
+            return Err(RaceError::Invalid);
+        }
+        for (index, run) in self.boards.iter().enumerate() {
+            self.verify_run(index, run)?;
+        }
+        Ok(())
+    }
+
+    /// One board's checks, in the order that names the most telling flaw.
+    fn verify_run(&self, index: usize, run: &BoardRun) -> Result<(), RaceError> {
+        let mut board = self.race.board(index);
+        if layout_hash(&board) != run.layout {
+            return Err(RaceError::WrongBoard(index));
+        }
+        let tampered = |flaw| Err(RaceError::Tampered(index, flaw));
+        if let Err(flaw) = replay::verify(&mut board, &run.moves, &run.times, run.secs, run.won) {
+            return tampered(flaw);
+        }
+        let seed = self.race.seeds[index];
+        let (handicap, moves) = (self.handicap(), &run.moves);
+        if run_hash(seed, index, handicap, run.secs, run.won, moves, &run.times) != run.hash {
+            return tampered(Flaw::Hash);
+        }
+        Ok(())
+    }
+
+    /// `--analyze`: every board's flag review as text.
+    pub fn flag_report(&self) -> String {
+        let mut out = String::new();
+        for (index, run) in self.boards.iter().enumerate() {
+            let _ = writeln!(out, "Board {} ({}):", index + 1, run.describe());
+            match run.flag_review(&self.race, index) {
+                Some(review) if review.flags.is_empty() => out.push_str("No flags\n"),
+                Some(review) => out.push_str(&review.to_string()),
+                None => out.push_str("Unfinished\n"),
+            }
+        }
+        out
+    }
+
+    /// Sum of the board scores, penalties included.
+    pub fn total_secs(&self) -> f64 {
+        self.boards.iter().map(BoardRun::score).sum()
+    }
+
+    /// This player's handicap in seconds.
+    pub fn handicap(&self) -> i64 {
+        self.race.handicap(&self.player)
+    }
+
+    /// The total with the handicap added, as results are ranked.
+    pub fn adjusted_secs(&self) -> f64 {
+        self.total_secs() + self.handicap() as f64
+    }
+
+    /// Boards won, which breaks ties between equal adjusted totals.
+    pub fn boards_won(&self) -> usize {
+        self.boards.iter().filter(|run| run.won).count()
+    }
+}
+
+/// The place of each of `results`, in the same order, from 1. Adjusted
+/// totals are compared to the tenth of a second shown, so a handicap
+/// can't win on rounding; equal ones go to whoever won more boards, and
+/// are shared if that's equal too.
+pub fn places(results: &[RaceResult]) -> Vec<usize> {
+    let key = |r: &RaceResult| {
+        let tenths = (r.adjusted_secs() * 10.0).round() as i64;
+        (tenths, std::cmp::Reverse(r.boards_won()))
+    };
+    let keys: Vec<_> = results.iter().map(key).collect();
+    keys.iter()
+        .map(|k| 1 + keys.iter().filter(|other| *other < k).count())
+        .collect()
+}
+
+/// "+30s", or "–" for no handicap.
+pub fn describe_handicap(secs: i64) -> String {
+    if secs == 0 {
+        "–".into()
+    } else {
+        format!("+{secs}s")
+    }
+}
+
+/// Side-by-side table of `results`, one column per player, for the
+/// terminal. All of them must be for the same race.
+pub fn comparison_table(results: &[RaceResult]) -> Result<String, RaceError> {
+    let Some(first) = results.first() else {
+        return Ok(String::new());
+    };
+    if results.iter().any(|r| r.race != first.race) {
+        return Err(RaceError::OtherRace);
+    }
+    let mut out = format!("{:<8}", "");
+    for result in results {
+        let _ = write!(out, "{:>16}", result.player);
+    }
+    out.push('\n');
+    for index in 0..first.race.seeds.len() {
+        let _ = write!(out, "{:<8}", format!("Board {}", index + 1));
+        for result in results {
+            let cell = result
+                .boards
+                .get(index)
+                .map_or("–".into(), BoardRun::describe);
+            let _ = write!(out, "{cell:>16}");
+        }
+        out.push('\n');
+    }
+    let _ = write!(out, "{:<8}", "Total");
+    for result in results {
+        let _ = write!(out, "{:>16}", format::clock_tenths(result.total_secs()));
+    }
+    out.push('\n');
+    if !first.race.handicaps.is_empty() {
+        let _ = write!(out, "{:<8}", "Handicap");
+        for result in results {
+            let _ = write!(out, "{:>16}", describe_handicap(result.handicap()));
+        }
+        let _ = write!(out, "\n{:<8}", "Adjusted");
+        for result in results {
+            let _ = write!(out, "{:>16}", format::clock_tenths(result.adjusted_secs()));
+        }
+        out.push('\n');
+    }
+    let _ = write!(out, "{:<8}", "Place");
+    for place in places(results) {
+        let _ = write!(out, "{place:>16}");
+    }
+    out.push('\n');
+    Ok(out)
+}
+
+/// FNV-1a over everything a result claims about one board. Written out
+/// rather than using std's hasher, whose output may change between
+/// releases. No handicap and no move times hash as before either existed,
+/// so older results still verify.
+fn run_hash(
+    seed: u64,
+    index: usize,
+    handicap: i64,
+    secs: f64,
+    won: bool,
+    moves: &[Move],
+    times: &[f64],
+) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    let mut eat = |bytes: &[u8]| {
+        for &b in bytes {
+            hash ^= u64::from(b);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    };
+    eat(&seed.to_le_bytes());
+    eat(&(index as u64).to_le_bytes());
+    if handicap != 0 {
+        eat(b"h");
+        eat(&handicap.to_le_bytes());
+    }
+    eat(&secs.to_bits().to_le_bytes());
+    eat(&[u8::from(won)]);
+    for m in moves {
+        let (kind, x, y) = match *m {
+            Move::Reveal(x, y) => (b'r', x, y),
+            Move::Flag(x, y) => (b'f', x, y),
+        };
+        eat(&[kind]);
+        eat(&(x as u64).to_le_bytes());
+        eat(&(y as u64).to_le_bytes());
+    }
+    if !times.is_empty() {
+        eat(b"t");
+        for at in times {
+            eat(&at.to_bits().to_le_bytes());
+        }
+    }
+    format!("{hash:016x}")
+}
+
+/// `Board::layout_hash` as it appears in files and tooltips.
+pub fn layout_hash(board: &Board) -> String {
+    format!("{:016x}", board.layout_hash())
+}
+
+/// `audit --seed SEED --spec SPEC`: the layout hash of the race board
+/// `SEED` gives, where `SPEC` is `b`, `i` or `e` (or the full preset name)
+/// or `WIDTHxHEIGHTxMINES`.
+pub fn audit(words: &[String]) -> Result<String, CliError> {
+    let usage = || CliError::Usage(AUDIT_USAGE.into());
+    let (mut seed, mut spec) = (None, None);
+    let mut words = words.iter();
+    while let Some(word) = words.next() {
+        match word.as_str() {
+            "--seed" => seed = words.next().and_then(|s| s.parse::<u64>().ok()),
+            "--spec" => spec = words.next().map(String::as_str).and_then(board_spec),
+            _ => return Err(usage()),
+        }
+    }
+    let (Some(seed), Some((width, height, mines))) = (seed, spec) else {
+        return Err(usage());
+    };
+    let race = Race {
+        width,
+        height,
+        mines,
+        seeds: vec![seed],
+        handicaps: BTreeMap::new(),
+        modifier: None,
+    };
+    race.check().map_err(|e| CliError::Usage(e.to_string()))?;
+    Ok(layout_hash(&race.board(0)))
+}
+
+const AUDIT_USAGE: &str = "usage: audit --seed SEED --spec b|i|e|WIDTHxHEIGHTxMINES";
+
+/// `b`/`beginner` and so on, or `WIDTHxHEIGHTxMINES`.
+fn board_spec(text: &str) -> Option<(usize, usize, usize)> {
+    let text = text.to_ascii_lowercase();
+    let short = match text.as_str() {
+        "b" => Some(Difficulty::Beginner),
+        "i" => Some(Difficulty::Intermediate),
+        "e" => Some(Difficulty::Expert),
+        name => preset(name),
+    };
+    if let Some(difficulty) = short {
+        return Some(difficulty.spec().params());
+    }
+    let numbers: Vec<usize> = text
+        .split('x')
+        .map(|n| n.parse().ok())
+        .collect::<Option<_>>()?;
+    match numbers[..] {
+        [width, height, mines] => Some((width, height, mines)),
+        _ => None,
+    }
+}
+
+/// `race create [beginner|intermediate|expert] [--handicap PLAYER=SECS]...
+/// [FILE]`, `race run FILE` and `race compare RESULTS...`. Returns the race to play for `run`.
+pub fn cli(words: &[String]) -> Result<Option<Race>, CliError> {
+    let usage = || CliError::Usage(USAGE.into());
+    let words: Vec<&str> = words.iter().map(String::as_str).collect();
+    match words.as_slice() {
+        ["create", rest @ ..] => {
+            let (difficulty, file) = match rest {
+                [name, file @ ..] if preset(name).is_some() => (preset(name), file),
+                file => (None, file),
+            };
+            let mut race = Race::random(
+                difficulty.unwrap_or(Difficulty::Intermediate),
+                &mut rand::thread_rng(),
+            );
+            let mut file = file;
+            while let ["--handicap", given, rest @ ..] = file {
+                let (player, secs) = given
+                    .split_once('=')
+                    .and_then(|(player, secs)| Some((player, secs.parse().ok()?)))
+                    .ok_or_else(usage)?;
+                race.set_handicap(player, secs)
+                    .map_err(|e| CliError::Usage(e.to_string()))?;
+                file = rest;
+            }
+            match file {
+                [] => println!("{}", race.to_json()),
+                [file] => std::fs::write(file, race.to_json())
+                    .map_err(|e| CliError::Output(format!("{file}: {e}")))?,
+                _ => return Err(usage()),
+            }
+            Ok(None)
+        }
+        ["run", file] => Race::load(Path::new(file))
+            .map(Some)
+            .map_err(|e| CliError::Input(format!("{file}: {e}"))),
+        ["compare", files @ ..] if !files.is_empty() => {
+            let results = files
+                .iter()
+                .map(|file| {
+                    RaceResult::load(Path::new(file))
+                        .map_err(|e| CliError::Input(format!("{file}: {e}")))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let table = comparison_table(&results).map_err(|e| CliError::Input(e.to_string()))?;
+            print!("{table}");
+            Ok(None)
+        }
+        _ => Err(usage()),
+    }
+}
+
+const USAGE: &str = "usage: race create [beginner|intermediate|expert] \
+                     [--handicap PLAYER=SECS]... [FILE]\n       \
+                     race run FILE\n       race compare RESULTS...";
+
+fn preset(name: &str) -> Option<Difficulty> {
+    match name {
+        "beginner" => Some(Difficulty::Beginner),
+        "intermediate" => Some(Difficulty::Intermediate),
+        "expert" => Some(Difficulty::Expert),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    fn race() -> Race {
+        Race::random(
+            Difficulty::Beginner,
+            &mut rand::rngs::StdRng::seed_from_u64(9),
+        )
+    }
+
+    /// Reveal every safe cell of board `index`, winning it.
+    fn winning_moves(race: &Race, index: usize) -> Vec<Move> {
+        let mut board = race.board(index);
+        let layout = board.encode_layout();
+        let mut moves = Vec::new();
+        for (y, row) in layout.lines().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                if c == '.' && Move::Reveal(x, y).apply(&mut board) {
+                    moves.push(Move::Reveal(x, y));
+                }
+            }
+        }
+        assert!(board.is_won());
+        moves
+    }
+
+    fn result(race: &Race) -> RaceResult {
+        result_of(race, "Ana")
+    }
+
+    /// `player` winning board `i` in 10 + `i` seconds.
+    fn result_of(race: &Race, player: &str) -> RaceResult {
+        let boards = (0..race.seeds.len())
+            .map(|i| {
+                BoardRun::new(
+                    race,
+                    player,
+                    i,
+                    10.0 + i as f64,
+                    true,
+                    winning_moves(race, i),
+                )
+            })
+            .collect();
+        RaceResult {
+            player: player.into(),
+            race: race.clone(),
+            boards,
+        }
+    }
+
+    #[test]
+    fn race_files_round_trip_and_share_boards() {
+        let race = race();
+        let again = Race::from_json(&race.to_json()).unwrap();
+        assert_eq!(again, race);
+        assert_eq!(
+            again.board(2).encode_layout(),
+            race.board(2).encode_layout()
+        );
+        assert!(race.board(0).cell(4, 4).is_revealed());
+    }
+
+    #[test]
+    fn unplayable_races_are_refused() {
+        let mut race = race();
+        race.mines = 81;
+        assert!(matches!(
+            Race::from_json(&race.to_json()),
+            Err(RaceError::Invalid)
+        ));
+
+        // refused before a board is laid, let alone five
+        let giant = r#"{"width": 4000000000, "height": 4000000000, "mines": 1, "seeds": [1]}"#;
+        assert!(matches!(Race::from_json(giant), Err(RaceError::TooLarge)));
+        assert!(audit(&["--seed", "1", "--spec", "4000x4000x10"].map(String::from)).is_err());
+    }
+
+    #[test]
+    fn results_verify_and_total_with_penalties() {
+        let race = race();
+        let mut result = result(&race);
+        let text = result.to_json();
+        assert_eq!(RaceResult::from_json(&text).unwrap(), result);
+        assert_eq!(result.total_secs(), 10.0 + 11.0 + 12.0 + 13.0 + 14.0);
+
+        result.boards[1] = BoardRun::new(&race, "Ana", 1, 3.0, false, Vec::new());
+        assert_eq!(
+            result.total_secs(),
+            10.0 + LOSS_PENALTY_SECS + 12.0 + 13.0 + 14.0
+        );
+        result.verify().unwrap();
+    }
+
+    #[test]
+    fn edited_results_are_caught() {
+        let race = race();
+        let mut faster = result(&race);
+        faster.boards[0].secs = 1.0;
+        assert!(matches!(faster.verify(), Err(RaceError::Tampered(0, _))));
+
+        // a rehashed claim of a win still has to replay as one
+        let mut fake = result(&race);
+        fake.boards[3] = BoardRun::new(&race, "Ana", 3, 2.0, true, Vec::new());
+        assert!(matches!(fake.verify(), Err(RaceError::Tampered(3, _))));
+    }
+
+    /// Board `index` won by "Ana" with a move a second.
+    fn timed_run(race: &Race, index: usize) -> BoardRun {
+        let moves = winning_moves(race, index);
+        let times: Vec<f64> = (1..=moves.len()).map(|n| n as f64).collect();
+        let secs = moves.len() as f64;
+        BoardRun::timed(race, "Ana", index, secs, true, moves, times)
+    }
+
+    #[test]
+    fn timed_results_verify_and_say_what_was_changed() {
+        let race = race();
+        let mut result = result(&race);
+        result.boards[0] = timed_run(&race, 0);
+        let valid = RaceResult::from_json(&result.to_json()).unwrap();
+        assert_eq!(valid, result);
+        let tampered = |result: &RaceResult| match result.verify() {
+            Err(RaceError::Tampered(0, flaw)) => flaw,
+            other => panic!("expected board 1 to fail, got {other:?}"),
+        };
+
+        // the last reveal flipped to a flag leaves the board unwon
+        let mut flipped = result.clone();
+        let last = flipped.boards[0].moves.last_mut().unwrap();
+        let Move::Reveal(x, y) = *last else {
+            unreachable!()
+        };
+        *last = Move::Flag(x, y);
+        assert_eq!(tampered(&flipped), Flaw::Outcome);
+
+        let mut extra = result.clone();
+        extra.boards[0].moves.push(Move::Flag(x, y));
+        let n = extra.boards[0].moves.len();
+        assert_eq!(tampered(&extra), Flaw::AfterEnd(n));
+
+        // a cut time is caught by the move times, even rehashed...
+        let run = &result.boards[0];
+        let (moves, times) = (run.moves.clone(), run.times.clone());
+        let mut faster = result.clone();
+        faster.boards[0] = BoardRun::timed(&race, "Ana", 0, 1.5, true, moves, times);
+        assert_eq!(tampered(&faster), Flaw::Gap(2));
+        assert_eq!(
+            faster.verify().unwrap_err().to_string(),
+            "board 1 doesn't match its move log: \
+             move 2 is timed before the move it follows or after the board ended"
+        );
+        // ...as are moves timed out of order, or times edited at all
+        let mut reordered = result.clone();
+        reordered.boards[0].times.swap(0, 1);
+        assert_eq!(tampered(&reordered), Flaw::Gap(2));
+        let mut edited = result.clone();
+        edited.boards[0].times[0] = 0.5;
+        assert_eq!(tampered(&edited), Flaw::Hash);
+        let mut dropped = result.clone();
+        dropped.boards[0].times.pop();
+        assert_eq!(tampered(&dropped), Flaw::MoveTimes);
+    }
+
+    #[test]
+    fn results_with_a_changed_seed_are_refused() {
+        let race = race();
+        let mut result = result(&race);
+        result.boards[0] = timed_run(&race, 0);
+        result.race.seeds[0] ^= 1;
+        assert!(matches!(result.verify(), Err(RaceError::WrongBoard(0))));
+    }
+
+    #[test]
+    fn results_from_another_board_are_refused() {
+        let race = race();
+        let mut result = result(&race);
+        result.boards[2].layout = layout_hash(&race.board(3));
+        assert!(matches!(result.verify(), Err(RaceError::WrongBoard(2))));
+    }
+
+    #[test]
+    fn audits_match_race_boards() {
+        let words = |s: &str| s.split(' ').map(String::from).collect::<Vec<_>>();
+        // pinned: changing how seeds become boards breaks every audit
+        assert_eq!(
+            audit(&words("--seed 1 --spec I")).as_deref(),
+            Ok("7dd26cfc901329a1")
+        );
+        assert_eq!(
+            audit(&words("--spec 16x16x40 --seed 1")),
+            audit(&words("--seed 1 --spec intermediate"))
+        );
+
+        let race = race();
+        let seed = race.seeds[0].to_string();
+        let hash = audit(&words(&format!("--seed {seed} --spec b"))).unwrap();
+        assert_eq!(hash, result(&race).boards[0].layout);
+
+        assert!(audit(&words("--seed 1 --spec 9x9x81")).is_err());
+        assert!(audit(&words("--seed 1")).is_err());
+    }
+
+    #[test]
+    fn flag_reports_replay_the_flags() {
+        let race = race();
+        let mut moves = winning_moves(&race, 0);
+        let mine = race.board(0).encode_layout().find('*').unwrap();
+        let (mx, my) = (mine % (race.width + 1), mine / (race.width + 1));
+        let safe = match moves[0] {
+            Move::Reveal(x, y) => (x, y),
+            Move::Flag(..) => unreachable!(),
+        };
+        // a wrong flag taken off before its cell is opened, and a right one
+        moves.splice(
+            0..0,
+            [
+                Move::Flag(safe.0, safe.1),
+                Move::Flag(safe.0, safe.1),
+                Move::Flag(mx, my),
+            ],
+        );
+        let run = BoardRun::new(&race, "Ana", 0, 20.0, true, moves);
+        let review = run.flag_review(&race, 0).unwrap();
+        assert_eq!((review.on_mines(), review.corrected()), (1, 1));
+
+        let result = RaceResult {
+            player: "Ana".into(),
+            race: race.clone(),
+            boards: vec![run],
+        };
+        let report = result.flag_report();
+        assert!(report.starts_with("Board 1 (0:20.0):\nFlags: 1 on mines, 1 on safe cells"));
+        assert!(report.contains("removed at move 2\n"));
+    }
+
+    #[test]
+    fn tables_need_one_race() {
+        let a = result(&race());
+        let mut b = result(&race());
+        b.player = "Bo".into();
+        let table = comparison_table(&[a.clone(), b]).unwrap();
+        assert!(table.contains("Ana") && table.contains("Bo") && table.contains("Total"));
+
+        let other = result(&Race::random(
+            Difficulty::Beginner,
+            &mut rand::rngs::StdRng::seed_from_u64(10),
+        ));
+        assert!(matches!(
+            comparison_table(&[a, other]),
+            Err(RaceError::OtherRace)
+        ));
+    }
+
+    #[test]
+    fn handicaps_are_added_and_ties_go_to_more_wins() {
+        let mut race = race();
+        race.set_handicap("Bo", 20).unwrap();
+        race.set_handicap("Dee", 290).unwrap();
+        let ana = result(&race);
+        let mut bo = result_of(&race, "Bo");
+        assert_eq!(bo.adjusted_secs(), bo.total_secs() + 20.0);
+
+        // sixteen seconds quicker doesn't make up for the handicap...
+        bo.boards[1] = BoardRun::new(&race, "Bo", 1, 1.0, true, winning_moves(&race, 1));
+        bo.boards[2] = BoardRun::new(&race, "Bo", 2, 6.0, true, winning_moves(&race, 2));
+        bo.verify().unwrap();
+        assert_eq!(places(&[ana.clone(), bo.clone()]), [1, 2]);
+        // ...twenty draws, and the tie is shared
+        bo.boards[3] = BoardRun::new(&race, "Bo", 3, 9.0, true, winning_moves(&race, 3));
+        assert_eq!(ana.adjusted_secs(), bo.adjusted_secs());
+        assert_eq!(places(&[ana.clone(), bo.clone()]), [1, 1]);
+
+        // equal adjusted totals with a lost board in one go to more wins
+        let mut carl = result_of(&race, "Carl");
+        carl.boards[0] = BoardRun::new(&race, "Carl", 0, 1.0, false, Vec::new());
+        let dee = result_of(&race, "Dee");
+        assert_eq!(carl.adjusted_secs(), dee.adjusted_secs());
+        assert_eq!(places(&[carl, dee, ana, bo]), [4, 3, 1, 1]);
+
+        let table = comparison_table(&[result(&race)]).unwrap();
+        assert!(table.contains("Handicap") && table.contains("Adjusted"));
+    }
+
+    #[test]
+    fn negative_handicaps_are_refused() {
+        let mut race = race();
+        assert!(matches!(
+            race.set_handicap("Ana", -5),
+            Err(RaceError::NegativeHandicap(player)) if player == "Ana"
+        ));
+        race.handicaps.insert("Ana".into(), -5);
+        assert!(matches!(
+            Race::from_json(&race.to_json()),
+            Err(RaceError::NegativeHandicap(_))
+        ));
+
+        let words = |s: &str| s.split(' ').map(String::from).collect::<Vec<_>>();
+        assert!(cli(&words("create beginner --handicap Ana=-30")).is_err());
+    }
+
+    #[test]
+    fn handicaps_travel_with_the_result() {
+        let mut race = race();
+        race.set_handicap("Ana", 30).unwrap();
+        let result = result(&race);
+        assert_eq!(RaceResult::from_json(&result.to_json()).unwrap(), result);
+
+        // shedding the handicap under another name breaks every hash
+        let mut renamed = result.clone();
+        renamed.player = "Anna".into();
+        assert!(matches!(renamed.verify(), Err(RaceError::Tampered(0, _))));
+
+        // and dropping it from the file makes it another race
+        let mut dropped = result.clone();
+        dropped.race.handicaps.clear();
+        assert!(matches!(dropped.verify(), Err(RaceError::Tampered(0, _))));
+        let mut plain = dropped.clone();
+        plain.player = "Bo".into();
+        assert!(matches!(
+            comparison_table(&[result, plain]),
+            Err(RaceError::OtherRace)
+        ));
+    }
+
+    #[test]
+    fn modifiers_travel_with_the_result_and_change_the_boards() {
+        let mut race = race();
+        race.modifier = Some(Modifier::Spaced);
+        let result = result(&race);
+        assert_eq!(RaceResult::from_json(&result.to_json()).unwrap(), result);
+        assert!(race.label().ends_with(" · Spaced"));
+
+        // the same seeds without the rule lay other boards
+        let mut dropped = result.clone();
+        dropped.race.modifier = None;
+        assert!(matches!(dropped.verify(), Err(RaceError::WrongBoard(0))));
+
+        race.modifier = Some(Modifier::FlagLimited);
+        assert_eq!(race.board(0).max_flags(), Some(race.mines));
+        assert!(race.to_json().contains(r#""modifier": "flag_limited""#));
+    }
+}
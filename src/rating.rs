@@ -0,0 +1,186 @@
+//! A difficulty score for each finished board and a personal rating that
+//! follows a player's games, for a sense of progress beyond best times.
+//!
+//! A board's difficulty adds up what makes it hard:
+//!
+//! ```text
+//! difficulty = ln(1 + 3BV) + 10 × density + 0.5 × forced guesses
+//!            + 0.5 × log2(cells / 81), the last never below 0
+//! ```
+//!
+//! which puts a beginner board near 4, intermediate near 6 and expert near
+//! 8, each forced guess half a point more. A win counts as a game at a
+//! higher difficulty the faster it went (see [`performance`]), and the
+//! rating moves towards each game's as an Elo-style update:
+//!
+//! ```text
+//! expected = 1 / (1 + e^(difficulty − rating))
+//! rating  += K × (result − expected), result 1 for a win and 0 for a loss
+//! ```
+//!
+//! so beating a board above the rating lifts it a lot and losing one below
+//! drops it a lot. Each game moves it by a share of the surprise, so older
+//! games fade out geometrically, as in an exponentially weighted average.
+//!
+//! ```
+//! use minesweeper_rs::rating::{self, Played};
+//!
+//! let hard = Played { difficulty: 8.0, won: true, three_bv: 120, secs: 60.0 };
+//! let after = rating::update(rating::START, &hard);
+//! assert!(after > rating::START);
+//! assert!(rating::update(after, &Played { won: false, ..hard }) < after);
+//! ```
+
+use crate::{solver, stats, Board, RevealResult};
+
+/// Where a new player's rating starts, about a beginner board.
+pub const START: f64 = 4.0;
+/// How far one game can move the rating.
+pub const K: f64 = 0.6;
+/// The largest board, in cells, whose forced guesses are counted, as that
+/// means solving it once for every guess and run of safe cells.
+pub const MEASURED_CELLS: usize = 2500;
+/// The speed, in 3BV a second, at which a win counts at its board's own
+/// difficulty; twice as fast adds `SPEED_POINTS`, half as fast takes
+/// them off.
+const PAR_SPEED: f64 = 1.0;
+/// What doubling the speed is worth.
+const SPEED_POINTS: f64 = 0.5;
+/// The most speed can add to or take off a win.
+const SPEED_CAP: f64 = 1.0;
+
+/// What a board's difficulty is worked out from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Metrics {
+    /// Cells on the board.
+    pub cells: usize,
+    /// Mines on the board.
+    pub mines: usize,
+    /// Clicks needed to clear the board without flagging.
+    pub three_bv: usize,
+    /// How many times deduction ran dry playing it from its opening, see
+    /// [`forced_guesses`].
+    pub forced_guesses: u32,
+}
+
+impl Metrics {
+    /// The measurements of `board`, played from `start`; its mines must be
+    /// laid. `None` for boards over [`MEASURED_CELLS`].
+    pub fn measure(board: &Board, start: (usize, usize)) -> Option<Metrics> {
+        let cells = board.width() * board.height();
+        (cells <= MEASURED_CELLS).then(|| Metrics {
+            cells,
+            mines: board.mine_count(),
+            three_bv: stats::three_bv(board),
+            forced_guesses: forced_guesses(board, start),
+        })
+    }
+
+    /// The board's difficulty, by the formula at the top.
+    ///
+    /// ```
+    /// use minesweeper_rs::rating::Metrics;
+    ///
+    /// let beginner = Metrics { cells: 81, mines: 10, three_bv: 20, forced_guesses: 0 };
+    /// let guessy = Metrics { forced_guesses: 2, ..beginner };
+    /// assert!((beginner.difficulty() - 4.28).abs() < 0.01);
+    /// assert!((guessy.difficulty() - beginner.difficulty() - 1.0).abs() < 1e-9);
+    /// ```
+    pub fn difficulty(&self) -> f64 {
+        let cells = self.cells.max(1) as f64;
+        let density = self.mines as f64 / cells;
+        (1.0 + self.three_bv as f64).ln()
+            + 10.0 * density
+            + 0.5 * f64::from(self.forced_guesses)
+            + 0.5 * (cells / 81.0).log2().max(0.0)
+    }
+}
+
+/// How many guesses playing `board` from `start` takes when every certain
+/// cell is opened and, whenever none is, the least likely mine is guessed
+/// and turns out safe: the guesses the board forces, whatever the luck of
+/// whoever played it. Reads the mines, so only for boards already over.
+pub fn forced_guesses(board: &Board, start: (usize, usize)) -> u32 {
+    let layout = board.encode_layout();
+    let mut play = Board::decode_layout(&layout).expect("a board's layout reads back");
+    let mine: Vec<bool> = layout
+        .bytes()
+        .filter(|&b| b != b'\n')
+        .map(|b| b == b'*')
+        .collect();
+    let width = play.width();
+    let mut guesses = 0;
+    if play.reveal_cell(start.0, start.1) == RevealResult::Exploded {
+        return 0;
+    }
+    while !play.is_over() {
+        let analysis = solver::analyze(&play);
+        if !analysis.safe().is_empty() {
+            for &(x, y) in analysis.safe() {
+                play.reveal_cell(x, y);
+            }
+            continue;
+        }
+        // the safe cell that looked least like a mine
+        let guess = (0..mine.len())
+            .filter(|&i| !mine[i] && !play.cell(i % width, i / width).is_revealed())
+            .min_by(|&a, &b| analysis.probabilities()[a].total_cmp(&analysis.probabilities()[b]));
+        let Some(i) = guess else { break };
+        guesses += 1;
+        play.reveal_cell(i % width, i / width);
+    }
+    guesses
+}
+
+/// One game, as far as the rating goes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Played {
+    /// The board's, see [`Metrics::difficulty`].
+    pub difficulty: f64,
+    /// Whether it was won.
+    pub won: bool,
+    /// The board's 3BV.
+    pub three_bv: usize,
+    /// How long it took, in seconds.
+    pub secs: f64,
+}
+
+/// The difficulty `game` counts at: a loss at its board's, a win at its
+/// board's plus `SPEED_POINTS` for every doubling of its speed over 1 3BV a
+/// second (less for every halving), by at most 1 either way.
+///
+/// ```
+/// use minesweeper_rs::rating::{performance, Played};
+///
+/// let par = Played { difficulty: 6.0, won: true, three_bv: 50, secs: 50.0 };
+/// assert_eq!(performance(&par), 6.0);
+/// assert_eq!(performance(&Played { secs: 25.0, ..par }), 6.5);
+/// assert_eq!(performance(&Played { secs: 1.0, ..par }), 7.0);
+/// assert_eq!(performance(&Played { won: false, secs: 1.0, ..par }), 6.0);
+/// ```
+pub fn performance(game: &Played) -> f64 {
+    if !game.won || game.secs <= 0.0 {
+        return game.difficulty;
+    }
+    let speed = game.three_bv as f64 / game.secs;
+    let bonus = SPEED_POINTS * (speed / PAR_SPEED).log2();
+    game.difficulty + bonus.clamp(-SPEED_CAP, SPEED_CAP)
+}
+
+/// `rating` after `game`, by the update at the top.
+pub fn update(rating: f64, game: &Played) -> f64 {
+    let expected = 1.0 / (1.0 + (performance(game) - rating).exp());
+    let result = if game.won { 1.0 } else { 0.0 };
+    rating + K * (result - expected)
+}
+
+/// The rating after each of `games`, oldest first, from [`START`].
+pub fn trend(games: impl IntoIterator<Item = Played>) -> Vec<f64> {
+    games
+        .into_iter()
+        .scan(START, |rating, game| {
+            *rating = update(*rating, &game);
+            Some(*rating)
+        })
+        .collect()
+}
@@ -0,0 +1,797 @@
+//! Input recordings, for regression tests of the whole UI (only built with
+//! the `dev-tools` feature): `--record FILE` plays a fresh app with its
+//! boards dealt from a seed and writes every frame's raw input to FILE as
+//! the window closes, followed by the state the app ended in. Playing one
+//! back feeds the same frames to a new `MinesweeperApp` without a window
+//! and checks it ends in that state again.
+//!
+//! A recording is text, a line per frame under `frames`:
+//!
+//! ```text
+//! # minesweeper-rs input recording
+//! seed 1234
+//! scale 1
+//! frames
+//! 0.016666666666666666 | window 1280 1024
+//! 0.03333333333333333 | move 640 412.5
+//! 0.05 | press primary 640 412.5
+//! 0.06666666666666667 | key Z down ctrl command | text z
+//! expect
+//! hash 5f0c6e7a1d2b3c4e
+//! screen Game
+//! ```
+//!
+//! Each frame is its time in seconds and the events in it: the window's
+//! size in points when it changes, pointer moves, button presses and
+//! releases, keys, typed text, clipboard pastes and focus changes.
+//! Scrolling and zooming gestures aren't kept. `expect` lists the state
+//! as `key value` lines (see `State`) under a hash of them; a run that
+//! ends elsewhere reports the lines that differ and, if the board does,
+//! `Board::diff` of the recorded board against the played one.
+
+use crate::app::{AppState, MinesweeperApp};
+use crate::logging::LogBuffer;
+use eframe::egui;
+use minesweeper_rs::report::position_code;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+// playing a recording back is for the tests
+#[cfg(test)]
+use minesweeper_rs::report::decode_position_code;
+#[cfg(test)]
+use std::path::Path;
+
+/// First line of every recording.
+const HEADER: &str = "# minesweeper-rs input recording";
+
+/// A recorded session: how the app was set up and each frame's input.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Recording {
+    pub seed: u64,  // deals the boards and seeds the effects
+    pub scale: f32, // the window's native pixels per point
+    pub frames: Vec<Frame>,
+    pub expect: Option<State>, // missing only while being recorded
+}
+
+/// One frame of input.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Frame {
+    pub time: f64,
+    pub window: egui::Vec2, // size in points
+    pub events: Vec<egui::Event>,
+}
+
+/// The parts of the app a recording is checked against: the screen, the
+/// board with its play state, and every setting, as `key value` lines in
+/// a fixed order. The game clock, anything dated and the compact window's
+/// place are left out, as a replay runs at its own pace, on its own day
+/// and without a window.
+#[derive(Clone, Debug, PartialEq)]
+pub struct State {
+    lines: Vec<(String, String)>,
+}
+
+impl State {
+    pub fn of(app: &MinesweeperApp) -> State {
+        let state = &app.state;
+        let board = &state.board;
+        let game = if state.awaiting_first_click {
+            "waiting"
+        } else if board.is_won() {
+            "won"
+        } else if board.is_lost() {
+            "lost"
+        } else {
+            "playing"
+        };
+        let mut lines = vec![
+            ("screen".to_string(), format!("{:?}", app.screen)),
+            ("difficulty".to_string(), format!("{:?}", state.difficulty)),
+            ("game".to_string(), game.to_string()),
+            ("board".to_string(), position_code(board)),
+        ];
+        let mut settings = serde_json::to_value(&state.settings).expect("settings serialize");
+        // where the platform put the window, which playback has none of
+        settings["compact_window"].take();
+        flatten("settings", &settings, &mut lines);
+        State { lines }
+    }
+
+    /// FNV-1a of the lines, the quick check a replay is held to.
+    pub fn hash(&self) -> u64 {
+        let mut hash = 0xcbf2_9ce4_8422_2325_u64;
+        for (key, value) in &self.lines {
+            for byte in key
+                .bytes()
+                .chain([b' '])
+                .chain(value.bytes())
+                .chain([b'\n'])
+            {
+                hash = (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3);
+            }
+        }
+        hash
+    }
+
+    #[cfg(test)]
+    fn get(&self, key: &str) -> Option<&str> {
+        self.lines
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// `value`'s leaves as `prefix.path` lines, objects by key, values as JSON.
+fn flatten(prefix: &str, value: &serde_json::Value, lines: &mut Vec<(String, String)>) {
+    match value {
+        serde_json::Value::Object(fields) => {
+            for (key, field) in fields {
+                flatten(&format!("{prefix}.{key}"), field, lines);
+            }
+        }
+        leaf => lines.push((prefix.to_string(), leaf.to_string())),
+    }
+}
+
+/// Records the app it is attached to, a frame at a time.
+pub struct Recorder {
+    pub path: PathBuf,
+    recording: Recording,
+}
+
+impl Recorder {
+    /// Start recording, to be written to `path`, with boards dealt from
+    /// `seed`, which `seed_app` must have given the app.
+    pub fn new(path: PathBuf, seed: u64) -> Self {
+        Recorder {
+            path,
+            recording: Recording {
+                seed,
+                scale: 1.0,
+                frames: Vec::new(),
+                expect: None,
+            },
+        }
+    }
+
+    /// Note the input of the frame `ctx` is running.
+    pub fn capture(&mut self, ctx: &egui::Context) {
+        let frame = ctx.input(|i| {
+            let last = self.recording.frames.last();
+            Frame {
+                time: i.time,
+                window: i
+                    .raw
+                    .screen_rect
+                    .map(|rect| rect.size())
+                    .or(last.map(|frame| frame.window))
+                    .unwrap_or_default(),
+                events: i.raw.events.iter().filter(|e| kept(e)).cloned().collect(),
+            }
+        });
+        if self.recording.frames.is_empty() {
+            let scale = ctx.input(|i| i.raw.viewport().native_pixels_per_point);
+            self.recording.scale = scale.unwrap_or(1.0);
+        }
+        self.recording.frames.push(frame);
+    }
+
+    /// The recording so far, ending in `app`'s state.
+    pub fn finish(&self, app: &MinesweeperApp) -> Recording {
+        Recording {
+            expect: Some(State::of(app)),
+            ..self.recording.clone()
+        }
+    }
+
+    /// Write the recording, ending in `app`'s state, to its file.
+    pub fn save(&self, app: &MinesweeperApp) -> io::Result<()> {
+        fs::write(&self.path, self.finish(app).to_string())
+    }
+}
+
+/// Give `state` the boards and effects a recording with `seed` expects.
+pub fn seed_app(state: &mut AppState, seed: u64) {
+    state.board_seeds = Some(StdRng::seed_from_u64(seed));
+    state.visual_rng = StdRng::seed_from_u64(seed);
+}
+
+/// Open the window for `--record PATH`: a fresh app on its default
+/// settings, without the profiles or any saved game, so that playback can
+/// start from the same place. The recording is written as it closes.
+pub fn run(path: PathBuf, log: LogBuffer, options: eframe::NativeOptions) -> eframe::Result<()> {
+    let seed = rand::random();
+    let mut state = AppState {
+        log,
+        ..AppState::default()
+    };
+    seed_app(&mut state, seed);
+    let mut app = MinesweeperApp::new(state);
+    app.recorder = Some(Recorder::new(path, seed));
+    eframe::run_native(
+        "Bosnia Simulator (recording)",
+        options,
+        Box::new(move |cc| {
+            app.state.settings.apply(&cc.egui_ctx);
+            Ok(Box::new(app))
+        }),
+    )
+}
+
+/// The events a recording keeps.
+fn kept(event: &egui::Event) -> bool {
+    matches!(
+        event,
+        egui::Event::PointerMoved(_)
+            | egui::Event::PointerGone
+            | egui::Event::PointerButton { .. }
+            | egui::Event::Key { .. }
+            | egui::Event::Text(_)
+            | egui::Event::Paste(_)
+            | egui::Event::WindowFocused(_)
+    )
+}
+
+/// Play `recording` on a new app without a window, returning the app as
+/// it ends.
+#[cfg(test)]
+pub fn play(recording: &Recording) -> MinesweeperApp {
+    let ctx = egui::Context::default();
+    ctx.enable_accesskit();
+    let mut state = AppState::default();
+    seed_app(&mut state, recording.seed);
+    state.settings.apply(&ctx);
+    let mut app = MinesweeperApp::new(state);
+    for frame in &recording.frames {
+        let mut input = egui::RawInput {
+            screen_rect: Some(egui::Rect::from_min_size(egui::Pos2::ZERO, frame.window)),
+            time: Some(frame.time),
+            events: frame.events.clone(),
+            ..Default::default()
+        };
+        input
+            .viewports
+            .entry(egui::ViewportId::ROOT)
+            .or_default()
+            .native_pixels_per_point = Some(recording.scale);
+        let _ = ctx.run(input, |ctx| app.ui(ctx));
+    }
+    app
+}
+
+/// Play `recording` back and check it ends in the state it was recorded
+/// in.
+#[cfg(test)]
+pub fn check(recording: &Recording) -> Result<(), Mismatch> {
+    let expect = recording.expect.as_ref().ok_or(Mismatch::NoState)?;
+    let played = State::of(&play(recording));
+    if played.hash() == expect.hash() {
+        return Ok(());
+    }
+    let mut lines: Vec<(String, Option<String>, Option<String>)> = Vec::new();
+    let keys = expect.lines.iter().chain(&played.lines).map(|(key, _)| key);
+    for key in keys {
+        let (was, now) = (expect.get(key), played.get(key));
+        if was != now && !lines.iter().any(|(k, ..)| k == key) {
+            lines.push((
+                key.clone(),
+                was.map(str::to_string),
+                now.map(str::to_string),
+            ));
+        }
+    }
+    let board = match (expect.get("board"), played.get("board")) {
+        (Some(was), Some(now)) if was != now => {
+            let diff = decode_position_code(was)
+                .map_err(|err| err.to_string())
+                .and_then(|was| {
+                    let now = decode_position_code(now).expect("a played board's code reads");
+                    was.diff(&now).map_err(|err| err.to_string())
+                })
+                .map(|diff| diff.to_string());
+            Some(diff.unwrap_or_else(|err| err))
+        }
+        _ => None,
+    };
+    Err(Mismatch::State {
+        expected: expect.hash(),
+        played: played.hash(),
+        lines,
+        board,
+    })
+}
+
+/// How a replay ended differently from its recording.
+#[cfg(test)]
+#[derive(Clone, Debug, PartialEq)]
+pub enum Mismatch {
+    /// The recording has no state to check against.
+    NoState,
+    /// The state hashes differ: each differing line as recorded and as
+    /// played (`None` where one has no such line), and the boards' diff if
+    /// they differ.
+    State {
+        expected: u64,
+        played: u64,
+        lines: Vec<(String, Option<String>, Option<String>)>,
+        board: Option<String>,
+    },
+}
+
+#[cfg(test)]
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Mismatch::NoState => f.write_str("the recording has no expected state"),
+            Mismatch::State {
+                expected,
+                played,
+                lines,
+                board,
+            } => {
+                writeln!(f, "state hash {played:016x}, recorded {expected:016x}")?;
+                let shown =
+                    |value: &Option<String>| value.as_deref().unwrap_or("(none)").to_owned();
+                for (key, was, now) in lines {
+                    if key != "board" {
+                        writeln!(f, "{key}: {}, recorded {}", shown(now), shown(was))?;
+                    }
+                }
+                if let Some(diff) = board {
+                    write!(
+                        f,
+                        "the board ends differently (`.` where it agrees):\n{diff}"
+                    )?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl fmt::Display for Recording {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{HEADER}")?;
+        writeln!(f, "seed {}", self.seed)?;
+        writeln!(f, "scale {}", self.scale)?;
+        writeln!(f, "frames")?;
+        let mut window = None;
+        for frame in &self.frames {
+            write!(f, "{}", frame.time)?;
+            if window != Some(frame.window) {
+                window = Some(frame.window);
+                write!(f, " | window {} {}", frame.window.x, frame.window.y)?;
+            }
+            for event in &frame.events {
+                write!(f, " | {}", write_event(event))?;
+            }
+            writeln!(f)?;
+        }
+        if let Some(expect) = &self.expect {
+            writeln!(f, "expect")?;
+            writeln!(f, "hash {:016x}", expect.hash())?;
+            for (key, value) in &expect.lines {
+                writeln!(f, "{key} {value}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn write_event(event: &egui::Event) -> String {
+    match event {
+        egui::Event::PointerMoved(pos) => format!("move {} {}", pos.x, pos.y),
+        egui::Event::PointerGone => "gone".into(),
+        egui::Event::PointerButton {
+            pos,
+            button,
+            pressed,
+            modifiers,
+        } => {
+            let verb = if *pressed { "press" } else { "release" };
+            let button = BUTTONS
+                .iter()
+                .find(|(b, _)| b == button)
+                .map_or("primary", |b| b.1);
+            format!(
+                "{verb} {button} {} {}{}",
+                pos.x,
+                pos.y,
+                write_modifiers(*modifiers)
+            )
+        }
+        egui::Event::Key {
+            key,
+            pressed,
+            repeat,
+            modifiers,
+            ..
+        } => {
+            let state = if *pressed { "down" } else { "up" };
+            let repeat = if *repeat { " repeat" } else { "" };
+            format!(
+                "key {} {state}{repeat}{}",
+                key.name(),
+                write_modifiers(*modifiers)
+            )
+        }
+        egui::Event::Text(text) => format!("text {}", escape(text)),
+        egui::Event::Paste(text) => format!("paste {}", escape(text)),
+        egui::Event::WindowFocused(focused) => format!("focus {focused}"),
+        _ => unreachable!("only kept events are recorded"),
+    }
+}
+
+const BUTTONS: [(egui::PointerButton, &str); 5] = [
+    (egui::PointerButton::Primary, "primary"),
+    (egui::PointerButton::Secondary, "secondary"),
+    (egui::PointerButton::Middle, "middle"),
+    (egui::PointerButton::Extra1, "extra1"),
+    (egui::PointerButton::Extra2, "extra2"),
+];
+
+/// The held modifiers as words, each after a space.
+fn write_modifiers(modifiers: egui::Modifiers) -> String {
+    let held = [
+        (modifiers.alt, "alt"),
+        (modifiers.ctrl, "ctrl"),
+        (modifiers.shift, "shift"),
+        (modifiers.mac_cmd, "mac_cmd"),
+        (modifiers.command, "command"),
+    ];
+    held.iter()
+        .filter(|(down, _)| *down)
+        .map(|(_, name)| format!(" {name}"))
+        .collect()
+}
+
+fn read_modifiers<'a>(words: impl Iterator<Item = &'a str>) -> Option<egui::Modifiers> {
+    let mut modifiers = egui::Modifiers::NONE;
+    for word in words {
+        match word {
+            "alt" => modifiers.alt = true,
+            "ctrl" => modifiers.ctrl = true,
+            "shift" => modifiers.shift = true,
+            "mac_cmd" => modifiers.mac_cmd = true,
+            "command" => modifiers.command = true,
+            _ => return None,
+        }
+    }
+    Some(modifiers)
+}
+
+/// `text` with backslashes, bars and line breaks escaped, so it stays in
+/// its place on the frame's line.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '|' => escaped.push_str("\\/"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn unescape(text: &str) -> Option<String> {
+    let mut plain = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        plain.push(match c {
+            '\\' => match chars.next()? {
+                '\\' => '\\',
+                '/' => '|',
+                'n' => '\n',
+                'r' => '\r',
+                _ => return None,
+            },
+            c => c,
+        });
+    }
+    Some(plain)
+}
+
+/// Why a recording couldn't be read: the line it stopped at, counting
+/// from 1, and what was wrong there.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub why: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.why)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl std::str::FromStr for Recording {
+    type Err = ParseError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut lines = text.lines().enumerate().map(|(i, line)| (i + 1, line));
+        let fail = |line: usize, why: &str| ParseError {
+            line,
+            why: why.to_string(),
+        };
+        if lines.next().map(|(_, line)| line) != Some(HEADER) {
+            return Err(fail(1, "not an input recording"));
+        }
+        let mut value = |name: &str| {
+            let (at, line) = lines.next().ok_or_else(|| fail(1, "cut short"))?;
+            let rest = line
+                .strip_prefix(name)
+                .and_then(|rest| rest.strip_prefix(' '));
+            rest.map(|rest| (at, rest.to_string()))
+                .ok_or_else(|| fail(at, &format!("expected {name}")))
+        };
+        let (at, seed) = value("seed")?;
+        let seed = seed
+            .parse()
+            .map_err(|_| fail(at, "the seed isn't a number"))?;
+        let (at, scale) = value("scale")?;
+        let scale = scale
+            .parse()
+            .map_err(|_| fail(at, "the scale isn't a number"))?;
+        match lines.next() {
+            Some((_, "frames")) => {}
+            Some((at, _)) => return Err(fail(at, "expected frames")),
+            None => return Err(fail(at + 1, "cut short")),
+        }
+
+        let mut frames = Vec::new();
+        let mut window = egui::Vec2::ZERO;
+        let mut expect = None;
+        for (at, line) in lines.by_ref() {
+            if line == "expect" {
+                expect = Some(Vec::new());
+                break;
+            }
+            let mut parts = line.split(" | ");
+            let time = parts.next().and_then(|time| time.parse().ok());
+            let time = time.ok_or_else(|| fail(at, "a frame starts with its time"))?;
+            let mut events = Vec::new();
+            for part in parts {
+                if let Some(size) = part.strip_prefix("window ") {
+                    window = read_vec(size).ok_or_else(|| fail(at, "bad window size"))?;
+                } else {
+                    let event = read_event(part);
+                    events.push(event.ok_or_else(|| fail(at, &format!("bad event {part:?}")))?);
+                }
+            }
+            frames.push(Frame {
+                time,
+                window,
+                events,
+            });
+        }
+
+        let expect = match expect {
+            None => None,
+            Some(mut state) => {
+                let (at, hash) = lines.next().ok_or_else(|| fail(1, "expected a hash"))?;
+                let hash = hash
+                    .strip_prefix("hash ")
+                    .and_then(|hash| u64::from_str_radix(hash, 16).ok())
+                    .ok_or_else(|| fail(at, "expected a hash"))?;
+                for (at, line) in lines {
+                    let (key, value) = line
+                        .split_once(' ')
+                        .ok_or_else(|| fail(at, "not a key and value"))?;
+                    state.push((key.to_string(), value.to_string()));
+                }
+                let state = State { lines: state };
+                if state.hash() != hash {
+                    return Err(fail(at, "the hash doesn't match the state below it"));
+                }
+                Some(state)
+            }
+        };
+        Ok(Recording {
+            seed,
+            scale,
+            frames,
+            expect,
+        })
+    }
+}
+
+fn read_vec(text: &str) -> Option<egui::Vec2> {
+    let (x, y) = text.split_once(' ')?;
+    Some(egui::vec2(x.parse().ok()?, y.parse().ok()?))
+}
+
+fn read_event(text: &str) -> Option<egui::Event> {
+    let (verb, rest) = text.split_once(' ').unwrap_or((text, ""));
+    let mut words = rest.split(' ');
+    Some(match verb {
+        "move" => egui::Event::PointerMoved(read_vec(rest)?.to_pos2()),
+        "gone" => egui::Event::PointerGone,
+        "press" | "release" => {
+            let name = words.next()?;
+            let button = BUTTONS.iter().find(|b| b.1 == name)?.0;
+            let pos = egui::pos2(words.next()?.parse().ok()?, words.next()?.parse().ok()?);
+            egui::Event::PointerButton {
+                pos,
+                button,
+                pressed: verb == "press",
+                modifiers: read_modifiers(words)?,
+            }
+        }
+        "key" => {
+            let key = egui::Key::from_name(words.next()?)?;
+            let pressed = match words.next()? {
+                "down" => true,
+                "up" => false,
+                _ => return None,
+            };
+            let mut words = words.peekable();
+            let repeat = words.next_if_eq(&"repeat").is_some();
+            egui::Event::Key {
+                key,
+                physical_key: None,
+                pressed,
+                repeat,
+                modifiers: read_modifiers(words)?,
+            }
+        }
+        "text" => egui::Event::Text(unescape(rest)?),
+        "paste" => egui::Event::Paste(unescape(rest)?),
+        "focus" => egui::Event::WindowFocused(rest.parse().ok()?),
+        _ => return None,
+    })
+}
+
+/// Read the recording at `path`.
+#[cfg(test)]
+pub fn load(path: &Path) -> Result<Recording, String> {
+    let text = fs::read_to_string(path).map_err(|err| format!("{}: {err}", path.display()))?;
+    text.parse()
+        .map_err(|err| format!("{}: {err}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The checked-in recording `name`, under `tests/recordings`.
+    fn fixture(name: &str) -> Recording {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/recordings")
+            .join(format!("{name}.rec"));
+        load(&path).unwrap_or_else(|err| {
+            panic!(
+                "{err}; the recordings are made by \
+                 `cargo test --features dev-tools -- --ignored record_the_playback_fixtures`"
+            )
+        })
+    }
+
+    fn plays_back(name: &str) {
+        if let Err(mismatch) = check(&fixture(name)) {
+            panic!("{name} played back differently:\n{mismatch}");
+        }
+    }
+
+    #[test]
+    fn a_beginner_game_played_with_the_mouse() {
+        plays_back("beginner_mouse");
+    }
+
+    #[test]
+    fn an_intermediate_game_played_from_the_keyboard() {
+        plays_back("intermediate_keyboard");
+    }
+
+    #[test]
+    fn a_trip_through_the_settings() {
+        plays_back("settings_round_trip");
+    }
+
+    #[test]
+    fn a_recording_reads_back_as_written() {
+        let press = |pressed| egui::Event::Key {
+            key: egui::Key::Z,
+            physical_key: None,
+            pressed,
+            repeat: !pressed,
+            modifiers: egui::Modifiers::COMMAND | egui::Modifiers::SHIFT,
+        };
+        let frame = |time, events| Frame {
+            time,
+            window: egui::vec2(800.0, 600.5),
+            events,
+        };
+        let recording = Recording {
+            seed: 7,
+            scale: 1.5,
+            frames: vec![
+                frame(0.0, Vec::new()),
+                frame(
+                    1.0 / 60.0,
+                    vec![
+                        egui::Event::PointerMoved(egui::pos2(10.5, 20.0)),
+                        egui::Event::PointerButton {
+                            pos: egui::pos2(10.5, 20.0),
+                            button: egui::PointerButton::Secondary,
+                            pressed: true,
+                            modifiers: egui::Modifiers::NONE,
+                        },
+                    ],
+                ),
+                frame(
+                    0.1,
+                    vec![
+                        press(true),
+                        egui::Event::Text("a | b\\".into()),
+                        press(false),
+                    ],
+                ),
+            ],
+            expect: Some(State {
+                lines: vec![
+                    ("screen".into(), "Game".into()),
+                    ("game".into(), "won".into()),
+                ],
+            }),
+        };
+        let text = recording.to_string();
+        assert_eq!(text.parse::<Recording>().unwrap(), recording);
+        assert_eq!(
+            text.matches("window").count(),
+            1,
+            "written once, then carried"
+        );
+
+        let tampered = text.replace("game won", "game lost");
+        let err = tampered.parse::<Recording>().unwrap_err();
+        assert!(err.why.contains("hash"), "{err}");
+        assert!("not a recording".parse::<Recording>().is_err());
+    }
+
+    #[test]
+    fn a_changed_outcome_names_what_differs() {
+        let mut recording = Recording {
+            seed: 3,
+            scale: 1.0,
+            frames: vec![Frame {
+                time: 0.0,
+                window: egui::vec2(1280.0, 1024.0),
+                events: Vec::new(),
+            }],
+            expect: None,
+        };
+        assert_eq!(check(&recording), Err(Mismatch::NoState));
+
+        let mut app = play(&recording);
+        recording.expect = Some(State::of(&app));
+        assert_eq!(check(&recording), Ok(()));
+
+        // recorded after a first click the replay no longer makes
+        app.state.awaiting_first_click = false;
+        app.state.board = minesweeper_rs::Board::with_seed(9, 9, 10, 3, Some((4, 4)));
+        app.state.board.reveal_cell(4, 4);
+        recording.expect = Some(State::of(&app));
+        let mismatch = check(&recording).unwrap_err().to_string();
+        assert!(
+            mismatch.contains("game: waiting, recorded playing"),
+            "{mismatch}"
+        );
+        assert!(
+            mismatch.contains("the board ends differently"),
+            "{mismatch}"
+        );
+        assert!(!mismatch.contains("settings."), "{mismatch}");
+    }
+}
@@ -0,0 +1,697 @@
+//! Text renderings of a board for terminals and logs. Every style draws
+//! from the same [`Glyph`] per cell, so they agree on what a cell shows
+//! and differ only in how it is drawn.
+//!
+//! ```
+//! use minesweeper_rs::render::{BoardRenderer, Style};
+//! use minesweeper_rs::Board;
+//!
+//! let mut board = Board::decode_layout("*..\n...\n")?;
+//! board.reveal_cell(2, 1);
+//! let compact: Style = "compact".parse()?;
+//! assert_eq!(compact.render(&board), "┌───┐\n│░1 │\n│░1 │\n└───┘\n");
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::{Board, Cell, Marking};
+use std::fmt;
+use std::str::FromStr;
+
+/// What a cell shows, whatever the style.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Glyph {
+    /// Not revealed yet.
+    Hidden,
+    /// Flagged by the player.
+    Flag,
+    /// Question-marked by the player.
+    Question,
+    /// A revealed mine.
+    Mine,
+    /// A revealed safe cell with this many neighbouring mines (0 to 8).
+    Count(u8),
+}
+
+impl Glyph {
+    /// The glyph for `cell`. Unrevealed mines stay hidden even after the
+    /// game ends; reveal them with `Board::reveal_all` to show them.
+    ///
+    /// ```
+    /// use minesweeper_rs::render::Glyph;
+    /// use minesweeper_rs::Board;
+    ///
+    /// let mut board = Board::decode_layout("*..\n")?;
+    /// board.toggle_flag(0, 0);
+    /// assert_eq!(Glyph::of(board.cell(0, 0)), Glyph::Flag);
+    /// assert_eq!(Glyph::of(board.cell(2, 0)), Glyph::Hidden);
+    /// # Ok::<(), minesweeper_rs::LayoutError>(())
+    /// ```
+    pub fn of(cell: Cell) -> Self {
+        if !cell.is_revealed() {
+            match cell.marking() {
+                Marking::Flag => Glyph::Flag,
+                Marking::Question => Glyph::Question,
+                _ => Glyph::Hidden,
+            }
+        } else if cell.is_mine() == Some(true) {
+            Glyph::Mine
+        } else {
+            Glyph::Count(cell.neighbor_mines().unwrap_or(0))
+        }
+    }
+
+    /// The GUI's label: emoji for mines and flags, a blank for zero.
+    ///
+    /// ```
+    /// use minesweeper_rs::render::Glyph;
+    ///
+    /// assert_eq!(Glyph::Count(3).label(), "3");
+    /// assert_eq!(Glyph::Count(0).label(), " ");
+    /// ```
+    pub fn label(self) -> &'static str {
+        const COUNTS: [&str; 9] = [" ", "1", "2", "3", "4", "5", "6", "7", "8"];
+        match self {
+            Glyph::Hidden => "■",
+            Glyph::Flag => "🚩",
+            Glyph::Question => "❓",
+            Glyph::Mine => "💣",
+            Glyph::Count(n) => COUNTS[usize::from(n.min(8))],
+        }
+    }
+
+    /// Words for a screen reader.
+    ///
+    /// ```
+    /// use minesweeper_rs::render::Glyph;
+    ///
+    /// assert_eq!(Glyph::Flag.spoken(), "flagged");
+    /// assert_eq!(Glyph::Count(2).spoken(), "revealed, 2 adjacent mines");
+    /// ```
+    pub fn spoken(self) -> String {
+        match self {
+            Glyph::Hidden => "hidden".to_string(),
+            Glyph::Flag => "flagged".to_string(),
+            Glyph::Question => "question mark".to_string(),
+            Glyph::Mine => "mine".to_string(),
+            Glyph::Count(0) => "revealed, no adjacent mines".to_string(),
+            Glyph::Count(1) => "revealed, 1 adjacent mine".to_string(),
+            Glyph::Count(n) => format!("revealed, {n} adjacent mines"),
+        }
+    }
+
+    /// One character of terminal width, for grids that must line up.
+    ///
+    /// ```
+    /// assert_eq!(minesweeper_rs::render::Glyph::Flag.narrow(), '⚑');
+    /// ```
+    pub fn narrow(self) -> char {
+        match self {
+            Glyph::Hidden => '░',
+            Glyph::Flag => '⚑',
+            Glyph::Question => '?',
+            Glyph::Mine => '*',
+            Glyph::Count(0) => ' ',
+            Glyph::Count(n) => char::from(b'0' + n.min(8)),
+        }
+    }
+}
+
+/// Draws a whole board as text, one line per row plus any frame, each line
+/// ending in a newline.
+pub trait BoardRenderer {
+    /// The board as text, with `markers` for flags, question marks and
+    /// mines.
+    fn render_with(&self, board: &Board, markers: &Markers) -> String;
+
+    /// The board as text with the classic markers.
+    fn render(&self, board: &Board) -> String {
+        self.render_with(board, &Markers::default())
+    }
+}
+
+/// The original output: each cell's label with a space between cells.
+///
+/// ```
+/// use minesweeper_rs::render::{BoardRenderer, Classic};
+/// use minesweeper_rs::Board;
+///
+/// let board = Board::decode_layout("*.\n")?;
+/// assert_eq!(Classic.render(&board), "■ ■\n");
+/// # Ok::<(), minesweeper_rs::LayoutError>(())
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Classic;
+
+impl BoardRenderer for Classic {
+    fn render_with(&self, board: &Board, markers: &Markers) -> String {
+        let mut out = String::new();
+        for y in 0..board.height() {
+            let row: Vec<_> = (0..board.width())
+                .map(|x| markers.label(Glyph::of(board.cell(x, y))))
+                .collect();
+            out.push_str(&row.join(" "));
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// A box-drawing frame with one narrow glyph per cell, optionally with `│`
+/// between cells and cell coordinates along the edges.
+///
+/// ```
+/// use minesweeper_rs::render::{BoardRenderer, Compact};
+/// use minesweeper_rs::Board;
+///
+/// let board = Board::decode_layout("*.\n")?;
+/// let grid = Compact {
+///     separators: true,
+///     ..Compact::default()
+/// };
+/// assert_eq!(grid.render(&board), "┌─┬─┐\n│░│░│\n└─┴─┘\n");
+/// let labelled = Compact {
+///     coordinates: true,
+///     ..Compact::default()
+/// };
+/// assert_eq!(labelled.render(&board), "  AB\n ┌──┐\n1│░░│\n └──┘\n");
+/// # Ok::<(), minesweeper_rs::LayoutError>(())
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Compact {
+    /// Draw `│` between cells, with matching joints in the frame.
+    pub separators: bool,
+    /// Label columns A, B, … above the frame and rows 1, 2, … to its left,
+    /// so a position can be talked about cell by cell. Column names longer
+    /// than a letter are written downwards.
+    pub coordinates: bool,
+}
+
+impl Compact {
+    /// Rows of column names above the frame, after `gutter` spaces of row
+    /// numbers.
+    fn column_header(&self, width: usize, gutter: usize) -> String {
+        let names: Vec<String> = (0..width).map(column_name).collect();
+        // the last name is the longest; shorter ones are bottom-aligned
+        let lines = names.last().map_or(0, String::len);
+        let mut out = String::new();
+        for line in 0..lines {
+            out.push_str(&" ".repeat(gutter + 1));
+            for (x, name) in names.iter().enumerate() {
+                if x > 0 && self.separators {
+                    out.push(' ');
+                }
+                let blank = lines - name.len();
+                out.push(if line < blank {
+                    ' '
+                } else {
+                    char::from(name.as_bytes()[line - blank])
+                });
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    fn border(&self, width: usize, left: char, joint: char, right: char) -> String {
+        let mut line = String::from(left);
+        for x in 0..width {
+            if x > 0 && self.separators {
+                line.push(joint);
+            }
+            line.push('─');
+        }
+        line.push(right);
+        line.push('\n');
+        line
+    }
+}
+
+impl BoardRenderer for Compact {
+    fn render_with(&self, board: &Board, markers: &Markers) -> String {
+        let gutter = if self.coordinates {
+            board.height().to_string().len()
+        } else {
+            0
+        };
+        let mut out = String::new();
+        if self.coordinates {
+            out.push_str(&self.column_header(board.width(), gutter));
+        }
+        out.push_str(&" ".repeat(gutter));
+        out.push_str(&self.border(board.width(), '┌', '┬', '┐'));
+        for y in 0..board.height() {
+            if self.coordinates {
+                out.push_str(&format!("{:>gutter$}", y + 1));
+            }
+            out.push('│');
+            for x in 0..board.width() {
+                if x > 0 && self.separators {
+                    out.push('│');
+                }
+                out.push(markers.narrow(Glyph::of(board.cell(x, y))));
+            }
+            out.push_str("│\n");
+        }
+        out.push_str(&" ".repeat(gutter));
+        out.push_str(&self.border(board.width(), '└', '┴', '┘'));
+        out
+    }
+}
+
+/// One of the glyphs a player can choose: the flag, the question mark or
+/// the mine.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Marker {
+    /// On flagged cells.
+    Flag,
+    /// On question-marked cells.
+    Question,
+    /// On revealed mines.
+    Mine,
+}
+
+impl Marker {
+    /// Every marker, in the order settings list them.
+    pub const ALL: [Marker; 3] = [Marker::Flag, Marker::Question, Marker::Mine];
+
+    /// The marker `glyph` is drawn with, if it is one.
+    pub fn of(glyph: Glyph) -> Option<Marker> {
+        match glyph {
+            Glyph::Flag => Some(Marker::Flag),
+            Glyph::Question => Some(Marker::Question),
+            Glyph::Mine => Some(Marker::Mine),
+            Glyph::Hidden | Glyph::Count(_) => None,
+        }
+    }
+
+    /// Lowercase name, for settings and errors.
+    pub fn name(self) -> &'static str {
+        match self {
+            Marker::Flag => "flag",
+            Marker::Question => "question mark",
+            Marker::Mine => "mine",
+        }
+    }
+}
+
+/// The glyphs for flags, question marks and mines, picked apart from the
+/// rest of the board. Each marker has a label, for the GUI and the classic
+/// style, and a narrow character for the compact grids.
+///
+/// ```
+/// use minesweeper_rs::render::{BoardRenderer, Glyph, Markers, Style};
+/// use minesweeper_rs::Board;
+///
+/// let markers: Markers = "letters".parse()?;
+/// assert_eq!(markers.label(Glyph::Flag), "F");
+/// assert_eq!(markers.label(Glyph::Count(2)), "2");
+///
+/// let mut board = Board::decode_layout("*.\n")?;
+/// board.toggle_flag(0, 0);
+/// assert_eq!(Style::Grid.render_with(&board, &markers), "┌─┬─┐\n│F│░│\n└─┴─┘\n");
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Markers {
+    labels: [String; 3],
+    narrow: [char; 3],
+}
+
+/// The ready-made marker sets.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MarkerSet {
+    /// 🚩 ❓ 💣, the GUI's original look.
+    #[default]
+    Classic,
+    /// ▲ · ●
+    Minimal,
+    /// F ? M
+    Letters,
+}
+
+impl MarkerSet {
+    /// Every set, in the order help text lists them.
+    pub const ALL: [MarkerSet; 3] = [MarkerSet::Classic, MarkerSet::Minimal, MarkerSet::Letters];
+
+    /// The name `Markers::from_str` accepts.
+    pub fn name(self) -> &'static str {
+        match self {
+            MarkerSet::Classic => "classic",
+            MarkerSet::Minimal => "minimal",
+            MarkerSet::Letters => "letters",
+        }
+    }
+
+    /// The set's markers.
+    pub fn markers(self) -> Markers {
+        let (labels, narrow) = match self {
+            MarkerSet::Classic => (["🚩", "❓", "💣"], ['⚑', '?', '*']),
+            MarkerSet::Minimal => (["▲", "·", "●"], ['▲', '·', '●']),
+            MarkerSet::Letters => (["F", "?", "M"], ['F', '?', 'M']),
+        };
+        Markers {
+            labels: labels.map(String::from),
+            narrow,
+        }
+    }
+}
+
+impl Default for Markers {
+    fn default() -> Self {
+        MarkerSet::Classic.markers()
+    }
+}
+
+impl Markers {
+    /// The label `marker` is drawn with.
+    pub fn get(&self, marker: Marker) -> &str {
+        &self.labels[marker as usize]
+    }
+
+    /// Draw `marker` as `text`, which must be one grapheme, like `é`, `🇧🇦`
+    /// or a ZWJ emoji sequence (see [`is_one_grapheme`]). A single `char`
+    /// stands for itself in the compact grids too; anything longer keeps
+    /// the narrow character the marker had.
+    ///
+    /// ```
+    /// use minesweeper_rs::render::{Glyph, Marker, Markers};
+    ///
+    /// let mut markers = Markers::default();
+    /// markers.set(Marker::Mine, "X")?;
+    /// assert_eq!((markers.label(Glyph::Mine), markers.narrow(Glyph::Mine)), ("X", 'X'));
+    /// assert!(markers.set(Marker::Flag, "no").is_err());
+    /// # Ok::<(), minesweeper_rs::render::MarkerError>(())
+    /// ```
+    pub fn set(&mut self, marker: Marker, text: &str) -> Result<(), MarkerError> {
+        if !is_one_grapheme(text) {
+            return Err(MarkerError::NotOneGrapheme(marker, text.to_string()));
+        }
+        let i = marker as usize;
+        self.labels[i] = text.to_string();
+        let mut chars = text.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            self.narrow[i] = c;
+        }
+        Ok(())
+    }
+
+    /// What `glyph` shows: its marker if it has one, else [`Glyph::label`].
+    pub fn label(&self, glyph: Glyph) -> &str {
+        match Marker::of(glyph) {
+            Some(marker) => self.get(marker),
+            None => glyph.label(),
+        }
+    }
+
+    /// [`Glyph::narrow`], with this set's markers.
+    pub fn narrow(&self, glyph: Glyph) -> char {
+        match Marker::of(glyph) {
+            Some(marker) => self.narrow[marker as usize],
+            None => glyph.narrow(),
+        }
+    }
+}
+
+/// A marker that can't be used, or a `--markers` value that names no set.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MarkerError {
+    /// The text for this marker isn't exactly one grapheme.
+    NotOneGrapheme(Marker, String),
+    /// Neither a set name nor three markers.
+    Unknown(String),
+}
+
+impl fmt::Display for MarkerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MarkerError::NotOneGrapheme(marker, text) => {
+                write!(
+                    f,
+                    "{text:?} isn't a single character, so it can't be the {}",
+                    marker.name()
+                )
+            }
+            MarkerError::Unknown(text) => {
+                write!(f, "unknown markers {text:?}; expected one of")?;
+                for set in MarkerSet::ALL {
+                    write!(f, " {}", set.name())?;
+                }
+                f.write_str(", or three separated by commas like F,?,M")
+            }
+        }
+    }
+}
+
+impl std::error::Error for MarkerError {}
+
+impl FromStr for Markers {
+    type Err = MarkerError;
+
+    /// A set name, or flag, question mark and mine separated by commas.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(set) = MarkerSet::ALL.into_iter().find(|set| set.name() == s) {
+            return Ok(set.markers());
+        }
+        let parts: Vec<&str> = s.split(',').collect();
+        let Ok(parts) = <[&str; 3]>::try_from(parts) else {
+            return Err(MarkerError::Unknown(s.to_string()));
+        };
+        let mut markers = Markers::default();
+        for (marker, text) in Marker::ALL.into_iter().zip(parts) {
+            markers.set(marker, text)?;
+        }
+        Ok(markers)
+    }
+}
+
+/// Whether `text` is exactly one user-perceived character: one base
+/// character with any combining marks, variation selectors and emoji
+/// modifiers after it, a ZWJ sequence of emoji, a pair of regional
+/// indicators (a flag), or a keycap. Whitespace and control characters
+/// don't count, since they would leave a marker invisible.
+///
+/// This follows the grapheme cluster rules closely enough for markers
+/// without the Unicode tables; Hangul jamo sequences, for one, count as
+/// several characters here.
+///
+/// ```
+/// use minesweeper_rs::render::is_one_grapheme;
+///
+/// assert!(is_one_grapheme("👩\u{200d}🚀"));
+/// assert!(is_one_grapheme("e\u{301}"));
+/// assert!(!is_one_grapheme("ab"));
+/// ```
+pub fn is_one_grapheme(text: &str) -> bool {
+    let mut chars = text.chars().peekable();
+    let Some(first) = chars.next() else {
+        return false;
+    };
+    if first.is_whitespace() || first.is_control() || extends(first) || first == ZWJ {
+        return false;
+    }
+    let mut base = first;
+    let mut paired = !is_regional_indicator(first);
+    while let Some(c) = chars.next() {
+        if extends(c) {
+            continue;
+        }
+        if is_regional_indicator(c) && !paired {
+            paired = true;
+            continue;
+        }
+        // only emoji join into one character
+        match chars.next() {
+            Some(next) if c == ZWJ && is_pictographic(base) && is_pictographic(next) => {
+                base = next;
+            }
+            _ => return false,
+        }
+    }
+    true
+}
+
+const ZWJ: char = '\u{200d}';
+
+/// Characters that attach to the one before: combining marks, variation
+/// selectors, the keycap, emoji skin tones and tag characters.
+fn extends(c: char) -> bool {
+    matches!(
+        u32::from(c),
+        0x0300..=0x036f
+            | 0x0483..=0x0489
+            | 0x0591..=0x05bd
+            | 0x0610..=0x061a
+            | 0x064b..=0x065f
+            | 0x0900..=0x0903
+            | 0x093a..=0x094f
+            | 0x1ab0..=0x1aff
+            | 0x1dc0..=0x1dff
+            | 0x20d0..=0x20ff
+            | 0xfe00..=0xfe0f
+            | 0xfe20..=0xfe2f
+            | 0x1f3fb..=0x1f3ff
+            | 0xe0020..=0xe007f
+            | 0xe0100..=0xe01ef
+    )
+}
+
+fn is_regional_indicator(c: char) -> bool {
+    matches!(u32::from(c), 0x1f1e6..=0x1f1ff)
+}
+
+/// Roughly Unicode's Extended_Pictographic: the blocks emoji come from.
+fn is_pictographic(c: char) -> bool {
+    matches!(
+        u32::from(c),
+        0xa9 | 0xae
+            | 0x203c
+            | 0x2049
+            | 0x2122
+            | 0x2139
+            | 0x2194..=0x21aa
+            | 0x231a..=0x23ff
+            | 0x24c2
+            | 0x25aa..=0x27bf
+            | 0x2934..=0x2935
+            | 0x2b05..=0x2b55
+            | 0x3030
+            | 0x303d
+            | 0x3297
+            | 0x3299
+            | 0x1f000..=0x1faff
+    )
+}
+
+/// Spreadsheet-style column name: A to Z, then AA, AB and so on.
+///
+/// ```
+/// use minesweeper_rs::render::column_name;
+///
+/// assert_eq!(column_name(0), "A");
+/// assert_eq!(column_name(25), "Z");
+/// assert_eq!(column_name(26), "AA");
+/// ```
+pub fn column_name(x: usize) -> String {
+    let mut letters = Vec::new();
+    let mut n = x + 1;
+    while n > 0 {
+        n -= 1;
+        letters.push(char::from(b'A' + (n % 26) as u8));
+        n /= 26;
+    }
+    letters.iter().rev().collect()
+}
+
+/// The column a spreadsheet-style name stands for, the inverse of
+/// [`column_name`]; case doesn't matter.
+///
+/// ```
+/// use minesweeper_rs::render::column_index;
+///
+/// assert_eq!(column_index("A"), Some(0));
+/// assert_eq!(column_index("ab"), Some(27));
+/// assert_eq!(column_index("A1"), None);
+/// ```
+pub fn column_index(name: &str) -> Option<usize> {
+    if name.is_empty() {
+        return None;
+    }
+    let mut n: usize = 0;
+    for c in name.chars() {
+        if !c.is_ascii_alphabetic() {
+            return None;
+        }
+        let digit = (c.to_ascii_uppercase() as u8 - b'A') as usize + 1;
+        n = n.checked_mul(26)?.checked_add(digit)?;
+    }
+    Some(n - 1)
+}
+
+/// A cell named as in the coordinate labels, column letters then the row
+/// from 1 ("B7"), as `(x, y)`. Whether it is on a given board is up to the
+/// caller.
+///
+/// ```
+/// use minesweeper_rs::render::parse_cell_name;
+///
+/// assert_eq!(parse_cell_name("B7"), Some((1, 6)));
+/// assert_eq!(parse_cell_name("aa10"), Some((26, 9)));
+/// assert_eq!(parse_cell_name("B0"), None);
+/// assert_eq!(parse_cell_name("7B"), None);
+/// ```
+pub fn parse_cell_name(name: &str) -> Option<(usize, usize)> {
+    let split = name.find(|c: char| c.is_ascii_digit())?;
+    let (letters, digits) = name.split_at(split);
+    if !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let row: usize = digits.parse().ok()?;
+    Some((column_index(letters)?, row.checked_sub(1)?))
+}
+
+/// A renderer picked by name, as from a `--style` flag.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Style {
+    /// [`Classic`].
+    #[default]
+    Classic,
+    /// [`Compact`] without separators.
+    Compact,
+    /// [`Compact`] with separators.
+    Grid,
+}
+
+impl Style {
+    /// Every style, in the order help text lists them.
+    pub const ALL: [Style; 3] = [Style::Classic, Style::Compact, Style::Grid];
+
+    /// The name `FromStr` accepts.
+    pub fn name(self) -> &'static str {
+        match self {
+            Style::Classic => "classic",
+            Style::Compact => "compact",
+            Style::Grid => "grid",
+        }
+    }
+}
+
+impl BoardRenderer for Style {
+    fn render_with(&self, board: &Board, markers: &Markers) -> String {
+        match self {
+            Style::Classic => Classic.render_with(board, markers),
+            Style::Compact => Compact::default().render_with(board, markers),
+            Style::Grid => Compact {
+                separators: true,
+                ..Compact::default()
+            }
+            .render_with(board, markers),
+        }
+    }
+}
+
+/// A `--style` value that isn't one of [`Style::ALL`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnknownStyle(pub String);
+
+impl fmt::Display for UnknownStyle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown style {:?}; expected one of", self.0)?;
+        for style in Style::ALL {
+            write!(f, " {}", style.name())?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for UnknownStyle {}
+
+impl FromStr for Style {
+    type Err = UnknownStyle;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Style::ALL
+            .into_iter()
+            .find(|style| style.name() == s)
+            .ok_or_else(|| UnknownStyle(s.to_string()))
+    }
+}
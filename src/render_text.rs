@@ -0,0 +1,224 @@
+//! `render-text`: print the debug snapshot of a saved position (see
+//! `minesweeper_rs::report`) without opening the window, for pasting into
+//! a bug report. The position comes from an autosave slot, a board of a
+//! race result replayed up to some move, or a position code.
+
+use crate::cli::CliError;
+use crate::race::{Move, RaceResult};
+use minesweeper_rs::autosave::Autosave;
+use minesweeper_rs::report::{decode_position_code, DebugSnapshot};
+use minesweeper_rs::session::Command;
+use minesweeper_rs::Board;
+use std::path::PathBuf;
+
+const USAGE: &str = "usage: render-text (--autosave FILE | --replay RESULT.json \
+                     [--board N] [--move K] | --code HEX) [--mines]";
+
+/// Where the position comes from.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Source {
+    Autosave(PathBuf),
+    Replay {
+        path: PathBuf,
+        board: usize,         // from 0
+        moves: Option<usize>, // played; all of them if `None`
+    },
+    Code(String),
+}
+
+/// `render-text` read from the command line.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Request {
+    pub source: Source,
+    pub show_mines: bool,
+}
+
+pub fn parse(words: &[String]) -> Result<Request, String> {
+    let mut show_mines = false;
+    let source = parse_source(words, USAGE, |word| {
+        show_mines |= word == "--mines";
+        word == "--mines"
+    })?;
+    Ok(Request { source, show_mines })
+}
+
+/// The one position source in `words`, for any subcommand that reads a
+/// position. `flag` takes the subcommand's own flags, saying whether it
+/// knew the word; anything else is answered with `usage`.
+pub fn parse_source(
+    words: &[String],
+    usage: &str,
+    mut flag: impl FnMut(&str) -> bool,
+) -> Result<Source, String> {
+    let mut source = None;
+    let (mut board, mut moves) = (None, None);
+    let mut words = words.iter();
+    while let Some(word) = words.next() {
+        let mut value = || words.next().cloned().ok_or_else(|| usage.to_string());
+        let number = |text: String| {
+            text.parse::<usize>()
+                .map_err(|_| format!("{word} expects a number, not {text:?}"))
+        };
+        let given = match word.as_str() {
+            "--autosave" => Some(Source::Autosave(value()?.into())),
+            "--replay" => Some(Source::Replay {
+                path: value()?.into(),
+                board: 0,
+                moves: None,
+            }),
+            "--code" => Some(Source::Code(value()?)),
+            "--board" => {
+                board = Some(number(value()?)?);
+                None
+            }
+            "--move" => {
+                moves = Some(number(value()?)?);
+                None
+            }
+            word if flag(word) => None,
+            _ => return Err(usage.into()),
+        };
+        if given.is_some() {
+            if source.is_some() {
+                return Err("give one of --autosave, --replay and --code".into());
+            }
+            source = given;
+        }
+    }
+    let mut source = source.ok_or_else(|| usage.to_string())?;
+    match &mut source {
+        Source::Replay {
+            board: index,
+            moves: played,
+            ..
+        } => {
+            *index = board.unwrap_or(0);
+            *played = moves;
+        }
+        _ if board.is_some() || moves.is_some() => {
+            return Err("--board and --move go with --replay".into());
+        }
+        _ => {}
+    }
+    Ok(source)
+}
+
+/// The snapshot `words` ask for.
+pub fn render(words: &[String]) -> Result<String, CliError> {
+    let request = parse(words).map_err(CliError::Usage)?;
+    let (board, secs, moves) = load(&request.source)?;
+    let mut snapshot = DebugSnapshot::new(&board).show_mines(request.show_mines);
+    if let Some(secs) = secs {
+        snapshot = snapshot.secs(secs);
+    }
+    if let Some(moves) = &moves {
+        snapshot = snapshot.moves(moves);
+    }
+    Ok(snapshot.to_string())
+}
+
+/// A loaded position: the board, and how long it had been played and the
+/// moves that led to it where the source knows them.
+pub type Position = (Board, Option<f64>, Option<Vec<Command>>);
+
+/// The position `source` names.
+pub fn load(source: &Source) -> Result<Position, CliError> {
+    match source {
+        Source::Autosave(path) => {
+            let input = |err: String| CliError::Input(format!("{}: {err}", path.display()));
+            let session = Autosave::new(path)
+                .read()
+                .map_err(|err| input(err.to_string()))?
+                .ok_or_else(|| input("no saved game".into()))?;
+            Ok((session.board, Some(session.elapsed_secs), None))
+        }
+        Source::Replay { path, board, moves } => {
+            let input = |err: String| CliError::Input(format!("{}: {err}", path.display()));
+            let result = RaceResult::load(path).map_err(|err| input(err.to_string()))?;
+            let run = result.boards.get(*board).ok_or_else(|| {
+                let count = result.boards.len();
+                CliError::Usage(format!("--board {board}: the result has {count} boards"))
+            })?;
+            let played = moves.unwrap_or(run.moves.len());
+            if played > run.moves.len() {
+                let count = run.moves.len();
+                return Err(CliError::Usage(format!(
+                    "--move {played}: board {board} has {count} moves"
+                )));
+            }
+            let mut position = result.race.board(*board);
+            for &step in &run.moves[..played] {
+                step.apply(&mut position);
+            }
+            let secs = match played {
+                0 => Some(0.0),
+                n if n == run.moves.len() => Some(run.secs),
+                n => run.times.get(n - 1).copied(),
+            };
+            let commands = run.moves[..played].iter().map(|&step| command(step));
+            Ok((position, secs, Some(commands.collect())))
+        }
+        Source::Code(code) => {
+            let board = decode_position_code(code)
+                .map_err(|err| CliError::Input(format!("--code: {err}")))?;
+            Ok((board, None, None))
+        }
+    }
+}
+
+/// A race move as the line that plays it.
+fn command(step: Move) -> Command {
+    match step {
+        Move::Reveal(x, y) => Command::Reveal(x, y),
+        Move::Flag(x, y) => Command::Flag(x, y),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(s: &str) -> Vec<String> {
+        s.split(' ').map(String::from).collect()
+    }
+
+    #[test]
+    fn one_source_is_read_with_its_options() {
+        assert_eq!(
+            parse(&words("--replay run.json --move 12 --board 2 --mines")),
+            Ok(Request {
+                source: Source::Replay {
+                    path: "run.json".into(),
+                    board: 2,
+                    moves: Some(12),
+                },
+                show_mines: true,
+            })
+        );
+        assert_eq!(
+            parse(&words("--code 0a0b")).map(|request| request.source),
+            Ok(Source::Code("0a0b".into()))
+        );
+        assert!(parse(&words("--code 0a --autosave a.bin")).is_err());
+        assert!(parse(&words("--autosave a.bin --move 3")).is_err());
+        assert!(parse(&words("--mines")).is_err());
+        assert!(parse(&words("--replay")).is_err());
+    }
+
+    #[test]
+    fn a_position_code_renders_without_a_file() {
+        let mut board = Board::decode_layout("*..\n...\n").unwrap();
+        board.toggle_flag(0, 0);
+        let code = minesweeper_rs::report::position_code(&board);
+        let text = render(&words(&format!("--code {code}"))).unwrap();
+        assert!(text.contains("flags: 1\n"));
+        assert!(text.contains("time: unknown\n"));
+        assert!(!text.contains("mines:"));
+        let shown = render(&words(&format!("--code {code} --mines"))).unwrap();
+        assert!(shown.ends_with(&format!("code: {code}\n")));
+        assert_eq!(
+            render(&words("--code xyz")).map_err(|err| err.code()),
+            Err(3)
+        );
+    }
+}
@@ -0,0 +1,185 @@
+//! Replaying a logged game to check it holds up: each move has to do
+//! something on the board, none may come after the game ended, the moves
+//! must end the way the log claims, and any move times run forward within
+//! the game's time. Race results are checked this way board by board, with
+//! their own hashes on top.
+
+use crate::{Board, FlagResult, RevealResult};
+use std::fmt;
+
+/// One logged move.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Move {
+    /// Open the cell at (x, y).
+    Reveal(usize, usize),
+    /// Put down or take up a flag at (x, y).
+    Flag(usize, usize),
+}
+
+impl Move {
+    /// Play the move, returning false if the board ignored it.
+    pub fn apply(self, board: &mut Board) -> bool {
+        match self {
+            Move::Reveal(x, y) => !matches!(board.reveal_cell(x, y), RevealResult::Ignored(_)),
+            Move::Flag(x, y) => !matches!(board.toggle_flag(x, y), FlagResult::Ignored(_)),
+        }
+    }
+}
+
+/// What gave a doctored log away. Moves count from 1, as players would.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Flaw {
+    /// A move the board would have ignored, which play never logs.
+    Ignored(usize),
+    /// A move after the game was won or lost.
+    AfterEnd(usize),
+    /// The moves don't end the way the log says.
+    Outcome,
+    /// The game's time is negative or not a number.
+    Time,
+    /// A different number of move times than moves.
+    MoveTimes,
+    /// A move timed before the one it follows, or after the game's time.
+    Gap(usize),
+    /// The log or time was changed after it was hashed. [`verify`] never
+    /// finds this: it is for callers that hash their logs, as races do.
+    Hash,
+}
+
+impl fmt::Display for Flaw {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Flaw::Ignored(n) => write!(f, "move {n} does nothing on that board"),
+            Flaw::AfterEnd(n) => write!(f, "move {n} comes after the board was over"),
+            Flaw::Outcome => f.write_str("the moves don't end the way the result says"),
+            Flaw::Time => f.write_str("its time is negative or not a number"),
+            Flaw::MoveTimes => f.write_str("it doesn't time every move"),
+            Flaw::Gap(n) => write!(
+                f,
+                "move {n} is timed before the move it follows or after the board ended"
+            ),
+            Flaw::Hash => f.write_str("its moves or time were changed after it was hashed"),
+        }
+    }
+}
+
+/// Play `moves` on `board` and check they end in a win exactly when `won`
+/// says, within `secs` seconds. `times` holds the seconds into the game of
+/// each move, or is empty for a log that kept none. The checks run in the
+/// order that names the most telling flaw; `board` is left as the moves
+/// left it, up to the first bad one.
+///
+/// ```
+/// use minesweeper_rs::replay::{self, Flaw, Move};
+/// use minesweeper_rs::Board;
+///
+/// let board = Board::decode_layout("*..\n")?;
+/// let moves = [Move::Reveal(2, 0)];
+/// assert_eq!(replay::verify(&mut board.clone(), &moves, &[0.5], 0.5, true), Ok(()));
+/// assert_eq!(
+///     replay::verify(&mut board.clone(), &moves, &[], 0.5, false),
+///     Err(Flaw::Outcome)
+/// );
+/// # Ok::<(), minesweeper_rs::LayoutError>(())
+/// ```
+pub fn verify(
+    board: &mut Board,
+    moves: &[Move],
+    times: &[f64],
+    secs: f64,
+    won: bool,
+) -> Result<(), Flaw> {
+    for (n, &m) in moves.iter().enumerate() {
+        if board.is_over() {
+            return Err(Flaw::AfterEnd(n + 1));
+        }
+        if !m.apply(board) {
+            return Err(Flaw::Ignored(n + 1));
+        }
+    }
+    if board.is_won() != won {
+        return Err(Flaw::Outcome);
+    }
+    if !secs.is_finite() || secs < 0.0 {
+        return Err(Flaw::Time);
+    }
+    if !times.is_empty() {
+        if times.len() != moves.len() {
+            return Err(Flaw::MoveTimes);
+        }
+        let mut last = 0.0;
+        for (n, &at) in times.iter().enumerate() {
+            if !(last..=secs).contains(&at) {
+                return Err(Flaw::Gap(n + 1));
+            }
+            last = at;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A mine top left and two safe cells; opening (2, 0) clears the board.
+    fn board() -> Board {
+        Board::decode_layout("*..\n").unwrap()
+    }
+
+    fn check(moves: &[Move], times: &[f64], secs: f64, won: bool) -> Result<(), Flaw> {
+        verify(&mut board(), moves, times, secs, won)
+    }
+
+    #[test]
+    fn a_true_log_holds_with_or_without_times() {
+        let moves = [Move::Flag(0, 0), Move::Reveal(2, 0)];
+        assert_eq!(check(&moves, &[1.0, 2.5], 3.0, true), Ok(()));
+        assert_eq!(check(&moves, &[], 3.0, true), Ok(()));
+        assert_eq!(check(&[Move::Reveal(0, 0)], &[], 1.0, false), Ok(()));
+        // an unfinished log is a loss, not a win
+        assert_eq!(check(&[Move::Flag(0, 0)], &[], 1.0, false), Ok(()));
+    }
+
+    #[test]
+    fn the_board_is_left_as_the_moves_left_it() {
+        let mut played = board();
+        verify(&mut played, &[Move::Reveal(2, 0)], &[], 1.0, true).unwrap();
+        assert!(played.is_won());
+    }
+
+    #[test]
+    fn moves_that_do_nothing_or_come_too_late_are_flaws() {
+        let reveal = Move::Reveal(2, 0);
+        assert_eq!(
+            check(&[Move::Reveal(3, 0)], &[], 1.0, false),
+            Err(Flaw::Ignored(1))
+        );
+        let twice = [
+            Move::Flag(0, 0),
+            Move::Flag(0, 0),
+            Move::Reveal(0, 0),
+            reveal,
+        ];
+        assert_eq!(check(&twice, &[], 1.0, true), Err(Flaw::AfterEnd(4)));
+        let flagged = [Move::Flag(2, 0), Move::Reveal(2, 0)];
+        assert_eq!(check(&flagged, &[], 1.0, true), Err(Flaw::Ignored(2)));
+    }
+
+    #[test]
+    fn a_claimed_outcome_or_time_that_doesnt_fit_is_a_flaw() {
+        let moves = [Move::Reveal(2, 0)];
+        assert_eq!(check(&moves, &[], 1.0, false), Err(Flaw::Outcome));
+        assert_eq!(
+            check(&[Move::Reveal(0, 0)], &[], 1.0, true),
+            Err(Flaw::Outcome)
+        );
+        assert_eq!(check(&moves, &[], -1.0, true), Err(Flaw::Time));
+        assert_eq!(check(&moves, &[], f64::NAN, true), Err(Flaw::Time));
+        assert_eq!(check(&moves, &[0.5, 0.6], 1.0, true), Err(Flaw::MoveTimes));
+        assert_eq!(check(&moves, &[1.5], 1.0, true), Err(Flaw::Gap(1)));
+        let two = [Move::Flag(0, 0), Move::Reveal(2, 0)];
+        assert_eq!(check(&two, &[0.8, 0.4], 1.0, true), Err(Flaw::Gap(2)));
+    }
+}
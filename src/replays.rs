@@ -0,0 +1,467 @@
+//! Saved race results, the only games kept move by move and so the only
+//! replays there are, one file each under `races/` in the data dir.
+//!
+//! Next to them sits a small index with what lists and pruning need (date,
+//! player, board size, boards won, time, 3BV and file size), so nothing
+//! has to open every result to show how many there are or to decide which
+//! to drop. The index is rebuilt by reading the results whenever it is
+//! missing, doesn't parse, or names other files than the folder holds,
+//! which is also how results copied in by a data import get indexed.
+
+use crate::challenge::Modifier;
+use crate::history;
+use crate::race::{RaceError, RaceResult};
+use chrono::NaiveDate;
+use minesweeper_rs::{format, stats};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+/// The index's file name, the one file in the folder that isn't a result.
+const INDEX: &str = "index.json";
+
+/// What the index knows about one result.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    pub file: String,
+    pub day: NaiveDate, // saved on, or last changed on for a rebuilt entry
+    pub player: String,
+    pub size: (usize, usize, usize), // width, height, mines
+    pub boards: usize,               // in the race, played or not
+    pub won: usize,
+    pub secs: f64, // adjusted total
+    pub three_bv: usize,
+    pub bytes: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modifier: Option<Modifier>, // a challenge's special rule
+}
+
+impl Entry {
+    fn new(file: &str, day: NaiveDate, result: &RaceResult, bytes: u64) -> Self {
+        let race = &result.race;
+        Entry {
+            file: file.into(),
+            day,
+            player: result.player.clone(),
+            size: (race.width, race.height, race.mines),
+            boards: race.seeds.len(),
+            won: result.boards_won(),
+            secs: result.adjusted_secs(),
+            three_bv: (0..race.seeds.len())
+                .map(|index| stats::three_bv(&race.board(index)))
+                .sum(),
+            bytes,
+            modifier: race.modifier,
+        }
+    }
+
+    /// Every board of the race won.
+    pub fn is_win(&self) -> bool {
+        self.won == self.boards
+    }
+}
+
+/// Which of a player's results pruning keeps.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Keep {
+    #[default]
+    All,
+    Last(usize),
+    WinsAndBests, // races won outright, and the lowest total at each size
+}
+
+/// The results folder and its index, oldest result first.
+pub struct ReplayStore {
+    dir: PathBuf,
+    index: Index,
+}
+
+/// The index as written: the results, and the files in the folder that
+/// didn't read as one, so that they don't set off a rebuild every time.
+#[derive(Default, Serialize, Deserialize)]
+struct Index {
+    entries: Vec<Entry>,
+    unreadable: BTreeSet<String>,
+}
+
+impl ReplayStore {
+    /// The store in `dir`, rebuilding its index if it can't be trusted.
+    /// A folder that doesn't exist yet is an empty store.
+    pub fn open(dir: PathBuf) -> Self {
+        let mut store = ReplayStore {
+            dir,
+            index: Index::default(),
+        };
+        let Ok(files) = store.files() else {
+            return store;
+        };
+        let index = std::fs::read_to_string(store.dir.join(INDEX))
+            .ok()
+            .and_then(|text| serde_json::from_str::<Index>(&text).ok());
+        match index {
+            Some(index) if index.files() == files => store.index = index,
+            _ => store.rebuild(),
+        }
+        store
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    #[cfg(test)]
+    pub fn entries(&self) -> &[Entry] {
+        &self.index.entries
+    }
+
+    /// Results kept and the bytes they take, index included.
+    pub fn usage(&self) -> (usize, u64) {
+        let index = std::fs::metadata(self.dir.join(INDEX)).map_or(0, |m| m.len());
+        let bytes = self
+            .index
+            .entries
+            .iter()
+            .map(|entry| entry.bytes)
+            .sum::<u64>();
+        (self.index.entries.len(), bytes + index)
+    }
+
+    /// Write `result` as `file` and index it, replacing any result of
+    /// that name.
+    pub fn add(&mut self, file: &str, result: &RaceResult) -> io::Result<PathBuf> {
+        let path = self.dir.join(file);
+        let text = result.to_json();
+        std::fs::create_dir_all(&self.dir).and_then(|()| std::fs::write(&path, &text))?;
+        self.index.entries.retain(|entry| entry.file != file);
+        self.index.unreadable.remove(file);
+        let entry = Entry::new(file, history::today(), result, text.len() as u64);
+        self.index.entries.push(entry);
+        self.sort();
+        self.save_index()?;
+        Ok(path)
+    }
+
+    /// The whole result behind `entry`.
+    #[cfg(test)]
+    pub fn load(&self, entry: &Entry) -> Result<RaceResult, RaceError> {
+        RaceResult::load(&self.dir.join(&entry.file))
+    }
+
+    /// Delete `player`'s results that `keep` doesn't keep, returning how
+    /// many went. Other players' results are left alone.
+    pub fn prune(&mut self, player: &str, keep: Keep) -> io::Result<usize> {
+        let theirs: Vec<&Entry> = self
+            .index
+            .entries
+            .iter()
+            .filter(|e| e.player == player)
+            .collect();
+        let kept: BTreeSet<&str> = match keep {
+            Keep::All => return Ok(0),
+            Keep::Last(n) => theirs
+                .iter()
+                .rev()
+                .take(n)
+                .map(|e| e.file.as_str())
+                .collect(),
+            Keep::WinsAndBests => {
+                let mut bests = BTreeMap::<_, &Entry>::new();
+                for &entry in &theirs {
+                    let best = bests.entry(entry.size).or_insert(entry);
+                    if entry.secs < best.secs {
+                        *best = entry;
+                    }
+                }
+                let wins = theirs.iter().filter(|e| e.is_win());
+                wins.chain(bests.values())
+                    .map(|e| e.file.as_str())
+                    .collect()
+            }
+        };
+        let doomed: Vec<String> = theirs
+            .iter()
+            .filter(|e| !kept.contains(e.file.as_str()))
+            .map(|e| e.file.clone())
+            .collect();
+        let mut removed = Ok(0);
+        for file in &doomed {
+            match std::fs::remove_file(self.dir.join(file)) {
+                Err(err) if err.kind() != io::ErrorKind::NotFound => {
+                    removed = Err(err);
+                    break;
+                }
+                _ => {}
+            }
+            self.index.entries.retain(|entry| &entry.file != file);
+            removed = removed.map(|n| n + 1);
+        }
+        // what did go stays gone from the index, even after a failure
+        self.save_index()?;
+        if let Ok(n @ 1..) = removed {
+            info!(pruned = n, ?keep, "pruned race results");
+        }
+        removed
+    }
+
+    /// Index every result in the folder afresh. Files that don't load as
+    /// results are left where they are, unindexed.
+    pub fn rebuild(&mut self) {
+        self.index = Index::default();
+        for file in self.files().unwrap_or_default() {
+            let path = self.dir.join(&file);
+            let read = RaceResult::load(&path)
+                .ok()
+                .zip(std::fs::metadata(&path).ok());
+            let Some((result, meta)) = read else {
+                self.index.unreadable.insert(file);
+                continue;
+            };
+            let day = meta.modified().map_or_else(
+                |_| history::today(),
+                |time| chrono::DateTime::<chrono::Local>::from(time).date_naive(),
+            );
+            self.index
+                .entries
+                .push(Entry::new(&file, day, &result, meta.len()));
+        }
+        self.sort();
+        info!(
+            results = self.index.entries.len(),
+            "rebuilt race result index"
+        );
+        if let Err(err) = self.save_index() {
+            warn!(%err, "couldn't write the race result index");
+        }
+    }
+
+    /// Names of the results in the folder, in name order, leaving out
+    /// anything that isn't a result file.
+    fn files(&self) -> io::Result<BTreeSet<String>> {
+        let mut files = BTreeSet::new();
+        for entry in std::fs::read_dir(&self.dir)? {
+            let name = entry?.file_name().to_string_lossy().into_owned();
+            if name.ends_with(".json") && name != INDEX {
+                files.insert(name);
+            }
+        }
+        Ok(files)
+    }
+
+    /// Oldest first, files of the same day by name.
+    fn sort(&mut self) {
+        self.index
+            .entries
+            .sort_by(|a, b| (a.day, &a.file).cmp(&(b.day, &b.file)));
+    }
+
+    fn save_index(&self) -> io::Result<()> {
+        let text = serde_json::to_string(&self.index).expect("indexes serialize");
+        write_atomically(&self.dir.join(INDEX), &text)
+    }
+}
+
+impl Index {
+    /// Every file the index accounts for, read or not.
+    fn files(&self) -> BTreeSet<String> {
+        let results = self.entries.iter().map(|entry| entry.file.clone());
+        results.chain(self.unreadable.iter().cloned()).collect()
+    }
+}
+
+/// Write through a temporary file, so a crash leaves the old index or the
+/// new one and never half of either.
+fn write_atomically(path: &Path, text: &str) -> io::Result<()> {
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, text)?;
+    std::fs::rename(tmp, path)
+}
+
+/// `verify FILE`: replay a result file against its seeds as loading one
+/// does, summed up in a line if it holds. The error names the board and
+/// the check it failed.
+pub fn verify(path: &Path) -> Result<String, RaceError> {
+    let result = RaceResult::load(path)?;
+    Ok(format!(
+        "verified: {}, {} of {} boards won, {}",
+        result.player,
+        result.boards_won(),
+        result.race.seeds.len(),
+        format::clock_tenths(result.adjusted_secs())
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::race::{BoardRun, Move, Race};
+
+    fn store_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir()
+            .join(format!("minesweeper-replays-{}", std::process::id()))
+            .join(name);
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    /// A one-board race on seed `seed`, won by `player` in `secs` or lost.
+    /// Six mines keep the opened start from clearing any of the seeds used
+    /// here, which would make a loss impossible.
+    fn result(player: &str, seed: u64, won: bool, secs: f64) -> RaceResult {
+        let race = Race {
+            width: 5,
+            height: 5,
+            mines: 6,
+            seeds: vec![seed],
+            handicaps: BTreeMap::new(),
+            modifier: None,
+        };
+        let mut moves = Vec::new();
+        if won {
+            let mut board = race.board(0);
+            for (y, row) in board.encode_layout().lines().enumerate() {
+                for (x, c) in row.chars().enumerate() {
+                    if c == '.' && Move::Reveal(x, y).apply(&mut board) {
+                        moves.push(Move::Reveal(x, y));
+                    }
+                }
+            }
+        }
+        RaceResult {
+            player: player.into(),
+            boards: vec![BoardRun::new(&race, player, 0, secs, won, moves)],
+            race,
+        }
+    }
+
+    /// Three hundred results, Ana's and Bo's in turn, every third a win.
+    fn filled(name: &str) -> ReplayStore {
+        let mut store = ReplayStore::open(store_dir(name));
+        for i in 0..300u64 {
+            let player = if i % 2 == 0 { "Ana" } else { "Bo" };
+            let result = result(player, i, i % 3 == 0, 100.0 - i as f64 / 10.0);
+            store.add(&format!("{i:03}.json"), &result).unwrap();
+        }
+        store
+    }
+
+    /// The result files actually in the folder.
+    fn on_disk(store: &ReplayStore) -> BTreeSet<String> {
+        store.files().unwrap()
+    }
+
+    #[test]
+    fn the_index_follows_adds_and_prunes() {
+        let mut store = filled("adds");
+        assert_eq!(store.entries().len(), 300);
+        assert_eq!(store.index.files(), on_disk(&store));
+        let (results, bytes) = store.usage();
+        assert_eq!(results, 300);
+        assert!(bytes > store.entries().iter().map(|e| e.bytes).sum::<u64>());
+
+        // the same name again replaces rather than adds
+        store.add("000.json", &result("Ana", 0, true, 1.0)).unwrap();
+        assert_eq!(store.entries().len(), 300);
+
+        assert_eq!(store.prune("Ana", Keep::Last(50)).unwrap(), 100);
+        let ana: Vec<&Entry> = store
+            .entries()
+            .iter()
+            .filter(|e| e.player == "Ana")
+            .collect();
+        assert_eq!(ana.len(), 50);
+        assert_eq!(ana[0].file, "200.json");
+        assert_eq!(store.entries().len(), 200);
+        assert_eq!(store.index.files(), on_disk(&store));
+
+        // Bo's wins are every sixth from 3; the fastest of them is the last
+        assert_eq!(store.prune("Bo", Keep::WinsAndBests).unwrap(), 100);
+        let bo: Vec<&Entry> = store
+            .entries()
+            .iter()
+            .filter(|e| e.player == "Bo")
+            .collect();
+        assert_eq!(bo.len(), 50);
+        assert!(bo.iter().all(|e| e.is_win()));
+        assert_eq!(store.prune("Bo", Keep::All).unwrap(), 0);
+        assert_eq!(store.index.files(), on_disk(&store));
+
+        let reopened = ReplayStore::open(store.dir().to_path_buf());
+        assert_eq!(reopened.entries(), store.entries());
+        let loaded = reopened.load(&reopened.entries()[0]).unwrap();
+        assert_eq!(loaded.player, reopened.entries()[0].player);
+    }
+
+    #[test]
+    fn without_a_win_the_best_loss_is_kept() {
+        let mut store = ReplayStore::open(store_dir("bests"));
+        store
+            .add("won.json", &result("Ana", 1, true, 50.0))
+            .unwrap();
+        store
+            .add("lost.json", &result("Ana", 2, false, 5.0))
+            .unwrap();
+        assert_eq!(store.prune("Ana", Keep::WinsAndBests).unwrap(), 1);
+
+        for seed in 3..6 {
+            let file = format!("bo-{seed}.json");
+            store.add(&file, &result("Bo", seed, false, 5.0)).unwrap();
+        }
+        assert_eq!(store.prune("Bo", Keep::WinsAndBests).unwrap(), 2);
+        let files: Vec<&str> = store.entries().iter().map(|e| e.file.as_str()).collect();
+        assert_eq!(files, ["bo-3.json", "won.json"]);
+    }
+
+    #[test]
+    fn a_missing_or_corrupt_index_is_rebuilt() {
+        let store = filled("rebuild");
+        let dir = store.dir().to_path_buf();
+        let entries = store.entries().to_vec();
+
+        std::fs::remove_file(dir.join(INDEX)).unwrap();
+        assert_eq!(ReplayStore::open(dir.clone()).entries(), entries);
+        assert!(dir.join(INDEX).exists());
+
+        std::fs::write(dir.join(INDEX), "{\"entries\": [").unwrap();
+        assert_eq!(ReplayStore::open(dir.clone()).entries(), entries);
+
+        // files dropped in or taken out behind its back, as a data import
+        // or a tidy-up by hand would
+        std::fs::copy(dir.join("001.json"), dir.join("copied.json")).unwrap();
+        std::fs::remove_file(dir.join("002.json")).unwrap();
+        std::fs::write(dir.join("notes.json"), "not a result").unwrap();
+        let store = ReplayStore::open(dir.clone());
+        assert_eq!(store.entries().len(), 300);
+        assert!(store.entries().iter().any(|e| e.file == "copied.json"));
+        assert!(store.entries().iter().all(|e| e.file != "002.json"));
+        assert_eq!(store.index.files(), on_disk(&store));
+
+        // the stray file is remembered, so that an index matching the
+        // folder is trusted without opening a single result
+        assert!(store.index.unreadable.contains("notes.json"));
+        std::fs::write(dir.join("003.json"), "changed behind its back").unwrap();
+        assert!(ReplayStore::open(dir.clone())
+            .entries()
+            .iter()
+            .any(|e| e.file == "003.json"));
+    }
+
+    #[test]
+    fn verify_sums_up_a_good_file_and_names_a_bad_one() {
+        let mut store = ReplayStore::open(store_dir("verify"));
+        let path = store
+            .add("won.json", &result("Ana", 4, true, 12.5))
+            .unwrap();
+        assert_eq!(
+            verify(&path).unwrap(),
+            "verified: Ana, 1 of 1 boards won, 0:12.5"
+        );
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, text.replace("12.5", "2.5")).unwrap();
+        assert!(matches!(verify(&path), Err(RaceError::Tampered(0, _))));
+        std::fs::write(&path, "{").unwrap();
+        assert!(matches!(verify(&path), Err(RaceError::Json(_))));
+    }
+}
@@ -0,0 +1,215 @@
+//! Plain-text snapshots of a game for bug reports: the board as the player
+//! sees it, where the game stands, the seed and the last few moves, all in
+//! a block that pastes cleanly into an issue.
+//!
+//! The format is meant to be read by people and scraped by scripts, so it
+//! only ever grows. It opens with a version line and then has one
+//! `key: value` line per fact, in a fixed order. Three keys are followed
+//! by lines of their own: `board:` by the grid (see [`Compact`]), `moves:`
+//! by the last moves, indented and in the command language of
+//! [`session`](crate::session), and `mines:` by the layout.
+//!
+//! Where the mines are stays out of a snapshot of a game still in play:
+//! the seed would give them away, so it reads `hidden` until the game is
+//! over, unless [`DebugSnapshot::show_mines`] asks otherwise.
+//!
+//! A position can also travel as a [`position_code`]: the board's saved
+//! state in hex, which [`decode_position_code`] turns back into the board.
+//! It gives away the mines, so a snapshot only includes it where it shows
+//! them.
+
+use crate::render::{BoardRenderer, Compact};
+use crate::session::Command;
+use crate::{Board, DecodeError};
+use std::fmt::{self, Write};
+
+/// Version of the snapshot format, on its first line.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Moves listed at the end of a snapshot; earlier ones are only counted.
+pub const RECENT_MOVES: usize = 5;
+
+/// A board written out for a bug report. Everything but the board is
+/// optional; a snapshot says which facts it wasn't given.
+///
+/// ```
+/// use minesweeper_rs::report::DebugSnapshot;
+/// use minesweeper_rs::session::Command;
+/// use minesweeper_rs::Board;
+///
+/// let mut board = Board::decode_layout("*..\n...\n")?;
+/// board.reveal_cell(2, 1);
+/// let text = DebugSnapshot::new(&board)
+///     .secs(12.5)
+///     .moves(&[Command::Reveal(2, 1)])
+///     .to_string();
+/// assert!(text.starts_with("minesweeper-rs snapshot v1\nsize: 3x2, 1 mines\n"));
+/// assert!(text.contains("state: playing\ntime: 0:12.5\n"));
+/// assert!(text.ends_with("moves: 1\n  1. C2\n"));
+/// # Ok::<(), minesweeper_rs::LayoutError>(())
+/// ```
+#[derive(Clone)]
+pub struct DebugSnapshot<'a> {
+    board: &'a Board,
+    secs: Option<f64>,
+    moves: Option<&'a [Command]>,
+    show_mines: bool,
+}
+
+impl<'a> DebugSnapshot<'a> {
+    /// A snapshot of `board`, without a time or moves, with the mines kept
+    /// back while it is in play.
+    pub fn new(board: &'a Board) -> Self {
+        DebugSnapshot {
+            board,
+            secs: None,
+            moves: None,
+            show_mines: board.is_over(),
+        }
+    }
+
+    /// Seconds the game has been played.
+    pub fn secs(mut self, secs: f64) -> Self {
+        self.secs = Some(secs);
+        self
+    }
+
+    /// Every move made so far, oldest first; the last [`RECENT_MOVES`] are
+    /// listed.
+    pub fn moves(mut self, moves: &'a [Command]) -> Self {
+        self.moves = Some(moves);
+        self
+    }
+
+    /// Include the seed, layout and position code even though the game
+    /// isn't over. They are always included once it is.
+    pub fn show_mines(mut self, show: bool) -> Self {
+        self.show_mines = show || self.board.is_over();
+        self
+    }
+}
+
+impl fmt::Display for DebugSnapshot<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let board = self.board;
+        writeln!(f, "minesweeper-rs snapshot v{FORMAT_VERSION}")?;
+        writeln!(
+            f,
+            "size: {}x{}, {} mines",
+            board.width(),
+            board.height(),
+            board.mine_count()
+        )?;
+        match board.seed() {
+            Some(seed) if self.show_mines => writeln!(f, "seed: {seed}")?,
+            Some(_) => writeln!(f, "seed: hidden")?,
+            None => writeln!(f, "seed: none")?,
+        }
+        writeln!(f, "layout hash: {:016x}", board.layout_hash())?;
+        let state = if board.is_won() {
+            "won"
+        } else if board.is_lost() {
+            "lost"
+        } else {
+            "playing"
+        };
+        writeln!(f, "state: {state}")?;
+        match self.secs {
+            Some(secs) => writeln!(f, "time: {}", crate::format::clock_tenths(secs))?,
+            None => writeln!(f, "time: unknown")?,
+        }
+        writeln!(f, "flags: {}", board.flags())?;
+        let safe = board.width() * board.height() - board.mine_count();
+        writeln!(f, "open: {} of {safe} safe cells", board.revealed_safe())?;
+        let grid = Compact {
+            coordinates: true,
+            ..Compact::default()
+        };
+        write!(f, "board:\n{}", grid.render(board))?;
+        match self.moves {
+            Some(moves) => {
+                writeln!(f, "moves: {}", moves.len())?;
+                let first = moves.len().saturating_sub(RECENT_MOVES);
+                for (n, command) in moves.iter().enumerate().skip(first) {
+                    writeln!(f, "  {}. {command}", n + 1)?;
+                }
+            }
+            None => writeln!(f, "moves: not recorded")?,
+        }
+        if self.show_mines {
+            write!(f, "mines:\n{}", board.encode_layout())?;
+            writeln!(f, "code: {}", position_code(board))?;
+        }
+        Ok(())
+    }
+}
+
+/// `board` with its play state as a line of hex, for
+/// [`decode_position_code`].
+///
+/// ```
+/// use minesweeper_rs::report::{decode_position_code, position_code};
+/// use minesweeper_rs::Board;
+///
+/// let mut board = Board::with_seed(9, 9, 10, 7, None);
+/// board.toggle_flag(4, 4);
+/// let restored = decode_position_code(&position_code(&board))?;
+/// assert!(restored.cell(4, 4).is_flagged());
+/// assert_eq!(restored.layout_hash(), board.layout_hash());
+/// # Ok::<(), minesweeper_rs::report::CodeError>(())
+/// ```
+pub fn position_code(board: &Board) -> String {
+    let mut code = String::new();
+    for byte in board.encode_state() {
+        let _ = write!(code, "{byte:02x}");
+    }
+    code
+}
+
+/// The board a [`position_code`] stands for. Whitespace is ignored, so a
+/// code wrapped across lines still reads.
+pub fn decode_position_code(code: &str) -> Result<Board, CodeError> {
+    let digits: Vec<u8> = code
+        .bytes()
+        .filter(|b| !b.is_ascii_whitespace())
+        .map(|b| match b {
+            b'0'..=b'9' => Ok(b - b'0'),
+            b'a'..=b'f' => Ok(b - b'a' + 10),
+            b'A'..=b'F' => Ok(b - b'A' + 10),
+            _ => Err(CodeError::NotHex),
+        })
+        .collect::<Result<_, _>>()?;
+    let pairs = digits.chunks_exact(2);
+    if !pairs.remainder().is_empty() {
+        return Err(CodeError::NotHex);
+    }
+    let bytes: Vec<u8> = pairs.map(|pair| pair[0] << 4 | pair[1]).collect();
+    Board::decode_state(&bytes).map_err(CodeError::Board)
+}
+
+/// Why a position code couldn't be read.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CodeError {
+    /// The code isn't pairs of hex digits.
+    NotHex,
+    /// The bytes aren't a board.
+    Board(DecodeError),
+}
+
+impl fmt::Display for CodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CodeError::NotHex => f.write_str("a position code is pairs of hex digits"),
+            CodeError::Board(err) => write!(f, "not a position code: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for CodeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CodeError::NotHex => None,
+            CodeError::Board(err) => Some(err),
+        }
+    }
+}
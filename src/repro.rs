@@ -0,0 +1,332 @@
+//! Bug report bundles: the game in progress saved with everything needed
+//! to play it again, and `reproduce`, which does so without a window and
+//! checks it goes the same way.
+//!
+//! A bundle is a zip in `bug-reports/` under the data dir holding
+//! `journal.json` (the build, the board size, seed and gameplay settings,
+//! the board as play began, every action since, the last events, and the
+//! board as it ended) and `snapshot.txt`, the game's debug snapshot with
+//! the mines shown. Replaying it starts a fresh game state with the same
+//! settings from that first board, carries out each action through the
+//! same code the game uses, and passes if the events and the final board
+//! come out as recorded, so a bundle doubles as a regression test.
+
+mod zip;
+
+use crate::app::{AppState, GameEvent};
+use crate::changelog;
+use crate::cli::CliError;
+use crate::journal::{Action, EVENTS};
+use crate::settings::Gameplay;
+use eframe::egui;
+use minesweeper_rs::report::{decode_position_code, position_code, DebugSnapshot};
+use minesweeper_rs::{Board, Difficulty};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use tracing::info;
+pub use zip::ZipError;
+
+/// Tag every journal carries, so other JSON is turned away.
+const FORMAT: &str = "bosnia-simulator-repro";
+
+/// Journal version written by this build.
+pub const VERSION: u32 = 1;
+
+const JOURNAL: &str = "journal.json";
+const SNAPSHOT: &str = "snapshot.txt";
+
+/// What a bundle's `journal.json` holds.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Report {
+    pub format: String,
+    pub version: u32,
+    pub app_version: String,
+    pub size: (usize, usize, usize), // width, height, mines
+    pub seed: Option<u64>,
+    pub gameplay: Gameplay,
+    pub start: String, // position code of the board as play began
+    pub actions: Vec<Action>,
+    pub events: Vec<GameEvent>, // the last `EVENTS`, oldest first
+    pub end: String,            // position code of the board when saved
+}
+
+impl Report {
+    /// The game in `state`, if its mines have been laid.
+    pub fn capture(state: &AppState) -> Option<Report> {
+        let board = &state.board;
+        Some(Report {
+            format: FORMAT.into(),
+            version: VERSION,
+            app_version: changelog::CURRENT.into(),
+            size: (board.width(), board.height(), board.mine_count()),
+            seed: board.seed(),
+            gameplay: state.settings.gameplay.clone(),
+            start: state.journal.start()?.to_string(),
+            actions: state.journal.actions().to_vec(),
+            events: state.journal.events().collect(),
+            end: position_code(board),
+        })
+    }
+}
+
+/// Why a bundle couldn't be written or read.
+#[derive(Debug)]
+pub enum ReproError {
+    NotStarted, // no mines laid yet, so nothing to replay
+    NoDataDir,
+    Io(io::Error),
+    Zip(ZipError),
+    Json(serde_json::Error),
+    Invalid(String), // a zip, but not a bundle this build can replay
+}
+
+impl fmt::Display for ReproError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReproError::NotStarted => f.write_str("the game hasn't started"),
+            ReproError::NoDataDir => f.write_str("there is no data folder to save it in"),
+            ReproError::Io(err) => write!(f, "{err}"),
+            ReproError::Zip(err) => write!(f, "{err}"),
+            ReproError::Json(err) => write!(f, "{JOURNAL}: {err}"),
+            ReproError::Invalid(why) => f.write_str(why),
+        }
+    }
+}
+
+impl std::error::Error for ReproError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ReproError::Io(err) => Some(err),
+            ReproError::Zip(err) => Some(err),
+            ReproError::Json(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ReproError {
+    fn from(err: io::Error) -> Self {
+        ReproError::Io(err)
+    }
+}
+
+/// The bundle for the game in `state`, as zip bytes.
+pub fn bundle(state: &AppState) -> Result<Vec<u8>, ReproError> {
+    let report = Report::capture(state).ok_or(ReproError::NotStarted)?;
+    let journal = serde_json::to_string_pretty(&report).map_err(ReproError::Json)?;
+    let moves = state.journal.commands();
+    let snapshot = DebugSnapshot::new(&state.board)
+        .secs(state.clock.secs)
+        .moves(&moves)
+        .show_mines(true)
+        .to_string();
+    Ok(zip::write(&[
+        (JOURNAL, journal.as_bytes()),
+        (SNAPSHOT, snapshot.as_bytes()),
+    ]))
+}
+
+/// Write the bundle for the game in `state` to a new file under the data
+/// dir, returning where.
+pub fn save(state: &AppState) -> Result<PathBuf, ReproError> {
+    let dir = state
+        .data_dir
+        .as_ref()
+        .ok_or(ReproError::NoDataDir)?
+        .join("bug-reports");
+    let bytes = bundle(state)?;
+    std::fs::create_dir_all(&dir)?;
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    let path = dir.join(format!("bug-{stamp}.zip"));
+    std::fs::write(&path, bytes)?;
+    info!(path = %path.display(), "saved bug report bundle");
+    Ok(path)
+}
+
+/// The journal in the bundle at `path`.
+pub fn load(path: &Path) -> Result<Report, ReproError> {
+    read(&std::fs::read(path)?)
+}
+
+/// The journal in a bundle's bytes.
+pub fn read(bytes: &[u8]) -> Result<Report, ReproError> {
+    let files = zip::read(bytes).map_err(ReproError::Zip)?;
+    let (_, journal) = files
+        .iter()
+        .find(|(name, _)| name == JOURNAL)
+        .ok_or_else(|| ReproError::Invalid(format!("no {JOURNAL} in the bundle")))?;
+    let report: Report = serde_json::from_slice(journal).map_err(ReproError::Json)?;
+    if report.format != FORMAT {
+        return Err(ReproError::Invalid("not a bug report bundle".into()));
+    }
+    if report.version > VERSION {
+        return Err(ReproError::Invalid(format!(
+            "bundle version {} is newer than this build reads",
+            report.version
+        )));
+    }
+    Ok(report)
+}
+
+/// How a replay went differently from the recording.
+#[derive(Debug)]
+pub enum Mismatch {
+    BadPosition(String), // the start or end position isn't a board
+    Events {
+        at: usize, // index into the recorded events
+        recorded: Option<GameEvent>,
+        replayed: Option<GameEvent>,
+    },
+    Board(String), // the end boards' diff
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Mismatch::BadPosition(why) => f.write_str(why),
+            Mismatch::Events {
+                at,
+                recorded,
+                replayed,
+            } => write!(
+                f,
+                "event {} differs: recorded {recorded:?}, replayed {replayed:?}",
+                at + 1
+            ),
+            Mismatch::Board(diff) => {
+                write!(
+                    f,
+                    "the board ends differently (`.` where it agrees):\n{diff}"
+                )
+            }
+        }
+    }
+}
+
+/// Play `report` again from its first board and check the events and the
+/// final board match, returning a line saying what was replayed.
+pub fn reproduce(report: &Report) -> Result<String, Mismatch> {
+    let position = |code: &str, which: &str| {
+        decode_position_code(code)
+            .map_err(|err| Mismatch::BadPosition(format!("{which} position: {err}")))
+    };
+    let start = position(&report.start, "start")?;
+    let end = position(&report.end, "end")?;
+
+    let ctx = egui::Context::default();
+    let mut state = AppState::default();
+    state.settings.gameplay = report.gameplay.clone();
+    let (width, height, mines) = report.size;
+    state.difficulty = Difficulty::from_params(width, height, mines);
+    state.reset();
+    state.awaiting_first_click = false;
+    state.auto_open_due = false;
+    state.board = start;
+    state.visuals.reset(&state.board);
+    state.journal.begin(&state.board);
+    for &action in &report.actions {
+        state.replay(&ctx, action);
+    }
+
+    let replayed: Vec<GameEvent> = state.journal.events().collect();
+    if replayed != report.events {
+        let at = (0..)
+            .find(|&i| replayed.get(i) != report.events.get(i))
+            .unwrap_or(0);
+        return Err(Mismatch::Events {
+            at,
+            recorded: report.events.get(at).copied(),
+            replayed: replayed.get(at).copied(),
+        });
+    }
+    check_end(&state.board, &end)?;
+    Ok(format!(
+        "{} actions replayed on a {width}x{height} board with {mines} mines; \
+         {} events and the final board match",
+        report.actions.len(),
+        replayed.len().min(EVENTS)
+    ))
+}
+
+fn check_end(replayed: &Board, recorded: &Board) -> Result<(), Mismatch> {
+    let diff = match recorded.diff(replayed) {
+        Ok(diff) => diff,
+        Err(err) => return Err(Mismatch::Board(err.to_string())),
+    };
+    if diff.is_empty() && replayed.is_over() == recorded.is_over() {
+        return Ok(());
+    }
+    Err(Mismatch::Board(diff.to_string()))
+}
+
+/// `reproduce BUNDLE.zip`: exit 0 if the bundle replays as recorded, 1
+/// with what differed if not.
+pub fn cli(path: &Path) -> Result<String, CliError> {
+    let report = load(path).map_err(|err| CliError::Input(format!("{}: {err}", path.display())))?;
+    reproduce(&report).map_err(|err| CliError::Failed(format!("{}: {err}", path.display())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::widgets::CellClick;
+    use minesweeper_rs::Marking;
+
+    /// A game played through the state as the screens do: a first click
+    /// that lays the mines, a flag from the menu, a chord and whatever
+    /// else the seed allows.
+    fn played() -> AppState {
+        let ctx = egui::Context::default();
+        let mut state = AppState::default();
+        state.settings.gameplay.question_marks = true;
+        state.reset();
+        state.handle_click(&ctx, CellClick::Flag(0, 0));
+        state.handle_click(&ctx, CellClick::Reveal(4, 4));
+        for _ in 0..20 {
+            let next = minesweeper_rs::solver::analyze(&state.board);
+            // the flag put down first may sit on a cell the solver calls safe
+            let safe = next
+                .safe()
+                .iter()
+                .find(|&&(x, y)| !state.board.cell(x, y).is_flagged());
+            match (safe, next.mines().first()) {
+                (_, Some(&(x, y))) if !state.board.cell(x, y).is_flagged() => {
+                    state.mark(&ctx, x, y, Marking::Flag)
+                }
+                (Some(&(x, y)), _) => state.handle_click(&ctx, CellClick::Reveal(x, y)),
+                _ => break,
+            }
+        }
+        state
+    }
+
+    #[test]
+    fn a_bundle_replays_as_recorded() {
+        let state = played();
+        assert_eq!(state.journal.actions()[0], Action::Reveal(4, 4));
+        let report = read(&bundle(&state).unwrap()).unwrap();
+        assert_eq!(report.start, state.journal.start().unwrap());
+        assert!(reproduce(&report).is_ok(), "{:?}", reproduce(&report));
+    }
+
+    #[test]
+    fn a_different_outcome_is_caught() {
+        let state = played();
+        let mut report = Report::capture(&state).unwrap();
+        report.actions.pop();
+        assert!(reproduce(&report).is_err());
+
+        let mut report = Report::capture(&state).unwrap();
+        report.events.push(GameEvent::Exploded(0, 0));
+        assert!(matches!(reproduce(&report), Err(Mismatch::Events { .. })));
+    }
+
+    #[test]
+    fn nothing_to_bundle_before_the_mines() {
+        let state = AppState::default();
+        assert!(matches!(bundle(&state), Err(ReproError::NotStarted)));
+        assert!(matches!(read(b"not a zip"), Err(ReproError::Zip(_))));
+    }
+}
@@ -0,0 +1,207 @@
+//! Just enough of the zip format for bug report bundles: a handful of
+//! small files, stored without compression, so any unzip tool opens one
+//! and nothing beyond std is needed to write or read it.
+//!
+//! Entries are written with a fixed 1980-01-01 timestamp and UTF-8 names.
+//! Reading goes through the central directory at the end, as unzip does,
+//! refuses compressed or encrypted entries, and checks every CRC.
+
+use std::fmt;
+
+const LOCAL_HEADER: u32 = 0x0403_4b50;
+const CENTRAL_HEADER: u32 = 0x0201_4b50;
+const END_OF_DIRECTORY: u32 = 0x0605_4b50;
+const END_LEN: usize = 22;
+const VERSION: u16 = 20; // 2.0, the first with folders; all this needs
+const UTF8_NAMES: u16 = 1 << 11;
+const ENCRYPTED: u16 = 1;
+const DOS_DATE: u16 = 0x21; // 1980-01-01
+
+/// Why an archive couldn't be read.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ZipError {
+    NotZip,
+    Truncated,
+    Unsupported(String), // the entry, compressed or encrypted
+    Corrupt(String),     // the entry whose CRC doesn't match
+}
+
+impl fmt::Display for ZipError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ZipError::NotZip => f.write_str("not a zip file"),
+            ZipError::Truncated => f.write_str("the zip file is cut short"),
+            ZipError::Unsupported(name) => write!(f, "{name} is compressed or encrypted"),
+            ZipError::Corrupt(name) => write!(f, "{name} is damaged"),
+        }
+    }
+}
+
+impl std::error::Error for ZipError {}
+
+/// An archive holding `files`, by name, in order.
+pub fn write(files: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut directory = Vec::new();
+    for &(name, data) in files {
+        let offset = out.len() as u32;
+        let crc = crc32(data);
+        put32(&mut out, LOCAL_HEADER);
+        entry_fields(&mut out, name, data, crc);
+        put16(&mut out, 0); // extra field
+        out.extend_from_slice(name.as_bytes());
+        out.extend_from_slice(data);
+
+        put32(&mut directory, CENTRAL_HEADER);
+        put16(&mut directory, VERSION); // made by
+        entry_fields(&mut directory, name, data, crc);
+        for _ in 0..4 {
+            put16(&mut directory, 0); // extra, comment, disk, internal attributes
+        }
+        put32(&mut directory, 0); // external attributes
+        put32(&mut directory, offset);
+        directory.extend_from_slice(name.as_bytes());
+    }
+    let start = out.len() as u32;
+    out.extend_from_slice(&directory);
+    put32(&mut out, END_OF_DIRECTORY);
+    put32(&mut out, 0); // this disk and the directory's
+    put16(&mut out, files.len() as u16);
+    put16(&mut out, files.len() as u16);
+    put32(&mut out, directory.len() as u32);
+    put32(&mut out, start);
+    put16(&mut out, 0); // comment
+    out
+}
+
+/// The fields a local header and its directory entry share, from the
+/// version needed through the name's length.
+fn entry_fields(out: &mut Vec<u8>, name: &str, data: &[u8], crc: u32) {
+    put16(out, VERSION);
+    put16(out, UTF8_NAMES);
+    put16(out, 0); // stored
+    put16(out, 0); // time
+    put16(out, DOS_DATE);
+    put32(out, crc);
+    put32(out, data.len() as u32);
+    put32(out, data.len() as u32);
+    put16(out, name.len() as u16);
+}
+
+/// Every file in an archive, by name, in the directory's order.
+pub fn read(bytes: &[u8]) -> Result<Vec<(String, Vec<u8>)>, ZipError> {
+    // the end record is last, unless a comment follows it
+    let end = (0..=bytes.len().checked_sub(END_LEN).ok_or(ZipError::NotZip)?)
+        .rev()
+        .find(|&at| get32(bytes, at) == Some(END_OF_DIRECTORY))
+        .ok_or(ZipError::NotZip)?;
+    let field = |at: usize| get16(bytes, end + at).ok_or(ZipError::Truncated);
+    let entries = field(10)?;
+    let mut at = get32(bytes, end + 16).ok_or(ZipError::Truncated)? as usize;
+
+    let mut files = Vec::new();
+    for _ in 0..entries {
+        let field16 = |offset: usize| get16(bytes, at + offset).ok_or(ZipError::Truncated);
+        let field32 = |offset: usize| get32(bytes, at + offset).ok_or(ZipError::Truncated);
+        if field32(0)? != CENTRAL_HEADER {
+            return Err(ZipError::NotZip);
+        }
+        let (flags, method, crc) = (field16(8)?, field16(10)?, field32(16)?);
+        let size = field32(20)? as usize;
+        let (name_len, extra_len, comment_len) = (
+            field16(28)? as usize,
+            field16(30)? as usize,
+            field16(32)? as usize,
+        );
+        let local = field32(42)? as usize;
+        let name = slice(bytes, at + 46, name_len)?;
+        let name = String::from_utf8_lossy(name).into_owned();
+        at += 46 + name_len + extra_len + comment_len;
+
+        if method != 0 || flags & ENCRYPTED != 0 {
+            return Err(ZipError::Unsupported(name));
+        }
+        if get32(bytes, local) != Some(LOCAL_HEADER) {
+            return Err(ZipError::NotZip);
+        }
+        let skip = |offset| get16(bytes, local + offset).ok_or(ZipError::Truncated);
+        let data_at = local + 30 + skip(26)? as usize + skip(28)? as usize;
+        let data = slice(bytes, data_at, size)?;
+        if crc32(data) != crc {
+            return Err(ZipError::Corrupt(name));
+        }
+        files.push((name, data.to_vec()));
+    }
+    Ok(files)
+}
+
+/// CRC-32 as zip uses it (IEEE, reflected).
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xedb8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+fn put16(out: &mut Vec<u8>, value: u16) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put32(out: &mut Vec<u8>, value: u32) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+fn slice(bytes: &[u8], at: usize, len: usize) -> Result<&[u8], ZipError> {
+    bytes
+        .get(at..at.checked_add(len).ok_or(ZipError::Truncated)?)
+        .ok_or(ZipError::Truncated)
+}
+
+fn get16(bytes: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(bytes.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn get32(bytes: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(bytes.get(at..at + 4)?.try_into().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn files_come_back_as_they_went_in() {
+        let files: [(&str, &[u8]); 3] = [
+            ("journal.json", b"{\"version\": 1}"),
+            ("snapshot.txt", "board:\n⚑1\n".as_bytes()),
+            ("empty", b""),
+        ];
+        let archive = write(&files);
+        let read = read(&archive).unwrap();
+        assert_eq!(read.len(), 3);
+        for ((name, data), (read_name, read_data)) in files.iter().zip(&read) {
+            assert_eq!((*name, *data), (read_name.as_str(), read_data.as_slice()));
+        }
+    }
+
+    #[test]
+    fn crcs_match_the_standard() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+    }
+
+    #[test]
+    fn damage_is_caught() {
+        let mut archive = write(&[("a.txt", b"hello")]);
+        assert_eq!(read(&archive[..archive.len() - 3]), Err(ZipError::NotZip));
+        let at = archive.windows(5).position(|w| w == b"hello").unwrap();
+        archive[at] = b'j';
+        assert_eq!(read(&archive), Err(ZipError::Corrupt("a.txt".into())));
+        assert_eq!(read(b"PK not really"), Err(ZipError::NotZip));
+    }
+}
@@ -0,0 +1,47 @@
+//! The random number generator behind seeded boards.
+//!
+//! Shared seeds must give the same board on every platform and in every
+//! release, so this is pinned here instead of borrowed from `rand`, whose
+//! algorithms may change between versions. It is xoshiro256** with its state
+//! filled from the seed by SplitMix64, exactly as in the reference
+//! implementations; changing anything here changes every seeded board.
+
+pub struct BoardRng {
+    s: [u64; 4],
+}
+
+impl BoardRng {
+    pub fn new(seed: u64) -> Self {
+        let mut sm = seed;
+        let mut next = || {
+            sm = sm.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            let mut z = sm;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            z ^ (z >> 31)
+        };
+        BoardRng {
+            s: [next(), next(), next(), next()],
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let s = &mut self.s;
+        let result = s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = s[1] << 17;
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(45);
+        result
+    }
+
+    /// A number in `0..n` from exactly one `next_u64` call, by taking the
+    /// high half of a 128-bit product. The bias is below `n / 2^64`, which
+    /// is nothing at board sizes, and the call count stays fixed.
+    pub fn below(&mut self, n: usize) -> usize {
+        ((self.next_u64() as u128 * n as u128) >> 64) as usize
+    }
+}
@@ -0,0 +1,64 @@
+//! About the game: its version and the release notes of every version,
+//! newest first.
+
+use super::Screen;
+use crate::app::AppState;
+use crate::changelog::{self, Category, Release};
+use eframe::egui::{self, Color32, RichText};
+
+pub fn show(ctx: &egui::Context, _state: &mut AppState) -> Option<Screen> {
+    let mut next = None;
+
+    egui::CentralPanel::default().show(ctx, |ui| {
+        ui.horizontal(|ui| {
+            ui.heading("About");
+            if ui.button("Back to Menu").clicked() {
+                next = Some(Screen::Menu);
+            }
+        });
+        ui.label(format!("Bosnia Simulator {}", changelog::CURRENT));
+        ui.label("By Rylan Hillman");
+        ui.add_space(8.0);
+        ui.separator();
+
+        let changelog = match changelog::bundled() {
+            Ok(changelog) => changelog,
+            Err(err) => {
+                ui.colored_label(Color32::LIGHT_RED, format!("No release notes: {err}"));
+                return;
+            }
+        };
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for (i, release) in changelog.releases.iter().enumerate() {
+                egui::CollapsingHeader::new(title(release))
+                    .default_open(i == 0)
+                    .show(ui, |ui| release_notes(ui, release));
+            }
+        });
+    });
+
+    next
+}
+
+/// "0.2.0 — 2026-10-15", or just the version when there's no date.
+pub fn title(release: &Release) -> String {
+    match release.date.as_str() {
+        "" => release.version.to_string(),
+        date => format!("{} — {date}", release.version),
+    }
+}
+
+/// A release's entries under a heading per category.
+pub fn release_notes(ui: &mut egui::Ui, release: &Release) {
+    for category in Category::ALL {
+        let mut entries = release.in_category(category).peekable();
+        if entries.peek().is_none() {
+            continue;
+        }
+        ui.label(RichText::new(category.label()).strong());
+        for entry in entries {
+            ui.label(format!("• {}", entry.text));
+        }
+        ui.add_space(4.0);
+    }
+}
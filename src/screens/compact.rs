@@ -0,0 +1,167 @@
+//! Compact mode: the window shrunk to just the board, with small cells,
+//! no header and no title bar, kept above other windows so a slow game
+//! can go on in a corner while working. Hovering shows a strip with the
+//! mine counter, a handle to drag the window by and a button back to the
+//! full layout.
+//!
+//! Its window's place and size are remembered apart from the normal
+//! window's, which comes back on the way out. A platform that won't drop
+//! decorations, float the window or resize it just ignores the request:
+//! the board is still drawn compactly in whatever window there is, and
+//! its size isn't taken as compact mode's own.
+
+use super::Screen;
+use crate::app::AppState;
+use crate::clock;
+use crate::perf::profile_scope;
+use crate::settings::Geometry;
+use crate::widgets::{self, BoardView, CELL_SIZE};
+use eframe::egui;
+
+/// Side of a cell in compact mode, in points.
+const COMPACT_CELL: f32 = 16.0;
+
+/// Largest window compact mode opens at; a bigger board scrolls.
+const MAX_SIZE: egui::Vec2 = egui::vec2(640.0, 480.0);
+
+/// The compact board's view, and the normal window to go back to.
+#[derive(Default)]
+pub struct CompactMode {
+    view: BoardView,
+    normal: Option<Geometry>,
+}
+
+/// Shrink the window to the board and float it over the others.
+pub fn enter(ctx: &egui::Context, state: &mut AppState) -> Screen {
+    let mut view = BoardView::default();
+    view.zoom_by(COMPACT_CELL / CELL_SIZE, egui::Vec2::ZERO);
+    state.compact = CompactMode {
+        view,
+        normal: ctx.input(|i| Geometry::of(i.viewport())),
+    };
+    ctx.send_viewport_cmd(egui::ViewportCommand::Decorations(false));
+    ctx.send_viewport_cmd(egui::ViewportCommand::WindowLevel(
+        egui::WindowLevel::AlwaysOnTop,
+    ));
+    let geometry = state.settings.compact_window.unwrap_or_else(|| {
+        let cells = egui::vec2(state.board.width() as f32, state.board.height() as f32);
+        Geometry {
+            pos: None,
+            size: (cells * COMPACT_CELL).min(MAX_SIZE).into(),
+        }
+    });
+    geometry.apply(ctx);
+    Screen::Compact
+}
+
+/// Put the window back the way it was before compact mode.
+pub fn leave(ctx: &egui::Context, state: &mut AppState) -> Screen {
+    ctx.send_viewport_cmd(egui::ViewportCommand::Decorations(true));
+    ctx.send_viewport_cmd(egui::ViewportCommand::WindowLevel(
+        egui::WindowLevel::Normal,
+    ));
+    if let Some(normal) = state.compact.normal.take() {
+        normal.apply(ctx);
+    }
+    Screen::Game
+}
+
+pub fn show(ctx: &egui::Context, state: &mut AppState) -> Option<Screen> {
+    let mut next = None;
+    remember_geometry(ctx, state);
+    // Escape is a dialog's to close, if one is up
+    let escape = !state.covered
+        && ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::Escape));
+    if escape {
+        next = Some(leave(ctx, state));
+    }
+
+    state.auto_open(ctx);
+    state.poll_generation();
+    state.expire_feedback(ctx);
+    let animate = state.settings.accessibility.animations;
+    let now = ctx.input(|i| i.time);
+    let cascading = state.visuals.tick_cascade(now, animate);
+    if state.visuals.tick_finale(now, animate) || cascading {
+        ctx.request_repaint();
+    }
+
+    egui::CentralPanel::default()
+        .frame(egui::Frame::NONE)
+        .show(ctx, |ui| {
+            if state.covered {
+                ui.disable();
+            }
+            let mut view = std::mem::take(&mut state.compact.view);
+            let response = widgets::board_widget(
+                ui,
+                &state.board,
+                &mut view,
+                Some(&state.visuals),
+                None,
+                None,
+            );
+            state.compact.view = view;
+            match response.click.filter(|_| !state.covered) {
+                Some(click) if response.keyed => state.handle_key(ctx, click),
+                Some(click) => state.handle_click(ctx, click),
+                None => {}
+            }
+            super::game::play_effects(ctx, state, &response);
+        });
+
+    if ctx.input(|i| i.pointer.has_pointer()) && next.is_none() {
+        next = overlay(ctx, state);
+    }
+    state.sync_revision(ctx);
+    let dt = clock::animation_dt(ctx.input(|i| i.stable_dt));
+    profile_scope!(crate::perf::Scope::Particles);
+    state
+        .particles
+        .update_and_paint(ctx, dt, &mut state.visual_rng);
+
+    next
+}
+
+/// The strip shown while the pointer is over the window: a drag handle,
+/// the mine counter and the way back.
+fn overlay(ctx: &egui::Context, state: &mut AppState) -> Option<Screen> {
+    let mut next = None;
+    egui::Area::new(egui::Id::new("compact_overlay"))
+        .anchor(egui::Align2::RIGHT_TOP, egui::vec2(-4.0, 4.0))
+        .order(egui::Order::Foreground)
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    let handle = ui
+                        .add(egui::Label::new("☰").sense(egui::Sense::drag()))
+                        .on_hover_text("Drag to move the window");
+                    if handle.drag_started() {
+                        ctx.send_viewport_cmd(egui::ViewportCommand::StartDrag);
+                    }
+                    widgets::mine_counter(ui, &state.board, false);
+                    if ui
+                        .button("⤢ Expand")
+                        .on_hover_text("Back to the full window (Escape)")
+                        .clicked()
+                    {
+                        next = Some(leave(ctx, state));
+                    }
+                });
+            });
+        });
+    next
+}
+
+/// Keep compact mode's window as the player leaves it, once it has taken
+/// a size of its own: one still the normal window's size means resizing
+/// didn't happen, and that isn't worth remembering.
+fn remember_geometry(ctx: &egui::Context, state: &mut AppState) {
+    let Some(now) = ctx.input(|i| Geometry::of(i.viewport())) else {
+        return;
+    };
+    let normal = state.compact.normal.map(|normal| normal.size);
+    if normal != Some(now.size) && state.settings.compact_window != Some(now) {
+        state.settings.compact_window = Some(now);
+    }
+}
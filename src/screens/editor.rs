@@ -0,0 +1,549 @@
+//! The board editor: puzzles laid out by hand. A click puts a mine down or
+//! takes it up, a right click picks the cell the puzzle is opened from,
+//! and the panel beside the board checks the layout as it changes. Saving
+//! writes a puzzle file (see `minesweeper_rs::puzzle`) under the data dir,
+//! and test play opens the board as a game that goes into no records and
+//! comes back here.
+
+use super::Screen;
+use crate::app::{AppState, Discard};
+use crate::coach::cell_name;
+use crate::widgets::{self, BoardView, CellClick};
+use eframe::egui;
+use minesweeper_rs::difficulty::MAX_CUSTOM_SIDE;
+use minesweeper_rs::formats::{self, tatham};
+use minesweeper_rs::objective::Objective;
+use minesweeper_rs::puzzle::Puzzle;
+use minesweeper_rs::{solver, stats, Board, Symmetry};
+use std::io;
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+/// Edits kept for undo; older ones are dropped.
+const UNDO_DEPTH: usize = 200;
+
+/// The layout being edited.
+#[derive(Clone, Debug, PartialEq)]
+struct Draft {
+    width: usize,
+    height: usize,
+    mines: Vec<bool>, // row by row
+    start: Option<(usize, usize)>,
+}
+
+impl Draft {
+    fn empty(width: usize, height: usize) -> Self {
+        Draft {
+            width,
+            height,
+            mines: vec![false; width * height],
+            start: None,
+        }
+    }
+
+    /// The mines and start of `puzzle`.
+    fn of(puzzle: &Puzzle) -> Self {
+        let board = &puzzle.board;
+        let layout = board.encode_layout();
+        Draft {
+            width: board.width(),
+            height: board.height(),
+            mines: layout
+                .bytes()
+                .filter(|&b| b != b'\n')
+                .map(|b| b == b'*')
+                .collect(),
+            start: puzzle.start,
+        }
+    }
+
+    /// The layout as a fresh board, nothing opened.
+    fn board(&self) -> Board {
+        let mut text = String::with_capacity((self.width + 1) * self.height);
+        for row in self.mines.chunks(self.width) {
+            text.extend(row.iter().map(|&mine| if mine { '*' } else { '.' }));
+            text.push('\n');
+        }
+        Board::decode_layout(&text).expect("an editor layout is never empty or ragged")
+    }
+}
+
+/// What the panel says about the layout, worked out on each change.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Check {
+    pub mines: usize,
+    pub three_bv: usize,
+    pub no_guess: Option<bool>, // from the start, once there is one
+}
+
+impl Check {
+    fn of(draft: &Draft, board: &Board) -> Self {
+        Check {
+            mines: board.mine_count(),
+            three_bv: stats::three_bv(board),
+            no_guess: draft
+                .start
+                .map(|start| solver::solvable_from(board, start, &|| false) == Some(true)),
+        }
+    }
+}
+
+/// Everything the editor keeps between frames.
+pub struct EditorScreen {
+    draft: Draft,
+    undo: Vec<Draft>,
+    open: Board, // the draft fully open, for the board widget to show
+    check: Check,
+    size: (usize, usize), // the next empty grid's width and height
+    title: String,
+    objective: Objective, // kept from a loaded puzzle, as the editor can't set one
+    paste: String,        // a board being pasted in, in any format `formats::read` takes
+    view: BoardView,
+}
+
+impl Default for EditorScreen {
+    fn default() -> Self {
+        let mut editor = EditorScreen {
+            draft: Draft::empty(9, 9),
+            undo: Vec::new(),
+            open: Board::blank(9, 9, 0),
+            check: Check {
+                mines: 0,
+                three_bv: 0,
+                no_guess: None,
+            },
+            size: (9, 9),
+            title: String::new(),
+            objective: Objective::default(),
+            paste: String::new(),
+            view: BoardView::editing(),
+        };
+        editor.refresh();
+        editor
+    }
+}
+
+impl EditorScreen {
+    /// Start over from an empty grid of `size`; this can be undone too.
+    pub fn new_grid(&mut self, (width, height): (usize, usize)) {
+        let (width, height) = (
+            width.clamp(2, MAX_CUSTOM_SIDE),
+            height.clamp(2, MAX_CUSTOM_SIDE),
+        );
+        self.objective = Objective::default();
+        self.edit(|draft| *draft = Draft::empty(width, height));
+    }
+
+    /// Take over `puzzle`'s layout, start and objective, and its title if
+    /// it has one; the layout can be undone too.
+    pub fn load(&mut self, puzzle: Puzzle) {
+        self.objective = puzzle.objective.clone();
+        self.edit(|draft| *draft = Draft::of(&puzzle));
+        if let Some(title) = puzzle.title {
+            self.title = title;
+        }
+    }
+
+    /// Put a mine on (x, y) or take it off. A mine on the start clears the
+    /// start, which has to be safe.
+    pub fn toggle(&mut self, x: usize, y: usize) {
+        self.edit(|draft| {
+            let idx = y * draft.width + x;
+            draft.mines[idx] = !draft.mines[idx];
+            if draft.start == Some((x, y)) {
+                draft.start = None;
+            }
+        });
+    }
+
+    /// Make the safe cell (x, y) the start, or no longer the start if it
+    /// already is. A mine can't be the start.
+    pub fn pick_start(&mut self, x: usize, y: usize) {
+        if self.draft.mines[y * self.draft.width + x] {
+            return;
+        }
+        self.edit(|draft| {
+            draft.start = (draft.start != Some((x, y))).then_some((x, y));
+        });
+    }
+
+    /// Flip the layout, start and all, across the middle: left to right
+    /// for `Symmetry::Horizontal`, top to bottom for `Vertical`.
+    pub fn mirror(&mut self, symmetry: Symmetry) {
+        self.edit(|draft| {
+            let (width, height) = (draft.width, draft.height);
+            let mut mines = vec![false; width * height];
+            for (idx, &mine) in draft.mines.iter().enumerate() {
+                let (x, y) = symmetry.image(idx % width, idx / width, width, height);
+                mines[y * width + x] = mine;
+            }
+            draft.mines = mines;
+            draft.start = draft
+                .start
+                .map(|(x, y)| symmetry.image(x, y, width, height));
+        });
+        let (width, height) = (self.draft.width, self.draft.height);
+        for cell in self.objective.region.iter_mut().flatten() {
+            *cell = symmetry.image(cell.0, cell.1, width, height);
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    /// Take back the last edit.
+    pub fn undo(&mut self) {
+        if let Some(draft) = self.undo.pop() {
+            self.draft = draft;
+            self.refresh();
+        }
+    }
+
+    pub fn check(&self) -> Check {
+        self.check
+    }
+
+    /// Whether the layout can be played: at least one mine and one safe
+    /// cell, and an objective it can meet.
+    pub fn playable(&self) -> bool {
+        (1..self.draft.mines.len()).contains(&self.check.mines) && self.puzzle().check().is_ok()
+    }
+
+    /// The layout as a puzzle, titled if the title isn't blank.
+    pub fn puzzle(&self) -> Puzzle {
+        let title = self.title.trim();
+        Puzzle {
+            title: (!title.is_empty()).then(|| title.to_string()),
+            start: self.draft.start,
+            board: self.draft.board(),
+            objective: self.objective.clone(),
+        }
+    }
+
+    /// Write the puzzle to `puzzles/` under `data_dir`, named after its
+    /// title, replacing an earlier save of the same name. Returns where.
+    pub fn save(&self, data_dir: &Path) -> io::Result<PathBuf> {
+        let dir = data_dir.join("puzzles");
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join(format!("{}.txt", file_stem(&self.title)));
+        std::fs::write(&path, self.puzzle().to_string())?;
+        info!(path = %path.display(), mines = self.check.mines, "saved puzzle");
+        Ok(path)
+    }
+
+    /// Change the draft through `change`, keeping the old one for undo if
+    /// it did change anything.
+    fn edit(&mut self, change: impl FnOnce(&mut Draft)) {
+        let before = self.draft.clone();
+        change(&mut self.draft);
+        if self.draft == before {
+            return;
+        }
+        if self.undo.len() == UNDO_DEPTH {
+            self.undo.remove(0);
+        }
+        self.undo.push(before);
+        self.refresh();
+    }
+
+    fn refresh(&mut self) {
+        let board = self.draft.board();
+        self.check = Check::of(&self.draft, &board);
+        self.open = board;
+        self.open.reveal_all();
+    }
+}
+
+/// A file name for a puzzle titled `title`: its letters and digits in
+/// lower case, with a `-` for each run of anything else.
+pub fn file_stem(title: &str) -> String {
+    let mut stem = String::new();
+    for c in title.trim().chars() {
+        if c.is_alphanumeric() {
+            stem.extend(c.to_lowercase());
+        } else if !stem.is_empty() && !stem.ends_with('-') {
+            stem.push('-');
+        }
+    }
+    let stem = stem.trim_end_matches('-');
+    if stem.is_empty() {
+        "untitled".to_string()
+    } else {
+        stem.to_string()
+    }
+}
+
+pub fn show(ctx: &egui::Context, state: &mut AppState) -> Option<Screen> {
+    let mut next = None;
+    let undo = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Z);
+    if state.editor.can_undo() && ctx.input_mut(|i| i.consume_shortcut(&undo)) {
+        state.editor.undo();
+    }
+
+    egui::CentralPanel::default().show(ctx, |ui| {
+        ui.horizontal(|ui| {
+            ui.heading("Board editor");
+            if ui.button("Back to Menu").clicked() {
+                next = Some(Screen::Menu);
+            }
+        });
+
+        ui.horizontal(|ui| {
+            let editor = &mut state.editor;
+            let side = 2..=MAX_CUSTOM_SIDE;
+            ui.label("Width");
+            ui.add(egui::DragValue::new(&mut editor.size.0).range(side.clone()));
+            ui.label("Height");
+            ui.add(egui::DragValue::new(&mut editor.size.1).range(side));
+            if ui.button("New empty grid").clicked() {
+                editor.new_grid(editor.size);
+            }
+            ui.separator();
+            if ui
+                .add_enabled(editor.can_undo(), egui::Button::new("Undo"))
+                .on_hover_text(ctx.format_shortcut(&undo))
+                .clicked()
+            {
+                editor.undo();
+            }
+            if ui
+                .button("Mirror ↔")
+                .on_hover_text("Flip left to right")
+                .clicked()
+            {
+                editor.mirror(Symmetry::Horizontal);
+            }
+            if ui
+                .button("Mirror ↕")
+                .on_hover_text("Flip top to bottom")
+                .clicked()
+            {
+                editor.mirror(Symmetry::Vertical);
+            }
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Title");
+            ui.text_edit_singleline(&mut state.editor.title);
+            let playable = state.editor.playable();
+            if ui
+                .add_enabled(playable, egui::Button::new("▶ Test play"))
+                .on_hover_text("Play it as a game, not recorded, then come back here")
+                .on_disabled_hover_text(
+                    "It needs at least one mine and one safe cell, and an objective it can meet",
+                )
+                .clicked()
+            {
+                state.discard(Discard::TestPlay);
+                next = Some(Screen::Game);
+            }
+            // saved under the data dir, so without one there is no Save
+            let Some(dir) = state.data_dir.clone() else {
+                return;
+            };
+            if !ui
+                .add_enabled(playable, egui::Button::new("Save"))
+                .clicked()
+            {
+                return;
+            }
+            match state.editor.save(&dir) {
+                Ok(path) => state
+                    .toasts
+                    .notice("Saved the puzzle", path.display().to_string()),
+                Err(err) => {
+                    state
+                        .toasts
+                        .error_at("couldn't save puzzle", &dir.join("puzzles"), &err)
+                }
+            }
+        });
+
+        ui.horizontal(|ui| {
+            let editor = &mut state.editor;
+            ui.add(
+                egui::TextEdit::multiline(&mut editor.paste)
+                    .hint_text("Paste a layout, position code or Tatham game ID")
+                    .desired_rows(1),
+            );
+            let pasted = !editor.paste.trim().is_empty();
+            if ui
+                .add_enabled(pasted, egui::Button::new("Import"))
+                .clicked()
+            {
+                match formats::read(&editor.paste) {
+                    Ok(puzzle) => {
+                        editor.load(puzzle);
+                        editor.paste.clear();
+                    }
+                    Err(err) => state.toasts.error("couldn't import the board", &err),
+                }
+            }
+            if ui
+                .add_enabled(editor.playable(), egui::Button::new("Copy Tatham ID"))
+                .on_hover_text("To play it in Mines from Simon Tatham's puzzle collection")
+                .clicked()
+            {
+                ctx.copy_text(tatham::encode(&editor.puzzle()));
+            }
+        });
+
+        check_panel(ui, &state.editor);
+        ui.label("Click to put a mine down or take it up; right-click a safe cell to start there.");
+        ui.separator();
+
+        let editor = &mut state.editor;
+        let start: Vec<(usize, usize)> = editor.draft.start.into_iter().collect();
+        let highlight = widgets::Highlight::new(&editor.open, &start);
+        let response = widgets::board_widget(
+            ui,
+            &editor.open,
+            &mut editor.view,
+            None,
+            Some(&highlight),
+            None,
+        );
+        match response.click {
+            Some(CellClick::Reveal(x, y)) => editor.toggle(x, y),
+            Some(CellClick::Flag(x, y)) => editor.pick_start(x, y),
+            None => {}
+        }
+    });
+
+    next
+}
+
+/// Mines, 3BV and whether the board needs a guess from its start.
+fn check_panel(ui: &mut egui::Ui, editor: &EditorScreen) {
+    let check = editor.check();
+    let (width, height) = (editor.draft.width, editor.draft.height);
+    ui.horizontal(|ui| {
+        ui.label(format!("{width}x{height}, {} mines", check.mines));
+        ui.separator();
+        ui.label(format!("3BV {}", check.three_bv));
+        ui.separator();
+        match (editor.draft.start, check.no_guess) {
+            (Some(start), Some(true)) => {
+                ui.label(format!("No guessing needed from {}", cell_name(start)))
+            }
+            (Some(start), _) => ui.colored_label(
+                egui::Color32::LIGHT_YELLOW,
+                format!("Needs a guess from {}", cell_name(start)),
+            ),
+            (None, _) => ui.label("No start picked, so it may need a guess"),
+        };
+        if !editor.objective.is_classic() {
+            ui.separator();
+            ui.label(format!("Objective: {}", editor.objective));
+        }
+        if !(1..editor.draft.mines.len()).contains(&check.mines) {
+            ui.separator();
+            ui.colored_label(
+                egui::Color32::LIGHT_RED,
+                "Needs at least one mine and one safe cell",
+            );
+        } else if let Err(err) = editor.puzzle().check() {
+            ui.separator();
+            ui.colored_label(egui::Color32::LIGHT_RED, err.to_string());
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edits_undo_and_mirror() {
+        let mut editor = EditorScreen::default();
+        editor.new_grid((4, 2));
+        editor.toggle(0, 0);
+        editor.toggle(1, 1);
+        editor.pick_start(3, 1);
+        editor.pick_start(0, 0); // a mine can't be the start
+        assert_eq!(editor.puzzle().board.encode_layout(), "*...\n.*..\n");
+        assert_eq!(editor.draft.start, Some((3, 1)));
+
+        editor.mirror(Symmetry::Horizontal);
+        assert_eq!(editor.puzzle().board.encode_layout(), "...*\n..*.\n");
+        assert_eq!(editor.draft.start, Some((0, 1)));
+        editor.mirror(Symmetry::Vertical);
+        assert_eq!(editor.puzzle().board.encode_layout(), "..*.\n...*\n");
+        editor.toggle(0, 0);
+        editor.toggle(0, 0);
+
+        for _ in 0..3 {
+            editor.undo();
+        }
+        assert_eq!(editor.puzzle().board.encode_layout(), "...*\n..*.\n");
+        // the open board the widget shows keeps up
+        assert_eq!(editor.open.cell(3, 0).is_mine(), Some(true));
+        assert!(editor.can_undo());
+    }
+
+    #[test]
+    fn a_pasted_board_loads_and_goes_back_out_the_same() {
+        let mut editor = EditorScreen {
+            title: "Mine".to_string(),
+            ..Default::default()
+        };
+        editor.load(formats::read("3x2:2,1,m34").unwrap());
+        assert_eq!(editor.puzzle().board.encode_layout(), "*..\n...\n");
+        assert_eq!(editor.draft.start, Some((2, 1)));
+        assert_eq!(editor.title, "Mine", "an ID has no title to take");
+        assert!(editor.playable());
+        assert_eq!(tatham::encode(&editor.puzzle()), "3x2:2,1,m34");
+
+        editor.undo();
+        assert_eq!(editor.draft, Draft::empty(9, 9));
+    }
+
+    #[test]
+    fn the_check_follows_the_layout() {
+        let mut editor = EditorScreen::default();
+        editor.new_grid((5, 2));
+        assert!(!editor.playable());
+        editor.toggle(0, 0);
+        editor.toggle(0, 1);
+        editor.pick_start(4, 0);
+        assert!(editor.playable());
+        assert_eq!(
+            editor.check(),
+            Check {
+                mines: 2,
+                three_bv: 1,
+                no_guess: Some(true)
+            }
+        );
+        // a wall of mines down the middle cuts the start off from the left
+        editor.toggle(0, 0);
+        editor.toggle(2, 0);
+        editor.toggle(2, 1);
+        editor.toggle(0, 0);
+        assert_eq!(editor.check().no_guess, Some(false));
+        editor.toggle(4, 0);
+        assert_eq!(editor.draft.start, None);
+        assert_eq!(editor.check().no_guess, None);
+    }
+
+    #[test]
+    fn puzzles_are_saved_under_their_title() {
+        assert_eq!(file_stem("  Corner trap #2 "), "corner-trap-2");
+        assert_eq!(file_stem("!!"), "untitled");
+
+        let dir = std::env::temp_dir().join(format!("editor-test-{}", std::process::id()));
+        let mut editor = EditorScreen::default();
+        editor.new_grid((3, 2));
+        editor.toggle(0, 0);
+        editor.pick_start(2, 0);
+        editor.title = "Pair".to_string();
+        let path = editor.save(&dir).unwrap();
+        assert_eq!(path, dir.join("puzzles").join("pair.txt"));
+        let saved: Puzzle = std::fs::read_to_string(&path).unwrap().parse().unwrap();
+        assert_eq!(saved.title.as_deref(), Some("Pair"));
+        assert_eq!(saved.start, Some((2, 0)));
+        assert_eq!(saved.board.encode_layout(), "*..\n...\n");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
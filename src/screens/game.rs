@@ -0,0 +1,1055 @@
+//! The game itself: difficulty and control rows, status line and the board.
+
+use super::Screen;
+use crate::app::{AppState, Discard, FatalOdds, GameEvent, GameMode};
+use crate::clock;
+use crate::coach::{self, Coach, Suggestion};
+use crate::particles::Emitter;
+use crate::perf::profile_scope;
+use crate::settings::WinStyle;
+use crate::widgets;
+use eframe::egui;
+use minesweeper_rs::annotations::{Note, PALETTE};
+use minesweeper_rs::difficulty::{Density, Difficulty, MAX_CUSTOM_SIDE, MAX_LARGE_SIDE};
+use minesweeper_rs::format;
+use minesweeper_rs::hints::PENALTY_SECS;
+use minesweeper_rs::objective::Verdict;
+use minesweeper_rs::render::{BoardRenderer, Compact};
+use minesweeper_rs::{solver, Ignored, Marking, Symmetry};
+use std::sync::atomic::Ordering;
+
+/// The menu a long press opens on a cell.
+pub struct CellMenu {
+    pub cell: (usize, usize),
+    pub pos: egui::Pos2, // where the press was; the menu hangs from it
+    pub armed: bool,     // the opening press is over, so clicks elsewhere close it
+}
+
+pub fn show(ctx: &egui::Context, state: &mut AppState) -> Option<Screen> {
+    let mut next = None;
+    let mut callout = None;
+    state.particles.enabled = state.settings.accessibility.animations;
+
+    // a dialog over the board has the keys to itself
+    if state.annotating && !state.covered {
+        note_keys(ctx, state);
+    } else if !state.covered {
+        quick_entry_keys(ctx, state);
+        play_keys(ctx, state);
+    }
+
+    egui::CentralPanel::default().show(ctx, |ui| {
+        ui.horizontal(|ui| {
+            ui.heading("Bosnia Simulator");
+            if state.mode == GameMode::Zen {
+                ui.label("🍃")
+                    .on_hover_text("Zen mode: untimed, forgiving and not recorded");
+            }
+            if state.mode == GameMode::TestPlay {
+                ui.label("🛠 Test play")
+                    .on_hover_text("A board from the editor, tried out and not recorded");
+            }
+            if state.mode == GameMode::Rematch {
+                ui.label("🔁 Rematch")
+                    .on_hover_text("A board played before: recorded, but never a best time");
+            }
+            super::profile_switcher(ui, state);
+        });
+
+        // Difficulty row (can change mid-game)
+        ui.horizontal(|ui| {
+            ui.label("Difficulty:");
+
+            // the selected one is left alone, so a stray click can't restart
+            for diff in Difficulty::PRESETS {
+                let selected = state.difficulty == diff;
+                if ui.selectable_label(selected, diff.label()).clicked() && !selected {
+                    state.discard(Discard::Difficulty(diff));
+                }
+            }
+
+            ui.separator();
+            for density in Density::ALL {
+                let selected = state.difficulty.density() == Some(density);
+                if ui.selectable_label(selected, density.label()).clicked() && !selected {
+                    let difficulty = state.difficulty.with_density(density);
+                    state.discard(Discard::Difficulty(difficulty));
+                }
+            }
+            if ui.button("Surprise me").clicked() {
+                state.surprise = Some(Difficulty::surprise(&mut rand::thread_rng()));
+            }
+            ui.separator();
+
+            let custom = matches!(state.difficulty, Difficulty::Custom { .. });
+            if ui
+                .selectable_label(custom || state.editing_custom, "Custom")
+                .clicked()
+            {
+                state.editing_custom = !state.editing_custom;
+            }
+
+            if state.mode == GameMode::TestPlay {
+                if ui.button("Back to Editor").clicked() {
+                    next = Some(Screen::Editor);
+                }
+            } else if ui.button("Back to Menu").clicked() {
+                next = Some(Screen::Menu);
+            }
+
+            if ui.button("Settings").clicked() {
+                next = Some(Screen::Settings);
+            }
+
+            if ui
+                .button("⧉ Compact")
+                .on_hover_text("Shrink to just the board, kept above other windows")
+                .clicked()
+            {
+                next = Some(super::compact::enter(ctx, state));
+            }
+        });
+
+        if state.editing_custom {
+            custom_row(ui, state);
+        }
+        super::surprise_row(ui, state);
+        if matches!(state.difficulty, Difficulty::Scaled { .. }) {
+            ui.label(state.difficulty.spec().to_string());
+        }
+
+        // Controls row
+        ui.horizontal(|ui| {
+            if ui.button("New Game").clicked() {
+                // a test play starts over on the same board
+                if state.mode == GameMode::TestPlay {
+                    state.discard(Discard::TestPlay);
+                } else {
+                    state.discard(Discard::NewGame);
+                }
+            }
+
+            let available = state.no_guess_available();
+            let no_guess =
+                egui::Checkbox::new(&mut state.settings.gameplay.no_guess, "No guessing");
+            if ui
+                .add_enabled(available, no_guess)
+                .on_disabled_hover_text("Board too large for no-guess generation")
+                .changed()
+            {
+                state.discard(Discard::NewGame);
+            }
+
+            let shake = state
+                .feedback
+                .as_ref()
+                .is_some_and(|f| f.reason == Ignored::NoFlagsLeft);
+            widgets::mine_counter(ui, &state.board, shake);
+            if let Some(outlook) = state.endgame_outlook(ctx) {
+                widgets::live(&ui.label(format!("🎲 {outlook}")).on_hover_text(format!(
+                    "Best play over every way the mines could lie in the {} cells in question",
+                    outlook.cells
+                )));
+            }
+            objective_badge(ui, state);
+            if !state.quick_entry.is_empty() {
+                let echo = egui::RichText::new(format!("⌨ {}", state.quick_entry.echo()));
+                let echo = match state.quick_entry.target(&state.board) {
+                    Some(_) => echo.monospace(),
+                    None => echo.monospace().color(egui::Color32::LIGHT_RED),
+                };
+                widgets::live(
+                    &ui.label(echo)
+                        .on_hover_text("Enter plays it, Escape clears it"),
+                );
+            }
+
+            if let Some(generation) = &state.generation {
+                let attempt = generation.attempts.load(Ordering::Relaxed);
+                ui.spinner();
+                ui.label(format!("Generating… attempt {attempt}"));
+            }
+
+            if state.can_reveal_remaining() {
+                // loud on purpose: one wrong flag and this loses the game
+                let button = egui::Button::new("Open all remaining")
+                    .fill(egui::Color32::from_rgb(190, 110, 0));
+                if ui
+                    .add(button)
+                    .on_hover_text(format!(
+                        "Reveal every unflagged cell ({}). A wrong flag loses the game.",
+                        ctx.format_shortcut(&REVEAL_REMAINING)
+                    ))
+                    .clicked()
+                {
+                    state.reveal_remaining();
+                }
+            }
+
+            if !state.undo.is_empty()
+                && ui
+                    .button("Undo")
+                    .on_hover_text(ctx.format_shortcut(&UNDO))
+                    .clicked()
+            {
+                state.undo();
+            }
+            let hintable = !state.awaiting_first_click && !state.is_settled();
+            if hintable
+                && ui
+                    .button("💡 Hint")
+                    .on_hover_text(format!(
+                        "Press again for more: where to look, which number decides it, \
+                         then the move itself. Costs {}, {} and then {} seconds (H)",
+                        PENALTY_SECS[0], PENALTY_SECS[1], PENALTY_SECS[2]
+                    ))
+                    .clicked()
+            {
+                state.hint(ctx);
+            }
+
+            if state.board.is_won() && !state.mode.celebrates() {
+                widgets::live(&ui.label("Board cleared."));
+            } else {
+                widgets::game_status(ui, &state.board);
+                fatal_odds(ui, state);
+            }
+            if state.board.flags() > 0
+                && !state.board.is_over()
+                && ui
+                    .button("Clear flags")
+                    .on_hover_text("Take every flag off the board")
+                    .clicked()
+            {
+                state.confirm_clear_flags = true;
+            }
+            if !state.awaiting_first_click
+                && ui
+                    .button("Copy position")
+                    .on_hover_text("The board as text with A1 coordinates, for sharing a puzzle")
+                    .clicked()
+            {
+                let labelled = Compact {
+                    coordinates: true,
+                    ..Compact::default()
+                };
+                let markers = state.settings.appearance.markers();
+                ctx.copy_text(labelled.render_with(&state.board, &markers));
+            }
+            if !state.awaiting_first_click {
+                ui.checkbox(&mut state.annotating, "✏ Notes").on_hover_text(
+                    "Study the position: the clock stops and clicks scribble notes on cells",
+                );
+            }
+            if state.board.is_over() && state.used_reveal_remaining {
+                ui.label("(finished with reveal-remaining)");
+            }
+            if state.board.is_over() && !state.reveal_times.is_empty() {
+                let mut on = state.heatmap.is_some();
+                if ui
+                    .checkbox(&mut on, "Heatmap")
+                    .on_hover_text("Tint cells by when they were revealed: blue early, red late")
+                    .changed()
+                {
+                    state.set_heatmap(on);
+                }
+            }
+        });
+
+        let step = coach_row(ui, state);
+
+        if state.board.is_over() && state.input_stats.left_clicks > 0 {
+            summary(ui, state);
+        }
+
+        // Status bar for ignored actions
+        if let Some(feedback) = &state.feedback {
+            let response = ui.colored_label(egui::Color32::LIGHT_RED, feedback.reason.message());
+            widgets::live(&response);
+        }
+        if let Some((revision, note)) = &state.hint_note {
+            if *revision == state.board.revision() {
+                widgets::live(&ui.label(format!("💡 {note}")));
+            }
+        }
+        if state.board.flags_misplaced() {
+            let response = ui.colored_label(
+                egui::Color32::LIGHT_YELLOW,
+                "Every hidden cell is flagged but the game isn't won, \
+                 so at least one flag is on a safe cell.",
+            );
+            widgets::live(&response);
+        }
+
+        if state.board.is_over() && !state.board.is_won() && ui.button("Reveal all").clicked() {
+            state.board.reveal_all();
+        }
+
+        ui.separator();
+
+        // Board grid
+        state.auto_open(ctx);
+        state.poll_generation();
+        state.expire_feedback(ctx);
+        if !state.covered {
+            state.tick_coach(ctx, step);
+        }
+        // any click or key shows the rest of the finale at once; a cascade
+        // plays on, as the moves made during it queue up behind
+        let pressed = ctx.input(|i| {
+            i.events.iter().any(|e| {
+                matches!(
+                    e,
+                    egui::Event::Key { pressed: true, .. }
+                        | egui::Event::PointerButton { pressed: true, .. }
+                )
+            })
+        });
+        if pressed {
+            state.visuals.skip_finale();
+        }
+        let animate = state.settings.accessibility.animations;
+        let now = ctx.input(|i| i.time);
+        let cascading = state.visuals.tick_cascade(now, animate);
+        if state.visuals.tick_finale(now, animate) || cascading {
+            ctx.request_repaint();
+        }
+
+        if state.covered {
+            ui.disable();
+        }
+        // taken out while the board is drawn, as the analysis borrows `state`
+        let notes = std::mem::take(&mut state.notes);
+        let mut view = std::mem::take(&mut state.board_view);
+        let kill_screen = if state.show_analysis {
+            profile_scope!(crate::perf::Scope::Overlay);
+            state.kill_screen()
+        } else {
+            None
+        };
+        let response = match kill_screen {
+            // the position before the final move, so clicks land nowhere
+            Some(kill_screen) => widgets::board_widget(
+                ui,
+                &kill_screen.position,
+                &mut view,
+                None,
+                Some(kill_screen),
+                Some(&notes),
+            ),
+            None => match state.coach.as_ref().and_then(|c| c.plan.as_ref()) {
+                // the bot's next move, with the numbers behind it
+                Some(plan) => {
+                    let mut cells = plan.numbers.clone();
+                    cells.push(plan.target);
+                    let highlight = widgets::Highlight::new(&state.board, &cells);
+                    let response = widgets::board_widget(
+                        ui,
+                        &state.board,
+                        &mut view,
+                        Some(&state.visuals),
+                        Some(&highlight),
+                        Some(&notes),
+                    );
+                    let (x, y) = plan.target;
+                    callout = Some((response.cell_rect(x, y), plan.why.clone()));
+                    response
+                }
+                None => match &state.constraints {
+                    // a cell and the constraints around it, from its menu
+                    Some(cells) => {
+                        let highlight = widgets::Highlight::new(&state.board, cells);
+                        widgets::board_widget(
+                            ui,
+                            &state.board,
+                            &mut view,
+                            Some(&state.visuals),
+                            Some(&highlight),
+                            Some(&notes),
+                        )
+                    }
+                    // the cells a quick entry can mean so far
+                    None if !state.quick_entry.is_empty() => {
+                        let cells = state.quick_entry.cells(&state.board);
+                        let highlight = widgets::Highlight::new(&state.board, &cells);
+                        widgets::board_widget(
+                            ui,
+                            &state.board,
+                            &mut view,
+                            Some(&state.visuals),
+                            Some(&highlight),
+                            Some(&notes),
+                        )
+                    }
+                    // the heatmap, or else a puzzle's target region
+                    None => {
+                        let region =
+                            widgets::Region::new(&state.board, state.objective.objective());
+                        let overlay = match &state.heatmap {
+                            Some(heatmap) => Some(heatmap as &dyn widgets::Overlay),
+                            None => region.as_ref().map(|r| r as &dyn widgets::Overlay),
+                        };
+                        widgets::board_widget(
+                            ui,
+                            &state.board,
+                            &mut view,
+                            Some(&state.visuals),
+                            overlay,
+                            Some(&notes),
+                        )
+                    }
+                },
+            },
+        };
+        state.notes = notes;
+        state.board_view = view;
+        match response.click.filter(|_| !state.covered) {
+            Some(click) if state.annotating => {
+                let (x, y) = click.cell();
+                state.annotate(x, y);
+            }
+            Some(click) if response.keyed => {
+                state.constraints = None;
+                state.handle_key(ctx, click);
+            }
+            Some(click) => {
+                state.constraints = None;
+                // a click that closes the menu does nothing else
+                if state.cell_menu.take().is_none() {
+                    state.handle_click(ctx, click);
+                }
+            }
+            None => {}
+        }
+        if let Some((x, y)) = response
+            .menu
+            .filter(|_| !state.annotating && !state.covered)
+        {
+            let pos = ctx.input(|i| i.pointer.interact_pos());
+            state.cell_menu = Some(CellMenu {
+                cell: (x, y),
+                pos: pos.unwrap_or(response.cell_rect(x, y).center()),
+                armed: false,
+            });
+        }
+        play_effects(ctx, state, &response);
+    });
+
+    if state.cell_menu.is_some() {
+        cell_menu(ctx, state);
+    }
+
+    if state.annotating {
+        note_palette(ctx, state);
+    }
+
+    if state.confirm_clear_flags {
+        clear_flags_prompt(ctx, state);
+    }
+
+    if let Some((target, why)) = callout {
+        widgets::callout(ctx, egui::Id::new("coach_callout"), target, &why);
+    }
+
+    state.sync_revision(ctx);
+
+    // Particles go on top of everything
+    let dt = clock::animation_dt(ctx.input(|i| i.stable_dt));
+    profile_scope!(crate::perf::Scope::Particles);
+    state
+        .particles
+        .update_and_paint(ctx, dt, &mut state.visual_rng);
+
+    next
+}
+
+/// Opens every unflagged cell left; the button's tooltip names it too.
+const REVEAL_REMAINING: egui::KeyboardShortcut =
+    egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Enter);
+
+/// Takes back the last move, or brings back cleared notes while taking them.
+const UNDO: egui::KeyboardShortcut =
+    egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Z);
+
+/// Keys while playing: Cmd+Enter opens everything left, Cmd+Z undoes, H
+/// asks for a hint and Cmd+Shift+B saves a bug report bundle. None of
+/// them while a text field is being typed in.
+fn play_keys(ctx: &egui::Context, state: &mut AppState) {
+    if ctx.wants_keyboard_input() {
+        return;
+    }
+    if state.can_reveal_remaining() && ctx.input_mut(|i| i.consume_shortcut(&REVEAL_REMAINING)) {
+        state.reveal_remaining();
+    }
+    if !state.undo.is_empty() && ctx.input_mut(|i| i.consume_shortcut(&UNDO)) {
+        state.undo();
+    }
+    let report = egui::KeyboardShortcut::new(
+        egui::Modifiers::COMMAND | egui::Modifiers::SHIFT,
+        egui::Key::B,
+    );
+    if state.journal.start().is_some() && ctx.input_mut(|i| i.consume_shortcut(&report)) {
+        state.save_bug_report();
+    }
+    if ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::H)) {
+        state.hint(ctx);
+    }
+}
+
+/// Quick entry, when it is switched on: letters and digits build a cell
+/// name, Backspace takes one back, Enter plays it and Escape clears it.
+/// The keys it takes are kept from the shortcuts, except that F on its own
+/// still flags at the cursor of a focused board.
+fn quick_entry_keys(ctx: &egui::Context, state: &mut AppState) {
+    if !state.settings.controls.quick_entry || ctx.wants_keyboard_input() {
+        return;
+    }
+    let cursor = widgets::board_focused(ctx);
+    let entry = &mut state.quick_entry;
+    let mut commit = false;
+    ctx.input_mut(|i| {
+        let mut taken = Vec::new();
+        for event in &i.events {
+            let egui::Event::Text(text) = event else {
+                continue;
+            };
+            for c in text.chars() {
+                let cursor_flag = cursor && entry.is_empty() && c.eq_ignore_ascii_case(&'f');
+                if !cursor_flag && entry.push(c) {
+                    taken.extend(egui::Key::from_name(&c.to_ascii_uppercase().to_string()));
+                }
+            }
+        }
+        i.events.retain(|event| match event {
+            egui::Event::Key { key, modifiers, .. } => {
+                let typing = modifiers.is_none() || *modifiers == egui::Modifiers::SHIFT;
+                !(typing && taken.contains(key))
+            }
+            _ => true,
+        });
+        if !entry.is_empty() {
+            if i.consume_key(egui::Modifiers::NONE, egui::Key::Backspace) {
+                entry.pop();
+            }
+            if i.consume_key(egui::Modifiers::NONE, egui::Key::Escape) {
+                entry.clear();
+            }
+            commit = i.consume_key(egui::Modifiers::NONE, egui::Key::Enter);
+        }
+    });
+    // a name that isn't on the board stays up, in red, to be corrected
+    if let Some(click) = commit
+        .then(|| state.quick_entry.commit(&state.board))
+        .flatten()
+    {
+        state.quick_entry.clear();
+        state.constraints = None;
+        state.handle_key(ctx, click);
+    }
+}
+
+const NOTE_KEYS: [egui::Key; 10] = [
+    egui::Key::Num0,
+    egui::Key::Num1,
+    egui::Key::Num2,
+    egui::Key::Num3,
+    egui::Key::Num4,
+    egui::Key::Num5,
+    egui::Key::Num6,
+    egui::Key::Num7,
+    egui::Key::Num8,
+    egui::Key::Num9,
+];
+
+/// Keys while taking notes: 1 to 9 pick from the palette, 0 the eraser,
+/// Cmd+Z brings back cleared notes and Escape stops.
+fn note_keys(ctx: &egui::Context, state: &mut AppState) {
+    if ctx.wants_keyboard_input() {
+        return;
+    }
+    for (i, key) in NOTE_KEYS.into_iter().enumerate() {
+        if ctx.input_mut(|input| input.consume_key(egui::Modifiers::NONE, key)) {
+            state.note_pick = i.checked_sub(1).map(|i| PALETTE[i]);
+        }
+    }
+    if state.notes.can_undo_clear() && ctx.input_mut(|i| i.consume_shortcut(&UNDO)) {
+        state.notes.undo_clear();
+    }
+    if ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::Escape)) {
+        state.annotating = false;
+    }
+}
+
+/// The note a palette button shows.
+fn note_label(note: Option<Note>) -> String {
+    match note {
+        Some(Note::Letter(c)) => c.to_string(),
+        Some(Note::Dot(hue)) => format!("{hue:?} dot"),
+        Some(Note::Corner(hue)) => format!("{hue:?} corner"),
+        None => "Eraser".to_string(),
+    }
+}
+
+/// The floating palette shown while taking notes.
+fn note_palette(ctx: &egui::Context, state: &mut AppState) {
+    egui::Window::new("Notes")
+        .resizable(false)
+        .collapsible(false)
+        .anchor(egui::Align2::RIGHT_TOP, egui::vec2(-12.0, 60.0))
+        .show(ctx, |ui| {
+            ui.weak("Only you see these; they never go in results.");
+            let picks = PALETTE.into_iter().map(Some).chain([None]);
+            for (note, key) in picks.zip((1..=9).chain([0])) {
+                let picked = state.note_pick == note;
+                let label = format!("{key}  {}", note_label(note));
+                if ui.selectable_label(picked, label).clicked() {
+                    state.note_pick = note;
+                }
+            }
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(!state.notes.is_empty(), egui::Button::new("Clear all"))
+                    .clicked()
+                {
+                    state.notes.clear();
+                }
+                if ui
+                    .add_enabled(
+                        state.notes.can_undo_clear(),
+                        egui::Button::new("Undo clear"),
+                    )
+                    .clicked()
+                {
+                    state.notes.undo_clear();
+                }
+            });
+            if ui.button("Done").clicked() {
+                state.annotating = false;
+            }
+        });
+}
+
+/// What can be done to the long-pressed cell: mark it, chord it, see the
+/// numbers it's tied to, or copy its name. Closes on a choice, Escape or a
+/// click elsewhere.
+fn cell_menu(ctx: &egui::Context, state: &mut AppState) {
+    let Some(menu) = &mut state.cell_menu else {
+        return;
+    };
+    let ((x, y), pos) = (menu.cell, menu.pos);
+    menu.armed |= !ctx.input(|i| i.pointer.any_down() || i.pointer.any_released());
+    let armed = menu.armed;
+    let cell = state.board.cell(x, y);
+    let over = state.board.is_over();
+
+    let mut close = ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::Escape));
+    let area = egui::Area::new(egui::Id::new("cell_menu"))
+        .order(egui::Order::Foreground)
+        .fixed_pos(pos)
+        .show(ctx, |ui| {
+            egui::Frame::menu(ui.style()).show(ui, |ui| {
+                ui.set_min_width(140.0);
+                ui.weak(coach::cell_name((x, y)));
+                if !cell.is_revealed() && !over {
+                    let (label, marking) = if cell.is_flagged() {
+                        ("Unflag", Marking::None)
+                    } else {
+                        ("Flag", Marking::Flag)
+                    };
+                    if ui.button(label).clicked() {
+                        state.mark(ctx, x, y, marking);
+                        close = true;
+                    }
+                    let question = cell.marking() == Marking::Question;
+                    let label = if question {
+                        "Clear question mark"
+                    } else {
+                        "Question mark"
+                    };
+                    if ui.button(label).clicked() {
+                        let marking = if question {
+                            Marking::None
+                        } else {
+                            Marking::Question
+                        };
+                        state.mark(ctx, x, y, marking);
+                        close = true;
+                    }
+                }
+                if !state.board.chord_targets(x, y).is_empty()
+                    && ui
+                        .button("Chord")
+                        .on_hover_text("Open every unflagged neighbour of this number")
+                        .clicked()
+                {
+                    state.chord(x, y);
+                    close = true;
+                }
+                if !state.awaiting_first_click
+                    && ui
+                        .button("Highlight constraints")
+                        .on_hover_text("Show the numbers and hidden cells this cell is tied to")
+                        .clicked()
+                {
+                    let mut cells = solver::constraint_links(&state.board, x, y);
+                    cells.push((x, y));
+                    state.constraints = Some(cells);
+                    close = true;
+                }
+                if ui.button("Copy coordinate").clicked() {
+                    ctx.copy_text(coach::cell_name((x, y)));
+                    close = true;
+                }
+            });
+        });
+    if close || (armed && area.response.clicked_elsewhere()) {
+        state.cell_menu = None;
+    }
+}
+
+/// "Remove flags" or "Cancel".
+fn clear_flags_prompt(ctx: &egui::Context, state: &mut AppState) {
+    let mut clear = false;
+    let mut cancel = false;
+    let modal = egui::Modal::new(egui::Id::new("clear_flags_prompt")).show(ctx, |ui| {
+        let flags = state.board.flags();
+        let s = if flags == 1 { "" } else { "s" };
+        ui.heading(format!("Remove all {flags} flag{s}?"));
+        ui.horizontal(|ui| {
+            clear = ui.button("Remove flags").clicked();
+            cancel = ui.button("Cancel").clicked();
+        });
+    });
+    if clear {
+        state.clear_flags();
+    }
+    if clear || cancel || modal.should_close() {
+        state.confirm_clear_flags = false;
+    }
+}
+
+/// Start a particle effect for each game event since the last frame.
+pub fn play_effects(ctx: &egui::Context, state: &mut AppState, board: &widgets::BoardResponse) {
+    for event in std::mem::take(&mut state.events) {
+        let (emitter, area) = match event {
+            GameEvent::Opened { x, y, .. } => (Emitter::dust(), board.cell_rect(x, y)),
+            GameEvent::FinalFlag(x, y) => (Emitter::sparks(), board.cell_rect(x, y)),
+            GameEvent::Exploded(x, y) => (Emitter::debris(), board.cell_rect(x, y)),
+            GameEvent::Won(..) => {
+                let animations = state.settings.accessibility.animations;
+                let style = state.settings.appearance.win_effect;
+                let style = if animations { style } else { WinStyle::None };
+                style.effect().trigger(&state.board, &mut state.visuals.win);
+                if !state.visuals.win.take_confetti() {
+                    continue;
+                }
+                // confetti thrown in from both top corners of the window
+                let window = ctx.content_rect();
+                let corner = |x| {
+                    egui::Rect::from_center_size(
+                        egui::pos2(x, window.top()),
+                        egui::vec2(24.0, 24.0),
+                    )
+                };
+                let left = corner(window.left());
+                let rng = &mut state.visual_rng;
+                state.particles.emit(Emitter::confetti(), left, rng);
+                (Emitter::confetti().mirrored(), corner(window.right()))
+            }
+        };
+        state.particles.emit(emitter, area, &mut state.visual_rng);
+    }
+}
+
+/// "Watch & learn" controls. Returns whether Step was pressed.
+fn coach_row(ui: &mut egui::Ui, state: &mut AppState) -> bool {
+    let mut step = false;
+    if state.board.is_over() {
+        return step;
+    }
+    ui.horizontal(|ui| match &mut state.coach {
+        None => {
+            if ui
+                .button("Watch & learn")
+                .on_hover_text("Let a bot play on from here, explaining each move first")
+                .clicked()
+            {
+                state.coach = Some(Coach::default());
+            }
+        }
+        Some(coach) => {
+            let pause = if coach.paused { "Resume" } else { "Pause" };
+            if ui.button(pause).clicked() {
+                coach.paused = !coach.paused;
+            }
+            step = ui.button("Step").clicked();
+            if ui
+                .button("Take over")
+                .on_hover_text("Or just click the board")
+                .clicked()
+            {
+                state.coach = None;
+            }
+        }
+    });
+    step
+}
+
+/// The advisor's suggestion, if any, with a button to take it up.
+fn suggestion_banner(ui: &mut egui::Ui, state: &mut AppState) {
+    let Some(suggestion) = state.advisor.shown() else {
+        return;
+    };
+    egui::Frame::group(ui.style()).show(ui, |ui| {
+        ui.horizontal(|ui| {
+            match suggestion {
+                Suggestion::StepUp(harder) => {
+                    ui.label("You've won most of your recent games here. Ready for more?");
+                    if ui.button(format!("Start {}", harder.label())).clicked() {
+                        state.discard(Discard::Difficulty(harder));
+                    }
+                }
+                Suggestion::Easier { down, no_guess } => {
+                    ui.label("A tough run. Want to ease off for a while?");
+                    if let Some(easier) = down {
+                        if ui.button(format!("Start {}", easier.label())).clicked() {
+                            state.discard(Discard::Difficulty(easier));
+                        }
+                    }
+                    if no_guess && ui.button("Turn on no guessing").clicked() {
+                        state.settings.gameplay.no_guess = true;
+                        state.discard(Discard::NewGame);
+                    }
+                }
+            }
+            if ui.small_button("✕").on_hover_text("Dismiss").clicked() {
+                state.advisor.dismiss();
+            }
+        });
+    });
+}
+
+/// A puzzle's objective beside the mine counter, with the clicks left in
+/// its budget, and then whether it was met. Nothing for a classic game.
+fn objective_badge(ui: &mut egui::Ui, state: &AppState) {
+    let objective = state.objective.objective();
+    if objective.is_classic() {
+        return;
+    }
+    let (text, color) = match (state.objective.verdict(), state.objective.clicks_left()) {
+        (Verdict::Open, Some(1)) => (format!("🎯 {objective}: 1 click left"), None),
+        (Verdict::Open, Some(left)) => (format!("🎯 {objective}: {left} clicks left"), None),
+        (Verdict::Open, None) => (format!("🎯 {objective}"), None),
+        (Verdict::Met, _) => ("🎯 Objective met".to_string(), Some(egui::Color32::GREEN)),
+        (Verdict::Failed(why), _) => (
+            format!("🎯 Objective failed: {why}"),
+            Some(egui::Color32::LIGHT_RED),
+        ),
+    };
+    let text = match color {
+        Some(color) => egui::RichText::new(text).color(color),
+        None => egui::RichText::new(text),
+    };
+    widgets::live(&ui.label(text));
+}
+
+/// After the banner on a loss, how likely the fatal cell was to be a mine
+/// before the click, once the worker has it.
+fn fatal_odds(ui: &mut egui::Ui, state: &mut AppState) {
+    match state.fatal_odds() {
+        Some(FatalOdds::Pending(_)) => {
+            ui.weak("analyzing…");
+            ui.ctx()
+                .request_repaint_after(std::time::Duration::from_millis(100));
+        }
+        Some(FatalOdds::Ready(Some(chance))) => {
+            widgets::live(&ui.colored_label(egui::Color32::RED, chance.to_string()));
+        }
+        Some(FatalOdds::Ready(None)) | None => {}
+    }
+}
+
+/// The game's flags in the order they went down: a green tick on a mine,
+/// a red cross on a safe cell, hollow once taken off. Hover one for when,
+/// click it to point out its cell on the board. Only drawn once the game is
+/// over, so it never says which flags are right mid-game.
+fn flag_strip(ui: &mut egui::Ui, state: &mut AppState) {
+    let Some(review) = state.flag_log.review(&state.board) else {
+        return;
+    };
+    if review.flags.is_empty() {
+        return;
+    }
+    let mut picked = None;
+    ui.horizontal_wrapped(|ui| {
+        ui.spacing_mut().item_spacing.x = 2.0;
+        ui.label(format!(
+            "Flags: {} on mines, {} on safe cells ({} corrected) ",
+            review.on_mines(),
+            review.on_safe_cells(),
+            review.corrected()
+        ));
+        for flag in &review.flags {
+            let (rect, response) =
+                ui.allocate_exact_size(egui::vec2(14.0, 14.0), egui::Sense::click());
+            let color = if flag.on_mine {
+                egui::Color32::from_rgb(60, 170, 80)
+            } else {
+                egui::Color32::from_rgb(210, 60, 60)
+            };
+            let painter = ui.painter();
+            if flag.removed.is_some() {
+                painter.circle_stroke(rect.center(), 4.5, egui::Stroke::new(1.5, color));
+            } else {
+                let glyph = if flag.on_mine { "✔" } else { "✖" };
+                let font = egui::FontId::proportional(12.0);
+                painter.text(
+                    rect.center(),
+                    egui::Align2::CENTER_CENTER,
+                    glyph,
+                    font,
+                    color,
+                );
+            }
+            let text = flag.to_string();
+            response
+                .widget_info(|| egui::WidgetInfo::labeled(egui::WidgetType::Button, true, &text));
+            if response.on_hover_text(text).clicked() {
+                picked = Some(flag.cell);
+            }
+        }
+    });
+    if let Some(cell) = picked {
+        state.show_analysis = false;
+        state.constraints = Some(vec![cell]);
+    }
+}
+
+/// Width, height and mine count for a custom board, with its symmetry
+/// and spacing under "Advanced generation".
+fn custom_row(ui: &mut egui::Ui, state: &mut AppState) {
+    ui.horizontal(|ui| {
+        let max_side = if state.settings.advanced.large_boards {
+            MAX_LARGE_SIDE
+        } else {
+            MAX_CUSTOM_SIDE
+        };
+        ui.label("Width:");
+        ui.add(egui::DragValue::new(&mut state.custom_width).range(2..=max_side));
+        ui.label("Height:");
+        ui.add(egui::DragValue::new(&mut state.custom_height).range(2..=max_side));
+        ui.label("Mines:");
+        ui.add(egui::DragValue::new(&mut state.custom_mines).range(1..=max_side * max_side));
+
+        if ui.button("Start").clicked() {
+            let custom = Difficulty::custom(
+                state.custom_width,
+                state.custom_height,
+                state.custom_mines,
+                max_side,
+            );
+            (state.custom_width, state.custom_height, state.custom_mines) = custom.spec().params();
+            // refused up front, rather than on the first click
+            state.custom_error = state.board_options(custom).check().err();
+            if state.custom_error.is_none() {
+                state.discard(Discard::Difficulty(custom));
+            }
+        }
+    });
+    egui::CollapsingHeader::new("Advanced generation").show(ui, |ui| {
+        ui.horizontal(|ui| {
+            ui.label("Symmetry:");
+            ui.radio_value(&mut state.custom_symmetry, None, "None");
+            for symmetry in Symmetry::ALL {
+                ui.radio_value(
+                    &mut state.custom_symmetry,
+                    Some(symmetry),
+                    symmetry_label(symmetry),
+                );
+            }
+        });
+        ui.checkbox(&mut state.custom_spaced, "No two mines side by side");
+    });
+    if let Some(err) = state.custom_error {
+        ui.colored_label(egui::Color32::LIGHT_RED, format!("Can't start: {err}."));
+    }
+}
+
+/// How the custom row names a symmetry.
+fn symmetry_label(symmetry: Symmetry) -> &'static str {
+    match symmetry {
+        Symmetry::Horizontal => "Mirrored left to right",
+        Symmetry::Vertical => "Mirrored top to bottom",
+        Symmetry::Rotational180 => "Same upside down",
+    }
+}
+
+/// End-of-game click statistics, with lifetime averages underneath, and
+/// the post-game analysis toggle.
+fn summary(ui: &mut egui::Ui, state: &mut AppState) {
+    state.start_analysis(ui.ctx());
+    let stats = &state.input_stats;
+    let efficiency = stats
+        .efficiency(state.three_bv)
+        .map_or("–".to_string(), |e| format!("{e:.0}%"));
+    ui.label(format!(
+        "Clicks: {} left, {} right, {} wasted · Flags: {} placed, {} correct · 3BV {} · Efficiency {}",
+        stats.left_clicks,
+        stats.right_clicks,
+        stats.wasted_clicks,
+        stats.flags_placed,
+        stats.flags_correct,
+        state.three_bv,
+        efficiency
+    ));
+
+    let history = &state.history;
+    if let (Some(efficiency), Some((left, right, wasted))) =
+        (history.average_efficiency(), history.average_clicks())
+    {
+        ui.weak(format!(
+            "Lifetime ({} games): {efficiency:.0}% efficiency, \
+             {left:.1} left / {right:.1} right / {wasted:.1} wasted clicks per game",
+            history.games
+        ));
+    }
+    let [regions, numbers, answers] = state.hints.used();
+    if state.hints.penalty_secs() > 0 {
+        ui.label(format!(
+            "Hints: {regions} where to look, {numbers} which number, {answers} moves played \
+             (+{}s)",
+            state.hints.penalty_secs()
+        ));
+    }
+    if state.clock.used_undo {
+        ui.label(format!(
+            "Time: {}, with a move undone, so it isn't a best time",
+            format::clock_tenths(state.clock.secs)
+        ));
+    }
+    flag_strip(ui, state);
+    if state.settings.gameplay.suggestions {
+        suggestion_banner(ui, state);
+    }
+
+    ui.horizontal(|ui| {
+        if ui
+            .checkbox(&mut state.show_analysis, "Analysis")
+            .on_hover_text("The solver's view of the position before your last move")
+            .changed()
+            && state.show_analysis
+        {
+            state.heatmap = None;
+        }
+        if !state.show_analysis {
+            return;
+        }
+        if state.kill_screen().is_some() {
+            ui.weak("Red: solver's mine chance · Blue: actual mine · Outline: forced by logic");
+        } else {
+            ui.spinner();
+            ui.label("Analysing…");
+        }
+    });
+}
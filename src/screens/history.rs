@@ -0,0 +1,350 @@
+//! Past games as a calendar: one square per day for the last six months,
+//! shaded by how many games were played, in the colour of the difficulty
+//! picked above it. Hovering a day shows its games, wins and best time;
+//! clicking it narrows the totals underneath to that day. Below them the
+//! personal rating (see `rating`) is drawn game by game at the same size.
+
+use super::Screen;
+use crate::app::AppState;
+use crate::history::{Game, Tally};
+use chrono::{Datelike, Duration, NaiveDate};
+use eframe::egui::{self, Color32, Sense, Stroke};
+use minesweeper_rs::{format, Difficulty};
+
+/// Columns in the calendar, one per week.
+const WEEKS: usize = 26;
+/// Side of a day's square, and the gap between squares.
+const DAY: f32 = 12.0;
+const GAP: f32 = 2.0;
+/// Room for the weekday names on the left and the months above.
+const GUTTER: f32 = 30.0;
+const HEADER: f32 = 16.0;
+/// Height of the rating chart; it is as wide as the calendar.
+const CHART: f32 = 110.0;
+
+/// What the history screen is narrowed to.
+#[derive(Default)]
+pub struct HistoryView {
+    pub size: Option<(usize, usize, usize)>, // one board size, or all
+    pub day: Option<NaiveDate>,              // the clicked day, or every day
+}
+
+pub fn show(ctx: &egui::Context, state: &mut AppState) -> Option<Screen> {
+    let mut next = None;
+
+    egui::CentralPanel::default().show(ctx, |ui| {
+        ui.horizontal(|ui| {
+            ui.heading("History");
+            if ui.button("Back to Menu").clicked() {
+                next = Some(Screen::Menu);
+            }
+            super::profile_switcher(ui, state);
+        });
+        ui.add_space(8.0);
+
+        let view = &mut state.history_view;
+        let history = &state.history;
+        ui.horizontal_wrapped(|ui| {
+            ui.selectable_value(&mut view.size, None, "All sizes");
+            for size in history.logged_sizes() {
+                let text = egui::RichText::new(size_label(size)).color(hue(Some(size)));
+                ui.selectable_value(&mut view.size, Some(size), text);
+            }
+        });
+        ui.add_space(8.0);
+
+        let today = state.calendar.today();
+        calendar(ui, view, &history.days(view.size), today);
+        ui.add_space(12.0);
+
+        let tally = history.tally(view.size, view.day);
+        ui.horizontal(|ui| match view.day {
+            Some(day) => {
+                ui.strong(format::long_date(day.year(), day.month(), day.day()));
+                if ui.small_button("Show every day").clicked() {
+                    view.day = None;
+                }
+            }
+            None => {
+                ui.strong("Every logged game");
+            }
+        });
+        totals(ui, &tally, view.size.is_none());
+
+        let ratings = history.ratings(view.size);
+        if let Some(latest) = ratings.last() {
+            ui.add_space(12.0);
+            ui.strong(format!("Rating: {latest:.2}"));
+            rating_chart(ui, &ratings, hue(view.size));
+        }
+
+        let trainer = &history.trainer;
+        if let Some(accuracy) = trainer.accuracy() {
+            ui.add_space(12.0);
+            ui.strong("Probability trainer");
+            ui.label(format!(
+                "{} {} · {accuracy:.0}% right · level {}",
+                trainer.answered,
+                plural(trainer.answered, "answer"),
+                trainer.level + 1
+            ));
+        }
+    });
+
+    next
+}
+
+/// The heatmap of days, Monday at the top of each week, ending today.
+fn calendar(
+    ui: &mut egui::Ui,
+    view: &mut HistoryView,
+    days: &std::collections::BTreeMap<NaiveDate, Tally>,
+    today: NaiveDate,
+) {
+    let monday = today - Duration::days(today.weekday().num_days_from_monday().into());
+    let start = monday - Duration::weeks(WEEKS as i64 - 1);
+    let step = DAY + GAP;
+    let size = egui::vec2(GUTTER + WEEKS as f32 * step, HEADER + 7.0 * step);
+    let (rect, response) = ui.allocate_exact_size(size, Sense::click());
+    let painter = ui.painter_at(rect);
+    let text = ui.visuals().weak_text_color();
+    let font = egui::FontId::proportional(10.0);
+
+    let origin = rect.min + egui::vec2(GUTTER, HEADER);
+    let square = |week: usize, weekday: usize| {
+        let min = origin + egui::vec2(week as f32 * step, weekday as f32 * step);
+        egui::Rect::from_min_size(min, egui::vec2(DAY, DAY))
+    };
+    for (weekday, name) in [(0, "Mon"), (2, "Wed"), (4, "Fri")] {
+        let at = egui::pos2(rect.min.x, square(0, weekday).center().y);
+        painter.text(at, egui::Align2::LEFT_CENTER, name, font.clone(), text);
+    }
+    for (week, name) in month_labels(start) {
+        let at = egui::pos2(square(week, 0).min.x, rect.min.y);
+        painter.text(at, egui::Align2::LEFT_TOP, name, font.clone(), text);
+    }
+
+    let busiest = days.values().map(|tally| tally.games).max().unwrap_or(0);
+    let empty = ui.visuals().widgets.inactive.bg_fill;
+    let color = hue(view.size);
+    for week in 0..WEEKS {
+        for weekday in 0..7 {
+            let day = start + Duration::days((week * 7 + weekday) as i64);
+            if day > today {
+                break;
+            }
+            let games = days.get(&day).map_or(0, |tally| tally.games);
+            let fill = if games == 0 {
+                empty
+            } else {
+                // four shades, the busiest day darkest
+                let level = (games * 4).div_ceil(busiest) as f32;
+                color.gamma_multiply(0.25 + 0.75 * level / 4.0)
+            };
+            painter.rect_filled(square(week, weekday), 2.0, fill);
+            if view.day == Some(day) {
+                let stroke = Stroke::new(1.5, ui.visuals().strong_text_color());
+                painter.rect_stroke(
+                    square(week, weekday),
+                    2.0,
+                    stroke,
+                    egui::StrokeKind::Outside,
+                );
+            }
+        }
+    }
+
+    let hovered = response.hover_pos().and_then(|pos| {
+        let at = pos - origin;
+        let (week, weekday) = ((at.x / step).floor(), (at.y / step).floor());
+        if at.x < 0.0 || at.y < 0.0 || week >= WEEKS as f32 || weekday >= 7.0 {
+            return None;
+        }
+        let day = start + Duration::days((week as usize * 7 + weekday as usize) as i64);
+        (day <= today).then_some(day)
+    });
+    let Some(day) = hovered else {
+        return;
+    };
+    if response.clicked() {
+        view.day = (view.day != Some(day)).then_some(day);
+    }
+    let tally = days.get(&day).copied().unwrap_or_default();
+    response.on_hover_ui_at_pointer(|ui| {
+        ui.strong(format::date(day.year(), day.month(), day.day()));
+        ui.label(format!(
+            "{} {}, {} {}",
+            format::count(u64::from(tally.games)),
+            plural(tally.games, "game"),
+            format::count(u64::from(tally.wins)),
+            plural(tally.wins, "win")
+        ));
+        if let Some(fastest) = tally.fastest {
+            ui.label(format!(
+                "Best time: {}{}",
+                format::clock_tenths(fastest.secs),
+                assisted(&fastest)
+            ));
+        }
+    });
+}
+
+/// The rating after each game as a line, oldest on the left, between the
+/// lowest and highest it has been. Hovering shows the game's rating.
+fn rating_chart(ui: &mut egui::Ui, ratings: &[f64], color: Color32) {
+    let width = GUTTER + WEEKS as f32 * (DAY + GAP);
+    let (rect, response) = ui.allocate_exact_size(egui::vec2(width, CHART), Sense::hover());
+    let painter = ui.painter_at(rect);
+    let text = ui.visuals().weak_text_color();
+    let font = egui::FontId::proportional(10.0);
+
+    let low = ratings
+        .iter()
+        .cloned()
+        .fold(f64::INFINITY, f64::min)
+        .floor();
+    let high = ratings
+        .iter()
+        .cloned()
+        .fold(f64::NEG_INFINITY, f64::max)
+        .ceil();
+    let high = high.max(low + 1.0);
+    let plot = egui::Rect::from_min_max(
+        rect.min + egui::vec2(GUTTER, 4.0),
+        rect.max - egui::vec2(0.0, 4.0),
+    );
+    let x = |i: usize| {
+        let across = i as f32 / ratings.len().saturating_sub(1).max(1) as f32;
+        plot.min.x + across * plot.width()
+    };
+    let y = |rating: f64| plot.max.y - ((rating - low) / (high - low)) as f32 * plot.height();
+
+    for value in [low, high] {
+        let at = egui::pos2(rect.min.x, y(value));
+        painter.text(
+            at,
+            egui::Align2::LEFT_CENTER,
+            format!("{value:.0}"),
+            font.clone(),
+            text,
+        );
+        let rule = Stroke::new(1.0, ui.visuals().widgets.inactive.bg_fill);
+        painter.hline(plot.x_range(), y(value), rule);
+    }
+    let points: Vec<_> = ratings
+        .iter()
+        .enumerate()
+        .map(|(i, &rating)| egui::pos2(x(i), y(rating)))
+        .collect();
+    painter.add(egui::Shape::line(points.clone(), Stroke::new(1.5, color)));
+    if let Some(&last) = points.last() {
+        painter.circle_filled(last, 3.0, color);
+    }
+
+    let Some(pos) = response.hover_pos() else {
+        return;
+    };
+    let across = ((pos.x - plot.min.x) / plot.width()).clamp(0.0, 1.0);
+    let game = (across * ratings.len().saturating_sub(1) as f32).round() as usize;
+    painter.circle_stroke(
+        points[game],
+        4.0,
+        Stroke::new(1.5, ui.visuals().strong_text_color()),
+    );
+    response.on_hover_ui_at_pointer(|ui| {
+        ui.label(format!("Game {}: {:.2}", game + 1, ratings[game]));
+    });
+}
+
+/// The mark on a time set from the best opening, which the player was
+/// handed rather than found.
+fn assisted(game: &Game) -> &'static str {
+    if game.assisted {
+        " (best-opening start)"
+    } else {
+        ""
+    }
+}
+
+/// Month names above the week each month starts in, leaving out a first
+/// label that would run into the next.
+fn month_labels(start: NaiveDate) -> Vec<(usize, String)> {
+    let mut labels = Vec::new();
+    let mut month = None;
+    for week in 0..WEEKS {
+        let monday = start + Duration::weeks(week as i64);
+        let sunday = monday + Duration::days(6);
+        let starts = if week == 0 { monday } else { sunday };
+        if month != Some(starts.month()) {
+            month = Some(starts.month());
+            if labels.len() == 1 && week < 3 {
+                labels.clear();
+            }
+            labels.push((week, format::month_short(starts.month()).to_string()));
+        }
+    }
+    labels
+}
+
+/// Games, wins, the best time and the mean 3BV/s of `tally`. The best
+/// time names its board when sizes are mixed.
+fn totals(ui: &mut egui::Ui, tally: &Tally, mixed: bool) {
+    let Some(win_rate) = tally.win_rate() else {
+        ui.weak("No games here yet.");
+        return;
+    };
+    let abandoned = match tally.abandoned {
+        0 => String::new(),
+        n => format!(" · {} abandoned", format::count(u64::from(n))),
+    };
+    ui.label(format!(
+        "{} {} · {} {} ({win_rate:.0}%){abandoned}",
+        format::count(u64::from(tally.games)),
+        plural(tally.games, "game"),
+        format::count(u64::from(tally.wins)),
+        plural(tally.wins, "win")
+    ));
+    if let Some(fastest) = tally.fastest {
+        let board = if mixed {
+            format!(" on {}", size_label(fastest.size))
+        } else {
+            String::new()
+        };
+        let assisted = assisted(&fastest);
+        ui.label(format!(
+            "Best time: {}{board}{assisted}",
+            format::clock_tenths(fastest.secs)
+        ));
+    }
+    if let Some(speed) = tally.speed() {
+        ui.label(format!("Mean speed: {speed:.2} 3BV/s"));
+    }
+}
+
+fn plural(n: u32, word: &str) -> String {
+    if n == 1 {
+        word.to_string()
+    } else {
+        format!("{word}s")
+    }
+}
+
+/// A preset's menu name, or the size spelled out.
+pub fn size_label((width, height, mines): (usize, usize, usize)) -> String {
+    match Difficulty::from_params(width, height, mines) {
+        Difficulty::Custom { .. } => format!("{width}x{height}, {mines} mines"),
+        preset => preset.label().to_string(),
+    }
+}
+
+/// The calendar's colour for a board size: green, blue and red for the
+/// presets, purple for anything else and orange for all sizes together.
+fn hue(size: Option<(usize, usize, usize)>) -> Color32 {
+    match size.map(|(w, h, m)| Difficulty::from_params(w, h, m)) {
+        None => Color32::from_rgb(230, 140, 40),
+        Some(Difficulty::Beginner) => Color32::from_rgb(70, 170, 80),
+        Some(Difficulty::Intermediate) => Color32::from_rgb(60, 130, 220),
+        Some(Difficulty::Expert) => Color32::from_rgb(210, 60, 60),
+        Some(_) => Color32::from_rgb(150, 90, 200),
+    }
+}
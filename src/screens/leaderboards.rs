@@ -0,0 +1,132 @@
+//! The online leaderboards: the top 50 for each preset, with the player's
+//! own places highlighted. While the feature is off this explains what
+//! taking part shares and offers to turn it on.
+
+use super::Screen;
+use crate::app::AppState;
+use crate::net::leaderboard::{self, Leaderboard, Standings};
+use eframe::egui::{self, Color32, RichText};
+use minesweeper_rs::{format, Difficulty};
+
+pub fn show(ctx: &egui::Context, state: &mut AppState) -> Option<Screen> {
+    let mut next = None;
+
+    egui::CentralPanel::default().show(ctx, |ui| {
+        ui.horizontal(|ui| {
+            ui.heading("Leaderboards");
+            if ui.button("Back to Menu").clicked() {
+                next = Some(Screen::Menu);
+            }
+        });
+        ui.add_space(8.0);
+
+        let wake = state.waker(ui.ctx());
+        let settings = &mut state.settings.advanced.leaderboard;
+        if !settings.enabled {
+            opt_in(ui, settings);
+            return;
+        }
+        if settings.name.trim().is_empty() {
+            ui.colored_label(
+                Color32::YELLOW,
+                "Pick a display name in Settings → Advanced to submit your wins.",
+            );
+        }
+        let queued = state.leaderboard.queued();
+        if queued > 0 {
+            let why = state.leaderboard.last_error.as_deref().unwrap_or("sending");
+            ui.weak(format!("{queued} score(s) waiting to be sent ({why})"));
+        }
+
+        let id = ui.id().with("leaderboard_tab");
+        let mut tab: Difficulty = ui.data(|d| d.get_temp(id)).unwrap_or(Difficulty::Beginner);
+        ui.horizontal(|ui| {
+            for difficulty in Difficulty::PRESETS {
+                ui.selectable_value(&mut tab, difficulty, difficulty.label());
+            }
+        });
+        ui.data_mut(|d| d.insert_temp(id, tab));
+        let Some(spec) = leaderboard::spec(tab) else {
+            return;
+        };
+
+        if ui.button("Refresh").clicked() {
+            state.leaderboard.refresh(&spec);
+        }
+        let now = ui.input(|i| i.time);
+        let view = state.leaderboard.table(settings, &spec, now, wake);
+        if let Some(error) = view.error {
+            ui.colored_label(
+                Color32::LIGHT_RED,
+                format!("Can't reach the leaderboard: {error}"),
+            );
+        }
+        match view.standings {
+            Some(standings) => table(ui, standings, settings.name.trim()),
+            None if view.loading => {
+                ui.spinner();
+            }
+            None => {}
+        }
+    });
+
+    next
+}
+
+/// What taking part means, and the switch to do it.
+fn opt_in(ui: &mut egui::Ui, settings: &mut Leaderboard) {
+    ui.label("Online leaderboards are off.");
+    ui.label(format!(
+        "If you turn them on, each win on Beginner, Intermediate or Expert is sent to {} \
+         with your display name, time, 3BV, the board's seed and fingerprints of the \
+         board and of the order you opened it in. Nothing else about you or your \
+         computer is sent, and you can turn this off again in Settings → Advanced.",
+        settings.server.trim()
+    ));
+    ui.horizontal(|ui| {
+        ui.label("Display name:");
+        ui.text_edit_singleline(&mut settings.name);
+    });
+    let named = !settings.name.trim().is_empty();
+    if ui
+        .add_enabled(named, egui::Button::new("Turn on leaderboards"))
+        .clicked()
+    {
+        settings.enabled = true;
+    }
+}
+
+/// The ranked entries, rows under `name` highlighted.
+fn table(ui: &mut egui::Ui, standings: &Standings, name: &str) {
+    if standings.entries.is_empty() {
+        ui.weak("No scores yet. Win a game to be first!");
+        return;
+    }
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        egui::Grid::new("leaderboard")
+            .striped(true)
+            .num_columns(4)
+            .show(ui, |ui| {
+                for heading in ["Rank", "Name", "Time", "3BV"] {
+                    ui.strong(heading);
+                }
+                ui.end_row();
+                for entry in &standings.entries {
+                    let own = !name.is_empty() && entry.name == name;
+                    let cell = |text: String| {
+                        let text = RichText::new(text);
+                        if own {
+                            text.strong().color(Color32::GOLD)
+                        } else {
+                            text
+                        }
+                    };
+                    ui.label(cell(entry.rank.to_string()));
+                    ui.label(cell(entry.name.clone()));
+                    ui.label(cell(format::clock_millis(entry.time_ms)));
+                    ui.label(cell(entry.bbbv.to_string()));
+                    ui.end_row();
+                }
+            });
+    });
+}
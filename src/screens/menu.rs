@@ -0,0 +1,347 @@
+//! Start menu: difficulty picker, recent boards, challenges, profiles and
+//! crash recovery.
+
+use super::{about, history::size_label, versus, Screen};
+use crate::app::{AppState, Discard};
+use crate::challenge::{ChallengeResult, ChallengeSpec, Kind};
+use crate::changelog;
+use crate::profiles::NameError;
+use crate::tutorial::Tutorial;
+use eframe::egui;
+use minesweeper_rs::autosave::Session;
+use minesweeper_rs::format;
+use minesweeper_rs::Difficulty;
+
+/// Text typed into the profile controls on the menu.
+#[derive(Default)]
+pub struct ProfileForm {
+    pub new_name: String,
+    pub rename: String,
+    pub delete: Option<String>, // profile awaiting typed confirmation
+    pub confirm: String,
+    pub error: Option<NameError>,
+}
+
+pub fn show(ctx: &egui::Context, state: &mut AppState) -> Option<Screen> {
+    let mut next = None;
+
+    egui::CentralPanel::default().show(ctx, |ui| {
+        ui.vertical_centered(|ui| {
+            ui.add_space(40.0);
+            ui.heading("Bosnia Simulator");
+            ui.label(format!("Version {}", changelog::CURRENT));
+            ui.label("By Rylan Hillman");
+            ui.add_space(10.0);
+            ui.horizontal(|ui| {
+                ui.label("Playing as");
+                super::profile_switcher(ui, state);
+            });
+            egui::CollapsingHeader::new("Manage profiles").show(ui, |ui| profiles(ui, state));
+            ui.add_space(10.0);
+            ui.separator();
+            ui.add_space(10.0);
+            if !state.settings.tutorial_done {
+                ui.label("New to minesweeper? The tutorial takes a minute.");
+            }
+            if ui.button("Tutorial").clicked() {
+                state.tutorial = Tutorial::new();
+                next = Some(Screen::Tutorial);
+            }
+            if ui.button("Probability trainer").clicked() {
+                next = Some(Screen::Trainer);
+            }
+            if ui.button("Local versus").clicked() {
+                next = Some(versus::enter(ctx, state));
+            }
+            ui.add_space(10.0);
+            ui.label("Select difficulty to start:");
+            ui.checkbox(&mut state.settings.gameplay.zen, "🍃 Zen mode")
+                .on_hover_text(
+                    "Relax: a mine gets flagged instead of ending the game, \
+                     any move can be undone, and nothing is recorded",
+                );
+
+            ui.add_space(10.0);
+            for diff in Difficulty::PRESETS {
+                if ui.button(diff.label()).clicked() {
+                    state.discard(Discard::Menu(diff));
+                    next = Some(Screen::Game);
+                }
+            }
+            if ui.button("Custom…").clicked() {
+                state.editing_custom = true;
+                next = Some(Screen::Game);
+            }
+            if ui.button("Surprise me").clicked() {
+                state.surprise = Some(Difficulty::surprise(&mut rand::thread_rng()));
+            }
+            if super::surprise_row(ui, state) {
+                next = Some(Screen::Game);
+            }
+            egui::CollapsingHeader::new("Recent boards").show(ui, |ui| {
+                if recent_boards(ui, state) {
+                    next = Some(Screen::Game);
+                }
+            });
+            ui.add_space(10.0);
+            if challenge_cards(ui, state) {
+                next = Some(Screen::Race);
+            }
+            ui.add_space(10.0);
+            if ui.button("Seed race").clicked() {
+                next = Some(Screen::Race);
+            }
+            if ui.button("Spectate").clicked() {
+                next = Some(Screen::Spectate);
+            }
+            if ui.button("Board editor").clicked() {
+                next = Some(Screen::Editor);
+            }
+            if ui.button("History").clicked() {
+                next = Some(Screen::History);
+            }
+            if ui.button("Leaderboards").clicked() {
+                next = Some(Screen::Leaderboards);
+            }
+            if ui.button("Settings").clicked() {
+                next = Some(Screen::Settings);
+            }
+            if ui.button("About").clicked() {
+                next = Some(Screen::About);
+            }
+
+            if let Some(summary) = state.recovered.as_ref().map(recovery_summary) {
+                ui.add_space(20.0);
+                ui.separator();
+                ui.label(summary);
+                if ui.button("Recover previous session").clicked() {
+                    state.recover();
+                    next = Some(Screen::Game);
+                }
+                if ui.button("Discard").clicked() {
+                    state.discard_recovered();
+                }
+            }
+        });
+    });
+    if whats_new(ctx, state) {
+        next = Some(Screen::About);
+    }
+
+    next
+}
+
+/// The boards played lately, newest first, with how their last game went
+/// and how often each was tried. Returns true if one was picked to play
+/// again.
+fn recent_boards(ui: &mut egui::Ui, state: &mut AppState) -> bool {
+    let recent = state.history.recent_boards();
+    if recent.is_empty() {
+        ui.label("Boards you play show up here, to play again.");
+        return false;
+    }
+    let mut rematch = None;
+    egui::Grid::new("recent_boards")
+        .striped(true)
+        .show(ui, |ui| {
+            for line in &recent {
+                let game = &line.last;
+                ui.label(size_label(game.size))
+                    .on_hover_text(format!("Board {:016x}", line.dealt.hash));
+                ui.label(match (game.won, game.abandoned) {
+                    (true, _) => format!("Won in {}", format::clock_tenths(game.secs)),
+                    (false, true) => "Abandoned".to_string(),
+                    (false, false) => format!("Lost after {}", format::clock_tenths(game.secs)),
+                });
+                ui.label(match line.attempts {
+                    1 => "1 try".to_string(),
+                    n => format!("{n} tries"),
+                });
+                ui.label(game.day.to_string());
+                if ui
+                    .button("Rematch")
+                    .on_hover_text("The same mines, opened from the same cell; never a best time")
+                    .clicked()
+                {
+                    rematch = Some(line.dealt.hash);
+                }
+                match line.dealt.seed {
+                    Some(seed) => {
+                        if ui.button("Copy seed").clicked() {
+                            ui.ctx().copy_text(seed.to_string());
+                        }
+                    }
+                    None => {
+                        ui.label("");
+                    }
+                }
+                ui.end_row();
+            }
+        });
+    if ui
+        .button("Clear recent boards")
+        .on_hover_text("Forget these boards; the history keeps their games")
+        .clicked()
+    {
+        state.history.forget_boards();
+    }
+    let Some(hash) = rematch else {
+        return false;
+    };
+    state.discard(Discard::Rematch(hash));
+    true
+}
+
+/// The daily and weekly challenges side by side, each with its board, its
+/// rule and how it has gone so far. Returns true if one was picked, which
+/// is then waiting on the race screen.
+fn challenge_cards(ui: &mut egui::Ui, state: &mut AppState) -> bool {
+    let today = state.calendar.today();
+    let challenges = &state.history.challenges;
+    let mut picked = None;
+    ui.horizontal(|ui| {
+        for kind in Kind::ALL {
+            let challenge = ChallengeSpec::new(kind, today);
+            ui.group(|ui| {
+                ui.set_max_width(220.0);
+                ui.vertical(|ui| {
+                    ui.strong(challenge.title());
+                    ui.label(challenge.rules());
+                    let longest = challenges.longest_streak(kind);
+                    ui.label(format!(
+                        "Streak: {}",
+                        kind.periods(challenges.streak(kind, today))
+                    ))
+                    .on_hover_text(format!("Longest: {}", kind.periods(longest)));
+                    ui.label(match challenges.get(&challenge) {
+                        Some(ChallengeResult {
+                            best: Some(best), ..
+                        }) => format!("Won in {best:.1} s"),
+                        Some(ChallengeResult { tries: 1, .. }) => "Not won yet (1 try)".into(),
+                        Some(result) => format!("Not won yet ({} tries)", result.tries),
+                        None => "Not played yet".into(),
+                    });
+                    if ui.button("Play").clicked() {
+                        picked = Some(challenge.clone());
+                    }
+                });
+            });
+        }
+    });
+    let Some(challenge) = picked else {
+        return false;
+    };
+    state.race.load_challenge(challenge);
+    true
+}
+
+/// The notes for the versions this profile hasn't seen, until dismissed.
+/// Returns true if the full changelog was asked for.
+fn whats_new(ctx: &egui::Context, state: &mut AppState) -> bool {
+    if state.whats_new.is_empty() {
+        return false;
+    }
+    let mut dismissed = false;
+    let mut full = false;
+    egui::Window::new("What's new")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(ctx, |ui| {
+            egui::ScrollArea::vertical()
+                .max_height(300.0)
+                .show(ui, |ui| {
+                    for release in &state.whats_new {
+                        ui.heading(about::title(release));
+                        about::release_notes(ui, release);
+                    }
+                });
+            ui.separator();
+            ui.horizontal(|ui| {
+                dismissed = ui.button("Got it").clicked();
+                full = ui.button("Full changelog").clicked();
+            });
+        });
+    if dismissed || full {
+        state.whats_new.clear();
+    }
+    full
+}
+
+/// Create, rename and delete profiles. Deleting asks for the name to be
+/// typed back, since it throws away that player's records for good.
+fn profiles(ui: &mut egui::Ui, state: &mut AppState) {
+    let form = &mut state.profile_form;
+
+    ui.horizontal(|ui| {
+        ui.text_edit_singleline(&mut form.new_name);
+        if ui.button("Create").clicked() {
+            form.error = state.profiles.create(&form.new_name).err();
+            if form.error.is_none() {
+                form.new_name.clear();
+            }
+        }
+    });
+    ui.horizontal(|ui| {
+        ui.text_edit_singleline(&mut form.rename);
+        if ui.button("Rename current").clicked() {
+            form.error = state.profiles.rename_current(&form.rename).err();
+            if form.error.is_none() {
+                form.rename.clear();
+            }
+        }
+    });
+    if let Some(error) = form.error {
+        ui.colored_label(egui::Color32::LIGHT_RED, error.to_string());
+    }
+
+    let current = state.profiles.current().to_string();
+    let others: Vec<String> = state
+        .profiles
+        .names()
+        .filter(|&name| name != current)
+        .map(str::to_string)
+        .collect();
+    for name in others {
+        ui.horizontal(|ui| {
+            ui.label(&name);
+            if ui.small_button("Delete…").clicked() {
+                form.delete = Some(name.clone());
+                form.confirm.clear();
+            }
+        });
+    }
+
+    let Some(name) = form.delete.clone() else {
+        return;
+    };
+    ui.label(format!("Type \"{name}\" to delete it and all its records:"));
+    let mut confirmed = false;
+    ui.horizontal(|ui| {
+        ui.text_edit_singleline(&mut form.confirm);
+        confirmed = ui
+            .add_enabled(form.confirm == name, egui::Button::new("Delete"))
+            .clicked();
+        if confirmed || ui.button("Cancel").clicked() {
+            form.delete = None;
+        }
+    });
+    if confirmed {
+        state.delete_profile(&name);
+    }
+}
+
+/// One-line description of an autosaved game for the start menu.
+fn recovery_summary(session: &Session) -> String {
+    let board = &session.board;
+    let safe = board.width() * board.height() - board.mine_count();
+    let cleared = board.revealed_safe() * 100 / safe.max(1);
+    format!(
+        "Unfinished game: {}x{}, {} mines — {} played, {}% cleared",
+        board.width(),
+        board.height(),
+        board.mine_count(),
+        format::clock(session.elapsed_secs),
+        cleared
+    )
+}
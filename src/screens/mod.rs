@@ -0,0 +1,81 @@
+//! One module per screen. Each draws itself from `&mut AppState` and
+//! returns the screen to switch to, if any.
+
+use crate::app::{AppState, Discard};
+use eframe::egui;
+use minesweeper_rs::Difficulty;
+
+pub mod about;
+pub mod compact;
+pub mod editor;
+pub mod game;
+pub mod history;
+pub mod leaderboards;
+pub mod menu;
+pub mod race;
+pub mod settings;
+pub mod spectate;
+pub mod themes;
+pub mod trainer;
+pub mod tutorial;
+pub mod versus;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Screen {
+    Menu,
+    Game,
+    Compact,
+    Settings,
+    Race,
+    Spectate,
+    Tutorial,
+    Trainer,
+    Versus,
+    History,
+    Leaderboards,
+    About,
+    Editor,
+    Themes,
+}
+
+/// One-click profile switcher for screen headers.
+pub fn profile_switcher(ui: &mut egui::Ui, state: &mut AppState) {
+    let current = state.profiles.current().to_string();
+    let mut picked = None;
+    egui::ComboBox::from_id_salt("profile")
+        .selected_text(format!("👤 {current}"))
+        .show_ui(ui, |ui| {
+            for name in state.profiles.names() {
+                if ui.selectable_label(name == current, name).clicked() {
+                    picked = Some(name.to_string());
+                }
+            }
+        });
+    if let Some(name) = picked {
+        state.switch_profile(ui.ctx(), &name);
+    }
+}
+
+/// The "Surprise me" pick waiting to be played, with buttons to play it or
+/// roll again. Returns true once it has been started.
+pub fn surprise_row(ui: &mut egui::Ui, state: &mut AppState) -> bool {
+    let Some(pick) = state.surprise else {
+        return false;
+    };
+    let mut started = false;
+    ui.horizontal(|ui| {
+        ui.label(format!("Surprise: {}", pick.spec()));
+        if ui.button("Play it").clicked() {
+            state.surprise = None;
+            state.discard(Discard::Difficulty(pick));
+            started = true;
+        }
+        if ui.button("Reroll").clicked() {
+            state.surprise = Some(Difficulty::surprise(&mut rand::thread_rng()));
+        }
+        if ui.button("Cancel").clicked() {
+            state.surprise = None;
+        }
+    });
+    started
+}
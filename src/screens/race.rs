@@ -0,0 +1,434 @@
+//! Seed race: load or create a race, play its boards back to back against
+//! the clock, then compare results with friends' result files. The daily
+//! and weekly challenges are played here too, as one-board races.
+
+use super::Screen;
+use crate::app::AppState;
+use crate::challenge::ChallengeSpec;
+use crate::played::PlayedBoards;
+use crate::race::{
+    describe_handicap, layout_hash, places, BoardRun, Move, Race, RaceError, RaceResult,
+    RACE_BOARDS,
+};
+use crate::widgets::{self, BoardView, CellClick};
+use eframe::egui;
+use minesweeper_rs::{format, Board, Difficulty};
+use tracing::{info, warn};
+
+/// Everything the race screen keeps between frames.
+#[derive(Default)]
+pub struct RaceScreen {
+    pub race: Option<Race>,
+    pub play: Option<Play>,
+    pub runs: Vec<BoardRun>,      // boards finished so far
+    pub results: Vec<RaceResult>, // ours first once finished, then imported
+    pub paste: String,            // race or result file text being pasted
+    pub message: Option<String>,  // last error, or where results were saved
+    pub verified: bool,           // ours replays cleanly; imported ones have to
+    handicap: (String, i64),      // player and seconds being added
+    view: BoardView,              // zoom and scroll, kept from board to board
+    /// The challenge whose board the race is, if it is one.
+    pub challenge: Option<ChallengeSpec>,
+}
+
+/// The board being raced.
+pub struct Play {
+    index: usize,
+    board: Board,
+    moves: Vec<Move>,
+    times: Vec<f64>,      // seconds into the board of each move
+    started: f64,         // egui time the clock started
+    secs: f64,            // frozen once the board is over
+    seen: Option<String>, // how it went the times it was played before
+}
+
+impl RaceScreen {
+    /// Take on `race`, dropping any earlier one and its results.
+    pub fn load(&mut self, race: Race) {
+        info!(boards = race.seeds.len(), "race loaded");
+        *self = RaceScreen {
+            race: Some(race),
+            ..RaceScreen::default()
+        };
+    }
+
+    /// Take on `challenge`, a one-board race under its rules.
+    pub fn load_challenge(&mut self, challenge: ChallengeSpec) {
+        self.load(challenge.race());
+        self.challenge = Some(challenge);
+    }
+
+    fn start_board(&mut self, ctx: &egui::Context, index: usize, played: &PlayedBoards) {
+        let Some(race) = &self.race else {
+            return;
+        };
+        let board = race.board(index);
+        self.play = Some(Play {
+            index,
+            seen: played.lookup(&board).map(|played| played.summary()),
+            board,
+            moves: Vec::new(),
+            times: Vec::new(),
+            started: ctx.input(|i| i.time),
+            secs: 0.0,
+        });
+    }
+}
+
+pub fn show(ctx: &egui::Context, state: &mut AppState) -> Option<Screen> {
+    let mut next = None;
+
+    egui::CentralPanel::default().show(ctx, |ui| {
+        ui.horizontal(|ui| {
+            ui.heading("Seed race");
+            if ui.button("Back to Menu").clicked() {
+                next = Some(Screen::Menu);
+            }
+        });
+        if let Some(message) = &state.race.message {
+            ui.label(message);
+        }
+        ui.separator();
+
+        let screen = &state.race;
+        if screen.race.is_none() {
+            pick_race(ui, state);
+        } else if screen.play.is_some() {
+            play(ui, state);
+        } else if screen.results.is_empty() {
+            ready(ui, state);
+        } else {
+            results(ui, state);
+        }
+    });
+
+    next
+}
+
+/// Create a race or paste one from a friend.
+fn pick_race(ui: &mut egui::Ui, state: &mut AppState) {
+    let screen = &mut state.race;
+    let create = format!("Create race: {RACE_BOARDS} random Intermediate seeds");
+    if ui.button(create).clicked() {
+        screen.load(Race::random(
+            Difficulty::Intermediate,
+            &mut rand::thread_rng(),
+        ));
+    }
+    ui.add_space(10.0);
+    ui.label("Or paste a race file:");
+    ui.text_edit_multiline(&mut screen.paste);
+    if ui.button("Load race").clicked() {
+        match Race::from_json(&screen.paste) {
+            Ok(race) => screen.load(race),
+            Err(err) => screen.message = Some(err.to_string()),
+        }
+    }
+}
+
+/// The loaded race, to share before starting.
+fn ready(ui: &mut egui::Ui, state: &mut AppState) {
+    let ctx = ui.ctx().clone();
+    let screen = &mut state.race;
+    let Some(race) = &screen.race else {
+        return;
+    };
+    ui.label(format!("Race: {}", race.label()));
+    let seen: Vec<String> = (0..race.seeds.len())
+        .filter_map(|index| {
+            let played = state.played.lookup(&race.board(index))?;
+            Some(format!("Board {}: {}", index + 1, played.summary()))
+        })
+        .collect();
+    if !seen.is_empty() {
+        let badge = format!(
+            "↺ You've played {} of these {} boards before",
+            seen.len(),
+            race.seeds.len()
+        );
+        ui.label(egui::RichText::new(badge).strong())
+            .on_hover_text(seen.join("\n"));
+    }
+    match screen.challenge.clone() {
+        Some(challenge) => {
+            ui.strong(challenge.title());
+            ui.label(challenge.rules());
+        }
+        None => {
+            ui.label("Send the race file to your friends, then play it.");
+            handicaps(ui, screen);
+        }
+    }
+    let Some(race) = &screen.race else {
+        return;
+    };
+    let (mut start, mut another) = (false, false);
+    ui.horizontal(|ui| {
+        if ui.button("Copy race file").clicked() {
+            ctx.copy_text(race.to_json());
+        }
+        start = ui.button("Start").clicked();
+        another = ui.button("Another race").clicked();
+    });
+    if start {
+        screen.start_board(&ctx, 0, &state.played);
+    } else if another {
+        screen.race = None;
+    }
+}
+
+/// Handicaps for the host to hand out before sending the race file;
+/// changing them makes it a different race.
+fn handicaps(ui: &mut egui::Ui, screen: &mut RaceScreen) {
+    let Some(race) = &mut screen.race else {
+        return;
+    };
+    let mut remove = None;
+    for (player, &secs) in &race.handicaps {
+        ui.horizontal(|ui| {
+            ui.label(format!("{player}: {} handicap", describe_handicap(secs)));
+            if ui.small_button("Remove").clicked() {
+                remove = Some(player.clone());
+            }
+        });
+    }
+    if let Some(player) = remove {
+        race.handicaps.remove(&player);
+    }
+    ui.horizontal(|ui| {
+        let (player, secs) = &mut screen.handicap;
+        ui.label("Handicap:");
+        ui.add(
+            egui::TextEdit::singleline(player)
+                .hint_text("player")
+                .desired_width(100.0),
+        );
+        ui.add(egui::DragValue::new(secs).range(0..=3600).suffix("s"));
+        let named = !player.trim().is_empty();
+        if ui.add_enabled(named, egui::Button::new("Add")).clicked() {
+            match race.set_handicap(player.trim(), *secs) {
+                Ok(()) => *player = String::new(),
+                Err(err) => screen.message = Some(err.to_string()),
+            }
+        }
+    });
+}
+
+/// The current board with its clock.
+fn play(ui: &mut egui::Ui, state: &mut AppState) {
+    let ctx = ui.ctx().clone();
+    let swap = state.settings.controls.swap_buttons;
+    let player = state.profiles.current().to_string();
+    let played = &mut state.played;
+    let screen = &mut state.race;
+    let (Some(race), Some(play)) = (&screen.race, &mut screen.play) else {
+        return;
+    };
+    if !play.board.is_over() {
+        play.secs = ctx.input(|i| i.time) - play.started;
+        ctx.request_repaint_after(std::time::Duration::from_millis(100));
+    }
+
+    let mut next_board = false;
+    ui.horizontal(|ui| {
+        let handicap = match race.handicap(&player) {
+            0 => String::new(),
+            secs => format!(" ({} handicap)", describe_handicap(secs)),
+        };
+        ui.label(format!(
+            "Board {} of {} · {}{handicap}",
+            play.index + 1,
+            race.seeds.len(),
+            format::clock_tenths(play.secs)
+        ))
+        .on_hover_text(format!(
+            "Seed {} · layout {}",
+            race.seeds[play.index],
+            &layout_hash(&play.board)[..8]
+        ));
+        widgets::mine_counter(ui, &play.board, false);
+        widgets::game_status(ui, &play.board);
+        if play.board.is_over() {
+            let last = play.index + 1 == race.seeds.len();
+            next_board = ui
+                .button(if last { "See results" } else { "Next board" })
+                .clicked();
+        }
+    });
+    if let Some(seen) = &play.seen {
+        ui.label(egui::RichText::new(seen).weak());
+    }
+    ui.separator();
+
+    let response = widgets::board_widget(ui, &play.board, &mut screen.view, None, None, None);
+    if let Some(click) = response.click.filter(|_| !play.board.is_over()) {
+        let click = if swap && !response.keyed {
+            click.swapped()
+        } else {
+            click
+        };
+        let m = match click {
+            CellClick::Reveal(x, y) => Move::Reveal(x, y),
+            CellClick::Flag(x, y) => Move::Flag(x, y),
+        };
+        let at = ctx.input(|i| i.time) - play.started;
+        if m.apply(&mut play.board) {
+            play.moves.push(m);
+            play.times.push(at);
+        }
+        if play.board.is_over() {
+            play.secs = at;
+            played.record(&play.board, play.board.is_won(), at);
+            if let Err(err) = played.save() {
+                state.toasts.error("couldn't save played boards", &err);
+            }
+        }
+    }
+
+    if next_board {
+        let run = BoardRun::timed(
+            race,
+            &player,
+            play.index,
+            play.secs,
+            play.board.is_won(),
+            std::mem::take(&mut play.moves),
+            std::mem::take(&mut play.times),
+        );
+        let index = play.index + 1;
+        screen.runs.push(run);
+        screen.play = None;
+        if index < race.seeds.len() {
+            screen.start_board(&ctx, index, played);
+        } else {
+            finish(state);
+        }
+    }
+}
+
+/// Wrap up our result and keep a copy under the data dir, pruning older
+/// ones as the settings say.
+fn finish(state: &mut AppState) {
+    let screen = &mut state.race;
+    let Some(race) = screen.race.clone() else {
+        return;
+    };
+    let result = RaceResult {
+        player: state.profiles.current().to_string(),
+        race,
+        boards: std::mem::take(&mut screen.runs),
+    };
+    info!(total = result.total_secs(), "race finished");
+    screen.verified = match result.verify() {
+        Ok(()) => true,
+        Err(err) => {
+            warn!(%err, "our race result doesn't verify");
+            false
+        }
+    };
+    if let Some(replays) = &mut state.replays {
+        let id = state.profiles.data().id;
+        let file = format!("{}-{id}.json", result.race.seeds[0]);
+        match replays.add(&file, &result) {
+            Ok(path) => screen.message = Some(format!("Results saved to {}", path.display())),
+            Err(err) => state.toasts.error("couldn't save race results", &err),
+        }
+        let keep = state.settings.advanced.keep_races;
+        if let Err(err) = replays.prune(&result.player, keep) {
+            state.toasts.error("couldn't prune race results", &err);
+        }
+    }
+    if let (Some(challenge), Some(run)) = (&screen.challenge, result.boards.first()) {
+        state
+            .history
+            .challenges
+            .record(challenge, run.won, run.secs);
+    }
+    screen.results.insert(0, result);
+}
+
+/// What the badge next to a player's name means.
+const VERIFIED: &str = "Verified: replayed from the seeds, every move, the outcome \
+                        and the times check out";
+
+/// Everyone's times side by side, plus importing a friend's results.
+fn results(ui: &mut egui::Ui, state: &mut AppState) {
+    let ctx = ui.ctx().clone();
+    let screen = &mut state.race;
+    let (Some(race), Some(ours)) = (&screen.race, screen.results.first()) else {
+        return;
+    };
+
+    egui::Grid::new("race_results")
+        .striped(true)
+        .show(ui, |ui| {
+            ui.label("");
+            for (i, result) in screen.results.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.strong(&result.player);
+                    if i > 0 || screen.verified {
+                        ui.label("✔").on_hover_text(VERIFIED);
+                    }
+                });
+            }
+            ui.end_row();
+            for index in 0..race.seeds.len() {
+                ui.label(format!("Board {}", index + 1));
+                for result in &screen.results {
+                    ui.label(
+                        result
+                            .boards
+                            .get(index)
+                            .map_or("–".into(), BoardRun::describe),
+                    );
+                }
+                ui.end_row();
+            }
+            ui.strong("Total");
+            for result in &screen.results {
+                ui.strong(format::clock_tenths(result.total_secs()));
+            }
+            ui.end_row();
+            if !race.handicaps.is_empty() {
+                ui.label("Handicap");
+                for result in &screen.results {
+                    ui.label(describe_handicap(result.handicap()));
+                }
+                ui.end_row();
+                ui.strong("Adjusted");
+                for result in &screen.results {
+                    ui.strong(format::clock_tenths(result.adjusted_secs()));
+                }
+                ui.end_row();
+            }
+            ui.label("Place");
+            for place in places(&screen.results) {
+                ui.label(place.to_string());
+            }
+            ui.end_row();
+        });
+
+    ui.add_space(10.0);
+    if ui.button("Copy my results").clicked() {
+        ctx.copy_text(ours.to_json());
+    }
+    ui.label("Paste a friend's results to compare:");
+    ui.text_edit_multiline(&mut screen.paste);
+    if ui.button("Import results").clicked() {
+        let imported = RaceResult::from_json(&screen.paste).and_then(|result| {
+            if &result.race == race {
+                Ok(result)
+            } else {
+                Err(RaceError::OtherRace)
+            }
+        });
+        match imported {
+            Ok(result) => {
+                screen.results.push(result);
+                screen.paste.clear();
+                screen.message = None;
+            }
+            Err(err) => screen.message = Some(err.to_string()),
+        }
+    }
+}
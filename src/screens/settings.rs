@@ -0,0 +1,464 @@
+//! Settings, one collapsible section per group. Changes apply as they are
+//! made; the theme is pushed to egui straight away so it previews live.
+
+use super::Screen;
+use crate::app::AppState;
+use crate::bundle::{self, Import, Mode};
+use crate::replays::Keep;
+use crate::settings::{
+    AutoOpen, CountStyle, MarkerChoice, Theme, WinStyle, MAX_ENDGAME_CELLS, MAX_FOCUS_MARGIN,
+    MAX_SAFE_RADIUS,
+};
+use crate::widgets;
+use eframe::egui;
+use minesweeper_rs::render::{Marker, Markers};
+use minesweeper_rs::report::DebugSnapshot;
+use std::path::PathBuf;
+
+/// Moving data between computers: the file typed in, a bundle read and
+/// waiting for merge or replace, and what the last step did.
+#[derive(Default)]
+pub struct DataTransfer {
+    pub path: String,
+    pub pending: Option<Import>,
+    pub report: Vec<String>,
+}
+
+impl DataTransfer {
+    /// Hold `import` for the player to merge or replace, listing what it
+    /// had to leave out.
+    pub fn open(&mut self, import: Import) {
+        self.report = import
+            .skipped
+            .iter()
+            .map(|skipped| format!("Skipped {skipped}"))
+            .collect();
+        self.pending = Some(import);
+    }
+}
+
+/// Draw the settings; `back` is the screen the Back button returns to.
+pub fn show(ctx: &egui::Context, state: &mut AppState, back: Screen) -> Option<Screen> {
+    let mut next = None;
+    let before = state.settings.clone();
+
+    egui::CentralPanel::default().show(ctx, |ui| {
+        ui.horizontal(|ui| {
+            if ui.button("Back").clicked() {
+                next = Some(back);
+            }
+            ui.heading("Settings");
+        });
+        ui.separator();
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            let settings = &mut state.settings;
+            let earcons = &mut state.earcons;
+            let moves = state.journal.commands();
+            let snapshot = DebugSnapshot::new(&state.board)
+                .secs(state.clock.secs)
+                .moves(&moves);
+            let started = state.journal.start().is_some();
+            let mut save_report = false;
+
+            section(ui, "Gameplay", &mut settings.gameplay, |ui, gameplay| {
+                ui.checkbox(&mut gameplay.no_guess, "No guessing by default")
+                    .on_hover_text("Only offered on boards up to 40,000 cells");
+                ui.checkbox(&mut gameplay.flag_limit, "Limit flags to the mine count")
+                    .on_hover_text("Applies from the next new game");
+                ui.checkbox(&mut gameplay.question_marks, "Question marks")
+                    .on_hover_text("Right click cycles flag, question mark, then clear");
+                ui.checkbox(
+                    &mut gameplay.open_on_number,
+                    "Open on number (beginner assist)",
+                )
+                .on_hover_text(
+                    "When a flag completes exactly one number, open that number's other \
+                         neighbours. Flags the numbers prove wrong never open anything, but a \
+                         flag that is wrong without being provably so still sets off a mine.",
+                );
+                ui.horizontal(|ui| {
+                    ui.label("Safe first click:");
+                    for radius in 0..=MAX_SAFE_RADIUS {
+                        let label = match radius {
+                            0 => "Just the cell".to_string(),
+                            r => format!("{0}×{0}", 2 * r + 1),
+                        };
+                        ui.radio_value(&mut gameplay.safe_radius, radius, label);
+                    }
+                })
+                .response
+                .on_hover_text("Shrinks on boards too crowded for it");
+                ui.horizontal(|ui| {
+                    ui.label("Open on start:");
+                    for choice in AutoOpen::ALL {
+                        ui.radio_value(&mut gameplay.auto_open, choice, choice.label());
+                    }
+                })
+                .response
+                .on_hover_text(
+                    "Make the first click for you as the game starts. \
+                     Best opening reads the layout, so wins with it are marked",
+                );
+                ui.checkbox(&mut gameplay.endgame_outlook, "Endgame outlook")
+                    .on_hover_text(
+                        "Once few cells are in question, show how many guesses are left and \
+                         the best chance of winning from there. It reads nothing hidden, but \
+                         wins with it shown are marked",
+                    );
+                ui.add_enabled(
+                    gameplay.endgame_outlook,
+                    egui::Slider::new(&mut gameplay.endgame_cells, 4..=MAX_ENDGAME_CELLS)
+                        .text("cells in question at most"),
+                );
+                ui.checkbox(&mut gameplay.suggestions, "Suggest difficulty changes")
+                    .on_hover_text("After a run of wins or a losing streak");
+                ui.checkbox(
+                    &mut gameplay.abandon_is_loss,
+                    "Abandoned games count as losses",
+                )
+                .on_hover_text(
+                    "A game given up for a new one, another difficulty or quitting \
+                         without saving goes into your history as a loss",
+                );
+                ui.checkbox(
+                    &mut gameplay.confirm_abandon,
+                    "Ask before abandoning a game",
+                );
+            });
+
+            section(
+                ui,
+                "Appearance",
+                &mut settings.appearance,
+                |ui, appearance| {
+                    ui.horizontal(|ui| {
+                        ui.label("Theme:");
+                        for theme in Theme::ALL {
+                            ui.radio_value(&mut appearance.theme, theme, theme.label());
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        let board = match &appearance.palette {
+                            Some(palette) => palette.name.as_str(),
+                            None => "from the theme",
+                        };
+                        ui.label(format!("Board colours: {board}"));
+                        if ui
+                            .button("Edit themes…")
+                            .on_hover_text("Built-in, your own and imported board colours")
+                            .clicked()
+                        {
+                            next = Some(Screen::Themes);
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Markers:");
+                        for choice in MarkerChoice::ALL {
+                            ui.radio_value(&mut appearance.markers, choice, choice.label());
+                        }
+                    });
+                    if appearance.markers == MarkerChoice::Custom {
+                        custom_markers(ui, &mut appearance.custom_markers);
+                    }
+                    ui.horizontal(|ui| {
+                        ui.label("Counts:");
+                        for style in CountStyle::ALL {
+                            ui.radio_value(&mut appearance.counts, style, style.label());
+                        }
+                    })
+                    .response
+                    .on_hover_text("Pips and dots are quicker to read on small cells for some");
+                    ui.horizontal(|ui| {
+                        ui.label("Wins:");
+                        for style in WinStyle::ALL {
+                            ui.radio_value(&mut appearance.win_effect, style, style.label());
+                        }
+                    })
+                    .response
+                    .on_hover_text("With animations off, every win is quiet");
+                },
+            );
+
+            section(ui, "Controls", &mut settings.controls, |ui, controls| {
+                ui.checkbox(
+                    &mut controls.swap_buttons,
+                    "Swap mouse buttons (left click flags)",
+                );
+                ui.checkbox(&mut controls.quick_entry, "Quick entry by cell name")
+                    .on_hover_text(
+                        "Type a cell as the coordinates name it and press Enter to open it: \
+                         B7, or FB7 to flag it. Backspace and Escape correct it. Letters \
+                         go to the cell name, so H no longer asks for a hint; F on its \
+                         own still flags at the cursor of a focused board",
+                    );
+            });
+
+            section(
+                ui,
+                "Accessibility",
+                &mut settings.accessibility,
+                |ui, accessibility| {
+                    ui.checkbox(&mut accessibility.animations, "Animations");
+                    ui.checkbox(
+                        &mut accessibility.follow_focus,
+                        "Keep the keyboard cursor in view",
+                    )
+                    .on_hover_text(
+                        "Scroll the board as the cursor moves. Tab to the board or click it, \
+                         then use the arrows; Ctrl+arrows scroll a screenful, Home and End \
+                         jump to the corners and Ctrl+plus or minus zoom around the cursor.",
+                    );
+                    ui.add_enabled(
+                        accessibility.follow_focus,
+                        egui::Slider::new(&mut accessibility.focus_margin, 0..=MAX_FOCUS_MARGIN)
+                            .text("cells of margin"),
+                    );
+                    ui.checkbox(&mut accessibility.earcons, "Earcons")
+                        .on_hover_text(
+                            "Tones for each move: a number's pitch rises with its count, a \
+                             cascade sweeps longer the more it opens, flags rise going on and \
+                             fall coming off, and a refused or impossible move buzzes",
+                        );
+                    ui.add_enabled_ui(accessibility.earcons, |ui| {
+                        ui.horizontal(|ui| {
+                            ui.checkbox(&mut accessibility.earcon_pan, "Pan to the cell's column");
+                            if ui.button("🔊 Test").clicked() {
+                                earcons.demo(accessibility.earcon_pan);
+                            }
+                        });
+                    });
+                },
+            );
+
+            section(ui, "Advanced", &mut settings.advanced, |ui, advanced| {
+                ui.checkbox(&mut advanced.large_boards, "Experimental large boards");
+                ui.checkbox(&mut advanced.show_log, "Log window");
+                ui.horizontal(|ui| {
+                    ui.label("Webhook URL:");
+                    ui.text_edit_singleline(&mut advanced.webhook.url)
+                        .on_hover_text(
+                            "Finished games are posted here as JSON, e.g. to a chat bot",
+                        );
+                });
+                ui.horizontal(|ui| {
+                    let webhook = &mut advanced.webhook;
+                    ui.label("Post:");
+                    ui.checkbox(&mut webhook.wins, "Wins");
+                    ui.checkbox(&mut webhook.bests, "Personal bests");
+                    ui.checkbox(&mut webhook.losses, "Losses");
+                });
+                let leaderboard = &mut advanced.leaderboard;
+                ui.checkbox(&mut leaderboard.enabled, "Online leaderboards")
+                    .on_hover_text(
+                        "Wins on the presets are sent to the server below with your display \
+                         name, time, 3BV, seed and board fingerprints. Nothing else is sent.",
+                    );
+                ui.add_enabled_ui(leaderboard.enabled, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Server:");
+                        ui.text_edit_singleline(&mut leaderboard.server);
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Display name:");
+                        ui.text_edit_singleline(&mut leaderboard.name);
+                    });
+                });
+                ui.checkbox(&mut advanced.crash_reports, "Save crash reports")
+                    .on_hover_text(
+                        "If the game crashes, write what went wrong and the board, with the \
+                         mines left out, to the crashes folder under the data folder. Nothing \
+                         is sent; the next run offers to open the folder or copy the report.",
+                    );
+                if ui
+                    .button("Copy debug snapshot")
+                    .on_hover_text(
+                        "The current game as text for a bug report: the board, its state and \
+                         the last moves. Where the mines are stays out until the game is over.",
+                    )
+                    .clicked()
+                {
+                    ctx.copy_text(snapshot.to_string());
+                }
+                save_report = ui
+                    .add_enabled(started, egui::Button::new("Save bug report bundle"))
+                    .on_hover_text(
+                        "Everything needed to replay the current game, in a zip under the data \
+                         folder: the first board, every move and the last events. Attach it to \
+                         a bug report; the reproduce subcommand plays it back. Also \
+                         Ctrl+Shift+B in a game.",
+                    )
+                    .on_disabled_hover_text("A game has to be under way")
+                    .clicked();
+            });
+            if save_report {
+                state.save_bug_report();
+            }
+            egui::CollapsingHeader::new("Advanced: move data")
+                .default_open(true)
+                .show(ui, |ui| data_transfer(ui, state));
+            egui::CollapsingHeader::new("Advanced: saved races")
+                .default_open(true)
+                .show(ui, |ui| saved_races(ui, state));
+        });
+    });
+
+    if state.settings.appearance != before.appearance
+        || state.settings.accessibility != before.accessibility
+    {
+        state.settings.apply(ctx);
+    }
+    if !state.settings.accessibility.animations {
+        state.particles.clear();
+    }
+
+    next
+}
+
+/// A box per marker for any single character. One that isn't a single
+/// character is refused and the classic marker used; one the font can't
+/// draw is drawn as a shape on the board.
+fn custom_markers(ui: &mut egui::Ui, texts: &mut [String; 3]) {
+    for (marker, text) in Marker::ALL.into_iter().zip(texts) {
+        ui.horizontal(|ui| {
+            ui.label(match marker {
+                Marker::Flag => "Flag:",
+                Marker::Question => "Question mark:",
+                Marker::Mine => "Mine:",
+            });
+            ui.add(egui::TextEdit::singleline(text).desired_width(40.0));
+            let mut check = Markers::default();
+            if text.trim().is_empty() {
+                ui.weak("classic");
+            } else if let Err(err) = check.set(marker, text.trim()) {
+                ui.colored_label(egui::Color32::LIGHT_RED, err.to_string());
+            } else if !widgets::can_draw(ui.ctx(), text.trim()) {
+                ui.weak("not in the font; drawn as a shape");
+            }
+        });
+    }
+}
+
+/// Export every profile's data to a file, or read one and merge it in or
+/// replace with it. Nothing is written until one of those is picked.
+fn data_transfer(ui: &mut egui::Ui, state: &mut AppState) {
+    let transfer = &mut state.data_transfer;
+    ui.horizontal(|ui| {
+        ui.label("File:");
+        ui.add(
+            egui::TextEdit::singleline(&mut transfer.path).hint_text("bosnia-simulator-data.json"),
+        );
+    });
+    let path = PathBuf::from(transfer.path.trim());
+    let mut export = false;
+    ui.horizontal(|ui| {
+        export = ui
+            .button("Export data…")
+            .on_hover_text(
+                "Settings, history, saved games and race results of every profile, in one file",
+            )
+            .clicked();
+        if ui.button("Import data…").clicked() {
+            match bundle::load(&path) {
+                Ok(import) => transfer.open(import),
+                Err(err) => transfer.report = vec![format!("Couldn't import: {err}")],
+            }
+        }
+    });
+    if export {
+        let done = match state.export_data(&path) {
+            Ok(()) => format!("Exported to {}", path.display()),
+            Err(err) => format!("Couldn't export: {err}"),
+        };
+        state.data_transfer.report = vec![done];
+    }
+
+    if let Some(import) = &state.data_transfer.pending {
+        ui.label(format!("Ready to import {}.", import.summary()));
+        let mut mode = None;
+        ui.horizontal(|ui| {
+            if ui
+                .button("Merge")
+                .on_hover_text("Add the history to yours; keep your settings and saved games")
+                .clicked()
+            {
+                mode = Some(Mode::Merge);
+            }
+            if ui
+                .button("Replace")
+                .on_hover_text("Overwrite same-named profiles with the file's")
+                .clicked()
+            {
+                mode = Some(Mode::Replace);
+            }
+            if ui.button("Cancel").clicked() {
+                state.data_transfer.pending = None;
+            }
+        });
+        let chosen = mode.and_then(|mode| Some((mode, state.data_transfer.pending.take()?)));
+        if let Some((mode, import)) = chosen {
+            let ctx = ui.ctx().clone();
+            state.data_transfer.report = state.import_data(&ctx, import, mode);
+        }
+    }
+    for line in &state.data_transfer.report {
+        ui.label(line);
+    }
+}
+
+/// How much room saved race results take, and which of the player's own
+/// to keep: pruning runs after each race, or now from the button.
+fn saved_races(ui: &mut egui::Ui, state: &mut AppState) {
+    let Some(replays) = &mut state.replays else {
+        ui.weak("No data folder, so race results aren't saved.");
+        return;
+    };
+    let (results, bytes) = replays.usage();
+    ui.label(format!(
+        "{results} race result{} taking {:.1} KB",
+        if results == 1 { "" } else { "s" },
+        bytes as f64 / 1024.0
+    ));
+
+    let keep = &mut state.settings.advanced.keep_races;
+    ui.horizontal(|ui| {
+        ui.label("Keep:");
+        ui.radio_value(keep, Keep::All, "All");
+        let last = matches!(keep, Keep::Last(_));
+        if ui.radio(last, "The last").clicked() && !last {
+            *keep = Keep::Last(20);
+        }
+        if let Keep::Last(n) = keep {
+            ui.add(egui::DragValue::new(n).range(1..=1000));
+        }
+        ui.radio_value(keep, Keep::WinsAndBests, "Wins and bests")
+            .on_hover_text("Races with every board won, and the fastest at each size");
+    });
+    let player = state.profiles.current().to_string();
+    if ui
+        .add_enabled(*keep != Keep::All, egui::Button::new("Prune now"))
+        .on_hover_text("Only this profile's results are pruned")
+        .clicked()
+    {
+        if let Err(err) = replays.prune(&player, *keep) {
+            state.toasts.error("couldn't prune race results", &err);
+        }
+    }
+}
+
+/// A collapsible group with a "Restore defaults" button for just its fields.
+fn section<T: Default>(
+    ui: &mut egui::Ui,
+    title: &str,
+    values: &mut T,
+    contents: impl FnOnce(&mut egui::Ui, &mut T),
+) {
+    egui::CollapsingHeader::new(title)
+        .default_open(true)
+        .show(ui, |ui| {
+            contents(ui, values);
+            if ui.small_button("Restore defaults").clicked() {
+                *values = T::default();
+            }
+        });
+}
@@ -0,0 +1,156 @@
+//! Watching a game served over a socket (`--serve` in the terminal
+//! example, or `minesweeper_rs::serve`) without playing: every seat's
+//! board side by side, scaled down to fit, each with how much of it is
+//! open and its clock.
+
+use super::Screen;
+use crate::app::AppState;
+use crate::net::spectate::{SeatBoard, Watch};
+use crate::widgets::{self, GlyphScale};
+use eframe::egui::{self, Color32, RichText};
+use minesweeper_rs::format;
+use minesweeper_rs::render::Marker;
+use std::path::Path;
+
+/// Largest cell drawn; small boards don't need to fill the screen.
+const MAX_CELL: f32 = 18.0;
+
+/// Everything the spectate screen keeps between frames.
+#[derive(Default)]
+pub struct SpectateScreen {
+    pub path: String, // the socket being watched, or to watch
+    pub watch: Option<Watch>,
+    pub message: Option<String>, // why connecting failed
+}
+
+impl SpectateScreen {
+    /// Start watching the socket at `path`, or say why not.
+    pub fn connect(&mut self, wake: impl Fn() + Send + 'static) {
+        match Watch::connect(Path::new(self.path.trim()), wake) {
+            Ok(watch) => (self.watch, self.message) = (Some(watch), None),
+            Err(err) => self.message = Some(format!("Can't connect: {err}")),
+        }
+    }
+}
+
+pub fn show(ctx: &egui::Context, state: &mut AppState) -> Option<Screen> {
+    let mut next = None;
+
+    egui::CentralPanel::default().show(ctx, |ui| {
+        ui.horizontal(|ui| {
+            ui.heading("Spectate");
+            if ui.button("Back to Menu").clicked() {
+                state.spectate.watch = None;
+                next = Some(Screen::Menu);
+            }
+        });
+        ui.add_space(8.0);
+
+        let wake = state.waker(ui.ctx());
+        let screen = &mut state.spectate;
+        ui.horizontal(|ui| {
+            ui.label("Socket:");
+            let editable = screen.watch.is_none();
+            ui.add_enabled(editable, egui::TextEdit::singleline(&mut screen.path));
+            if screen.watch.is_some() {
+                if ui.button("Stop watching").clicked() {
+                    screen.watch = None;
+                }
+            } else if ui.button("Watch").clicked() {
+                screen.connect(wake);
+            }
+        });
+        if let Some(message) = &screen.message {
+            ui.colored_label(Color32::LIGHT_RED, message);
+        }
+        let Some(watch) = &mut screen.watch else {
+            ui.weak("Enter the socket a game is served on to watch every board live.");
+            return;
+        };
+        let now = ui.input(|i| i.time);
+        watch.poll(now);
+        if let Some(ended) = &watch.ended {
+            ui.weak(format!("Stopped: {ended}"));
+        }
+        ui.separator();
+        if watch.seats.is_empty() {
+            ui.spinner();
+            return;
+        }
+
+        let gap = 16.0;
+        let count = watch.seats.len() as f32;
+        let each = (ui.available_width() - gap * (count - 1.0)) / count;
+        let mut running = false;
+        ui.horizontal_top(|ui| {
+            ui.spacing_mut().item_spacing.x = gap;
+            for (seat, watched) in &watch.seats {
+                let board = &watched.board;
+                running |= !board.is_over();
+                ui.vertical(|ui| {
+                    ui.set_width(each);
+                    let result = match board.state.as_str() {
+                        "won" => RichText::new("won").color(Color32::GREEN),
+                        "lost" => RichText::new("lost").color(Color32::LIGHT_RED),
+                        _ => RichText::new("playing"),
+                    };
+                    ui.horizontal(|ui| {
+                        ui.strong(format!("Seat {}", seat + 1));
+                        ui.label(result);
+                        ui.monospace(format::clock_tenths(watched.secs(now)));
+                    });
+                    let (open, safe) = board.progress();
+                    let share = open as f32 / safe.max(1) as f32;
+                    ui.add(
+                        egui::ProgressBar::new(share)
+                            .desired_width(each)
+                            .text(format!("{open} / {safe} safe cells")),
+                    );
+                    ui.weak(format!("{} of {} mines flagged", board.flags, board.mines));
+                    mini_board(ui, board, each);
+                });
+            }
+        });
+        if running && watch.ended.is_none() {
+            // the clocks tick between boards
+            ui.ctx()
+                .request_repaint_after(std::time::Duration::from_millis(100));
+        }
+    });
+
+    next
+}
+
+/// `board` drawn no wider than `width`, its cells as the server's glyphs,
+/// sized and simplified by the board widget's rules.
+fn mini_board(ui: &mut egui::Ui, board: &SeatBoard, width: f32) {
+    let cell = (width / board.width.max(1) as f32).min(MAX_CELL);
+    let size = egui::vec2(board.width as f32, board.height as f32) * cell;
+    let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    let visuals = ui.visuals();
+    let scale = GlyphScale::of(cell - 1.0);
+    for (y, row) in board.rows.iter().enumerate() {
+        for (x, glyph) in row.chars().enumerate() {
+            let square = widgets::cell_rect(rect.min, cell, x, y).shrink(0.5);
+            let fill = match glyph {
+                '░' | '⚑' | '?' => visuals.widgets.inactive.bg_fill,
+                '*' => Color32::from_rgb(220, 40, 40),
+                _ => visuals.extreme_bg_color,
+            };
+            painter.rect_filled(square, 1.0, fill);
+            let color = visuals.text_color();
+            let marker = match glyph {
+                '⚑' => Some(Marker::Flag),
+                '?' => Some(Marker::Question),
+                '*' => Some(Marker::Mine),
+                _ => None,
+            };
+            if let Some(marker) = marker {
+                widgets::paint_marker(&painter, square, marker, color);
+            } else if !matches!(glyph, '░' | ' ') {
+                widgets::centered_text(&painter, square, &glyph.to_string(), scale.font, color);
+            }
+        }
+    }
+}
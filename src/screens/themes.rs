@@ -0,0 +1,218 @@
+//! Board themes: picking one, and a colour editor that previews every kind
+//! of cell as it changes. The built-in themes can only be copied; the
+//! player's own are saved as theme files under the data dir (see
+//! `crate::themes`), and a file from someone else can be imported.
+
+use super::Screen;
+use crate::app::AppState;
+use crate::themes::{self, Palette, Rgb};
+use crate::widgets;
+use eframe::egui;
+use std::path::PathBuf;
+
+/// Everything the theme screen keeps between frames.
+#[derive(Default)]
+pub struct ThemeEditor {
+    saved: Option<Vec<Palette>>, // the player's themes, read again when None
+    draft: Option<Palette>,      // the theme being edited
+    import: String,              // the path of a theme file to import
+}
+
+impl ThemeEditor {
+    /// Forget the player's themes, so they are read again next frame.
+    fn reload(&mut self) {
+        self.saved = None;
+    }
+}
+
+pub fn show(ctx: &egui::Context, state: &mut AppState) -> Option<Screen> {
+    let mut next = None;
+    if state.theme_editor.saved.is_none() {
+        let (saved, failed) = match &state.data_dir {
+            Some(dir) => themes::load_all(dir),
+            None => (Vec::new(), Vec::new()),
+        };
+        for (path, err) in failed {
+            state
+                .toasts
+                .error_at("couldn't read the theme", &path, &err);
+        }
+        state.theme_editor.saved = Some(saved);
+    }
+    let before = state.settings.appearance.palette.clone();
+
+    egui::CentralPanel::default().show(ctx, |ui| {
+        ui.horizontal(|ui| {
+            if ui.button("Back").clicked() {
+                next = Some(Screen::Settings);
+            }
+            ui.heading("Board themes");
+        });
+        ui.separator();
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            pick(ui, state);
+            ui.separator();
+            import(ui, state);
+            if state.theme_editor.draft.is_some() {
+                ui.separator();
+                edit(ui, state);
+            }
+        });
+    });
+
+    if state.settings.appearance.palette != before {
+        state.settings.apply(ctx);
+    }
+    next
+}
+
+/// The themes to pick from, each with a preview, and the way back to the
+/// board following egui's light or dark look.
+fn pick(ui: &mut egui::Ui, state: &mut AppState) {
+    let active = &mut state.settings.appearance.palette;
+    ui.radio_value(active, None, "Board colours from the light or dark theme");
+    let saved = state.theme_editor.saved.as_deref().unwrap_or_default();
+    let built_in = Palette::built_in();
+    let mut copy = None;
+    for theme in built_in.iter().chain(saved) {
+        ui.horizontal(|ui| {
+            let picked = active.as_ref().is_some_and(|p| p.name == theme.name);
+            if ui.radio(picked, &theme.name).clicked() {
+                *active = Some(theme.clone());
+            }
+            if Palette::is_built_in(&theme.name) {
+                if ui.small_button("Copy to edit").clicked() {
+                    copy = Some(Palette {
+                        name: format!("{} copy", theme.name),
+                        ..theme.clone()
+                    });
+                }
+            } else if ui.small_button("Edit").clicked() {
+                copy = Some(theme.clone());
+            }
+        });
+        widgets::palette_preview(ui, theme);
+    }
+    if copy.is_some() {
+        state.theme_editor.draft = copy;
+    }
+}
+
+/// A theme file typed in by path, checked and saved among the player's
+/// themes.
+fn import(ui: &mut egui::Ui, state: &mut AppState) {
+    let Some(dir) = state.data_dir.clone() else {
+        ui.weak("Themes are saved under the data folder, which isn't available");
+        return;
+    };
+    ui.horizontal(|ui| {
+        ui.label("Theme file:");
+        ui.text_edit_singleline(&mut state.theme_editor.import);
+        let typed = !state.theme_editor.import.trim().is_empty();
+        if !ui.add_enabled(typed, egui::Button::new("Import")).clicked() {
+            return;
+        }
+        let path = PathBuf::from(state.theme_editor.import.trim());
+        let saved = themes::load(&path).map(|theme| (theme.save(&dir), theme.name));
+        match saved {
+            Ok((Ok(_), name)) => {
+                state.toasts.notice("Imported the theme", name);
+                state.theme_editor.import.clear();
+                state.theme_editor.reload();
+            }
+            Ok((Err(err), _)) => {
+                let to = dir.join(themes::DIR);
+                state.toasts.error_at("couldn't save the theme", &to, &err)
+            }
+            Err(err) => state
+                .toasts
+                .error_at("couldn't import the theme", &path, &err),
+        }
+    });
+}
+
+/// The theme being edited: its name, a button per colour and the preview.
+fn edit(ui: &mut egui::Ui, state: &mut AppState) {
+    let Some(draft) = &mut state.theme_editor.draft else {
+        return;
+    };
+    ui.horizontal(|ui| {
+        ui.label("Name");
+        ui.text_edit_singleline(&mut draft.name);
+    });
+    egui::Grid::new("theme_colours")
+        .num_columns(2)
+        .show(ui, |ui| {
+            let colour = |ui: &mut egui::Ui, label: &str, rgb: &mut Rgb| {
+                ui.label(label);
+                ui.color_edit_button_srgb(&mut rgb.0);
+                ui.end_row();
+            };
+            colour(ui, "Background", &mut draft.background);
+            colour(ui, "Hidden cell", &mut draft.hidden);
+            colour(ui, "Hovered cell", &mut draft.hovered);
+            colour(ui, "Pressed cell", &mut draft.pressed);
+            colour(ui, "Open cell", &mut draft.open);
+            colour(ui, "Text and outlines", &mut draft.text);
+            colour(ui, "Flag", &mut draft.flag);
+            colour(ui, "Mine", &mut draft.mine);
+            colour(ui, "Mine that went off", &mut draft.exploded);
+            colour(ui, "Keyboard cursor", &mut draft.highlight);
+            ui.label("Counts 1 to 8");
+            ui.horizontal(|ui| {
+                for rgb in &mut draft.numbers {
+                    ui.color_edit_button_srgb(&mut rgb.0);
+                }
+            });
+            ui.end_row();
+        });
+    widgets::palette_preview(ui, draft);
+
+    let name = draft.name.trim().to_string();
+    let problem = if name.is_empty() {
+        Some("It needs a name")
+    } else if Palette::is_built_in(&name) {
+        Some("That name is taken by a built-in theme")
+    } else {
+        None
+    };
+    let mut save = false;
+    let mut close = false;
+    ui.horizontal(|ui| {
+        save = state.data_dir.is_some()
+            && ui
+                .add_enabled(problem.is_none(), egui::Button::new("Save"))
+                .on_disabled_hover_text(problem.unwrap_or_default())
+                .clicked();
+        close = ui.button("Close").clicked();
+    });
+    if close {
+        state.theme_editor.draft = None;
+    }
+    let (Some(dir), Some(mut theme)) = (state.data_dir.clone(), state.theme_editor.draft.clone())
+    else {
+        return;
+    };
+    if !save {
+        return;
+    }
+    theme.name = name;
+    match theme.save(&dir) {
+        Ok(path) => {
+            state
+                .toasts
+                .notice("Saved the theme", path.display().to_string());
+            // the theme in use follows its edits
+            let active = &mut state.settings.appearance.palette;
+            if active.as_ref().is_some_and(|p| p.name == theme.name) {
+                *active = Some(theme);
+            }
+            state.theme_editor.reload();
+        }
+        Err(err) => {
+            let to = dir.join(themes::DIR);
+            state.toasts.error_at("couldn't save the theme", &to, &err)
+        }
+    }
+}
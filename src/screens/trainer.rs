@@ -0,0 +1,223 @@
+//! The probability trainer: a position from partway through a game, two of
+//! its hidden cells picked out, and the question of which is safer. A
+//! click on either answers; then both chances are shown and the answer
+//! goes into the history, where a run of right answers moves the questions
+//! on to closer chances and misses move them back (see `history::Trainer`).
+//!
+//! Positions take a moment to play out, so a background thread keeps a
+//! couple ready for the level being played.
+
+use super::Screen;
+use crate::app::AppState;
+use crate::coach::cell_name;
+use crate::widgets::{self, BoardView, CellClick, Highlight};
+use eframe::egui::{self, Color32};
+use minesweeper_rs::trainer::{self, Question, GAPS};
+use std::collections::VecDeque;
+use std::sync::mpsc;
+
+/// The board questions are asked on: width, height, mines.
+const SIZE: (usize, usize, usize) = (16, 16, 40);
+
+/// Questions kept ready for the level being played.
+const AHEAD: usize = 2;
+
+/// Seeds tried for one question before the thread gives the level up.
+const TRIES: u64 = 500;
+
+/// Everything the trainer keeps between frames.
+pub struct TrainerScreen {
+    question: Option<Question>,
+    picked: Option<usize>, // which of the question's cells, once answered
+    ready: Vec<VecDeque<Question>>, // per level
+    making: Option<mpsc::Receiver<(usize, Option<Question>)>>,
+    seed: u64, // the next seed to try
+    view: BoardView,
+}
+
+impl Default for TrainerScreen {
+    fn default() -> Self {
+        TrainerScreen {
+            question: None,
+            picked: None,
+            ready: vec![VecDeque::new(); GAPS.len()],
+            making: None,
+            seed: rand::random(),
+            view: BoardView::default(),
+        }
+    }
+}
+
+impl TrainerScreen {
+    /// Take in a question the thread has made, and start it on another if
+    /// fewer than `AHEAD` are ready at `level`. `wake` is called when the
+    /// next one is done.
+    pub fn poll(&mut self, level: usize, wake: impl Fn() + Send + 'static) {
+        if let Some(making) = &self.making {
+            match making.try_recv() {
+                Ok((made, question)) => {
+                    self.making = None;
+                    if let Some(question) = question {
+                        self.ready[made].push_back(question);
+                    }
+                }
+                Err(mpsc::TryRecvError::Empty) => return,
+                Err(mpsc::TryRecvError::Disconnected) => self.making = None,
+            }
+        }
+        if self.ready[level].len() >= AHEAD {
+            return;
+        }
+        let (tx, rx) = mpsc::channel();
+        let seed = self.seed;
+        self.seed = self.seed.wrapping_add(TRIES);
+        std::thread::spawn(move || {
+            let (width, height, mines) = SIZE;
+            let question = (seed..seed.wrapping_add(TRIES))
+                .find_map(|seed| trainer::generate(width, height, mines, seed, GAPS[level]));
+            let _ = tx.send((level, question));
+            wake();
+        });
+        self.making = Some(rx);
+    }
+
+    /// Move on to a ready question at `level`, if there is one yet.
+    pub fn next(&mut self, level: usize) {
+        self.picked = None;
+        self.question = self.ready[level].pop_front();
+    }
+
+    /// Answer with the cell at (x, y), if it is one of the two asked about
+    /// and the question is still open. Returns whether it was the safer.
+    pub fn answer(&mut self, x: usize, y: usize) -> Option<bool> {
+        let question = self.question.as_ref().filter(|_| self.picked.is_none())?;
+        let picked = question.cells.iter().position(|&cell| cell == (x, y))?;
+        self.picked = Some(picked);
+        Some(picked == question.safer())
+    }
+}
+
+pub fn show(ctx: &egui::Context, state: &mut AppState) -> Option<Screen> {
+    let mut next = None;
+
+    egui::CentralPanel::default().show(ctx, |ui| {
+        ui.horizontal(|ui| {
+            ui.heading("Probability trainer");
+            if ui.button("Back to Menu").clicked() {
+                next = Some(Screen::Menu);
+            }
+        });
+        let record = &state.history.trainer;
+        let level = record.level;
+        ui.label(format!(
+            "Level {} of {}: the two chances are at least {:.0} points apart",
+            level + 1,
+            GAPS.len(),
+            GAPS[level].0 * 100.0
+        ));
+        match (record.accuracy(), record.recent_accuracy()) {
+            (Some(all), Some(recent)) => ui.weak(format!(
+                "{} answered · {all:.0}% right · {recent:.0}% of the last {}",
+                record.answered,
+                record.recent.len()
+            )),
+            _ => ui.weak("No answers yet."),
+        };
+        ui.separator();
+
+        let wake = state.waker(ui.ctx());
+        let screen = &mut state.trainer;
+        screen.poll(level, wake);
+        if screen.question.is_none() {
+            screen.next(level);
+        }
+        let Some(question) = &screen.question else {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label("Setting up a position…");
+            });
+            return;
+        };
+
+        let mut moving_on = false;
+        match screen.picked {
+            None => {
+                ui.label("Which highlighted cell is safer? Click it.");
+            }
+            Some(picked) => {
+                let right = picked == question.safer();
+                let (text, color) = if right {
+                    ("Right!", Color32::GREEN)
+                } else {
+                    ("Not this time.", Color32::LIGHT_RED)
+                };
+                ui.horizontal(|ui| {
+                    ui.colored_label(color, text);
+                    for (&cell, chance) in question.cells.iter().zip(question.chances) {
+                        ui.label(format!(
+                            "{}: {:.1}% a mine",
+                            cell_name(cell),
+                            chance * 100.0
+                        ));
+                    }
+                    moving_on = ui.button("Next").clicked();
+                });
+            }
+        }
+        if moving_on {
+            screen.question = None;
+            return;
+        }
+        let highlight = Highlight::new(&question.board, &question.cells);
+        let response = widgets::board_widget(
+            ui,
+            &question.board,
+            &mut screen.view,
+            None,
+            Some(&highlight),
+            None,
+        );
+        let answer = response.click.map(CellClick::cell);
+        if let Some(right) = answer.and_then(|(x, y)| screen.answer(x, y)) {
+            state.history.trainer.answer(right);
+        }
+    });
+
+    next
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn questions_are_made_ahead_and_answered_once() {
+        let mut screen = TrainerScreen {
+            seed: 0,
+            ..TrainerScreen::default()
+        };
+        let started = Instant::now();
+        while screen.ready[0].len() < AHEAD {
+            assert!(started.elapsed() < Duration::from_secs(60));
+            screen.poll(0, || {});
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert!(screen.ready[1].is_empty());
+
+        screen.next(0);
+        let question = screen.question.clone().unwrap();
+        assert_eq!(screen.ready[0].len(), AHEAD - 1);
+        let other = (0..SIZE.0)
+            .map(|x| (x, 0))
+            .find(|cell| !question.cells.contains(cell))
+            .unwrap();
+        assert_eq!(screen.answer(other.0, other.1), None);
+
+        let (x, y) = question.cells[question.safer()];
+        assert_eq!(screen.answer(x, y), Some(true));
+        let (x, y) = question.cells[1 - question.safer()];
+        assert_eq!(screen.answer(x, y), None);
+        assert_eq!(screen.picked, Some(question.safer()));
+    }
+}
@@ -0,0 +1,82 @@
+//! The tutorial: the scripted board with a callout for each step.
+
+use super::Screen;
+use crate::app::{AppState, Discard};
+use crate::widgets::{self, Highlight};
+use eframe::egui;
+use minesweeper_rs::Difficulty;
+
+pub fn show(ctx: &egui::Context, state: &mut AppState) -> Option<Screen> {
+    let mut next = None;
+    let mut callout = None;
+
+    egui::CentralPanel::default().show(ctx, |ui| {
+        let tutorial = &mut state.tutorial;
+        ui.horizontal(|ui| {
+            ui.heading("Tutorial");
+            if ui.button("Back to Menu").clicked() {
+                next = Some(Screen::Menu);
+            }
+            if ui.button("Start over").clicked() {
+                *tutorial = Default::default();
+            }
+        });
+
+        let step = tutorial.step();
+        match step {
+            Some(step) => {
+                let (at, of) = tutorial.progress();
+                ui.label(format!("Step {at} of {of}"));
+                if step.anchor.is_none() {
+                    ui.label(step.say);
+                }
+            }
+            None => {
+                ui.colored_label(
+                    egui::Color32::GREEN,
+                    "You cleared it! You're ready to play.",
+                );
+                if ui.button("Play Beginner").clicked() {
+                    state.discard(Discard::Menu(Difficulty::Beginner));
+                    next = Some(Screen::Game);
+                }
+            }
+        }
+        let tutorial = &mut state.tutorial;
+        if let Some(hint) = tutorial.hint {
+            ui.colored_label(egui::Color32::LIGHT_RED, hint);
+        }
+        ui.separator();
+
+        let highlight = Highlight::new(&tutorial.board, step.map_or(&[], |s| s.highlight));
+        let response = widgets::board_widget(
+            ui,
+            &tutorial.board,
+            &mut tutorial.view,
+            None,
+            Some(&highlight),
+            None,
+        );
+        if let Some(click) = response.click {
+            let click = if state.settings.controls.swap_buttons && !response.keyed {
+                click.swapped()
+            } else {
+                click
+            };
+            tutorial.act(click);
+        }
+        // the step may have moved on with this click
+        callout = tutorial
+            .step()
+            .and_then(|s| s.anchor.map(|(x, y)| (response.cell_rect(x, y), s.say)));
+    });
+
+    if state.tutorial.is_done() {
+        state.settings.tutorial_done = true;
+    }
+    if let Some((target, text)) = callout {
+        widgets::callout(ctx, egui::Id::new("tutorial_callout"), target, text);
+    }
+
+    next
+}
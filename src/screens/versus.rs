@@ -0,0 +1,250 @@
+//! Local versus: the window split between two boards from one seed, one
+//! player on each side of the keyboard. The left board takes W, A, S and
+//! D to move its cursor, Q to open and E to flag; the right board takes
+//! the arrows, period and slash. Neither board is focused to get its
+//! keys: this screen reads both sets every frame and routes them, so the
+//! two players can press at once. A mouse click goes to the board it
+//! lands on.
+
+use super::Screen;
+use crate::app::AppState;
+use crate::settings::Geometry;
+use crate::versus::{Outcome, Versus};
+use crate::widgets::{self, BoardView, CellClick, CELL_SIZE, FOCUS_MARGIN};
+use eframe::egui::{self, Color32, Key, Modifiers};
+use minesweeper_rs::{format, Board, Difficulty};
+
+/// Room around the two boards for the header, banner and pane edges, in
+/// points.
+const CHROME: egui::Vec2 = egui::vec2(64.0, 200.0);
+
+/// The keys of one seat: up, left, down, right, open, flag.
+const KEYS: [[Key; 6]; 2] = [
+    [Key::W, Key::A, Key::S, Key::D, Key::Q, Key::E],
+    [
+        Key::ArrowUp,
+        Key::ArrowLeft,
+        Key::ArrowDown,
+        Key::ArrowRight,
+        Key::Period,
+        Key::Slash,
+    ],
+];
+
+/// The seats' names, left then right.
+const NAMES: [&str; 2] = ["Left", "Right"];
+
+/// Everything local versus keeps between frames.
+pub struct VersusScreen {
+    difficulty: Difficulty,
+    game: Option<Versus>,
+    views: [BoardView; 2],
+}
+
+impl Default for VersusScreen {
+    fn default() -> Self {
+        VersusScreen {
+            difficulty: Difficulty::Intermediate,
+            game: None,
+            views: [BoardView::steered(), BoardView::steered()],
+        }
+    }
+}
+
+impl VersusScreen {
+    /// Start a game at the screen's difficulty from a fresh seed, with
+    /// both cursors on the opened centre.
+    fn start(&mut self, now: f64) {
+        let game = Versus::new(self.difficulty, rand::random(), now);
+        let board = &game.seats[0].board;
+        let centre = (board.width() / 2, board.height() / 2);
+        self.views = [BoardView::steered(), BoardView::steered()];
+        for view in &mut self.views {
+            view.focus = Some(centre);
+        }
+        self.game = Some(game);
+    }
+}
+
+/// Grow the window to fit two Intermediate boards side by side; bigger
+/// boards scroll in their panes.
+pub fn enter(ctx: &egui::Context, state: &mut AppState) -> Screen {
+    state.versus.game = None;
+    let (width, height, _) = Difficulty::Intermediate.spec().params();
+    let boards = egui::vec2(2.0 * width as f32, height as f32) * CELL_SIZE + CHROME;
+    if let Some(now) = ctx.input(|i| Geometry::of(i.viewport())) {
+        let size = egui::Vec2::from(now.size).max(boards);
+        Geometry {
+            pos: None,
+            size: size.into(),
+        }
+        .apply(ctx);
+    }
+    Screen::Versus
+}
+
+/// The move `seat`'s keys ask for this frame, after moving its cursor.
+fn keys(
+    ctx: &egui::Context,
+    seat: usize,
+    view: &mut BoardView,
+    board: &Board,
+) -> Option<CellClick> {
+    let [up, left, down, right, open, flag] = KEYS[seat];
+    let mut click = None;
+    ctx.input_mut(|i| {
+        let before = view.focus;
+        for (key, dx, dy) in [(up, 0, -1), (left, -1, 0), (down, 0, 1), (right, 1, 0)] {
+            if i.consume_key(Modifiers::NONE, key) {
+                view.move_focus(dx, dy, board);
+            }
+        }
+        if view.focus != before {
+            view.follow(FOCUS_MARGIN);
+        }
+        if i.consume_key(Modifiers::NONE, open) {
+            click = view.focus.map(|(x, y)| CellClick::Reveal(x, y));
+        }
+        if i.consume_key(Modifiers::NONE, flag) {
+            click = view.focus.map(|(x, y)| CellClick::Flag(x, y));
+        }
+    });
+    click
+}
+
+pub fn show(ctx: &egui::Context, state: &mut AppState) -> Option<Screen> {
+    let mut next = None;
+    let now = ctx.input(|i| i.time);
+    let swap = state.settings.controls.swap_buttons;
+    let screen = &mut state.versus;
+
+    if let Some(game) = &mut screen.game {
+        for (seat, view) in screen.views.iter_mut().enumerate() {
+            if let Some(click) = keys(ctx, seat, view, &game.seats[seat].board) {
+                game.act(seat, click, now);
+            }
+        }
+        if game.outcome().is_none() {
+            ctx.request_repaint_after(std::time::Duration::from_millis(100));
+        }
+    }
+
+    egui::CentralPanel::default().show(ctx, |ui| {
+        ui.horizontal(|ui| {
+            ui.heading("Local versus");
+            if ui.button("Back to Menu").clicked() {
+                next = Some(Screen::Menu);
+            }
+        });
+        let Some(game) = &mut screen.game else {
+            ui.label(
+                "Two players, one keyboard, the same board each. Left: W A S D to \
+                 move, Q to open, E to flag. Right: arrows to move, . to open, / to \
+                 flag. First to clear their board wins; a mine locks that board \
+                 while the other plays on.",
+            );
+            ui.horizontal(|ui| {
+                for diff in Difficulty::PRESETS {
+                    ui.selectable_value(&mut screen.difficulty, diff, diff.label());
+                }
+            });
+            if ui.button("Start").clicked() {
+                screen.start(now);
+            }
+            return;
+        };
+
+        let mut rematch = false;
+        match (game.countdown(now), game.outcome()) {
+            (Some(left), _) => {
+                ui.label(egui::RichText::new(left.to_string()).size(32.0).strong());
+            }
+            (None, None) if now < game.go_at() + 1.0 => {
+                ui.label(egui::RichText::new("Go!").size(32.0).strong());
+            }
+            (None, None) => {
+                ui.label(egui::RichText::new("First to clear wins").size(32.0));
+            }
+            (None, Some(outcome)) => {
+                ui.horizontal(|ui| {
+                    banner(ui, game, outcome, now);
+                    rematch = ui.button("Rematch").clicked();
+                });
+            }
+        }
+        ui.separator();
+
+        ui.columns(2, |columns| {
+            for (seat, column) in columns.iter_mut().enumerate() {
+                column.push_id(seat, |ui| {
+                    let board = &game.seats[seat].board;
+                    ui.horizontal(|ui| {
+                        ui.strong(NAMES[seat]);
+                        ui.label(format!("{:.1}s", game.secs(seat, now)));
+                        widgets::mine_counter(ui, board, false);
+                        widgets::game_status(ui, board);
+                    });
+                    let view = &mut screen.views[seat];
+                    let response = widgets::board_widget(ui, board, view, None, None, None);
+                    if let Some(click) = response.click {
+                        let click = if swap { click.swapped() } else { click };
+                        game.act(seat, click, now);
+                    }
+                });
+            }
+        });
+        if rematch {
+            screen.start(now);
+        }
+    });
+
+    next
+}
+
+/// The results line: who won and in what time, and how the other did.
+fn banner(ui: &mut egui::Ui, game: &Versus, outcome: Outcome, now: f64) {
+    match outcome {
+        Outcome::Won { seat, secs } => {
+            let other = 1 - seat;
+            let board = &game.seats[other].board;
+            let how = if board.is_lost() {
+                format!(
+                    "went off at {}",
+                    format::clock_tenths(game.secs(other, now))
+                )
+            } else {
+                format!("had {:.0}% cleared", cleared(board))
+            };
+            ui.colored_label(
+                Color32::GREEN,
+                egui::RichText::new(format!(
+                    "{} wins in {}",
+                    NAMES[seat],
+                    format::clock_tenths(secs)
+                ))
+                .size(24.0),
+            );
+            ui.label(format!("{} {how}", NAMES[other]));
+        }
+        Outcome::BothLost => {
+            ui.colored_label(
+                Color32::LIGHT_RED,
+                egui::RichText::new("Both boards went off").size(24.0),
+            );
+            let times = [0, 1].map(|seat| {
+                format!(
+                    "{} at {}",
+                    NAMES[seat],
+                    format::clock_tenths(game.secs(seat, now))
+                )
+            });
+            ui.label(times.join(", "));
+        }
+    }
+}
+
+/// How much of `board`'s safe ground is open, as a percentage.
+fn cleared(board: &Board) -> f64 {
+    let safe = board.width() * board.height() - board.mine_count();
+    100.0 * board.revealed_safe() as f64 / safe.max(1) as f64
+}
@@ -0,0 +1,497 @@
+//! Games shared over a Unix socket, for scripts driving a long game,
+//! players racing on the same board, or someone looking in on either.
+//! Clients send the commands of [`session`] one per line; the server plays
+//! them in the order they arrive and answers with one JSON object per line:
+//!
+//! - `{"seat":0,"secs":12.5,"board":{...}}`, a seat's board as
+//!   `Session::to_json` gives it with the seconds since its first move, to
+//!   every client after each command that changes it. A client that joins
+//!   gets every seat's board first, so it starts from the game as it
+//!   stands;
+//! - `{"reply":"..."}`, to the sender alone, when there is something to
+//!   say, such as a hint or why a move was refused;
+//! - `{"role":"spectator"}` or `{"role":"player","seat":1}`, to the
+//!   sender alone, answering a `role` line.
+//!
+//! A client's first line may be `role spectator` or `role player`; without
+//! one it plays. Spectators get everything players do, but every line they
+//! send after the role is refused and never reaches a board, and they can
+//! come and go without the players noticing. In a co-op game
+//! ([`Server::run`]) every player plays the one board. In a race
+//! ([`Server::run_race`]) each player takes a seat of their own with their
+//! `role player` line or first move, in the order they arrive; once the
+//! seats are full, anyone else can only watch. A seat whose player hangs
+//! up is free for the next one.
+//!
+//! The server stops once every game is over, after sending the last board.
+//! A client that hangs up mid-line has that line dropped, not played.
+//!
+//! ```no_run
+//! use minesweeper_rs::serve::Server;
+//! use minesweeper_rs::session::Session;
+//! use minesweeper_rs::Board;
+//!
+//! let server = Server::bind("/tmp/minesweeper.sock")?;
+//! let mut session = Session::new(Board::with_seed(9, 9, 10, 1, None));
+//! server.run(&mut session)?;
+//! # Ok::<(), std::io::Error>(())
+//! ```
+//!
+//! [`Host`] does the same over any streams, for a transport of your own.
+//!
+//! [`session`]: crate::session
+
+use crate::session::{Command, Session};
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::Shutdown;
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Instant;
+
+/// What a client is in the game.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Role {
+    /// Sends moves, on the shared board or its own seat in a race.
+    Player,
+    /// Sees every board but can't touch any.
+    Spectator,
+}
+
+struct Client {
+    out: Box<dyn Write + Send>,
+    role: Role,
+    settled: bool, // a role or a move was sent, so the role is final
+    seat: Option<usize>,
+}
+
+/// One board being played, and its clock.
+struct Seat<'a> {
+    session: &'a mut Session,
+    player: Option<usize>, // in a race, the client playing it
+    started: Option<Instant>,
+    secs: Option<f64>, // stopped when the game ended
+}
+
+impl Seat<'_> {
+    fn secs(&self) -> f64 {
+        self.secs
+            .or_else(|| self.started.map(|at| at.elapsed().as_secs_f64()))
+            .unwrap_or(0.0)
+    }
+}
+
+/// The game side of a server, apart from how clients reach it: the
+/// protocol in the [module docs](self) played on any streams. Tell it who
+/// joins and leaves and each line they send; it writes the answers.
+///
+/// ```
+/// use minesweeper_rs::serve::Host;
+/// use minesweeper_rs::session::Session;
+/// use minesweeper_rs::Board;
+///
+/// let mut session = Session::new(Board::decode_layout("*.\n..\n")?);
+/// let mut host = Host::shared(&mut session);
+/// host.join(0, std::io::sink());
+/// host.line(0, "f 0 0");
+/// drop(host);
+/// assert_eq!(session.board().flags(), 1);
+/// # Ok::<(), minesweeper_rs::LayoutError>(())
+/// ```
+pub struct Host<'a> {
+    seats: Vec<Seat<'a>>,
+    race: bool,
+    clients: BTreeMap<usize, Client>,
+}
+
+impl<'a> Host<'a> {
+    /// Every player plays `session`.
+    pub fn shared(session: &'a mut Session) -> Host<'a> {
+        Host::new(std::slice::from_mut(session), false)
+    }
+
+    /// Each player plays a seat of their own.
+    pub fn race(seats: &'a mut [Session]) -> Host<'a> {
+        Host::new(seats, true)
+    }
+
+    fn new(sessions: &'a mut [Session], race: bool) -> Host<'a> {
+        let seats = sessions
+            .iter_mut()
+            .map(|session| Seat {
+                session,
+                player: None,
+                started: None,
+                secs: None,
+            })
+            .collect();
+        Host {
+            seats,
+            race,
+            clients: BTreeMap::new(),
+        }
+    }
+
+    /// Whether every board has been won or lost.
+    pub fn is_over(&self) -> bool {
+        self.seats.iter().all(|seat| seat.session.board().is_over())
+    }
+
+    /// Client `id` connected, to be answered on `out`. It gets every
+    /// seat's board straight away.
+    pub fn join(&mut self, id: usize, out: impl Write + Send + 'static) {
+        self.clients.insert(
+            id,
+            Client {
+                out: Box::new(out),
+                role: Role::Player,
+                settled: false,
+                seat: None,
+            },
+        );
+        for seat in 0..self.seats.len() {
+            let board = self.board(seat);
+            self.send(id, &board);
+        }
+    }
+
+    /// Client `id` hung up; a seat it held is free again.
+    pub fn leave(&mut self, id: usize) {
+        let seat = self.clients.remove(&id).and_then(|client| client.seat);
+        if let Some(seat) = seat.filter(|_| self.race) {
+            self.seats[seat].player = None;
+        }
+    }
+
+    /// Client `id` sent `line`: a role or a move.
+    pub fn line(&mut self, id: usize, line: &str) {
+        let Some(client) = self.clients.get(&id) else {
+            return;
+        };
+        let (role, settled) = (client.role, client.settled);
+        if let Some(name) = line.strip_prefix("role ") {
+            let reply = match name.trim() {
+                _ if settled => "the role is chosen by a client's first line".to_string(),
+                "spectator" => {
+                    self.settle(id, Role::Spectator, None);
+                    return self.send(id, "{\"role\":\"spectator\"}\n");
+                }
+                "player" => match self.take_seat(id) {
+                    Some(seat) => {
+                        self.settle(id, Role::Player, Some(seat));
+                        let ack = match self.race {
+                            true => format!("{{\"role\":\"player\",\"seat\":{seat}}}\n"),
+                            false => "{\"role\":\"player\"}\n".to_string(),
+                        };
+                        return self.send(id, &ack);
+                    }
+                    None => "every seat is taken; join as a spectator".to_string(),
+                },
+                other => format!("no role called {other}; try player or spectator"),
+            };
+            return self.reply(id, &reply);
+        }
+        if role == Role::Spectator {
+            return self.reply(id, "spectators can't play");
+        }
+        let Some(seat) = self.take_seat(id) else {
+            return self.reply(id, "every seat is taken, so you can only watch");
+        };
+        self.settle(id, Role::Player, Some(seat));
+
+        let playing = &mut self.seats[seat];
+        let revision = playing.session.board().revision();
+        let reply = match line.parse::<Command>() {
+            Ok(command) => playing.session.apply(command),
+            Err(err) => err.to_string(),
+        };
+        self.reply(id, &reply);
+        let playing = &mut self.seats[seat];
+        if playing.session.board().revision() != revision {
+            playing.started.get_or_insert_with(Instant::now);
+            if playing.session.board().is_over() && playing.secs.is_none() {
+                playing.secs = Some(playing.secs());
+            }
+            let board = self.board(seat);
+            let ids: Vec<usize> = self.clients.keys().copied().collect();
+            for id in ids {
+                self.send(id, &board);
+            }
+        }
+    }
+
+    /// The seat client `id` plays: its own, the shared board, or in a race
+    /// the first free one still in play.
+    fn take_seat(&mut self, id: usize) -> Option<usize> {
+        if let Some(seat) = self.clients.get(&id)?.seat {
+            return Some(seat);
+        }
+        if !self.race {
+            return Some(0);
+        }
+        let seat = self
+            .seats
+            .iter()
+            .position(|seat| seat.player.is_none() && !seat.session.board().is_over())?;
+        self.seats[seat].player = Some(id);
+        Some(seat)
+    }
+
+    fn settle(&mut self, id: usize, role: Role, seat: Option<usize>) {
+        if let Some(client) = self.clients.get_mut(&id) {
+            (client.role, client.settled, client.seat) = (role, true, seat);
+        }
+    }
+
+    fn board(&self, seat: usize) -> String {
+        let at = &self.seats[seat];
+        format!(
+            "{{\"seat\":{seat},\"secs\":{:.1},\"board\":{}}}\n",
+            at.secs(),
+            at.session.to_json()
+        )
+    }
+
+    fn reply(&mut self, id: usize, reply: &str) {
+        if !reply.is_empty() {
+            self.send(id, &format!("{{\"reply\":{}}}\n", json_string(reply)));
+        }
+    }
+
+    /// Write `message` to client `id`, letting it go if that fails.
+    fn send(&mut self, id: usize, message: &str) {
+        let Some(client) = self.clients.get_mut(&id) else {
+            return;
+        };
+        if client
+            .out
+            .write_all(message.as_bytes())
+            .and_then(|()| client.out.flush())
+            .is_err()
+        {
+            self.leave(id);
+        }
+    }
+}
+
+/// What the client threads tell the game loop.
+enum Event {
+    Joined(usize, UnixStream),
+    Line(usize, String),
+    Left(usize),
+}
+
+/// A socket a game can be served on. Dropping it removes the socket file.
+#[derive(Debug)]
+pub struct Server {
+    listener: UnixListener,
+    path: PathBuf,
+}
+
+impl Server {
+    /// Listen on `path`. A socket file left behind by a server that is
+    /// gone is removed first; one a server still answers on, or a file
+    /// that isn't a socket, is refused. Errors name the path, so a
+    /// permission problem says where.
+    pub fn bind(path: impl AsRef<Path>) -> io::Result<Server> {
+        let path = path.as_ref();
+        let context =
+            |err: io::Error| io::Error::new(err.kind(), format!("{}: {err}", path.display()));
+        match std::fs::symlink_metadata(path) {
+            Ok(meta) if !meta.file_type().is_socket() => {
+                return Err(context(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    "not a socket, so it was left alone",
+                )));
+            }
+            Ok(_) => match UnixStream::connect(path) {
+                Ok(_) => {
+                    return Err(context(io::Error::new(
+                        io::ErrorKind::AddrInUse,
+                        "another server is listening there",
+                    )));
+                }
+                Err(err) if err.kind() == io::ErrorKind::ConnectionRefused => {
+                    std::fs::remove_file(path).map_err(context)?;
+                }
+                Err(err) => return Err(context(err)),
+            },
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => return Err(context(err)),
+        }
+        let listener = UnixListener::bind(path).map_err(context)?;
+        Ok(Server {
+            listener,
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// Where the socket is.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Play `session` with whoever connects until the game is over, then
+    /// hang up on everyone.
+    pub fn run(&self, session: &mut Session) -> io::Result<()> {
+        self.serve(Host::shared(session))
+    }
+
+    /// Race on `seats`, one player to each, until every game is over, then
+    /// hang up on everyone.
+    pub fn run_race(&self, seats: &mut [Session]) -> io::Result<()> {
+        self.serve(Host::race(seats))
+    }
+
+    fn serve(&self, mut host: Host<'_>) -> io::Result<()> {
+        let (events, inbox) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let accepting = {
+            let listener = self.listener.try_clone()?;
+            let stop = Arc::clone(&stop);
+            thread::spawn(move || accept(listener, events, stop))
+        };
+
+        // kept to hang up on; the host writes through the other half
+        let mut streams: BTreeMap<usize, UnixStream> = BTreeMap::new();
+        while !host.is_over() {
+            let Ok(event) = inbox.recv() else { break };
+            match event {
+                Event::Joined(id, stream) => {
+                    if let Ok(writer) = stream.try_clone() {
+                        streams.insert(id, stream);
+                        host.join(id, writer);
+                    }
+                }
+                Event::Left(id) => {
+                    streams.remove(&id);
+                    host.leave(id);
+                }
+                Event::Line(id, line) => host.line(id, &line),
+            }
+        }
+
+        stop.store(true, Ordering::SeqCst);
+        let _ = UnixStream::connect(&self.path); // wakes the accept loop to see `stop`
+        let _ = accepting.join();
+        for stream in streams.values() {
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Hand each new connection to the game loop, with a thread reading its
+/// lines, until `stop` is set.
+fn accept(listener: UnixListener, events: mpsc::Sender<Event>, stop: Arc<AtomicBool>) {
+    for (id, stream) in listener.incoming().enumerate() {
+        if stop.load(Ordering::SeqCst) {
+            break;
+        }
+        let Ok(stream) = stream else { continue };
+        let Ok(reader) = stream.try_clone() else {
+            continue;
+        };
+        if events.send(Event::Joined(id, stream)).is_err() {
+            break;
+        }
+        let events = events.clone();
+        thread::spawn(move || read_lines(id, reader, events));
+    }
+}
+
+/// Pass on each whole line a client sends, then that it left.
+fn read_lines(id: usize, stream: UnixStream, events: mpsc::Sender<Event>) {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    loop {
+        line.clear();
+        match reader.read_line(&mut line) {
+            // a line cut off by a hang-up isn't played
+            Ok(_) if line.ends_with('\n') => {
+                let command = line.trim();
+                if !command.is_empty() && events.send(Event::Line(id, command.into())).is_err() {
+                    return;
+                }
+            }
+            _ => break,
+        }
+    }
+    let _ = events.send(Event::Left(id));
+}
+
+/// Join the game served at `path`: each line of `input` is sent as a
+/// command and everything the server sends is copied to `output`. When
+/// `input` ends, the answers still owed are copied before returning; the
+/// game goes on without this client.
+pub fn attach(
+    path: impl AsRef<Path>,
+    input: impl BufRead,
+    output: impl Write + Send + 'static,
+) -> io::Result<()> {
+    let stream = connect(path.as_ref())?;
+    let incoming = stream.try_clone()?;
+    let relay = thread::spawn(move || relay(incoming, output));
+    for line in input.lines() {
+        let line = line?;
+        if (&stream).write_all(format!("{line}\n").as_bytes()).is_err() {
+            break; // the server hung up
+        }
+    }
+    let _ = stream.shutdown(Shutdown::Write);
+    relay
+        .join()
+        .unwrap_or_else(|_| Err(io::Error::other("output thread panicked")))
+}
+
+/// Watch the game served at `path` as a spectator, copying everything the
+/// server sends to `output` until it hangs up.
+pub fn spectate(path: impl AsRef<Path>, output: impl Write) -> io::Result<()> {
+    let stream = connect(path.as_ref())?;
+    (&stream).write_all(b"role spectator\n")?;
+    relay(stream, output)
+}
+
+fn connect(path: &Path) -> io::Result<UnixStream> {
+    UnixStream::connect(path)
+        .map_err(|err| io::Error::new(err.kind(), format!("{}: {err}", path.display())))
+}
+
+/// Copy the server's lines to `output` as they come.
+fn relay(incoming: UnixStream, mut output: impl Write) -> io::Result<()> {
+    let mut incoming = BufReader::new(incoming);
+    let mut line = String::new();
+    while incoming.read_line(&mut line)? > 0 {
+        output.write_all(line.as_bytes())?;
+        output.flush()?;
+        line.clear();
+    }
+    Ok(())
+}
+
+/// `text` as a JSON string, quotes included.
+fn json_string(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + 2);
+    out.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if u32::from(c) < 0x20 => out.push_str(&format!("\\u{:04x}", u32::from(c))),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
@@ -0,0 +1,406 @@
+//! A game played through the commands of the terminal game
+//! (`cargo run --bin minesweeper-term`), one per line: `x y` reveals,
+//! `f x y` flags, `c` clears every flag, `ra` opens every unflagged cell
+//! once there is a flag per mine, `h` climbs the hint ladder and
+//! `analyze` reports on the board. A cell can be named as in the
+//! coordinate labels instead (`B7`, `f B7`). A [`Session`] carries them
+//! out and says what to tell the player, so the same game can be typed
+//! at, scripted or shared over a socket (see `serve`).
+//!
+//! ```
+//! use minesweeper_rs::session::{Command, Session};
+//! use minesweeper_rs::Board;
+//!
+//! let mut session = Session::new(Board::decode_layout("*..\n...\n..*\n")?);
+//! let reply = session.apply("2 0".parse().unwrap());
+//! assert_eq!(reply, "");
+//! assert_eq!(session.board().cell(2, 0).neighbor_mines(), Some(0));
+//! assert!("f 1".parse::<Command>().is_err());
+//! assert!(session.to_json().contains(r#""state":"playing""#));
+//! # Ok::<(), minesweeper_rs::LayoutError>(())
+//! ```
+
+use crate::analysis::AnalysisReport;
+use crate::hints::{Hint, HintLadder};
+use crate::objective::{Objective, Tracker, Verdict};
+use crate::odds::{self, Chance};
+use crate::render::{self, Glyph};
+use crate::{Board, CycleMode, Ignored, Marking, MarkingResult, RevealResult};
+use std::fmt;
+use std::str::FromStr;
+
+/// One line of the command language.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Command {
+    /// `x y` or `A1`: reveal a cell.
+    Reveal(usize, usize),
+    /// `f x y` or `f A1`: cycle a cell's flag.
+    Flag(usize, usize),
+    /// `c`: take every flag off.
+    ClearFlags,
+    /// `ra`: open every cell that isn't flagged, once there are as many
+    /// flags as mines. Wins if the flags are right and explodes if not.
+    RevealRemaining,
+    /// `h`: the next hint, stronger each time on the same position.
+    Hint,
+    /// `analyze`: how hard the board is from here.
+    Analyze,
+}
+
+/// A line that isn't a command.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommandError(pub String);
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "expected `x y`, `A1`, `f x y`, `f A1`, `c`, `ra`, `h` or `analyze`, not `{}`",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+impl FromStr for Command {
+    type Err = CommandError;
+
+    fn from_str(line: &str) -> Result<Command, CommandError> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            ["c"] => return Ok(Command::ClearFlags),
+            ["ra"] => return Ok(Command::RevealRemaining),
+            ["h"] => return Ok(Command::Hint),
+            ["analyze"] => return Ok(Command::Analyze),
+            _ => {}
+        }
+        let (flag, coords) = match words.as_slice() {
+            ["f", rest @ ..] => (true, rest),
+            rest => (false, rest),
+        };
+        let cell = match coords {
+            [name] => render::parse_cell_name(name),
+            [x, y] => x.parse().ok().zip(y.parse().ok()),
+            _ => None,
+        };
+        match cell {
+            Some((x, y)) if flag => Ok(Command::Flag(x, y)),
+            Some((x, y)) => Ok(Command::Reveal(x, y)),
+            None => Err(CommandError(line.trim().to_string())),
+        }
+    }
+}
+
+/// A command as it would be typed, naming cells as the coordinate labels
+/// do, so it reads back as the same command.
+///
+/// ```
+/// use minesweeper_rs::session::Command;
+///
+/// assert_eq!(Command::Flag(1, 6).to_string(), "f B7");
+/// assert_eq!("f B7".parse::<Command>(), Ok(Command::Flag(1, 6)));
+/// ```
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = |x, y: usize| format!("{}{}", render::column_name(x), y + 1);
+        match *self {
+            Command::Reveal(x, y) => f.write_str(&name(x, y)),
+            Command::Flag(x, y) => write!(f, "f {}", name(x, y)),
+            Command::ClearFlags => f.write_str("c"),
+            Command::RevealRemaining => f.write_str("ra"),
+            Command::Hint => f.write_str("h"),
+            Command::Analyze => f.write_str("analyze"),
+        }
+    }
+}
+
+/// Why a game in progress was given up rather than played out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AbandonReason {
+    /// A new game on the same settings.
+    NewGame,
+    /// A switch to another difficulty.
+    Difficulty,
+    /// Another game picked from the menu.
+    Menu,
+    /// The program was closed without keeping the game.
+    Quit,
+}
+
+impl AbandonReason {
+    /// For logs and messages: "new game", "quit" and so on.
+    pub fn label(self) -> &'static str {
+        match self {
+            AbandonReason::NewGame => "new game",
+            AbandonReason::Difficulty => "difficulty switch",
+            AbandonReason::Menu => "game from the menu",
+            AbandonReason::Quit => "quit",
+        }
+    }
+}
+
+/// A board with the hints taken on it, the puzzle objective it is played
+/// against and, once lost, the odds of the cell that lost it.
+#[derive(Clone)]
+pub struct Session {
+    board: Board,
+    hints: HintLadder,
+    fatal: Option<Chance>,
+    abandoned: Option<AbandonReason>,
+    objective: Tracker,
+}
+
+impl Session {
+    /// A session on `board`.
+    pub fn new(board: Board) -> Session {
+        Session {
+            board,
+            hints: HintLadder::default(),
+            fatal: None,
+            abandoned: None,
+            objective: Tracker::default(),
+        }
+    }
+
+    /// A session on `board` that ends once `objective` is met or failed,
+    /// saying which, and counts down a click budget as it goes.
+    ///
+    /// ```
+    /// use minesweeper_rs::objective::{Objective, Verdict};
+    /// use minesweeper_rs::session::Session;
+    /// use minesweeper_rs::Board;
+    ///
+    /// let board = Board::decode_layout("*..\n...\n..*\n")?;
+    /// let objective = Objective { clicks: Some(2), ..Objective::default() };
+    /// let mut session = Session::with_objective(board, objective);
+    /// assert_eq!(session.apply("2 0".parse().unwrap()), "1 click left");
+    /// assert_eq!(
+    ///     session.apply("0 1".parse().unwrap()),
+    ///     "Objective failed: out of clicks"
+    /// );
+    /// assert_eq!(session.objective().verdict(), Verdict::Failed(OutOfClicks));
+    /// # use minesweeper_rs::objective::Failure::OutOfClicks;
+    /// # Ok::<(), minesweeper_rs::LayoutError>(())
+    /// ```
+    pub fn with_objective(board: Board, objective: Objective) -> Session {
+        Session {
+            objective: Tracker::new(objective),
+            ..Session::new(board)
+        }
+    }
+
+    /// The objective and how the game stands against it.
+    pub fn objective(&self) -> &Tracker {
+        &self.objective
+    }
+
+    /// The board as it stands.
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    /// The hints taken so far.
+    pub fn hints(&self) -> &HintLadder {
+        &self.hints
+    }
+
+    /// How likely the cell that lost the game was to be a mine.
+    pub fn fatal(&self) -> Option<Chance> {
+        self.fatal
+    }
+
+    /// Whether there is a game to lose: a cell has been opened, the game
+    /// isn't over, its objective is still open and it hasn't been
+    /// abandoned.
+    pub fn in_progress(&self) -> bool {
+        self.board.revealed_safe() > 0
+            && !self.board.is_over()
+            && !self.objective.is_decided()
+            && self.abandoned.is_none()
+    }
+
+    /// Give the game up for `reason`, if it is in progress; returns
+    /// whether it was. An abandoned game takes no more moves and is over
+    /// as far as a score is concerned, so a caller throwing a game away
+    /// (a new game, a quit) goes through here to count it.
+    ///
+    /// ```
+    /// use minesweeper_rs::session::{AbandonReason, Session};
+    /// use minesweeper_rs::Board;
+    ///
+    /// let mut session = Session::new(Board::decode_layout("*..\n...\n..*\n")?);
+    /// assert!(!session.abandon(AbandonReason::NewGame), "nothing opened yet");
+    /// session.apply("2 0".parse().unwrap());
+    /// assert!(session.abandon(AbandonReason::Quit));
+    /// assert_eq!(session.abandoned(), Some(AbandonReason::Quit));
+    /// assert_eq!(session.apply("0 2".parse().unwrap()), "game was abandoned — start a new game");
+    /// # Ok::<(), minesweeper_rs::LayoutError>(())
+    /// ```
+    pub fn abandon(&mut self, reason: AbandonReason) -> bool {
+        let in_progress = self.in_progress();
+        if in_progress {
+            self.abandoned = Some(reason);
+        }
+        in_progress
+    }
+
+    /// Why the game was abandoned, if it was.
+    pub fn abandoned(&self) -> Option<AbandonReason> {
+        self.abandoned
+    }
+
+    /// Carry out `command`, returning what to tell the player: empty when
+    /// the board says it all, otherwise one or more lines without the
+    /// last newline. Commands after the game is over, its objective met or
+    /// failed, or it is abandoned are refused.
+    pub fn apply(&mut self, command: Command) -> String {
+        if self.abandoned.is_some() && command != Command::Analyze {
+            return "game was abandoned — start a new game".to_string();
+        }
+        let decided = self.board.is_over() || self.objective.is_decided();
+        if decided && command != Command::Analyze {
+            return Ignored::GameOver.message().to_string();
+        }
+        let board = &mut self.board;
+        let mut reply = match command {
+            Command::Reveal(x, y) => {
+                // the odds are read off the board as it was, and only for
+                // a click that can lose
+                let mine = x < board.width() && y < board.height() && board.bits(x, y).is_mine();
+                let fatal = if mine {
+                    odds::chance(board, x, y)
+                } else {
+                    None
+                };
+                match board.reveal_cell(x, y) {
+                    RevealResult::Ignored(reason) => reason.message().to_string(),
+                    RevealResult::Exploded => {
+                        self.objective.click();
+                        self.fatal = fatal;
+                        String::new()
+                    }
+                    _ => {
+                        self.objective.click();
+                        String::new()
+                    }
+                }
+            }
+            Command::Flag(x, y) => match board.cycle_marking(x, y, CycleMode::Flags) {
+                MarkingResult::Ignored(reason) => reason.message().to_string(),
+                MarkingResult::Changed {
+                    to: Marking::Flag, ..
+                } => {
+                    self.objective.flag();
+                    String::new()
+                }
+                _ => String::new(),
+            },
+            Command::ClearFlags => {
+                board.clear_flags();
+                String::new()
+            }
+            // only with a flag per mine, so it can't go off by accident
+            Command::RevealRemaining if board.flags() != board.mine_count() => format!(
+                "`ra` needs a flag on every mine first: {} of {} flagged",
+                board.flags(),
+                board.mine_count()
+            ),
+            Command::RevealRemaining => match board.reveal_remaining() {
+                RevealResult::Ignored(reason) => reason.message().to_string(),
+                RevealResult::Exploded => {
+                    self.objective.click();
+                    "Finished with reveal-remaining: a flag was on a safe cell.".to_string()
+                }
+                _ => {
+                    self.objective.click();
+                    "Finished with reveal-remaining.".to_string()
+                }
+            },
+            Command::Hint => match self.hints.next(board) {
+                Some(hint) => {
+                    if let Hint::Answer { cell: (x, y), mine } = hint {
+                        if mine {
+                            board.set_marking(x, y, Marking::Flag);
+                        } else {
+                            board.set_marking(x, y, Marking::None);
+                            board.reveal_cell(x, y);
+                        }
+                    }
+                    hint.to_string()
+                }
+                None => "Nothing is certain from here, so it's a guess.".to_string(),
+            },
+            Command::Analyze => AnalysisReport::new(board).to_string(),
+        };
+        let mut say = |line: &str| {
+            if !reply.is_empty() {
+                reply.push('\n');
+            }
+            reply.push_str(line);
+        };
+        if board.flags_misplaced() {
+            say("Every hidden cell is flagged but the game isn't won, so a flag is wrong.");
+        }
+        if !self.objective.objective().is_classic() {
+            match self.objective.check(board) {
+                Verdict::Met => say("Objective met"),
+                Verdict::Failed(why) => say(&format!("Objective failed: {why}")),
+                Verdict::Open => match (self.objective.clicks_left(), command) {
+                    (Some(1), Command::Reveal(..)) => say("1 click left"),
+                    (Some(left), Command::Reveal(..)) => say(&format!("{left} clicks left")),
+                    _ => {}
+                },
+            }
+        }
+        reply.trim_end().to_string()
+    }
+
+    /// The board as one line of JSON: its size, mines, flags, `state`
+    /// (`playing`, `won`, `lost` or `abandoned`), `revision` (`Board::revision`) and
+    /// `rows`, one string per row of `Glyph::narrow` characters.
+    ///
+    /// ```
+    /// use minesweeper_rs::session::Session;
+    /// use minesweeper_rs::Board;
+    ///
+    /// let session = Session::new(Board::decode_layout("*.\n")?);
+    /// assert_eq!(
+    ///     session.to_json(),
+    ///     r#"{"width":2,"height":1,"mines":1,"flags":0,"state":"playing","revision":0,"#
+    ///         .to_string()
+    ///         + r#""rows":["░░"]}"#
+    /// );
+    /// # Ok::<(), minesweeper_rs::LayoutError>(())
+    /// ```
+    pub fn to_json(&self) -> String {
+        let board = &self.board;
+        let state = if board.is_won() {
+            "won"
+        } else if board.is_lost() {
+            "lost"
+        } else if self.abandoned.is_some() {
+            "abandoned"
+        } else {
+            "playing"
+        };
+        let rows: Vec<String> = (0..board.height())
+            .map(|y| {
+                let row: String = (0..board.width())
+                    .map(|x| Glyph::of(board.cell(x, y)).narrow())
+                    .collect();
+                format!("\"{row}\"")
+            })
+            .collect();
+        format!(
+            "{{\"width\":{},\"height\":{},\"mines\":{},\"flags\":{},\"state\":\"{state}\",\
+             \"revision\":{},\"rows\":[{}]}}",
+            board.width(),
+            board.height(),
+            board.mine_count(),
+            board.flags(),
+            board.revision(),
+            rows.join(",")
+        )
+    }
+}
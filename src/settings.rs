@@ -0,0 +1,333 @@
+//! Player preferences, grouped the way the settings screen shows them.
+//!
+//! Everything lives in one `Settings` value that the game reads directly and
+//! that is persisted per profile. Each section has its own `Default`, which
+//! is what its "Restore defaults" button goes back to. Fields missing from
+//! an older save fall back to their defaults, so adding a setting never
+//! throws away the ones already stored.
+
+use crate::net::leaderboard::Leaderboard;
+use crate::notify::Webhook;
+use crate::replays::Keep;
+use crate::themes::Palette;
+use crate::widgets;
+use crate::win_effects::{Confetti, FlagWave, GlowSweep, NoEffect, WinEffect};
+use eframe::egui;
+use minesweeper_rs::render::{Marker, MarkerSet, Markers};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub gameplay: Gameplay,
+    pub appearance: Appearance,
+    pub controls: Controls,
+    pub accessibility: Accessibility,
+    pub advanced: Advanced,
+    pub tutorial_done: bool, // finished once, so the menu stops suggesting it
+    pub seen_version: String, // release notes last shown; empty before any
+    pub compact_window: Option<Geometry>, // compact mode's window, apart from the normal one
+}
+
+/// A window's place and size, in points.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Geometry {
+    pub pos: Option<[f32; 2]>, // outer top-left corner, where the platform says
+    pub size: [f32; 2],        // inside the frame
+}
+
+impl Geometry {
+    /// The window as `viewport` reports it, if the platform says.
+    pub fn of(viewport: &egui::ViewportInfo) -> Option<Geometry> {
+        Some(Geometry {
+            pos: viewport.outer_rect.map(|rect| rect.min.into()),
+            size: viewport.inner_rect?.size().into(),
+        })
+    }
+
+    /// Ask for the window to take this size and place. Platforms that
+    /// can't move or resize windows ignore it.
+    pub fn apply(&self, ctx: &egui::Context) {
+        ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(self.size.into()));
+        if let Some(pos) = self.pos {
+            ctx.send_viewport_cmd(egui::ViewportCommand::OuterPosition(pos.into()));
+        }
+    }
+}
+
+/// Largest safe-start radius offered: a 5x5 opening.
+pub const MAX_SAFE_RADIUS: usize = 2;
+
+/// Most cells in question the endgame outlook may search; it tries every
+/// way the mines could lie among them.
+pub const MAX_ENDGAME_CELLS: usize = 24;
+
+/// Widest margin offered around the keyboard cursor, in cells.
+pub const MAX_FOCUS_MARGIN: usize = 6;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Gameplay {
+    pub no_guess: bool,        // generate boards that never need a guess
+    pub flag_limit: bool,      // no more flags than mines
+    pub question_marks: bool,  // right click cycles flag, question mark, clear
+    pub open_on_number: bool,  // a flag completing one number opens its other neighbours
+    pub safe_radius: usize,    // cells around the first click kept clear, 0..=MAX_SAFE_RADIUS
+    pub auto_open: AutoOpen,   // the first move, made for the player as the game starts
+    pub suggestions: bool,     // suggest difficulty changes after games
+    pub zen: bool,             // untimed, forgiving games kept out of the records
+    pub abandon_is_loss: bool, // a game given up for another counts as lost
+    pub confirm_abandon: bool, // ask before giving up a game in progress
+    pub endgame_outlook: bool, // guesses and best chance ahead, once few cells are in question
+    pub endgame_cells: usize,  // how few, up to MAX_ENDGAME_CELLS
+}
+
+impl Default for Gameplay {
+    fn default() -> Self {
+        Gameplay {
+            no_guess: false,
+            flag_limit: false,
+            question_marks: false,
+            open_on_number: false,
+            safe_radius: 1,
+            auto_open: AutoOpen::Off,
+            suggestions: true,
+            zen: false,
+            abandon_is_loss: true,
+            confirm_abandon: true,
+            endgame_outlook: false,
+            endgame_cells: 16,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Appearance {
+    pub theme: Theme,
+    pub markers: MarkerChoice,
+    pub custom_markers: [String; 3], // flag, question mark and mine for `MarkerChoice::Custom`
+    pub counts: CountStyle,
+    pub win_effect: WinStyle,
+    pub palette: Option<Palette>, // the board's colours; None follows the theme
+}
+
+impl Appearance {
+    /// The markers to draw with. A custom marker that isn't one character
+    /// falls back to the classic one.
+    pub fn markers(&self) -> Markers {
+        let set = match self.markers {
+            MarkerChoice::Classic => MarkerSet::Classic,
+            MarkerChoice::Minimal => MarkerSet::Minimal,
+            MarkerChoice::Letters => MarkerSet::Letters,
+            MarkerChoice::Custom => {
+                let mut markers = Markers::default();
+                for (marker, text) in Marker::ALL.into_iter().zip(&self.custom_markers) {
+                    // the settings screen says which ones were refused
+                    let _ = markers.set(marker, text.trim());
+                }
+                return markers;
+            }
+        };
+        set.markers()
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Controls {
+    pub swap_buttons: bool, // left click flags, right click reveals
+    pub quick_entry: bool,  // typing a cell's name and Enter plays it
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Accessibility {
+    pub animations: bool,    // particle effects and other motion
+    pub follow_focus: bool,  // scroll the board to keep the keyboard cursor in view
+    pub focus_margin: usize, // cells kept between the cursor and the edge, 0..=MAX_FOCUS_MARGIN
+    pub earcons: bool,       // tones for numbers, cascades, flags and refused moves
+    pub earcon_pan: bool,    // earcons panned to the cell's column
+}
+
+impl Default for Accessibility {
+    fn default() -> Self {
+        Accessibility {
+            animations: true,
+            follow_focus: true,
+            focus_margin: widgets::FOCUS_MARGIN,
+            earcons: false,
+            earcon_pan: true,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Advanced {
+    pub large_boards: bool,       // lift the custom size cap (experimental)
+    pub show_log: bool,           // debug log window open
+    pub webhook: Webhook,         // brag posts for finished games
+    pub leaderboard: Leaderboard, // online scores; off until opted into
+    pub keep_races: Keep,         // which of the player's saved race results to keep
+    pub crash_reports: bool,      // write a report on a crash; off until opted into
+    pub visual_seed: Option<u64>, // seeds the effects for repeatable captures; no UI
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Theme {
+    #[default]
+    System,
+    Light,
+    Dark,
+}
+
+/// Which glyphs flags, question marks and mines are drawn with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MarkerChoice {
+    #[default]
+    Classic,
+    Minimal,
+    Letters,
+    Custom,
+}
+
+impl MarkerChoice {
+    pub const ALL: [MarkerChoice; 4] = [
+        MarkerChoice::Classic,
+        MarkerChoice::Minimal,
+        MarkerChoice::Letters,
+        MarkerChoice::Custom,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            MarkerChoice::Classic => "🚩 ❓ 💣",
+            MarkerChoice::Minimal => "▲ · ●",
+            MarkerChoice::Letters => "F ? M",
+            MarkerChoice::Custom => "Custom",
+        }
+    }
+}
+
+/// How the count on a revealed cell is drawn.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CountStyle {
+    #[default]
+    Numerals,
+    Pips, // laid out like the faces of a die
+    Dots, // a row of dots in the corner, one per mine
+}
+
+impl CountStyle {
+    pub const ALL: [CountStyle; 3] = [CountStyle::Numerals, CountStyle::Pips, CountStyle::Dots];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            CountStyle::Numerals => "Numerals",
+            CountStyle::Pips => "Dice pips",
+            CountStyle::Dots => "Corner dots",
+        }
+    }
+}
+
+/// How a win is celebrated. Without animations, it isn't.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WinStyle {
+    #[default]
+    Confetti,
+    GlowSweep, // a soft highlight crosses the board once
+    FlagWave,  // the flags bounce one after another
+    None,
+}
+
+impl WinStyle {
+    pub const ALL: [WinStyle; 4] = [
+        WinStyle::Confetti,
+        WinStyle::GlowSweep,
+        WinStyle::FlagWave,
+        WinStyle::None,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            WinStyle::Confetti => "Confetti",
+            WinStyle::GlowSweep => "Glow sweep",
+            WinStyle::FlagWave => "Flag wave",
+            WinStyle::None => "None",
+        }
+    }
+
+    /// The effect that plays it.
+    pub fn effect(self) -> &'static dyn WinEffect {
+        match self {
+            WinStyle::Confetti => &Confetti,
+            WinStyle::GlowSweep => &GlowSweep,
+            WinStyle::FlagWave => &FlagWave,
+            WinStyle::None => &NoEffect,
+        }
+    }
+}
+
+/// Which cell, if any, is opened for the player as a game starts.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AutoOpen {
+    #[default]
+    Off,
+    Centre,
+    Random,
+    BestOpening, // the largest opening, read off the layout; wins with it are marked
+}
+
+impl AutoOpen {
+    pub const ALL: [AutoOpen; 4] = [
+        AutoOpen::Off,
+        AutoOpen::Centre,
+        AutoOpen::Random,
+        AutoOpen::BestOpening,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            AutoOpen::Off => "Off",
+            AutoOpen::Centre => "Centre",
+            AutoOpen::Random => "Random",
+            AutoOpen::BestOpening => "Best opening",
+        }
+    }
+}
+
+impl Theme {
+    pub const ALL: [Theme; 3] = [Theme::System, Theme::Light, Theme::Dark];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Theme::System => "Follow system",
+            Theme::Light => "Light",
+            Theme::Dark => "Dark",
+        }
+    }
+
+    fn preference(self) -> egui::ThemePreference {
+        match self {
+            Theme::System => egui::ThemePreference::System,
+            Theme::Light => egui::ThemePreference::Light,
+            Theme::Dark => egui::ThemePreference::Dark,
+        }
+    }
+}
+
+impl Settings {
+    /// Push the settings that egui owns, the theme, the board's markers,
+    /// counts and colours and how it follows the keyboard cursor, into
+    /// `ctx`.
+    pub fn apply(&self, ctx: &egui::Context) {
+        ctx.set_theme(self.appearance.theme.preference());
+        widgets::set_markers(ctx, self.appearance.markers());
+        widgets::set_count_style(ctx, self.appearance.counts);
+        widgets::set_palette(ctx, self.appearance.palette.clone());
+        let accessibility = &self.accessibility;
+        let margin = accessibility.focus_margin.min(MAX_FOCUS_MARGIN);
+        widgets::set_follow_focus(ctx, accessibility.follow_focus.then_some(margin));
+    }
+}
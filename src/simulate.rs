@@ -0,0 +1,441 @@
+//! `simulate`: a headless bot plays many boards at each mine count of a
+//! sweep, for research into how density changes the odds, and the totals
+//! come out as CSV.
+//!
+//! Every policy opens the centre (kept clear, as on a real first click)
+//! and plays whatever the solver proves safe; they differ only when
+//! nothing is certain. `solver` then opens the cell with the lowest
+//! counted chance (`odds::chance`), `greedy` the one with the lowest
+//! estimate (`solver::analyze`), and `random` any unsettled cell.
+//!
+//! Game `i` at every mine count is played on a board from
+//! `derive_seed(seed, i)`, so a run with `--seed` gives the same results
+//! whatever the number of threads (only the timings vary). Ctrl+C stops the run: games
+//! under way finish, and the point they belong to is written with the
+//! games it got through.
+
+use crate::cli::CliError;
+use minesweeper_rs::generate::derive_seed;
+use minesweeper_rs::solver::{self, Analysis};
+use minesweeper_rs::{odds, stats, Board, BoardOptions};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::fmt::Write as _;
+use std::ops::RangeInclusive;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// What the bot does when nothing is certain.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Policy {
+    Solver,
+    Greedy,
+    Random,
+}
+
+/// One game's result.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Game {
+    pub won: bool,
+    pub three_bv: usize,
+    pub thinking: Duration, // spent choosing moves
+    pub guesses: u32,
+}
+
+/// A sweep: the board, the mine counts to try and how to play them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sweep {
+    pub width: usize,
+    pub height: usize,
+    pub mines: Vec<usize>,
+    pub games: u32,
+    pub policy: Policy,
+    pub seed: u64,
+}
+
+/// One CSV row: the totals at one mine count.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Row {
+    pub width: usize,
+    pub height: usize,
+    pub mines: usize,
+    pub games: u32, // played; fewer than asked for if interrupted
+    pub wins: u32,
+    pub three_bv: usize,
+    pub thinking: Duration,
+    pub guesses: u32,
+}
+
+/// The CSV header, matching `Row::to_csv`.
+pub const CSV_HEADER: &str =
+    "width,height,mines,density,games,win_rate,avg_3bv,avg_solver_ms,avg_guesses";
+
+impl Row {
+    fn add(&mut self, game: &Game) {
+        self.games += 1;
+        self.wins += u32::from(game.won);
+        self.three_bv += game.three_bv;
+        self.thinking += game.thinking;
+        self.guesses += game.guesses;
+    }
+
+    /// The row as CSV, without a line break. Averages are per game played.
+    pub fn to_csv(self) -> String {
+        let per_game = |total: f64| total / f64::from(self.games.max(1));
+        let density = self.mines as f64 / (self.width * self.height) as f64;
+        let mut line = String::new();
+        let _ = write!(
+            line,
+            "{},{},{},{density:.4},{},{:.4},{:.2},{:.3},{:.3}",
+            self.width,
+            self.height,
+            self.mines,
+            self.games,
+            per_game(f64::from(self.wins)),
+            per_game(self.three_bv as f64),
+            per_game(self.thinking.as_secs_f64() * 1000.0),
+            per_game(f64::from(self.guesses)),
+        );
+        line
+    }
+}
+
+impl std::str::FromStr for Policy {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text {
+            "solver" => Ok(Policy::Solver),
+            "greedy" => Ok(Policy::Greedy),
+            "random" => Ok(Policy::Random),
+            other => Err(format!(
+                "unknown policy {other}: expected solver, greedy or random"
+            )),
+        }
+    }
+}
+
+/// `30..60`, `30..=60` (the same: both ends are included) or a lone `40`.
+pub fn parse_range(text: &str) -> Result<RangeInclusive<usize>, String> {
+    let number = |n: &str| {
+        n.trim()
+            .parse::<usize>()
+            .map_err(|_| format!("bad number {n:?} in range {text:?}"))
+    };
+    let range = match text.split_once("..") {
+        Some((low, high)) => number(low)?..=number(high.strip_prefix('=').unwrap_or(high))?,
+        None => number(text)?..=number(text)?,
+    };
+    if range.is_empty() {
+        return Err(format!("range {text:?} is empty"));
+    }
+    Ok(range)
+}
+
+/// Every `step`th value of `range`, from its start.
+pub fn stepped(range: RangeInclusive<usize>, step: usize) -> Result<Vec<usize>, String> {
+    if step == 0 {
+        return Err("--step must be at least 1".into());
+    }
+    Ok(range.step_by(step).collect())
+}
+
+/// `simulate --width W --height H --mines A..B [--step N] [--games N]
+/// [--policy solver|greedy|random] [--seed N]`, read into a sweep.
+pub fn parse(words: &[String]) -> Result<Sweep, String> {
+    let (mut width, mut height, mut mines) = (None, None, None);
+    let (mut step, mut games, mut policy) = (1, 100, Policy::Solver);
+    let mut seed = rand::thread_rng().gen();
+    let mut words = words.iter();
+    while let Some(word) = words.next() {
+        let mut value = || {
+            words
+                .next()
+                .map(String::as_str)
+                .ok_or_else(|| USAGE.to_string())
+        };
+        let number = |text: &str| {
+            text.parse::<u64>()
+                .map_err(|_| format!("{word} expects a number, not {text:?}"))
+        };
+        match word.as_str() {
+            "--width" => width = Some(number(value()?)? as usize),
+            "--height" => height = Some(number(value()?)? as usize),
+            "--mines" => mines = Some(parse_range(value()?)?),
+            "--step" => step = number(value()?)? as usize,
+            "--games" => games = number(value()?)? as u32,
+            "--policy" => policy = value()?.parse()?,
+            "--seed" => seed = number(value()?)?,
+            _ => return Err(USAGE.into()),
+        }
+    }
+    let (Some(width), Some(height), Some(mines)) = (width, height, mines) else {
+        return Err(USAGE.into());
+    };
+    let mines = stepped(mines, step)?;
+    for &count in &mines {
+        // checked, not built: a long range on a big board would lay every
+        // one of its boards before the first game
+        BoardOptions::new(width, height, count)
+            .safe_start(width / 2, height / 2)
+            .check()
+            .map_err(|err| format!("{width}x{height} with {count} mines: {err}"))?;
+    }
+    Ok(Sweep {
+        width,
+        height,
+        mines,
+        games,
+        policy,
+        seed,
+    })
+}
+
+const USAGE: &str = "usage: simulate --width W --height H --mines A..B [--step N] \
+                     [--games N] [--policy solver|greedy|random] [--seed N]";
+
+/// Play board `seed` through with `policy`.
+pub fn play(width: usize, height: usize, mines: usize, seed: u64, policy: Policy) -> Game {
+    let start = (width / 2, height / 2);
+    let mut board = Board::with_seed(width, height, mines, seed, Some(start));
+    let mut game = Game {
+        three_bv: stats::three_bv(&board),
+        ..Game::default()
+    };
+    let mut rng = StdRng::seed_from_u64(seed);
+    board.reveal_cell(start.0, start.1);
+    while !board.is_over() {
+        let started = Instant::now();
+        let analysis = solver::analyze(&board);
+        let moves = if analysis.safe().is_empty() {
+            game.guesses += 1;
+            guess(&board, &analysis, policy, &mut rng)
+                .into_iter()
+                .collect()
+        } else {
+            analysis.safe().to_vec()
+        };
+        game.thinking += started.elapsed();
+        if moves.is_empty() {
+            break; // nothing hidden but proven mines, which can't happen
+        }
+        for (x, y) in moves {
+            board.reveal_cell(x, y);
+        }
+    }
+    game.won = board.is_won();
+    game
+}
+
+/// The cell `policy` opens when nothing is certain.
+fn guess(
+    board: &Board,
+    analysis: &Analysis,
+    policy: Policy,
+    rng: &mut StdRng,
+) -> Option<(usize, usize)> {
+    let open: Vec<(usize, usize)> = (0..board.height())
+        .flat_map(|y| (0..board.width()).map(move |x| (x, y)))
+        .filter(|&(x, y)| !board.cell(x, y).is_revealed() && analysis.probability(x, y) < 1.0)
+        .collect();
+    let lowest = |chance: &dyn Fn((usize, usize)) -> f64| {
+        open.iter()
+            .map(|&cell| (cell, chance(cell)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(cell, _)| cell)
+    };
+    match policy {
+        Policy::Random if open.is_empty() => None,
+        Policy::Random => Some(open[rng.gen_range(0..open.len())]),
+        Policy::Greedy => lowest(&|(x, y)| analysis.probability(x, y)),
+        Policy::Solver => {
+            let chances = odds::chances(board);
+            lowest(&|(x, y)| chances[y * board.width() + x].map_or(1.0, odds::Chance::value))
+        }
+    }
+}
+
+/// Run `sweep` on every core, writing the header and then a row per mine
+/// count to `out` as each finishes, and progress to stderr. Setting `stop`
+/// ends the run after the games under way, with their row written.
+pub fn run(sweep: &Sweep, out: &mut dyn std::io::Write, stop: &AtomicBool) -> std::io::Result<()> {
+    writeln!(out, "{CSV_HEADER}")?;
+    out.flush()?;
+    let workers = thread::available_parallelism().map_or(1, |n| n.get().min(8));
+    for &mines in &sweep.mines {
+        let next = AtomicU32::new(0);
+        let done = AtomicU32::new(0);
+        let slots: Vec<Mutex<Option<Game>>> = (0..sweep.games).map(|_| Mutex::new(None)).collect();
+        thread::scope(|scope| {
+            for _ in 0..workers {
+                scope.spawn(|| loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    if i >= sweep.games || stop.load(Ordering::Relaxed) {
+                        break;
+                    }
+                    let seed = derive_seed(sweep.seed, i);
+                    let game = play(sweep.width, sweep.height, mines, seed, sweep.policy);
+                    *slots[i as usize].lock().expect("one writer per slot") = Some(game);
+                    done.fetch_add(1, Ordering::Relaxed);
+                });
+            }
+            while done.load(Ordering::Relaxed) < sweep.games && !stop.load(Ordering::Relaxed) {
+                eprint!(
+                    "\r{mines} mines: {}/{} games",
+                    done.load(Ordering::Relaxed),
+                    sweep.games
+                );
+                thread::sleep(Duration::from_millis(200));
+            }
+        });
+        eprintln!(
+            "\r{mines} mines: {}/{} games",
+            done.into_inner(),
+            sweep.games
+        );
+
+        // in board order, so the totals don't depend on which thread was first
+        let mut row = Row {
+            width: sweep.width,
+            height: sweep.height,
+            mines,
+            ..Row::default()
+        };
+        for slot in slots {
+            if let Some(game) = slot.into_inner().expect("workers are done") {
+                row.add(&game);
+            }
+        }
+        writeln!(out, "{}", row.to_csv())?;
+        out.flush()?;
+        if stop.load(Ordering::Relaxed) {
+            eprintln!("interrupted; results so far are written");
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// `simulate ...` from the command line: CSV on stdout, Ctrl+C to stop.
+pub fn cli(words: &[String]) -> Result<(), CliError> {
+    let sweep = parse(words).map_err(CliError::Usage)?;
+    eprintln!(
+        "{} games at each of {} mine counts, seed {}",
+        sweep.games,
+        sweep.mines.len(),
+        sweep.seed
+    );
+    let stop = Arc::new(AtomicBool::new(false));
+    let flag = stop.clone();
+    if let Err(err) = ctrlc::set_handler(move || flag.store(true, Ordering::Relaxed)) {
+        eprintln!("can't catch Ctrl+C: {err}");
+    }
+    run(&sweep, &mut std::io::stdout().lock(), &stop)
+        .map_err(|e| CliError::Output(format!("writing the results: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(s: &str) -> Vec<String> {
+        s.split(' ').map(String::from).collect()
+    }
+
+    #[test]
+    fn ranges_include_both_ends() {
+        assert_eq!(parse_range("30..60"), Ok(30..=60));
+        assert_eq!(parse_range("30..=60"), Ok(30..=60));
+        assert_eq!(parse_range("40"), Ok(40..=40));
+        assert!(parse_range("60..30").is_err());
+        assert!(parse_range("a..5").is_err());
+        assert_eq!(stepped(30..=60, 5), Ok(vec![30, 35, 40, 45, 50, 55, 60]));
+        assert_eq!(stepped(30..=42, 5), Ok(vec![30, 35, 40]));
+        assert!(stepped(1..=2, 0).is_err());
+    }
+
+    #[test]
+    fn sweeps_are_read_and_checked() {
+        let sweep = parse(&words(
+            "--width 16 --height 16 --mines 30..60 --step 10 --policy greedy --seed 3",
+        ))
+        .unwrap();
+        assert_eq!(sweep.mines, [30, 40, 50, 60]);
+        assert_eq!(
+            (sweep.policy, sweep.seed, sweep.games),
+            (Policy::Greedy, 3, 100)
+        );
+
+        assert!(parse(&words("--width 16 --mines 30")).is_err());
+        assert!(parse(&words("--width 3 --height 3 --mines 9")).is_err());
+        assert!(parse(&words("--width 9 --height 9 --mines 10 --policy psychic")).is_err());
+    }
+
+    #[test]
+    fn rows_are_written_as_csv() {
+        let mut row = Row {
+            width: 10,
+            height: 10,
+            mines: 15,
+            ..Row::default()
+        };
+        row.add(&Game {
+            won: true,
+            three_bv: 120,
+            thinking: Duration::from_millis(3),
+            guesses: 1,
+        });
+        row.add(&Game {
+            won: false,
+            three_bv: 100,
+            thinking: Duration::from_millis(1),
+            guesses: 2,
+        });
+        assert_eq!(row.to_csv(), "10,10,15,0.1500,2,0.5000,110.00,2.000,1.500");
+        assert_eq!(
+            CSV_HEADER.split(',').count(),
+            row.to_csv().split(',').count()
+        );
+
+        let empty = Row {
+            width: 9,
+            height: 9,
+            mines: 10,
+            ..Row::default()
+        };
+        assert_eq!(empty.to_csv(), "9,9,10,0.1235,0,0.0000,0.00,0.000,0.000");
+    }
+
+    #[test]
+    fn runs_are_repeatable_and_stop_when_asked() {
+        let sweep = parse(&words(
+            "--width 9 --height 9 --mines 8..12 --step 2 --games 6 --seed 5",
+        ))
+        .unwrap();
+        let csv = |stop: &AtomicBool| {
+            let mut out = Vec::new();
+            run(&sweep, &mut out, stop).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        // only the timings may differ between runs
+        let outcomes = |text: &str| -> Vec<String> {
+            text.lines()
+                .map(|line| {
+                    let fields: Vec<&str> = line.split(',').collect();
+                    [&fields[..7], &fields[8..]].concat().join(",")
+                })
+                .collect()
+        };
+        let first = csv(&AtomicBool::new(false));
+        assert_eq!(first.lines().count(), 4);
+        assert_eq!(outcomes(&first), outcomes(&csv(&AtomicBool::new(false))));
+
+        let stopped = csv(&AtomicBool::new(true));
+        assert_eq!(
+            stopped.lines().collect::<Vec<_>>(),
+            [CSV_HEADER, "9,9,8,0.0988,0,0.0000,0.00,0.000,0.000"]
+        );
+    }
+}
@@ -0,0 +1,672 @@
+//! Deduction-only solver. It looks at what a player could see (revealed
+//! numbers plus the mines it has already proven) and never guesses, which
+//! makes it suitable for checking that a board can be cleared by logic alone.
+
+use crate::{Board, RevealResult};
+use std::collections::HashMap;
+use std::time::Instant;
+use tracing::debug;
+
+mod incremental;
+
+pub use incremental::{CacheStats, DirtyInfo, Solver, BUDGET};
+
+/// Cells proven safe or mined by one pass over the visible numbers.
+#[derive(Default)]
+pub struct Step {
+    /// Hidden cells that can't be mines.
+    pub safe: Vec<(usize, usize)>,
+    /// Hidden cells that must be mines.
+    pub mines: Vec<(usize, usize)>,
+    reasons: Vec<((usize, usize), Reason)>, // one per cell above, row-major
+}
+
+impl Step {
+    /// True if nothing new could be deduced.
+    pub fn is_empty(&self) -> bool {
+        self.safe.is_empty() && self.mines.is_empty()
+    }
+
+    /// Why (x, y) was deduced, if it was.
+    pub fn reason(&self, x: usize, y: usize) -> Option<Reason> {
+        self.reasons
+            .iter()
+            .find(|&&(at, _)| at == (x, y))
+            .map(|&(_, reason)| reason)
+    }
+}
+
+/// The rule that settled a cell, naming the revealed numbers it rests on.
+/// "Known mines" are the ones proven so far, which a player would have
+/// flagged.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Reason {
+    /// The number at this cell already touches as many known mines as it
+    /// shows, so its other hidden neighbours are safe.
+    Satisfied((usize, usize)),
+    /// The number at this cell has exactly as many hidden neighbours as
+    /// mines still missing, so they are all mines.
+    Filled((usize, usize)),
+    /// Every hidden neighbour of `inner` is also a neighbour of `outer`, so
+    /// `outer`'s other hidden neighbours hold the difference in their
+    /// missing mines: none (all safe) or one per cell (all mines).
+    Subset {
+        /// The number whose hidden neighbours are all shared.
+        inner: (usize, usize),
+        /// The number with the extra hidden neighbours.
+        outer: (usize, usize),
+    },
+    /// The board's mine count: either every mine is known, so the rest is
+    /// safe, or the hidden cells left are exactly the mines left.
+    MineCount,
+}
+
+impl Reason {
+    /// The revealed numbers the deduction reads, to point them out.
+    ///
+    /// ```
+    /// use minesweeper_rs::solver::Reason;
+    ///
+    /// let reason = Reason::Subset { inner: (1, 0), outer: (2, 0) };
+    /// assert_eq!(reason.numbers(), [(1, 0), (2, 0)]);
+    /// assert!(Reason::MineCount.numbers().is_empty());
+    /// ```
+    pub fn numbers(self) -> Vec<(usize, usize)> {
+        match self {
+            Reason::Satisfied(at) | Reason::Filled(at) => vec![at],
+            Reason::Subset { inner, outer } => vec![inner, outer],
+            Reason::MineCount => Vec::new(),
+        }
+    }
+}
+
+/// "Exactly `mines` of `cells` are mines", from one revealed number.
+struct Constraint {
+    at: (usize, usize), // the number it comes from
+    cells: Vec<usize>,  // sorted cell indices (y * width + x)
+    mines: usize,
+}
+
+/// Run one round of deductions. `known_mines` is indexed `y * width + x`
+/// and marks cells already proven to be mines; player flags are ignored.
+///
+/// ```
+/// use minesweeper_rs::{solver, Board};
+///
+/// let mut board = Board::decode_layout("*..\n...\n..*\n")?;
+/// board.reveal_cell(2, 0);
+/// let mut known_mines = [false; 9];
+/// known_mines[0] = true; // (0, 0)
+/// let step = solver::step(&board, &known_mines);
+/// assert_eq!(step.safe, [(0, 1)]);
+/// assert_eq!(step.reason(0, 1), Some(solver::Reason::Satisfied((1, 0))));
+/// # Ok::<(), minesweeper_rs::LayoutError>(())
+/// ```
+pub fn step(board: &Board, known_mines: &[bool]) -> Step {
+    let width = board.width();
+    let hidden = |i: usize| !board.cells[i].is_revealed() && !known_mines[i];
+
+    let mut constraints = Vec::new();
+    for y in 0..board.height() {
+        for x in 0..width {
+            let cell = board.bits(x, y);
+            if !cell.is_revealed() || cell.is_mine() || cell.neighbor_mines() == 0 {
+                continue;
+            }
+            let mut cells = Vec::new();
+            let mut found = 0;
+            for (nx, ny) in board.neighbors(x, y) {
+                let i = ny * width + nx;
+                if known_mines[i] {
+                    found += 1;
+                } else if hidden(i) {
+                    cells.push(i);
+                }
+            }
+            if !cells.is_empty() {
+                cells.sort_unstable();
+                let mines = (cell.neighbor_mines() as usize).saturating_sub(found);
+                constraints.push(Constraint {
+                    at: (x, y),
+                    cells,
+                    mines,
+                });
+            }
+        }
+    }
+
+    let (mut safe, mut mines) = apply_rules(&constraints);
+
+    // Global mine count, useful for closing out the last cells.
+    if safe.is_empty() && mines.is_empty() {
+        let unknown: Vec<_> = (0..width * board.height())
+            .filter(|&i| hidden(i))
+            .map(|i| (i, Reason::MineCount))
+            .collect();
+        let found = known_mines.iter().filter(|&&m| m).count();
+        let left = board.mine_count().saturating_sub(found);
+        if left == 0 {
+            safe = unknown;
+        } else if left == unknown.len() {
+            mines = unknown;
+        }
+    }
+
+    // a cell can be deduced more than once; keep the first reason found
+    let mut reasons = Vec::new();
+    let mut to_coords = |mut v: Vec<(usize, Reason)>| {
+        v.sort_by_key(|&(i, _)| i);
+        v.dedup_by_key(|&mut (i, _)| i);
+        v.into_iter()
+            .map(|(i, reason)| {
+                let at = (i % width, i / width);
+                reasons.push((at, reason));
+                at
+            })
+            .collect()
+    };
+    let safe = to_coords(safe);
+    let mines = to_coords(mines);
+    reasons.sort_by_key(|&((x, y), _)| (y, x));
+    Step {
+        safe,
+        mines,
+        reasons,
+    }
+}
+
+/// Cells proven one way, each with its reason.
+type Proven = Vec<(usize, Reason)>;
+
+/// The single-number rules over `constraints` and, if they find nothing,
+/// the subset rule: the cells proven safe and the mines, in the order
+/// found.
+fn apply_rules(constraints: &[Constraint]) -> (Proven, Proven) {
+    let mut safe = Vec::new();
+    let mut mines = Vec::new();
+
+    // Single constraints: all-safe or all-mine.
+    for c in constraints {
+        if c.mines == 0 {
+            safe.extend(c.cells.iter().map(|&i| (i, Reason::Satisfied(c.at))));
+        } else if c.mines == c.cells.len() {
+            mines.extend(c.cells.iter().map(|&i| (i, Reason::Filled(c.at))));
+        }
+    }
+
+    // Pairs where one constraint's cells are a subset of another's.
+    if safe.is_empty() && mines.is_empty() {
+        let mut by_cell: HashMap<usize, Vec<usize>> = HashMap::new();
+        for (ci, c) in constraints.iter().enumerate() {
+            for &i in &c.cells {
+                by_cell.entry(i).or_default().push(ci);
+            }
+        }
+        for a in constraints {
+            let Some(candidates) = by_cell.get(&a.cells[0]) else {
+                continue;
+            };
+            for &bi in candidates {
+                let b = &constraints[bi];
+                if b.cells.len() <= a.cells.len() || b.mines < a.mines {
+                    continue;
+                }
+                if !a.cells.iter().all(|i| b.cells.binary_search(i).is_ok()) {
+                    continue;
+                }
+                let rest: Vec<usize> = b
+                    .cells
+                    .iter()
+                    .copied()
+                    .filter(|i| a.cells.binary_search(i).is_err())
+                    .collect();
+                let rest_mines = b.mines - a.mines;
+                let reason = Reason::Subset {
+                    inner: a.at,
+                    outer: b.at,
+                };
+                if rest_mines == 0 {
+                    safe.extend(rest.iter().map(|&i| (i, reason)));
+                } else if rest_mines == rest.len() {
+                    mines.extend(rest.iter().map(|&i| (i, reason)));
+                }
+            }
+        }
+    }
+    (safe, mines)
+}
+
+/// Everything a player could work out from the board as it stands: the
+/// hidden cells that are certainly safe or certainly mines, why each one
+/// is, and an estimated mine chance for every cell.
+///
+/// ```
+/// use minesweeper_rs::{solver, Board};
+///
+/// let mut board = Board::decode_layout("*..\n...\n..*\n")?;
+/// board.reveal_cell(2, 0);
+/// let analysis = solver::analyze(&board);
+/// assert!(analysis.safe().is_empty() && analysis.mines().is_empty());
+/// assert_eq!(analysis.probability(2, 0), 0.0);
+/// assert_eq!(analysis.probability(0, 2), 0.4); // 2 mines over 5 cells
+/// # Ok::<(), minesweeper_rs::LayoutError>(())
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Analysis {
+    width: usize,
+    safe: Vec<(usize, usize)>,
+    mines: Vec<(usize, usize)>,
+    reasons: Vec<Option<Reason>>, // per cell, for the deduced ones
+    probabilities: Vec<f64>,
+}
+
+impl Analysis {
+    /// Hidden cells proven safe, in row-major order.
+    pub fn safe(&self) -> &[(usize, usize)] {
+        &self.safe
+    }
+
+    /// Hidden cells proven to be mines, in row-major order.
+    pub fn mines(&self) -> &[(usize, usize)] {
+        &self.mines
+    }
+
+    /// Why (x, y) is in [`safe`](Self::safe) or [`mines`](Self::mines).
+    /// Reasons from later rounds build on the mines proven before them.
+    ///
+    /// ```
+    /// use minesweeper_rs::solver::{self, Reason};
+    /// use minesweeper_rs::Board;
+    ///
+    /// let mut board = Board::decode_layout("*...\n*...\n")?;
+    /// board.reveal_cell(3, 0);
+    /// let analysis = solver::analyze(&board);
+    /// assert_eq!(analysis.mines(), [(0, 0), (0, 1)]);
+    /// assert_eq!(analysis.reason(0, 0), Some(Reason::Filled((1, 0))));
+    /// assert_eq!(analysis.reason(3, 0), None); // revealed, not deduced
+    /// # Ok::<(), minesweeper_rs::LayoutError>(())
+    /// ```
+    pub fn reason(&self, x: usize, y: usize) -> Option<Reason> {
+        self.reasons[y * self.width + x]
+    }
+
+    /// Estimated chance that (x, y) is a mine; see [`mine_probabilities`].
+    pub fn probability(&self, x: usize, y: usize) -> f64 {
+        self.probabilities[y * self.width + x]
+    }
+
+    /// Every cell's estimate, indexed `y * width + x`.
+    pub fn probabilities(&self) -> &[f64] {
+        &self.probabilities
+    }
+}
+
+/// Deduce what the visible numbers allow. Player flags are ignored, so a
+/// wrong flag can't mislead it.
+pub fn analyze(board: &Board) -> Analysis {
+    let settled = settle(board);
+    let unresolved = (0..settled.len())
+        .filter(|&i| settled.unresolved(board, i))
+        .count();
+    let density = if unresolved == 0 {
+        0.0
+    } else {
+        settled.left(board) as f64 / unresolved as f64
+    };
+    settled.into_analysis(board, |_| density)
+}
+
+/// What the deduction rounds settle, indexed `y * width + x`.
+struct Settled {
+    known_mines: Vec<bool>,
+    safe: Vec<bool>,
+    reasons: Vec<Option<Reason>>, // for the cells above
+}
+
+/// Deduce until no new mines turn up. Safe cells stay hidden, as opening
+/// them would use information the player doesn't have yet.
+fn settle(board: &Board) -> Settled {
+    let width = board.width();
+    let len = width * board.height();
+    let mut known_mines = vec![false; len];
+    let mut safe = vec![false; len];
+    let mut reasons = vec![None; len];
+    loop {
+        let step = step(board, &known_mines);
+        let mut progress = false;
+        for &(x, y) in &step.mines {
+            let i = y * width + x;
+            if !std::mem::replace(&mut known_mines[i], true) {
+                reasons[i] = step.reason(x, y);
+                progress = true;
+            }
+        }
+        for &(x, y) in &step.safe {
+            let i = y * width + x;
+            if !std::mem::replace(&mut safe[i], true) {
+                reasons[i] = step.reason(x, y);
+            }
+        }
+        if !progress {
+            break;
+        }
+    }
+    Settled {
+        known_mines,
+        safe,
+        reasons,
+    }
+}
+
+impl Settled {
+    fn len(&self) -> usize {
+        self.safe.len()
+    }
+
+    /// Whether cell `i` is hidden and neither proven safe nor a mine.
+    fn unresolved(&self, board: &Board, i: usize) -> bool {
+        !board.cells[i].is_revealed() && !self.known_mines[i] && !self.safe[i]
+    }
+
+    /// Mines neither proven nor revealed.
+    fn left(&self, board: &Board) -> usize {
+        let revealed_mines = board
+            .cells
+            .iter()
+            .filter(|c| c.is_revealed() && c.is_mine())
+            .count();
+        let found = self.known_mines.iter().filter(|&&m| m).count();
+        board.mine_count().saturating_sub(found + revealed_mines)
+    }
+
+    /// The analysis, with `chance` giving the unresolved cells' estimates.
+    fn into_analysis(self, board: &Board, chance: impl Fn(usize) -> f64) -> Analysis {
+        let width = board.width();
+        let len = self.len();
+        let probabilities = (0..len)
+            .map(|i| {
+                let cell = board.cells[i];
+                if cell.is_revealed() {
+                    if cell.is_mine() {
+                        1.0
+                    } else {
+                        0.0
+                    }
+                } else if self.known_mines[i] {
+                    1.0
+                } else if self.safe[i] {
+                    0.0
+                } else {
+                    chance(i)
+                }
+            })
+            .collect();
+        let coords = |marked: &[bool]| -> Vec<(usize, usize)> {
+            (0..len)
+                .filter(|&i| marked[i])
+                .map(|i| (i % width, i / width))
+                .collect()
+        };
+        Analysis {
+            width,
+            safe: coords(&self.safe),
+            mines: coords(&self.known_mines),
+            reasons: self.reasons,
+            probabilities,
+        }
+    }
+}
+
+/// Chance that each cell is a mine given what the player can see, indexed
+/// `y * width + x`. Revealed cells and cells the deductions settle get 0 or
+/// 1; the rest share the remaining mines evenly, so this is an estimate
+/// rather than an exact count over every consistent layout.
+///
+/// ```
+/// use minesweeper_rs::{solver, Board};
+///
+/// let board = Board::decode_layout("*...\n")?;
+/// assert_eq!(solver::mine_probabilities(&board), [0.25; 4]);
+/// # Ok::<(), minesweeper_rs::LayoutError>(())
+/// ```
+pub fn mine_probabilities(board: &Board) -> Vec<f64> {
+    analyze(board).probabilities
+}
+
+/// Flags the visible numbers show to be wrong: flagged cells deduction
+/// proves safe, and every flag around a number that touches more flags
+/// than it shows, as at least one of those must be wrong. A flag left out
+/// can still be wrong, just not provably so yet.
+///
+/// ```
+/// use minesweeper_rs::{solver, Board};
+///
+/// let mut board = Board::decode_layout("*..*\n....\n")?;
+/// board.reveal_cell(0, 1);
+/// board.reveal_cell(1, 1);
+/// board.toggle_flag(2, 1); // the two 1s leave no mine for C2
+/// board.toggle_flag(3, 0); // could be right, as far as anyone can see
+/// assert_eq!(solver::contradicted_flags(&board), [(2, 1)]);
+/// # Ok::<(), minesweeper_rs::LayoutError>(())
+/// ```
+pub fn contradicted_flags(board: &Board) -> Vec<(usize, usize)> {
+    let mut wrong: Vec<(usize, usize)> = analyze(board)
+        .safe()
+        .iter()
+        .copied()
+        .filter(|&(x, y)| board.bits(x, y).is_flagged())
+        .collect();
+    for y in 0..board.height() {
+        for x in 0..board.width() {
+            let cell = board.bits(x, y);
+            if !cell.is_revealed() || cell.is_mine() {
+                continue;
+            }
+            let flags: Vec<_> = board
+                .neighbors(x, y)
+                .filter(|&(nx, ny)| board.bits(nx, ny).is_flagged())
+                .collect();
+            if flags.len() > usize::from(cell.neighbor_mines()) {
+                wrong.extend(flags);
+            }
+        }
+    }
+    wrong.sort_unstable_by_key(|&(x, y)| (y, x));
+    wrong.dedup();
+    wrong
+}
+
+/// The cells one visible constraint ties to (x, y), in row-major order:
+/// for a hidden cell, the revealed numbers that count it; for a revealed
+/// number, the hidden cells it counts. Flags count as hidden, as the
+/// solver ignores them.
+///
+/// ```
+/// use minesweeper_rs::{solver, Board};
+///
+/// let mut board = Board::decode_layout("*..\n...\n")?;
+/// board.reveal_cell(1, 0);
+/// board.reveal_cell(1, 1);
+/// assert_eq!(solver::constraint_links(&board, 0, 1), [(1, 0), (1, 1)]);
+/// assert_eq!(solver::constraint_links(&board, 1, 1), [(0, 0), (2, 0), (0, 1), (2, 1)]);
+/// # Ok::<(), minesweeper_rs::LayoutError>(())
+/// ```
+pub fn constraint_links(board: &Board, x: usize, y: usize) -> Vec<(usize, usize)> {
+    let number = |(x, y): (usize, usize)| {
+        let cell = board.bits(x, y);
+        cell.is_revealed() && !cell.is_mine() && cell.neighbor_mines() > 0
+    };
+    let mut links: Vec<_> = if board.bits(x, y).is_revealed() {
+        if !number((x, y)) {
+            return Vec::new();
+        }
+        board
+            .neighbors(x, y)
+            .filter(|&(nx, ny)| !board.bits(nx, ny).is_revealed())
+            .collect()
+    } else {
+        board.neighbors(x, y).filter(|&cell| number(cell)).collect()
+    };
+    links.sort_unstable_by_key(|&(x, y)| (y, x));
+    links
+}
+
+/// Whether the board can be cleared by deduction alone after opening
+/// `start`. Returns `None` if `should_stop` asked to give up early.
+///
+/// ```
+/// use minesweeper_rs::{solver, Board};
+///
+/// let board = Board::decode_layout("*..\n")?;
+/// assert_eq!(solver::solvable_from(&board, (2, 0), &|| false), Some(true));
+/// # Ok::<(), minesweeper_rs::LayoutError>(())
+/// ```
+pub fn solvable_from(
+    board: &Board,
+    start: (usize, usize),
+    should_stop: &dyn Fn() -> bool,
+) -> Option<bool> {
+    let started = Instant::now();
+    let (result, steps) = solve(board, start, should_stop);
+    debug!(
+        ?result,
+        steps,
+        elapsed_us = started.elapsed().as_micros() as u64,
+        "solver finished"
+    );
+    result
+}
+
+/// How far deduction alone gets from a position.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Deduction {
+    /// Whether it clears the board.
+    pub solved: bool,
+    /// Cells in the largest constraint group met on the way: hidden cells
+    /// linked through the revealed numbers they border, which have to be
+    /// reasoned about together.
+    pub largest_group: usize,
+}
+
+/// Deduce and open cells from the position as it stands, player flags
+/// ignored, until the board is cleared or nothing more follows. An
+/// untouched board has nothing to deduce from; open a cell first.
+///
+/// ```
+/// use minesweeper_rs::{solver, Board};
+///
+/// let mut board = Board::decode_layout("*..\n...\n..*\n")?;
+/// board.reveal_cell(2, 0);
+/// let deduction = solver::deduce(&board);
+/// assert!(!deduction.solved);
+/// assert_eq!(deduction.largest_group, 5); // every hidden cell borders a 1
+/// # Ok::<(), minesweeper_rs::LayoutError>(())
+/// ```
+pub fn deduce(board: &Board) -> Deduction {
+    let mut sim = board.clone();
+    sim.clear_flags();
+    let mut known_mines = vec![false; board.width() * board.height()];
+    let mut largest_group = 0;
+    loop {
+        if sim.is_over() {
+            return Deduction {
+                solved: sim.is_won(),
+                largest_group,
+            };
+        }
+        largest_group = largest_group.max(largest_constraint_group(&sim, &known_mines));
+        let step = step(&sim, &known_mines);
+        if step.is_empty() {
+            return Deduction {
+                solved: false,
+                largest_group,
+            };
+        }
+        for (x, y) in step.mines {
+            known_mines[y * sim.width() + x] = true;
+        }
+        for (x, y) in step.safe {
+            sim.reveal_cell(x, y);
+        }
+    }
+}
+
+/// Size of the largest set of unresolved hidden cells joined by the
+/// revealed numbers they border.
+fn largest_constraint_group(board: &Board, known_mines: &[bool]) -> usize {
+    fn root(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+
+    let width = board.width();
+    let len = width * board.height();
+    let mut parent: Vec<usize> = (0..len).collect();
+    let mut bordered = vec![false; len];
+    for y in 0..board.height() {
+        for x in 0..width {
+            let cell = board.bits(x, y);
+            if !cell.is_revealed() || cell.is_mine() || cell.neighbor_mines() == 0 {
+                continue;
+            }
+            let mut group = None;
+            for (nx, ny) in board.neighbors(x, y) {
+                let i = ny * width + nx;
+                if board.cells[i].is_revealed() || known_mines[i] {
+                    continue;
+                }
+                bordered[i] = true;
+                let other = root(&mut parent, i);
+                match group {
+                    None => group = Some(other),
+                    Some(group) => parent[other] = group,
+                }
+            }
+        }
+    }
+
+    let mut sizes = vec![0; len];
+    for i in (0..len).filter(|&i| bordered[i]) {
+        sizes[root(&mut parent, i)] += 1;
+    }
+    sizes.into_iter().max().unwrap_or(0)
+}
+
+/// The solver loop behind `solvable_from`; also counts deduction rounds.
+fn solve(
+    board: &Board,
+    start: (usize, usize),
+    should_stop: &dyn Fn() -> bool,
+) -> (Option<bool>, u32) {
+    let mut sim = board.clone();
+    let mut known_mines = vec![false; board.width() * board.height()];
+
+    if sim.reveal_cell(start.0, start.1) == RevealResult::Exploded {
+        return (Some(false), 0);
+    }
+
+    let mut steps = 0;
+    loop {
+        if sim.is_over() {
+            return (Some(sim.is_won()), steps);
+        }
+        if should_stop() {
+            return (None, steps);
+        }
+
+        let step = step(&sim, &known_mines);
+        if step.is_empty() {
+            return (Some(false), steps);
+        }
+        for (x, y) in step.mines {
+            known_mines[y * sim.width() + x] = true;
+        }
+        for (x, y) in step.safe {
+            sim.reveal_cell(x, y);
+        }
+        steps += 1;
+    }
+}
@@ -0,0 +1,699 @@
+//! Counted mine chances, kept up to date from one position to the next.
+//!
+//! [`analyze`](super::analyze) gives every unresolved cell the same share
+//! of the mines left. A [`Solver`] counts instead. The hidden cells next to
+//! revealed numbers fall into groups joined by the numbers they share, and
+//! each group is worked on its own: the deduction rules of [`step`] settle
+//! what they can, and every placement of mines on the cells left that
+//! meets the numbers is enumerated and tallied by how many mines it uses.
+//! The tallies and the cells no number touches are then weighed together
+//! against the mines left, so a cell's chance reflects the whole board.
+//!
+//! Working a group is the slow part, so a solver keeps each group's
+//! result, keyed by the group itself: its cells and the numbers on them.
+//! The next analysis rebuilds the groups, which is quick, and only works
+//! the ones it hasn't met, which after a single move is the group or two
+//! the move touched. The dirty cells passed in decide which results are
+//! worth keeping; the answers never depend on them, so a stale or empty
+//! list costs time, not correctness.
+//!
+//! [`step`]: super::step
+
+use super::{apply_rules, Analysis, Constraint, Reason, Settled};
+use crate::Board;
+use std::collections::HashMap;
+
+/// Most placements tried on one part of a group; cells past it are
+/// weighed as if no number touched them.
+pub const BUDGET: usize = 2_000_000;
+
+/// Analyses an unused result outlives, in case its group comes back.
+const KEEP: u64 = 8;
+
+/// What changed on the board since the solver last looked at it.
+///
+/// ```
+/// use minesweeper_rs::solver::{DirtyInfo, Solver};
+/// use minesweeper_rs::Board;
+///
+/// let mut board = Board::decode_layout("*..\n...\n..*\n")?;
+/// let mut solver = Solver::new();
+/// solver.analyze(&board);
+/// board.take_dirty();
+/// board.reveal_cell(2, 0);
+/// let dirty = DirtyInfo::cells(board.take_dirty());
+/// let analysis = solver.analyze_incremental(&board, &dirty);
+/// assert_eq!(analysis.probability(2, 0), 0.0);
+/// # Ok::<(), minesweeper_rs::LayoutError>(())
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DirtyInfo {
+    cells: Vec<(usize, usize)>,
+    everything: bool,
+}
+
+impl DirtyInfo {
+    /// These cells changed, as `Board::take_dirty` lists them.
+    pub fn cells(cells: Vec<(usize, usize)>) -> DirtyInfo {
+        DirtyInfo {
+            cells,
+            everything: false,
+        }
+    }
+
+    /// The board may have changed anywhere: a new game, an undo or a
+    /// loaded position. Everything the solver kept is dropped.
+    pub fn everything() -> DirtyInfo {
+        DirtyInfo {
+            cells: Vec::new(),
+            everything: true,
+        }
+    }
+}
+
+/// How the last analysis came by its group results.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Groups worked afresh.
+    pub counted: usize,
+    /// Groups whose result was kept from before.
+    pub reused: usize,
+}
+
+/// Hidden cells joined by the revealed numbers they border.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct Group {
+    cells: Vec<usize>, // sorted board indices
+    numbers: Vec<Number>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct Number {
+    at: (usize, usize),
+    members: Vec<usize>, // sorted positions in the group's cells
+    mines: usize,        // shown, less any revealed mines beside it
+}
+
+/// What a group works out to on its own.
+struct Worked {
+    settled: Vec<(usize, bool, Reason)>, // position, whether a mine, why
+    parts: Vec<Part>,
+}
+
+/// Unresolved cells of a group still joined by its numbers, with their
+/// tally; none once enumeration ran past the budget.
+struct Part {
+    cells: Vec<usize>, // positions in the group's cells
+    tally: Option<Tally>,
+}
+
+/// A number's open cells, as positions in a part, and the mines it still
+/// wants among them.
+type Rule = (Vec<usize>, usize);
+
+/// A part's placements by mine count, scaled so the largest is 1.
+struct Tally {
+    totals: Vec<f64>,
+    hits: Vec<Vec<f64>>, // per cell, the placements with a mine there
+}
+
+struct Cached {
+    worked: Worked,
+    used: u64, // the analysis that last needed it
+}
+
+/// Counted analyses that remember each group's result between calls.
+///
+/// ```
+/// use minesweeper_rs::solver::{self, Solver};
+/// use minesweeper_rs::Board;
+///
+/// let mut board = Board::decode_layout("*..*\n....\n")?;
+/// board.reveal_cell(1, 1);
+/// board.reveal_cell(2, 1);
+/// // a mine on B1 or C1 would meet both 1s and leave the second mine
+/// // nowhere to go, which only counting notices
+/// let mut solver = Solver::new();
+/// let analysis = solver.analyze(&board);
+/// assert_eq!(analysis.probability(1, 0), 0.0);
+/// assert_eq!(analysis.probability(0, 0), 0.5);
+/// assert_eq!(solver::analyze(&board).probability(1, 0), 1.0 / 3.0);
+/// # Ok::<(), minesweeper_rs::LayoutError>(())
+/// ```
+#[derive(Default)]
+pub struct Solver {
+    size: (usize, usize),
+    cache: HashMap<Group, Cached>,
+    analyses: u64,
+    stats: CacheStats,
+}
+
+impl Solver {
+    /// A solver with nothing kept yet.
+    pub fn new() -> Solver {
+        Solver::default()
+    }
+
+    /// Analyse `board` from scratch, forgetting every kept result.
+    pub fn analyze(&mut self, board: &Board) -> Analysis {
+        self.analyze_incremental(board, &DirtyInfo::everything())
+    }
+
+    /// Analyse `board`, working only the groups not met before. `dirty`
+    /// lists what changed since the last call; a board of another size
+    /// starts over whatever it says.
+    ///
+    /// The deductions are those of [`step`](super::step), run on each
+    /// group until it finds no more mines and then with the mine count,
+    /// so they can settle a little more or less than
+    /// [`analyze`](super::analyze), whose rounds cover the whole board.
+    pub fn analyze_incremental(&mut self, board: &Board, dirty: &DirtyInfo) -> Analysis {
+        self.run(board, dirty, None)
+    }
+
+    /// As [`analyze`](Self::analyze), but only (x, y)'s chance is weighed
+    /// out, so no other unresolved cell's should be read. The groups are all still
+    /// worked, since the mines each may hold bear on the rest, but the
+    /// counting is only carried through the part (x, y) is in.
+    pub(crate) fn analyze_cell(&mut self, board: &Board, x: usize, y: usize) -> Analysis {
+        let only = y * board.width() + x;
+        self.run(board, &DirtyInfo::everything(), Some(only))
+    }
+
+    /// An analysis, of every cell's chance or of `only` that one's.
+    fn run(&mut self, board: &Board, dirty: &DirtyInfo, only: Option<usize>) -> Analysis {
+        let size = (board.width(), board.height());
+        if dirty.everything || size != self.size {
+            self.cache.clear();
+            self.size = size;
+        }
+        self.analyses += 1;
+        let now = self.analyses;
+        self.stats = CacheStats::default();
+
+        let groups = groups(board);
+        for group in &groups {
+            match self.cache.get_mut(group) {
+                Some(cached) => {
+                    cached.used = now;
+                    self.stats.reused += 1;
+                }
+                None => {
+                    let worked = group.work();
+                    self.cache
+                        .insert(group.clone(), Cached { worked, used: now });
+                    self.stats.counted += 1;
+                }
+            }
+        }
+        let worked: Vec<&Worked> = groups.iter().map(|g| &self.cache[g].worked).collect();
+        let analysis = combine(board, &groups, &worked, only);
+
+        // results for cells that changed can only come back after an undo
+        let (width, height) = size;
+        let mut changed = vec![false; width * height];
+        for &(x, y) in dirty
+            .cells
+            .iter()
+            .filter(|&&(x, y)| x < width && y < height)
+        {
+            changed[y * width + x] = true;
+        }
+        self.cache.retain(|group, cached| {
+            let stale = group.cells.iter().any(|&i| changed[i]);
+            cached.used == now || (now - cached.used < KEEP && !stale)
+        });
+        analysis
+    }
+
+    /// How the last analysis came by its group results.
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    /// Whether the last analysis counted (x, y)'s chance, rather than
+    /// weighing it with the cells no number touches because its part ran
+    /// past [`BUDGET`]. True for a cell in no part at all.
+    pub(crate) fn counted(&self, x: usize, y: usize) -> bool {
+        let i = y * self.size.0 + x;
+        (self.cache.iter())
+            .filter(|(_, cached)| cached.used == self.analyses)
+            .find_map(|(group, cached)| {
+                let at = group.cells.binary_search(&i).ok()?;
+                let mut parts = cached.worked.parts.iter();
+                parts.find(|part| part.cells.contains(&at))
+            })
+            .is_none_or(|part| part.tally.is_some())
+    }
+}
+
+/// The groups on the board, ordered by their first cell. Flags are
+/// ignored, as they are everywhere in the solver.
+fn groups(board: &Board) -> Vec<Group> {
+    let width = board.width();
+    let len = width * board.height();
+
+    // every number with hidden neighbours, and the mines it shows
+    let mut numbers: Vec<Number> = Vec::new();
+    let mut cells_of: Vec<Vec<usize>> = Vec::new(); // board indices, per number
+    for i in 0..len {
+        let cell = board.cells[i];
+        if !cell.is_revealed() || cell.is_mine() || cell.neighbor_mines() == 0 {
+            continue;
+        }
+        let (x, y) = (i % width, i / width);
+        let mut cells = Vec::new();
+        let mut revealed_mines = 0;
+        for (nx, ny) in board.neighbors(x, y) {
+            let j = ny * width + nx;
+            if !board.cells[j].is_revealed() {
+                cells.push(j);
+            } else if board.cells[j].is_mine() {
+                revealed_mines += 1;
+            }
+        }
+        if !cells.is_empty() {
+            cells.sort_unstable();
+            numbers.push(Number {
+                at: (x, y),
+                members: Vec::new(),
+                mines: usize::from(cell.neighbor_mines()).saturating_sub(revealed_mines),
+            });
+            cells_of.push(cells);
+        }
+    }
+
+    // join numbers that share a cell
+    fn root(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+    let mut parent: Vec<usize> = (0..numbers.len()).collect();
+    let mut first = vec![usize::MAX; len]; // the first number seen on each cell
+    for (n, cells) in cells_of.iter().enumerate() {
+        for &i in cells {
+            if first[i] == usize::MAX {
+                first[i] = n;
+            } else {
+                let (a, b) = (root(&mut parent, first[i]), root(&mut parent, n));
+                parent[b] = a;
+            }
+        }
+    }
+
+    let mut joined: HashMap<usize, Vec<usize>> = HashMap::new();
+    for n in 0..numbers.len() {
+        joined.entry(root(&mut parent, n)).or_default().push(n);
+    }
+    let mut groups: Vec<Group> = joined
+        .into_values()
+        .map(|ns| {
+            let mut cells: Vec<usize> = ns.iter().flat_map(|&n| cells_of[n].clone()).collect();
+            cells.sort_unstable();
+            cells.dedup();
+            let numbers = ns
+                .iter()
+                .map(|&n| {
+                    let members = (cells_of[n].iter())
+                        .map(|i| cells.binary_search(i).expect("a cell of the group"))
+                        .collect();
+                    Number {
+                        members,
+                        ..numbers[n].clone()
+                    }
+                })
+                .collect();
+            Group { cells, numbers }
+        })
+        .collect();
+    groups.sort_unstable_by_key(|group| group.cells[0]);
+    groups
+}
+
+impl Group {
+    /// Deduce until no new mines turn up, as `analyze` does over the whole
+    /// board, then tally the cells left in each part.
+    fn work(&self) -> Worked {
+        let n = self.cells.len();
+        let mut known = vec![false; n];
+        let mut safe = vec![false; n];
+        let mut reasons = vec![None; n];
+        loop {
+            let constraints: Vec<Constraint> = self
+                .numbers
+                .iter()
+                .filter_map(|number| {
+                    let cells: Vec<usize> = (number.members.iter().copied())
+                        .filter(|&m| !known[m])
+                        .collect();
+                    let found = number.members.len() - cells.len();
+                    (!cells.is_empty()).then(|| Constraint {
+                        at: number.at,
+                        cells,
+                        mines: number.mines.saturating_sub(found),
+                    })
+                })
+                .collect();
+            let (proven_safe, proven_mines) = apply_rules(&constraints);
+            let mut progress = false;
+            for (mut proven, mine) in [(proven_mines, true), (proven_safe, false)] {
+                // a cell can be deduced more than once; keep the first reason
+                proven.sort_by_key(|&(i, _)| i);
+                proven.dedup_by_key(|&mut (i, _)| i);
+                let marks = if mine { &mut known } else { &mut safe };
+                for (i, reason) in proven {
+                    if !std::mem::replace(&mut marks[i], true) {
+                        reasons[i] = Some(reason);
+                        progress |= mine;
+                    }
+                }
+            }
+            if !progress {
+                break;
+            }
+        }
+
+        let settled = (0..n)
+            .filter_map(|i| Some((i, known[i], reasons[i]?)))
+            .collect();
+        let open = |i: usize| !known[i] && !safe[i];
+
+        // split the open cells by the numbers still joining them
+        let mut part_of = vec![usize::MAX; n];
+        let mut parts: Vec<(Vec<usize>, Vec<Rule>)> = Vec::new();
+        for start in (0..n).filter(|&i| open(i)) {
+            if part_of[start] != usize::MAX {
+                continue;
+            }
+            let id = parts.len();
+            part_of[start] = id;
+            let mut cells = vec![start];
+            let mut next = 0;
+            while next < cells.len() {
+                let cell = cells[next];
+                next += 1;
+                for number in self.numbers.iter().filter(|n| n.members.contains(&cell)) {
+                    for &m in &number.members {
+                        if open(m) && part_of[m] == usize::MAX {
+                            part_of[m] = id;
+                            cells.push(m);
+                        }
+                    }
+                }
+            }
+            cells.sort_unstable();
+            parts.push((cells, Vec::new()));
+        }
+        for number in &self.numbers {
+            let Some(&first) = number.members.iter().find(|&&m| open(m)) else {
+                continue;
+            };
+            let (cells, constraints) = &mut parts[part_of[first]];
+            let members = (number.members.iter())
+                .filter(|&&m| open(m))
+                .map(|m| cells.binary_search(m).expect("a cell of the part"))
+                .collect();
+            let found = number.members.iter().filter(|&&m| known[m]).count();
+            constraints.push((members, number.mines.saturating_sub(found)));
+        }
+        let parts = parts
+            .into_iter()
+            .map(|(cells, constraints)| Part {
+                tally: tally(cells.len(), &constraints),
+                cells,
+            })
+            .collect();
+        Worked { settled, parts }
+    }
+}
+
+/// Every placement of mines on `n` cells that meets `constraints`,
+/// tallied; `None` past the budget or if
+/// no placement fits.
+fn tally(n: usize, constraints: &[Rule]) -> Option<Tally> {
+    let mut by_cell = vec![Vec::new(); n];
+    for (c, (members, _)) in constraints.iter().enumerate() {
+        for &m in members {
+            by_cell[m].push(c);
+        }
+    }
+    let mut search = Search {
+        wanted: constraints.iter().map(|&(_, w)| w).collect(),
+        open: constraints.iter().map(|(m, _)| m.len()).collect(),
+        placed: vec![0; constraints.len()],
+        by_cell,
+        mines: vec![false; n],
+        count: 0,
+        totals: vec![0.0; n + 1],
+        hits: vec![vec![0.0; n + 1]; n],
+        steps: 0,
+    };
+    if !search.place(0) {
+        return None;
+    }
+    let top = search.totals.iter().copied().fold(0.0, f64::max);
+    if top == 0.0 {
+        return None;
+    }
+    let scale = |v: &mut Vec<f64>| v.iter_mut().for_each(|t| *t /= top);
+    scale(&mut search.totals);
+    search.hits.iter_mut().for_each(scale);
+    Some(Tally {
+        totals: search.totals,
+        hits: search.hits,
+    })
+}
+
+/// Backtracking over a part's cells in order, keeping for each number the
+/// mines placed and the cells still open around it.
+struct Search {
+    wanted: Vec<usize>,
+    open: Vec<usize>,
+    placed: Vec<usize>,
+    by_cell: Vec<Vec<usize>>, // the numbers each cell is under
+    mines: Vec<bool>,
+    count: usize, // mines placed so far
+    totals: Vec<f64>,
+    hits: Vec<Vec<f64>>,
+    steps: usize,
+}
+
+impl Search {
+    /// Try both values for `cell` and everything after it. False once the
+    /// budget is spent.
+    fn place(&mut self, cell: usize) -> bool {
+        if cell == self.mines.len() {
+            self.totals[self.count] += 1.0;
+            for (hits, _) in self.hits.iter_mut().zip(&self.mines).filter(|(_, &m)| m) {
+                hits[self.count] += 1.0;
+            }
+            return true;
+        }
+        for mine in [false, true] {
+            self.steps += 1;
+            if self.steps > BUDGET {
+                return false;
+            }
+            let added = usize::from(mine);
+            self.mines[cell] = mine;
+            self.count += added;
+            for &c in &self.by_cell[cell] {
+                self.open[c] -= 1;
+                self.placed[c] += added;
+            }
+            let fits = self.by_cell[cell].iter().all(|&c| {
+                self.placed[c] <= self.wanted[c] && self.placed[c] + self.open[c] >= self.wanted[c]
+            });
+            let finished = !fits || self.place(cell + 1);
+            for &c in &self.by_cell[cell] {
+                self.open[c] += 1;
+                self.placed[c] -= added;
+            }
+            self.count -= added;
+            self.mines[cell] = false;
+            if !finished {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Put the groups' results together: their deductions, the mine count
+/// over what they leave, and the counted chances, of every cell or only
+/// of `only`.
+fn combine(board: &Board, groups: &[Group], worked: &[&Worked], only: Option<usize>) -> Analysis {
+    let len = board.width() * board.height();
+    let mut settled = Settled {
+        known_mines: vec![false; len],
+        safe: vec![false; len],
+        reasons: vec![None; len],
+    };
+    for (group, worked) in groups.iter().zip(worked) {
+        for &(i, mine, reason) in &worked.settled {
+            let i = group.cells[i];
+            let marks = if mine {
+                &mut settled.known_mines
+            } else {
+                &mut settled.safe
+            };
+            marks[i] = true;
+            settled.reasons[i] = Some(reason);
+        }
+    }
+
+    // the mine count closes out the last cells, as in `step`
+    let left = settled.left(board);
+    let unknown: Vec<usize> = (0..len)
+        .filter(|&i| !board.cells[i].is_revealed() && !settled.known_mines[i])
+        .collect();
+    let marks = if left == 0 {
+        Some(&mut settled.safe)
+    } else if left == unknown.len() {
+        Some(&mut settled.known_mines)
+    } else {
+        None
+    };
+    if let Some(marks) = marks {
+        for i in unknown {
+            if !std::mem::replace(&mut marks[i], true) {
+                settled.reasons[i] = Some(Reason::MineCount);
+            }
+        }
+        return settled.into_analysis(board, |_| 0.0);
+    }
+
+    let chances = chances(board, &settled, groups, worked, only);
+    settled.into_analysis(board, |i| chances[i])
+}
+
+/// Each unresolved cell's counted chance, indexed `y * width + x`, or
+/// only `only`'s with the rest left at 0. Cells of a part past the budget
+/// are weighed with the ones no number touches. If the counts admit no
+/// layout at all, which a board only does with a contradiction on it,
+/// every unresolved cell gets the flat share.
+fn chances(
+    board: &Board,
+    settled: &Settled,
+    groups: &[Group],
+    worked: &[&Worked],
+    only: Option<usize>,
+) -> Vec<f64> {
+    let len = settled.len();
+    let left = settled.left(board);
+    // each counted part as board indices, with its tally
+    let counted: Vec<(Vec<usize>, &Tally)> = groups
+        .iter()
+        .zip(worked)
+        .flat_map(|(group, worked)| {
+            worked.parts.iter().filter_map(|part| {
+                let cells = part.cells.iter().map(|&p| group.cells[p]).collect();
+                Some((cells, part.tally.as_ref()?))
+            })
+        })
+        .collect();
+    let mut in_part = vec![false; len];
+    for &i in counted.iter().flat_map(|(cells, _)| cells) {
+        in_part[i] = true;
+    }
+    let free: Vec<usize> = (0..len)
+        .filter(|&i| settled.unresolved(board, i) && !in_part[i])
+        .collect();
+    let elsewhere = free.len();
+
+    // weight of K mines in the parts: the ways to put the rest elsewhere
+    let most: usize = counted.iter().map(|(cells, _)| cells.len()).sum();
+    let ln_weights: Vec<Option<f64>> = (0..=most)
+        .map(|k| {
+            let rest = left.checked_sub(k).filter(|&rest| rest <= elsewhere)?;
+            Some(ln_choose(elsewhere, rest))
+        })
+        .collect();
+    let top = ln_weights
+        .iter()
+        .flatten()
+        .copied()
+        .fold(f64::MIN, f64::max);
+    let weights: Vec<f64> = ln_weights
+        .iter()
+        .map(|w| w.map_or(0.0, |w| (w - top).exp()))
+        .collect();
+
+    // placements over all parts by mine count, and over all but one
+    let mut prefix = vec![vec![1.0]];
+    for (_, tally) in &counted {
+        prefix.push(convolve(prefix.last().unwrap(), &tally.totals));
+    }
+    let mut suffix = vec![vec![1.0]];
+    for (_, tally) in counted.iter().rev() {
+        suffix.push(convolve(suffix.last().unwrap(), &tally.totals));
+    }
+    suffix.reverse();
+    let all = &prefix[counted.len()];
+    let total: f64 = all.iter().zip(&weights).map(|(t, w)| t * w).sum();
+    if total == 0.0 || !total.is_finite() {
+        let flat = left as f64 / (elsewhere + most).max(1) as f64;
+        return vec![flat; len];
+    }
+
+    let mut chances = vec![0.0; len];
+    let wanted = |i: usize| only.is_none_or(|only| only == i);
+    if elsewhere > 0 && free.iter().any(|&i| wanted(i)) {
+        let mines: f64 = all
+            .iter()
+            .zip(&weights)
+            .enumerate()
+            .map(|(k, (t, w))| t * w * left.saturating_sub(k) as f64)
+            .sum();
+        let chance = mines / (total * elsewhere as f64);
+        for &i in free.iter().filter(|&&i| wanted(i)) {
+            chances[i] = chance;
+        }
+    }
+    for (p, (cells, tally)) in counted.iter().enumerate() {
+        if !cells.iter().any(|&i| wanted(i)) {
+            continue;
+        }
+        let others = convolve(&prefix[p], &suffix[p + 1]);
+        // weight of this part holding k mines, over the others' placements
+        let reach: Vec<f64> = (0..tally.totals.len())
+            .map(|k| {
+                others
+                    .iter()
+                    .enumerate()
+                    .map(|(j, o)| o * weights.get(k + j).copied().unwrap_or(0.0))
+                    .sum()
+            })
+            .collect();
+        let dot = |v: &[f64]| -> f64 { v.iter().zip(&reach).map(|(a, b)| a * b).sum() };
+        let whole = dot(&tally.totals);
+        for (&i, hits) in cells.iter().zip(&tally.hits).filter(|(&i, _)| wanted(i)) {
+            chances[i] = if whole > 0.0 { dot(hits) / whole } else { 0.0 };
+        }
+    }
+    chances
+}
+
+/// The distribution of a sum of two independent counts, scaled so the
+/// largest entry is 1 to keep long products in range.
+fn convolve(a: &[f64], b: &[f64]) -> Vec<f64> {
+    let mut out = vec![0.0; a.len() + b.len() - 1];
+    for (i, x) in a.iter().enumerate() {
+        for (j, y) in b.iter().enumerate() {
+            out[i + j] += x * y;
+        }
+    }
+    let top = out.iter().copied().fold(0.0, f64::max);
+    if top > 0.0 {
+        out.iter_mut().for_each(|v| *v /= top);
+    }
+    out
+}
+
+/// ln C(n, k), which stays finite where the count itself would overflow.
+fn ln_choose(n: usize, k: usize) -> f64 {
+    (1..=k).map(|i| ((n - k + i) as f64 / i as f64).ln()).sum()
+}
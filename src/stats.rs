@@ -0,0 +1,226 @@
+//! Per-game input statistics and the board's 3BV.
+//!
+//! `InputStats` is fed the results the board hands back for each action,
+//! not raw UI events, so every frontend counts clicks the same way.
+
+use crate::{Board, FlagResult, Marking, MarkingResult, RevealResult};
+
+/// Clicks and flags for one game.
+///
+/// ```
+/// use minesweeper_rs::stats::InputStats;
+/// use minesweeper_rs::Board;
+///
+/// let mut board = Board::decode_layout("*..\n")?;
+/// let mut stats = InputStats::default();
+/// stats.record_flag(board.toggle_flag(0, 0));
+/// stats.record_reveal(board.reveal_cell(2, 0));
+/// stats.finish(&board);
+/// assert_eq!((stats.effective_clicks(), stats.flags_correct), (2, 1));
+/// # Ok::<(), minesweeper_rs::LayoutError>(())
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct InputStats {
+    /// Reveal attempts.
+    pub left_clicks: u32,
+    /// Flag attempts.
+    pub right_clicks: u32,
+    /// Clicks the board ignored.
+    pub wasted_clicks: u32,
+    /// Flags put down, including ones later removed.
+    pub flags_placed: u32,
+    /// Flags on mines when the game ended.
+    pub flags_correct: u32,
+}
+
+impl InputStats {
+    /// Count a reveal and what the board made of it.
+    pub fn record_reveal(&mut self, result: RevealResult) {
+        self.left_clicks += 1;
+        if matches!(result, RevealResult::Ignored(_)) {
+            self.wasted_clicks += 1;
+        }
+    }
+
+    /// Count a flag toggle and what the board made of it.
+    pub fn record_flag(&mut self, result: FlagResult) {
+        self.right_clicks += 1;
+        match result {
+            FlagResult::Flagged => self.flags_placed += 1,
+            FlagResult::Unflagged => {}
+            FlagResult::Ignored(_) => self.wasted_clicks += 1,
+        }
+    }
+
+    /// Count a marking click and what the board made of it. Only flags
+    /// count as placed; a click that changed nothing is wasted.
+    pub fn record_marking(&mut self, result: MarkingResult) {
+        self.right_clicks += 1;
+        match result {
+            MarkingResult::Changed {
+                to: Marking::Flag, ..
+            } => self.flags_placed += 1,
+            MarkingResult::Changed { .. } => {}
+            MarkingResult::Unchanged | MarkingResult::Ignored(_) => self.wasted_clicks += 1,
+        }
+    }
+
+    /// Settle the flag tally once the game is over.
+    pub fn finish(&mut self, board: &Board) {
+        let correct = board
+            .cells
+            .iter()
+            .filter(|cell| cell.is_flagged() && cell.is_mine())
+            .count();
+        self.flags_correct = correct as u32;
+    }
+
+    /// Clicks that changed the board.
+    pub fn effective_clicks(&self) -> u32 {
+        self.left_clicks + self.right_clicks - self.wasted_clicks
+    }
+
+    /// 3BV per effective click, as a percentage. `None` before any click.
+    pub fn efficiency(&self, three_bv: usize) -> Option<f64> {
+        let clicks = self.effective_clicks();
+        (clicks > 0).then(|| three_bv as f64 * 100.0 / clicks as f64)
+    }
+}
+
+/// The board's 3BV: the fewest left clicks that clear it without flags.
+/// Each blank opening counts once, plus every numbered safe cell that no
+/// opening reaches.
+///
+/// ```
+/// use minesweeper_rs::{stats, Board};
+///
+/// let board = Board::decode_layout(".*.\n")?;
+/// assert_eq!(stats::three_bv(&board), 2);
+/// # Ok::<(), minesweeper_rs::LayoutError>(())
+/// ```
+pub fn three_bv(board: &Board) -> usize {
+    let (openings, reached) = flood_openings(board);
+    let width = board.width();
+    let lone = (0..board.height())
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .filter(|&(x, y)| !reached[y * width + x] && !board.bits(x, y).is_mine())
+        .count();
+    openings.len() + lone
+}
+
+/// One blank cell from each opening, the areas a single click clears, in
+/// reading order. Like [`three_bv`], this reads the whole layout.
+///
+/// ```
+/// use minesweeper_rs::{stats, Board};
+///
+/// let board = Board::decode_layout("..*..\n..*..\n")?;
+/// assert_eq!(stats::openings(&board), [(0, 0), (4, 0)]);
+/// # Ok::<(), minesweeper_rs::LayoutError>(())
+/// ```
+pub fn openings(board: &Board) -> Vec<(usize, usize)> {
+    flood_openings(board).0
+}
+
+/// The cell to open first for the largest opening: a blank cell whose
+/// flood reveals the most cells, numbered edges included, and with no mine
+/// within `radius` of it, as a safe first click asks. Ties go to the
+/// opening found first in reading order, and within an opening to its
+/// first cell that qualifies. `None` if no blank cell does. Like
+/// [`three_bv`], this reads the whole layout, so it is for whoever placed
+/// the mines, not the player.
+///
+/// ```
+/// use minesweeper_rs::{stats, Board};
+///
+/// let board = Board::decode_layout("...*....\n...*....\n")?;
+/// assert_eq!(stats::best_opening(&board, 1), Some((5, 0)));
+/// # Ok::<(), minesweeper_rs::LayoutError>(())
+/// ```
+pub fn best_opening(board: &Board, radius: usize) -> Option<(usize, usize)> {
+    let (width, height) = (board.width(), board.height());
+    let index = |x: usize, y: usize| y * width + x;
+    let blank = |x: usize, y: usize| {
+        let cell = board.bits(x, y);
+        !cell.is_mine() && cell.neighbor_mines() == 0
+    };
+    let clear = |x: usize, y: usize| {
+        let xs = x.saturating_sub(radius)..=(x + radius).min(width - 1);
+        (y.saturating_sub(radius)..=(y + radius).min(height - 1))
+            .all(|ny| xs.clone().all(|nx| !board.bits(nx, ny).is_mine()))
+    };
+    // the opening that last reached each cell, numbered from 1, so edge
+    // cells shared between openings count towards each
+    let mut reached = vec![0; width * height];
+    let mut best: Option<((usize, usize), usize)> = None;
+    let mut opening = 0;
+
+    for y in 0..height {
+        for x in 0..width {
+            if reached[index(x, y)] != 0 || !blank(x, y) {
+                continue;
+            }
+            opening += 1;
+            reached[index(x, y)] = opening;
+            let mut size = 1;
+            let mut first = clear(x, y).then_some((y, x));
+            let mut stack = vec![(x, y)];
+            while let Some((x, y)) = stack.pop() {
+                for (nx, ny) in board.neighbors(x, y) {
+                    if reached[index(nx, ny)] == opening {
+                        continue;
+                    }
+                    reached[index(nx, ny)] = opening;
+                    size += 1;
+                    if blank(nx, ny) {
+                        stack.push((nx, ny));
+                        if clear(nx, ny) && first.is_none_or(|f| (ny, nx) < f) {
+                            first = Some((ny, nx));
+                        }
+                    }
+                }
+            }
+            if let Some((fy, fx)) = first {
+                if best.is_none_or(|(_, most)| size > most) {
+                    best = Some(((fx, fy), size));
+                }
+            }
+        }
+    }
+    best.map(|(cell, _)| cell)
+}
+
+/// Flood every opening, returning a cell of each and which cells the
+/// floods reached, numbered edges included.
+fn flood_openings(board: &Board) -> (Vec<(usize, usize)>, Vec<bool>) {
+    let (width, height) = (board.width(), board.height());
+    let index = |x: usize, y: usize| y * width + x;
+    let blank = |x: usize, y: usize| {
+        let cell = board.bits(x, y);
+        !cell.is_mine() && cell.neighbor_mines() == 0
+    };
+    let mut reached = vec![false; width * height];
+    let mut openings = Vec::new();
+
+    for y in 0..height {
+        for x in 0..width {
+            if reached[index(x, y)] || !blank(x, y) {
+                continue;
+            }
+            openings.push((x, y));
+            reached[index(x, y)] = true;
+            let mut stack = vec![(x, y)];
+            while let Some((x, y)) = stack.pop() {
+                for (nx, ny) in board.neighbors(x, y) {
+                    if !reached[index(nx, ny)] {
+                        reached[index(nx, ny)] = true;
+                        if blank(nx, ny) {
+                            stack.push((nx, ny));
+                        }
+                    }
+                }
+            }
+        }
+    }
+    (openings, reached)
+}
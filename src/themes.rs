@@ -0,0 +1,338 @@
+//! Board colour themes: the built-in ones, among them a high-contrast and
+//! a colour-blind-safe palette, and the player's own, kept as TOML files
+//! in a `themes` folder under the data dir so they can be passed around.
+//!
+//! A theme file may name any of the colours, as `"#rrggbb"`; the ones it
+//! leaves out come from the default theme, and keys this version doesn't
+//! know are skipped, so a file from a newer or older version still loads:
+//!
+//! ```toml
+//! name = "Dusk"
+//! hidden = "#3b4252"
+//! flag = "#bf616a"
+//! ```
+//!
+//! Without a theme picked, the board follows egui's light or dark look as
+//! it always has.
+
+use crate::screens::editor::file_stem;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use tracing::info;
+
+/// One colour, written `#rrggbb` in theme files.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rgb(pub [u8; 3]);
+
+impl Rgb {
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Rgb([r, g, b])
+    }
+}
+
+impl fmt::Display for Rgb {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [r, g, b] = self.0;
+        write!(f, "#{r:02x}{g:02x}{b:02x}")
+    }
+}
+
+impl FromStr for Rgb {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let hex = text.trim().trim_start_matches('#');
+        let channel = |i: usize| {
+            hex.get(i..i + 2)
+                .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+        };
+        match (hex.len(), channel(0), channel(2), channel(4)) {
+            (6, Some(r), Some(g), Some(b)) => Ok(Rgb([r, g, b])),
+            _ => Err(format!("{text:?} isn't a colour like \"#1e90ff\"")),
+        }
+    }
+}
+
+impl Serialize for Rgb {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Rgb {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        text.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Every colour the board is painted in.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Palette {
+    pub name: String,
+    pub background: Rgb, // behind the cells, showing between them
+    pub hidden: Rgb,
+    pub hovered: Rgb,
+    pub pressed: Rgb,
+    pub open: Rgb,
+    pub text: Rgb,         // question marks, and flag poles and mine spikes
+    pub numbers: [Rgb; 8], // counts 1 to 8, as numerals, pips or dots
+    pub flag: Rgb,
+    pub mine: Rgb,
+    pub exploded: Rgb,  // the mine that ended the game
+    pub highlight: Rgb, // the keyboard cursor
+}
+
+impl Default for Palette {
+    /// The grey board and numbers of the classic game, unnamed.
+    fn default() -> Self {
+        Palette {
+            name: String::new(),
+            background: Rgb::new(128, 128, 128),
+            hidden: Rgb::new(192, 192, 192),
+            hovered: Rgb::new(208, 208, 208),
+            pressed: Rgb::new(160, 160, 160),
+            open: Rgb::new(224, 224, 224),
+            text: Rgb::new(0, 0, 0),
+            numbers: [
+                Rgb::new(0, 0, 255),
+                Rgb::new(0, 128, 0),
+                Rgb::new(255, 0, 0),
+                Rgb::new(0, 0, 128),
+                Rgb::new(128, 0, 0),
+                Rgb::new(0, 128, 128),
+                Rgb::new(0, 0, 0),
+                Rgb::new(128, 128, 128),
+            ],
+            flag: Rgb::new(255, 0, 0),
+            mine: Rgb::new(0, 0, 0),
+            exploded: Rgb::new(255, 0, 0),
+            highlight: Rgb::new(255, 200, 0),
+        }
+    }
+}
+
+impl Palette {
+    /// The themes that come with the game, which can be copied but not
+    /// changed: the default, one for low vision and one whose numbers stay
+    /// apart under the common kinds of colour blindness.
+    pub fn built_in() -> [Palette; 3] {
+        let high_contrast = Palette {
+            name: "High contrast".to_string(),
+            background: Rgb::new(0, 0, 0),
+            hidden: Rgb::new(90, 90, 90),
+            hovered: Rgb::new(130, 130, 130),
+            pressed: Rgb::new(60, 60, 60),
+            open: Rgb::new(0, 0, 0),
+            text: Rgb::new(255, 255, 255),
+            numbers: [
+                Rgb::new(0, 255, 255),
+                Rgb::new(0, 255, 0),
+                Rgb::new(255, 255, 0),
+                Rgb::new(255, 0, 255),
+                Rgb::new(255, 160, 0),
+                Rgb::new(255, 255, 255),
+                Rgb::new(255, 120, 120),
+                Rgb::new(160, 160, 255),
+            ],
+            flag: Rgb::new(255, 60, 60),
+            mine: Rgb::new(255, 255, 255),
+            exploded: Rgb::new(255, 0, 0),
+            highlight: Rgb::new(255, 255, 0),
+        };
+        // the Okabe-Ito colours
+        let colour_blind = Palette {
+            name: "Colour-blind safe".to_string(),
+            numbers: [
+                Rgb::new(0, 114, 178),
+                Rgb::new(0, 158, 115),
+                Rgb::new(213, 94, 0),
+                Rgb::new(204, 121, 167),
+                Rgb::new(230, 159, 0),
+                Rgb::new(86, 180, 233),
+                Rgb::new(0, 0, 0),
+                Rgb::new(100, 100, 100),
+            ],
+            flag: Rgb::new(213, 94, 0),
+            exploded: Rgb::new(230, 159, 0),
+            highlight: Rgb::new(0, 114, 178),
+            ..Palette::default()
+        };
+        let retro = Palette {
+            name: "Retro".to_string(),
+            ..Palette::default()
+        };
+        [retro, high_contrast, colour_blind]
+    }
+
+    /// Whether `name` is one of the built-in themes.
+    pub fn is_built_in(name: &str) -> bool {
+        Palette::built_in().iter().any(|theme| theme.name == name)
+    }
+
+    /// The theme as a theme file.
+    pub fn to_toml(&self) -> String {
+        toml::to_string_pretty(self).expect("a palette is plain strings")
+    }
+
+    /// Read a theme file; missing colours come from the default theme and
+    /// keys it doesn't know are ignored.
+    pub fn from_toml(text: &str) -> Result<Palette, toml::de::Error> {
+        toml::from_str(text)
+    }
+
+    /// Write the theme to `themes/` under `data_dir`, named after it,
+    /// replacing an earlier save of the same name. Returns where.
+    pub fn save(&self, data_dir: &Path) -> io::Result<PathBuf> {
+        let dir = data_dir.join(DIR);
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join(format!("{}.toml", file_stem(&self.name)));
+        std::fs::write(&path, self.to_toml())?;
+        info!(path = %path.display(), "saved theme");
+        Ok(path)
+    }
+}
+
+/// Where themes live under the data dir.
+pub const DIR: &str = "themes";
+
+/// Why a theme file couldn't be read.
+#[derive(Debug)]
+pub enum ThemeError {
+    Io(io::Error),
+    Toml(toml::de::Error),
+}
+
+impl fmt::Display for ThemeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ThemeError::Io(err) => write!(f, "{err}"),
+            ThemeError::Toml(err) => write!(f, "not a theme file: {}", err.message()),
+        }
+    }
+}
+
+impl std::error::Error for ThemeError {}
+
+/// Read the theme file at `path`, named after the file if it doesn't name
+/// itself, or with "(imported)" added if it takes a built-in theme's name.
+pub fn load(path: &Path) -> Result<Palette, ThemeError> {
+    let text = std::fs::read_to_string(path).map_err(ThemeError::Io)?;
+    let mut theme = Palette::from_toml(&text).map_err(ThemeError::Toml)?;
+    theme.name = theme.name.trim().to_string();
+    if theme.name.is_empty() {
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        theme.name = stem.into_owned();
+    }
+    if Palette::is_built_in(&theme.name) {
+        theme.name.push_str(" (imported)");
+    }
+    Ok(theme)
+}
+
+/// The player's themes under `data_dir`, by name, and the files among them
+/// that wouldn't load.
+pub fn load_all(data_dir: &Path) -> (Vec<Palette>, Vec<(PathBuf, ThemeError)>) {
+    let mut themes = Vec::new();
+    let mut failed = Vec::new();
+    let Ok(entries) = std::fs::read_dir(data_dir.join(DIR)) else {
+        return (themes, failed);
+    };
+    for path in entries.filter_map(|entry| Some(entry.ok()?.path())) {
+        if path.extension().is_some_and(|ext| ext == "toml") {
+            match load(&path) {
+                Ok(theme) => themes.push(theme),
+                Err(err) => failed.push((path, err)),
+            }
+        }
+    }
+    themes.sort_by(|a, b| a.name.cmp(&b.name));
+    (themes, failed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir()
+            .join(format!("minesweeper-themes-{}", std::process::id()))
+            .join(name);
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn colours_read_back_as_they_were_written() {
+        let colour = Rgb::new(30, 144, 255);
+        assert_eq!(colour.to_string(), "#1e90ff");
+        assert_eq!("#1e90ff".parse(), Ok(colour));
+        assert_eq!("1E90FF".parse(), Ok(colour));
+        assert!("#1e90f".parse::<Rgb>().is_err());
+        assert!("#1e90fg".parse::<Rgb>().is_err());
+        assert!("#1é90ff".parse::<Rgb>().is_err());
+    }
+
+    #[test]
+    fn every_built_in_theme_round_trips() {
+        for theme in Palette::built_in() {
+            let text = theme.to_toml();
+            assert_eq!(Palette::from_toml(&text).unwrap(), theme, "{text}");
+        }
+    }
+
+    #[test]
+    fn a_partial_theme_fills_in_from_the_default() {
+        let text = "name = \"Dusk\"\n\
+                    hidden = \"#3b4252\"\n\
+                    sparkle = \"#ffffff\" # from some other version\n\
+                    [extras]\n\
+                    glow = true\n";
+        let theme = Palette::from_toml(text).unwrap();
+        assert_eq!(theme.name, "Dusk");
+        assert_eq!(theme.hidden, Rgb::new(0x3b, 0x42, 0x52));
+        let expected = Palette {
+            name: "Dusk".to_string(),
+            hidden: theme.hidden,
+            ..Palette::default()
+        };
+        assert_eq!(theme, expected);
+    }
+
+    #[test]
+    fn a_bad_colour_is_an_error_naming_it() {
+        let err = Palette::from_toml("flag = \"red\"\n").unwrap_err();
+        assert!(err.message().contains("\"red\""), "{err}");
+    }
+
+    #[test]
+    fn saved_themes_load_back_and_imports_keep_clear_of_built_in_names() {
+        let dir = data_dir("save");
+        let theme = Palette {
+            name: "My Dusk".to_string(),
+            open: Rgb::new(1, 2, 3),
+            ..Palette::default()
+        };
+        let path = theme.save(&dir).unwrap();
+        assert_eq!(path, dir.join(DIR).join("my-dusk.toml"));
+        assert_eq!(load(&path).unwrap(), theme);
+
+        // nameless, and one calling itself a built-in theme
+        std::fs::write(dir.join(DIR).join("plain.toml"), "open = \"#000000\"\n").unwrap();
+        std::fs::write(
+            dir.join(DIR).join("fake.toml"),
+            "name = \"High contrast\"\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join(DIR).join("broken.toml"), "open = [\n").unwrap();
+        let (themes, failed) = load_all(&dir);
+        let names: Vec<_> = themes.iter().map(|theme| theme.name.as_str()).collect();
+        assert_eq!(names, ["High contrast (imported)", "My Dusk", "plain"]);
+        assert_eq!(failed.len(), 1);
+        assert!(failed[0].0.ends_with("broken.toml"));
+    }
+}
@@ -0,0 +1,342 @@
+//! When each cell was revealed, for the end-of-game heatmap and for finding
+//! where a game stalled, and when each flag went down or came off, for the
+//! post-game flag review.
+
+use crate::render::column_name;
+use crate::Board;
+use std::fmt;
+
+/// Reveal times in milliseconds since the game started. Only revealed cells
+/// are stored, as (cell index, millis) pairs in reveal order, so a typical
+/// game costs a few KB however large the board is.
+///
+/// ```
+/// use minesweeper_rs::timeline::RevealTimes;
+/// use minesweeper_rs::Board;
+///
+/// let mut board = Board::decode_layout("*..\n")?;
+/// let mut times = RevealTimes::new(board.width());
+/// board.reveal_cell(1, 0);
+/// let dirty = board.take_dirty();
+/// times.record(&board, &dirty, 1_500);
+/// assert_eq!(times.per_cell(3), [None, Some(1_500), None]);
+/// # Ok::<(), minesweeper_rs::LayoutError>(())
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RevealTimes {
+    width: usize,
+    entries: Vec<(u32, u32)>,
+}
+
+/// A gap between two consecutive reveals.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Pause {
+    /// Length of the gap.
+    pub millis: u32,
+    /// Last cell revealed before the gap.
+    pub after: (usize, usize),
+    /// First cell revealed after it.
+    pub next: (usize, usize),
+}
+
+impl RevealTimes {
+    /// No reveals yet, for a board `width` cells wide.
+    pub fn new(width: usize) -> Self {
+        RevealTimes {
+            width,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Note `cells` as revealed at `millis`, skipping any that aren't. Pass
+    /// the clicked cell first so pauses report where the player clicked
+    /// rather than wherever a flood happened to start.
+    pub fn record(&mut self, board: &Board, cells: &[(usize, usize)], millis: u32) {
+        for &(x, y) in cells {
+            if board.bits(x, y).is_revealed() {
+                self.entries.push(((y * self.width + x) as u32, millis));
+            }
+        }
+    }
+
+    /// Cells recorded so far.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// True before the first reveal.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Time of the latest reveal, or 0 before the first.
+    pub fn last_millis(&self) -> u32 {
+        self.entries.last().map_or(0, |&(_, millis)| millis)
+    }
+
+    /// Reveal time of every cell of a `cells`-long board, indexed like the
+    /// board; `None` where nothing was recorded.
+    pub fn per_cell(&self, cells: usize) -> Vec<Option<u32>> {
+        let mut times = vec![None; cells];
+        for &(idx, millis) in &self.entries {
+            if let Some(time) = times.get_mut(idx as usize) {
+                *time = Some(millis);
+            }
+        }
+        times
+    }
+
+    /// A fingerprint of the reveals in order, to vouch for how a game was
+    /// played without sending the whole log. 64-bit FNV-1a over each
+    /// cell's index and time, both as little-endian `u64`s, like
+    /// `Board::layout_hash`.
+    ///
+    /// ```
+    /// use minesweeper_rs::timeline::RevealTimes;
+    /// use minesweeper_rs::Board;
+    ///
+    /// let mut board = Board::decode_layout("*..\n")?;
+    /// let mut times = RevealTimes::new(board.width());
+    /// board.reveal_cell(1, 0);
+    /// let dirty = board.take_dirty();
+    /// times.record(&board, &dirty, 1_500);
+    /// assert_eq!(format!("{:016x}", times.hash()), "c0a43e1705ecacbf");
+    /// # Ok::<(), minesweeper_rs::LayoutError>(())
+    /// ```
+    pub fn hash(&self) -> u64 {
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        let mut eat = |n: u32| {
+            for b in u64::from(n).to_le_bytes() {
+                hash ^= u64::from(b);
+                hash = hash.wrapping_mul(0x0100_0000_01b3);
+            }
+        };
+        for &(idx, millis) in &self.entries {
+            eat(idx);
+            eat(millis);
+        }
+        hash
+    }
+
+    /// The `n` longest gaps between one reveal and the next, longest first.
+    /// Ties keep game order.
+    ///
+    /// ```
+    /// use minesweeper_rs::timeline::RevealTimes;
+    /// use minesweeper_rs::Board;
+    ///
+    /// let mut board = Board::decode_layout("....\n")?;
+    /// board.reveal_all();
+    /// let mut times = RevealTimes::new(4);
+    /// times.record(&board, &[(0, 0)], 0);
+    /// times.record(&board, &[(1, 0)], 200);
+    /// times.record(&board, &[(2, 0)], 5_000);
+    /// let longest = times.longest_pauses(1);
+    /// assert_eq!((longest[0].millis, longest[0].next), (4_800, (2, 0)));
+    /// # Ok::<(), minesweeper_rs::LayoutError>(())
+    /// ```
+    pub fn longest_pauses(&self, n: usize) -> Vec<Pause> {
+        let xy = |idx: u32| (idx as usize % self.width, idx as usize / self.width);
+        let mut pauses: Vec<Pause> = self
+            .entries
+            .windows(2)
+            .filter(|pair| pair[1].1 > pair[0].1)
+            .map(|pair| Pause {
+                millis: pair[1].1 - pair[0].1,
+                after: xy(pair[0].0),
+                next: xy(pair[1].0),
+            })
+            .collect();
+        pauses.sort_by_key(|p| std::cmp::Reverse(p.millis));
+        pauses.truncate(n);
+        pauses
+    }
+}
+
+/// A flag put down or taken off.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FlagChange {
+    /// The flagged cell.
+    pub cell: (usize, usize),
+    /// True when the flag went down, false when it came off.
+    pub placed: bool,
+    /// The player's move it happened on, counting from 1.
+    pub move_number: u32,
+    /// Milliseconds since the game started, where the log keeps time.
+    pub millis: Option<u32>,
+}
+
+/// Every flag change of a game in order. The log only knows where flags
+/// went; whether they were right is for [`FlagLog::review`] to say, and it
+/// won't before the game is over, so nothing here can give a mine away.
+///
+/// ```
+/// use minesweeper_rs::timeline::FlagLog;
+/// use minesweeper_rs::Board;
+///
+/// let mut board = Board::decode_layout("*..\n")?;
+/// let mut log = FlagLog::default();
+/// board.toggle_flag(1, 0);
+/// log.record((1, 0), true, 1, Some(800));
+/// assert!(log.review(&board).is_none());
+///
+/// board.reveal_cell(0, 0);
+/// let review = log.review(&board).unwrap();
+/// assert_eq!((review.on_mines(), review.on_safe_cells()), (0, 1));
+/// # Ok::<(), minesweeper_rs::LayoutError>(())
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FlagLog {
+    changes: Vec<FlagChange>,
+}
+
+/// One flag of a finished game, from when it went down to when (if ever)
+/// it came off.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReviewedFlag {
+    /// The flagged cell.
+    pub cell: (usize, usize),
+    /// Whether the cell holds a mine.
+    pub on_mine: bool,
+    /// When the flag went down.
+    pub placed: FlagChange,
+    /// When it came off, if it did.
+    pub removed: Option<FlagChange>,
+}
+
+/// The flags of a finished game against its mines, in the order they were
+/// put down. `Display` writes it for the terminal.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FlagReview {
+    /// Every flag, a cell flagged twice appearing twice.
+    pub flags: Vec<ReviewedFlag>,
+}
+
+impl FlagLog {
+    /// Note that the flag on `cell` went down (`placed`) or came off.
+    pub fn record(
+        &mut self,
+        cell: (usize, usize),
+        placed: bool,
+        move_number: u32,
+        millis: Option<u32>,
+    ) {
+        self.changes.push(FlagChange {
+            cell,
+            placed,
+            move_number,
+            millis,
+        });
+    }
+
+    /// Changes recorded so far.
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    /// True before the first flag.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Each flag checked against the mines of `board`, or `None` while the
+    /// game on it is still going.
+    pub fn review(&self, board: &Board) -> Option<FlagReview> {
+        if !board.is_over() {
+            return None;
+        }
+        let mut flags: Vec<ReviewedFlag> = Vec::new();
+        for &change in &self.changes {
+            let (x, y) = change.cell;
+            if change.placed {
+                flags.push(ReviewedFlag {
+                    cell: change.cell,
+                    on_mine: board.bits(x, y).is_mine(),
+                    placed: change,
+                    removed: None,
+                });
+            } else if let Some(flag) = flags
+                .iter_mut()
+                .rev()
+                .find(|flag| flag.cell == change.cell && flag.removed.is_none())
+            {
+                flag.removed = Some(change);
+            }
+        }
+        Some(FlagReview { flags })
+    }
+}
+
+impl ReviewedFlag {
+    /// True for a flag put on a safe cell, even if it came off later.
+    pub fn is_wrong(&self) -> bool {
+        !self.on_mine
+    }
+}
+
+impl FlagReview {
+    /// Flags put on mines, kept or not.
+    pub fn on_mines(&self) -> usize {
+        self.flags.iter().filter(|flag| flag.on_mine).count()
+    }
+
+    /// Flags put on safe cells, kept or not.
+    pub fn on_safe_cells(&self) -> usize {
+        self.flags.iter().filter(|flag| flag.is_wrong()).count()
+    }
+
+    /// Flags on safe cells that came off before the end.
+    pub fn corrected(&self) -> usize {
+        let corrected = |flag: &&ReviewedFlag| flag.is_wrong() && flag.removed.is_some();
+        self.flags.iter().filter(corrected).count()
+    }
+}
+
+impl fmt::Display for FlagChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "move {}", self.move_number)?;
+        if let Some(millis) = self.millis {
+            write!(f, " ({})", crate::format::clock(f64::from(millis) / 1000.0))?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ReviewedFlag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (x, y) = self.cell;
+        let what = if self.on_mine { "mine" } else { "safe cell" };
+        write!(
+            f,
+            "{}{} {what}, placed at {}",
+            column_name(x),
+            y + 1,
+            self.placed
+        )?;
+        if let Some(removed) = self.removed {
+            write!(f, ", removed at {removed}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for FlagReview {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Flags: {} on mines, {} on safe cells ({} corrected)",
+            self.on_mines(),
+            self.on_safe_cells(),
+            self.corrected()
+        )?;
+        for flag in &self.flags {
+            let mark = match (flag.on_mine, flag.removed.is_some()) {
+                (_, true) => 'o',
+                (true, false) => '+',
+                (false, false) => 'x',
+            };
+            writeln!(f, "  {mark} {flag}")?;
+        }
+        Ok(())
+    }
+}
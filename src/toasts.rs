@@ -0,0 +1,179 @@
+//! Errors the player should hear about, such as a save that failed, shown
+//! as toasts in a corner instead of a dialog: play goes on underneath, and
+//! each toast fades on its own after a while or can be closed. The odd
+//! notice, such as where a file was saved, goes the same way.
+//!
+//! A toast reads "couldn't save game: permission denied", with the full
+//! error (paths, OS codes) kept behind a "Details" expander. The same
+//! failure again refreshes its toast rather than stacking another, so a
+//! slot that fails on every autosave shows once.
+
+use eframe::egui::{self, Color32, RichText};
+use std::error::Error;
+use std::io;
+use std::path::Path;
+use tracing::{info, warn};
+
+/// Seconds a toast stays up unless closed.
+const TOAST_SECS: f64 = 8.0;
+
+/// Most toasts shown at once; the oldest make way.
+const MAX_TOASTS: usize = 4;
+
+pub struct Toast {
+    pub summary: String, // "couldn't save game: permission denied"
+    pub details: String,
+    pub error: bool, // shown in red; a notice isn't
+    until: f64,
+}
+
+#[derive(Default)]
+pub struct Toasts {
+    shown: Vec<Toast>,
+    now: f64, // the time of the last frame shown
+}
+
+impl Toasts {
+    /// Report that `what` failed because of `err`, e.g. `what` = "couldn't
+    /// save game". The error is logged as well.
+    pub fn error(&mut self, what: &str, err: &(dyn Error + 'static)) {
+        warn!(%err, "{what}");
+        self.push(what, err, String::new());
+    }
+
+    /// Tell the player something that went right, with `details` (say, a
+    /// path) behind the expander.
+    pub fn notice(&mut self, summary: &str, details: String) {
+        info!(%details, "{summary}");
+        self.show_toast(summary.to_string(), details, false);
+    }
+
+    /// `error` for a failure at `path`, which goes in the details.
+    pub fn error_at(&mut self, what: &str, path: &Path, err: &(dyn Error + 'static)) {
+        warn!(%err, path = %path.display(), "{what}");
+        self.push(what, err, format!("{}\n", path.display()));
+    }
+
+    fn push(&mut self, what: &str, err: &(dyn Error + 'static), mut details: String) {
+        let summary = format!("{what}: {}", cause(err));
+        details.push_str(&err.to_string());
+        let mut source = err.source();
+        while let Some(err) = source {
+            details.push_str(&format!("\ncaused by: {err}"));
+            source = err.source();
+        }
+        self.show_toast(summary, details, true);
+    }
+
+    fn show_toast(&mut self, summary: String, details: String, error: bool) {
+        let until = self.now + TOAST_SECS;
+        if let Some(toast) = self.shown.iter_mut().find(|t| t.summary == summary) {
+            toast.details = details;
+            toast.until = until;
+            return;
+        }
+        if self.shown.len() == MAX_TOASTS {
+            self.shown.remove(0);
+        }
+        self.shown.push(Toast {
+            summary,
+            details,
+            error,
+            until,
+        });
+    }
+
+    #[cfg(test)]
+    pub fn shown(&self) -> &[Toast] {
+        &self.shown
+    }
+
+    /// Draw the toasts in the bottom right corner, over whatever screen
+    /// is up, and drop the ones whose time is over.
+    pub fn show(&mut self, ctx: &egui::Context) {
+        self.now = ctx.input(|i| i.time);
+        let now = self.now;
+        self.shown.retain(|toast| toast.until > now);
+        if self.shown.is_empty() {
+            return;
+        }
+        let mut closed = None;
+        egui::Area::new(egui::Id::new("toasts"))
+            .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-12.0, -12.0))
+            .order(egui::Order::Foreground)
+            .show(ctx, |ui| {
+                ui.set_max_width(360.0);
+                for (i, toast) in self.shown.iter().enumerate() {
+                    egui::Frame::popup(ui.style()).show(ui, |ui| {
+                        ui.horizontal(|ui| {
+                            let summary = RichText::new(&toast.summary);
+                            ui.label(if toast.error {
+                                summary.color(Color32::LIGHT_RED)
+                            } else {
+                                summary
+                            });
+                            if ui.small_button("×").on_hover_text("Dismiss").clicked() {
+                                closed = Some(i);
+                            }
+                        });
+                        egui::CollapsingHeader::new("Details")
+                            .id_salt(("toast", i))
+                            .show(ui, |ui| ui.monospace(&toast.details));
+                    });
+                }
+            });
+        if let Some(i) = closed {
+            self.shown.remove(i);
+        }
+        // wake up to take the next one down on time
+        let next = self.shown.iter().map(|t| t.until).fold(f64::MAX, f64::min);
+        if next < f64::MAX {
+            ctx.request_repaint_after(std::time::Duration::from_secs_f64(next - now));
+        }
+    }
+}
+
+/// The short reason for a toast: an I/O error's kind ("permission
+/// denied") rather than its full text, which goes in the details.
+fn cause(err: &(dyn Error + 'static)) -> String {
+    let mut current = Some(err);
+    while let Some(err) = current {
+        if let Some(io) = err.downcast_ref::<io::Error>() {
+            return match io.kind() {
+                io::ErrorKind::Other => io.to_string(),
+                kind => kind.to_string(),
+            };
+        }
+        current = err.source();
+    }
+    err.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn io_errors_read_as_their_kind_with_the_rest_in_details() {
+        let mut toasts = Toasts::default();
+        let err = io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "/data/autosave.tmp: EACCES",
+        );
+        toasts.error("couldn't save game", &err);
+        let toast = &toasts.shown()[0];
+        assert_eq!(toast.summary, "couldn't save game: permission denied");
+        assert!(toast.details.contains("/data/autosave.tmp"));
+    }
+
+    #[test]
+    fn the_same_failure_again_shows_once() {
+        let mut toasts = Toasts::default();
+        let err = io::Error::from(io::ErrorKind::PermissionDenied);
+        for _ in 0..10 {
+            toasts.error("couldn't save game", &err);
+        }
+        toasts.error("couldn't remove profile files", &err);
+        assert_eq!(toasts.shown().len(), 2);
+    }
+}
@@ -0,0 +1,147 @@
+//! Questions for the probability trainer: a position from partway through
+//! a game, and two cells on its frontier whose chances of being a mine
+//! are some way apart, to ask "which is safer?".
+//!
+//! A position comes from the same bot as `simulate`'s solver policy: it
+//! opens the centre, plays everything the solver proves safe, and when
+//! nothing is certain takes the lowest counted chance. It stops at a
+//! random point, and from there on each position is tried until one has
+//! two unsettled frontier cells whose counted chances (see
+//! [`odds::chance`](crate::odds::chance)) differ by an amount in the
+//! asked-for range. Closer gaps make harder questions, so [`GAPS`] runs
+//! from wide to close, one range per level.
+//!
+//! ```
+//! use minesweeper_rs::trainer::{self, GAPS};
+//!
+//! let question = (0..50).find_map(|seed| trainer::generate(9, 9, 10, seed, GAPS[0]));
+//! let question = question.expect("some beginner board has a wide gap");
+//! assert!(question.gap() >= GAPS[0].0);
+//! let safer = question.cells[question.safer()];
+//! assert!(!question.board.cell(safer.0, safer.1).is_revealed());
+//! ```
+
+use crate::odds::{self, Chance};
+use crate::rng::BoardRng;
+use crate::{solver, Board};
+
+/// The gaps between the two chances asked about at each level, as
+/// (smallest, largest), from the easiest level to the hardest.
+pub const GAPS: [(f64, f64); 5] = [
+    (0.30, 1.0),
+    (0.18, 0.30),
+    (0.10, 0.18),
+    (0.05, 0.10),
+    (0.01, 0.05),
+];
+
+/// Most moves the bot plays before it starts looking for a question.
+const MAX_STOP: usize = 12;
+
+/// A position and the two cells it asks about.
+#[derive(Clone)]
+pub struct Question {
+    /// The position, as the bot left it; nothing is flagged.
+    pub board: Board,
+    /// The two hidden cells to choose between.
+    pub cells: [(usize, usize); 2],
+    /// Each cell's counted chance of being a mine.
+    pub chances: [f64; 2],
+}
+
+impl Question {
+    /// Which of [`cells`](Self::cells) is less likely to be a mine.
+    pub fn safer(&self) -> usize {
+        usize::from(self.chances[1] < self.chances[0])
+    }
+
+    /// How far apart the two chances are.
+    pub fn gap(&self) -> f64 {
+        (self.chances[0] - self.chances[1]).abs()
+    }
+}
+
+/// A question on the `width` x `height` board with `mines` from `seed`,
+/// its gap within `gap` (smallest, largest), or `None` if the game ends
+/// before any position has such a pair.
+pub fn generate(
+    width: usize,
+    height: usize,
+    mines: usize,
+    seed: u64,
+    gap: (f64, f64),
+) -> Option<Question> {
+    let start = (width / 2, height / 2);
+    let mut board = Board::with_seed(width, height, mines, seed, Some(start));
+    // a stream apart from the board's, so the two don't move together
+    let mut rng = BoardRng::new(!seed);
+    let stop = 1 + rng.below(MAX_STOP);
+    board.reveal_cell(start.0, start.1);
+    let mut played = 1;
+    while !board.is_over() {
+        if played >= stop {
+            if let Some(question) = pick(&board, gap, &mut rng) {
+                return Some(question);
+            }
+        }
+        let analysis = solver::analyze(&board);
+        let moves = if analysis.safe().is_empty() {
+            safest(&board).into_iter().collect()
+        } else {
+            analysis.safe().to_vec()
+        };
+        if moves.is_empty() {
+            break;
+        }
+        for (x, y) in moves {
+            board.reveal_cell(x, y);
+        }
+        played += 1;
+    }
+    None
+}
+
+/// Two frontier cells of `board` whose counted chances are `gap` apart,
+/// chosen at random among the pairs that are.
+fn pick(board: &Board, gap: (f64, f64), rng: &mut BoardRng) -> Option<Question> {
+    let width = board.width();
+    let frontier: Vec<((usize, usize), f64)> = (odds::chances(board).into_iter().enumerate())
+        .filter_map(|(i, chance)| match chance? {
+            Chance::Counted(p) if p > 0.0 && p < 1.0 => Some(((i % width, i / width), p)),
+            _ => None,
+        })
+        .collect();
+    let mut pairs = Vec::new();
+    for (i, &(a, pa)) in frontier.iter().enumerate() {
+        for &(b, pb) in &frontier[i + 1..] {
+            let apart = (pa - pb).abs();
+            if apart >= gap.0 && apart <= gap.1 {
+                pairs.push(([a, b], [pa, pb]));
+            }
+        }
+    }
+    if pairs.is_empty() {
+        return None;
+    }
+    let (cells, chances) = pairs[rng.below(pairs.len())];
+    Some(Question {
+        board: board.clone(),
+        cells,
+        chances,
+    })
+}
+
+/// The hidden cell least likely to be a mine, for the bot's guess.
+fn safest(board: &Board) -> Option<(usize, usize)> {
+    let analysis = solver::analyze(board);
+    let chances = odds::chances(board);
+    (0..board.height())
+        .flat_map(|y| (0..board.width()).map(move |x| (x, y)))
+        .filter(|&(x, y)| !board.cell(x, y).is_revealed() && analysis.probability(x, y) < 1.0)
+        .map(|(x, y)| {
+            let chance = chances[y * board.width() + x].map_or(1.0, Chance::value);
+            ((x, y), chance)
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(cell, _)| cell)
+}
@@ -0,0 +1,202 @@
+//! The first-run tutorial: a fixed 9x9 board and a short script. Each step
+//! waits for one action. Anything else is refused, or undone if it hit a
+//! mine, and answered with a hint, so the board can't be lost while
+//! learning.
+
+use crate::widgets::{BoardView, CellClick};
+use minesweeper_rs::{Board, CycleMode, RevealResult};
+
+/// The tutorial board. The script below depends on exactly this layout.
+const LAYOUT: &str = "\
+.........
+.........
+.........
+.....*...
+.........
+*........
+.........
+...*.....
+.......*.
+";
+
+/// What a step waits for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Expect {
+    Reveal(usize, usize),
+    Flag(usize, usize),
+    Win, // free play; only explosions are undone
+}
+
+pub struct Step {
+    pub say: &'static str,
+    pub anchor: Option<(usize, usize)>, // cell the callout points at
+    pub highlight: &'static [(usize, usize)],
+    expect: Expect,
+    hint: &'static str, // shown after any other action
+}
+
+const SCRIPT: [Step; 4] = [
+    Step {
+        say: "Left-click the highlighted cell to reveal it.",
+        anchor: Some((1, 1)),
+        highlight: &[(1, 1)],
+        expect: Expect::Reveal(1, 1),
+        hint: "Left-click the highlighted cell.",
+    },
+    Step {
+        say: "A number counts the mines touching it. This 1 touches just one \
+              hidden cell, so that cell is a mine. Right-click it to flag it.",
+        anchor: Some((1, 4)),
+        highlight: &[(0, 5)],
+        expect: Expect::Flag(0, 5),
+        hint: "Right-click the highlighted cell: it's the only hidden cell the 1 touches.",
+    },
+    Step {
+        say: "This 1 already touches the flagged mine, so its other hidden \
+              neighbour is safe. Reveal it.",
+        anchor: Some((1, 5)),
+        highlight: &[(0, 6)],
+        expect: Expect::Reveal(0, 6),
+        hint: "Left-click the highlighted cell; the flag already accounts for this 1.",
+    },
+    Step {
+        say: "That's all there is to it. Reveal every safe cell to win.",
+        anchor: None,
+        highlight: &[],
+        expect: Expect::Win,
+        hint: "That one was a mine, so it's been undone. Look for a number whose \
+               mines are all flagged, or one with as many hidden neighbours as its count.",
+    },
+];
+
+/// A tutorial in progress.
+pub struct Tutorial {
+    pub board: Board,
+    step: usize,
+    pub hint: Option<&'static str>,
+    pub view: BoardView,
+}
+
+impl Tutorial {
+    pub fn new() -> Self {
+        Tutorial {
+            board: Board::decode_layout(LAYOUT).expect("tutorial layout is valid"),
+            step: 0,
+            hint: None,
+            view: BoardView::default(),
+        }
+    }
+
+    /// The step being played, or `None` once the board is won.
+    pub fn step(&self) -> Option<&'static Step> {
+        SCRIPT.get(self.step)
+    }
+
+    /// Step number from 1, and how many there are.
+    pub fn progress(&self) -> (usize, usize) {
+        ((self.step + 1).min(SCRIPT.len()), SCRIPT.len())
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.step == SCRIPT.len()
+    }
+
+    /// Apply a click if the step allows it, otherwise leave the board be
+    /// and show the step's hint.
+    pub fn act(&mut self, click: CellClick) {
+        let Some(step) = self.step() else {
+            return;
+        };
+        let allowed = match (step.expect, click) {
+            (Expect::Reveal(x, y), CellClick::Reveal(cx, cy)) => (x, y) == (cx, cy),
+            (Expect::Flag(x, y), CellClick::Flag(cx, cy)) => (x, y) == (cx, cy),
+            (Expect::Win, _) => true,
+            _ => false,
+        };
+        if !allowed {
+            self.hint = Some(step.hint);
+            return;
+        }
+
+        let before = self.board.snapshot();
+        let exploded = match click {
+            CellClick::Reveal(x, y) => self.board.reveal_cell(x, y) == RevealResult::Exploded,
+            CellClick::Flag(x, y) => {
+                self.board.cycle_marking(x, y, CycleMode::Flags);
+                false
+            }
+        };
+        if exploded {
+            self.board
+                .restore(&before)
+                .expect("snapshot taken from this board");
+            self.hint = Some(step.hint);
+            return;
+        }
+        self.hint = None;
+        if step.expect != Expect::Win || self.board.is_won() {
+            self.step += 1;
+        }
+    }
+}
+
+impl Default for Tutorial {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn script_plays_through() {
+        let mut tutorial = Tutorial::new();
+        tutorial.act(CellClick::Reveal(1, 1));
+        // the callouts point at real 1s
+        assert_eq!(tutorial.board.cell(1, 4).neighbor_mines(), Some(1));
+        assert_eq!(tutorial.board.cell(1, 5).neighbor_mines(), Some(1));
+        tutorial.act(CellClick::Flag(0, 5));
+        tutorial.act(CellClick::Reveal(0, 6));
+        assert_eq!(tutorial.progress(), (4, 4));
+        assert!(tutorial.hint.is_none());
+
+        for (y, row) in LAYOUT.lines().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                if c == '.' && !tutorial.board.cell(x, y).is_revealed() {
+                    tutorial.act(CellClick::Reveal(x, y));
+                }
+            }
+        }
+        assert!(tutorial.is_done());
+        assert!(tutorial.step().is_none());
+    }
+
+    #[test]
+    fn wrong_actions_get_a_hint_and_change_nothing() {
+        let mut tutorial = Tutorial::new();
+        tutorial.act(CellClick::Flag(1, 1));
+        assert!(!tutorial.board.cell(1, 1).is_flagged());
+        assert_eq!(tutorial.hint, Some(SCRIPT[0].hint));
+
+        tutorial.act(CellClick::Reveal(1, 1));
+        assert!(tutorial.hint.is_none());
+        tutorial.act(CellClick::Reveal(0, 5)); // the mine, on the flag step
+        assert!(!tutorial.board.is_over());
+        assert_eq!(tutorial.progress().0, 2);
+    }
+
+    #[test]
+    fn explosions_in_free_play_are_undone() {
+        let mut tutorial = Tutorial::new();
+        tutorial.act(CellClick::Reveal(1, 1));
+        tutorial.act(CellClick::Flag(0, 5));
+        tutorial.act(CellClick::Reveal(0, 6));
+
+        tutorial.act(CellClick::Reveal(3, 7));
+        assert!(!tutorial.board.is_over());
+        assert!(!tutorial.board.cell(3, 7).is_revealed());
+        assert!(tutorial.hint.is_some());
+    }
+}
@@ -0,0 +1,653 @@
+//! Scripted runs of the whole app: a headless driver feeds `MinesweeperApp`
+//! synthetic pointer input and finds what to click through AccessKit
+//! (widgets, by label) or `widgets::cell_rect` (board cells), so nothing
+//! here knows a pixel position.
+
+use crate::app::{AppState, Discard, MinesweeperApp};
+use crate::screens::Screen;
+use crate::settings::CountStyle;
+use crate::widgets;
+use eframe::egui;
+use minesweeper_rs::autosave::Autosave;
+use minesweeper_rs::{Board, Difficulty};
+use std::sync::atomic::Ordering;
+
+/// Seconds between driven frames.
+const FRAME_SECS: f64 = 1.0 / 60.0;
+
+/// The app in a window of its own, one frame at a time.
+struct Driver {
+    ctx: egui::Context,
+    app: MinesweeperApp,
+    time: f64,
+    labels: Vec<(String, egui::Rect)>, // every labelled widget of the last frame
+    focused: Option<String>,           // the label of the widget with the keyboard
+}
+
+impl Driver {
+    fn new() -> Self {
+        let ctx = egui::Context::default();
+        ctx.enable_accesskit();
+        let mut driver = Driver {
+            ctx,
+            app: MinesweeperApp::new(AppState::default()),
+            time: 0.0,
+            labels: Vec::new(),
+            focused: None,
+        };
+        driver.frame(Vec::new());
+        driver
+    }
+
+    /// A fresh app recording its input to `path` from the first frame,
+    /// set up as `recording::play` sets up the app it plays back.
+    #[cfg(feature = "dev-tools")]
+    fn recording(path: std::path::PathBuf, seed: u64) -> Self {
+        let ctx = egui::Context::default();
+        ctx.enable_accesskit();
+        let mut state = AppState::default();
+        crate::recording::seed_app(&mut state, seed);
+        state.settings.apply(&ctx);
+        let mut app = MinesweeperApp::new(state);
+        app.recorder = Some(crate::recording::Recorder::new(path, seed));
+        let mut driver = Driver {
+            ctx,
+            app,
+            time: 0.0,
+            labels: Vec::new(),
+            focused: None,
+        };
+        driver.frame(Vec::new());
+        driver
+    }
+
+    /// Start a game straight away, as the menu would.
+    fn in_game(difficulty: Difficulty) -> Self {
+        let mut driver = Driver::new();
+        driver.app.start_game(difficulty);
+        driver.frame(Vec::new());
+        driver
+    }
+
+    /// Run one frame with `events`, then note where everything landed.
+    fn frame(&mut self, events: Vec<egui::Event>) {
+        self.time += FRAME_SECS;
+        let input = egui::RawInput {
+            screen_rect: Some(egui::Rect::from_min_size(
+                egui::Pos2::ZERO,
+                egui::vec2(1280.0, 1024.0),
+            )),
+            time: Some(self.time),
+            events,
+            ..Default::default()
+        };
+        let output = self.ctx.run(input, |ctx| self.app.ui(ctx));
+        let update = output.platform_output.accesskit_update;
+        self.focused = update.as_ref().and_then(|update| {
+            let (_, node) = update.nodes.iter().find(|(id, _)| *id == update.focus)?;
+            Some(node.label()?.to_string())
+        });
+        self.labels = update
+            .into_iter()
+            .flat_map(|update| update.nodes)
+            .filter_map(|(_, node)| {
+                let (label, bounds) = (node.label()?, node.bounds()?);
+                let rect = egui::Rect::from_min_max(
+                    egui::pos2(bounds.x0 as f32, bounds.y0 as f32),
+                    egui::pos2(bounds.x1 as f32, bounds.y1 as f32),
+                );
+                Some((label.to_string(), rect))
+            })
+            .collect();
+    }
+
+    /// Move to `pos`, press `button` there and let go, a frame apiece.
+    fn click_at(&mut self, pos: egui::Pos2, button: egui::PointerButton) {
+        let press = |pressed| egui::Event::PointerButton {
+            pos,
+            button,
+            pressed,
+            modifiers: egui::Modifiers::NONE,
+        };
+        self.frame(vec![egui::Event::PointerMoved(pos)]);
+        self.frame(vec![press(true)]);
+        self.frame(vec![press(false)]);
+        // let the layout settle on what the click did
+        self.frame(Vec::new());
+    }
+
+    /// Click the widget labelled `label`, which must be on screen.
+    fn click(&mut self, label: &str) {
+        let rect = self
+            .labels
+            .iter()
+            .find(|(text, _)| text == label)
+            .map(|&(_, rect)| rect)
+            .unwrap_or_else(|| panic!("no {label:?} among {:?}", self.labels));
+        self.click_at(rect.center(), egui::PointerButton::Primary);
+    }
+
+    /// Click board cell (x, y) with `button`.
+    fn click_cell(&mut self, x: usize, y: usize, button: egui::PointerButton) {
+        let (board, cell) = widgets::last_board_rect(&self.ctx).expect("a board on screen");
+        self.click_at(widgets::cell_rect(board.min, cell, x, y).center(), button);
+    }
+
+    /// Press and release `key` with `modifiers`, a frame apiece.
+    fn press(&mut self, key: egui::Key, modifiers: egui::Modifiers) {
+        let event = |pressed| egui::Event::Key {
+            key,
+            physical_key: None,
+            pressed,
+            repeat: false,
+            modifiers,
+        };
+        self.frame(vec![event(true)]);
+        self.frame(vec![event(false)]);
+    }
+
+    /// Type `text` a key at a time, each key pressed and let go as a
+    /// keyboard would send it.
+    fn type_text(&mut self, text: &str) {
+        for c in text.chars() {
+            let key = egui::Key::from_name(&c.to_ascii_uppercase().to_string()).unwrap();
+            let event = |pressed| egui::Event::Key {
+                key,
+                physical_key: None,
+                pressed,
+                repeat: false,
+                modifiers: egui::Modifiers::NONE,
+            };
+            self.frame(vec![event(true), egui::Event::Text(c.to_string())]);
+            self.frame(vec![event(false)]);
+        }
+    }
+
+    /// Press Tab until `done`, failing after a couple of hundred.
+    #[cfg(feature = "dev-tools")]
+    fn tab_until(&mut self, done: impl Fn(&Driver) -> bool) {
+        for _ in 0..200 {
+            if done(self) {
+                return;
+            }
+            self.press(egui::Key::Tab, egui::Modifiers::NONE);
+        }
+        panic!("Tab never got there; focus is on {:?}", self.focused);
+    }
+
+    /// Walk the board's cursor to (x, y) with the arrow keys; the board
+    /// must have the keyboard.
+    #[cfg(feature = "dev-tools")]
+    fn cursor_to(&mut self, x: usize, y: usize) {
+        use egui::Key;
+        while let Some((at_x, at_y)) = self.app.state.board_view.focus.filter(|&at| at != (x, y)) {
+            let key = if at_x < x {
+                Key::ArrowRight
+            } else if at_x > x {
+                Key::ArrowLeft
+            } else if at_y < y {
+                Key::ArrowDown
+            } else {
+                Key::ArrowUp
+            };
+            self.press(key, egui::Modifiers::NONE);
+            let moved = self.app.state.board_view.focus != Some((at_x, at_y));
+            assert!(moved, "the cursor is stuck on {:?}", (at_x, at_y));
+        }
+    }
+
+    /// Open every safe cell still covered with `open`, reading the mines
+    /// off the layout, which wins the game.
+    #[cfg(feature = "dev-tools")]
+    fn clear_board(&mut self, open: impl Fn(&mut Driver, usize, usize)) {
+        let layout = self.board().encode_layout();
+        let width = self.board().width();
+        let mines: Vec<bool> = layout
+            .bytes()
+            .filter(|&b| b != b'\n')
+            .map(|b| b == b'*')
+            .collect();
+        for (i, _) in mines.iter().enumerate().filter(|(_, &mine)| !mine) {
+            let (x, y) = (i % width, i / width);
+            if !self.board().cell(x, y).is_revealed() {
+                open(self, x, y);
+            }
+        }
+    }
+
+    fn board(&self) -> &Board {
+        &self.app.state.board
+    }
+
+    /// Put a known board in play, as if its first click had happened.
+    fn load(&mut self, layout: &str) {
+        self.app.state.board = Board::decode_layout(layout).unwrap();
+        self.app.state.awaiting_first_click = false;
+        self.frame(Vec::new());
+    }
+}
+
+#[test]
+fn clicking_a_cell_reveals_it() {
+    let mut driver = Driver::in_game(Difficulty::Beginner);
+    driver.click_cell(4, 4, egui::PointerButton::Primary);
+    assert!(!driver.app.state.awaiting_first_click);
+    assert!(driver.board().cell(4, 4).is_revealed());
+}
+
+#[test]
+fn right_clicking_a_cell_flags_it() {
+    let mut driver = Driver::in_game(Difficulty::Beginner);
+    driver.load("*..\n...\n..*\n");
+    driver.click_cell(2, 2, egui::PointerButton::Secondary);
+    assert!(driver.board().cell(2, 2).is_flagged());
+    driver.click_cell(2, 2, egui::PointerButton::Secondary);
+    assert!(!driver.board().cell(2, 2).is_flagged());
+}
+
+#[test]
+fn the_keyboard_cursor_opens_and_flags_cells() {
+    use egui::{Key, Modifiers};
+    let mut driver = Driver::in_game(Difficulty::Beginner);
+    driver.load("*..\n...\n..*\n");
+    // a click puts the cursor on the board
+    driver.click_cell(1, 0, egui::PointerButton::Primary);
+    driver.press(Key::ArrowDown, Modifiers::NONE);
+    driver.press(Key::Space, Modifiers::NONE);
+    assert!(driver.board().cell(1, 1).is_revealed());
+
+    driver.press(Key::End, Modifiers::NONE);
+    driver.press(Key::F, Modifiers::NONE);
+    assert!(driver.board().cell(2, 2).is_flagged());
+    driver.press(Key::Home, Modifiers::NONE);
+    assert_eq!(driver.app.state.board_view.focus, Some((0, 0)));
+}
+
+#[test]
+fn clicks_find_their_cell_after_zooming_and_scrolling() {
+    for zoom in [widgets::MIN_ZOOM, widgets::MAX_ZOOM] {
+        let mut driver = Driver::in_game(Difficulty::Expert);
+        let view = &mut driver.app.state.board_view;
+        view.zoom_by(zoom, egui::Vec2::ZERO);
+        view.scroll_to(egui::vec2(12.0, 6.0) * view.cell_size());
+        driver.frame(Vec::new());
+
+        // the first whole cell in view, wherever the scrolling stopped
+        let view = &driver.app.state.board_view;
+        let first = (view.offset() / view.cell_size()).ceil();
+        let (x, y) = (first.x as usize + 1, first.y as usize + 1);
+        driver.click_cell(x, y, egui::PointerButton::Primary);
+        assert!(driver.board().cell(x, y).is_revealed(), "zoom {zoom}");
+    }
+}
+
+#[test]
+fn the_view_follows_the_cursor_to_the_far_corner() {
+    use egui::{Key, Modifiers};
+    let mut driver = Driver::in_game(Difficulty::Expert);
+    driver
+        .app
+        .state
+        .board_view
+        .zoom_by(widgets::MAX_ZOOM, egui::Vec2::ZERO);
+    driver.click_cell(0, 0, egui::PointerButton::Secondary);
+    driver.press(Key::End, Modifiers::NONE);
+    assert!(driver.app.state.board_view.offset().min_elem() > 0.0);
+    // still where the pointer expects it
+    let (x, y) = (driver.board().width() - 1, driver.board().height() - 1);
+    driver.click_cell(x, y, egui::PointerButton::Secondary);
+    assert!(driver.board().cell(x, y).is_flagged());
+}
+
+#[test]
+fn count_styles_switch_mid_game() {
+    let mut driver = Driver::in_game(Difficulty::Beginner);
+    driver.load("*..\n...\n..*\n");
+    driver.click_cell(1, 0, egui::PointerButton::Primary);
+    driver.click("Settings");
+    driver.click("Dice pips");
+    assert_eq!(
+        driver.app.state.settings.appearance.counts,
+        CountStyle::Pips
+    );
+    driver.click("Back");
+
+    // the same game, drawn the new way
+    assert_eq!(driver.app.screen, Screen::Game);
+    assert!(driver.board().cell(1, 0).is_revealed());
+    assert_eq!(widgets::count_style(&driver.ctx), CountStyle::Pips);
+}
+
+#[test]
+fn quick_entry_plays_cells_by_name() {
+    let mut driver = Driver::in_game(Difficulty::Beginner);
+    driver.app.state.settings.controls.quick_entry = true;
+    driver.load("*..\n...\n..*\n");
+
+    driver.type_text("b");
+    assert_eq!(driver.app.state.quick_entry.cells(driver.board()).len(), 3);
+    driver.type_text("1");
+    driver.press(egui::Key::Enter, egui::Modifiers::NONE);
+    assert!(driver.board().cell(1, 0).is_revealed());
+    assert!(driver.app.state.quick_entry.is_empty());
+
+    driver.type_text("fa1");
+    driver.press(egui::Key::Enter, egui::Modifiers::NONE);
+    assert!(driver.board().cell(0, 0).is_flagged());
+
+    // H goes to the name rather than asking for a hint
+    driver.type_text("h");
+    assert!(driver.app.state.hint_note.is_none());
+    driver.press(egui::Key::Escape, egui::Modifiers::NONE);
+    assert!(driver.app.state.quick_entry.is_empty());
+}
+
+#[test]
+fn compact_mode_plays_the_board_and_expands_back() {
+    let mut driver = Driver::in_game(Difficulty::Beginner);
+    driver.load("*..\n...\n..*\n");
+    driver.click("⧉ Compact");
+    assert_eq!(driver.app.screen, Screen::Compact);
+    let (_, cell) = widgets::last_board_rect(&driver.ctx).unwrap();
+    assert!(cell < widgets::CELL_SIZE, "cells drawn small: {cell}");
+
+    driver.click_cell(2, 2, egui::PointerButton::Secondary);
+    assert!(driver.board().cell(2, 2).is_flagged());
+    driver.click_cell(1, 1, egui::PointerButton::Primary);
+    assert!(driver.board().cell(1, 1).is_revealed());
+
+    driver.click("⤢ Expand");
+    assert_eq!(driver.app.screen, Screen::Game);
+    driver.click("⧉ Compact");
+    driver.press(egui::Key::Escape, egui::Modifiers::NONE);
+    assert_eq!(driver.app.screen, Screen::Game);
+}
+
+#[test]
+fn a_lost_board_ignores_clicks() {
+    let mut driver = Driver::in_game(Difficulty::Beginner);
+    driver.load("*..\n...\n..*\n");
+    driver.click_cell(0, 0, egui::PointerButton::Primary);
+    assert!(driver.board().is_over());
+
+    let state = driver.board().encode_state();
+    driver.click_cell(1, 0, egui::PointerButton::Primary);
+    driver.click_cell(2, 0, egui::PointerButton::Secondary);
+    assert_eq!(driver.board().encode_state(), state);
+}
+
+#[test]
+fn new_game_starts_over() {
+    let mut driver = Driver::in_game(Difficulty::Beginner);
+    driver.load("*..\n...\n..*\n");
+    driver.click_cell(1, 0, egui::PointerButton::Primary);
+    driver.click("New Game");
+    assert!(driver.board().cell(1, 0).is_revealed(), "asked first");
+
+    driver.click("Abandon game");
+    assert!(driver.app.state.awaiting_first_click);
+    assert!(!driver.board().cell(1, 0).is_revealed());
+}
+
+#[test]
+fn a_dialog_over_the_board_stops_its_clock_and_its_input() {
+    use egui::{Key, Modifiers};
+    let dialogs: [fn(&mut MinesweeperApp); 4] = [
+        |app| app.state.confirm_clear_flags = true,
+        |app| app.state.pending_discard = Some(Discard::NewGame),
+        |app| app.state.editing_custom = true,
+        // Ctrl+C with a game in progress asks whether to save it
+        |app| app.interrupt.store(true, Ordering::Relaxed),
+    ];
+    for (i, open) in dialogs.into_iter().enumerate() {
+        let mut driver = Driver::in_game(Difficulty::Beginner);
+        driver.load("*..\n...\n..*\n");
+        // the cursor on the board and the clock running
+        driver.click_cell(1, 0, egui::PointerButton::Primary);
+        open(&mut driver.app);
+        driver.frame(Vec::new());
+        assert!(driver.app.state.covered, "dialog {i}");
+
+        let secs = driver.app.state.clock.secs;
+        let board = driver.board().encode_state();
+        for _ in 0..30 {
+            driver.frame(Vec::new());
+        }
+        assert_eq!(driver.app.state.clock.secs, secs, "dialog {i}");
+        driver.press(Key::ArrowDown, Modifiers::NONE);
+        driver.press(Key::Space, Modifiers::NONE);
+        driver.press(Key::F, Modifiers::NONE);
+        driver.press(Key::H, Modifiers::NONE);
+        driver.click_cell(2, 2, egui::PointerButton::Secondary);
+        assert_eq!(driver.board().encode_state(), board, "dialog {i}");
+        assert!(driver.app.state.hint_note.is_none(), "dialog {i}");
+    }
+}
+
+#[test]
+fn the_clock_carries_on_as_it_was_once_a_dialog_closes() {
+    let mut driver = Driver::in_game(Difficulty::Beginner);
+    driver.load("*..\n...\n..*\n");
+    driver.click_cell(1, 0, egui::PointerButton::Primary);
+    driver.click("New Game");
+    driver.click("Keep playing");
+    assert!(!driver.app.state.covered);
+    let secs = driver.app.state.clock.secs;
+    driver.frame(Vec::new());
+    assert!(driver.app.state.clock.secs > secs);
+
+    // stopped for notes before the dialog, and still stopped after it
+    driver.app.state.annotating = true;
+    driver.click("New Game");
+    driver.click("Keep playing");
+    let secs = driver.app.state.clock.secs;
+    driver.frame(Vec::new());
+    assert_eq!(driver.app.state.clock.secs, secs);
+    assert!(driver.app.state.annotating);
+}
+
+#[test]
+fn the_game_pauses_when_the_machine_sleeps_and_counts_none_of_it() {
+    let mut driver = Driver::in_game(Difficulty::Beginner);
+    driver.load("*..\n...\n..*\n");
+    driver.click_cell(1, 0, egui::PointerButton::Primary);
+    let secs = driver.app.state.clock.secs;
+    // an hour between two frames with the window up
+    driver.time += 3600.0;
+    driver.frame(Vec::new());
+    assert!(driver.app.state.slept);
+    assert_eq!(driver.app.state.clock.secs, secs);
+    driver.frame(Vec::new());
+    assert!(driver.app.state.covered);
+
+    driver.click("Resume");
+    assert!(!driver.app.state.slept);
+    let secs = driver.app.state.clock.secs;
+    driver.frame(Vec::new());
+    assert!(driver.app.state.clock.secs > secs);
+    assert!(driver.app.state.clock.secs < 5.0);
+}
+
+#[test]
+fn switching_difficulty_starts_a_board_of_that_size() {
+    let mut driver = Driver::in_game(Difficulty::Beginner);
+    driver.load("*..\n...\n..*\n");
+    driver.click_cell(1, 0, egui::PointerButton::Primary);
+
+    // the selected difficulty doesn't throw the game away
+    driver.click(Difficulty::Beginner.label());
+    assert!(driver.app.state.pending_discard.is_none());
+    assert!(driver.board().cell(1, 0).is_revealed());
+
+    driver.click(Difficulty::Intermediate.label());
+    driver.click("Keep playing");
+    assert_eq!(driver.app.state.difficulty, Difficulty::Beginner);
+    assert!(driver.board().cell(1, 0).is_revealed());
+
+    driver.click(Difficulty::Intermediate.label());
+    driver.click("Abandon game");
+    assert_eq!(driver.app.state.difficulty, Difficulty::Intermediate);
+    assert_eq!((driver.board().width(), driver.board().height()), (16, 16));
+    assert!(driver.app.state.awaiting_first_click);
+}
+
+#[test]
+fn every_way_of_leaving_a_game_logs_it_abandoned() {
+    let mut driver = Driver::in_game(Difficulty::Beginner);
+    let play = |driver: &mut Driver| {
+        driver.load("*..\n...\n..*\n");
+        driver.click_cell(1, 0, egui::PointerButton::Primary);
+        assert!(driver.app.state.in_progress());
+    };
+    let abandoned = |driver: &Driver| {
+        let history = &driver.app.state.history;
+        assert!(history.log.iter().all(|game| game.abandoned && !game.won));
+        (history.abandoned, history.games)
+    };
+
+    play(&mut driver);
+    driver.click("New Game");
+    driver.click("Abandon game");
+    assert_eq!(abandoned(&driver), (1, 1));
+
+    play(&mut driver);
+    driver.click(Difficulty::Expert.label());
+    driver.click("Abandon game");
+    assert_eq!(abandoned(&driver), (2, 2));
+
+    play(&mut driver);
+    driver.click("Back to Menu");
+    driver.click(Difficulty::Beginner.label());
+    assert_eq!(driver.app.screen, Screen::Game);
+    driver.click("Abandon game");
+    assert_eq!(abandoned(&driver), (3, 3));
+
+    // Ctrl+C asks, as a window close does
+    play(&mut driver);
+    driver.app.interrupt.store(true, Ordering::Relaxed);
+    driver.frame(Vec::new());
+    // the prompt's first frame only measures it; it's placed on the next
+    driver.frame(Vec::new());
+    driver.click("Quit without saving");
+    assert_eq!(abandoned(&driver), (4, 4));
+    assert_eq!(driver.app.state.history.log.len(), 4);
+}
+
+#[test]
+fn menu_navigation() {
+    let mut driver = Driver::new();
+    assert_eq!(driver.app.screen, Screen::Menu);
+
+    driver.click(Difficulty::Beginner.label());
+    assert_eq!(driver.app.screen, Screen::Game);
+    driver.click("Settings");
+    assert_eq!(driver.app.screen, Screen::Settings);
+    driver.click("Back");
+    assert_eq!(driver.app.screen, Screen::Game);
+    driver.click("Back to Menu");
+    assert_eq!(driver.app.screen, Screen::Menu);
+    driver.click("History");
+    assert_eq!(driver.app.screen, Screen::History);
+}
+
+#[test]
+fn hostile_files_show_as_toasts_and_play_goes_on() {
+    let dir = std::env::temp_dir().join(format!("minesweeper-ui-hostile-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let corrupt = dir.join("autosave.bin");
+    std::fs::write(&corrupt, b"MSS2 not a save").unwrap();
+    // a file where the data dir should be, so every save fails
+    let blocker = dir.join("data");
+    std::fs::write(&blocker, b"not a directory").unwrap();
+
+    let mut driver = Driver::new();
+    driver.app.state.autosave = Some(Autosave::new(&corrupt));
+    driver.app.state.recovered = driver.app.state.read_recovered();
+    assert!(driver.app.state.recovered.is_none());
+
+    driver.app.state.autosave = Some(Autosave::new(blocker.join("1").join("autosave.bin")));
+    driver.click(Difficulty::Beginner.label());
+    driver.load("..*\n...\n...\n");
+    driver.click_cell(0, 0, egui::PointerButton::Primary);
+    assert!(driver.board().is_won());
+
+    let summaries: Vec<&str> = (driver.app.state.toasts.shown().iter())
+        .map(|toast| toast.summary.as_str())
+        .collect();
+    assert_eq!(
+        summaries,
+        [
+            "couldn't recover the last game: the save is corrupt",
+            "couldn't save game: not a directory",
+        ]
+    );
+
+    // a race pasted with giant boards is refused on the race screen
+    driver.app.screen = Screen::Race;
+    driver.frame(Vec::new());
+    driver.app.state.race.paste =
+        r#"{"width": 100000, "height": 100000, "mines": 1, "seeds": [1]}"#.into();
+    driver.click("Load race");
+    let message = driver.app.state.race.message.clone().unwrap_or_default();
+    assert!(message.contains("at most"), "{message}");
+}
+
+/// Records the playback fixtures in `tests/recordings` (see `recording`),
+/// each checked to play back the same before it is written. Run by hand
+/// when the UI changes in a way the recordings should follow:
+/// `cargo test --features dev-tools -- --ignored record_the_playback_fixtures`.
+#[cfg(feature = "dev-tools")]
+#[test]
+#[ignore]
+fn record_the_playback_fixtures() {
+    use crate::recording;
+    use egui::{Key, Modifiers, PointerButton};
+
+    let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/recordings");
+    std::fs::create_dir_all(&dir).unwrap();
+    let record = |name: &str, seed: u64, script: &dyn Fn(&mut Driver)| {
+        let mut driver = Driver::recording(dir.join(format!("{name}.rec")), seed);
+        script(&mut driver);
+        let recorder = driver.app.recorder.as_ref().unwrap();
+        let recording = recorder.finish(&driver.app);
+        if let Err(mismatch) = recording::check(&recording) {
+            panic!("{name} doesn't play back the same:\n{mismatch}");
+        }
+        recorder.save(&driver.app).unwrap();
+    };
+
+    record("beginner_mouse", 1, &|driver| {
+        driver.click(Difficulty::Beginner.label());
+        driver.click_cell(4, 4, PointerButton::Primary);
+        driver.clear_board(|driver, x, y| driver.click_cell(x, y, PointerButton::Primary));
+        assert!(driver.board().is_won());
+    });
+
+    record("intermediate_keyboard", 2, &|driver| {
+        let label = Difficulty::Intermediate.label();
+        driver.tab_until(|driver| driver.focused.as_deref() == Some(label));
+        driver.press(Key::Enter, Modifiers::NONE);
+        assert_eq!(driver.app.screen, Screen::Game);
+        driver.tab_until(|driver| widgets::board_focused(&driver.ctx));
+        driver.press(Key::Space, Modifiers::NONE);
+        assert!(!driver.app.state.awaiting_first_click);
+        driver.clear_board(|driver, x, y| {
+            driver.cursor_to(x, y);
+            driver.press(Key::Space, Modifiers::NONE);
+        });
+        assert!(driver.board().is_won());
+    });
+
+    record("settings_round_trip", 3, &|driver| {
+        driver.click("Settings");
+        driver.click("Dice pips");
+        driver.click("Glow sweep");
+        driver.click("Back");
+        assert_eq!(driver.app.screen, Screen::Menu);
+        driver.click("Settings");
+        driver.click("Back");
+        let appearance = &driver.app.state.settings.appearance;
+        assert_eq!(appearance.counts, CountStyle::Pips);
+        assert_eq!(appearance.win_effect, crate::settings::WinStyle::GlowSweep);
+    });
+}
@@ -0,0 +1,171 @@
+//! Local versus: two players at one keyboard, each on their own copy of
+//! the same board, first to clear it wins. Both boards come from one seed
+//! with the centre opened, as in a seed race, and a countdown starts both
+//! clocks together so neither gets a head start. A mine locks that
+//! player's board while the other plays on; the game ends at the first
+//! board cleared, or once both have gone off.
+
+use crate::widgets::CellClick;
+use minesweeper_rs::{Board, Difficulty};
+
+/// Seconds of "3, 2, 1" before the clocks start.
+pub const COUNTDOWN_SECS: f64 = 3.0;
+
+/// One player's board.
+pub struct Seat {
+    pub board: Board,
+    pub ended: Option<f64>, // seconds in when it was cleared or went off
+}
+
+/// How a game ended.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Outcome {
+    /// The seat that cleared its board first, and its time.
+    Won { seat: usize, secs: f64 },
+    /// Both boards went off.
+    BothLost,
+}
+
+/// A game between the two seats, left then right.
+pub struct Versus {
+    pub seats: [Seat; 2],
+    go: f64, // frame time the clocks start at
+}
+
+impl Versus {
+    /// A game at `difficulty` from `seed`, counting down from `now`.
+    pub fn new(difficulty: Difficulty, seed: u64, now: f64) -> Self {
+        let (width, height, mines) = difficulty.spec().params();
+        let centre = (width / 2, height / 2);
+        let mut board = Board::with_seed(width, height, mines, seed, Some(centre));
+        board.reveal_cell(centre.0, centre.1);
+        let seat = |board: Board| Seat { board, ended: None };
+        Versus {
+            seats: [seat(board.clone()), seat(board)],
+            go: now + COUNTDOWN_SECS,
+        }
+    }
+
+    /// Whole seconds left before the clocks start at `now`, counting 3, 2,
+    /// 1; `None` once they have.
+    pub fn countdown(&self, now: f64) -> Option<u32> {
+        (now < self.go).then(|| (self.go - now).ceil() as u32)
+    }
+
+    /// Frame time the clocks start at.
+    pub fn go_at(&self) -> f64 {
+        self.go
+    }
+
+    /// Seconds on `seat`'s clock at `now`, stopped where its board ended.
+    pub fn secs(&self, seat: usize, now: f64) -> f64 {
+        let running = if self.outcome().is_some() {
+            self.ended_at()
+        } else {
+            now - self.go
+        };
+        self.seats[seat].ended.unwrap_or(running).max(0.0)
+    }
+
+    /// Make `click` on `seat`'s board at `now`. Nothing happens before the
+    /// countdown is over, on a board that has ended or once the game has.
+    pub fn act(&mut self, seat: usize, click: CellClick, now: f64) {
+        if now < self.go || self.outcome().is_some() || self.seats[seat].ended.is_some() {
+            return;
+        }
+        let Seat { board, ended } = &mut self.seats[seat];
+        match click {
+            CellClick::Reveal(x, y) => {
+                board.reveal_cell(x, y);
+            }
+            CellClick::Flag(x, y) => {
+                board.toggle_flag(x, y);
+            }
+        }
+        if board.is_over() {
+            *ended = Some(now - self.go);
+        }
+    }
+
+    /// How the game ended, once it has.
+    pub fn outcome(&self) -> Option<Outcome> {
+        if let Some(seat) = self.seats.iter().position(|s| s.board.is_won()) {
+            let secs = self.seats[seat].ended.unwrap_or(0.0);
+            return Some(Outcome::Won { seat, secs });
+        }
+        let lost = self.seats.iter().all(|s| s.board.is_lost());
+        lost.then_some(Outcome::BothLost)
+    }
+
+    /// When the game ended: the last board to end.
+    fn ended_at(&self) -> f64 {
+        let ends = self.seats.iter().filter_map(|s| s.ended);
+        ends.fold(0.0, f64::max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A versus game on `layout` rather than a seeded board, started.
+    fn on(layout: &str) -> Versus {
+        let board = Board::decode_layout(layout).unwrap();
+        let seat = |board: Board| Seat { board, ended: None };
+        Versus {
+            seats: [seat(board.clone()), seat(board)],
+            go: 0.0,
+        }
+    }
+
+    #[test]
+    fn both_boards_match_and_wait_for_the_countdown() {
+        let mut versus = Versus::new(Difficulty::Intermediate, 7, 10.0);
+        let [left, right] = &versus.seats;
+        assert_eq!(left.board.encode_layout(), right.board.encode_layout());
+        assert!(left.board.cell(8, 8).is_revealed());
+        assert_eq!(versus.countdown(10.0), Some(3));
+        assert_eq!(versus.countdown(12.5), Some(1));
+        assert_eq!(versus.countdown(13.0), None);
+
+        let hidden = (0..16)
+            .find(|&x| !versus.seats[0].board.cell(x, 0).is_revealed())
+            .unwrap();
+        versus.act(0, CellClick::Flag(hidden, 0), 12.9);
+        assert!(!versus.seats[0].board.cell(hidden, 0).is_flagged());
+        versus.act(0, CellClick::Flag(hidden, 0), 13.0);
+        assert!(versus.seats[0].board.cell(hidden, 0).is_flagged());
+        assert_eq!(versus.secs(1, 15.5), 2.5);
+    }
+
+    #[test]
+    fn a_mine_locks_one_board_and_the_other_plays_on() {
+        let mut versus = on("*..\n");
+        versus.act(0, CellClick::Reveal(0, 0), 4.0);
+        assert!(versus.seats[0].board.is_lost());
+        assert_eq!(versus.outcome(), None);
+        versus.act(0, CellClick::Reveal(2, 0), 5.0);
+        assert!(!versus.seats[0].board.cell(2, 0).is_revealed());
+        assert_eq!(versus.secs(0, 9.0), 4.0);
+
+        versus.act(1, CellClick::Reveal(2, 0), 6.0);
+        assert_eq!(versus.outcome(), Some(Outcome::Won { seat: 1, secs: 6.0 }));
+        // the clocks stop with the game
+        assert_eq!(versus.secs(1, 20.0), 6.0);
+    }
+
+    #[test]
+    fn the_first_board_cleared_ends_the_game() {
+        let mut versus = on("*..\n");
+        versus.act(1, CellClick::Reveal(2, 0), 3.0);
+        assert_eq!(versus.outcome(), Some(Outcome::Won { seat: 1, secs: 3.0 }));
+        versus.act(0, CellClick::Reveal(2, 0), 4.0);
+        assert!(!versus.seats[0].board.cell(2, 0).is_revealed());
+        assert_eq!(versus.secs(0, 10.0), 3.0);
+
+        let mut versus = on("*..\n");
+        versus.act(0, CellClick::Reveal(0, 0), 1.0);
+        versus.act(1, CellClick::Reveal(0, 0), 2.0);
+        assert_eq!(versus.outcome(), Some(Outcome::BothLost));
+    }
+}
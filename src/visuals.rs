@@ -0,0 +1,567 @@
+//! How each cell looks beyond what the board says. The core `Cell` holds
+//! only the mine, the marking, whether it is open and the count; anything
+//! the board widget draws on top of that lives here, in a buffer laid out
+//! like the board's cells (row-major, `y * width + x`).
+//!
+//! The buffer follows the game through `sync_from_events`, fed the events
+//! of each move and the cells the board marked dirty, so a cell undone or
+//! unflagged loses its look with it. A new board starts it over.
+//!
+//! A game's end also starts the finale: where the mines were, shown a cell
+//! at a time from the explosion or the last cell revealed. Its timing is
+//! worked out up front by `finale_schedule`; the buffer only keeps the
+//! clock and hands each cell its cue.
+//!
+//! A reveal plays as a cascade, opening ring by ring out from the click,
+//! in the rings the board reports (`Board::last_reveal_wavefronts`). The
+//! board is never held back for it: every move lands on the board at
+//! once, and only the drawing lags. A cell the board has open but the
+//! cascade hasn't reached is veiled, drawn as it was before the move, and
+//! the cascades of moves made while one plays queue up behind it.
+//!
+//! A win's own effect, picked in the settings, runs beside the finale in
+//! `win` (see `win_effects`), and is cleared and skipped with it.
+
+use crate::app::GameEvent;
+use crate::win_effects::Animations;
+use minesweeper_rs::Board;
+use std::collections::VecDeque;
+
+/// How long the detonated mine flashes on its own before the rest follow.
+const FLASH_SECS: f32 = 0.4;
+/// How long the ripple takes to reach the farthest mine.
+const SPREAD_SECS: f32 = 1.5;
+/// How long each cell of the finale takes to fade in.
+const FADE_SECS: f32 = 0.25;
+/// The longest a win's finale takes to flag the last mine, which effects
+/// that play on the flags wait out.
+pub const WIN_FINALE_SECS: f32 = SPREAD_SECS + FADE_SECS;
+/// How long a cascade takes to open each ring of cells out from the click.
+const RING_SECS: f64 = 0.03;
+/// The longest a cascade takes to open out; a wider one opens its rings
+/// closer together.
+const CASCADE_SECS: f64 = 0.6;
+
+/// The look of one cell.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CellVisual {
+    pub flash: bool,      // a refused move was about this cell
+    pub exploded: bool,   // the mine that ended the game
+    pub final_flag: bool, // the flag that completed a correct set
+    /// Open on the board, but drawn hidden until the cascade reaches it.
+    pub veiled: bool,
+}
+
+/// One `CellVisual` per cell of a board.
+#[derive(Debug, Default)]
+pub struct CellVisuals {
+    width: usize,
+    height: usize,
+    cells: Vec<CellVisual>,
+    flashing: Option<usize>, // the one cell with `flash` set
+    finale: Option<Finale>,
+    cascade: VecDeque<(usize, f64)>, // veiled cells and when each is shown, in order
+    now: f64,                        // the frame time of the last `tick_cascade`
+    pub win: Animations,             // the win effect, started by the screen
+}
+
+/// What the finale shows on a cell.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Reveal {
+    Detonated, // the mine that ended the game, flashing
+    Mine,      // a mine left unflagged on a loss
+    WrongFlag, // a flag on a safe cell, crossed out
+    Flag,      // a mine flagged for the player on a win
+}
+
+/// One cell of the finale, starting `at` seconds in.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Cue {
+    pub x: usize,
+    pub y: usize,
+    pub reveal: Reveal,
+    pub at: f32,
+}
+
+/// The finale for a finished `board`, spreading from `origin`, in the
+/// order the cues start. On a loss the mine at `origin` flashes, the mines
+/// not flagged fade in nearest first over `SPREAD_SECS`, and wrong flags are crossed out once
+/// they all have; on a win the mines not flagged are flagged in the same
+/// ripple. Empty while the game goes on.
+pub fn finale_schedule(board: &Board, origin: (usize, usize)) -> Vec<Cue> {
+    if !board.is_over() {
+        return Vec::new();
+    }
+    let lost = board.is_lost();
+    let distance = |x: usize, y: usize| {
+        let (dx, dy) = (x.abs_diff(origin.0), y.abs_diff(origin.1));
+        (dx as f32).hypot(dy as f32)
+    };
+    let mut ripple = Vec::new();
+    let mut wrong = Vec::new();
+    for y in 0..board.height() {
+        for x in 0..board.width() {
+            let cell = board.cell(x, y);
+            if cell.is_revealed() {
+                continue;
+            }
+            match (cell.is_mine() == Some(true), cell.is_flagged()) {
+                (true, false) => ripple.push((x, y)),
+                (false, true) if lost => wrong.push((x, y)),
+                _ => {}
+            }
+        }
+    }
+
+    let mut cues = Vec::new();
+    let (start, reveal) = if lost {
+        let (x, y) = origin;
+        if board.cell(x, y).is_revealed() && board.cell(x, y).is_mine() == Some(true) {
+            cues.push(Cue {
+                x,
+                y,
+                reveal: Reveal::Detonated,
+                at: 0.0,
+            });
+        }
+        (FLASH_SECS, Reveal::Mine)
+    } else {
+        (0.0, Reveal::Flag)
+    };
+    let nearest = ripple
+        .iter()
+        .map(|&(x, y)| distance(x, y))
+        .fold(f32::INFINITY, f32::min);
+    let farthest = ripple
+        .iter()
+        .map(|&(x, y)| distance(x, y))
+        .fold(0.0, f32::max);
+    for &(x, y) in &ripple {
+        let spread = if farthest > nearest {
+            SPREAD_SECS * (distance(x, y) - nearest) / (farthest - nearest)
+        } else {
+            0.0
+        };
+        cues.push(Cue {
+            x,
+            y,
+            reveal,
+            at: start + spread,
+        });
+    }
+    // after the last mine has finished fading in
+    let last = cues.iter().map(|c| c.at + FADE_SECS).fold(start, f32::max);
+    for (x, y) in wrong {
+        cues.push(Cue {
+            x,
+            y,
+            reveal: Reveal::WrongFlag,
+            at: last,
+        });
+    }
+    cues.sort_by(|a, b| a.at.total_cmp(&b.at).then((a.y, a.x).cmp(&(b.y, b.x))));
+    cues
+}
+
+/// How far a cue starting `at` has faded in, from 0 to 1, `elapsed`
+/// seconds into the finale; all the way once it has been skipped.
+pub fn shown(at: f32, elapsed: f32, skipped: bool) -> f32 {
+    if skipped {
+        1.0
+    } else {
+        ((elapsed - at) / FADE_SECS).clamp(0.0, 1.0)
+    }
+}
+
+/// The finale under way: each cell's cue and how far in it is.
+#[derive(Debug)]
+struct Finale {
+    cues: Vec<Option<Cue>>, // laid out like the cells
+    end: f32,               // when the last cue has faded in
+    started: Option<f64>,   // the frame time it began at, once drawn
+    elapsed: f32,
+    skipped: bool,
+}
+
+impl Finale {
+    fn new(schedule: Vec<Cue>, width: usize, height: usize) -> Self {
+        let end = schedule
+            .iter()
+            .map(|c| c.at + FADE_SECS)
+            .fold(0.0, f32::max);
+        let mut cues = vec![None; width * height];
+        for cue in schedule {
+            cues[cue.y * width + cue.x] = Some(cue);
+        }
+        Finale {
+            cues,
+            end,
+            started: None,
+            elapsed: 0.0,
+            skipped: false,
+        }
+    }
+}
+
+impl CellVisuals {
+    /// Every cell of `board` plain.
+    pub fn new(board: &Board) -> Self {
+        let mut visuals = CellVisuals::default();
+        visuals.reset(board);
+        visuals
+    }
+
+    /// Start over for `board`, keeping the buffer where the size allows.
+    pub fn reset(&mut self, board: &Board) {
+        self.width = board.width();
+        self.height = board.height();
+        self.flashing = None;
+        self.finale = None;
+        self.win.clear();
+        self.cascade.clear();
+        self.cells.clear();
+        self.cells
+            .resize(board.width() * board.height(), CellVisual::default());
+    }
+
+    /// The look of (x, y), plain for a cell off the buffer.
+    pub fn get(&self, x: usize, y: usize) -> CellVisual {
+        if x >= self.width {
+            return CellVisual::default();
+        }
+        self.cells
+            .get(y * self.width + x)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Bring the buffer up to date with `board` after a move: `events` say
+    /// what happened, `dirty` which cells changed (as `Board::take_dirty`
+    /// gives them). A look only stays while the board bears it out, so the
+    /// same events can be fed more than once, and a board of another size
+    /// starts the buffer over. A loss or a win starts the finale, which
+    /// lasts until the game is taken back or a new one begins.
+    pub fn sync_from_events(
+        &mut self,
+        board: &Board,
+        events: &[GameEvent],
+        dirty: &[(usize, usize)],
+    ) {
+        if (self.width, self.height) != (board.width(), board.height()) {
+            self.reset(board);
+        }
+        if !board.is_over() {
+            self.finale = None;
+            self.win.clear();
+        }
+        for &(x, y) in dirty {
+            let Some(visual) = self.cells.get_mut(y * self.width + x) else {
+                continue;
+            };
+            let cell = board.cell(x, y);
+            visual.exploded &= cell.is_revealed();
+            visual.final_flag &= cell.is_flagged();
+            if visual.veiled && !cell.is_revealed() {
+                // taken back before the cascade got there
+                visual.veiled = false;
+                let i = y * self.width + x;
+                self.cascade.retain(|&(veiled, _)| veiled != i);
+            }
+        }
+        for &event in events {
+            match event {
+                GameEvent::Exploded(x, y) if board.cell(x, y).is_mine() == Some(true) => {
+                    self.cells[y * self.width + x].exploded = true;
+                }
+                GameEvent::FinalFlag(x, y) if board.cell(x, y).is_flagged() => {
+                    self.cells[y * self.width + x].final_flag = true;
+                }
+                _ => {}
+            }
+            if let GameEvent::Exploded(x, y) | GameEvent::Won(x, y) = event {
+                if self.finale.is_none() && board.is_over() {
+                    let schedule = finale_schedule(board, (x, y));
+                    self.finale = Some(Finale::new(schedule, self.width, self.height));
+                }
+            }
+        }
+    }
+
+    /// What the finale shows on (x, y) by the last `tick_finale`, and how
+    /// far it has faded in.
+    pub fn finale(&self, x: usize, y: usize) -> Option<(Reveal, f32)> {
+        let finale = self.finale.as_ref()?;
+        if x >= self.width {
+            return None;
+        }
+        let cue = (*finale.cues.get(y * self.width + x)?)?;
+        Some((cue.reveal, shown(cue.at, finale.elapsed, finale.skipped)))
+    }
+
+    /// Move the finale's clock to `now`, the frame time, starting it on
+    /// the first call, and the win effect's with it; without `animate` both
+    /// are skipped to the end. Returns whether there is more to show, so
+    /// the caller keeps repainting.
+    pub fn tick_finale(&mut self, now: f64, animate: bool) -> bool {
+        let win = self.win.tick(now, animate);
+        let Some(finale) = &mut self.finale else {
+            return win;
+        };
+        finale.skipped |= !animate;
+        let started = *finale.started.get_or_insert(now);
+        finale.elapsed = (now - started) as f32;
+        (!finale.skipped && finale.elapsed < finale.end) || win
+    }
+
+    /// Show the rest of the finale at once, and end the win effect.
+    pub fn skip_finale(&mut self) {
+        if let Some(finale) = &mut self.finale {
+            finale.skipped = true;
+        }
+        self.win.skip();
+    }
+
+    /// Play the opening `rings` as a cascade, a ring at a time, after any
+    /// cascade still playing. Each cell is veiled until its cue, except
+    /// those due by the last frame.
+    pub fn cascade(&mut self, rings: &[Vec<(usize, usize)>]) {
+        let widest = rings.len().saturating_sub(1);
+        let step = RING_SECS.min(CASCADE_SECS / widest.max(1) as f64);
+        let start = self
+            .cascade
+            .back()
+            .map_or(self.now, |&(_, at)| at.max(self.now));
+        for (ring, cells) in rings.iter().enumerate() {
+            let at = start + ring as f64 * step;
+            if at <= self.now {
+                continue;
+            }
+            for &(x, y) in cells {
+                if x < self.width && y < self.height {
+                    let i = y * self.width + x;
+                    self.cells[i].veiled = true;
+                    self.cascade.push_back((i, at));
+                }
+            }
+        }
+    }
+
+    /// Move the cascade's clock to `now`, the frame time, showing every
+    /// cell whose cue has come; without `animate`, every cell. Returns
+    /// whether any are still veiled, so the caller keeps repainting.
+    pub fn tick_cascade(&mut self, now: f64, animate: bool) -> bool {
+        self.now = now;
+        while let Some(&(i, at)) = self.cascade.front() {
+            if animate && at > now {
+                break;
+            }
+            self.cells[i].veiled = false;
+            self.cascade.pop_front();
+        }
+        !self.cascade.is_empty()
+    }
+
+    /// Flash (x, y), putting out any other flash; `None` puts it out.
+    pub fn set_flash(&mut self, cell: Option<(usize, usize)>) {
+        if let Some(visual) = self.flashing.and_then(|i| self.cells.get_mut(i)) {
+            visual.flash = false;
+        }
+        self.flashing = cell
+            .filter(|&(x, y)| x < self.width && y < self.height)
+            .map(|(x, y)| y * self.width + x);
+        if let Some(i) = self.flashing {
+            self.cells[i].flash = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-4
+    }
+
+    fn cue(schedule: &[Cue], x: usize, y: usize) -> Cue {
+        *schedule.iter().find(|c| (c.x, c.y) == (x, y)).unwrap()
+    }
+
+    /// Mines at A1, E1 and E3 with every safe cell open.
+    fn won() -> Board {
+        let layout = "*...*\n.....\n....*\n";
+        let mut board = Board::decode_layout(layout).unwrap();
+        board.toggle_flag(4, 0);
+        for (y, row) in layout.lines().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                if c == '.' {
+                    board.reveal_cell(x, y);
+                }
+            }
+        }
+        assert!(board.is_won());
+        board
+    }
+
+    #[test]
+    fn a_loss_flashes_then_spreads_then_crosses_out() {
+        let mut board = Board::decode_layout("*...*\n.....\n*...*\n").unwrap();
+        board.toggle_flag(4, 2); // right
+        board.toggle_flag(2, 2); // wrong
+        board.reveal_cell(0, 0);
+        assert!(board.is_lost());
+
+        let schedule = finale_schedule(&board, (0, 0));
+        let order: Vec<_> = schedule.iter().map(|c| (c.x, c.y, c.reveal)).collect();
+        assert_eq!(
+            order,
+            [
+                (0, 0, Reveal::Detonated),
+                (0, 2, Reveal::Mine),
+                (4, 0, Reveal::Mine),
+                (2, 2, Reveal::WrongFlag),
+            ]
+        );
+        assert_eq!(schedule[0].at, 0.0);
+        // nearest mine as the flash ends, farthest a spread later
+        assert!(close(cue(&schedule, 0, 2).at, FLASH_SECS));
+        assert!(close(cue(&schedule, 4, 0).at, FLASH_SECS + SPREAD_SECS));
+        // crossed out once the last mine has faded in
+        let wrong = cue(&schedule, 2, 2).at;
+        assert!(close(wrong, FLASH_SECS + SPREAD_SECS + FADE_SECS));
+        assert!(schedule.windows(2).all(|w| w[0].at <= w[1].at));
+    }
+
+    #[test]
+    fn a_win_flags_the_rest_outward_from_the_last_cell() {
+        let board = won();
+        let schedule = finale_schedule(&board, (1, 1));
+        let order: Vec<_> = schedule.iter().map(|c| (c.x, c.y, c.reveal)).collect();
+        // the flagged mine is left alone
+        assert_eq!(order, [(0, 0, Reveal::Flag), (4, 2, Reveal::Flag)]);
+        assert_eq!(schedule[0].at, 0.0);
+        assert!(close(schedule[1].at, SPREAD_SECS));
+
+        // the same distance is the same moment
+        let mut board = Board::decode_layout("*.*\n").unwrap();
+        board.reveal_cell(1, 0);
+        let schedule = finale_schedule(&board, (1, 0));
+        assert_eq!(schedule.len(), 2);
+        assert!(schedule.iter().all(|c| c.at == 0.0));
+    }
+
+    #[test]
+    fn nothing_is_scheduled_while_playing() {
+        let mut board = Board::decode_layout("*..\n...\n").unwrap();
+        board.reveal_cell(2, 1);
+        assert!(!board.is_over());
+        assert!(finale_schedule(&board, (2, 1)).is_empty());
+    }
+
+    #[test]
+    fn cues_fade_in_over_their_own_span() {
+        assert_eq!(shown(1.0, 0.5, false), 0.0);
+        assert!(close(shown(1.0, 1.0 + FADE_SECS / 2.0, false), 0.5));
+        assert_eq!(shown(1.0, 9.0, false), 1.0);
+        assert_eq!(shown(1.0, 0.0, true), 1.0);
+    }
+
+    #[test]
+    fn the_finale_runs_from_its_first_frame_until_skipped() {
+        let board = won();
+        let mut visuals = CellVisuals::new(&board);
+        assert!(!visuals.tick_finale(5.0, true));
+        visuals.sync_from_events(&board, &[GameEvent::Won(1, 1)], &[]);
+        assert_eq!(visuals.finale(0, 0), Some((Reveal::Flag, 0.0)));
+        assert_eq!(visuals.finale(1, 1), None);
+
+        // the clock starts when it is first drawn
+        assert!(visuals.tick_finale(100.0, true));
+        let mid = 100.0 + f64::from(SPREAD_SECS + FADE_SECS / 2.0);
+        assert!(visuals.tick_finale(mid, true));
+        assert_eq!(visuals.finale(0, 0), Some((Reveal::Flag, 1.0)));
+        let (_, half) = visuals.finale(4, 2).unwrap();
+        assert!(close(half, 0.5));
+        // the same event again doesn't start it over
+        visuals.sync_from_events(&board, &[GameEvent::Won(1, 1)], &[]);
+        assert!(visuals.tick_finale(mid, true));
+        assert!(close(visuals.finale(4, 2).unwrap().1, 0.5));
+        assert!(!visuals.tick_finale(200.0, true));
+
+        visuals.reset(&board);
+        visuals.sync_from_events(&board, &[GameEvent::Won(1, 1)], &[]);
+        assert!(visuals.tick_finale(0.0, true));
+        visuals.skip_finale();
+        assert!(!visuals.tick_finale(0.1, true));
+        assert_eq!(visuals.finale(4, 2), Some((Reveal::Flag, 1.0)));
+
+        // reduced motion shows it all from the first frame
+        visuals.reset(&board);
+        visuals.sync_from_events(&board, &[GameEvent::Won(1, 1)], &[]);
+        assert!(!visuals.tick_finale(0.0, false));
+        assert_eq!(visuals.finale(4, 2), Some((Reveal::Flag, 1.0)));
+    }
+
+    #[test]
+    fn a_loss_starts_from_the_explosion_and_ends_with_the_game() {
+        let mut board = Board::decode_layout("*..\n..*\n").unwrap();
+        let mut visuals = CellVisuals::new(&board);
+        let before = board.snapshot();
+        board.reveal_cell(2, 1);
+        visuals.sync_from_events(&board, &[GameEvent::Exploded(2, 1)], &[]);
+        visuals.tick_finale(0.0, true);
+        assert_eq!(visuals.finale(2, 1), Some((Reveal::Detonated, 0.0)));
+        assert_eq!(visuals.finale(0, 0), Some((Reveal::Mine, 0.0)));
+
+        // taken back, the board is in play again and the finale is gone
+        board.restore(&before).unwrap();
+        let dirty = board.take_dirty();
+        visuals.sync_from_events(&board, &[], &dirty);
+        assert_eq!(visuals.finale(0, 0), None);
+    }
+
+    #[test]
+    fn cascades_open_ring_by_ring_one_after_another() {
+        let mut board = Board::decode_layout("...*...\n").unwrap();
+        let mut visuals = CellVisuals::new(&board);
+        let veiled = |visuals: &CellVisuals| -> Vec<bool> {
+            (0..7).map(|x| visuals.get(x, 0).veiled).collect()
+        };
+        visuals.tick_cascade(10.0, true);
+        board.reveal_cell(0, 0);
+        let dirty = board.take_dirty();
+        visuals.sync_from_events(&board, &[], &dirty);
+        visuals.cascade(&board.last_reveal_wavefronts());
+        // the clicked cell shows at once
+        assert_eq!(
+            veiled(&visuals),
+            [false, true, true, false, false, false, false]
+        );
+
+        // a move made meanwhile waits for the first to finish
+        let before = board.snapshot();
+        board.reveal_cell(6, 0);
+        let dirty = board.take_dirty();
+        visuals.sync_from_events(&board, &[], &dirty);
+        visuals.cascade(&board.last_reveal_wavefronts());
+        assert!(visuals.tick_cascade(10.0 + 2.5 * RING_SECS, true));
+        assert_eq!(
+            veiled(&visuals),
+            [false, false, false, false, true, true, false]
+        );
+
+        // taken back, a cell loses its veil and its cue
+        board.restore(&before).unwrap();
+        let dirty = board.take_dirty();
+        visuals.sync_from_events(&board, &[], &dirty);
+        assert!(!visuals.tick_cascade(10.0 + 2.5 * RING_SECS, true));
+        assert_eq!(veiled(&visuals), vec![false; 7]);
+
+        // without animations the rest shows at the next frame
+        board.reveal_cell(6, 0);
+        visuals.cascade(&board.last_reveal_wavefronts());
+        assert!(visuals.get(4, 0).veiled);
+        assert!(!visuals.tick_cascade(10.0, false));
+        assert_eq!(veiled(&visuals), vec![false; 7]);
+    }
+}
@@ -0,0 +1,1758 @@
+//! Reusable pieces of the game UI.
+
+use crate::coach::cell_name;
+use crate::perf::profile_scope;
+use crate::settings::CountStyle;
+use crate::themes::{Palette, Rgb};
+use crate::visuals::{CellVisuals, Reveal};
+use eframe::egui;
+use eframe::egui::accesskit;
+use minesweeper_rs::annotations::{Annotations, Hue, Note};
+use minesweeper_rs::objective::Objective;
+use minesweeper_rs::render::{Glyph, Marker, Markers};
+use minesweeper_rs::solver;
+use minesweeper_rs::timeline::RevealTimes;
+use minesweeper_rs::Board;
+
+/// Side length of one painted cell, in points.
+pub const CELL_SIZE: f32 = 28.0;
+
+/// How long a press on a cell is held, in seconds, before it opens the
+/// cell's menu instead of acting on release.
+pub const HOLD_SECS: f64 = 0.5;
+
+/// A click the board widget picked up, in cell coordinates.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CellClick {
+    Reveal(usize, usize),
+    Flag(usize, usize),
+}
+
+impl CellClick {
+    pub fn cell(self) -> (usize, usize) {
+        match self {
+            CellClick::Reveal(x, y) | CellClick::Flag(x, y) => (x, y),
+        }
+    }
+
+    /// The same cell with the other action, for swapped mouse buttons.
+    pub fn swapped(self) -> Self {
+        match self {
+            CellClick::Reveal(x, y) => CellClick::Flag(x, y),
+            CellClick::Flag(x, y) => CellClick::Reveal(x, y),
+        }
+    }
+}
+
+/// What the pointer did over the board in one frame, in cell coordinates;
+/// `None` is off the board.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PointerEvent {
+    Down {
+        cell: Option<(usize, usize)>,
+        secondary: bool,
+    },
+    Move(Option<(usize, usize)>),
+    Up {
+        cell: Option<(usize, usize)>,
+        secondary: bool,
+    },
+    Scroll, // the press turned into scrolling the board
+}
+
+/// A button held on the board. As in classic Minesweeper, the action
+/// happens on release, and only if the pointer comes up over the cell it
+/// went down on: dragging off the cell first abandons a misclick.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Press {
+    held: Option<CellClick>,
+    over: Option<(usize, usize)>, // cell under the pointer while held
+}
+
+impl Press {
+    /// Follow one event, returning the action a release completes.
+    pub fn handle(&mut self, event: PointerEvent) -> Option<CellClick> {
+        match event {
+            PointerEvent::Down { cell, secondary } => {
+                self.held = cell.map(|(x, y)| {
+                    if secondary {
+                        CellClick::Flag(x, y)
+                    } else {
+                        CellClick::Reveal(x, y)
+                    }
+                });
+                self.over = cell;
+                None
+            }
+            PointerEvent::Move(cell) => {
+                self.over = cell;
+                None
+            }
+            PointerEvent::Up { cell, secondary } => {
+                // the other button coming up leaves this press held
+                let held = self.held?;
+                if matches!(held, CellClick::Flag(..)) != secondary {
+                    return None;
+                }
+                *self = Press::default();
+                (Some(held.cell()) == cell).then_some(held)
+            }
+            PointerEvent::Scroll => {
+                *self = Press::default();
+                None
+            }
+        }
+    }
+
+    /// The cell to draw pushed in: the held one, while the pointer is on it.
+    pub fn pushed(&self) -> Option<(usize, usize)> {
+        let cell = self.held?.cell();
+        (self.over == Some(cell)).then_some(cell)
+    }
+
+    /// Turn a long press into the cell's menu: drop the press, so its
+    /// release does nothing, and return the cell if the pointer is still
+    /// on it.
+    pub fn hold(&mut self) -> Option<(usize, usize)> {
+        let cell = self.pushed()?;
+        *self = Press::default();
+        Some(cell)
+    }
+}
+
+/// Extra per-cell colouring painted over the board, indexed `y * width + x`.
+pub trait Overlay {
+    /// Wash over the whole cell.
+    fn tint(&self, idx: usize) -> Option<egui::Color32>;
+
+    /// Outline around the cell.
+    fn outline(&self, _idx: usize) -> Option<egui::Color32> {
+        None
+    }
+}
+
+/// Reveal times laid out per cell, for tinting the board from blue (early)
+/// to red (late). Built once when the overlay is switched on.
+pub struct Heatmap {
+    times: Vec<Option<u32>>,
+    last: u32,
+}
+
+impl Heatmap {
+    pub fn new(times: &RevealTimes, cells: usize) -> Self {
+        Heatmap {
+            times: times.per_cell(cells),
+            last: times.last_millis(),
+        }
+    }
+}
+
+impl Overlay for Heatmap {
+    fn tint(&self, idx: usize) -> Option<egui::Color32> {
+        let millis = self.times.get(idx).copied().flatten()?;
+        let t = millis as f32 / self.last.max(1) as f32;
+        let red = (255.0 * t) as u8;
+        Some(egui::Color32::from_rgba_unmultiplied(
+            red,
+            60,
+            255 - red,
+            110,
+        ))
+    }
+}
+
+/// Post-game study of the position before the final move: the solver's mine
+/// chance in red, where the mines really were in blue, and cells logic had
+/// already settled outlined (green safe, red mine).
+pub struct KillScreen {
+    pub position: Board,
+    chances: Vec<Option<f64>>, // hidden cells only
+    mines: Vec<bool>,
+    forced: Vec<Option<bool>>, // Some(is_mine) where deduction settled it
+}
+
+impl KillScreen {
+    /// Analyse `position`. Slow on big boards, so run it off the UI thread.
+    pub fn new(position: Board) -> Self {
+        let analysis = solver::analyze(&position);
+        let cells = position.width() * position.height();
+        let width = position.width();
+        let chances = (0..cells)
+            .map(|i| {
+                let hidden = !position.cell(i % width, i / width).is_revealed();
+                hidden.then(|| analysis.probabilities()[i])
+            })
+            .collect();
+        let mines = position
+            .encode_layout()
+            .bytes()
+            .filter(|&b| b != b'\n')
+            .map(|b| b == b'*')
+            .collect();
+        let mut forced = vec![None; cells];
+        for &(x, y) in analysis.safe() {
+            forced[y * width + x] = Some(false);
+        }
+        for &(x, y) in analysis.mines() {
+            forced[y * width + x] = Some(true);
+        }
+        KillScreen {
+            position,
+            chances,
+            mines,
+            forced,
+        }
+    }
+}
+
+impl Overlay for KillScreen {
+    fn tint(&self, idx: usize) -> Option<egui::Color32> {
+        let chance = self.chances.get(idx).copied().flatten()?;
+        let red = (255.0 * chance) as u8;
+        let blue = if self.mines[idx] { 255 } else { 0 };
+        Some(egui::Color32::from_rgba_unmultiplied(red, 40, blue, 130))
+    }
+
+    fn outline(&self, idx: usize) -> Option<egui::Color32> {
+        self.forced.get(idx).copied().flatten().map(|mine| {
+            if mine {
+                egui::Color32::RED
+            } else {
+                egui::Color32::GREEN
+            }
+        })
+    }
+}
+
+/// Cells to draw the eye to, such as the ones a callout talks about.
+pub struct Highlight {
+    cells: Vec<usize>,
+}
+
+impl Highlight {
+    pub fn new(board: &Board, cells: &[(usize, usize)]) -> Self {
+        let cells = cells.iter().map(|&(x, y)| y * board.width() + x).collect();
+        Highlight { cells }
+    }
+}
+
+impl Overlay for Highlight {
+    fn tint(&self, idx: usize) -> Option<egui::Color32> {
+        self.cells
+            .contains(&idx)
+            .then(|| egui::Color32::from_rgba_unmultiplied(255, 220, 0, 70))
+    }
+
+    fn outline(&self, idx: usize) -> Option<egui::Color32> {
+        self.cells
+            .contains(&idx)
+            .then_some(egui::Color32::from_rgb(255, 200, 0))
+    }
+}
+
+/// A puzzle's target region, washed over faintly so it reads as the goal
+/// without hiding what is in it.
+pub struct Region {
+    cells: Vec<bool>,
+}
+
+impl Region {
+    /// The target of `objective` on `board`; `None` if it has no region.
+    pub fn new(board: &Board, objective: &Objective) -> Option<Self> {
+        let width = board.width();
+        objective.region.as_ref().map(|region| {
+            let mut cells = vec![false; width * board.height()];
+            for &(x, y) in region {
+                cells[y * width + x] = true;
+            }
+            Region { cells }
+        })
+    }
+}
+
+impl Overlay for Region {
+    fn tint(&self, idx: usize) -> Option<egui::Color32> {
+        self.cells
+            .get(idx)
+            .is_some_and(|&target| target)
+            .then(|| egui::Color32::from_rgba_unmultiplied(90, 170, 255, 40))
+    }
+}
+
+/// A floating bubble of `text` beside `target` (a cell rect from
+/// `BoardResponse::cell_rect`), with an arrow from the bubble to it.
+pub fn callout(ctx: &egui::Context, id: egui::Id, target: egui::Rect, text: &str) {
+    // right of the cell, or left of it when that would run off the screen
+    let screen = ctx.content_rect();
+    let gap = CELL_SIZE * 1.5;
+    let on_right = target.right() + gap + CALLOUT_WIDTH <= screen.right();
+    let pos = if on_right {
+        egui::pos2(target.right() + gap, target.top() - CELL_SIZE)
+    } else {
+        egui::pos2(
+            target.left() - gap - CALLOUT_WIDTH,
+            target.top() - CELL_SIZE,
+        )
+    };
+
+    let bubble = egui::Area::new(id)
+        .order(egui::Order::Foreground)
+        .fixed_pos(pos)
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.set_max_width(CALLOUT_WIDTH);
+                ui.label(text);
+            });
+        })
+        .response
+        .rect;
+
+    let from = if on_right {
+        bubble.left_center()
+    } else {
+        bubble.right_center()
+    };
+    let tip = target.center() + (from - target.center()).normalized() * CELL_SIZE * 0.5;
+    let painter = ctx.layer_painter(egui::LayerId::new(
+        egui::Order::Foreground,
+        id.with("arrow"),
+    ));
+    let stroke = egui::Stroke::new(2.0, egui::Color32::from_rgb(255, 200, 0));
+    painter.arrow(from, tip - from, stroke);
+}
+
+/// Widest a callout bubble gets, in points.
+const CALLOUT_WIDTH: f32 = 260.0;
+
+/// Zoom limits for `BoardView`, as multiples of `CELL_SIZE`.
+pub const MIN_ZOOM: f32 = 0.25;
+pub const MAX_ZOOM: f32 = 4.0;
+
+/// Cells kept between the keyboard cursor and the edge of the view when
+/// following focus, unless the settings say otherwise.
+pub const FOCUS_MARGIN: usize = 2;
+
+/// How a board is looked at: zoom, scroll offset and the keyboard cursor.
+/// The screen showing the board owns it between frames, and anything may
+/// set it; `board_widget` hands a changed offset to the scroll area and
+/// reads the user's scrolling back.
+///
+/// An `editing` view is the board editor's: its board comes fully open, so
+/// the mines show, every cell lights up under the pointer, and a long
+/// press is a right click rather than the cell menu.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BoardView {
+    zoom: f32,
+    offset: egui::Vec2,                // board point at the view's top-left corner
+    viewport: egui::Vec2,              // size of the view, as last drawn
+    moved: bool,                       // offset set here, for the scroll area to take
+    pub focus: Option<(usize, usize)>, // the keyboard cursor
+    pub editing: bool,
+    /// The screen moves the cursor itself, so it is always drawn and the
+    /// widget reads no keys, as when two boards share one keyboard.
+    pub steered: bool,
+}
+
+impl Default for BoardView {
+    fn default() -> Self {
+        BoardView {
+            zoom: 1.0,
+            offset: egui::Vec2::ZERO,
+            viewport: egui::Vec2::ZERO,
+            moved: false,
+            focus: None,
+            editing: false,
+            steered: false,
+        }
+    }
+}
+
+impl BoardView {
+    /// A fresh view for the board editor.
+    pub fn editing() -> Self {
+        BoardView {
+            editing: true,
+            ..BoardView::default()
+        }
+    }
+
+    /// A fresh view whose cursor the screen moves, as in local versus.
+    pub fn steered() -> Self {
+        BoardView {
+            steered: true,
+            ..BoardView::default()
+        }
+    }
+
+    #[cfg(test)]
+    pub fn zoom(&self) -> f32 {
+        self.zoom
+    }
+
+    /// Side of one cell at this zoom, in points.
+    pub fn cell_size(&self) -> f32 {
+        CELL_SIZE * self.zoom
+    }
+
+    #[cfg(test)]
+    pub fn offset(&self) -> egui::Vec2 {
+        self.offset
+    }
+
+    /// Scroll so `offset` is at the view's top-left corner; the board
+    /// widget keeps it within the board.
+    pub fn scroll_to(&mut self, offset: egui::Vec2) {
+        self.offset = offset.max(egui::Vec2::ZERO);
+        self.moved = true;
+    }
+
+    /// Zoom by `factor`, within `MIN_ZOOM..=MAX_ZOOM`, keeping the board
+    /// point at `anchor` (relative to the view's top-left) where it is.
+    pub fn zoom_by(&mut self, factor: f32, anchor: egui::Vec2) {
+        let zoom = (self.zoom * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+        let point = (self.offset + anchor) / self.zoom;
+        self.zoom = zoom;
+        self.scroll_to(point * zoom - anchor);
+    }
+
+    /// Zoom by `factor` around the keyboard cursor, or the middle of the
+    /// view when there isn't one.
+    pub fn zoom_at_focus(&mut self, factor: f32) {
+        let anchor = match self.focus {
+            Some((x, y)) => {
+                let center = egui::vec2(x as f32 + 0.5, y as f32 + 0.5) * self.cell_size();
+                center - self.offset
+            }
+            None => self.viewport / 2.0,
+        };
+        self.zoom_by(factor, anchor);
+    }
+
+    /// Scroll a whole view's width or height in the direction (dx, dy),
+    /// taking the cursor along.
+    pub fn pan_screens(&mut self, dx: i32, dy: i32, board: &Board) {
+        let cell = self.cell_size();
+        let step = (self.viewport / cell).floor().max(egui::vec2(1.0, 1.0));
+        let cells = (dx * step.x as i32, dy * step.y as i32);
+        self.scroll_to(self.offset + egui::vec2(cells.0 as f32, cells.1 as f32) * cell);
+        self.move_focus(cells.0, cells.1, board);
+    }
+
+    /// Move the cursor by (dx, dy) cells, stopping at the edges. With no
+    /// cursor yet, it starts at the first cell in view.
+    pub fn move_focus(&mut self, dx: i32, dy: i32, board: &Board) {
+        let (x, y) = match self.focus {
+            Some((x, y)) => {
+                let step = |at: usize, by: i32, len: usize| {
+                    (at as i64 + i64::from(by)).clamp(0, len as i64 - 1) as usize
+                };
+                (step(x, dx, board.width()), step(y, dy, board.height()))
+            }
+            None => self.first_in_view(board),
+        };
+        self.focus = Some((x, y));
+    }
+
+    /// The top-left cell the view shows.
+    fn first_in_view(&self, board: &Board) -> (usize, usize) {
+        let cell = (self.offset / self.cell_size()).ceil();
+        let x = (cell.x.max(0.0) as usize).min(board.width() - 1);
+        let y = (cell.y.max(0.0) as usize).min(board.height() - 1);
+        (x, y)
+    }
+
+    /// Scroll just enough to have the cursor `margin` cells inside the
+    /// view, or as far in as the view allows.
+    pub fn follow(&mut self, margin: usize) {
+        let Some((x, y)) = self.focus else {
+            return;
+        };
+        let cell = self.cell_size();
+        // a margin that doesn't fit shrinks to centring the cursor
+        let room = (self.viewport / cell - egui::vec2(1.0, 1.0)) / 2.0;
+        let margin = egui::vec2(
+            (margin as f32).min(room.x.max(0.0)),
+            (margin as f32).min(room.y.max(0.0)),
+        ) * cell;
+        let min = egui::vec2(x as f32, y as f32) * cell - margin;
+        let max = egui::vec2(x as f32 + 1.0, y as f32 + 1.0) * cell + margin;
+        let mut offset = self.offset;
+        for axis in 0..2 {
+            if min[axis] < offset[axis] {
+                offset[axis] = min[axis];
+            } else if max[axis] > offset[axis] + self.viewport[axis] {
+                offset[axis] = max[axis] - self.viewport[axis];
+            }
+        }
+        if offset != self.offset {
+            self.scroll_to(offset);
+        }
+    }
+
+    /// Keep the offset and cursor on a `board` of this size.
+    fn clamp(&mut self, board: &Board) {
+        let size = egui::vec2(board.width() as f32, board.height() as f32) * self.cell_size();
+        let max = (size - self.viewport).max(egui::Vec2::ZERO);
+        let offset = self.offset.min(max).max(egui::Vec2::ZERO);
+        if offset != self.offset {
+            self.offset = offset;
+            self.moved = true;
+        }
+        if let Some((x, y)) = self.focus {
+            self.focus = Some((x.min(board.width() - 1), y.min(board.height() - 1)));
+        }
+    }
+
+    /// Follow the keys of a focused board: arrows move the cursor,
+    /// Ctrl+arrows scroll a view's worth, Home and End go to the first and
+    /// last cell, Ctrl+plus, minus and 0 zoom around the cursor. Space or
+    /// Enter open the cursor's cell and F flags it.
+    fn keys(&mut self, ui: &egui::Ui, board: &Board) -> Option<CellClick> {
+        use egui::{Key, Modifiers};
+        let mut click = None;
+        let steps = [
+            (Key::ArrowLeft, -1, 0),
+            (Key::ArrowRight, 1, 0),
+            (Key::ArrowUp, 0, -1),
+            (Key::ArrowDown, 0, 1),
+        ];
+        ui.input_mut(|i| {
+            for (key, dx, dy) in steps {
+                if i.consume_key(Modifiers::COMMAND, key) {
+                    self.pan_screens(dx, dy, board);
+                } else if i.consume_key(Modifiers::NONE, key) {
+                    self.move_focus(dx, dy, board);
+                }
+            }
+            if i.consume_key(Modifiers::NONE, Key::Home) {
+                self.focus = Some((0, 0));
+            }
+            if i.consume_key(Modifiers::NONE, Key::End) {
+                self.focus = Some((board.width() - 1, board.height() - 1));
+            }
+            let zoom_in = i.consume_key(Modifiers::COMMAND, Key::Plus)
+                || i.consume_key(Modifiers::COMMAND, Key::Equals);
+            if zoom_in {
+                self.zoom_at_focus(1.25);
+            }
+            if i.consume_key(Modifiers::COMMAND, Key::Minus) {
+                self.zoom_at_focus(0.8);
+            }
+            if i.consume_key(Modifiers::COMMAND, Key::Num0) {
+                self.zoom_at_focus(1.0 / self.zoom);
+            }
+            let cell = self.focus;
+            if i.consume_key(Modifiers::NONE, Key::Space)
+                || i.consume_key(Modifiers::NONE, Key::Enter)
+            {
+                click = cell.map(|(x, y)| CellClick::Reveal(x, y));
+            }
+            if i.consume_key(Modifiers::NONE, Key::F) {
+                click = cell.map(|(x, y)| CellClick::Flag(x, y));
+            }
+        });
+        click
+    }
+}
+
+/// Have board widgets keep the keyboard cursor `margin` cells inside the
+/// view, or leave the scrolling alone with `None`.
+pub fn set_follow_focus(ctx: &egui::Context, margin: Option<usize>) {
+    ctx.data_mut(|d| d.insert_temp(egui::Id::new(FOLLOW_FOCUS), margin));
+}
+
+fn follow_focus(ctx: &egui::Context) -> Option<usize> {
+    ctx.data(|d| d.get_temp(egui::Id::new(FOLLOW_FOCUS)))
+        .unwrap_or(Some(FOCUS_MARGIN))
+}
+
+const FOLLOW_FOCUS: &str = "follow_focus";
+
+/// What `board_widget` saw this frame, and where it drew the board.
+pub struct BoardResponse {
+    pub click: Option<CellClick>,
+    pub menu: Option<(usize, usize)>, // a cell held long enough for its menu
+    pub rect: egui::Rect,             // the whole board in screen space, scroll included
+    pub cell: f32,                    // side of a cell at the zoom it was drawn at
+    pub keyed: bool, // the click came from the keys, so swapped buttons don't apply
+}
+
+impl BoardResponse {
+    /// Screen rect of one cell, which may be scrolled out of view.
+    pub fn cell_rect(&self, x: usize, y: usize) -> egui::Rect {
+        cell_rect(self.rect.min, self.cell, x, y)
+    }
+}
+
+/// Where the board widget puts cell (x, y) of a board whose top-left
+/// corner is at `origin`, with cells `cell` points wide, in screen space.
+pub fn cell_rect(origin: egui::Pos2, cell: f32, x: usize, y: usize) -> egui::Rect {
+    let min = origin + egui::vec2(x as f32, y as f32) * cell;
+    egui::Rect::from_min_size(min, egui::vec2(cell, cell))
+}
+
+/// The rect the last board drawn through `board_widget` took up, scroll
+/// included, and the side of its cells, for code that has to find cells
+/// from outside a frame.
+#[cfg(test)]
+pub fn last_board_rect(ctx: &egui::Context) -> Option<(egui::Rect, f32)> {
+    ctx.data(|d| d.get_temp(egui::Id::new(LAST_BOARD_RECT)))
+}
+
+const LAST_BOARD_RECT: &str = "last_board_rect";
+
+/// Whether the last board drawn through `board_widget` has the keyboard,
+/// so its cursor keys apply.
+pub fn board_focused(ctx: &egui::Context) -> bool {
+    ctx.data(|d| d.get_temp::<egui::Id>(egui::Id::new(LAST_BOARD_ID)))
+        .is_some_and(|id| ctx.memory(|m| m.has_focus(id)))
+}
+
+const LAST_BOARD_ID: &str = "last_board_id";
+
+/// Draw boards with `markers` from now on.
+pub fn set_markers(ctx: &egui::Context, markers: Markers) {
+    ctx.data_mut(|d| d.insert_temp(egui::Id::new(MARKERS), markers));
+}
+
+fn markers(ctx: &egui::Context) -> Markers {
+    ctx.data(|d| d.get_temp(egui::Id::new(MARKERS)))
+        .unwrap_or_default()
+}
+
+const MARKERS: &str = "markers";
+
+/// Have board widgets draw revealed counts in `style`.
+pub fn set_count_style(ctx: &egui::Context, style: CountStyle) {
+    ctx.data_mut(|d| d.insert_temp(egui::Id::new(COUNT_STYLE), style));
+}
+
+/// The style board widgets draw revealed counts in.
+pub fn count_style(ctx: &egui::Context) -> CountStyle {
+    ctx.data(|d| d.get_temp(egui::Id::new(COUNT_STYLE)))
+        .unwrap_or_default()
+}
+
+const COUNT_STYLE: &str = "count_style";
+
+/// Paint boards in `palette` from now on, or in egui's own colours.
+pub fn set_palette(ctx: &egui::Context, palette: Option<Palette>) {
+    ctx.data_mut(|d| d.insert_temp(egui::Id::new(BOARD_PALETTE), palette));
+}
+
+fn palette(ctx: &egui::Context) -> Option<Palette> {
+    ctx.data(|d| d.get_temp(egui::Id::new(BOARD_PALETTE)))
+        .flatten()
+}
+
+const BOARD_PALETTE: &str = "board_palette";
+
+/// The colours a board is painted in: a theme's, or egui's own light or
+/// dark ones with plain numerals.
+struct CellColors {
+    background: Option<egui::Color32>,
+    hidden: egui::Color32,
+    hovered: egui::Color32,
+    pressed: egui::Color32,
+    open: egui::Color32,
+    flash: egui::Color32, // a cell that just refused a move
+    exploded: egui::Color32,
+    text: egui::Color32,
+    numbers: Option<[egui::Color32; 8]>,
+    flag: egui::Color32, // the cloth, and a themed flag drawn as text
+    mine: egui::Color32,
+    highlight: egui::Color32,
+}
+
+impl CellColors {
+    fn new(visuals: &egui::Visuals, palette: Option<&Palette>) -> Self {
+        let Some(palette) = palette else {
+            return CellColors {
+                background: None,
+                hidden: visuals.widgets.inactive.bg_fill,
+                hovered: visuals.widgets.hovered.bg_fill,
+                pressed: visuals.widgets.active.bg_fill,
+                open: visuals.extreme_bg_color,
+                flash: egui::Color32::from_rgb(180, 60, 60),
+                exploded: egui::Color32::from_rgb(220, 40, 40),
+                text: visuals.text_color(),
+                numbers: None,
+                flag: FLAG_RED,
+                mine: visuals.text_color(),
+                highlight: visuals.selection.stroke.color,
+            };
+        };
+        let color = |Rgb([r, g, b]): Rgb| egui::Color32::from_rgb(r, g, b);
+        CellColors {
+            background: Some(color(palette.background)),
+            hidden: color(palette.hidden),
+            hovered: color(palette.hovered),
+            pressed: color(palette.pressed),
+            open: color(palette.open),
+            flash: color(palette.exploded),
+            exploded: color(palette.exploded),
+            text: color(palette.text),
+            numbers: Some(palette.numbers.map(color)),
+            flag: color(palette.flag),
+            mine: color(palette.mine),
+            highlight: color(palette.highlight),
+        }
+    }
+
+    /// What `glyph` is drawn in, its count drawn in `style`. Without a
+    /// theme only corner dots are coloured.
+    fn glyph(&self, glyph: Glyph, style: CountStyle) -> egui::Color32 {
+        match (glyph, self.numbers) {
+            (Glyph::Count(n @ 1..=8), Some(numbers)) => numbers[usize::from(n) - 1],
+            (Glyph::Count(n @ 1..=6), None) if style == CountStyle::Dots => {
+                DOTS[usize::from(n) - 1]
+            }
+            (Glyph::Flag, Some(_)) => self.flag,
+            (Glyph::Mine, _) => self.mine,
+            _ => self.text,
+        }
+    }
+
+    /// The pole or outline, and the cloth, of `marker` drawn as shapes.
+    fn marker(&self, marker: Marker) -> (egui::Color32, egui::Color32) {
+        match marker {
+            Marker::Mine => (self.mine, self.flag),
+            Marker::Flag | Marker::Question => (self.text, self.flag),
+        }
+    }
+}
+
+/// The flag's cloth, without a theme.
+const FLAG_RED: egui::Color32 = egui::Color32::from_rgb(210, 50, 50);
+
+/// Corner dots for counts 1 to 6, without a theme; 7 and 8 are plain.
+const DOTS: [egui::Color32; 6] = [
+    egui::Color32::from_rgb(60, 130, 230),
+    egui::Color32::from_rgb(60, 170, 80),
+    egui::Color32::from_rgb(220, 70, 70),
+    egui::Color32::from_rgb(150, 80, 200),
+    egui::Color32::from_rgb(230, 140, 30),
+    egui::Color32::from_rgb(0, 160, 170),
+];
+
+/// A strip of cells in `palette`, one in every state a board cell can be
+/// in, drawn with the board's markers and counts: hidden, hovered, pressed,
+/// flagged, question-marked, a mine, the mine that went off, the keyboard
+/// cursor and every count.
+pub fn palette_preview(ui: &mut egui::Ui, palette: &Palette) {
+    const COLUMNS: usize = 9;
+    let colors = CellColors::new(ui.visuals(), Some(palette));
+    let counts = count_style(ui.ctx());
+    let markers = markers(ui.ctx());
+    let drawable = Marker::ALL.map(|marker| can_draw(ui.ctx(), markers.get(marker)));
+    let size = egui::vec2(COLUMNS as f32, 2.0) * CELL_SIZE;
+    let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    if let Some(background) = colors.background {
+        painter.rect_filled(rect, 0.0, background);
+    }
+    let mut cells = vec![
+        (Glyph::Hidden, colors.hidden),
+        (Glyph::Hidden, colors.hovered),
+        (Glyph::Hidden, colors.pressed),
+        (Glyph::Flag, colors.hidden),
+        (Glyph::Question, colors.hidden),
+        (Glyph::Mine, colors.open),
+        (Glyph::Mine, colors.exploded),
+        (Glyph::Hidden, colors.hidden), // under the cursor
+        (Glyph::Count(0), colors.open),
+    ];
+    cells.extend((1..=8).map(|n| (Glyph::Count(n), colors.open)));
+    let scale = GlyphScale::of(CELL_SIZE - 2.0);
+    for (i, (glyph, fill)) in cells.into_iter().enumerate() {
+        let cell = cell_rect(rect.min, CELL_SIZE, i % COLUMNS, i / COLUMNS);
+        painter.rect_filled(cell.shrink(1.0), 2.0, fill);
+        let color = colors.glyph(glyph, counts);
+        let label = markers.label(glyph);
+        match (glyph, Marker::of(glyph)) {
+            (Glyph::Count(n), _) if n > 0 && counts != CountStyle::Numerals => {
+                paint_count(&painter, cell.shrink(1.0), n, counts, color);
+            }
+            (_, Some(marker)) if !drawable[marker as usize] => {
+                let (ink, cloth) = colors.marker(marker);
+                paint_marker_in(&painter, cell.shrink(1.0), marker, ink, cloth);
+            }
+            _ if label != " " => {
+                centered_text(&painter, cell, label, scale.font, color);
+            }
+            _ => {}
+        }
+        if i == 7 {
+            let stroke = egui::Stroke::new(3.0, colors.highlight);
+            painter.rect_stroke(cell, 3.0, stroke, egui::StrokeKind::Inside);
+        }
+    }
+}
+
+/// Whether the proportional font has a glyph for every char of `text`.
+/// egui doesn't join ZWJ sequences, so those never pass.
+pub fn can_draw(ctx: &egui::Context, text: &str) -> bool {
+    let font = egui::FontId::proportional(16.0);
+    ctx.fonts_mut(|fonts| fonts.has_glyphs(&font, text))
+}
+
+/// Cells narrower than this, in points, get simplified markers: a plain
+/// triangle for a flag and a dot for a mine, which still read when tiny.
+pub const DETAILED_FROM: f32 = 20.0;
+
+/// Text size limits for `GlyphScale`, in points.
+const MIN_FONT: f32 = 4.0;
+const MAX_FONT: f32 = 48.0;
+
+/// How big to draw what goes in a cell: numbers at about 0.6 of its side,
+/// and markers scaled to match, so neither overflows a small cell nor
+/// gets lost in a big one.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GlyphScale {
+    pub font: f32,      // text size, in points
+    pub detailed: bool, // flag on a pole and spiked mine, not triangle and dot
+}
+
+impl GlyphScale {
+    /// The scale for a painted cell `side` points across.
+    pub fn of(side: f32) -> GlyphScale {
+        GlyphScale {
+            font: (side * 0.6).clamp(MIN_FONT, MAX_FONT),
+            detailed: side >= DETAILED_FROM,
+        }
+    }
+
+    /// Marker shapes are laid out for 16-point text; this is how much to
+    /// grow them by.
+    fn unit(self) -> f32 {
+        self.font / 16.0
+    }
+}
+
+/// Paint `text` with its ink centred on `rect`. The text is measured, not
+/// placed by its line box, which sits digits a little low. Returns where
+/// the ink went.
+pub fn centered_text(
+    painter: &egui::Painter,
+    rect: egui::Rect,
+    text: &str,
+    size: f32,
+    color: egui::Color32,
+) -> egui::Rect {
+    let galley = painter.layout_no_wrap(text.to_string(), egui::FontId::proportional(size), color);
+    let ink = match galley.mesh_bounds {
+        ink if ink.is_positive() => ink,
+        _ => galley.rect, // nothing visible, such as a space
+    };
+    let pos = rect.center() - ink.center().to_vec2();
+    painter.galley(pos, galley, color);
+    ink.translate(pos.to_vec2())
+}
+
+/// A marker as shapes, for one the font has no glyph for or a cell too
+/// small for its label. Drawn for a cell of `rect`'s size, so it scales
+/// with the zoom; returns where it went.
+pub fn paint_marker(
+    painter: &egui::Painter,
+    rect: egui::Rect,
+    marker: Marker,
+    color: egui::Color32,
+) -> egui::Rect {
+    paint_marker_in(painter, rect, marker, color, FLAG_RED)
+}
+
+/// `paint_marker` with a flag's cloth in `cloth`.
+fn paint_marker_in(
+    painter: &egui::Painter,
+    rect: egui::Rect,
+    marker: Marker,
+    color: egui::Color32,
+    cloth: egui::Color32,
+) -> egui::Rect {
+    let scale = GlyphScale::of(rect.width());
+    if marker == Marker::Question {
+        return centered_text(painter, rect, "?", scale.font, color);
+    }
+    let shapes = marker_shapes(rect.center(), scale, marker, color, cloth);
+    let bounds = shapes.iter().fold(egui::Rect::NOTHING, |b, shape| {
+        b.union(shape.visual_bounding_rect())
+    });
+    painter.extend(shapes);
+    bounds
+}
+
+/// The flag or mine around `c`.
+fn marker_shapes(
+    c: egui::Pos2,
+    scale: GlyphScale,
+    marker: Marker,
+    color: egui::Color32,
+    red: egui::Color32, // the flag's cloth
+) -> Vec<egui::Shape> {
+    let unit = scale.unit();
+    let at = |x: f32, y: f32| c + egui::vec2(x, y) * unit;
+    match (marker, scale.detailed) {
+        (Marker::Flag, true) => {
+            let pole = egui::Stroke::new(1.5 * unit, color);
+            let cloth = vec![at(-3.0, -7.0), at(6.0, -3.5), at(-3.0, 0.0)];
+            vec![
+                egui::Shape::line_segment([at(-3.0, -7.0), at(-3.0, 7.0)], pole),
+                egui::Shape::line_segment([at(-6.0, 7.0), at(2.0, 7.0)], pole),
+                egui::Shape::convex_polygon(cloth, red, egui::Stroke::NONE),
+            ]
+        }
+        (Marker::Flag, false) => {
+            let cloth = vec![at(-5.0, -6.0), at(6.0, 0.0), at(-5.0, 6.0)];
+            vec![egui::Shape::convex_polygon(cloth, red, egui::Stroke::NONE)]
+        }
+        (Marker::Mine, true) => {
+            let spike = egui::Stroke::new(1.5 * unit, color);
+            let mut shapes: Vec<egui::Shape> = [(1.0, 0.0), (0.0, 1.0), (0.7, 0.7), (0.7, -0.7)]
+                .into_iter()
+                .map(|(dx, dy)| {
+                    let d = egui::vec2(dx, dy) * 8.0 * unit;
+                    egui::Shape::line_segment([c - d, c + d], spike)
+                })
+                .collect();
+            shapes.push(egui::Shape::circle_filled(c, 5.0 * unit, color));
+            shapes
+        }
+        (Marker::Mine, false) => vec![egui::Shape::circle_filled(c, 6.0 * unit, color)],
+        (Marker::Question, _) => Vec::new(), // text, not shapes
+    }
+}
+
+/// A count drawn as marks rather than a numeral, for a cell of `rect`'s
+/// size. How many marks there are and where they sit both give the count,
+/// so the dots' colours are only a help.
+fn paint_count(
+    painter: &egui::Painter,
+    rect: egui::Rect,
+    count: u8,
+    style: CountStyle,
+    color: egui::Color32,
+) {
+    match style {
+        CountStyle::Numerals => {}
+        CountStyle::Pips => {
+            for pip in pip_layout(count, rect) {
+                painter.circle_filled(pip, rect.width() * 0.09, color);
+            }
+        }
+        CountStyle::Dots => {
+            for dot in dot_layout(count, rect) {
+                painter.circle_filled(dot, rect.width() * 0.06, color);
+            }
+        }
+    }
+}
+
+/// Where the pips for `count` go in a cell of `rect`: the faces of a die
+/// on a 3x3 grid, with 7 and 8 filling in the middle row and column.
+fn pip_layout(count: u8, rect: egui::Rect) -> Vec<egui::Pos2> {
+    const FACES: [&[(u8, u8)]; 9] = [
+        &[],
+        &[(1, 1)],
+        &[(0, 0), (2, 2)],
+        &[(0, 0), (1, 1), (2, 2)],
+        &[(0, 0), (2, 0), (0, 2), (2, 2)],
+        &[(0, 0), (2, 0), (1, 1), (0, 2), (2, 2)],
+        &[(0, 0), (2, 0), (0, 1), (2, 1), (0, 2), (2, 2)],
+        &[(0, 0), (2, 0), (0, 1), (1, 1), (2, 1), (0, 2), (2, 2)],
+        &[
+            (0, 0),
+            (1, 0),
+            (2, 0),
+            (0, 1),
+            (2, 1),
+            (0, 2),
+            (1, 2),
+            (2, 2),
+        ],
+    ];
+    FACES[usize::from(count.min(8))]
+        .iter()
+        .map(|&(col, row)| {
+            let at = egui::vec2(f32::from(col) + 1.0, f32::from(row) + 1.0) / 4.0;
+            rect.min + at * rect.size()
+        })
+        .collect()
+}
+
+/// Where the dots for `count` go in a cell of `rect`: four to a row from
+/// the bottom-left corner up, away from the corners notes use.
+fn dot_layout(count: u8, rect: egui::Rect) -> Vec<egui::Pos2> {
+    (0..count.min(8))
+        .map(|i| {
+            let (col, row) = (f32::from(i % 4), f32::from(i / 4));
+            let x = rect.left() + rect.width() * (0.15 + 0.15 * col);
+            let y = rect.bottom() - rect.height() * (0.15 + 0.15 * row);
+            egui::pos2(x, y)
+        })
+        .collect()
+}
+
+/// The zoom a painted cell rect (a cell less its 1-point border) is at.
+fn zoom_of(rect: egui::Rect) -> f32 {
+    rect.width() / (CELL_SIZE - 2.0)
+}
+
+/// Paint a player's note in the cell's corners, which nothing else uses, so
+/// it can't be taken for a marker, a tint or an outline: a letter on a dark
+/// badge top left, a corner top right, a dot bottom right.
+fn paint_note(painter: &egui::Painter, rect: egui::Rect, note: Note) {
+    let scale = zoom_of(rect);
+    let hue = |hue| match hue {
+        Hue::Pink => egui::Color32::from_rgb(255, 105, 200),
+        Hue::Cyan => egui::Color32::from_rgb(0, 215, 235),
+        Hue::Lime => egui::Color32::from_rgb(160, 240, 40),
+    };
+    match note {
+        Note::Letter(c) => {
+            let badge = egui::Rect::from_min_size(rect.min, egui::vec2(11.0, 12.0) * scale);
+            painter.rect_filled(badge, 3.0 * scale, egui::Color32::from_black_alpha(190));
+            painter.text(
+                badge.center(),
+                egui::Align2::CENTER_CENTER,
+                c,
+                egui::FontId::monospace(10.0 * scale),
+                egui::Color32::from_rgb(255, 235, 120),
+            );
+        }
+        Note::Corner(h) => {
+            let corner = vec![
+                rect.right_top(),
+                rect.right_top() + egui::vec2(0.0, 10.0) * scale,
+                rect.right_top() + egui::vec2(-10.0, 0.0) * scale,
+            ];
+            painter.add(egui::Shape::convex_polygon(
+                corner,
+                hue(h),
+                egui::Stroke::NONE,
+            ));
+        }
+        Note::Dot(h) => {
+            let at = rect.right_bottom() - egui::vec2(5.0, 5.0) * scale;
+            let ring = egui::Stroke::new(scale, egui::Color32::BLACK);
+            painter.circle(at, 3.5 * scale, hue(h), ring);
+        }
+    }
+}
+
+/// Paint the board inside a scroll area, zoomed and scrolled as `view`
+/// says. Only cells inside the viewport are touched, so a 2000x2000 board
+/// costs about the same per frame as Expert. Once focused (a click, or
+/// Tab), the board takes the keys `BoardView` lists. `looks` adds what the
+/// board itself doesn't say, such as a flashing cell or the mine that went
+/// off; `notes` are drawn over everything else.
+pub fn board_widget(
+    ui: &mut egui::Ui,
+    board: &Board,
+    view: &mut BoardView,
+    looks: Option<&CellVisuals>,
+    overlay: Option<&dyn Overlay>,
+    notes: Option<&Annotations>,
+) -> BoardResponse {
+    profile_scope!(crate::perf::Scope::Board);
+    let mut click = None;
+    let mut menu = None;
+    let mut board_rect = egui::Rect::NOTHING;
+
+    let id = ui.id().with("board");
+    ui.data_mut(|d| d.insert_temp(egui::Id::new(LAST_BOARD_ID), id));
+    let mut keyed = false;
+    // a disabled board, under a dialog, keeps its cursor but takes no keys
+    if !view.steered && ui.is_enabled() && ui.memory(|m| m.has_focus(id)) {
+        let before = view.focus;
+        click = view.keys(ui, board);
+        keyed = click.is_some();
+        if let Some(margin) = follow_focus(ui.ctx()).filter(|_| view.focus != before) {
+            view.follow(margin);
+        }
+        // arrows stay on the board rather than moving focus to a widget
+        ui.memory_mut(|m| {
+            let filter = egui::EventFilter {
+                horizontal_arrows: true,
+                vertical_arrows: true,
+                ..Default::default()
+            };
+            m.set_focus_lock_filter(id, filter);
+        });
+    }
+    view.clamp(board);
+    let cell_size = view.cell_size();
+
+    let mut scroll = egui::ScrollArea::both();
+    if std::mem::take(&mut view.moved) {
+        scroll = scroll.scroll_offset(view.offset);
+    }
+    let output = scroll.show_viewport(ui, |ui, viewport| {
+        let size = egui::vec2(board.width() as f32, board.height() as f32) * cell_size;
+        let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+        let response = ui.interact(rect, id, egui::Sense::click());
+        if response.is_pointer_button_down_on() {
+            response.request_focus();
+        }
+        if response.gained_focus() && view.focus.is_none() {
+            view.move_focus(0, 0, board);
+        }
+        board_rect = rect;
+        ui.data_mut(|d| d.insert_temp(egui::Id::new(LAST_BOARD_RECT), (rect, cell_size)));
+
+        // the inverse of `cell_rect`
+        let cell_at = |pos: egui::Pos2| {
+            let rel = (pos - rect.min) / cell_size;
+            let (x, y) = (rel.x.floor(), rel.y.floor());
+            let inside = x >= 0.0 && y >= 0.0;
+            (inside && (x as usize) < board.width() && (y as usize) < board.height())
+                .then_some((x as usize, y as usize))
+        };
+
+        // viewport is relative to the content's top-left, same as `rect`
+        let x0 = (viewport.min.x / cell_size).floor().max(0.0) as usize;
+        let y0 = (viewport.min.y / cell_size).floor().max(0.0) as usize;
+        let x1 = ((viewport.max.x / cell_size).ceil().max(0.0) as usize).min(board.width());
+        let y1 = ((viewport.max.y / cell_size).ceil().max(0.0) as usize).min(board.height());
+
+        let (mut press, last_origin): (Press, egui::Pos2) = ui
+            .data(|d| d.get_temp(response.id))
+            .unwrap_or((Press::default(), rect.min));
+        let under = if response.contains_pointer() {
+            ui.input(|i| i.pointer.interact_pos()).and_then(cell_at)
+        } else {
+            None
+        };
+        let events = ui.input(|i| {
+            let mut events = Vec::new();
+            // a touch drag scrolls the board, as may a mouse drag
+            let scrolling =
+                (i.any_touches() && i.pointer.is_decidedly_dragging()) || rect.min != last_origin;
+            if scrolling {
+                events.push(PointerEvent::Scroll);
+            }
+            let buttons = [
+                (egui::PointerButton::Primary, false),
+                (egui::PointerButton::Secondary, true),
+            ];
+            // not `hovered`: a scrollable view takes the press as the start
+            // of a drag, and a dragged widget is the only one hovered
+            for (button, secondary) in buttons {
+                if response.contains_pointer() && i.pointer.button_pressed(button) {
+                    events.push(PointerEvent::Down {
+                        cell: under,
+                        secondary,
+                    });
+                }
+            }
+            events.push(PointerEvent::Move(under));
+            for (button, secondary) in buttons {
+                if i.pointer.button_released(button) {
+                    events.push(PointerEvent::Up {
+                        cell: under,
+                        secondary,
+                    });
+                }
+            }
+            events
+        });
+        for event in events {
+            click = click.or(press.handle(event));
+        }
+        if let Some(clicked) = click {
+            view.focus = Some(clicked.cell());
+        }
+        // either button held still on a cell opens its menu, which is how
+        // touch screens reach flags and the rest
+        if press.pushed().is_some() {
+            let held = ui.input(|i| i.pointer.press_start_time().map(|t| i.time - t));
+            match held {
+                Some(secs) if secs >= HOLD_SECS && view.editing => {
+                    click = click.or(press.hold().map(|(x, y)| CellClick::Flag(x, y)))
+                }
+                Some(secs) if secs >= HOLD_SECS => menu = press.hold(),
+                Some(secs) => ui
+                    .ctx()
+                    .request_repaint_after(std::time::Duration::from_secs_f64(HOLD_SECS - secs)),
+                None => {}
+            }
+        }
+        ui.data_mut(|d| d.insert_temp(response.id, (press, rect.min)));
+
+        let hovered = response.hover_pos().and_then(cell_at);
+        let pushed = press.pushed();
+        let painter = ui.painter_at(rect);
+        let visuals = ui.visuals();
+        let colors = CellColors::new(visuals, palette(ui.ctx()).as_ref());
+        if let Some(background) = colors.background {
+            painter.rect_filled(rect, 0.0, background);
+        }
+        let scale = GlyphScale::of(cell_size - 2.0);
+        let markers = markers(ui.ctx());
+        let counts = count_style(ui.ctx());
+        let drawable = Marker::ALL.map(|marker| can_draw(ui.ctx(), markers.get(marker)));
+
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let cell_rect = cell_rect(rect.min, cell_size, x, y).shrink(1.0);
+                let cell = board.cell(x, y);
+                let look = looks.map(|l| l.get(x, y)).unwrap_or_default();
+
+                // a veiled cell looks hidden, but the press shows what the
+                // board will do with it, and an open cell won't go in
+                let open = cell.is_revealed() && !look.veiled;
+                let fill = if look.flash {
+                    colors.flash
+                } else if look.exploded {
+                    colors.exploded
+                } else if open && !view.editing {
+                    colors.open
+                } else if pushed == Some((x, y)) && (!cell.is_revealed() || view.editing) {
+                    colors.pressed
+                } else if hovered == Some((x, y)) {
+                    colors.hovered
+                } else if open {
+                    colors.open
+                } else {
+                    colors.hidden
+                };
+                painter.rect_filled(cell_rect, 2.0, fill);
+                let idx = y * board.width() + x;
+                if let Some(tint) = overlay.and_then(|o| o.tint(idx)) {
+                    painter.rect_filled(cell_rect, 2.0, tint);
+                }
+                if let Some(color) = overlay.and_then(|o| o.outline(idx)) {
+                    let stroke = egui::Stroke::new(2.0, color);
+                    painter.rect_stroke(cell_rect, 2.0, stroke, egui::StrokeKind::Inside);
+                }
+                // a win's glow sweep, over the open cells
+                let glow = looks.map_or(0.0, |l| l.win.glow(x, board.width()));
+                if open && glow > 0.0 {
+                    let warm = egui::Color32::from_rgb(255, 236, 160);
+                    painter.rect_filled(cell_rect, 2.0, warm.gamma_multiply(0.4 * glow));
+                }
+                if look.final_flag {
+                    let stroke = egui::Stroke::new(2.0, egui::Color32::GOLD);
+                    painter.rect_stroke(cell_rect, 2.0, stroke, egui::StrokeKind::Inside);
+                }
+
+                // the finale shows what the board doesn't: the mines left
+                // hidden, fading in, and the flags given on a win
+                let finale = looks.and_then(|l| l.finale(x, y));
+                let (glyph, shown) = match finale {
+                    Some((Reveal::Mine, shown)) if !cell.is_revealed() => (Glyph::Mine, shown),
+                    Some((Reveal::Flag, shown)) if !cell.is_revealed() => (Glyph::Flag, shown),
+                    _ if look.veiled => (Glyph::Hidden, 1.0),
+                    _ => (Glyph::of(cell), 1.0),
+                };
+                let text_color = colors.glyph(glyph, counts).gamma_multiply(shown);
+                if let Some((Reveal::Detonated, shown)) = finale {
+                    let flash = egui::Color32::WHITE.gamma_multiply(1.0 - shown);
+                    painter.rect_filled(cell_rect, 2.0, flash);
+                }
+
+                let label = markers.label(glyph);
+                let pictured = match glyph {
+                    Glyph::Count(n) if n > 0 && counts != CountStyle::Numerals => Some(n),
+                    _ => None,
+                };
+                // a flag the win's wave has bounced up
+                let glyph_rect = match glyph {
+                    Glyph::Flag => {
+                        let lift = looks.map_or(0.0, |l| l.win.lift(x, y));
+                        cell_rect.translate(egui::vec2(0.0, -0.3 * lift * cell_rect.height()))
+                    }
+                    _ => cell_rect,
+                };
+                if shown <= 0.0 {
+                    // not reached by the finale yet
+                } else if let Some(n) = pictured {
+                    paint_count(&painter, cell_rect, n, counts, text_color);
+                } else if let Some(marker) =
+                    Marker::of(glyph).filter(|&m| !drawable[m as usize] || !scale.detailed)
+                {
+                    // a small cell gets the simple shapes, whatever the labels
+                    let (ink, cloth) = colors.marker(marker);
+                    let (ink, cloth) = (ink.gamma_multiply(shown), cloth.gamma_multiply(shown));
+                    paint_marker_in(&painter, glyph_rect, marker, ink, cloth);
+                } else if label != " " {
+                    centered_text(&painter, glyph_rect, label, scale.font, text_color);
+                }
+                if let Some((Reveal::WrongFlag, shown)) = finale {
+                    paint_cross(&painter, cell_rect, shown);
+                }
+                if let Some(note) = notes.and_then(|n| n.get(x, y)) {
+                    paint_note(&painter, cell_rect, note);
+                }
+            }
+        }
+        if let Some((x, y)) = view.focus.filter(|_| view.steered || response.has_focus()) {
+            let stroke = egui::Stroke::new(3.0, colors.highlight);
+            let cursor = cell_rect(rect.min, cell_size, x, y);
+            painter.rect_stroke(cursor, 3.0, stroke, egui::StrokeKind::Inside);
+        }
+
+        // read out as the cursor's cell, so moving it is announced
+        let label = match view.focus {
+            Some((x, y)) => accessible_label(board, x, y),
+            None => "Board".to_string(),
+        };
+        response.widget_info(|| egui::WidgetInfo::labeled(egui::WidgetType::Other, true, &label));
+        describe_cells(ui, board, (rect, cell_size), (x0..x1, y0..y1));
+    });
+    view.offset = output.state.offset;
+    view.viewport = output.inner_rect.size();
+
+    BoardResponse {
+        click,
+        menu,
+        rect: board_rect,
+        cell: cell_size,
+        keyed,
+    }
+}
+
+/// Cross out a wrong flag, `shown` of the way faded in.
+fn paint_cross(painter: &egui::Painter, rect: egui::Rect, shown: f32) {
+    let r = rect.shrink(rect.width() * 0.2);
+    let stroke = egui::Stroke::new(
+        2.5 * zoom_of(rect),
+        egui::Color32::LIGHT_RED.gamma_multiply(shown),
+    );
+    painter.line_segment([r.left_top(), r.right_bottom()], stroke);
+    painter.line_segment([r.right_top(), r.left_bottom()], stroke);
+}
+
+/// Give screen readers a grid of rows of labelled cells for the visible
+/// part of the board, since it is painted rather than built from widgets.
+/// The cells only sense hover, so clicks still land on the board itself.
+/// Does nothing unless AccessKit is active.
+fn describe_cells(
+    ui: &mut egui::Ui,
+    board: &Board,
+    (rect, cell): (egui::Rect, f32),
+    (xs, ys): (std::ops::Range<usize>, std::ops::Range<usize>),
+) {
+    let grid = ui.ctx().accesskit_node_builder(ui.id(), |node| {
+        node.set_role(accesskit::Role::Grid);
+        node.set_row_count(board.height());
+        node.set_column_count(board.width());
+    });
+    if grid.is_none() {
+        return;
+    }
+    for y in ys {
+        let min = rect.min + egui::vec2(0.0, y as f32 * cell);
+        let row_rect = egui::Rect::from_min_size(min, egui::vec2(rect.width(), cell));
+        let row = ui.new_child(
+            egui::UiBuilder::new()
+                .id_salt(("board_row", y))
+                .max_rect(row_rect),
+        );
+        row.ctx().accesskit_node_builder(row.id(), |node| {
+            node.set_role(accesskit::Role::Row);
+            node.set_row_index(y);
+        });
+        for x in xs.clone() {
+            let cell_rect = cell_rect(rect.min, cell, x, y);
+            let id = row.id().with(x);
+            let response = row.interact(cell_rect, id, egui::Sense::hover());
+            response.widget_info(|| {
+                egui::WidgetInfo::labeled(
+                    egui::WidgetType::Other,
+                    true,
+                    accessible_label(board, x, y),
+                )
+            });
+            row.ctx().accesskit_node_builder(id, |node| {
+                node.set_role(accesskit::Role::Cell);
+                node.set_row_index(y);
+                node.set_column_index(x);
+            });
+        }
+    }
+}
+
+/// What a screen reader says for a cell: where it is, then what it shows,
+/// as in "C3, revealed, 2 adjacent mines".
+pub fn accessible_label(board: &Board, x: usize, y: usize) -> String {
+    format!(
+        "{}, {}",
+        cell_name((x, y)),
+        Glyph::of(board.cell(x, y)).spoken()
+    )
+}
+
+/// Have screen readers announce `response`'s text whenever it changes, for
+/// status lines that update without being focused.
+pub fn live(response: &egui::Response) {
+    response.ctx.accesskit_node_builder(response.id, |node| {
+        node.set_live(accesskit::Live::Polite);
+    });
+}
+
+/// Mines left to find, as mines minus flags. Turns amber once a flag limit
+/// is used up, and shakes while `shake` is set (a refused flag).
+pub fn mine_counter(ui: &mut egui::Ui, board: &Board, shake: bool) {
+    let left = board.mine_count() as i64 - board.flags() as i64;
+    if shake {
+        let t = ui.input(|i| i.time);
+        ui.add_space(3.0 + 3.0 * (t * 40.0).sin() as f32);
+        ui.ctx().request_repaint();
+    }
+    let text = format!("Mines: {left}");
+    let response = if board.flags_left() == Some(0) {
+        ui.colored_label(egui::Color32::from_rgb(255, 191, 0), text)
+    } else {
+        ui.label(text)
+    };
+    live(&response);
+}
+
+/// The win/loss banner, shown once the game is over.
+pub fn game_status(ui: &mut egui::Ui, board: &Board) {
+    if board.is_over() {
+        let response = if board.is_won() {
+            ui.colored_label(egui::Color32::GREEN, "You win! 🎉")
+        } else {
+            ui.colored_label(egui::Color32::RED, "You hit a mine! 💥")
+        };
+        live(&response);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cells_are_read_out_by_name_and_state() {
+        let mut board = Board::decode_layout("*..\n...\n..*\n").unwrap();
+        board.toggle_flag(0, 0);
+        board.reveal_cell(1, 1);
+        assert_eq!(accessible_label(&board, 0, 0), "A1, flagged");
+        assert_eq!(accessible_label(&board, 1, 0), "B1, hidden");
+        assert_eq!(
+            accessible_label(&board, 1, 1),
+            "B2, revealed, 2 adjacent mines"
+        );
+
+        board.reveal_cell(2, 2);
+        assert_eq!(accessible_label(&board, 2, 2), "C3, mine");
+    }
+
+    /// Feed `events` to a fresh press, collecting what completes.
+    fn play(events: &[PointerEvent]) -> (Vec<CellClick>, Press) {
+        let mut press = Press::default();
+        let clicks = events.iter().filter_map(|&e| press.handle(e)).collect();
+        (clicks, press)
+    }
+
+    fn down(x: usize, y: usize) -> PointerEvent {
+        PointerEvent::Down {
+            cell: Some((x, y)),
+            secondary: false,
+        }
+    }
+
+    fn up(cell: Option<(usize, usize)>) -> PointerEvent {
+        PointerEvent::Up {
+            cell,
+            secondary: false,
+        }
+    }
+
+    #[test]
+    fn releases_on_the_pressed_cell_act() {
+        let (clicks, press) = play(&[down(2, 3), PointerEvent::Move(Some((2, 3)))]);
+        assert!(clicks.is_empty());
+        assert_eq!(press.pushed(), Some((2, 3)));
+
+        let (clicks, _) = play(&[down(2, 3), up(Some((2, 3)))]);
+        assert_eq!(clicks, [CellClick::Reveal(2, 3)]);
+
+        let flag = PointerEvent::Down {
+            cell: Some((1, 1)),
+            secondary: true,
+        };
+        let release = PointerEvent::Up {
+            cell: Some((1, 1)),
+            secondary: true,
+        };
+        assert_eq!(play(&[flag, release]).0, [CellClick::Flag(1, 1)]);
+    }
+
+    #[test]
+    fn dragging_off_abandons_the_press() {
+        let (clicks, press) = play(&[down(2, 3), PointerEvent::Move(Some((3, 3)))]);
+        assert_eq!(press.pushed(), None);
+        assert!(clicks.is_empty());
+        let (clicks, _) = play(&[
+            down(2, 3),
+            PointerEvent::Move(Some((3, 3))),
+            up(Some((3, 3))),
+        ]);
+        assert!(clicks.is_empty());
+        let (clicks, _) = play(&[down(2, 3), PointerEvent::Move(None), up(None)]);
+        assert!(clicks.is_empty());
+
+        // coming back before letting go still counts
+        let moves = [
+            down(2, 3),
+            PointerEvent::Move(Some((3, 3))),
+            PointerEvent::Move(Some((2, 3))),
+            up(Some((2, 3))),
+        ];
+        assert_eq!(play(&moves).0, [CellClick::Reveal(2, 3)]);
+    }
+
+    #[test]
+    fn scrolling_and_stray_releases_do_nothing() {
+        let (clicks, press) = play(&[down(0, 0), PointerEvent::Scroll, up(Some((0, 0)))]);
+        assert!(clicks.is_empty());
+        assert_eq!(press, Press::default());
+
+        // a release with nothing held, and the other button's release
+        assert!(play(&[up(Some((0, 0)))]).0.is_empty());
+        let other = PointerEvent::Up {
+            cell: Some((0, 0)),
+            secondary: true,
+        };
+        let (clicks, press) = play(&[down(0, 0), other]);
+        assert!(clicks.is_empty());
+        assert_eq!(press.pushed(), Some((0, 0)));
+
+        // pressing off the board holds nothing
+        let off = PointerEvent::Down {
+            cell: None,
+            secondary: false,
+        };
+        assert!(play(&[off, up(None)]).0.is_empty());
+    }
+
+    #[test]
+    fn holding_opens_the_menu_instead_of_acting() {
+        let (_, mut press) = play(&[down(2, 3)]);
+        assert_eq!(press.hold(), Some((2, 3)));
+        assert_eq!(press.handle(up(Some((2, 3)))), None);
+
+        // nothing to hold once the pointer has left the cell
+        let (_, mut press) = play(&[down(2, 3), PointerEvent::Move(Some((3, 3)))]);
+        assert_eq!(press.hold(), None);
+    }
+
+    #[test]
+    fn pips_and_dots_have_a_mark_a_mine_at_any_size() {
+        let unit = egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(1.0, 1.0));
+        for side in [6.0, 14.0, 30.0, 110.0] {
+            let rect = egui::Rect::from_min_size(egui::pos2(40.0, -12.0), egui::vec2(side, side));
+            for count in 1..=8u8 {
+                for marks in [pip_layout(count, rect), dot_layout(count, rect)] {
+                    assert_eq!(marks.len(), usize::from(count));
+                    assert!(marks.iter().all(|&p| rect.shrink(side * 0.1).contains(p)));
+                    for (i, a) in marks.iter().enumerate() {
+                        assert!(marks[i + 1..].iter().all(|b| a.distance(*b) > side * 0.1));
+                    }
+                }
+                // the same pattern, scaled
+                let scaled = pip_layout(count, unit)
+                    .into_iter()
+                    .map(|p| rect.min + p.to_vec2() * side);
+                for (a, b) in pip_layout(count, rect).into_iter().zip(scaled) {
+                    assert!(a.distance(b) < 0.001);
+                }
+            }
+        }
+        assert!(pip_layout(0, unit).is_empty() && dot_layout(0, unit).is_empty());
+
+        // die faces on a 3x3 grid, a different one for every count
+        let face = |count| {
+            let mut cells: Vec<(i32, i32)> = pip_layout(count, unit)
+                .iter()
+                .map(|p| {
+                    (
+                        (p.x * 4.0).round() as i32 - 1,
+                        (p.y * 4.0).round() as i32 - 1,
+                    )
+                })
+                .collect();
+            cells.sort_unstable();
+            cells
+        };
+        assert_eq!(face(1), [(1, 1)]);
+        assert_eq!(face(5), [(0, 0), (0, 2), (1, 1), (2, 0), (2, 2)]);
+        assert!(!face(8).contains(&(1, 1)));
+        let faces: std::collections::BTreeSet<_> = (1..=8).map(face).collect();
+        assert_eq!(faces.len(), 8);
+    }
+
+    #[test]
+    fn glyphs_stay_inside_their_cells_at_every_size() {
+        let ctx = egui::Context::default();
+        let _ = ctx.run(egui::RawInput::default(), |ctx| {
+            let painter = ctx.layer_painter(egui::LayerId::background());
+            let color = egui::Color32::WHITE;
+            for side in [14.0, 28.0, 48.0] {
+                // as the board widget paints it: the cell less its border
+                let rect = cell_rect(egui::pos2(40.0, 40.0), side, 2, 3).shrink(1.0);
+                let scale = GlyphScale::of(rect.width());
+                assert_eq!(scale.detailed, side >= DETAILED_FROM);
+                for digit in ["1", "2", "3", "4", "5", "6", "7", "8"] {
+                    let ink = centered_text(&painter, rect, digit, scale.font, color);
+                    assert!(
+                        rect.contains_rect(ink),
+                        "{digit} at {side}: {ink:?} in {rect:?}"
+                    );
+                    let off = ink.center() - rect.center();
+                    assert!(off.length() < 0.5, "{digit} at {side} is off by {off:?}");
+                }
+                for marker in Marker::ALL {
+                    let ink = paint_marker(&painter, rect, marker, color);
+                    assert!(
+                        rect.contains_rect(ink),
+                        "{marker:?} at {side}: {ink:?} in {rect:?}"
+                    );
+                    // big enough to make out
+                    assert!(ink.height() > rect.height() * 0.3, "{marker:?} at {side}");
+                }
+            }
+        });
+    }
+
+    #[test]
+    fn text_grows_with_the_cell_within_limits() {
+        let font = |side| GlyphScale::of(side).font;
+        assert!(font(12.0) < font(26.0) && font(26.0) < font(46.0));
+        assert!((font(26.0) - 26.0 * 0.6).abs() < 0.01);
+        assert_eq!(font(1.0), MIN_FONT);
+        assert_eq!(font(1000.0), MAX_FONT);
+    }
+
+    /// A view of `width` by `height` cells at zoom 1.
+    fn view_of(width: f32, height: f32) -> BoardView {
+        BoardView {
+            viewport: egui::vec2(width, height) * CELL_SIZE,
+            ..BoardView::default()
+        }
+    }
+
+    #[test]
+    fn zooming_keeps_the_anchor_still_and_stops_at_the_limits() {
+        let mut view = view_of(10.0, 10.0);
+        view.scroll_to(egui::vec2(3.0, 2.0) * CELL_SIZE);
+        let anchor = egui::vec2(100.0, 60.0);
+        let before = (view.offset() + anchor) / view.cell_size();
+        view.zoom_by(2.0, anchor);
+        assert_eq!(view.zoom(), 2.0);
+        let after = (view.offset() + anchor) / view.cell_size();
+        assert!((after - before).length() < 0.001);
+
+        view.zoom_by(100.0, anchor);
+        assert_eq!(view.zoom(), MAX_ZOOM);
+        view.zoom_by(0.0001, anchor);
+        assert_eq!(view.zoom(), MIN_ZOOM);
+        assert!(view.offset().min_elem() >= 0.0);
+
+        // around the cursor, its cell stays put on screen
+        let mut view = view_of(10.0, 10.0);
+        view.focus = Some((4, 6));
+        let on_screen = |v: &BoardView| egui::vec2(4.5, 6.5) * v.cell_size() - v.offset();
+        let before = on_screen(&view);
+        view.zoom_at_focus(1.25);
+        assert!((on_screen(&view) - before).length() < 0.01);
+    }
+
+    #[test]
+    fn the_cursor_moves_within_the_board() {
+        let board = Board::blank(5, 4, 3);
+        let mut view = view_of(3.0, 3.0);
+        view.scroll_to(egui::vec2(1.0, 1.0) * CELL_SIZE);
+        // it starts on the first cell in view
+        view.move_focus(1, 0, &board);
+        assert_eq!(view.focus, Some((1, 1)));
+        view.move_focus(10, -10, &board);
+        assert_eq!(view.focus, Some((4, 0)));
+
+        // a screenful is three cells here, and the cursor comes along
+        let mut view = view_of(3.0, 3.0);
+        view.focus = Some((0, 0));
+        view.pan_screens(1, 1, &board);
+        assert_eq!(view.offset(), egui::vec2(3.0, 3.0) * CELL_SIZE);
+        assert_eq!(view.focus, Some((3, 3)));
+    }
+
+    #[test]
+    fn following_keeps_a_margin_round_the_cursor() {
+        let mut view = view_of(10.0, 8.0);
+        view.focus = Some((12, 3));
+        view.follow(2);
+        // cells 5 to 14 across: the cursor has two to its right
+        assert_eq!(view.offset(), egui::vec2(5.0 * CELL_SIZE, 0.0));
+
+        view.focus = Some((6, 3));
+        view.follow(2);
+        assert_eq!(view.offset().x, 4.0 * CELL_SIZE);
+
+        // a margin wider than the view allows centres the cursor instead
+        let mut view = view_of(5.0, 5.0);
+        view.focus = Some((20, 20));
+        view.follow(6);
+        assert_eq!(view.offset(), egui::vec2(18.0, 18.0) * CELL_SIZE);
+
+        // in view already, nothing moves
+        view.moved = false;
+        view.follow(1);
+        assert!(!view.moved);
+    }
+
+    /// A loose ceiling on painting an Expert board mid-game, so a change
+    /// that makes it many times slower fails here; the baselines are in
+    /// `benches/frame-budget.md`.
+    #[test]
+    fn an_expert_board_paints_within_budget() {
+        const FRAMES: u32 = 20;
+        const BUDGET: std::time::Duration = std::time::Duration::from_millis(250);
+        let mut board = Board::with_seed(30, 16, 99, 1, Some((15, 8)));
+        board.reveal_cell(15, 8);
+        let ctx = egui::Context::default();
+        let mut view = BoardView::default();
+        let mut frame = || {
+            let _ = ctx.run(egui::RawInput::default(), |ctx| {
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    board_widget(ui, &board, &mut view, None, None, None);
+                });
+            });
+        };
+        // the first frame lays out fonts
+        frame();
+        let started = std::time::Instant::now();
+        for _ in 0..FRAMES {
+            frame();
+        }
+        let average = started.elapsed() / FRAMES;
+        assert!(average < BUDGET, "{average:?} a frame");
+    }
+
+    #[test]
+    fn the_board_reaches_screen_readers() {
+        let board = Board::decode_layout(".*\n..\n").unwrap();
+        let ctx = egui::Context::default();
+        ctx.enable_accesskit();
+        let output = ctx.run(egui::RawInput::default(), |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                board_widget(ui, &board, &mut BoardView::default(), None, None, None);
+            });
+        });
+        let update = output
+            .platform_output
+            .accesskit_update
+            .expect("AccessKit is on");
+        let labels: Vec<&str> = update
+            .nodes
+            .iter()
+            .filter_map(|(_, node)| node.label())
+            .collect();
+        for label in ["A1, hidden", "B1, hidden", "A2, hidden", "B2, hidden"] {
+            assert!(labels.contains(&label), "{label} missing from {labels:?}");
+        }
+    }
+}
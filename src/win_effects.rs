@@ -0,0 +1,265 @@
+//! What a win looks like, as picked in the settings (`WinStyle`):
+//! confetti thrown from the top corners, a soft glow sweeping once across
+//! the board, the won board's flags bouncing one after another, or nothing.
+//!
+//! Each is a `WinEffect` that starts itself on the board's `Animations`,
+//! which run on the frame clock like the finale and which the board widget
+//! reads as it paints; confetti is handed on to the particles. Another
+//! effect needs only its impl and a `WinStyle` to pick it by.
+
+use crate::visuals::WIN_FINALE_SECS;
+use minesweeper_rs::Board;
+use std::f32::consts::PI;
+
+/// How long the glow takes to cross the board.
+const SWEEP_SECS: f32 = 1.2;
+/// Half the glow band's width, as a share of the board's.
+const BAND: f32 = 0.2;
+/// How long one flag takes to bounce up and land.
+const BOUNCE_SECS: f32 = 0.3;
+/// The gap between one flag's bounce and the next.
+const STAGGER_SECS: f32 = 0.05;
+/// The latest the last flag starts its bounce, after the first; a board
+/// with more flags staggers them closer.
+const WAVE_SECS: f32 = 1.5;
+
+/// A win celebration.
+pub trait WinEffect {
+    /// Start the effect for `board`, which has just been won.
+    fn trigger(&self, board: &Board, animations: &mut Animations);
+}
+
+/// Confetti from both top corners of the window.
+pub struct Confetti;
+
+/// A soft highlight that crosses the open cells once, left to right.
+pub struct GlowSweep;
+
+/// Each mine's flag in turn, column by column, bouncing once the finale
+/// has flagged them all.
+pub struct FlagWave;
+
+/// A quiet win.
+pub struct NoEffect;
+
+impl WinEffect for Confetti {
+    fn trigger(&self, _board: &Board, animations: &mut Animations) {
+        animations.confetti = true;
+    }
+}
+
+impl WinEffect for GlowSweep {
+    fn trigger(&self, _board: &Board, animations: &mut Animations) {
+        animations.start(SWEEP_SECS);
+        animations.sweep = true;
+    }
+}
+
+impl WinEffect for FlagWave {
+    fn trigger(&self, board: &Board, animations: &mut Animations) {
+        let mines: Vec<(usize, usize)> = (0..board.width())
+            .flat_map(|x| (0..board.height()).map(move |y| (x, y)))
+            .filter(|&(x, y)| board.cell(x, y).is_mine() == Some(true))
+            .collect();
+        let step = stagger(mines.len());
+        let last = step * mines.len().saturating_sub(1) as f32;
+        animations.start(WIN_FINALE_SECS + last + BOUNCE_SECS);
+        animations.wave = mines
+            .into_iter()
+            .enumerate()
+            .map(|(i, cell)| (cell, WIN_FINALE_SECS + step * i as f32))
+            .collect();
+    }
+}
+
+impl WinEffect for NoEffect {
+    fn trigger(&self, _board: &Board, _animations: &mut Animations) {}
+}
+
+/// The glow on a column `across` of the way over the board (0 to 1),
+/// `elapsed` seconds into the sweep, from 0 to 1: a band brightest at its
+/// middle that comes in from the left edge and is gone past the right
+/// edge after `SWEEP_SECS`.
+pub fn sweep(across: f32, elapsed: f32) -> f32 {
+    if !(0.0..SWEEP_SECS).contains(&elapsed) {
+        return 0.0;
+    }
+    let centre = -BAND + (1.0 + 2.0 * BAND) * elapsed / SWEEP_SECS;
+    let off = ((across - centre).abs() / BAND).min(1.0);
+    0.5 * (1.0 + (PI * off).cos())
+}
+
+/// How high a flag whose bounce starts `at` is lifted, from 0 to 1,
+/// `elapsed` seconds in: up and back down over `BOUNCE_SECS`.
+pub fn bounce(at: f32, elapsed: f32) -> f32 {
+    let t = (elapsed - at) / BOUNCE_SECS;
+    if (0.0..1.0).contains(&t) {
+        (PI * t).sin()
+    } else {
+        0.0
+    }
+}
+
+/// The gap between the bounces of `flags` flags: `STAGGER_SECS`, or less
+/// so that the last starts within `WAVE_SECS` of the first.
+pub fn stagger(flags: usize) -> f32 {
+    STAGGER_SECS.min(WAVE_SECS / flags.saturating_sub(1).max(1) as f32)
+}
+
+/// The win effect under way on one board. Cleared with the board, and
+/// stopped early by `skip`.
+#[derive(Debug, Default)]
+pub struct Animations {
+    confetti: bool,                   // for the screen to throw, once
+    sweep: bool,                      // the glow crossing the board
+    wave: Vec<((usize, usize), f32)>, // flags and when each bounces
+    end: f32,                         // when the last of it is over
+    started: Option<f64>,             // the frame time it began at, once drawn
+    elapsed: f32,
+    skipped: bool,
+}
+
+impl Animations {
+    /// Start afresh on an effect that lasts `secs`.
+    fn start(&mut self, secs: f32) {
+        *self = Animations {
+            end: secs,
+            ..Animations::default()
+        };
+    }
+
+    /// Drop whatever is under way, for a new or taken back game.
+    pub fn clear(&mut self) {
+        *self = Animations::default();
+    }
+
+    /// Whether confetti is due, which asking hands over.
+    pub fn take_confetti(&mut self) -> bool {
+        std::mem::take(&mut self.confetti)
+    }
+
+    /// Move the clock to `now`, the frame time, starting it on the first
+    /// call; without `animate` the effect is over. Returns whether there
+    /// is more to show, so the caller keeps repainting.
+    pub fn tick(&mut self, now: f64, animate: bool) -> bool {
+        if !self.sweep && self.wave.is_empty() {
+            return false;
+        }
+        self.skipped |= !animate;
+        let started = *self.started.get_or_insert(now);
+        self.elapsed = (now - started) as f32;
+        !self.skipped && self.elapsed < self.end
+    }
+
+    /// End the effect where it is.
+    pub fn skip(&mut self) {
+        self.skipped = true;
+    }
+
+    /// How brightly column `x` of a board `width` wide glows, 0 to 1.
+    pub fn glow(&self, x: usize, width: usize) -> f32 {
+        if !self.sweep || self.skipped {
+            return 0.0;
+        }
+        sweep((x as f32 + 0.5) / width.max(1) as f32, self.elapsed)
+    }
+
+    /// How high the flag on (x, y) is bounced, 0 to 1.
+    pub fn lift(&self, x: usize, y: usize) -> f32 {
+        if self.skipped {
+            return 0.0;
+        }
+        self.wave
+            .iter()
+            .find(|&&(cell, _)| cell == (x, y))
+            .map_or(0.0, |&(_, at)| bounce(at, self.elapsed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn won() -> Board {
+        let mut board = Board::decode_layout("*..\n..*\n*..\n").unwrap();
+        for (x, y) in [(1, 0), (2, 0), (0, 1), (1, 1), (1, 2), (2, 2)] {
+            board.reveal_cell(x, y);
+        }
+        assert!(board.is_won());
+        board
+    }
+
+    #[test]
+    fn the_glow_crosses_the_board_once() {
+        assert!(sweep(0.0, 0.0) < 1e-6, "starts off the left edge");
+        assert!(sweep(0.5, SWEEP_SECS / 2.0) > 0.999, "brightest mid-way");
+        assert_eq!(sweep(1.0, SWEEP_SECS), 0.0, "gone at the end");
+        // a column lights up as the band reaches it and dims after
+        let column: Vec<f32> = (0..12).map(|i| sweep(0.25, i as f32 * 0.1)).collect();
+        let peak = column.iter().cloned().fold(0.0, f32::max);
+        let at = column.iter().position(|&g| g == peak).unwrap();
+        assert!(peak > 0.9);
+        assert!(column[..at].windows(2).all(|w| w[0] <= w[1]));
+        assert!(column[at..].windows(2).all(|w| w[0] >= w[1]));
+        // the left edge lights before the right
+        let lit = |across| (0..120).position(|i| sweep(across, i as f32 * 0.01) > 0.5);
+        assert!(lit(0.1) < lit(0.9));
+    }
+
+    #[test]
+    fn a_bounce_goes_up_and_lands_in_its_own_span() {
+        assert_eq!(bounce(1.0, 0.9), 0.0);
+        assert_eq!(bounce(1.0, 1.0), 0.0);
+        assert!((bounce(1.0, 1.0 + BOUNCE_SECS / 2.0) - 1.0).abs() < 1e-6);
+        assert!(bounce(1.0, 1.0 + BOUNCE_SECS) < 1e-5);
+
+        assert_eq!(stagger(2), STAGGER_SECS);
+        // a crowded board still finishes its wave in time
+        assert!(stagger(1000) * 999.0 <= WAVE_SECS + 1e-4);
+        assert_eq!(stagger(0), STAGGER_SECS);
+    }
+
+    #[test]
+    fn the_flags_bounce_column_by_column_after_the_finale() {
+        let mut animations = Animations::default();
+        FlagWave.trigger(&won(), &mut animations);
+        assert!(animations.tick(10.0, true));
+        let order: Vec<_> = animations.wave.iter().map(|&(cell, _)| cell).collect();
+        assert_eq!(order, [(0, 0), (0, 2), (2, 1)]);
+
+        let at = |secs: f32| 10.0 + (WIN_FINALE_SECS + secs) as f64;
+        animations.tick(at(-0.5), true);
+        assert_eq!(animations.lift(0, 0), 0.0, "waits for the finale");
+        animations.tick(at(0.02), true);
+        assert!(animations.lift(0, 0) > 0.0);
+        assert_eq!(animations.lift(0, 2), 0.0, "its turn comes next");
+        animations.tick(at(STAGGER_SECS + BOUNCE_SECS / 2.0), true);
+        assert!(animations.lift(0, 2) > 0.9);
+        assert_eq!(animations.lift(1, 1), 0.0, "not a flag");
+
+        animations.skip();
+        assert_eq!(animations.lift(0, 2), 0.0);
+        assert!(!animations.tick(at(0.1), true));
+    }
+
+    #[test]
+    fn a_sweep_runs_until_it_is_over_and_confetti_is_handed_on_once() {
+        let mut animations = Animations::default();
+        assert!(!animations.tick(0.0, true), "nothing to show");
+        GlowSweep.trigger(&won(), &mut animations);
+        assert!(animations.tick(5.0, true));
+        animations.tick(5.0 + SWEEP_SECS as f64 / 2.0, true);
+        assert!(animations.glow(1, 3) > 0.9);
+        assert!(!animations.tick(5.0 + SWEEP_SECS as f64, true));
+        // switched off mid-way, it is over at once
+        GlowSweep.trigger(&won(), &mut animations);
+        assert!(!animations.tick(7.0, false));
+        assert_eq!(animations.glow(1, 3), 0.0);
+
+        Confetti.trigger(&won(), &mut animations);
+        assert!(animations.take_confetti());
+        assert!(!animations.take_confetti());
+        NoEffect.trigger(&won(), &mut animations);
+        assert!(!animations.take_confetti());
+    }
+}
@@ -0,0 +1,54 @@
+//! The board analysis against fixture boards with known grades, so the
+//! grading heuristic can't drift unnoticed.
+
+use minesweeper_rs::analysis::{AnalysisReport, Grade};
+use minesweeper_rs::Board;
+
+fn report(layout: &str) -> AnalysisReport {
+    AnalysisReport::new(&Board::decode_layout(layout).unwrap())
+}
+
+#[test]
+fn intermediate_seed_grades_medium() {
+    let report = report(include_str!("fixtures/deadbeef_16x16_40.txt"));
+    assert_eq!(
+        report.to_string(),
+        include_str!("fixtures/analysis_deadbeef_16x16_40.txt")
+    );
+    assert_eq!(
+        report.to_json(),
+        r#"{"width":16,"height":16,"mines":40,"density":0.15625,"three_bv":66,"openings":8,"#
+            .to_owned()
+            + r#""largest_group":27,"no_guess":true,"start":[2,0],"grade":"medium"}"#
+    );
+}
+
+#[test]
+fn tangled_board_grades_hard_though_it_needs_no_guess() {
+    let report = report(include_str!("fixtures/analysis_tangled_12x12_22.txt"));
+    assert_eq!((report.three_bv, report.openings), (52, 6));
+    assert_eq!(report.largest_group, 31);
+    assert!(report.no_guess);
+    assert_eq!(report.start, Some((0, 3))); // the first opening tried can't finish it
+    assert_eq!(report.grade, Grade::Hard);
+}
+
+#[test]
+fn dense_expert_seed_needs_a_guess() {
+    let report = report(include_str!("fixtures/deadbeef_30x16_99_safe_4_7.txt"));
+    assert_eq!((report.three_bv, report.openings), (181, 15));
+    assert_eq!(report.largest_group, 16);
+    assert!(!report.no_guess);
+    assert_eq!(report.start, Some((26, 0)));
+    assert_eq!(report.grade, Grade::Fiendish);
+}
+
+#[test]
+fn games_in_progress_are_judged_as_they_stand() {
+    let mut board = Board::decode_layout("*..\n...\n..*\n").unwrap();
+    board.reveal_cell(2, 0);
+    let report = AnalysisReport::new(&board);
+    assert_eq!(report.start, None);
+    assert_eq!((report.largest_group, report.no_guess), (5, false));
+    assert_eq!(report.grade, Grade::Hard);
+}
@@ -0,0 +1,137 @@
+//! Notes on cells: toggling, clearing with undo, and keeping them in the
+//! autosave slot.
+
+use minesweeper_rs::annotations::{Annotations, Hue, Note, PALETTE};
+use minesweeper_rs::autosave::Autosave;
+use minesweeper_rs::Board;
+
+mod common;
+
+use common::slot;
+
+#[test]
+fn a_note_replaces_another_and_toggles_itself_off() {
+    let mut notes = Annotations::default();
+    assert!(notes.toggle(2, 1, Note::Letter('A')));
+    assert!(notes.toggle(2, 1, Note::Dot(Hue::Lime)));
+    assert_eq!(notes.get(2, 1), Some(Note::Dot(Hue::Lime)));
+    assert_eq!(notes.len(), 1);
+
+    assert!(!notes.toggle(2, 1, Note::Dot(Hue::Lime)));
+    assert!(notes.is_empty());
+
+    notes.toggle(3, 0, Note::Corner(Hue::Pink));
+    notes.toggle(0, 1, Note::Letter('B'));
+    notes.erase(3, 0);
+    notes.erase(5, 5);
+    let left: Vec<_> = notes.iter().collect();
+    assert_eq!(left, [((0, 1), Note::Letter('B'))]);
+
+    // nothing outside ASCII can be written down
+    assert!(!notes.toggle(0, 0, Note::Letter('é')));
+    assert_eq!(notes.get(0, 0), None);
+}
+
+#[test]
+fn clearing_is_one_step_and_can_be_undone_until_the_next_change() {
+    let mut notes = Annotations::default();
+    for (i, &note) in PALETTE.iter().enumerate() {
+        notes.toggle(i, 0, note);
+    }
+    let before = notes.clone();
+
+    notes.clear();
+    assert!(notes.is_empty());
+    assert!(notes.undo_clear());
+    assert_eq!(
+        notes.iter().collect::<Vec<_>>(),
+        before.iter().collect::<Vec<_>>()
+    );
+    assert!(!notes.undo_clear());
+
+    notes.clear();
+    notes.toggle(0, 0, Note::Letter('C'));
+    assert!(!notes.can_undo_clear());
+    assert_eq!(notes.len(), 1);
+}
+
+#[test]
+fn every_change_moves_the_revision() {
+    let mut notes = Annotations::default();
+    let mut last = notes.revision();
+    let mut changed = |notes: &Annotations| {
+        let moved = notes.revision() != last;
+        last = notes.revision();
+        moved
+    };
+    notes.toggle(0, 0, Note::Letter('A'));
+    assert!(changed(&notes));
+    notes.erase(1, 1);
+    assert!(!changed(&notes));
+    notes.clear();
+    assert!(changed(&notes));
+    notes.undo_clear();
+    assert!(changed(&notes));
+}
+
+#[test]
+fn notes_are_kept_in_the_autosave_slot() {
+    let mut board = Board::with_seed(9, 9, 10, 21, Some((4, 4)));
+    board.reveal_cell(4, 4);
+    let mut notes = Annotations::default();
+    notes.toggle(0, 0, Note::Letter('A'));
+    notes.toggle(8, 0, Note::Letter('B'));
+    notes.toggle(8, 8, Note::Corner(Hue::Cyan));
+
+    let path = slot("notes.bin");
+    Autosave::new(&path)
+        .save_annotated(&board, 7.0, &notes)
+        .unwrap();
+    let session = Autosave::new(&path).load().expect("just saved");
+    assert_eq!(session.board.encode_state(), board.encode_state());
+    assert_eq!(
+        session.annotations.iter().collect::<Vec<_>>(),
+        notes.iter().collect::<Vec<_>>()
+    );
+
+    // a plain save has none
+    Autosave::new(&path).save(&board, 7.0).unwrap();
+    let session = Autosave::new(&path).load().unwrap();
+    assert!(session.annotations.is_empty());
+    Autosave::new(&path).clear().unwrap();
+}
+
+#[test]
+fn slots_from_before_notes_still_load() {
+    let board = Board::with_seed(9, 9, 10, 3, None);
+    let mut old = b"MSS1".to_vec();
+    old.extend_from_slice(&12.0f64.to_le_bytes());
+    old.extend_from_slice(&board.encode_state());
+
+    let path = slot("old.bin");
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(&path, old).unwrap();
+    let session = Autosave::new(&path).load().expect("old slot");
+    assert_eq!(session.elapsed_secs, 12.0);
+    assert!(session.annotations.is_empty());
+    Autosave::new(&path).clear().unwrap();
+}
+
+#[test]
+fn bad_notes_are_refused_and_stray_ones_dropped() {
+    let mut notes = Annotations::default();
+    notes.toggle(1, 1, Note::Dot(Hue::Pink));
+    notes.toggle(20, 1, Note::Letter('A'));
+    let bytes = notes.encode();
+
+    let small = Annotations::decode(&bytes, 9, 9).unwrap();
+    assert_eq!(
+        small.iter().collect::<Vec<_>>(),
+        [((1, 1), Note::Dot(Hue::Pink))]
+    );
+    assert!(Annotations::decode(&bytes[..bytes.len() - 1], 30, 30).is_none());
+
+    let mut bad_hue = bytes.clone();
+    bad_hue[4 + 9] = 7;
+    assert!(Annotations::decode(&bad_hue, 30, 30).is_none());
+}
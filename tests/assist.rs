@@ -0,0 +1,63 @@
+//! The "open on number" assist: when it opens cells and when it holds back.
+
+use minesweeper_rs::{assist, Board, RevealResult};
+
+/// A 1 at B2 with the mine at A1 among its five hidden neighbours.
+fn lone_one() -> Board {
+    let mut board = Board::decode_layout("*....\n.....\n").unwrap();
+    board.reveal_cell(1, 1);
+    board
+}
+
+/// 1s at A2 and B2: together they prove C1 and C2 safe.
+fn two_ones() -> Board {
+    let mut board = Board::decode_layout("*..*\n....\n").unwrap();
+    board.reveal_cell(0, 1);
+    board.reveal_cell(1, 1);
+    board
+}
+
+#[test]
+fn a_completing_flag_opens_the_rest_of_the_number() {
+    let mut board = lone_one();
+    board.toggle_flag(0, 0);
+    let open = assist::open_on_number(&board, 0, 0);
+    assert_eq!(open, [(1, 0), (2, 0), (0, 1), (2, 1)]);
+    for (x, y) in open {
+        assert_ne!(board.reveal_cell(x, y), RevealResult::Exploded);
+    }
+    assert!(board.is_won());
+}
+
+#[test]
+fn a_flag_completing_two_numbers_opens_nothing() {
+    let mut board = two_ones();
+    board.toggle_flag(0, 0);
+    assert!(assist::open_on_number(&board, 0, 0).is_empty());
+}
+
+#[test]
+fn a_provably_wrong_flag_opens_nothing() {
+    let mut board = two_ones();
+    board.toggle_flag(2, 1);
+    assert!(assist::open_on_number(&board, 2, 1).is_empty());
+}
+
+#[test]
+fn an_over_flagged_number_opens_nothing() {
+    let mut board = lone_one();
+    board.toggle_flag(0, 0);
+    board.toggle_flag(1, 0);
+    assert!(assist::open_on_number(&board, 1, 0).is_empty());
+}
+
+#[test]
+fn a_wrong_but_unprovable_flag_still_detonates() {
+    // nothing visible tells B1 from A1, so the assist trusts the flag and
+    // opens the mine, exactly as opening those cells by hand would
+    let mut board = lone_one();
+    board.toggle_flag(1, 0);
+    let open = assist::open_on_number(&board, 1, 0);
+    assert_eq!(open, [(0, 0), (2, 0), (0, 1), (2, 1)]);
+    assert_eq!(board.reveal_cell(0, 0), RevealResult::Exploded);
+}
@@ -0,0 +1,74 @@
+//! Autosave recovery, with a "crash" simulated by never clearing the slot.
+
+use minesweeper_rs::autosave::Autosave;
+use minesweeper_rs::{Board, DecodeError};
+
+mod common;
+
+use common::slot;
+
+#[test]
+fn crashed_session_is_recovered_exactly() {
+    let mut board = Board::with_seed(16, 16, 40, 0xDEAD_BEEF, Some((3, 3)));
+    board.reveal_cell(3, 3);
+    board.toggle_flag(15, 15);
+
+    let path = slot("crash.bin");
+    Autosave::new(&path).save(&board, 42.5).unwrap();
+    // process "dies" here: the clean-exit clear() never runs
+
+    let session = Autosave::new(&path).load().expect("autosave left behind");
+    assert_eq!(session.board.encode_state(), board.encode_state());
+    assert_eq!(session.board.revealed_safe(), board.revealed_safe());
+    assert_eq!(session.board.seed(), Some(0xDEAD_BEEF));
+    assert_eq!(session.elapsed_secs, 42.5);
+
+    Autosave::new(&path).clear().unwrap();
+    assert!(Autosave::new(&path).load().is_none());
+}
+
+#[test]
+fn corrupt_snapshots_are_rejected() {
+    let board = Board::with_seed(9, 9, 10, 1, None);
+    let bytes = board.encode_state();
+
+    assert_eq!(
+        Board::decode_state(b"nope").err(),
+        Some(DecodeError::BadMagic)
+    );
+    assert_eq!(
+        Board::decode_state(&bytes[..bytes.len() - 1]).err(),
+        Some(DecodeError::Truncated)
+    );
+
+    let mut huge = bytes.clone();
+    huge[4..8].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert_eq!(
+        Board::decode_state(&huge).err(),
+        Some(DecodeError::TooLarge)
+    );
+
+    let mut wrong_mines = bytes.clone();
+    wrong_mines[12] = 11;
+    assert_eq!(
+        Board::decode_state(&wrong_mines).err(),
+        Some(DecodeError::MineCountMismatch)
+    );
+}
+
+#[test]
+fn a_board_awaiting_its_first_click_round_trips() {
+    let mut board = Board::blank(9, 9, 10);
+    board.toggle_flag(4, 4);
+    let restored = Board::decode_state(&board.encode_state()).unwrap();
+    assert_eq!(restored.encode_state(), board.encode_state());
+    assert_eq!(restored.mine_count(), 10);
+
+    // a blank board with a cell open is no board at all
+    let mut opened = Board::blank(9, 9, 10).encode_state();
+    *opened.last_mut().unwrap() = 2;
+    assert_eq!(
+        Board::decode_state(&opened).err(),
+        Some(DecodeError::MineCountMismatch)
+    );
+}
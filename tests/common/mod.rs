@@ -0,0 +1,29 @@
+//! Helpers shared by the integration tests. Each test crate builds its own
+//! copy and uses only some of them.
+
+#![allow(dead_code)]
+
+use std::path::PathBuf;
+
+/// xorshift, so the moves depend only on the seed.
+pub fn next(state: &mut u64) -> u64 {
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    *state
+}
+
+/// A path called `name` in this run's temporary directory, left for the
+/// test to create.
+pub fn slot(name: &str) -> PathBuf {
+    std::env::temp_dir()
+        .join(format!("minesweeper-tests-{}", std::process::id()))
+        .join(name)
+}
+
+/// An existing directory called `name` in this run's temporary directory.
+pub fn scratch(name: &str) -> PathBuf {
+    let dir = slot(name);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
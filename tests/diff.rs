@@ -0,0 +1,124 @@
+//! Board diffs: the cells a move changes are the cells the board marks
+//! dirty, and its revision only ever goes up, over random play and undo on
+//! seeded boards; diffs between unrelated boards say what differs and
+//! refuse boards of another size.
+
+use minesweeper_rs::{Board, CycleMode, Marking, SizeMismatch};
+
+mod common;
+
+use common::next;
+
+/// One random move: mostly a reveal, sometimes a flag, a question mark,
+/// a chord, clearing the flags or opening everything left.
+fn play(board: &mut Board, rng: &mut u64) {
+    let pick = next(rng) as usize;
+    let (x, y) = (
+        pick / 16 % board.width(),
+        pick / 16 / board.width() % board.height(),
+    );
+    match pick % 16 {
+        0..=3 => {
+            board.toggle_flag(x, y);
+        }
+        4 | 5 => {
+            board.cycle_marking(x, y, CycleMode::FlagsAndQuestions);
+        }
+        6 => {
+            for (tx, ty) in board.chord_targets(x, y) {
+                board.reveal_cell(tx, ty);
+            }
+        }
+        7 => {
+            board.clear_flags();
+        }
+        8 if pick % 64 == 8 => {
+            board.reveal_remaining();
+        }
+        _ => {
+            board.reveal_cell(x, y);
+        }
+    }
+}
+
+#[test]
+fn a_move_changes_exactly_the_cells_it_marks_dirty() {
+    for seed in 0..40 {
+        let mut board = Board::with_seed(16, 16, 40, seed, Some((8, 8)));
+        board.reveal_cell(8, 8);
+        board.take_dirty();
+        let mut rng = seed + 1;
+        let mut snapshot = board.snapshot();
+        for step in 0..200 {
+            if board.is_over() {
+                break;
+            }
+            let before = board.clone();
+            // now and then an undo, to a few moves back
+            if step % 25 == 24 {
+                board.restore(&snapshot).unwrap();
+            } else {
+                play(&mut board, &mut rng);
+            }
+            let diff = before.diff(&board).unwrap();
+            assert_eq!(diff.positions(), board.take_dirty(), "seed {seed}");
+            assert!(!diff.layouts_differ());
+            // the revision never goes back, an undo included, and moves on
+            // with every change
+            assert!(board.revision() >= before.revision(), "seed {seed}");
+            assert!(diff.is_empty() || board.revision() > before.revision());
+            if step % 10 == 0 {
+                snapshot = board.snapshot();
+            }
+        }
+        let before = board.clone();
+        board.reveal_all();
+        assert_eq!(before.diff(&board).unwrap().positions(), board.take_dirty());
+    }
+}
+
+#[test]
+fn an_undone_move_diffs_back_to_nothing() {
+    let mut board = Board::with_seed(9, 9, 10, 7, Some((4, 4)));
+    let start = board.clone();
+    let snapshot = board.snapshot();
+    board.reveal_cell(4, 4);
+    board.toggle_flag(0, 0);
+    assert!(!start.diff(&board).unwrap().is_empty());
+    board.restore(&snapshot).unwrap();
+    assert!(start.diff(&board).unwrap().is_empty());
+}
+
+#[test]
+fn each_cell_says_what_changed() {
+    let ours = Board::decode_layout("*..\n...\n").unwrap();
+    let mut theirs = Board::decode_layout("..*\n...\n").unwrap();
+    theirs.cycle_marking(0, 1, CycleMode::FlagsAndQuestions);
+    theirs.cycle_marking(0, 1, CycleMode::FlagsAndQuestions);
+    theirs.reveal_cell(1, 1);
+
+    let diff = ours.diff(&theirs).unwrap();
+    assert_eq!(diff.positions(), [(0, 0), (2, 0), (0, 1), (1, 1)]);
+    let cells = diff.cells();
+    assert_eq!((cells[0].revealed, cells[0].mine), (None, Some(false)));
+    assert_eq!(cells[1].mine, Some(true));
+    assert_eq!(cells[2].marking, Some((Marking::None, Marking::Question)));
+    assert_eq!((cells[3].revealed, cells[3].mine), (Some(true), None));
+    assert!(diff.layouts_differ());
+    assert_eq!(diff.to_string(), "-.+\n?1.\n");
+}
+
+#[test]
+fn boards_of_another_size_are_refused() {
+    let small = Board::with_seed(9, 9, 10, 1, None);
+    let tall = Board::with_seed(9, 16, 10, 1, None);
+    let err = small.diff(&tall).unwrap_err();
+    assert_eq!(
+        err,
+        SizeMismatch {
+            ours: (9, 9),
+            theirs: (9, 16)
+        }
+    );
+    assert_eq!(err.to_string(), "can't compare a 9x9 board with a 9x16 one");
+}
@@ -0,0 +1,58 @@
+//! Best play through small endgames whose every placement can be listed
+//! by hand.
+
+use minesweeper_rs::endgame::{self, Outlook};
+use minesweeper_rs::Board;
+
+fn outlook(layout: &str, reveal: &[(usize, usize)]) -> Option<Outlook> {
+    let mut board = Board::decode_layout(layout).unwrap();
+    for &(x, y) in reveal {
+        board.reveal_cell(x, y);
+    }
+    endgame::outlook(&board, 16)
+}
+
+#[test]
+fn a_position_deduction_clears_needs_no_guess() {
+    // the middle 1 of the three sees the mine on its own
+    let outlook = outlook(".*.\n...\n", &[(0, 1), (1, 1), (2, 1)]).unwrap();
+    assert_eq!(outlook.cells, 3);
+    assert_eq!((outlook.win_chance, outlook.guesses), (1.0, 0));
+    assert_eq!(outlook.to_string(), "No guesses ahead");
+}
+
+#[test]
+fn a_pair_behind_two_ones_is_a_coin_flip() {
+    let outlook = outlook("*.\n..\n..\n", &[(0, 2)]).unwrap();
+    assert_eq!((outlook.win_chance, outlook.guesses), (0.5, 1));
+}
+
+#[test]
+fn two_separate_pairs_are_two_coin_flips() {
+    let outlook = outlook("*.\n..\n..\n..\n..\n.*\n", &[(0, 2)]).unwrap();
+    assert_eq!(outlook.cells, 4);
+    assert_eq!((outlook.win_chance, outlook.guesses), (0.25, 2));
+    assert_eq!(outlook.to_string(), "2 coin flips ahead, 25% best case");
+}
+
+#[test]
+fn best_play_picks_the_guess_that_tells_the_most() {
+    // one mine among the five cells around the lone 1: a corner guess
+    // that survives splits the rest, and half the time gives it away,
+    // for 4/5 × (1/2 + 1/2 × 1/2) = 3/5
+    let outlook = outlook(".*.\n...\n", &[(1, 1)]).unwrap();
+    assert_eq!(outlook.cells, 5);
+    assert!((outlook.win_chance - 0.6).abs() < 1e-9, "{outlook:?}");
+    assert_eq!(outlook.guesses, 2);
+}
+
+#[test]
+fn large_finished_or_undealt_boards_are_not_searched() {
+    let mut board = Board::decode_layout("*.\n..\n..\n").unwrap();
+    assert_eq!(endgame::outlook(&board, 16), None, "nothing revealed yet");
+    board.reveal_cell(0, 2);
+    assert_eq!(endgame::outlook(&board, 1), None, "over the limit");
+    assert!(endgame::outlook(&board, 2).is_some());
+    board.reveal_cell(0, 0);
+    assert_eq!(endgame::outlook(&board, 16), None, "lost");
+}
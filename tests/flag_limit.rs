@@ -0,0 +1,63 @@
+//! Flag-limit mode: no more flags than `max_flags`.
+
+use minesweeper_rs::{Board, FlagResult, Ignored};
+
+fn limited(mines: usize) -> Board {
+    let mut board = Board::with_seed(9, 9, mines, 11, None);
+    board.set_max_flags(Some(mines));
+    board
+}
+
+#[test]
+fn last_flag_fits_and_the_next_is_refused() {
+    let mut board = limited(3);
+    for x in 0..2 {
+        assert_eq!(board.toggle_flag(x, 0), FlagResult::Flagged);
+    }
+    assert_eq!(board.flags_left(), Some(1));
+
+    assert_eq!(board.toggle_flag(2, 0), FlagResult::Flagged);
+    assert_eq!(board.flags_left(), Some(0));
+    let revision = board.revision();
+    assert_eq!(
+        board.toggle_flag(3, 0),
+        FlagResult::Ignored(Ignored::NoFlagsLeft)
+    );
+    assert!(!board.cell(3, 0).is_flagged());
+    assert_eq!(board.revision(), revision);
+}
+
+#[test]
+fn removing_a_flag_frees_one_up() {
+    let mut board = limited(2);
+    board.toggle_flag(0, 0);
+    board.toggle_flag(1, 0);
+
+    assert_eq!(board.toggle_flag(0, 0), FlagResult::Unflagged);
+    assert_eq!(board.flags(), 1);
+    assert_eq!(board.toggle_flag(5, 5), FlagResult::Flagged);
+    assert_eq!(
+        board.toggle_flag(0, 0),
+        FlagResult::Ignored(Ignored::NoFlagsLeft)
+    );
+}
+
+#[test]
+fn unlimited_by_default() {
+    let mut board = Board::with_seed(9, 9, 1, 11, None);
+    assert_eq!(board.flags_left(), None);
+    for x in 0..9 {
+        assert_eq!(board.toggle_flag(x, 0), FlagResult::Flagged);
+    }
+    assert_eq!(board.flags(), 9);
+}
+
+#[test]
+fn limit_and_flags_survive_a_snapshot() {
+    let mut board = limited(4);
+    board.toggle_flag(8, 8);
+
+    let restored = Board::decode_state(&board.encode_state()).unwrap();
+    assert_eq!(restored.max_flags(), Some(4));
+    assert_eq!(restored.flags(), 1);
+}
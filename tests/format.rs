@@ -0,0 +1,54 @@
+//! How times, dates and counts read: the clock across the hour, zero and
+//! what can't be a time, tenths and milliseconds, weekdays, and grouping.
+
+use minesweeper_rs::format;
+
+#[test]
+fn the_clock_shows_minutes_then_hours() {
+    assert_eq!(format::clock(0.0), "0:00");
+    assert_eq!(format::clock(9.99), "0:09");
+    assert_eq!(format::clock(61.0), "1:01");
+    assert_eq!(format::clock(3599.9), "59:59");
+    assert_eq!(format::clock(3600.0), "1:00:00");
+    assert_eq!(format::clock(36_061.0), "10:01:01");
+}
+
+#[test]
+fn the_clock_never_runs_backwards() {
+    assert_eq!(format::clock(-1.0), "0:00");
+    assert_eq!(format::clock(f64::NAN), "0:00");
+    assert_eq!(format::clock(f64::INFINITY), "0:00");
+}
+
+#[test]
+fn counts_are_grouped_in_thousands() {
+    assert_eq!(format::count(0), "0");
+    assert_eq!(format::count(999), "999");
+    assert_eq!(format::count(1_000), "1,000");
+    assert_eq!(format::count(123_456), "123,456");
+    assert_eq!(format::count(1_234_567), "1,234,567");
+    assert_eq!(format::count(u64::MAX), "18,446,744,073,709,551,615");
+}
+
+#[test]
+fn tenths_round_and_millis_keep_every_digit() {
+    assert_eq!(format::clock_tenths(0.0), "0:00.0");
+    assert_eq!(format::clock_tenths(12.34), "0:12.3");
+    assert_eq!(format::clock_tenths(12.36), "0:12.4");
+    assert_eq!(format::clock_tenths(59.96), "1:00.0");
+    assert_eq!(format::clock_tenths(3605.0), "1:00:05.0");
+    assert_eq!(format::clock_tenths(-3.0), "0:00.0");
+    assert_eq!(format::clock_millis(0), "0:00.000");
+    assert_eq!(format::clock_millis(8_250), "0:08.250");
+    assert_eq!(format::clock_millis(3_723_004), "1:02:03.004");
+}
+
+#[test]
+fn dates_name_the_weekday_and_month() {
+    assert_eq!(format::date(2025, 2, 3), "Mon 3 Feb 2025");
+    assert_eq!(format::long_date(2024, 2, 29), "Thursday 29 February 2024");
+    assert_eq!(format::date(2000, 1, 1), "Sat 1 Jan 2000");
+    assert_eq!(format::date(2026, 12, 31), "Thu 31 Dec 2026");
+    assert_eq!(format::month_short(9), "Sep");
+    assert_eq!(format::month_name(13), "December");
+}
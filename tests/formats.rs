@@ -0,0 +1,115 @@
+//! Boards in other formats: Tatham "Mines" game IDs read and written bit
+//! for bit as the original does, and pasted text sorted into the right
+//! format.
+
+use minesweeper_rs::formats::{self, tatham, ReadError, TathamError};
+use minesweeper_rs::puzzle::Puzzle;
+use minesweeper_rs::report::position_code;
+use minesweeper_rs::Board;
+
+/// IDs as the original's `describe_layout` writes them, with the layouts
+/// they stand for.
+const IDS: [(&str, &str); 4] = [
+    (
+        "9x9:4,4,md6e795bf28b5f9d4b9750",
+        "...*..*..\n.....*...\n..*..*...\n.......**\n.........\n.........\n\
+         *........\n.....*...\n....*....\n",
+    ),
+    ("5x3:0,0,mfa2a", ".....\n..*..\n**...\n"),
+    ("7x1:6,0,m3e", "*...*..\n"),
+    (
+        "30x16:15,8,m9171facef4193f9a4340a00066ad980139a2cb5af05d53515e1305f2b15d89f697f\
+         149d11774f916ec577448804f30f0d1b8fb88be0f2e6e21e10481",
+        "***.*.*..*.*...........*.....*\n...*..*..................*....\n\
+         *...*..*...*..*......*..**....\n*.***.....**..*..*..*.........\n\
+         .....*.**..*...*.......*....*.\n..*.*.*.*.**.......*...*..*...\n\
+         .*.**..*....*...*..*......*.*.\n..*.*...............*...*....*\n\
+         ....*....*..................*.\n....*.......*.......*.........\n\
+         ..*..*...*....*..*.......*..**\n..........................*...\n\
+         ..*..*...*.*...........*....*.\n..*......*.*...*...........*.*\n\
+         ...*...*....*.....*..*....*...\n..**.*.......*..*.....*..*....\n",
+    ),
+];
+
+#[test]
+fn game_ids_read_and_write_as_the_original_does() {
+    for (id, layout) in IDS {
+        let puzzle = tatham::decode(id).unwrap();
+        assert_eq!(puzzle.board.encode_layout(), layout, "{id}");
+        assert_eq!(tatham::encode(&puzzle), id);
+    }
+    let puzzle = tatham::decode(IDS[0].0).unwrap();
+    assert_eq!(puzzle.start, Some((4, 4)));
+    assert_eq!(puzzle.board.mine_count(), 10);
+    assert!(
+        !puzzle.board.cell(4, 4).is_revealed(),
+        "nothing is opened yet"
+    );
+}
+
+#[test]
+fn the_start_masking_and_generation_settings_are_optional() {
+    let layout = "*..\n...\n";
+    let mut puzzle = Puzzle::new(Board::decode_layout(layout).unwrap());
+    assert_eq!(tatham::encode(&puzzle), "3x2:m34");
+    puzzle.start = Some((2, 1));
+    assert_eq!(tatham::encode(&puzzle), "3x2:2,1,m34");
+
+    for id in ["3x2:m34", "3x2:u80", "3x2n1a:m34", " 3x2n1:2,1,m34\n"] {
+        let read = tatham::decode(id).unwrap();
+        assert_eq!(read.board.encode_layout(), layout, "{id}");
+    }
+    // a single number is a square, and a plain bitmap needs no `u` if it
+    // doesn't start with a digit, which would read as the start
+    let read = tatham::decode("2:a").unwrap();
+    assert_eq!(read.board.encode_layout(), "*.\n*.\n");
+}
+
+#[test]
+fn seeds_and_bad_ids_are_refused() {
+    let refused = [
+        ("9x9n10#527419", TathamError::Seed),
+        ("9x9:r10,u,7e41bf0c", TathamError::Seed),
+        ("9x9n10a:r10,a,7e41bf0c", TathamError::Seed),
+        ("3x2", TathamError::NoDescription),
+        ("x2:m34", TathamError::Size),
+        ("0x2:m", TathamError::Size),
+        ("5000x5000:m", TathamError::TooLarge),
+        ("3x2:3,1,m34", TathamError::Start),
+        ("3x2:2,1m34", TathamError::Start),
+        ("3x2:0,0,u80", TathamError::Start), // the start is the mine
+        ("3x2:m345", TathamError::Length(2, 3)),
+        ("3x2:mzz", TathamError::NotHex),
+    ];
+    for (id, err) in refused {
+        assert_eq!(tatham::decode(id).err(), Some(err), "{id}");
+    }
+}
+
+#[test]
+fn pasted_text_is_read_as_whichever_format_it_is() {
+    let layout = "*..\n...\n";
+    let mut board = Board::decode_layout(layout).unwrap();
+    board.reveal_cell(2, 1);
+    let code = position_code(&board);
+    for text in [
+        "3x2:2,1,m34",
+        layout,
+        &code,
+        "+++\nstart = [2, 1]\n+++\n*..\n...\n",
+    ] {
+        let puzzle = formats::read(text).unwrap();
+        assert_eq!(puzzle.board.encode_layout(), layout, "{text}");
+        assert!(!puzzle.board.cell(2, 1).is_revealed(), "{text}");
+    }
+
+    assert!(matches!(
+        formats::read("3x2:mzz"),
+        Err(ReadError::Tatham(_))
+    ));
+    assert!(matches!(formats::read("0a1"), Err(ReadError::Code(_))));
+    assert!(matches!(
+        formats::read("*..\n.\n"),
+        Err(ReadError::Puzzle(_))
+    ));
+}
@@ -0,0 +1,100 @@
+//! Golden boards. Seeded layouts are promised to be stable across releases
+//! and platforms; if one of these fails, generation changed and every shared
+//! seed now gives a different board.
+
+use minesweeper_rs::{Board, BoardError, BoardOptions};
+
+#[test]
+fn deadbeef_16x16_40() {
+    let board = Board::with_seed(16, 16, 40, 0xDEAD_BEEF, None);
+    assert_eq!(
+        board.encode_layout(),
+        include_str!("fixtures/deadbeef_16x16_40.txt")
+    );
+}
+
+#[test]
+fn deadbeef_30x16_99_with_safe_start() {
+    let board = Board::with_seed(30, 16, 99, 0xDEAD_BEEF, Some((4, 7)));
+    assert_eq!(
+        board.encode_layout(),
+        include_str!("fixtures/deadbeef_30x16_99_safe_4_7.txt")
+    );
+}
+
+#[test]
+fn layout_hashes() {
+    let hash = |board: Board| format!("{:016x}", board.layout_hash());
+    assert_eq!(
+        hash(Board::with_seed(16, 16, 40, 0xDEAD_BEEF, None)),
+        "f0a426acdcd6ddc5"
+    );
+    assert_eq!(
+        hash(Board::with_seed(30, 16, 99, 0xDEAD_BEEF, Some((4, 7)))),
+        "73c67666b93bf85e"
+    );
+    assert_eq!(
+        hash(Board::with_seed(16, 16, 40, 1, Some((8, 8)))),
+        "7dd26cfc901329a1"
+    );
+    assert_eq!(hash(Board::blank(3, 2, 0)), "9928a76f5e35e864");
+}
+
+#[test]
+fn every_mine_is_placed() {
+    for mines in [0, 1, 40, 247, 256] {
+        let board = Board::with_seed(16, 16, mines, 7, None);
+        assert_eq!(board.encode_layout().matches('*').count(), mines);
+    }
+}
+
+#[test]
+fn safe_area_never_gets_mines() {
+    // corners, edges, one off an edge, and the middle
+    let starts = [(0, 0), (8, 8), (4, 0), (0, 4), (1, 7), (4, 4)];
+    for radius in 0..=2 {
+        for (sx, sy) in starts {
+            let clear = (0..9usize)
+                .flat_map(|y| (0..9usize).map(move |x| (x, y)))
+                .filter(|&(x, y)| x.abs_diff(sx) <= radius && y.abs_diff(sy) <= radius)
+                .count();
+            // as dense as the area allows
+            let mines = 81 - clear;
+            for seed in 0..200 {
+                let board = BoardOptions::new(9, 9, mines)
+                    .seed(seed)
+                    .safe_start(sx, sy)
+                    .safe_radius(radius)
+                    .build()
+                    .unwrap();
+                let layout = board.encode_layout();
+                for (y, row) in layout.lines().enumerate() {
+                    for (x, c) in row.chars().enumerate() {
+                        let inside = x.abs_diff(sx) <= radius && y.abs_diff(sy) <= radius;
+                        assert!(
+                            !(inside && c == '*'),
+                            "mine at ({x}, {y}), radius {radius} around ({sx}, {sy}), seed {seed}"
+                        );
+                    }
+                }
+                assert_eq!(layout.matches('*').count(), mines);
+            }
+        }
+    }
+}
+
+#[test]
+fn safe_area_counts_against_the_mines() {
+    let build = |mines, radius| {
+        BoardOptions::new(9, 9, mines)
+            .safe_start(0, 0)
+            .safe_radius(radius)
+            .build()
+            .err()
+    };
+    // a corner's 3x3 is clipped to 2x2, its 5x5 to 3x3
+    assert_eq!(build(77, 1), None);
+    assert_eq!(build(78, 1), Some(BoardError::TooManyMines));
+    assert_eq!(build(72, 2), None);
+    assert_eq!(build(73, 2), Some(BoardError::TooManyMines));
+}
@@ -0,0 +1,97 @@
+//! The hint ladder: how it climbs, what each rung says and when it starts
+//! over.
+
+use minesweeper_rs::hints::{Hint, HintLadder, PENALTY_SECS};
+use minesweeper_rs::Board;
+
+/// The 1 at B1 has A1 as its only hidden neighbour, so A1 is a mine;
+/// with it known, A3 and C3 are safe.
+fn filled_one() -> Board {
+    let mut board = Board::decode_layout("*..\n...\n.*.\n").unwrap();
+    board.reveal_cell(1, 0);
+    board.reveal_cell(2, 0);
+    board.reveal_cell(0, 1);
+    board.reveal_cell(1, 1);
+    board.reveal_cell(2, 1);
+    board
+}
+
+#[test]
+fn asking_again_climbs_the_ladder() {
+    let board = filled_one();
+    let mut ladder = HintLadder::default();
+
+    let region = ladder.next(&board).unwrap();
+    assert_eq!(region.level(), 1);
+    assert!(region.cells().contains(&(0, 0)));
+    assert!(
+        region.cells().len() > 1,
+        "the region mustn't give the cell away"
+    );
+
+    let numbers = ladder.next(&board).unwrap();
+    assert_eq!(numbers.level(), 2);
+    assert!(!numbers.cells().contains(&(0, 0)));
+
+    let answer = ladder.next(&board).unwrap();
+    assert_eq!(
+        answer,
+        Hint::Answer {
+            cell: (0, 0),
+            mine: true
+        }
+    );
+    assert_eq!(answer.to_string(), "A1 is a mine.");
+    assert_eq!(ladder.used(), [1, 1, 1]);
+    assert_eq!(ladder.penalty_secs(), PENALTY_SECS.iter().sum::<u32>());
+}
+
+#[test]
+fn any_change_starts_the_ladder_over() {
+    let mut board = filled_one();
+    let mut ladder = HintLadder::default();
+    ladder.next(&board);
+    ladder.next(&board);
+
+    board.toggle_flag(2, 2);
+    assert_eq!(ladder.next(&board).map(|hint| hint.level()), Some(1));
+    assert_eq!(ladder.used(), [2, 1, 0]);
+    assert_eq!(ladder.penalty_secs(), 2 * PENALTY_SECS[0] + PENALTY_SECS[1]);
+}
+
+#[test]
+fn after_the_answer_the_next_hint_is_a_region_again() {
+    let board = filled_one();
+    let mut ladder = HintLadder::default();
+    for _ in 0..3 {
+        ladder.next(&board);
+    }
+    assert_eq!(ladder.next(&board).map(|hint| hint.level()), Some(1));
+}
+
+#[test]
+fn a_flagged_mine_moves_the_hint_on() {
+    let mut board = filled_one();
+    board.toggle_flag(0, 0);
+    let mut ladder = HintLadder::default();
+    ladder.next(&board);
+    ladder.next(&board);
+    // with the mine flagged, the rest is safe
+    assert!(matches!(
+        ladder.next(&board),
+        Some(Hint::Answer { mine: false, .. })
+    ));
+}
+
+#[test]
+fn no_certain_move_means_no_hint_and_no_cost() {
+    let mut board = Board::decode_layout("*.\n.*\n").unwrap();
+    board.reveal_cell(1, 0);
+    let mut ladder = HintLadder::default();
+    assert_eq!(ladder.next(&board), None);
+    assert_eq!(ladder.penalty_secs(), 0);
+
+    let mut lost = filled_one();
+    lost.reveal_cell(0, 0);
+    assert_eq!(ladder.next(&lost), None);
+}
@@ -0,0 +1,230 @@
+//! Inputs a player or a broken disk can hand the game, each of which must
+//! come back as an error rather than a panic: corrupt or cut-short saves,
+//! a data dir that can't be written, boards far too big to build, and the
+//! fuzzers' seed inputs (`fuzz/corpus`) mangled at random.
+
+use minesweeper_rs::analysis::AnalysisReport;
+use minesweeper_rs::autosave::{Autosave, AutosaveError};
+use minesweeper_rs::difficulty::Presets;
+use minesweeper_rs::formats::{self, tatham};
+use minesweeper_rs::render::{BoardRenderer, Style};
+use minesweeper_rs::report::{decode_position_code, CodeError, DebugSnapshot};
+use minesweeper_rs::session::Command;
+use minesweeper_rs::{
+    solver, Board, BoardError, BoardOptions, DecodeError, Difficulty, LayoutError, MAX_CELLS,
+};
+
+mod common;
+
+use common::{next, scratch};
+
+#[test]
+fn corrupt_saves_are_refused_not_recovered() {
+    let dir = scratch("corrupt");
+    let mut board = Board::with_seed(16, 16, 40, 7, Some((8, 8)));
+    board.reveal_cell(8, 8);
+    let slot = Autosave::new(dir.join("autosave.bin"));
+    slot.save(&board, 30.0).unwrap();
+    let good = std::fs::read(slot.path()).unwrap();
+
+    // every cut-short slot, and a bit flipped in every byte
+    let mut refused = 0;
+    for len in 0..good.len() {
+        std::fs::write(slot.path(), &good[..len]).unwrap();
+        match slot.read() {
+            Err(AutosaveError::Corrupt) => refused += 1,
+            other => panic!("{len} bytes: {:?}", other.map(|s| s.is_some())),
+        }
+    }
+    for at in 0..good.len() {
+        let mut flipped = good.clone();
+        flipped[at] ^= 0x40;
+        std::fs::write(slot.path(), &flipped).unwrap();
+        // a flip can land somewhere harmless; it just mustn't panic
+        let _ = slot.read();
+    }
+    assert_eq!(refused, good.len());
+
+    std::fs::write(slot.path(), b"MSS2").unwrap();
+    assert!(slot.load().is_none(), "load treats corrupt as no save");
+}
+
+#[test]
+fn an_unwritable_data_dir_reports_errors() {
+    // a file where the data dir should be: even root can't write under it
+    let dir = scratch("unwritable");
+    let blocker = dir.join("data");
+    std::fs::write(&blocker, b"not a directory").unwrap();
+    let slot = Autosave::new(blocker.join("profile-1").join("autosave.bin"));
+
+    let board = Board::with_seed(9, 9, 10, 1, None);
+    assert!(slot.save(&board, 1.0).is_err());
+    assert!(matches!(slot.read(), Err(AutosaveError::Unreadable(_))));
+    assert!(slot.clear().is_err());
+
+    let mut presets = Presets::default();
+    presets.insert("tiny", Difficulty::Beginner).unwrap();
+    assert!(presets.save(blocker.join("minesweeper.toml")).is_err());
+    assert!(Presets::load(blocker.join("minesweeper.toml")).is_err());
+}
+
+#[test]
+fn giant_boards_are_refused_before_allocating() {
+    let side = u32::MAX.to_le_bytes();
+    let mut state = Board::with_seed(9, 9, 10, 1, None).encode_state();
+    state[4..8].copy_from_slice(&side);
+    state[8..12].copy_from_slice(&side);
+    assert_eq!(
+        Board::decode_state(&state).err(),
+        Some(DecodeError::TooLarge)
+    );
+
+    let row = ".".repeat(MAX_CELLS + 1);
+    assert_eq!(
+        Board::decode_layout(&row).err(),
+        Some(LayoutError::TooLarge)
+    );
+
+    for (width, height) in [(usize::MAX, 2), (MAX_CELLS, 2), (1 << 40, 1 << 40)] {
+        let options = BoardOptions::new(width, height, 10);
+        assert_eq!(options.check(), Err(BoardError::TooLarge));
+        assert_eq!(options.build().err(), Some(BoardError::TooLarge));
+    }
+}
+
+/// A blank 2x2 board (nothing laid, nothing open) claiming 1000 mines,
+/// which once read back and then panicked anything that counted its safe
+/// cells.
+const TOO_MANY_MINES: &str = "4d5342310200000002000000e803000000000000000000000000000000";
+
+#[test]
+fn a_snapshot_with_more_mines_than_cells_is_refused() {
+    assert_eq!(
+        decode_position_code(TOO_MANY_MINES).err(),
+        Some(CodeError::Board(DecodeError::TooManyMines))
+    );
+}
+
+#[test]
+fn a_snapshot_with_no_width_or_height_is_refused() {
+    // 0x5, which once read back and then panicked laying out its rows
+    assert_eq!(
+        decode_position_code("4d534231000000000500000000000000000000000000000000").err(),
+        Some(CodeError::Board(DecodeError::Empty))
+    );
+    let mut state = Board::with_seed(9, 9, 10, 1, None).encode_state();
+    state[8..12].copy_from_slice(&0u32.to_le_bytes());
+    assert_eq!(Board::decode_state(&state).err(), Some(DecodeError::Empty));
+}
+
+#[test]
+fn garbage_commands_are_parse_errors() {
+    let huge = format!("{} 1", u128::MAX);
+    for line in [
+        "",
+        "f",
+        "f -1 2",
+        huge.as_str(),
+        "x y z",
+        "\u{0}\u{fffd}",
+        "h h h",
+    ] {
+        let _ = line.parse::<Command>();
+    }
+    assert!(huge.parse::<Command>().is_err());
+}
+
+/// The fuzzer `target`'s seed inputs, each as it is, then `rounds` more
+/// made from them: cut short, with bits flipped, bytes dropped or added,
+/// or the end of another seed stuck on.
+fn mangled(target: &str, rounds: usize) -> Vec<Vec<u8>> {
+    let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("fuzz/corpus")
+        .join(target);
+    let mut seeds: Vec<Vec<u8>> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| std::fs::read(entry.unwrap().path()).unwrap())
+        .collect();
+    seeds.sort();
+    assert!(!seeds.is_empty(), "no seeds in {}", dir.display());
+
+    let mut rng = 0x9e37_79b9_7f4a_7c15;
+    let mut inputs = seeds.clone();
+    for _ in 0..rounds {
+        let mut bytes = seeds[next(&mut rng) as usize % seeds.len()].clone();
+        for _ in 0..=next(&mut rng) % 4 {
+            let at = next(&mut rng) as usize % (bytes.len() + 1);
+            let byte = next(&mut rng) as u8;
+            match next(&mut rng) % 5 {
+                0 => bytes.truncate(at),
+                1 if at < bytes.len() => bytes[at] ^= 1 << (byte % 8),
+                2 if at < bytes.len() => {
+                    bytes.remove(at);
+                }
+                3 => bytes.insert(at, byte),
+                _ => {
+                    let other = &seeds[usize::from(byte) % seeds.len()];
+                    bytes.extend_from_slice(&other[other.len() / 2..]);
+                }
+            }
+        }
+        inputs.push(bytes);
+    }
+    inputs
+}
+
+/// `mangled`, as text.
+fn mangled_text(target: &str, rounds: usize) -> Vec<String> {
+    mangled(target, rounds)
+        .into_iter()
+        .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+        .collect()
+}
+
+/// Everything the commands do with a board read from a file or code: its
+/// layout, the text renders and the analysis.
+fn used(board: &Board) {
+    board.encode_layout();
+    Style::default().render(board);
+    DebugSnapshot::new(board).show_mines(true).to_string();
+    AnalysisReport::new(board);
+    solver::analyze(board);
+}
+
+/// What the fuzzers in `fuzz/` check, over a fixed sample of what they
+/// would try.
+#[test]
+fn mangled_fuzz_seeds_are_errors_or_read_back() {
+    for bytes in mangled("state", 2000) {
+        if let Ok(board) = Board::decode_state(&bytes) {
+            let saved = board.encode_state();
+            let again = Board::decode_state(&saved).expect("a saved board reads back");
+            assert_eq!(again.encode_state(), saved);
+            used(&board);
+        }
+    }
+    for text in mangled_text("layout", 1000) {
+        if let Ok(board) = Board::decode_layout(&text) {
+            let layout = board.encode_layout();
+            let again = Board::decode_layout(&layout).expect("a written layout reads back");
+            assert_eq!(again.encode_layout(), layout);
+        }
+    }
+    for text in mangled_text("position_code", 2000) {
+        if let Ok(board) = decode_position_code(&text) {
+            used(&board);
+        }
+    }
+    for text in mangled_text("tatham", 1000) {
+        if let Ok(puzzle) = tatham::decode(&text) {
+            let again = tatham::decode(&tatham::encode(&puzzle)).expect("an encoded ID reads back");
+            assert_eq!(again.board.encode_layout(), puzzle.board.encode_layout());
+        }
+    }
+    for text in mangled_text("puzzle", 1000) {
+        let _ = formats::read(&text);
+    }
+    for text in mangled_text("command", 500) {
+        let _ = text.parse::<Command>();
+    }
+}
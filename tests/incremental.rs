@@ -0,0 +1,159 @@
+//! Counted analyses kept up to date between moves: the same answers as
+//! counting from scratch over random play, sound deductions, exact chances
+//! on small boards, and only the groups a move touches counted again.
+
+use minesweeper_rs::odds::{self, Chance};
+use minesweeper_rs::solver::{Analysis, DirtyInfo, Solver};
+use minesweeper_rs::{Board, Difficulty};
+
+mod common;
+
+use common::next;
+
+fn hidden(board: &Board) -> Vec<(usize, usize)> {
+    (0..board.height())
+        .flat_map(|y| (0..board.width()).map(move |x| (x, y)))
+        .filter(|&(x, y)| !board.cell(x, y).is_revealed())
+        .collect()
+}
+
+/// A random move: mostly opening a cell the analysis likes, sometimes a
+/// proven-safe one at random, sometimes a flag.
+fn play(board: &mut Board, analysis: &Analysis, rng: &mut u64) {
+    let hidden = hidden(board);
+    let pick = next(rng) as usize;
+    if pick.is_multiple_of(5) {
+        let (x, y) = hidden[pick / 5 % hidden.len()];
+        board.toggle_flag(x, y);
+        return;
+    }
+    let safe = analysis.safe();
+    let (x, y) = if safe.is_empty() {
+        *hidden
+            .iter()
+            .min_by(|a, b| {
+                let chance = |&(x, y): &(usize, usize)| analysis.probability(x, y);
+                chance(a).total_cmp(&chance(b))
+            })
+            .unwrap()
+    } else {
+        safe[pick / 5 % safe.len()]
+    };
+    board.set_marking(x, y, minesweeper_rs::Marking::None);
+    board.reveal_cell(x, y);
+}
+
+#[test]
+fn incremental_analyses_match_counting_from_scratch() {
+    let mut reused = 0;
+    for seed in 0..6 {
+        let (width, height, mines) = match seed % 3 {
+            0 => Difficulty::Expert.spec().params(),
+            1 => Difficulty::Intermediate.spec().params(),
+            _ => (12, 8, 20),
+        };
+        let start = (width / 2, height / 2);
+        let mut board = Board::with_seed(width, height, mines, seed, Some(start));
+        let mut solver = Solver::new();
+        solver.analyze(&board);
+        board.take_dirty();
+        board.reveal_cell(start.0, start.1);
+        let mut rng = seed + 1;
+        for _ in 0..60 {
+            let dirty = DirtyInfo::cells(board.take_dirty());
+            let incremental = solver.analyze_incremental(&board, &dirty);
+            let full = Solver::new().analyze(&board);
+            assert_eq!(incremental, full, "seed {seed}");
+            reused += solver.stats().reused;
+            if board.is_over() {
+                break;
+            }
+            play(&mut board, &incremental, &mut rng);
+        }
+    }
+    assert!(reused > 0, "some tallies carried over");
+}
+
+#[test]
+fn deductions_hold_on_the_real_layout() {
+    let mut proven = 0;
+    for seed in 0..8 {
+        let mut board = Board::with_seed(16, 16, 40, seed, Some((8, 8)));
+        board.reveal_cell(8, 8);
+        let mines: Vec<bool> = board
+            .encode_layout()
+            .bytes()
+            .filter(|&b| b != b'\n')
+            .map(|b| b == b'*')
+            .collect();
+        let analysis = Solver::new().analyze(&board);
+        for &(x, y) in analysis.mines() {
+            assert!(mines[y * 16 + x], "seed {seed}: ({x}, {y}) is no mine");
+        }
+        for &(x, y) in analysis.safe() {
+            assert!(!mines[y * 16 + x], "seed {seed}: ({x}, {y}) is a mine");
+        }
+        proven += analysis.mines().len() + analysis.safe().len();
+    }
+    assert!(proven > 0);
+}
+
+#[test]
+fn one_group_boards_agree_with_the_odds_count() {
+    // a single group, where `odds::chance` counts exactly the same thing
+    let mut compared = 0;
+    for (layout, opened) in [
+        ("*..\n...\n..*\n", vec![(2, 0)]),
+        ("*..*\n....\n", vec![(1, 1), (2, 1)]),
+        ("*....\n.....\n..*..\n.....\n*...*\n", vec![(2, 0), (4, 2)]),
+    ] {
+        let mut board = Board::decode_layout(layout).unwrap();
+        for (x, y) in opened {
+            board.reveal_cell(x, y);
+        }
+        let analysis = Solver::new().analyze(&board);
+        for (x, y) in hidden(&board) {
+            let Some(Chance::Counted(p)) = odds::chance(&board, x, y) else {
+                continue;
+            };
+            let counted = analysis.probability(x, y);
+            assert!(
+                (counted - p).abs() < 1e-9,
+                "{layout}({x}, {y}): {counted} vs {p}"
+            );
+            compared += 1;
+        }
+    }
+    assert!(compared >= 10, "{compared}");
+}
+
+#[test]
+fn a_move_recounts_only_the_groups_it_touched() {
+    // two frontiers far apart: a flag changes neither group, and a reveal
+    // on the right leaves the left one's tally standing
+    let layout = "*.........*\n...........\n...........\n*.........*\n";
+    let mut board = Board::decode_layout(layout).unwrap();
+    board.reveal_cell(1, 1);
+    board.reveal_cell(9, 1);
+    let mut solver = Solver::new();
+    solver.analyze(&board);
+    board.take_dirty();
+    let first = solver.stats();
+    assert!(first.counted >= 2, "{first:?}");
+
+    board.toggle_flag(10, 0);
+    let dirty = DirtyInfo::cells(board.take_dirty());
+    solver.analyze_incremental(&board, &dirty);
+    assert_eq!(solver.stats().counted, 0, "flags don't change a group");
+    assert_eq!(solver.stats().reused, first.counted);
+
+    board.reveal_cell(9, 2);
+    let dirty = DirtyInfo::cells(board.take_dirty());
+    solver.analyze_incremental(&board, &dirty);
+    let after = solver.stats();
+    assert!(after.counted >= 1 && after.reused >= 1, "{after:?}");
+
+    // telling it everything changed counts every group again
+    solver.analyze_incremental(&board, &DirtyInfo::everything());
+    assert_eq!(solver.stats().reused, 0);
+}
@@ -0,0 +1,131 @@
+//! Flags and question marks: every marking edge case in one table.
+
+use minesweeper_rs::{Board, CycleMode, FlagResult, Ignored, Marking, MarkingResult, RevealResult};
+
+/// Mines at A1 and D3; B1 is the cell every case marks.
+const LAYOUT: &str = "*...\n....\n...*\n";
+const TARGET: (usize, usize) = (1, 0);
+
+#[derive(Clone, Copy, Debug)]
+enum Setup {
+    Covered,
+    Revealed,
+    GameOver,
+    /// One flag allowed, and it is already on D1.
+    NoFlagsLeft,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Action {
+    Set(Marking),
+    Cycle(CycleMode),
+}
+
+fn changed(from: Marking, to: Marking) -> MarkingResult {
+    MarkingResult::Changed { from, to }
+}
+
+#[test]
+fn marking_table() {
+    use Action::{Cycle, Set};
+    use CycleMode::{Flags, FlagsAndQuestions};
+    use Marking::{Flag, None, Question};
+    use Setup::*;
+
+    let ignored = MarkingResult::Ignored;
+    let unchanged = MarkingResult::Unchanged;
+    // setup, marking before, action, result, marking after
+    #[rustfmt::skip]
+    let cases = [
+        // putting down a marking
+        (Covered, None, Set(Flag), changed(None, Flag), Flag),
+        (Covered, None, Set(Question), changed(None, Question), Question),
+        (Covered, Flag, Set(Question), changed(Flag, Question), Question),
+        (Covered, Question, Set(None), changed(Question, None), None),
+        (Covered, Flag, Set(Flag), unchanged, Flag),
+        (Covered, None, Set(None), unchanged, None),
+        // refusals
+        (Revealed, None, Set(Flag), ignored(Ignored::AlreadyRevealed), None),
+        (Revealed, None, Cycle(Flags), ignored(Ignored::AlreadyRevealed), None),
+        (GameOver, Flag, Set(None), ignored(Ignored::GameOver), Flag),
+        (GameOver, None, Cycle(FlagsAndQuestions), ignored(Ignored::GameOver), None),
+        // the flag limit only holds back new flags
+        (NoFlagsLeft, None, Set(Flag), ignored(Ignored::NoFlagsLeft), None),
+        (NoFlagsLeft, Question, Set(Flag), ignored(Ignored::NoFlagsLeft), Question),
+        (NoFlagsLeft, None, Cycle(FlagsAndQuestions), ignored(Ignored::NoFlagsLeft), None),
+        (NoFlagsLeft, None, Set(Question), changed(None, Question), Question),
+        // cycling
+        (Covered, None, Cycle(Flags), changed(None, Flag), Flag),
+        (Covered, Flag, Cycle(Flags), changed(Flag, None), None),
+        (Covered, Question, Cycle(Flags), changed(Question, Flag), Flag),
+        (Covered, None, Cycle(FlagsAndQuestions), changed(None, Flag), Flag),
+        (Covered, Flag, Cycle(FlagsAndQuestions), changed(Flag, Question), Question),
+        (Covered, Question, Cycle(FlagsAndQuestions), changed(Question, None), None),
+    ];
+
+    for (i, &(setup, before, action, expected, after)) in cases.iter().enumerate() {
+        let mut board = Board::decode_layout(LAYOUT).unwrap();
+        let (x, y) = TARGET;
+        board.set_marking(x, y, before);
+        match setup {
+            Covered => {}
+            Revealed => assert_eq!(board.reveal_cell(x, y), RevealResult::Revealed),
+            GameOver => assert_eq!(board.reveal_cell(0, 0), RevealResult::Exploded),
+            NoFlagsLeft => {
+                board.set_max_flags(Some(1));
+                assert!(board.set_marking(3, 0, Flag).flagged());
+            }
+        }
+        let flags = board.flags();
+        let revision = board.revision();
+
+        let result = match action {
+            Set(marking) => board.set_marking(x, y, marking),
+            Cycle(mode) => board.cycle_marking(x, y, mode),
+        };
+        let what = format!("case {i}: {setup:?} {before:?} -> {action:?}");
+        assert_eq!(result, expected, "{what}");
+        assert_eq!(board.cell(x, y).marking(), after, "{what}");
+        assert_eq!(board.cell(x, y).is_flagged(), after == Flag, "{what}");
+
+        let flagged_now = i64::from(after == Flag) - i64::from(before == Flag);
+        assert_eq!(board.flags() as i64, flags as i64 + flagged_now, "{what}");
+        let moved = matches!(result, MarkingResult::Changed { .. });
+        assert_eq!(board.revision() > revision, moved, "{what}");
+    }
+}
+
+#[test]
+fn off_the_board_is_refused() {
+    let mut board = Board::decode_layout(LAYOUT).unwrap();
+    let off = MarkingResult::Ignored(Ignored::OutOfBounds);
+    assert_eq!(board.set_marking(4, 0, Marking::Flag), off);
+    assert_eq!(board.cycle_marking(0, 3, CycleMode::Flags), off);
+    assert_eq!(
+        board.toggle_flag(4, 0),
+        FlagResult::Ignored(Ignored::OutOfBounds)
+    );
+}
+
+#[test]
+fn question_marks_stay_revealable_and_clear_when_opened() {
+    let mut board = Board::decode_layout(LAYOUT).unwrap();
+    board.set_marking(1, 0, Marking::Question);
+    board.set_marking(3, 0, Marking::Question);
+    // the opening from D1's corner sweeps over B1's question mark too
+    assert_eq!(board.reveal_cell(3, 0), RevealResult::Revealed);
+    for x in [1, 3] {
+        assert!(board.cell(x, 0).is_revealed());
+        assert_eq!(board.cell(x, 0).marking(), Marking::None);
+    }
+}
+
+#[test]
+fn toggle_flag_still_flips_a_flag() {
+    let mut board = Board::decode_layout(LAYOUT).unwrap();
+    assert_eq!(board.toggle_flag(1, 0), FlagResult::Flagged);
+    assert_eq!(board.toggle_flag(1, 0), FlagResult::Unflagged);
+    board.set_marking(1, 0, Marking::Question);
+    assert_eq!(board.toggle_flag(1, 0), FlagResult::Flagged);
+    assert_eq!(board.flags(), 1);
+}
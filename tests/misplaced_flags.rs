@@ -0,0 +1,70 @@
+//! Noticing when the flags can't all be right, and clearing them.
+
+use minesweeper_rs::Board;
+
+/// Mine at the left, the middle cell open: (0, 0) and (2, 0) are hidden.
+fn board() -> Board {
+    let mut board = Board::decode_layout("*..\n").unwrap();
+    board.reveal_cell(1, 0);
+    board
+}
+
+#[test]
+fn fires_only_once_every_hidden_cell_is_flagged() {
+    let mut board = board();
+    assert!(!board.flags_misplaced());
+    board.toggle_flag(2, 0); // wrong, but (0, 0) is still open to play
+    assert!(!board.flags_misplaced());
+    board.toggle_flag(0, 0);
+    assert!(board.flags_misplaced());
+    board.toggle_flag(2, 0);
+    assert!(!board.flags_misplaced());
+}
+
+#[test]
+fn right_flags_end_in_a_win_not_a_notice() {
+    let mut board = board();
+    board.toggle_flag(0, 0);
+    board.reveal_cell(2, 0);
+    assert!(board.is_won());
+    assert!(!board.flags_misplaced());
+}
+
+#[test]
+fn quiet_once_the_game_is_over() {
+    let mut board = Board::decode_layout("*.\n.*\n").unwrap();
+    board.toggle_flag(1, 0);
+    board.toggle_flag(0, 1);
+    board.toggle_flag(1, 1);
+    assert!(!board.flags_misplaced());
+    board.reveal_cell(0, 0);
+    assert!(board.is_lost());
+    assert!(!board.flags_misplaced());
+}
+
+#[test]
+fn a_board_flagged_from_scratch_counts_too() {
+    let mut board = Board::decode_layout("*.\n..\n").unwrap();
+    for (x, y) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+        board.toggle_flag(x, y);
+    }
+    assert!(board.flags_misplaced());
+}
+
+#[test]
+fn clearing_flags_leaves_everything_else() {
+    let mut board = board();
+    board.toggle_flag(0, 0);
+    board.toggle_flag(2, 0);
+    let revision = board.revision();
+    assert_eq!(board.clear_flags(), 2);
+    assert_eq!(board.flags(), 0);
+    assert!(!board.cell(0, 0).is_flagged() && !board.cell(2, 0).is_flagged());
+    assert!(board.cell(1, 0).is_revealed());
+    assert!(board.revision() > revision);
+    assert!(!board.flags_misplaced());
+
+    let revision = board.revision();
+    assert_eq!(board.clear_flags(), 0);
+    assert_eq!(board.revision(), revision);
+}
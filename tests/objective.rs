@@ -0,0 +1,123 @@
+//! Puzzle objectives: each kind met and failed, the budget's last click
+//! either way, and how a session reports them.
+
+use minesweeper_rs::objective::{self, Failure, Objective, Tracker, Verdict};
+use minesweeper_rs::session::Session;
+use minesweeper_rs::Board;
+
+/// Two islands of numbers apart from the opening on the left, so clearing
+/// the board takes three clicks.
+const LAYOUT: &str = "...*.\n...*.\n";
+
+/// Play `clicks` on a fresh board of `LAYOUT` against `objective`,
+/// returning the verdict after each.
+fn play(objective: Objective, clicks: &[(usize, usize)]) -> Vec<Verdict> {
+    let mut board = Board::decode_layout(LAYOUT).unwrap();
+    let mut tracker = Tracker::new(objective);
+    clicks
+        .iter()
+        .map(|&(x, y)| {
+            board.reveal_cell(x, y);
+            tracker.click();
+            tracker.check(&board)
+        })
+        .collect()
+}
+
+#[test]
+fn the_classic_objective_is_clearing_the_board() {
+    let classic = Objective::default();
+    assert!(classic.is_classic());
+    assert_eq!(classic.to_string(), "clear the board");
+    let verdicts = play(classic, &[(0, 0), (4, 0), (4, 1)]);
+    assert_eq!(verdicts, [Verdict::Open, Verdict::Open, Verdict::Met]);
+    assert_eq!(
+        play(Objective::default(), &[(3, 0)]),
+        [Verdict::Failed(Failure::Exploded)]
+    );
+}
+
+#[test]
+fn a_region_is_met_once_its_cells_are_open() {
+    let objective = Objective {
+        region: Some(vec![(1, 1), (4, 0)]),
+        ..Objective::default()
+    };
+    assert_eq!(objective.to_string(), "open the 2 marked cells");
+    assert!(objective.targets(4, 0) && !objective.targets(4, 1));
+    let verdicts = play(objective, &[(0, 0), (4, 0)]);
+    assert_eq!(verdicts, [Verdict::Open, Verdict::Met]);
+}
+
+#[test]
+fn a_budget_met_on_its_last_click_is_met() {
+    let objective = Objective {
+        clicks: Some(3),
+        ..Objective::default()
+    };
+    assert_eq!(objective.to_string(), "clear the board in at most 3 clicks");
+    let verdicts = play(objective, &[(0, 0), (4, 0), (4, 1)]);
+    assert_eq!(verdicts, [Verdict::Open, Verdict::Open, Verdict::Met]);
+}
+
+#[test]
+fn a_budget_spent_on_its_last_click_fails() {
+    let objective = Objective {
+        clicks: Some(3),
+        ..Objective::default()
+    };
+    // a click that opens nothing new is still spent
+    let verdicts = play(objective, &[(0, 0), (1, 0), (4, 0), (4, 1)]);
+    let failed = Verdict::Failed(Failure::OutOfClicks);
+    assert_eq!(verdicts, [Verdict::Open, Verdict::Open, failed, failed]);
+}
+
+#[test]
+fn a_flag_fails_a_no_flags_puzzle() {
+    let mut board = Board::decode_layout(LAYOUT).unwrap();
+    let objective = Objective {
+        no_flags: true,
+        ..Objective::default()
+    };
+    assert_eq!(objective.to_string(), "clear the board, without flags");
+    let mut tracker = Tracker::new(objective);
+    board.reveal_cell(0, 0);
+    tracker.click();
+    assert_eq!(tracker.check(&board), Verdict::Open);
+    tracker.flag();
+    assert_eq!(tracker.check(&board), Verdict::Failed(Failure::Flagged));
+    // the verdict stands
+    board.reveal_cell(4, 0);
+    board.reveal_cell(4, 1);
+    assert_eq!(tracker.check(&board), Verdict::Failed(Failure::Flagged));
+}
+
+#[test]
+fn the_three_bv_of_a_region_counts_only_what_it_needs() {
+    let board = Board::decode_layout(LAYOUT).unwrap();
+    assert_eq!(objective::three_bv(&board, Some(&[])), 0);
+    assert_eq!(objective::three_bv(&board, Some(&[(4, 0), (4, 1)])), 2);
+    assert_eq!(objective::three_bv(&board, Some(&[(2, 0), (0, 1)])), 1);
+    // a blank cell walled in by numbers is an opening of its own, once
+    let board = Board::decode_layout("*****\n*...*\n*...*\n*...*\n*****\n").unwrap();
+    assert_eq!(objective::three_bv(&board, Some(&[(2, 2)])), 1);
+    assert_eq!(objective::three_bv(&board, Some(&[(2, 2), (1, 1)])), 1);
+}
+
+#[test]
+fn a_session_counts_down_and_stops_at_the_verdict() {
+    let board = Board::decode_layout(LAYOUT).unwrap();
+    let objective = Objective {
+        clicks: Some(2),
+        region: Some(vec![(4, 0), (4, 1)]),
+        no_flags: false,
+    };
+    let mut session = Session::with_objective(board, objective);
+    assert_eq!(session.apply("f 3 0".parse().unwrap()), "");
+    assert_eq!(session.apply("4 0".parse().unwrap()), "1 click left");
+    assert!(session.in_progress());
+    assert_eq!(session.apply("4 1".parse().unwrap()), "Objective met");
+    assert!(!session.in_progress());
+    assert_eq!(session.objective().clicks(), 2);
+    assert_ne!(session.apply("0 0".parse().unwrap()), "");
+}
@@ -0,0 +1,111 @@
+//! The chance a clicked cell was a mine, as the loss banner reports it.
+
+use minesweeper_rs::odds::{self, Chance};
+use minesweeper_rs::Board;
+
+/// `A 1 B 1 C D E F` in a row, with B and F the mines: B alone satisfies
+/// both numbers, A and C together do too, and the other mine must then
+/// be among D, E and F, three ways to one.
+fn two_ones() -> Board {
+    let mut board = Board::decode_layout("..*....*\n").unwrap();
+    board.reveal_cell(1, 0);
+    board.reveal_cell(3, 0);
+    board
+}
+
+fn counted(chance: Option<Chance>) -> f64 {
+    match chance {
+        Some(Chance::Counted(p)) => p,
+        other => panic!("expected a counted chance, got {other:?}"),
+    }
+}
+
+#[test]
+fn placements_are_weighted_by_the_mines_left_elsewhere() {
+    let board = two_ones();
+    assert!((counted(odds::chance(&board, 2, 0)) - 0.75).abs() < 1e-9);
+    assert!((counted(odds::chance(&board, 0, 0)) - 0.25).abs() < 1e-9);
+    assert_eq!(
+        odds::chance(&board, 2, 0).unwrap().to_string(),
+        "That cell was 75% likely to be a mine."
+    );
+}
+
+#[test]
+fn a_one_among_three_is_a_third() {
+    let mut board = Board::decode_layout("*.\n..\n").unwrap();
+    board.reveal_cell(1, 1);
+    assert!((counted(odds::chance(&board, 1, 0)) - 1.0 / 3.0).abs() < 1e-9);
+    assert_eq!(
+        odds::chance(&board, 1, 0).unwrap().to_string(),
+        "That cell was 33% likely to be a mine."
+    );
+}
+
+#[test]
+fn an_even_split_is_a_coin_flip() {
+    let mut board = Board::decode_layout("*..\n").unwrap();
+    board.reveal_cell(1, 0);
+    assert_eq!(odds::chance(&board, 2, 0), Some(Chance::Counted(0.5)));
+    assert_eq!(
+        odds::chance(&board, 2, 0).unwrap().to_string(),
+        "It was a coin flip at 50%."
+    );
+}
+
+#[test]
+fn a_proven_mine_is_certain() {
+    let mut board = Board::decode_layout("*..\n").unwrap();
+    board.reveal_cell(2, 0); // opens B1 as well
+    assert_eq!(odds::chance(&board, 0, 0), Some(Chance::Counted(1.0)));
+    assert_eq!(
+        odds::chance(&board, 0, 0).unwrap().to_string(),
+        "The numbers showed that cell was a mine."
+    );
+}
+
+#[test]
+fn a_cell_no_number_touches_gets_the_background_density() {
+    let board = two_ones();
+    // two mines over the six hidden cells
+    assert_eq!(
+        odds::chance(&board, 5, 0),
+        Some(Chance::Background(2.0 / 6.0))
+    );
+    assert_eq!(
+        odds::chance(&board, 5, 0).unwrap().to_string(),
+        "Nothing around it gave it away: 33% of the unknown cells were mines."
+    );
+    assert_eq!(odds::chance(&board, 1, 0), None);
+}
+
+#[test]
+fn one_cell_is_judged_as_the_whole_board_judges_it() {
+    for seed in 0..20 {
+        let mut board = Board::with_seed(16, 16, 40, seed, Some((8, 8)));
+        board.reveal_cell(8, 8);
+        // a few more safe cells, so several groups border the openings
+        let layout = board.encode_layout();
+        for (i, c) in layout.bytes().filter(|&b| b != b'\n').enumerate() {
+            if c == b'.' && i % 23 == 0 {
+                board.reveal_cell(i % 16, i / 16);
+            }
+        }
+        let all = odds::chances(&board);
+        for y in 0..16 {
+            for x in 0..16 {
+                let (one, whole) = (odds::chance(&board, x, y), all[y * 16 + x]);
+                match (one, whole) {
+                    (Some(a), Some(b)) => {
+                        assert_eq!(std::mem::discriminant(&a), std::mem::discriminant(&b));
+                        assert!(
+                            (a.value() - b.value()).abs() < 1e-9,
+                            "seed {seed} ({x}, {y})"
+                        );
+                    }
+                    _ => assert_eq!(one, whole),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,66 @@
+//! Mega boards, which the `parallel` feature lays on several threads, come
+//! out bit for bit as they do one cell at a time: the hashes below were
+//! taken without the feature, and every count is checked against a recount
+//! from the layout. Run with and without `--features parallel` to compare
+//! the two.
+
+use minesweeper_rs::Board;
+
+/// Big enough to be laid in parallel.
+const WIDTH: usize = 400;
+const HEIGHT: usize = 300;
+const MINES: usize = 18_000;
+
+/// Every non-mine's count, from the mines in `board`'s layout, against
+/// what the board says once everything is open.
+fn assert_counts(mut board: Board) {
+    let (width, height) = (board.width(), board.height());
+    let mines: Vec<bool> = board
+        .encode_layout()
+        .bytes()
+        .filter(|&b| b != b'\n')
+        .map(|b| b == b'*')
+        .collect();
+    board.reveal_all();
+    for y in 0..height {
+        for x in 0..width {
+            if mines[y * width + x] {
+                continue;
+            }
+            let count = board
+                .neighbors(x, y)
+                .filter(|&(nx, ny)| mines[ny * width + nx])
+                .count() as u8;
+            assert_eq!(board.cell(x, y).neighbor_mines(), Some(count), "({x}, {y})");
+        }
+    }
+}
+
+#[test]
+fn seeded_mega_boards_keep_their_layouts() {
+    let hashes = [
+        "e150bb20cebe7c2d",
+        "8a337079d3249c38",
+        "2cfd3128195509de",
+        "ec4bfcf74cec89c7",
+        "2dcd1c7e2c0b78a8",
+    ];
+    for (seed, hash) in (1..).zip(hashes) {
+        let board = Board::with_seed(WIDTH, HEIGHT, MINES, seed, Some((200, 150)));
+        assert_eq!(format!("{:016x}", board.layout_hash()), hash, "seed {seed}");
+        assert_eq!(board.encode_layout().matches('*').count(), MINES);
+    }
+}
+
+#[test]
+fn mega_board_counts_match_a_recount() {
+    for seed in 0..3 {
+        let board = Board::with_seed(WIDTH, HEIGHT, MINES, seed, None);
+        let decoded = Board::decode_layout(&board.encode_layout()).unwrap();
+        assert_counts(board);
+        assert_counts(decoded);
+    }
+    // a band is at least a row, however wide, and many rows when narrow
+    assert_counts(Board::with_seed(120_000, 1, 30_000, 7, None));
+    assert_counts(Board::with_seed(3, 40_000, 20_000, 7, None));
+}
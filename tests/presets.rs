@@ -0,0 +1,180 @@
+//! Saved board presets and the terminal's difficulty prompt.
+
+use minesweeper_rs::difficulty::{PresetError, Presets, Reply, Wizard};
+use minesweeper_rs::Difficulty;
+
+mod common;
+
+use common::slot;
+
+/// Feed `lines`, expecting a question after each, and return the last one.
+fn ask(wizard: &mut Wizard, lines: &[&str]) -> String {
+    let mut text = String::new();
+    for line in lines {
+        match wizard.feed(line) {
+            Reply::Ask(asked) => text = asked,
+            other => panic!("{line:?} ended the prompt: {other:?}"),
+        }
+    }
+    text
+}
+
+fn mylarge() -> Presets {
+    "[presets.mylarge]\nwidth = 40\nheight = 20\nmines = 160\n"
+        .parse()
+        .unwrap()
+}
+
+#[test]
+fn built_in_and_saved_names_start_at_once() {
+    let mut wizard = Wizard::new(mylarge());
+    assert!(wizard.prompt().contains("custom, mylarge"));
+    assert_eq!(
+        wizard.feed("Expert"),
+        Reply::Start {
+            difficulty: Difficulty::Expert,
+            save_as: None
+        }
+    );
+    let mut wizard = Wizard::new(mylarge());
+    let Reply::Start { difficulty, .. } = wizard.feed("mylarge") else {
+        panic!("preset not found");
+    };
+    assert_eq!(difficulty.spec().params(), (40, 20, 160));
+}
+
+#[test]
+fn bad_answers_ask_again_at_the_same_step() {
+    let mut wizard = Wizard::new(Presets::default());
+    let text = ask(&mut wizard, &["huge"]);
+    assert!(text.starts_with("No difficulty called \"huge\".\nDifficulty ("));
+
+    let text = ask(&mut wizard, &["custom", "1"]);
+    assert!(text.ends_with("\nWidth (2 to 50):"), "{text}");
+    let text = ask(&mut wizard, &["51"]);
+    assert!(text.ends_with("\nWidth (2 to 50):"), "{text}");
+    let text = ask(&mut wizard, &["ten"]);
+    assert!(text.ends_with("\nWidth (2 to 50):"), "{text}");
+    assert_eq!(ask(&mut wizard, &["2"]), "Height (2 to 50):");
+    assert_eq!(ask(&mut wizard, &["2"]), "Mines (1 to 3):");
+
+    // a full board leaves nowhere to click
+    let text = ask(&mut wizard, &["4"]);
+    assert!(text.ends_with("\nMines (1 to 3):"), "{text}");
+    let text = ask(&mut wizard, &["0"]);
+    assert!(text.ends_with("\nMines (1 to 3):"), "{text}");
+}
+
+#[test]
+fn the_summary_gives_density_and_no_guess() {
+    let mut wizard = Wizard::new(Presets::default());
+    let text = ask(&mut wizard, &["custom", "40", "20", "160"]);
+    assert_eq!(
+        text,
+        "40×20, 160 mines: 20.0% of the cells are mines. No-guess generation: available.\n\
+         Start this board? [Y/n]"
+    );
+
+    let mut wizard = Wizard::new(Presets::default());
+    let text = ask(&mut wizard, &["custom", "3", "3", "1"]);
+    assert!(
+        text.contains("No-guess generation: not available"),
+        "{text}"
+    );
+}
+
+#[test]
+fn declining_starts_the_sizes_over() {
+    let mut wizard = Wizard::new(Presets::default());
+    let text = ask(&mut wizard, &["custom", "9", "9", "10", "maybe"]);
+    assert!(text.starts_with("Please answer y or n."));
+    let text = ask(&mut wizard, &["n"]);
+    assert_eq!(text, "Let's try again.\nWidth (2 to 50):");
+    ask(&mut wizard, &["8", "8", "10", ""]);
+    assert_eq!(
+        wizard.feed(""),
+        Reply::Start {
+            difficulty: Difficulty::Custom {
+                width: 8,
+                height: 8,
+                mines: 10
+            },
+            save_as: None
+        }
+    );
+}
+
+#[test]
+fn preset_names_are_checked_and_clashes_confirmed() {
+    let mut wizard = Wizard::new(mylarge());
+    ask(&mut wizard, &["custom", "30", "30", "150", "y"]);
+    let text = ask(&mut wizard, &["expert"]);
+    assert!(text.contains("can't be a preset name"), "{text}");
+    let text = ask(&mut wizard, &["my large"]);
+    assert!(text.contains("can't be a preset name"), "{text}");
+
+    let text = ask(&mut wizard, &["mylarge"]);
+    assert_eq!(
+        text,
+        "A preset called mylarge already exists (40×20, 160 mines).\nReplace preset mylarge? [y/N]"
+    );
+    // no goes back to naming, yes replaces
+    let text = ask(&mut wizard, &["n"]);
+    assert!(text.starts_with("Save it as a preset?"));
+    ask(&mut wizard, &["mylarge"]);
+    let Reply::Start { save_as, .. } = wizard.feed("y") else {
+        panic!("expected a start");
+    };
+    assert_eq!(save_as.as_deref(), Some("mylarge"));
+}
+
+#[test]
+fn quit_works_anywhere() {
+    let mut wizard = Wizard::new(Presets::default());
+    ask(&mut wizard, &["custom", "9"]);
+    assert_eq!(wizard.feed("quit"), Reply::Quit);
+}
+
+#[test]
+fn presets_round_trip_through_the_file() {
+    let path = slot("presets.toml");
+    let _ = std::fs::remove_file(&path);
+    assert_eq!(Presets::load(&path).unwrap(), Presets::default());
+
+    let mut presets = mylarge();
+    let wide = Difficulty::custom(30, 9, 40, 50);
+    assert_eq!(presets.insert("wide", wide).unwrap(), None);
+    presets.save(&path).unwrap();
+    let loaded = Presets::load(&path).unwrap();
+    assert_eq!(loaded, presets);
+    assert_eq!(loaded.names().collect::<Vec<_>>(), ["mylarge", "wide"]);
+}
+
+#[test]
+fn the_inline_form_and_other_tables_are_read() {
+    let text = "# mine\n[window]\nscale = 2\n\n[presets.mylarge] \nwidth=40 height=20 mines=160\n";
+    assert_eq!(text.parse::<Presets>().unwrap(), mylarge());
+}
+
+#[test]
+fn broken_files_say_where() {
+    let err = "[presets.a]\nwidth 40\n".parse::<Presets>().unwrap_err();
+    assert!(matches!(err, PresetError::Syntax(2)));
+    let err = "[presets.a]\nwidth = 40\nheight = 20\n"
+        .parse::<Presets>()
+        .unwrap_err();
+    assert!(matches!(err, PresetError::Invalid(ref name) if name == "a"));
+    let err = "[presets.a]\nwidth = 2\nheight = 2\nmines = 4\n"
+        .parse::<Presets>()
+        .unwrap_err();
+    assert!(matches!(err, PresetError::Invalid(_)));
+}
+
+#[test]
+fn failed_writes_are_reported() {
+    // a directory where the file should go
+    let path = slot("taken");
+    std::fs::create_dir_all(&path).unwrap();
+    let err = mylarge().save(&path).unwrap_err();
+    assert!(matches!(err, PresetError::Io(_)));
+}
@@ -0,0 +1,113 @@
+//! Puzzle files: the front matter round-trips, is optional, and a bad one
+//! says where it went wrong.
+
+use minesweeper_rs::objective::Objective;
+use minesweeper_rs::puzzle::{Puzzle, PuzzleError};
+use minesweeper_rs::{Board, LayoutError};
+
+#[test]
+fn title_and_start_round_trip() {
+    let mut puzzle = Puzzle::new(Board::decode_layout("*...\n..*.\n").unwrap());
+    puzzle.title = Some(r#"The "odd" \ one"#.to_string());
+    puzzle.start = Some((3, 1));
+    let text = puzzle.to_string();
+    assert!(text.starts_with("+++\ntitle = \"The \\\"odd\\\" \\\\ one\"\nstart = [3, 1]\n+++\n"));
+
+    let read: Puzzle = text.parse().unwrap();
+    assert_eq!(read.title, puzzle.title);
+    assert_eq!(read.start, Some((3, 1)));
+    assert_eq!(read.board.encode_layout(), "*...\n..*.\n");
+}
+
+#[test]
+fn a_bare_layout_is_a_puzzle() {
+    let puzzle = Puzzle::new(Board::decode_layout("*.\n").unwrap());
+    assert_eq!(puzzle.to_string(), "*.\n");
+    let read: Puzzle = "*.\n".parse().unwrap();
+    assert_eq!((read.title, read.start), (None, None));
+
+    // comments, blank lines and keys for someone else are passed over
+    let read: Puzzle = "+++\n# by hand\n\npack = \"intro\"\nstart = [1,0]\n+++\n*."
+        .parse()
+        .unwrap();
+    assert_eq!(read.start, Some((1, 0)));
+}
+
+#[test]
+fn bad_puzzles_are_turned_away() {
+    let cases = [
+        (
+            "+++\ntitle = Corner\n+++\n*.\n",
+            PuzzleError::FrontMatter(2),
+        ),
+        ("+++\n\nstart = [1]\n+++\n*.\n", PuzzleError::FrontMatter(3)),
+        ("+++\nstart = [1, 0]\n", PuzzleError::Unclosed),
+        ("+++\n+++\n*x\n", PuzzleError::Layout(LayoutError::BadChar)),
+        (
+            "+++\nstart = [0, 0]\n+++\n*.\n",
+            PuzzleError::BadStart(0, 0),
+        ),
+        (
+            "+++\nstart = [5, 0]\n+++\n*.\n",
+            PuzzleError::BadStart(5, 0),
+        ),
+    ];
+    for (text, expected) in cases {
+        assert_eq!(text.parse::<Puzzle>().err(), Some(expected), "{text:?}");
+    }
+}
+
+#[test]
+fn objectives_round_trip_and_are_checked() {
+    let mut puzzle = Puzzle::new(Board::decode_layout("...*.\n...*.\n").unwrap());
+    puzzle.objective = Objective {
+        clicks: Some(2),
+        region: Some(vec![(0, 1), (4, 0)]),
+        no_flags: true,
+    };
+    let text = puzzle.to_string();
+    assert!(text.starts_with("+++\nclicks = 2\nregion = [[0, 1], [4, 0]]\nno_flags = true\n+++\n"));
+    let read: Puzzle = text.parse().unwrap();
+    assert_eq!(read.objective, puzzle.objective);
+
+    // without the keys, a classic game
+    let read: Puzzle = "*.\n".parse().unwrap();
+    assert!(read.objective.is_classic());
+    let read: Puzzle = "+++\nregion = []\n+++\n*.\n".parse().unwrap();
+    assert_eq!(read.objective.region, Some(Vec::new()));
+
+    let cases = [
+        ("+++\nclicks = many\n+++\n*.\n", PuzzleError::FrontMatter(2)),
+        (
+            "+++\nregion = [1, 0]\n+++\n*.\n",
+            PuzzleError::FrontMatter(2),
+        ),
+        (
+            "+++\nno_flags = yes\n+++\n*.\n",
+            PuzzleError::FrontMatter(2),
+        ),
+        (
+            "+++\nregion = [[1, 0], [0, 0]]\n+++\n*.\n",
+            PuzzleError::BadTarget(0, 0),
+        ),
+        (
+            "+++\nregion = [[2, 0]]\n+++\n*.\n",
+            PuzzleError::BadTarget(2, 0),
+        ),
+        (
+            "+++\nclicks = 2\n+++\n...*.\n...*.\n",
+            PuzzleError::TightBudget {
+                clicks: 2,
+                needed: 3,
+            },
+        ),
+    ];
+    for (text, expected) in cases {
+        assert_eq!(text.parse::<Puzzle>().err(), Some(expected), "{text:?}");
+    }
+    // enough for the region, if not the whole board
+    let read: Puzzle = "+++\nclicks = 2\nregion = [[4, 0], [4, 1]]\n+++\n...*.\n...*.\n"
+        .parse()
+        .unwrap();
+    assert_eq!(read.objective.clicks, Some(2));
+}
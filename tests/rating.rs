@@ -0,0 +1,118 @@
+//! Board difficulty and the personal rating, on made-up histories.
+
+use minesweeper_rs::rating::{self, Metrics, Played};
+use minesweeper_rs::Board;
+
+const EXPERT: Played = Played {
+    difficulty: 8.0,
+    won: true,
+    three_bv: 130,
+    secs: 100.0,
+};
+
+#[test]
+fn bigger_denser_boards_score_higher() {
+    let beginner = Metrics {
+        cells: 81,
+        mines: 10,
+        three_bv: 20,
+        forced_guesses: 0,
+    };
+    let intermediate = Metrics {
+        cells: 256,
+        mines: 40,
+        three_bv: 50,
+        ..beginner
+    };
+    let expert = Metrics {
+        cells: 625,
+        mines: 99,
+        three_bv: 130,
+        ..beginner
+    };
+    let scores = [beginner, intermediate, expert].map(|m| m.difficulty());
+    assert!(scores[0] < scores[1] && scores[1] < scores[2], "{scores:?}");
+    assert!((3.5..4.5).contains(&scores[0]));
+    assert!((7.5..8.5).contains(&scores[2]));
+}
+
+#[test]
+fn a_board_deduction_clears_forces_no_guess() {
+    let board = Board::decode_layout("*........\n.........\n........*\n").unwrap();
+    assert_eq!(rating::forced_guesses(&board, (4, 1)), 0);
+}
+
+#[test]
+fn a_coin_flip_is_a_forced_guess() {
+    // the top row is left behind two 1s, either cell of it the mine
+    let board = Board::decode_layout("*.\n..\n..\n").unwrap();
+    assert_eq!(rating::forced_guesses(&board, (0, 2)), 1);
+    let metrics = Metrics::measure(&board, (0, 2)).unwrap();
+    assert_eq!(metrics.forced_guesses, 1);
+    assert_eq!(metrics.cells, 6);
+
+    let huge = Board::new(60, 60, 10);
+    assert_eq!(Metrics::measure(&huge, (0, 0)), None);
+}
+
+#[test]
+fn winning_every_game_only_ever_raises_it() {
+    let trend = rating::trend([EXPERT; 30]);
+    assert!(trend[0] > rating::START);
+    assert!(trend.windows(2).all(|w| w[1] > w[0]));
+    // a board well below the rating still nudges it up, just barely
+    let easy = Played {
+        difficulty: 3.0,
+        ..EXPERT
+    };
+    let after = rating::update(9.0, &easy);
+    assert!(after > 9.0 && after - 9.0 < 0.01);
+}
+
+#[test]
+fn losses_bring_it_down_and_hard_ones_cost_less() {
+    let lost = |difficulty| Played {
+        difficulty,
+        won: false,
+        ..EXPERT
+    };
+    let trend = rating::trend([lost(6.0); 10]);
+    assert!(trend.windows(2).all(|w| w[1] < w[0]));
+    let (easy, hard) = (
+        rating::update(6.0, &lost(3.0)),
+        rating::update(6.0, &lost(9.0)),
+    );
+    assert!(easy < hard && hard < 6.0);
+}
+
+#[test]
+fn beating_a_board_faster_lifts_it_further() {
+    let won_in = |secs| rating::update(rating::START, &Played { secs, ..EXPERT });
+    assert!(won_in(50.0) > won_in(200.0) && won_in(200.0) > rating::START);
+}
+
+#[test]
+fn alternating_results_settle_near_the_board() {
+    let games = (0..200).map(|i| Played {
+        difficulty: 6.0,
+        won: i % 2 == 0,
+        three_bv: 50,
+        secs: 50.0, // at par, so a win counts at the board's own 6
+    });
+    let trend = rating::trend(games);
+    let last = &trend[150..];
+    assert!(
+        last.iter().all(|r| (r - 6.0).abs() < rating::K / 2.0),
+        "{last:?}"
+    );
+    // it swings no further late on than it did early
+    let swing = |ratings: &[f64]| {
+        ratings
+            .windows(2)
+            .map(|w| (w[1] - w[0]).abs())
+            .fold(0.0, f64::max)
+    };
+    assert!(swing(last) <= swing(&trend[..20]) + 1e-9);
+    let mean = last.iter().sum::<f64>() / last.len() as f64;
+    assert!((mean - 6.0).abs() < 0.1, "{mean}");
+}
@@ -0,0 +1,168 @@
+//! Snapshots of every render style on one fixture board, so changes to the
+//! text output show up in review.
+
+use minesweeper_rs::render::{
+    column_name, is_one_grapheme, parse_cell_name, BoardRenderer, Compact, Glyph, Marker,
+    MarkerError, MarkerSet, Markers, Style,
+};
+use minesweeper_rs::Board;
+
+/// A flag, a cascade from the top right, and cells still hidden.
+fn fixture() -> Board {
+    let mut board = Board::decode_layout("*.....\n......\n...*..\n......\n").unwrap();
+    board.toggle_flag(0, 0);
+    board.reveal_cell(5, 0);
+    board
+}
+
+#[test]
+fn classic() {
+    assert_eq!(
+        Style::Classic.render(&fixture()),
+        include_str!("fixtures/render_classic.txt")
+    );
+}
+
+#[test]
+fn compact() {
+    assert_eq!(
+        Style::Compact.render(&fixture()),
+        include_str!("fixtures/render_compact.txt")
+    );
+}
+
+#[test]
+fn grid() {
+    assert_eq!(
+        Style::Grid.render(&fixture()),
+        include_str!("fixtures/render_grid.txt")
+    );
+}
+
+#[test]
+fn coordinates() {
+    let labelled = Compact {
+        coordinates: true,
+        ..Compact::default()
+    };
+    assert_eq!(
+        labelled.render(&fixture()),
+        include_str!("fixtures/render_coordinates.txt")
+    );
+}
+
+#[test]
+fn coordinates_stay_aligned_past_z_and_9() {
+    let labelled = Compact {
+        coordinates: true,
+        ..Compact::default()
+    };
+    let wide = labelled.render(&Board::blank(28, 1, 0));
+    let lines: Vec<&str> = wide.lines().collect();
+    assert_eq!(lines[0], format!("{}AA", " ".repeat(28)));
+    assert_eq!(lines[1], "  ABCDEFGHIJKLMNOPQRSTUVWXYZAB");
+
+    let tall = labelled.render(&Board::blank(2, 10, 0));
+    let lines: Vec<&str> = tall.lines().collect();
+    assert_eq!(lines[..3], ["   AB", "  ┌──┐", " 1│░░│"]);
+    assert_eq!(lines[11], "10│░░│");
+}
+
+#[test]
+fn styles_parse_by_name() {
+    for style in Style::ALL {
+        assert_eq!(style.name().parse::<Style>(), Ok(style));
+    }
+    assert!("fancy".parse::<Style>().is_err());
+}
+
+#[test]
+fn marker_sets_reach_every_style() {
+    let mut board = fixture();
+    board.reveal_cell(3, 2);
+    for set in MarkerSet::ALL {
+        let markers = set.markers();
+        assert_eq!(set.name().parse::<Markers>(), Ok(markers.clone()));
+        let classic = Style::Classic.render_with(&board, &markers);
+        assert!(classic.starts_with(markers.get(Marker::Flag)), "{classic}");
+        assert!(classic.contains(markers.get(Marker::Mine)), "{classic}");
+        let narrow = markers.narrow(Glyph::Flag);
+        let grid = Style::Grid.render_with(&board, &markers);
+        assert!(grid.contains(&format!("│{narrow}│")), "{grid}");
+    }
+    assert_eq!(
+        Style::Classic.render_with(&fixture(), &Markers::default()),
+        Style::Classic.render(&fixture())
+    );
+}
+
+#[test]
+fn custom_markers_take_one_grapheme_each() {
+    let markers: Markers = "🏴,¿,X".parse().unwrap();
+    assert_eq!(markers.label(Glyph::Flag), "🏴");
+    assert_eq!(markers.narrow(Glyph::Question), '¿');
+    assert_eq!(markers.label(Glyph::Count(3)), "3");
+
+    // a cluster keeps the set's narrow character for the grids
+    let mut family = Markers::default();
+    family.set(Marker::Mine, "👨\u{200d}👩\u{200d}👧").unwrap();
+    assert_eq!(family.narrow(Glyph::Mine), '*');
+
+    assert_eq!(
+        "F,?".parse::<Markers>(),
+        Err(MarkerError::Unknown("F,?".into()))
+    );
+    assert_eq!(
+        "F,??,M".parse::<Markers>(),
+        Err(MarkerError::NotOneGrapheme(Marker::Question, "??".into()))
+    );
+}
+
+#[test]
+fn graphemes_the_way_people_see_them() {
+    let one = [
+        "F",
+        "é",
+        "e\u{301}",        // e and a combining acute
+        "a\u{301}\u{316}", // two combining marks
+        "🚩",
+        "❤\u{fe0f}",              // with the emoji variation selector
+        "👍\u{1f3fd}",            // skin tone
+        "👨\u{200d}👩\u{200d}👧", // family, joined by ZWJs
+        "🏳\u{fe0f}\u{200d}🌈",    // rainbow flag
+        "🇧🇦",                     // regional indicator pair
+        "1\u{fe0f}\u{20e3}",      // keycap
+        "🏴\u{e0067}\u{e0062}\u{e0073}\u{e0063}\u{e0074}\u{e007f}", // tag sequence
+    ];
+    for text in one {
+        assert!(is_one_grapheme(text), "{text:?}");
+    }
+    let not_one = [
+        "",
+        " ",
+        "\t",
+        "ab",
+        "🚩🚩",
+        "\u{301}",     // a mark with nothing to attach to
+        "\u{200d}👩",  // a joiner with nothing before it
+        "👩\u{200d}",  // or after it
+        "a\u{200d}b",  // letters don't join
+        "🇧🇦🇧🇦",        // two flags
+        "🇧🇦\u{1f1e7}", // and a half
+    ];
+    for text in not_one {
+        assert!(!is_one_grapheme(text), "{text:?}");
+    }
+}
+
+#[test]
+fn cell_names_read_back_as_written() {
+    for (x, y) in [(0, 0), (25, 9), (26, 0), (701, 3), (702, 99)] {
+        let name = format!("{}{}", column_name(x), y + 1);
+        assert_eq!(parse_cell_name(&name), Some((x, y)), "{name}");
+        assert_eq!(parse_cell_name(&name.to_lowercase()), Some((x, y)));
+    }
+    for bad in ["", "A", "12", "A-1", "A1B", "É1", &"Z".repeat(40)] {
+        assert_eq!(parse_cell_name(bad), None, "{bad:?}");
+    }
+}
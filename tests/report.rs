@@ -0,0 +1,129 @@
+//! Debug snapshots, pinned character for character: issue templates and
+//! scripts read them, so any change to the format shows up here first.
+
+use minesweeper_rs::report::{decode_position_code, position_code, CodeError, DebugSnapshot};
+use minesweeper_rs::session::Command;
+use minesweeper_rs::{Board, DecodeError};
+
+#[test]
+fn a_game_in_play_keeps_its_mines_back() {
+    let mut board = Board::with_seed(5, 4, 3, 11, Some((0, 0)));
+    board.reveal_cell(0, 0);
+    let moves = [Command::Reveal(0, 0)];
+    let text = DebugSnapshot::new(&board)
+        .secs(4.0)
+        .moves(&moves)
+        .to_string();
+    let expected = format!(
+        "minesweeper-rs snapshot v1\n\
+         size: 5x4, 3 mines\n\
+         seed: hidden\n\
+         layout hash: {:016x}\n\
+         state: playing\n\
+         time: 0:04.0\n\
+         flags: 0\n\
+         open: {} of 17 safe cells\n\
+         board:\n\
+         {}\
+         moves: 1\n  1. A1\n",
+        board.layout_hash(),
+        board.revealed_safe(),
+        grid(&board),
+    );
+    assert_eq!(text, expected);
+    assert!(!text.contains("mines:") && !text.contains("code:"));
+}
+
+#[test]
+fn a_finished_game_shows_everything() {
+    let mut board = Board::decode_layout("*..\n...\n").unwrap();
+    board.toggle_flag(0, 0);
+    board.reveal_cell(2, 0);
+    board.reveal_cell(0, 1);
+    assert!(board.is_won());
+    let moves = [
+        Command::Flag(0, 0),
+        Command::Hint,
+        Command::ClearFlags,
+        Command::Flag(0, 0),
+        Command::Analyze,
+        Command::Reveal(2, 0),
+        Command::Reveal(0, 1),
+    ];
+    let text = DebugSnapshot::new(&board).moves(&moves).to_string();
+    let expected = format!(
+        "minesweeper-rs snapshot v1\n\
+         size: 3x2, 1 mines\n\
+         seed: none\n\
+         layout hash: {:016x}\n\
+         state: won\n\
+         time: unknown\n\
+         flags: 1\n\
+         open: 5 of 5 safe cells\n\
+         board:\n\
+         \x20 ABC\n\
+         \x20┌───┐\n\
+         1│⚑1 │\n\
+         2│11 │\n\
+         \x20└───┘\n\
+         moves: 7\n\
+         \x20 3. c\n\
+         \x20 4. f A1\n\
+         \x20 5. analyze\n\
+         \x20 6. C1\n\
+         \x20 7. A2\n\
+         mines:\n\
+         *..\n\
+         ...\n\
+         code: {}\n",
+        board.layout_hash(),
+        position_code(&board),
+    );
+    assert_eq!(text, expected);
+}
+
+#[test]
+fn mines_can_be_shown_before_the_end() {
+    let board = Board::with_seed(4, 4, 2, 5, None);
+    let text = DebugSnapshot::new(&board).show_mines(true).to_string();
+    assert!(text.contains("seed: 5\n"));
+    assert!(text.contains("moves: not recorded\nmines:\n"));
+    assert!(text.ends_with(&format!("code: {}\n", position_code(&board))));
+}
+
+#[test]
+fn position_codes_round_trip_and_refuse_junk() {
+    let mut board = Board::with_seed(9, 9, 10, 3, Some((4, 4)));
+    board.reveal_cell(4, 4);
+    board.toggle_flag(0, 8);
+    let code = position_code(&board);
+    let wrapped: String = code
+        .as_bytes()
+        .chunks(40)
+        .map(|line| format!("{}\n", std::str::from_utf8(line).unwrap().to_uppercase()))
+        .collect();
+    let restored = decode_position_code(&wrapped).unwrap();
+    assert!(restored.diff(&board).unwrap().is_empty());
+    assert_eq!(
+        DebugSnapshot::new(&restored).to_string(),
+        DebugSnapshot::new(&board).to_string()
+    );
+
+    let err = |code| decode_position_code(code).err();
+    assert_eq!(err("4d5"), Some(CodeError::NotHex));
+    assert_eq!(err("zz"), Some(CodeError::NotHex));
+    assert_eq!(
+        err("00112233"),
+        Some(CodeError::Board(DecodeError::BadMagic))
+    );
+}
+
+/// The board as the snapshot draws it.
+fn grid(board: &Board) -> String {
+    use minesweeper_rs::render::{BoardRenderer, Compact};
+    let grid = Compact {
+        coordinates: true,
+        ..Compact::default()
+    };
+    grid.render(board)
+}
@@ -0,0 +1,113 @@
+//! Reusing a board: `reset_in_place` and snapshots.
+
+use minesweeper_rs::{Board, BoardError, BoardOptions, RevealResult, SnapshotMismatch};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Counts allocations made by threads that ask for it, so tests running
+/// alongside don't disturb the count.
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    static COUNTING: Cell<bool> = const { Cell::new(false) };
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        // threads being torn down have no flag left to read
+        if COUNTING.try_with(Cell::get).unwrap_or(false) {
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        }
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+fn allocations_during(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    COUNTING.with(|on| on.set(true));
+    f();
+    COUNTING.with(|on| on.set(false));
+    ALLOCATIONS.load(Ordering::Relaxed) - before
+}
+
+#[test]
+fn reset_lays_out_what_build_would() {
+    let mut board = Board::new(9, 9, 10);
+    for options in [
+        BoardOptions::new(30, 16, 99).seed(1).safe_start(0, 0),
+        BoardOptions::new(16, 16, 40).seed(2),
+        BoardOptions::new(9, 9, 10)
+            .seed(3)
+            .safe_start(4, 4)
+            .safe_radius(2),
+        BoardOptions::new(3, 3, 8)
+            .seed(4)
+            .safe_start(1, 1)
+            .max_flags(8),
+    ] {
+        board.reset_in_place(&options).unwrap();
+        let built = options.build().unwrap();
+        assert_eq!(board.encode_layout(), built.encode_layout());
+        assert_eq!(board.encode_state(), built.encode_state());
+    }
+}
+
+#[test]
+fn failed_reset_leaves_the_board_alone() {
+    let mut board = Board::with_seed(9, 9, 10, 5, Some((4, 4)));
+    board.reveal_cell(4, 4);
+    let state = board.encode_state();
+    let full = BoardOptions::new(3, 3, 9).safe_start(1, 1);
+    assert_eq!(board.reset_in_place(&full), Err(BoardError::TooManyMines));
+    assert_eq!(board.encode_state(), state);
+}
+
+#[test]
+fn warm_reset_does_not_allocate() {
+    let options = |seed| BoardOptions::new(30, 16, 99).seed(seed).safe_start(15, 8);
+    let mut board = options(1).build().unwrap();
+    board.reveal_cell(15, 8);
+    board.toggle_flag(0, 0);
+    board.take_dirty();
+
+    let allocations = allocations_during(|| {
+        for seed in 2..50 {
+            board.reset_in_place(&options(seed)).unwrap();
+        }
+    });
+    assert_eq!(allocations, 0);
+    assert_eq!(
+        board.encode_layout(),
+        options(49).build().unwrap().encode_layout()
+    );
+}
+
+#[test]
+fn restore_takes_back_an_explosion() {
+    let mut board = Board::decode_layout("*...\n....\n...*\n").unwrap();
+    board.toggle_flag(3, 2);
+    board.reveal_cell(2, 0);
+    let state = board.encode_state();
+    let before = board.snapshot();
+
+    assert_eq!(board.reveal_cell(0, 0), RevealResult::Exploded);
+    board.take_dirty();
+    let revision = board.revision();
+    board.restore(&before).unwrap();
+    assert_eq!(board.encode_state(), state);
+    assert!(board.revision() > revision);
+    assert!(board.take_dirty().contains(&(0, 0)));
+
+    let mut other = Board::decode_layout(".*..\n....\n...*\n").unwrap();
+    assert_eq!(other.restore(&before), Err(SnapshotMismatch));
+}
@@ -0,0 +1,72 @@
+//! `Board::reveal_remaining`: finishing a board whose mines are all flagged.
+
+use minesweeper_rs::{Board, RevealResult};
+
+/// Build a board from rows: `*` mine, `F` flagged mine, `f` flagged safe
+/// cell, `.` hidden safe cell. Goes through the snapshot format so the
+/// layout is exact.
+fn board(rows: &[&str]) -> Board {
+    let (width, height) = (rows[0].len(), rows.len());
+    let cells: Vec<u8> = rows
+        .iter()
+        .flat_map(|row| row.bytes())
+        .map(|c| match c {
+            b'*' => 1,
+            b'F' => 1 | 4,
+            b'f' => 4,
+            _ => 0,
+        })
+        .collect();
+    let mines = cells.iter().filter(|&&c| c & 1 != 0).count();
+
+    let mut bytes = b"MSB1".to_vec();
+    for n in [width, height, mines] {
+        bytes.extend_from_slice(&(n as u32).to_le_bytes());
+    }
+    bytes.push(0);
+    bytes.extend_from_slice(&0u64.to_le_bytes());
+    bytes.extend_from_slice(&cells);
+    Board::decode_state(&bytes).unwrap()
+}
+
+#[test]
+fn correct_flags_win() {
+    let mut board = board(&["F....", ".....", "....F", "..F.."]);
+    assert_eq!(board.reveal_remaining(), RevealResult::Won);
+    assert!(board.is_won());
+    assert!(board.cell(0, 0).is_flagged() && !board.cell(0, 0).is_revealed());
+}
+
+#[test]
+fn wrong_flag_explodes() {
+    let mut board = board(&["F.f..", ".....", "....*"]);
+    assert_eq!(board.reveal_remaining(), RevealResult::Exploded);
+    assert!(board.is_lost());
+    assert!(board.cell(4, 2).is_revealed());
+    assert!(!board.cell(2, 0).is_revealed());
+}
+
+#[test]
+fn wrong_flag_next_to_a_large_opening_still_explodes() {
+    // the wrongly flagged cell borders an opening that would otherwise flood
+    let mut board = board(&[
+        "..........",
+        "..........",
+        "....f.....",
+        "..........",
+        "..........",
+        ".........*",
+    ]);
+    assert_eq!(board.reveal_remaining(), RevealResult::Exploded);
+    assert!(board.cell(0, 0).is_revealed());
+    assert!(board.cell(4, 2).is_flagged() && !board.cell(4, 2).is_revealed());
+}
+
+#[test]
+fn finished_games_are_left_alone() {
+    let mut board = board(&["F.", ".."]);
+    board.reveal_remaining();
+    let revision = board.revision();
+    assert!(matches!(board.reveal_remaining(), RevealResult::Ignored(_)));
+    assert_eq!(board.revision(), revision);
+}
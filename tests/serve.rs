@@ -0,0 +1,312 @@
+//! Serving a game over a Unix socket: clients seeing the same board, a
+//! socket left behind by a dead server, and hang-ups mid-command. Players
+//! and spectators are also run against a `Host` over in-memory streams.
+
+#![cfg(unix)]
+
+use minesweeper_rs::serve::{self, Host, Server};
+use minesweeper_rs::session::Session;
+use minesweeper_rs::Board;
+use std::io::{self, BufRead, BufReader, ErrorKind, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+mod common;
+
+fn socket(name: &str) -> PathBuf {
+    common::scratch("sockets").join(name)
+}
+
+struct Client {
+    stream: UnixStream,
+    reader: BufReader<UnixStream>,
+}
+
+impl Client {
+    fn connect(server: &Server) -> Client {
+        Client::connect_to(server.path())
+    }
+
+    fn connect_to(path: &Path) -> Client {
+        let stream = UnixStream::connect(path).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(10)))
+            .unwrap();
+        let reader = BufReader::new(stream.try_clone().unwrap());
+        Client { stream, reader }
+    }
+
+    fn send(&mut self, line: &str) {
+        writeln!(self.stream, "{line}").unwrap();
+    }
+
+    fn receive(&mut self) -> String {
+        let mut line = String::new();
+        self.reader.read_line(&mut line).unwrap();
+        line
+    }
+}
+
+/// The far end of an in-memory stream: what the host wrote to a client.
+#[derive(Clone, Default)]
+struct Inbox(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Inbox {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(bytes);
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Inbox {
+    /// The lines written since the last call.
+    fn take(&self) -> Vec<String> {
+        let bytes = std::mem::take(&mut *self.0.lock().unwrap());
+        String::from_utf8(bytes)
+            .unwrap()
+            .lines()
+            .map(str::to_string)
+            .collect()
+    }
+}
+
+/// A board message without its clock, which moves between two reads.
+fn board_of(line: &str) -> &str {
+    &line[line.find(r#""board":"#).expect(line)..]
+}
+
+/// Mines at A1 and C3; revealing C1 opens B1, B2 and C2.
+fn small() -> Session {
+    Session::new(Board::decode_layout("*..\n...\n..*\n").unwrap())
+}
+
+#[test]
+fn every_client_sees_every_move() {
+    let server = Server::bind(socket("shared.sock")).unwrap();
+    let mut a = Client::connect(&server);
+    let mut b = Client::connect(&server);
+    let running = thread::spawn(move || {
+        let mut session = small();
+        server.run(&mut session).unwrap();
+        session
+    });
+    let start = a.receive();
+    assert_eq!(b.receive(), start);
+    assert!(start.contains(r#""rows":["░░░","░░░","░░░"]"#));
+
+    a.send("2 0");
+    let after = a.receive();
+    assert_eq!(b.receive(), after);
+    assert!(after.contains(r#""rows":["░1 ","░21","░░░"]"#));
+
+    // a refusal is for the sender alone
+    b.send("1 0");
+    assert_eq!(b.receive(), "{\"reply\":\"cell is already revealed\"}\n");
+    b.send("f 0 0");
+    let flagged = a.receive();
+    assert_eq!(b.receive(), flagged);
+    assert!(flagged.contains(r#""flags":1"#));
+
+    // lines from both are played one at a time, and both see each result;
+    // A3 is left for last, as its opening would take the other two along
+    a.send("0 1");
+    b.send("1 2");
+    let seen: Vec<String> = (0..2).map(|_| a.receive()).collect();
+    assert_eq!((0..2).map(|_| b.receive()).collect::<Vec<_>>(), seen);
+    assert!(seen[1].contains(r#""state":"playing""#));
+    a.send("0 2");
+    let last = a.receive();
+    assert_eq!(b.receive(), last);
+    assert!(last.contains(r#""state":"won""#));
+    assert_eq!(a.receive(), "", "the server hangs up once the game is over");
+    assert!(running.join().unwrap().board().is_won());
+}
+
+#[test]
+fn a_socket_left_behind_is_replaced_but_a_live_one_is_not() {
+    let path = socket("stale.sock");
+    drop(UnixListener::bind(&path).unwrap());
+    assert!(path.exists());
+    let server = Server::bind(&path).expect("the dead socket is cleared away");
+
+    let err = Server::bind(&path).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::AddrInUse);
+    assert!(err.to_string().contains("stale.sock"));
+
+    drop(server);
+    assert!(!path.exists());
+
+    let file = socket("plain.txt");
+    std::fs::write(&file, "keep me").unwrap();
+    assert_eq!(
+        Server::bind(&file).unwrap_err().kind(),
+        ErrorKind::AlreadyExists
+    );
+    assert_eq!(std::fs::read_to_string(&file).unwrap(), "keep me");
+}
+
+#[test]
+fn a_line_cut_off_by_a_hang_up_is_not_played() {
+    let server = Server::bind(socket("cut.sock")).unwrap();
+    let mut a = Client::connect(&server);
+    let mut quitter = Client::connect(&server);
+    let running = thread::spawn(move || {
+        let mut session = small();
+        server.run(&mut session).unwrap();
+        session
+    });
+    a.receive();
+    quitter.receive();
+    write!(quitter.stream, "2 2").unwrap();
+    drop(quitter);
+
+    a.send("2 0");
+    let board = a.receive();
+    assert!(board.contains(r#""state":"playing""#), "{board}");
+    a.send("2 2");
+    assert!(a.receive().contains(r#""state":"lost""#));
+    assert!(running.join().unwrap().board().is_lost());
+}
+
+#[test]
+fn spectators_see_every_move_and_cannot_make_one() {
+    let mut session = small();
+    let mut host = Host::shared(&mut session);
+    let (player, watcher) = (Inbox::default(), Inbox::default());
+    host.join(0, player.clone());
+    host.join(1, watcher.clone());
+    let start = player.take();
+    assert_eq!(watcher.take(), start);
+    assert!(start[0].starts_with(r#"{"seat":0,"secs":0.0,"board":"#));
+
+    host.line(1, "role spectator");
+    assert_eq!(watcher.take(), [r#"{"role":"spectator"}"#]);
+    for line in ["2 0", "f 0 0", "h", "role player"] {
+        host.line(1, line);
+    }
+    let refused = watcher.take();
+    assert_eq!(refused.len(), 4);
+    assert!(refused[..3]
+        .iter()
+        .all(|line| line == r#"{"reply":"spectators can't play"}"#));
+    assert!(refused[3].contains("first line"), "{}", refused[3]);
+    assert!(player.take().is_empty(), "the player heard none of it");
+
+    // a spectator hanging up and another arriving leave the player alone
+    host.leave(1);
+    let again = Inbox::default();
+    host.join(2, again.clone());
+    host.line(2, "role spectator");
+    host.line(0, "2 0");
+    let moved = player.take();
+    assert_eq!(moved.len(), 1);
+    assert!(moved[0].contains(r#""rows":["░1 ","░21","░░░"]"#));
+    assert_eq!(again.take()[2], moved[0]);
+    assert!(watcher.take().is_empty());
+    drop(host);
+    assert_eq!(session.board().flags(), 0);
+    assert_eq!(session.board().revealed_safe(), 4);
+}
+
+#[test]
+fn a_late_spectator_starts_from_the_live_boards() {
+    let mut seats = [small(), small()];
+    let mut host = Host::race(&mut seats);
+    let (a, b, late) = (Inbox::default(), Inbox::default(), Inbox::default());
+    host.join(0, a.clone());
+    host.join(1, b.clone());
+    host.line(0, "role player");
+    host.line(1, "2 0"); // takes the next seat by moving
+    host.line(0, "f 0 0");
+    host.line(0, "1 1");
+    host.line(1, "0 1");
+
+    // the newest board of each seat, as the players saw them live
+    let live = |seen: Vec<String>| {
+        let mut boards = [String::new(), String::new()];
+        for line in seen {
+            for (seat, board) in boards.iter_mut().enumerate() {
+                if line.starts_with(&format!(r#"{{"seat":{seat},"#)) {
+                    *board = board_of(&line).to_string();
+                }
+            }
+        }
+        boards
+    };
+    let seen = a.take();
+    assert_eq!(seen[2], r#"{"role":"player","seat":0}"#);
+    let boards = live(seen);
+    assert_eq!(live(b.take()), boards);
+
+    host.join(2, late.clone());
+    host.line(2, "role spectator");
+    let snapshot = late.take();
+    assert_eq!(snapshot.len(), 3);
+    assert_eq!(board_of(&snapshot[0]), boards[0]);
+    assert_eq!(board_of(&snapshot[1]), boards[1]);
+    assert!(boards[0].contains(r#""flags":1"#));
+    assert!(boards[1].contains(r#""rows":["░1 ","121","░░░"]"#));
+
+    // a third player finds the seats full and plays nothing
+    let third = Inbox::default();
+    host.join(3, third.clone());
+    host.line(3, "0 2");
+    assert_eq!(
+        third.take()[2],
+        r#"{"reply":"every seat is taken, so you can only watch"}"#
+    );
+
+    // a seat its player left goes to the next one, mid-game
+    host.leave(1);
+    host.line(3, "0 2");
+    assert!(third.take()[0].starts_with(r#"{"seat":1,"#));
+    assert!(late.take()[0].contains(r#""state":"won""#));
+    drop(host);
+    assert!(seats[1].board().is_won());
+    assert_eq!(seats[0].board().flags(), 1);
+}
+
+#[test]
+fn a_race_is_watched_over_the_socket_until_it_ends() {
+    let server = Server::bind(socket("race.sock")).unwrap();
+    let path = server.path().to_path_buf();
+    let running = thread::spawn(move || {
+        let mut seats = [small(), small()];
+        server.run_race(&mut seats).unwrap();
+        seats
+    });
+    let seen = Inbox::default();
+    let watching = {
+        let seen = seen.clone();
+        thread::spawn(move || serve::spectate(path, seen))
+    };
+    // wait until the spectator is in before anyone plays
+    while !String::from_utf8_lossy(&seen.0.lock().unwrap()).contains("role") {
+        thread::sleep(Duration::from_millis(5));
+    }
+    let mut a = Client::connect_to(&socket("race.sock"));
+    let mut b = Client::connect_to(&socket("race.sock"));
+    a.receive();
+    b.receive();
+    a.send("0 0");
+    b.send("2 0");
+    b.send("0 2");
+    watching.join().unwrap().unwrap();
+
+    // the two boards, the role, then a move by one and two by the other,
+    // whichever seats they took
+    let seen = seen.take();
+    assert_eq!(seen.len(), 6, "{seen:?}");
+    assert!(seen[3..].iter().any(|l| l.contains(r#""state":"lost""#)));
+    assert!(seen[3..].iter().any(|l| l.contains(r#""state":"won""#)));
+    let seats = running.join().unwrap();
+    assert_eq!(seats.iter().filter(|s| s.board().is_lost()).count(), 1);
+    assert_eq!(seats.iter().filter(|s| s.board().is_won()).count(), 1);
+}
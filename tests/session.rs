@@ -0,0 +1,114 @@
+//! The command language: parsing lines and playing them on a board.
+
+use minesweeper_rs::session::{AbandonReason, Command, Session};
+use minesweeper_rs::Board;
+
+#[test]
+fn lines_parse_into_commands() {
+    assert_eq!("3 4".parse::<Command>(), Ok(Command::Reveal(3, 4)));
+    assert_eq!(" f 0 2 ".parse::<Command>(), Ok(Command::Flag(0, 2)));
+    assert_eq!("c".parse::<Command>(), Ok(Command::ClearFlags));
+    assert_eq!("h".parse::<Command>(), Ok(Command::Hint));
+    assert_eq!("analyze".parse::<Command>(), Ok(Command::Analyze));
+    assert_eq!("B7".parse::<Command>(), Ok(Command::Reveal(1, 6)));
+    assert_eq!("f aa1".parse::<Command>(), Ok(Command::Flag(26, 0)));
+    for bad in [
+        "", "f", "1", "1 2 3", "x y", "-1 0", "B", "B0", "7B", "f B7 1",
+    ] {
+        assert!(bad.parse::<Command>().is_err(), "{bad:?}");
+    }
+}
+
+#[test]
+fn commands_play_the_board_and_say_what_happened() {
+    let mut session = Session::new(Board::decode_layout("*..\n...\n..*\n").unwrap());
+    assert_eq!(session.apply(Command::Reveal(2, 0)), "");
+    assert_eq!(
+        session.apply(Command::Reveal(2, 0)),
+        "cell is already revealed"
+    );
+    assert_eq!(session.apply(Command::Flag(0, 0)), "");
+    assert_eq!(session.board().flags(), 1);
+    assert!(session.to_json().contains(r#""rows":["⚑1 ","░21","░░░"]"#));
+
+    assert_eq!(session.apply(Command::ClearFlags), "");
+    assert_eq!(session.board().flags(), 0);
+    assert!(session.apply(Command::Analyze).starts_with("Board: 3x3"));
+
+    session.apply(Command::Reveal(2, 2));
+    assert!(session.board().is_lost());
+    assert!(session.fatal().is_some());
+    assert!(session.to_json().contains(r#""state":"lost""#));
+    assert_eq!(
+        session.apply(Command::Reveal(0, 1)),
+        "game is over — start a new game"
+    );
+}
+
+#[test]
+fn hints_climb_to_the_move_itself() {
+    // the 1 at B1 has A1 as its only hidden neighbour
+    let mut board = Board::decode_layout("*..\n...\n.*.\n").unwrap();
+    for (x, y) in [(1, 0), (2, 0), (0, 1), (1, 1), (2, 1)] {
+        board.reveal_cell(x, y);
+    }
+    let mut session = Session::new(board);
+    for _ in 0..3 {
+        assert!(!session.board().cell(0, 0).is_flagged());
+        assert!(!session.apply(Command::Hint).is_empty());
+    }
+    assert!(session.board().cell(0, 0).is_flagged());
+    assert_eq!(session.hints().used(), [1, 1, 1]);
+}
+
+#[test]
+fn an_abandoned_game_is_over_and_takes_no_moves() {
+    let mut session = Session::new(Board::decode_layout("*..\n...\n..*\n").unwrap());
+    assert!(
+        !session.abandon(AbandonReason::NewGame),
+        "nothing opened yet"
+    );
+
+    session.apply(Command::Reveal(2, 0));
+    assert!(session.in_progress());
+    assert!(session.abandon(AbandonReason::Quit));
+    assert!(!session.abandon(AbandonReason::Quit), "only once");
+    assert_eq!(session.abandoned(), Some(AbandonReason::Quit));
+    assert!(!session.in_progress());
+    assert!(session.to_json().contains(r#""state":"abandoned""#));
+    assert_eq!(
+        session.apply(Command::Reveal(0, 1)),
+        "game was abandoned — start a new game"
+    );
+}
+
+#[test]
+fn reveal_remaining_needs_a_flag_per_mine_and_trusts_them() {
+    assert_eq!("ra".parse::<Command>(), Ok(Command::RevealRemaining));
+    assert_eq!(Command::RevealRemaining.to_string(), "ra");
+
+    let board = Board::decode_layout("*..\n...\n..*\n").unwrap();
+    let mut session = Session::new(board.clone());
+    session.apply(Command::Flag(0, 0));
+    assert_eq!(
+        session.apply(Command::RevealRemaining),
+        "`ra` needs a flag on every mine first: 1 of 2 flagged"
+    );
+    assert!(!session.board().cell(1, 1).is_revealed());
+    session.apply(Command::Flag(2, 2));
+    assert_eq!(
+        session.apply(Command::RevealRemaining),
+        "Finished with reveal-remaining."
+    );
+    assert!(session.board().is_won());
+
+    // a wrong flag leaves a mine to open
+    let mut session = Session::new(board);
+    session.apply(Command::Flag(0, 0));
+    session.apply(Command::Flag(1, 1));
+    assert_eq!(
+        session.apply(Command::RevealRemaining),
+        "Finished with reveal-remaining: a flag was on a safe cell."
+    );
+    assert!(session.board().is_lost());
+}
@@ -0,0 +1,112 @@
+//! Click statistics and 3BV.
+
+use minesweeper_rs::stats::{best_opening, three_bv, InputStats};
+use minesweeper_rs::{Board, RevealResult};
+
+/// Build a board with exactly this layout of `*` (mine) and `.` (safe).
+fn board(rows: &[&str]) -> Board {
+    Board::decode_layout(&rows.join("\n")).unwrap()
+}
+
+#[test]
+fn every_ignored_action_is_a_wasted_click() {
+    let mut game = board(&["*..", "...", "..."]);
+    game.set_max_flags(Some(1));
+    let mut stats = InputStats::default();
+
+    // out of bounds, for both buttons
+    stats.record_reveal(game.reveal_cell(9, 9));
+    stats.record_flag(game.toggle_flag(9, 9));
+    assert_eq!(stats.wasted_clicks, 2);
+
+    // flagged: the reveal is refused
+    stats.record_flag(game.toggle_flag(0, 0));
+    stats.record_reveal(game.reveal_cell(0, 0));
+    assert_eq!(stats.wasted_clicks, 3);
+
+    // no flags left
+    stats.record_flag(game.toggle_flag(1, 1));
+    assert_eq!(stats.wasted_clicks, 4);
+
+    // already revealed, for both buttons
+    stats.record_reveal(game.reveal_cell(1, 0));
+    stats.record_reveal(game.reveal_cell(1, 0));
+    stats.record_flag(game.toggle_flag(1, 0));
+    assert_eq!(stats.wasted_clicks, 6);
+
+    // game over
+    stats.record_reveal(game.reveal_cell(2, 2));
+    assert!(game.is_over());
+    stats.record_reveal(game.reveal_cell(2, 2));
+    assert_eq!(stats.wasted_clicks, 7);
+
+    assert_eq!(stats.left_clicks, 6);
+    assert_eq!(stats.right_clicks, 4);
+    assert_eq!(stats.flags_placed, 1);
+    assert_eq!(stats.effective_clicks(), 3);
+}
+
+#[test]
+fn correct_flags_are_counted_at_the_end() {
+    let mut game = board(&["*.", ".."]);
+    let mut stats = InputStats::default();
+    stats.record_flag(game.toggle_flag(0, 0));
+    stats.record_flag(game.toggle_flag(1, 1));
+    stats.record_flag(game.toggle_flag(1, 1));
+    stats.record_flag(game.toggle_flag(1, 0));
+    stats.finish(&game);
+
+    assert_eq!(stats.flags_placed, 3);
+    assert_eq!(stats.flags_correct, 1);
+}
+
+#[test]
+fn three_bv_counts_openings_and_lone_numbers() {
+    assert_eq!(three_bv(&board(&["....", "...."])), 1);
+    assert_eq!(three_bv(&board(&[".*."])), 2);
+    assert_eq!(three_bv(&board(&["*.*", "...", "..."])), 2);
+    assert_eq!(three_bv(&board(&["**", "**"])), 0);
+}
+
+#[test]
+fn efficiency_is_three_bv_per_effective_click() {
+    let mut game = board(&[".*."]);
+    let mut stats = InputStats::default();
+    stats.record_reveal(game.reveal_cell(0, 0));
+    stats.record_reveal(game.reveal_cell(0, 0));
+    assert_eq!(game.reveal_cell(2, 0), RevealResult::Won);
+    stats.record_reveal(RevealResult::Won);
+
+    assert_eq!(stats.efficiency(three_bv(&game)), Some(100.0));
+    assert_eq!(InputStats::default().efficiency(2), None);
+}
+
+#[test]
+fn the_best_opening_reveals_the_most_and_keeps_the_safe_radius() {
+    // the right-hand opening is the larger, whichever way round
+    let left = board(&["....*..", "....*.."]);
+    assert_eq!(best_opening(&left, 1), Some((0, 0)));
+    let right = board(&["..*....", "..*...."]);
+    assert_eq!(best_opening(&right, 1), Some((4, 0)));
+    let mut game = right.clone();
+    game.reveal_cell(4, 0);
+    assert_eq!(game.revealed_safe(), 8);
+
+    // a wider safe radius moves the start further from the mine
+    let one = board(&[
+        ".*.......",
+        ".........",
+        ".........",
+        ".........",
+        ".........",
+    ]);
+    assert_eq!(best_opening(&one, 0), Some((3, 0)));
+    assert_eq!(best_opening(&one, 1), Some((3, 0)));
+    assert_eq!(best_opening(&one, 2), Some((4, 0)));
+
+    // equal openings go to the first in reading order
+    assert_eq!(best_opening(&board(&["..*..", "..*.."]), 1), Some((0, 0)));
+    // no blank cell, or none far enough from a mine
+    assert_eq!(best_opening(&board(&[".*."]), 0), None);
+    assert_eq!(best_opening(&board(&["..*.."]), 2), None);
+}
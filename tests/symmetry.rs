@@ -0,0 +1,170 @@
+//! Shaped layouts: symmetry and spacing over many seeds, with a safe start
+//! and no-guess generation, and refusals for requests that can't be met.
+
+use minesweeper_rs::{generate, solver, Board, BoardError, BoardOptions, Symmetry};
+use std::sync::atomic::AtomicBool;
+use std::time::{Duration, Instant};
+
+fn mines(board: &Board) -> Vec<Vec<bool>> {
+    board
+        .encode_layout()
+        .lines()
+        .map(|row| row.chars().map(|c| c == '*').collect())
+        .collect()
+}
+
+fn symmetric(board: &Board, symmetry: Symmetry) -> bool {
+    let (w, h) = (board.width(), board.height());
+    let grid = mines(board);
+    (0..h).all(|y| {
+        (0..w).all(|x| {
+            let (ix, iy) = symmetry.image(x, y, w, h);
+            grid[y][x] == grid[iy][ix]
+        })
+    })
+}
+
+fn spaced(board: &Board) -> bool {
+    let grid = mines(board);
+    let (w, h) = (board.width(), board.height());
+    (0..h).all(|y| {
+        (0..w).all(|x| {
+            !grid[y][x] || ((x + 1 == w || !grid[y][x + 1]) && (y + 1 == h || !grid[y + 1][x]))
+        })
+    })
+}
+
+#[test]
+fn symmetry_holds_over_many_seeds() {
+    // odd and even sides, odd and even counts
+    for (w, h, m) in [
+        (9, 9, 10),
+        (9, 9, 11),
+        (10, 7, 14),
+        (7, 10, 15),
+        (16, 16, 40),
+    ] {
+        for symmetry in Symmetry::ALL {
+            let options = BoardOptions::new(w, h, m).symmetry(symmetry);
+            if options.check().is_err() {
+                continue;
+            }
+            for seed in 0..50 {
+                let board = options.clone().seed(seed).build().unwrap();
+                assert_eq!(board.mine_count(), m);
+                assert_eq!(mines(&board).concat().iter().filter(|&&m| m).count(), m);
+                assert!(
+                    symmetric(&board, symmetry),
+                    "{symmetry:?} {w}x{h} seed {seed}"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn the_safe_start_stays_clear_and_the_shape_holds_around_it() {
+    for seed in 0..50 {
+        let board = BoardOptions::new(12, 9, 30)
+            .symmetry(Symmetry::Rotational180)
+            .avoid_adjacent_mines(true)
+            .safe_start(2, 3)
+            .seed(seed)
+            .build()
+            .unwrap();
+        assert!(symmetric(&board, Symmetry::Rotational180));
+        assert!(spaced(&board), "seed {seed}");
+        let grid = mines(&board);
+        for (x, y) in [(2, 3), (1, 2), (3, 4)] {
+            assert!(!grid[y][x]);
+        }
+    }
+}
+
+#[test]
+fn spacing_holds_over_many_seeds() {
+    for seed in 0..100 {
+        let board = BoardOptions::new(16, 16, 60)
+            .avoid_adjacent_mines(true)
+            .seed(seed)
+            .build()
+            .unwrap();
+        assert!(spaced(&board), "seed {seed}");
+    }
+}
+
+#[test]
+fn the_same_options_give_the_same_board_either_way() {
+    let options = BoardOptions::new(10, 8, 16)
+        .symmetry(Symmetry::Horizontal)
+        .safe_start(0, 0)
+        .seed(7);
+    let mut board = Board::new(9, 9, 10);
+    let revision = board.revision();
+    board.reset_in_place(&options).unwrap();
+    assert_eq!(
+        board.encode_layout(),
+        options.build().unwrap().encode_layout()
+    );
+    assert!(board.revision() > revision);
+}
+
+#[test]
+fn impossible_requests_are_refused_quickly() {
+    let started = Instant::now();
+    let refused = [
+        // no centre cell on an even board for the odd mine
+        BoardOptions::new(8, 8, 7).symmetry(Symmetry::Rotational180),
+        BoardOptions::new(8, 5, 9).symmetry(Symmetry::Horizontal),
+        // more than a checkerboard holds
+        BoardOptions::new(10, 10, 51).avoid_adjacent_mines(true),
+        // the mirror pairs across an even axis touch, so a 2-wide board
+        // with spacing has nowhere at all
+        BoardOptions::new(2, 9, 1)
+            .symmetry(Symmetry::Horizontal)
+            .avoid_adjacent_mines(true),
+    ];
+    for options in refused {
+        assert_eq!(options.check(), Err(BoardError::Unplaceable), "{options:?}");
+        assert_eq!(options.build().err(), Some(BoardError::Unplaceable));
+        let mut board = Board::new(5, 5, 5);
+        let before = board.encode_layout();
+        assert!(board.reset_in_place(&options).is_err());
+        assert_eq!(board.encode_layout(), before, "left as it was");
+    }
+    // dense enough to pass the up-front checks but past what a random fill
+    // reaches: it gives up instead of looping
+    let tight = BoardOptions::new(20, 20, 200).avoid_adjacent_mines(true);
+    assert_eq!(tight.check(), Ok(()));
+    assert_eq!(tight.build().err(), Some(BoardError::Unplaceable));
+    assert!(started.elapsed() < Duration::from_secs(10));
+}
+
+#[test]
+fn no_guess_generation_keeps_the_shape() {
+    let abort = AtomicBool::new(false);
+    let options = BoardOptions::new(9, 9, 10)
+        .symmetry(Symmetry::Horizontal)
+        .avoid_adjacent_mines(true);
+    let board = generate::no_guess_with(&options, (4, 4), 3, &abort, &|_| {})
+        .unwrap()
+        .expect("a small board has a no-guess layout");
+    assert!(symmetric(&board, Symmetry::Horizontal));
+    assert!(spaced(&board));
+    assert_eq!(solver::solvable_from(&board, (4, 4), &|| false), Some(true));
+    let rebuilt = options
+        .safe_start(4, 4)
+        .seed(board.seed().unwrap())
+        .build()
+        .unwrap();
+    assert_eq!(rebuilt.encode_layout(), board.encode_layout());
+}
+
+#[test]
+fn symmetries_parse_by_name() {
+    for symmetry in Symmetry::ALL {
+        assert_eq!(symmetry.name().parse(), Ok(symmetry));
+    }
+    let err = "diagonal".parse::<Symmetry>().unwrap_err();
+    assert!(err.to_string().contains("horizontal vertical rotational"));
+}
@@ -0,0 +1,84 @@
+//! Reveal timestamps, pause analysis and the post-game flag review.
+
+use minesweeper_rs::timeline::{FlagLog, Pause, RevealTimes};
+use minesweeper_rs::Board;
+
+#[test]
+fn only_revealed_cells_are_recorded() {
+    let mut board = Board::with_seed(9, 9, 10, 5, Some((4, 4)));
+    board.reveal_cell(4, 4);
+    board.toggle_flag(0, 0);
+
+    let mut times = RevealTimes::new(9);
+    let dirty = board.take_dirty();
+    times.record(&board, &dirty, 250);
+
+    assert_eq!(times.len(), board.revealed_safe());
+    let cells = times.per_cell(81);
+    assert_eq!(cells[4 * 9 + 4], Some(250));
+    assert_eq!(cells[0], None);
+}
+
+#[test]
+fn longest_pauses_come_first_with_the_next_click() {
+    let board = {
+        let mut board = Board::with_seed(4, 1, 0, 1, None);
+        board.reveal_all();
+        board
+    };
+    let mut times = RevealTimes::new(4);
+    times.record(&board, &[(0, 0)], 1_000);
+    times.record(&board, &[(1, 0)], 41_000);
+    times.record(&board, &[(2, 0)], 43_000);
+    times.record(&board, &[(3, 0)], 53_000);
+
+    let pauses = times.longest_pauses(2);
+    assert_eq!(
+        pauses,
+        [
+            Pause {
+                millis: 40_000,
+                after: (0, 0),
+                next: (1, 0),
+            },
+            Pause {
+                millis: 10_000,
+                after: (2, 0),
+                next: (3, 0),
+            },
+        ]
+    );
+    assert_eq!(times.last_millis(), 53_000);
+}
+
+#[test]
+fn flag_review_pairs_removals_and_waits_for_the_end() {
+    // mines at A1 and C1
+    let mut board = Board::decode_layout("*.*\n...\n").unwrap();
+    let mut log = FlagLog::default();
+    log.record((1, 0), true, 1, Some(1_000));
+    log.record((0, 0), true, 2, Some(2_500));
+    log.record((1, 0), false, 4, Some(9_000));
+    log.record((1, 0), true, 5, Some(61_000));
+    assert_eq!(log.review(&board), None);
+
+    board.reveal_cell(2, 0);
+    let review = log.review(&board).unwrap();
+    let cells: Vec<_> = review.flags.iter().map(|flag| flag.cell).collect();
+    assert_eq!(cells, [(1, 0), (0, 0), (1, 0)]);
+    assert_eq!(review.flags[0].removed.map(|r| r.move_number), Some(4));
+    assert_eq!(review.flags[2].removed, None);
+    assert_eq!(
+        (
+            review.on_mines(),
+            review.on_safe_cells(),
+            review.corrected()
+        ),
+        (1, 2, 1)
+    );
+
+    let text = review.to_string();
+    assert!(text.starts_with("Flags: 1 on mines, 2 on safe cells (1 corrected)\n"));
+    assert!(text.contains("  o B1 safe cell, placed at move 1 (0:01), removed at move 4 (0:09)"));
+    assert!(text.contains("  x B1 safe cell, placed at move 5 (1:01)\n"));
+}
@@ -0,0 +1,38 @@
+//! Trainer questions: the two cells are hidden and unsettled, their gap is
+//! in the range asked for, and the same seed asks the same question.
+
+use minesweeper_rs::odds::{self, Chance};
+use minesweeper_rs::trainer::{self, GAPS};
+
+#[test]
+fn questions_keep_to_their_gap() {
+    for &gap in &GAPS[..3] {
+        let question = (0..200)
+            .find_map(|seed| trainer::generate(16, 16, 40, seed, gap))
+            .unwrap_or_else(|| panic!("no question for {gap:?}"));
+        assert!(question.gap() >= gap.0 && question.gap() <= gap.1);
+        assert!(!question.board.is_over());
+        for (i, &(x, y)) in question.cells.iter().enumerate() {
+            assert!(!question.board.cell(x, y).is_revealed());
+            let chance = odds::chance(&question.board, x, y);
+            assert_eq!(chance, Some(Chance::Counted(question.chances[i])));
+            assert!(question.chances[i] > 0.0 && question.chances[i] < 1.0);
+        }
+        let safer = question.safer();
+        assert!(question.chances[safer] < question.chances[1 - safer]);
+    }
+}
+
+#[test]
+fn a_seed_asks_the_same_question() {
+    let ask = || {
+        (0..200)
+            .find_map(|seed| trainer::generate(9, 9, 10, seed, GAPS[1]).map(|q| (seed, q)))
+            .unwrap()
+    };
+    let (seed, a) = ask();
+    let (again, b) = ask();
+    assert_eq!(seed, again);
+    assert_eq!(a.cells, b.cells);
+    assert_eq!(a.board.encode_layout(), b.board.encode_layout());
+}
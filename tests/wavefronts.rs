@@ -0,0 +1,108 @@
+//! `Board::last_reveal_wavefronts`: every opened cell in exactly one ring,
+//! each ring touching the one before, around flags as well as in the open.
+
+use minesweeper_rs::{Board, RevealResult};
+use std::collections::HashSet;
+
+/// Check the rings of a reveal from `origin` against the cells it opened.
+fn check_rings(board: &mut Board, origin: (usize, usize)) -> Vec<Vec<(usize, usize)>> {
+    let rings = board.last_reveal_wavefronts();
+    assert_eq!(rings[0], [origin]);
+    let opened: HashSet<_> = board.take_dirty().into_iter().collect();
+    let mut seen = HashSet::new();
+    for ring in &rings {
+        assert!(!ring.is_empty());
+        for &cell in ring {
+            assert!(seen.insert(cell), "{cell:?} is in two rings");
+        }
+    }
+    assert_eq!(seen, opened);
+    for pair in rings.windows(2) {
+        for &(x, y) in &pair[1] {
+            // opened by a blank of the ring before
+            let by = pair[0].iter().find(|&&(px, py)| {
+                px.abs_diff(x) <= 1
+                    && py.abs_diff(y) <= 1
+                    && board.cell(px, py).neighbor_mines() == Some(0)
+            });
+            assert!(by.is_some(), "nothing in the ring before opens ({x}, {y})");
+        }
+    }
+    rings
+}
+
+fn ring_of(rings: &[Vec<(usize, usize)>], cell: (usize, usize)) -> usize {
+    rings.iter().position(|ring| ring.contains(&cell)).unwrap()
+}
+
+#[test]
+fn openings_on_seeded_boards_make_proper_rings() {
+    for seed in 0..50 {
+        let mut board = Board::with_seed(30, 16, 99, seed, Some((15, 8)));
+        board.take_dirty();
+        assert_ne!(board.reveal_cell(15, 8), RevealResult::Exploded);
+        let rings = check_rings(&mut board, (15, 8));
+        for (n, ring) in rings.iter().enumerate() {
+            for &(x, y) in ring {
+                // the flood can't outrun a king's move a ring
+                assert!(x.abs_diff(15).max(y.abs_diff(8)) <= n);
+            }
+        }
+    }
+}
+
+#[test]
+fn the_opening_goes_around_a_wall_of_flags() {
+    let mut board = Board::decode_layout(".......\n.......\n.......\n.......\n......*\n").unwrap();
+    for y in 0..4 {
+        board.toggle_flag(3, y);
+    }
+    board.take_dirty();
+    board.reveal_cell(0, 0);
+    let rings = check_rings(&mut board, (0, 0));
+    // four cells across, but the only way there is under the wall
+    assert_eq!(ring_of(&rings, (3, 4)), 4);
+    assert_eq!(ring_of(&rings, (4, 0)), 8);
+    assert!(rings.iter().flatten().all(|&(x, y)| x != 3 || y == 4));
+    assert!(!board.cell(3, 0).is_revealed());
+}
+
+#[test]
+fn a_flag_pocket_is_reached_from_its_far_side() {
+    // flags ring (2, 2) on every side but the bottom right
+    let layout = "......\n......\n......\n......\n......\n.....*\n";
+    let mut board = Board::decode_layout(layout).unwrap();
+    for cell in [(1, 1), (2, 1), (3, 1), (1, 2), (3, 2), (1, 3), (2, 3)] {
+        board.toggle_flag(cell.0, cell.1);
+    }
+    board.take_dirty();
+    board.reveal_cell(0, 0);
+    let rings = check_rings(&mut board, (0, 0));
+    // two cells from the click, seven rings out
+    assert_eq!(ring_of(&rings, (3, 3)), 6);
+    assert_eq!(ring_of(&rings, (2, 2)), 7);
+}
+
+#[test]
+fn other_reveals_and_undo() {
+    let mut board = Board::decode_layout("*..\n...\n...\n").unwrap();
+    let start = board.snapshot();
+    // a number opens alone
+    board.reveal_cell(1, 0);
+    assert_eq!(board.last_reveal_wavefronts(), [vec![(1, 0)]]);
+    // a reveal that changes nothing leaves nothing
+    board.reveal_cell(1, 0);
+    assert!(board.last_reveal_wavefronts().is_empty());
+
+    board.reveal_cell(2, 2);
+    assert_eq!(board.last_reveal_wavefronts().len(), 3);
+    board.restore(&start).unwrap();
+    assert!(board.last_reveal_wavefronts().is_empty());
+
+    board.toggle_flag(0, 0);
+    board.reveal_cell(1, 0);
+    assert_eq!(board.reveal_remaining(), RevealResult::Won);
+    let rings = board.last_reveal_wavefronts();
+    assert_eq!(rings.len(), 1);
+    assert_eq!(rings[0].len(), 7);
+}
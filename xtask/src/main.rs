@@ -0,0 +1,59 @@
+//! Repository tasks, run as `cargo xtask <task>`.
+//!
+//! - `test-python`: build the Python bindings into a private virtualenv
+//!   under `target/` and run their pytest suite.
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitCode};
+
+fn main() -> ExitCode {
+    let result = match std::env::args().nth(1).as_deref() {
+        Some("test-python") => test_python(),
+        _ => Err("usage: cargo xtask test-python".to_string()),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("xtask: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn repo_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .expect("xtask lives inside the repository")
+        .to_path_buf()
+}
+
+fn test_python() -> Result<(), String> {
+    let root = repo_root();
+    let venv = root.join("target").join("python-venv");
+    let python = if cfg!(windows) { "python" } else { "python3" };
+    if !venv.exists() {
+        run(Command::new(python).args(["-m", "venv"]).arg(&venv))?;
+    }
+
+    let bin = venv.join(if cfg!(windows) { "Scripts" } else { "bin" });
+    run(Command::new(bin.join("python"))
+        .args(["-m", "pip", "install", "--quiet", "maturin", "pytest"]))?;
+    run(Command::new(bin.join("maturin"))
+        .arg("develop")
+        .current_dir(root.join("python"))
+        .env("VIRTUAL_ENV", &venv))?;
+    run(Command::new(bin.join("python"))
+        .args(["-m", "pytest"])
+        .arg(root.join("python").join("tests")))
+}
+
+fn run(command: &mut Command) -> Result<(), String> {
+    let status = command
+        .status()
+        .map_err(|err| format!("couldn't run {command:?}: {err}"))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("{command:?} failed ({status})"))
+    }
+}